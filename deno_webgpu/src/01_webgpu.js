@@ -1348,6 +1348,7 @@
         fragment = {
           module,
           entryPoint: descriptor.fragment.entryPoint,
+          constants: descriptor.fragment.constants,
           targets: descriptor.fragment.targets,
         };
       }
@@ -1359,6 +1360,7 @@
         vertex: {
           module,
           entryPoint: descriptor.vertex.entryPoint,
+          constants: descriptor.vertex.constants,
           buffers: descriptor.vertex.buffers,
         },
         primitive: descriptor.primitive,
@@ -2527,7 +2529,24 @@
     }
 
     compilationInfo() {
-      throw new Error("Not yet implemented");
+      webidl.assertBranded(this, GPUShaderModulePrototype);
+      const prefix =
+        "Failed to execute 'compilationInfo' on 'GPUShaderModule'";
+      const rid = assertResource(this, { prefix, context: "this" });
+      const { messages } = core.opSync(
+        "op_webgpu_shader_module_get_compilation_info",
+        rid,
+      );
+      return PromiseResolve({
+        messages: ArrayPrototypeMap(messages, (message) => ({
+          message: message.message,
+          type: message.messageType,
+          lineNum: message.location?.lineNumber ?? 0,
+          linePos: message.location?.linePosition ?? 0,
+          offset: message.location?.offset ?? 0,
+          length: message.location?.length ?? 0,
+        })),
+      });
     }
 
     [SymbolFor("Deno.privateCustomInspect")](inspect) {
@@ -2539,6 +2558,7 @@
     }
   }
   GPUObjectBaseMixin("GPUShaderModule", GPUShaderModule);
+  const GPUShaderModulePrototype = GPUShaderModule.prototype;
 
   class GPUShaderStage {
     constructor() {
@@ -2880,12 +2900,26 @@
         },
       );
 
+      let occlusionQuerySet;
+      if (descriptor.occlusionQuerySet) {
+        occlusionQuerySet = assertResource(descriptor.occlusionQuerySet, {
+          prefix,
+          context: "occlusion query set",
+        });
+        assertDeviceMatch(device, descriptor.occlusionQuerySet, {
+          prefix,
+          resourceContext: "occlusion query set",
+          selfContext: "this",
+        });
+      }
+
       const { rid } = core.opSync(
         "op_webgpu_command_encoder_begin_render_pass",
         commandEncoderRid,
         descriptor.label,
         colorAttachments,
         depthStencilAttachment,
+        occlusionQuerySet,
       );
 
       const renderPassEncoder = createGPURenderPassEncoder(
@@ -3651,12 +3685,51 @@
       );
     }
 
-    beginOcclusionQuery(_queryIndex) {
-      throw new Error("Not yet implemented");
+    /**
+     * @param {number} queryIndex
+     */
+    beginOcclusionQuery(queryIndex) {
+      webidl.assertBranded(this, GPURenderPassEncoderPrototype);
+      const prefix =
+        "Failed to execute 'beginOcclusionQuery' on 'GPURenderPassEncoder'";
+      webidl.requiredArguments(arguments.length, 1, { prefix });
+      queryIndex = webidl.converters.GPUSize32(queryIndex, {
+        prefix,
+        context: "Argument 1",
+      });
+      assertDevice(this[_encoder], {
+        prefix,
+        context: "encoder referenced by this",
+      });
+      assertResource(this[_encoder], {
+        prefix,
+        context: "encoder referenced by this",
+      });
+      const renderPassRid = assertResource(this, { prefix, context: "this" });
+      core.opSync(
+        "op_webgpu_render_pass_begin_occlusion_query",
+        renderPassRid,
+        queryIndex,
+      );
     }
 
     endOcclusionQuery() {
-      throw new Error("Not yet implemented");
+      webidl.assertBranded(this, GPURenderPassEncoderPrototype);
+      const prefix =
+        "Failed to execute 'endOcclusionQuery' on 'GPURenderPassEncoder'";
+      assertDevice(this[_encoder], {
+        prefix,
+        context: "encoder referenced by this",
+      });
+      assertResource(this[_encoder], {
+        prefix,
+        context: "encoder referenced by this",
+      });
+      const renderPassRid = assertResource(this, { prefix, context: "this" });
+      core.opSync(
+        "op_webgpu_render_pass_end_occlusion_query",
+        renderPassRid,
+      );
     }
 
     /**
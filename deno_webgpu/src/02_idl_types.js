@@ -390,6 +390,8 @@
       "astc-12x12-unorm-srgb",
     ],
   );
+  webidl.converters["sequence<GPUTextureFormat>"] = webidl
+    .createSequenceConverter(webidl.converters["GPUTextureFormat"]);
 
   // TYPEDEF: GPUTextureUsageFlags
   webidl.converters["GPUTextureUsageFlags"] = (V, opts) =>
@@ -427,6 +429,13 @@
       converter: webidl.converters["GPUTextureUsageFlags"],
       required: true,
     },
+    {
+      key: "viewFormats",
+      converter: webidl.converters["sequence<GPUTextureFormat>"],
+      get defaultValue() {
+        return [];
+      },
+    },
   ];
   webidl.converters["GPUTextureDescriptor"] = webidl.createDictionaryConverter(
     "GPUTextureDescriptor",
@@ -1836,6 +1845,10 @@
       key: "depthStencilAttachment",
       converter: webidl.converters["GPURenderPassDepthStencilAttachment"],
     },
+    {
+      key: "occlusionQuerySet",
+      converter: webidl.converters["GPUQuerySet"],
+    },
   ];
   webidl.converters["GPURenderPassDescriptor"] = webidl
     .createDictionaryConverter(
@@ -8,6 +8,7 @@ use deno_core::ResourceId;
 use serde::Deserialize;
 use serde::Serialize;
 use std::borrow::Cow;
+use std::collections::HashMap;
 
 use super::error::WebGpuError;
 use super::error::WebGpuResult;
@@ -53,7 +54,8 @@ pub enum GPUPipelineLayoutOrGPUAutoLayoutMode {
 pub struct GpuProgrammableStage {
     module: ResourceId,
     entry_point: String,
-    // constants: HashMap<String, GPUPipelineConstantValue>
+    #[serde(default)]
+    constants: HashMap<String, f64>,
 }
 
 #[op]
@@ -88,8 +90,9 @@ pub fn op_webgpu_create_compute_pipeline(
         stage: wgpu_core::pipeline::ProgrammableStageDescriptor {
             module: compute_shader_module_resource.0,
             entry_point: Cow::from(compute.entry_point),
-            // TODO(lucacasonato): support args.compute.constants
+            constants: Cow::Owned(compute.constants),
         },
+        cache: None,
     };
     let implicit_pipelines = match layout {
         GPUPipelineLayoutOrGPUAutoLayoutMode::Layout(_) => None,
@@ -252,6 +255,8 @@ impl<'a> From<GpuVertexBufferLayout> for wgpu_core::pipeline::VertexBufferLayout
 struct GpuVertexState {
     module: ResourceId,
     entry_point: String,
+    #[serde(default)]
+    constants: HashMap<String, f64>,
     buffers: Vec<Option<GpuVertexBufferLayout>>,
 }
 
@@ -279,7 +284,8 @@ struct GpuFragmentState {
     targets: Vec<Option<wgpu_types::ColorTargetState>>,
     module: u32,
     entry_point: String,
-    // TODO(lucacasonato): constants
+    #[serde(default)]
+    constants: HashMap<String, f64>,
 }
 
 #[derive(Deserialize)]
@@ -328,6 +334,7 @@ pub fn op_webgpu_create_render_pipeline(
             stage: wgpu_core::pipeline::ProgrammableStageDescriptor {
                 module: fragment_shader_module_resource.0,
                 entry_point: Cow::from(fragment.entry_point),
+                constants: Cow::Owned(fragment.constants),
             },
             targets: Cow::from(fragment.targets),
         })
@@ -350,6 +357,7 @@ pub fn op_webgpu_create_render_pipeline(
             stage: wgpu_core::pipeline::ProgrammableStageDescriptor {
                 module: vertex_shader_module_resource.0,
                 entry_point: Cow::Owned(args.vertex.entry_point),
+                constants: Cow::Owned(args.vertex.constants),
             },
             buffers: Cow::Owned(vertex_buffers),
         },
@@ -358,6 +366,7 @@ pub fn op_webgpu_create_render_pipeline(
         multisample: args.multisample,
         fragment,
         multiview: None,
+        cache: None,
     };
 
     let implicit_pipelines = match args.layout {
@@ -9,7 +9,10 @@ use std::borrow::Cow;
 
 use super::error::WebGpuResult;
 
-pub(crate) struct WebGpuShaderModule(pub(crate) wgpu_core::id::ShaderModuleId);
+pub(crate) struct WebGpuShaderModule(
+    pub(crate) wgpu_core::id::ShaderModuleId,
+    pub(crate) wgpu_types::CompilationInfo,
+);
 impl Resource for WebGpuShaderModule {
     fn name(&self) -> Cow<str> {
         "webGPUShaderModule".into()
@@ -36,10 +39,32 @@ pub fn op_webgpu_create_shader_module(
         shader_bound_checks: wgpu_types::ShaderBoundChecks::default(),
     };
 
-    gfx_put!(device => instance.device_create_shader_module(
+    let (shader_module, maybe_err) = gfx_select!(device => instance.device_create_shader_module(
     device,
     &descriptor,
     source,
     std::marker::PhantomData
-  ) => state, WebGpuShaderModule)
+  ));
+    // Keep the messages around, `compilationInfo()` can be called on invalid modules too.
+    let compilation_info = maybe_err
+        .as_ref()
+        .map(|err| err.compilation_info())
+        .unwrap_or_default();
+    let rid = state
+        .resource_table
+        .add(WebGpuShaderModule(shader_module, compilation_info));
+
+    Ok(WebGpuResult::rid_err(rid, maybe_err))
+}
+
+#[op]
+pub fn op_webgpu_shader_module_get_compilation_info(
+    state: &mut OpState,
+    shader_module_rid: ResourceId,
+) -> Result<wgpu_types::CompilationInfo, AnyError> {
+    let shader_module_resource = state
+        .resource_table
+        .get::<WebGpuShaderModule>(shader_module_rid)?;
+
+    Ok(shader_module_resource.1.clone())
 }
@@ -34,6 +34,18 @@ impl<I: Clone + Debug + wgc::id::TypedId> wgc::hub::IdentityHandlerFactory<I>
 }
 impl wgc::hub::GlobalIdentityHandlerFactory for IdentityPassThroughFactory {}
 
+fn load_shader_source(dir: &Path, data: &str) -> wgc::pipeline::ShaderModuleSource<'static> {
+    let code = fs::read_to_string(dir.join(data)).unwrap();
+    if data.ends_with(".wgsl") {
+        wgc::pipeline::ShaderModuleSource::Wgsl(Cow::Owned(code))
+    } else if data.ends_with(".ron") {
+        let module = ron::de::from_str(&code).unwrap();
+        wgc::pipeline::ShaderModuleSource::Naga(module)
+    } else {
+        panic!("Unknown shader {}", data);
+    }
+}
+
 pub trait GlobalPlay {
     fn encode_commands<A: wgc::hub::HalApi>(
         &self,
@@ -125,12 +137,14 @@ impl GlobalPlay for wgc::hub::Global<IdentityPassThroughFactory> {
                     base,
                     target_colors,
                     target_depth_stencil,
+                    occlusion_query_set,
                 } => {
                     self.command_encoder_run_render_pass_impl::<A>(
                         encoder,
                         base.as_ref(),
                         &target_colors,
                         target_depth_stencil.as_ref(),
+                        occlusion_query_set,
                     )
                     .unwrap();
                 }
@@ -170,6 +184,14 @@ impl GlobalPlay for wgc::hub::Global<IdentityPassThroughFactory> {
                     panic!("{:?}", e);
                 }
             }
+            Action::CreateUninitializedBuffer(id, desc) => {
+                self.device_maintain_ids::<A>(device).unwrap();
+                let (_, error) =
+                    unsafe { self.device_create_buffer_uninitialized::<A>(device, &desc, id) };
+                if let Some(e) = error {
+                    panic!("{:?}", e);
+                }
+            }
             Action::FreeBuffer(id) => {
                 self.buffer_destroy::<A>(id).unwrap();
             }
@@ -183,6 +205,14 @@ impl GlobalPlay for wgc::hub::Global<IdentityPassThroughFactory> {
                     panic!("{:?}", e);
                 }
             }
+            Action::CreateUninitializedTexture(id, desc) => {
+                self.device_maintain_ids::<A>(device).unwrap();
+                let (_, error) =
+                    unsafe { self.device_create_texture_uninitialized::<A>(device, &desc, id) };
+                if let Some(e) = error {
+                    panic!("{:?}", e);
+                }
+            }
             Action::FreeTexture(id) => {
                 self.texture_destroy::<A>(id).unwrap();
             }
@@ -251,15 +281,7 @@ impl GlobalPlay for wgc::hub::Global<IdentityPassThroughFactory> {
             }
             Action::CreateShaderModule { id, desc, data } => {
                 log::info!("Creating shader from {}", data);
-                let code = fs::read_to_string(dir.join(&data)).unwrap();
-                let source = if data.ends_with(".wgsl") {
-                    wgc::pipeline::ShaderModuleSource::Wgsl(Cow::Owned(code))
-                } else if data.ends_with(".ron") {
-                    let module = ron::de::from_str(&code).unwrap();
-                    wgc::pipeline::ShaderModuleSource::Naga(module)
-                } else {
-                    panic!("Unknown shader {}", data);
-                };
+                let source = load_shader_source(dir, &data);
                 let (_, error) = self.device_create_shader_module::<A>(device, &desc, source, id);
                 if let Some(e) = error {
                     panic!("{:?}", e);
@@ -268,6 +290,24 @@ impl GlobalPlay for wgc::hub::Global<IdentityPassThroughFactory> {
             Action::DestroyShaderModule(id) => {
                 self.shader_module_drop::<A>(id);
             }
+            Action::EnableShaderHotReload => {
+                self.device_enable_shader_hot_reload::<A>(device).unwrap();
+            }
+            Action::ReplaceShaderModule { id, desc, data } => {
+                log::info!("Replacing shader from {}", data);
+                let source = load_shader_source(dir, &data);
+                self.shader_module_replace::<A>(id, &desc, source).unwrap();
+            }
+            Action::CreatePipelineCache { id, desc } => {
+                let (_, error) =
+                    unsafe { self.device_create_pipeline_cache::<A>(device, &desc, id) };
+                if let Some(e) = error {
+                    panic!("{:?}", e);
+                }
+            }
+            Action::DestroyPipelineCache(id) => {
+                self.pipeline_cache_drop::<A>(id);
+            }
             Action::CreateComputePipeline {
                 id,
                 desc,
@@ -0,0 +1,272 @@
+//! Opt-in log of the resource state transitions of command buffers.
+//!
+//! Once [`Global::device_enable_barrier_log`] is called, the command encoders
+//! of the device record every buffer and texture barrier they emit, along with
+//! the command that caused it. Queue submission adds the barriers it inserts
+//! before and after each command buffer, and keeps the resulting
+//! [`BarrierLog`] until [`Global::device_take_barrier_logs`] is called.
+//!
+//! Barriers of `Queue::write_buffer` and `Queue::write_texture` belong to no
+//! command buffer, and aren't logged.
+//!
+//! [`Global::device_enable_barrier_log`]: crate::hub::Global::device_enable_barrier_log
+//! [`Global::device_take_barrier_logs`]: crate::hub::Global::device_take_barrier_logs
+
+use crate::{
+    hub::{HalApi, Resource, Storage},
+    id::{self, TypedId as _},
+    resource::{Buffer, Texture},
+    track::PendingTransition,
+};
+
+use std::{fmt, fmt::Write as _, ops::Range};
+
+/// The resource, and subresources, transitioned by a barrier.
+#[derive(Clone, Debug)]
+pub enum BarrierResource {
+    Buffer {
+        id: id::BufferId,
+        usage: Range<hal::BufferUses>,
+    },
+    Texture {
+        id: id::TextureId,
+        mip_levels: Range<u32>,
+        array_layers: Range<u32>,
+        usage: Range<hal::TextureUses>,
+    },
+}
+
+/// What made wgpu-core emit a barrier.
+#[derive(Clone, Debug)]
+pub enum BarrierCause {
+    /// Beginning of a render pass.
+    RenderPass { label: Option<String> },
+    /// A dispatch of a compute pass, at the given index in the pass commands.
+    ComputePass {
+        label: Option<String>,
+        command: usize,
+    },
+    /// A command of the encoder, like `"copy_buffer_to_buffer"`.
+    Command(&'static str),
+    /// Zero-initialization of memory the command buffer reads before writing.
+    MemoryInit,
+    /// Transition from the state resources are in when the command buffer is
+    /// submitted to the one it expects.
+    Submit,
+    /// Transition of the surface textures used by the command buffer for
+    /// presentation.
+    Present,
+}
+
+impl fmt::Display for BarrierCause {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Self::RenderPass { ref label } => {
+                write!(f, "render pass")?;
+                if let Some(ref label) = *label {
+                    write!(f, " `{}`", label)?;
+                }
+                Ok(())
+            }
+            Self::ComputePass { ref label, command } => {
+                write!(f, "compute pass")?;
+                if let Some(ref label) = *label {
+                    write!(f, " `{}`", label)?;
+                }
+                write!(f, ", command {}", command)
+            }
+            Self::Command(name) => write!(f, "{}", name),
+            Self::MemoryInit => write!(f, "memory initialization"),
+            Self::Submit => write!(f, "submission"),
+            Self::Present => write!(f, "presentation"),
+        }
+    }
+}
+
+/// A barrier emitted for a command buffer.
+#[derive(Clone, Debug)]
+pub struct BarrierRecord {
+    pub resource: BarrierResource,
+    /// Label of the resource.
+    pub label: String,
+    pub cause: BarrierCause,
+}
+
+/// The barriers of a submitted command buffer, in execution order.
+#[derive(Clone, Debug)]
+pub struct BarrierLog {
+    /// Label of the command buffer.
+    pub command_buffer: String,
+    pub barriers: Vec<BarrierRecord>,
+}
+
+impl BarrierLog {
+    /// Formats the log as a JSON object.
+    pub fn to_json(&self) -> String {
+        let mut out = String::from("{\"command_buffer\":");
+        write_json_string(&mut out, &self.command_buffer);
+        out.push_str(",\"barriers\":[");
+        for (i, record) in self.barriers.iter().enumerate() {
+            if i != 0 {
+                out.push(',');
+            }
+            let (kind, index, epoch) = match record.resource {
+                BarrierResource::Buffer { id, .. } => {
+                    let (index, epoch, _) = id.unzip();
+                    ("buffer", index, epoch)
+                }
+                BarrierResource::Texture { id, .. } => {
+                    let (index, epoch, _) = id.unzip();
+                    ("texture", index, epoch)
+                }
+            };
+            let _ = write!(
+                out,
+                "{{\"resource\":\"{}\",\"id\":[{},{}],\"label\":",
+                kind, index, epoch
+            );
+            write_json_string(&mut out, &record.label);
+            let (from, to) = match record.resource {
+                BarrierResource::Buffer { ref usage, .. } => {
+                    (format!("{:?}", usage.start), format!("{:?}", usage.end))
+                }
+                BarrierResource::Texture {
+                    ref mip_levels,
+                    ref array_layers,
+                    ref usage,
+                    ..
+                } => {
+                    let _ = write!(
+                        out,
+                        ",\"mip_levels\":[{},{}],\"array_layers\":[{},{}]",
+                        mip_levels.start, mip_levels.end, array_layers.start, array_layers.end
+                    );
+                    (format!("{:?}", usage.start), format!("{:?}", usage.end))
+                }
+            };
+            out.push_str(",\"from\":");
+            write_json_string(&mut out, &from);
+            out.push_str(",\"to\":");
+            write_json_string(&mut out, &to);
+            out.push_str(",\"cause\":");
+            write_json_string(&mut out, &record.cause.to_string());
+            out.push('}');
+        }
+        out.push_str("]}");
+        out
+    }
+}
+
+fn write_json_string(out: &mut String, value: &str) {
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+/// Records the barriers of a command buffer, if the barrier log is enabled.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct BarrierRecorder {
+    records: Option<Vec<BarrierRecord>>,
+    cause: Option<BarrierCause>,
+}
+
+impl BarrierRecorder {
+    pub(crate) fn new(enabled: bool) -> Self {
+        Self {
+            records: if enabled { Some(Vec::new()) } else { None },
+            cause: None,
+        }
+    }
+
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.records.is_some()
+    }
+
+    /// Attributes the barriers recorded from now on to `cause`.
+    ///
+    /// The cause is only built when the log is enabled.
+    pub(crate) fn set_cause(&mut self, cause: impl FnOnce() -> BarrierCause) {
+        if self.records.is_some() {
+            self.cause = Some(cause());
+        }
+    }
+
+    fn push(&mut self, resource: BarrierResource, label: &str) {
+        if let Some(ref mut records) = self.records {
+            records.push(BarrierRecord {
+                resource,
+                label: label.to_string(),
+                cause: self
+                    .cause
+                    .clone()
+                    .unwrap_or(BarrierCause::Command("unknown")),
+            });
+        }
+    }
+
+    /// Records the transition of a buffer, and passes it through.
+    pub(crate) fn buffer<A: HalApi>(
+        &mut self,
+        storage: &Storage<Buffer<A>, id::BufferId>,
+        pending: PendingTransition<hal::BufferUses>,
+    ) -> PendingTransition<hal::BufferUses> {
+        if self.records.is_some() {
+            let buffer = unsafe { storage.get_unchecked(pending.id) };
+            self.push(
+                BarrierResource::Buffer {
+                    id: storage.id_at(pending.id, A::VARIANT),
+                    usage: pending.usage.clone(),
+                },
+                buffer.label(),
+            );
+        }
+        pending
+    }
+
+    /// Records the transition of texture subresources, and passes it through.
+    pub(crate) fn texture<A: HalApi>(
+        &mut self,
+        storage: &Storage<Texture<A>, id::TextureId>,
+        pending: PendingTransition<hal::TextureUses>,
+    ) -> PendingTransition<hal::TextureUses> {
+        if self.records.is_some() {
+            let texture = unsafe { storage.get_unchecked(pending.id) };
+            self.push(
+                BarrierResource::Texture {
+                    id: storage.id_at(pending.id, A::VARIANT),
+                    mip_levels: pending.selector.mips.clone(),
+                    array_layers: pending.selector.layers.clone(),
+                    usage: pending.usage.clone(),
+                },
+                texture.label(),
+            );
+        }
+        pending
+    }
+
+    /// Adds the records of `other` after the ones of `self`.
+    pub(crate) fn append(&mut self, other: Self) {
+        if let (Some(records), Some(other)) = (self.records.as_mut(), other.records) {
+            records.extend(other);
+        }
+    }
+
+    pub(crate) fn finish(self, command_buffer: &str) -> Option<BarrierLog> {
+        self.records.map(|barriers| BarrierLog {
+            command_buffer: command_buffer.to_string(),
+            barriers,
+        })
+    }
+}
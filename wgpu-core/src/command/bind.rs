@@ -274,6 +274,14 @@ impl Binder {
             .map(move |index| payloads[index].group_id.as_ref().unwrap().value)
     }
 
+    /// Returns the index and payload of the groups compatible with the pipeline layout.
+    pub(super) fn list_active_payloads(&self) -> impl Iterator<Item = (usize, &EntryPayload)> + '_ {
+        let payloads = &self.payloads;
+        self.manager
+            .list_active()
+            .map(move |index| (index, &payloads[index]))
+    }
+
     pub(super) fn invalid_mask(&self) -> BindGroupMask {
         self.manager.invalid_mask()
     }
@@ -81,13 +81,17 @@ index format changes.
 use crate::{
     binding_model::{self, buffer_binding_type_alignment},
     command::{
-        BasePass, BindGroupStateChange, DrawError, MapPassErr, PassErrorScope, RenderCommand,
-        RenderCommandError, StateChange,
+        end_pipeline_statistics_query, render::IndirectDraws, BasePass, BindGroupStateChange,
+        DrawError, MapPassErr, PassErrorScope, QueryResetMap, QueryUseError, RenderCommand,
+        RenderCommandError, SimplifiedQueryType, StateChange,
     },
     conv,
     device::{
-        AttachmentData, Device, DeviceError, MissingDownlevelFlags, RenderPassContext,
-        SHADER_STAGE_COUNT,
+        index_range::IndexedDraw,
+        indirect_validation::IndirectArgs,
+        push_constants::{PushConstantCursor, PushConstantStates},
+        AttachmentData, Device, DeviceError, MissingDownlevelFlags, MissingFeatures,
+        RenderPassContext, SHADER_STAGE_COUNT,
     },
     error::{ErrorFormatter, PrettyError},
     hub::{GlobalIdentityHandlerFactory, HalApi, Hub, Resource, Storage, Token},
@@ -95,12 +99,12 @@ use crate::{
     init_tracker::{BufferInitTrackerAction, MemoryInitKind, TextureInitTrackerAction},
     pipeline::{self, PipelineFlags},
     resource,
-    track::RenderBundleScope,
+    track::{RenderBundleScope, UsageOrigin, UsageSource},
     validation::check_buffer_usage,
     Label, LabelHelpers, LifeGuard, Stored,
 };
 use arrayvec::ArrayVec;
-use std::{borrow::Cow, mem, num::NonZeroU32, ops::Range};
+use std::{borrow::Cow, mem, num::NonZeroU32, ops::Range, str};
 use thiserror::Error;
 
 use hal::CommandEncoder as _;
@@ -260,15 +264,22 @@ impl RenderBundleEncoder {
             vertex: (0..hal::MAX_VERTEX_BUFFERS).map(|_| None).collect(),
             index: None,
             flat_dynamic_offsets: Vec::new(),
+            debug_scope_depth: 0,
+            query_reset_state: QueryResetMap::new(),
+            active_query: None,
         };
         let mut commands = Vec::new();
+        let mut string_data = Vec::new();
+        let mut string_offset = 0;
         let mut buffer_memory_init_actions = Vec::new();
         let mut texture_memory_init_actions = Vec::new();
+        let mut indirect_args = Vec::new();
+        let mut indirect_size = 0;
 
         let base = self.base.as_ref();
         let mut next_dynamic_offset = 0;
 
-        for &command in base.commands {
+        for (command_index, &command) in base.commands.iter().enumerate() {
             match command {
                 RenderCommand::SetBindGroup {
                     index,
@@ -333,7 +344,17 @@ impl RenderBundleEncoder {
                     unsafe {
                         state
                             .trackers
-                            .merge_bind_group(&*texture_guard, &bind_group.used)
+                            .merge_bind_group(
+                                &*texture_guard,
+                                &bind_group.used,
+                                UsageOrigin::new(
+                                    UsageSource::BindGroup {
+                                        group: index as u32,
+                                        bind_group: bind_group_id,
+                                    },
+                                    Some(command_index),
+                                ),
+                            )
                             .map_pass_err(scope)?
                     };
                     //Note: stateless trackers are not merged: the lifetime reference
@@ -388,7 +409,12 @@ impl RenderBundleEncoder {
                     let buffer: &resource::Buffer<A> = state
                         .trackers
                         .buffers
-                        .merge_single(&*buffer_guard, buffer_id, hal::BufferUses::INDEX)
+                        .merge_single(
+                            &*buffer_guard,
+                            buffer_id,
+                            hal::BufferUses::INDEX,
+                            UsageOrigin::new(UsageSource::IndexBuffer, Some(command_index)),
+                        )
                         .map_pass_err(scope)?;
                     self.check_valid_to_use(buffer.device_id.value)
                         .map_pass_err(scope)?;
@@ -416,7 +442,15 @@ impl RenderBundleEncoder {
                     let buffer: &resource::Buffer<A> = state
                         .trackers
                         .buffers
-                        .merge_single(&*buffer_guard, buffer_id, hal::BufferUses::VERTEX)
+                        .merge_single(
+                            &*buffer_guard,
+                            buffer_id,
+                            hal::BufferUses::VERTEX,
+                            UsageOrigin::new(
+                                UsageSource::VertexBuffer { slot },
+                                Some(command_index),
+                            ),
+                        )
                         .map_pass_err(scope)?;
                     self.check_valid_to_use(buffer.device_id.value)
                         .map_pass_err(scope)?;
@@ -492,7 +526,7 @@ impl RenderBundleEncoder {
                     index_count,
                     instance_count,
                     first_index,
-                    base_vertex: _,
+                    base_vertex,
                     first_instance,
                 } => {
                     let scope = PassErrorScope::Draw {
@@ -506,7 +540,6 @@ impl RenderBundleEncoder {
                         Some(ref index) => index,
                         None => return Err(DrawError::MissingIndexBuffer).map_pass_err(scope),
                     };
-                    //TODO: validate that base_vertex + max_index() is within the provided range
                     let vertex_limits = state.vertex_limits(pipeline);
                     let index_limit = index.limit();
                     let last_index = first_index + index_count;
@@ -517,6 +550,21 @@ impl RenderBundleEncoder {
                         })
                         .map_pass_err(scope);
                     }
+                    if let Some(ref contents) = buffer_guard[id::Valid(index.buffer)].index_contents
+                    {
+                        IndexedDraw {
+                            format: index.format,
+                            strip: pipeline.strip_index_format.is_some(),
+                            offset: index.range.start,
+                            first_index,
+                            index_count,
+                            base_vertex,
+                            vertex_limit: vertex_limits.vertex_limit,
+                            vertex_limit_slot: vertex_limits.vertex_limit_slot,
+                        }
+                        .validate(&mut contents.lock())
+                        .map_pass_err(scope)?;
+                    }
                     let last_instance = first_instance + instance_count;
                     if last_instance > vertex_limits.instance_limit {
                         return Err(DrawError::InstanceBeyondLimit {
@@ -534,107 +582,311 @@ impl RenderBundleEncoder {
                 RenderCommand::MultiDrawIndirect {
                     buffer_id,
                     offset,
-                    count: None,
-                    indexed: false,
+                    count,
+                    indexed,
                 } => {
                     let scope = PassErrorScope::Draw {
-                        indexed: false,
+                        indexed,
                         indirect: true,
                         pipeline: state.pipeline_id(),
                     };
+                    if count.is_some() {
+                        device
+                            .require_features(wgt::Features::MULTI_DRAW_INDIRECT)
+                            .map_pass_err(scope)?;
+                    }
                     device
                         .require_downlevel_flags(wgt::DownlevelFlags::INDIRECT_EXECUTION)
                         .map_pass_err(scope)?;
 
                     let pipeline = state.pipeline(scope)?;
                     let used_bind_groups = pipeline.used_bind_groups;
+                    let args = state.indirect_args(pipeline, indexed);
+
+                    let stride = match indexed {
+                        false => mem::size_of::<wgt::DrawIndirectArgs>(),
+                        true => mem::size_of::<wgt::DrawIndexedIndirectArgs>(),
+                    } as u64;
 
                     let buffer: &resource::Buffer<A> = state
                         .trackers
                         .buffers
-                        .merge_single(&*buffer_guard, buffer_id, hal::BufferUses::INDIRECT)
+                        .merge_single(
+                            &*buffer_guard,
+                            buffer_id,
+                            hal::BufferUses::INDIRECT,
+                            UsageOrigin::new(UsageSource::IndirectBuffer, Some(command_index)),
+                        )
                         .map_pass_err(scope)?;
                     self.check_valid_to_use(buffer.device_id.value)
                         .map_pass_err(scope)?;
                     check_buffer_usage(buffer.usage, wgt::BufferUsages::INDIRECT)
                         .map_pass_err(scope)?;
 
+                    let actual_count = count.map_or(1, |c| c.get());
+                    let end_offset = offset + stride * actual_count as u64;
+                    if end_offset > buffer.size {
+                        return Err(RenderBundleErrorInner::IndirectBufferOverrun {
+                            count,
+                            offset,
+                            end_offset,
+                            buffer_size: buffer.size,
+                        })
+                        .map_pass_err(scope);
+                    }
+
                     buffer_memory_init_actions.extend(buffer.initialization_status.create_action(
                         buffer_id,
-                        offset..(offset + mem::size_of::<wgt::DrawIndirectArgs>() as u64),
+                        offset..end_offset,
                         MemoryInitKind::NeedsInitializedMemory,
                     ));
 
+                    if indexed {
+                        let index = match state.index {
+                            Some(ref mut index) => index,
+                            None => return Err(DrawError::MissingIndexBuffer).map_pass_err(scope),
+                        };
+                        commands.extend(index.flush());
+                    }
                     commands.extend(state.flush_vertices());
                     commands.extend(state.flush_binds(used_bind_groups, base.dynamic_offsets));
                     commands.push(command);
+                    indirect_args.push(args);
+                    indirect_size += args.size() * actual_count as u64;
                 }
-                RenderCommand::MultiDrawIndirect {
+                RenderCommand::MultiDrawIndirectCount {
                     buffer_id,
                     offset,
-                    count: None,
-                    indexed: true,
+                    count_buffer_id,
+                    count_buffer_offset,
+                    max_count,
+                    indexed,
                 } => {
                     let scope = PassErrorScope::Draw {
-                        indexed: true,
+                        indexed,
                         indirect: true,
                         pipeline: state.pipeline_id(),
                     };
+                    device
+                        .require_features(wgt::Features::MULTI_DRAW_INDIRECT_COUNT)
+                        .map_pass_err(scope)?;
                     device
                         .require_downlevel_flags(wgt::DownlevelFlags::INDIRECT_EXECUTION)
                         .map_pass_err(scope)?;
 
                     let pipeline = state.pipeline(scope)?;
                     let used_bind_groups = pipeline.used_bind_groups;
+                    let args = state.indirect_args(pipeline, indexed);
+
+                    let stride = match indexed {
+                        false => mem::size_of::<wgt::DrawIndirectArgs>(),
+                        true => mem::size_of::<wgt::DrawIndexedIndirectArgs>(),
+                    } as u64;
 
                     let buffer: &resource::Buffer<A> = state
                         .trackers
                         .buffers
-                        .merge_single(&*buffer_guard, buffer_id, hal::BufferUses::INDIRECT)
+                        .merge_single(
+                            &*buffer_guard,
+                            buffer_id,
+                            hal::BufferUses::INDIRECT,
+                            UsageOrigin::new(UsageSource::IndirectBuffer, Some(command_index)),
+                        )
                         .map_pass_err(scope)?;
                     self.check_valid_to_use(buffer.device_id.value)
                         .map_pass_err(scope)?;
                     check_buffer_usage(buffer.usage, wgt::BufferUsages::INDIRECT)
                         .map_pass_err(scope)?;
 
+                    let end_offset = offset + stride * max_count as u64;
+                    if end_offset > buffer.size {
+                        return Err(RenderBundleErrorInner::IndirectBufferOverrun {
+                            count: None,
+                            offset,
+                            end_offset,
+                            buffer_size: buffer.size,
+                        })
+                        .map_pass_err(scope);
+                    }
                     buffer_memory_init_actions.extend(buffer.initialization_status.create_action(
                         buffer_id,
-                        offset..(offset + mem::size_of::<wgt::DrawIndirectArgs>() as u64),
+                        offset..end_offset,
                         MemoryInitKind::NeedsInitializedMemory,
                     ));
 
-                    let index = match state.index {
-                        Some(ref mut index) => index,
-                        None => return Err(DrawError::MissingIndexBuffer).map_pass_err(scope),
-                    };
+                    let count_buffer: &resource::Buffer<A> = state
+                        .trackers
+                        .buffers
+                        .merge_single(
+                            &*buffer_guard,
+                            count_buffer_id,
+                            hal::BufferUses::INDIRECT,
+                            UsageOrigin::new(UsageSource::IndirectBuffer, Some(command_index)),
+                        )
+                        .map_pass_err(scope)?;
+                    self.check_valid_to_use(count_buffer.device_id.value)
+                        .map_pass_err(scope)?;
+                    check_buffer_usage(count_buffer.usage, wgt::BufferUsages::INDIRECT)
+                        .map_pass_err(scope)?;
+
+                    let begin_count_offset = count_buffer_offset;
+                    let end_count_offset = count_buffer_offset + 4;
+                    if end_count_offset > count_buffer.size {
+                        return Err(RenderBundleErrorInner::IndirectCountBufferOverrun {
+                            begin_count_offset,
+                            end_count_offset,
+                            count_buffer_size: count_buffer.size,
+                        })
+                        .map_pass_err(scope);
+                    }
+                    buffer_memory_init_actions.extend(
+                        count_buffer.initialization_status.create_action(
+                            count_buffer_id,
+                            begin_count_offset..end_count_offset,
+                            MemoryInitKind::NeedsInitializedMemory,
+                        ),
+                    );
 
-                    commands.extend(index.flush());
+                    if indexed {
+                        let index = match state.index {
+                            Some(ref mut index) => index,
+                            None => return Err(DrawError::MissingIndexBuffer).map_pass_err(scope),
+                        };
+                        commands.extend(index.flush());
+                    }
                     commands.extend(state.flush_vertices());
                     commands.extend(state.flush_binds(used_bind_groups, base.dynamic_offsets));
                     commands.push(command);
+                    indirect_args.push(args);
+                    indirect_size += args.size() * max_count as u64;
+                }
+                RenderCommand::PushDebugGroup { color: _, len } => {
+                    state.debug_scope_depth += 1;
+                    string_data
+                        .extend_from_slice(&base.string_data[string_offset..string_offset + len]);
+                    string_offset += len;
+                    commands.push(command);
+                }
+                RenderCommand::PopDebugGroup => {
+                    let scope = PassErrorScope::PopDebugGroup;
+                    if state.debug_scope_depth == 0 {
+                        return Err(RenderBundleErrorInner::InvalidPopDebugGroup)
+                            .map_pass_err(scope);
+                    }
+                    state.debug_scope_depth -= 1;
+                    commands.push(command);
+                }
+                RenderCommand::InsertDebugMarker { color: _, len } => {
+                    string_data
+                        .extend_from_slice(&base.string_data[string_offset..string_offset + len]);
+                    string_offset += len;
+                    commands.push(command);
+                }
+                RenderCommand::WriteTimestamp {
+                    query_set_id,
+                    query_index,
+                } => {
+                    let scope = PassErrorScope::WriteTimestamp;
+
+                    device
+                        .require_features(wgt::Features::WRITE_TIMESTAMP_INSIDE_PASSES)
+                        .map_pass_err(scope)?;
+
+                    let query_set: &resource::QuerySet<A> = state
+                        .trackers
+                        .query_sets
+                        .add_single(&*query_set_guard, query_set_id)
+                        .ok_or(RenderCommandError::InvalidQuerySet(query_set_id))
+                        .map_pass_err(scope)?;
+                    self.check_valid_to_use(query_set.device_id.value)
+                        .map_pass_err(scope)?;
+
+                    query_set
+                        .validate_query(
+                            query_set_id,
+                            SimplifiedQueryType::Timestamp,
+                            query_index,
+                            Some(&mut state.query_reset_state),
+                        )
+                        .map_pass_err(scope)?;
+
+                    commands.push(command);
+                }
+                RenderCommand::BeginPipelineStatisticsQuery {
+                    query_set_id,
+                    query_index,
+                } => {
+                    let scope = PassErrorScope::BeginPipelineStatisticsQuery;
+
+                    let query_set: &resource::QuerySet<A> = state
+                        .trackers
+                        .query_sets
+                        .add_single(&*query_set_guard, query_set_id)
+                        .ok_or(RenderCommandError::InvalidQuerySet(query_set_id))
+                        .map_pass_err(scope)?;
+                    self.check_valid_to_use(query_set.device_id.value)
+                        .map_pass_err(scope)?;
+
+                    query_set
+                        .validate_query(
+                            query_set_id,
+                            SimplifiedQueryType::PipelineStatistics,
+                            query_index,
+                            Some(&mut state.query_reset_state),
+                        )
+                        .map_pass_err(scope)?;
+
+                    if let Some((_, active_query_index)) = state.active_query {
+                        return Err(QueryUseError::AlreadyStarted {
+                            active_query_index,
+                            new_query_index: query_index,
+                        })
+                        .map_pass_err(scope);
+                    }
+                    state.active_query = Some((query_set_id, query_index));
+
+                    commands.push(command);
+                }
+                RenderCommand::EndPipelineStatisticsQuery => {
+                    let scope = PassErrorScope::EndPipelineStatisticsQuery;
+
+                    if state.active_query.take().is_none() {
+                        return Err(QueryUseError::AlreadyStopped).map_pass_err(scope);
+                    }
+
+                    commands.push(command);
                 }
-                RenderCommand::MultiDrawIndirect { .. }
-                | RenderCommand::MultiDrawIndirectCount { .. } => unimplemented!(),
-                RenderCommand::PushDebugGroup { color: _, len: _ } => unimplemented!(),
-                RenderCommand::InsertDebugMarker { color: _, len: _ } => unimplemented!(),
-                RenderCommand::PopDebugGroup => unimplemented!(),
-                RenderCommand::WriteTimestamp { .. } // Must check the WRITE_TIMESTAMP_INSIDE_PASSES feature
-                | RenderCommand::BeginPipelineStatisticsQuery { .. }
-                | RenderCommand::EndPipelineStatisticsQuery => unimplemented!(),
                 RenderCommand::ExecuteBundle(_)
                 | RenderCommand::SetBlendConstant(_)
                 | RenderCommand::SetStencilReference(_)
                 | RenderCommand::SetViewport { .. }
-                | RenderCommand::SetScissor(_) => unreachable!("not supported by a render bundle"),
+                | RenderCommand::SetScissor(_)
+                | RenderCommand::BeginOcclusionQuery { .. }
+                | RenderCommand::EndOcclusionQuery => {
+                    unreachable!("not supported by a render bundle")
+                }
             }
         }
 
+        // Debug groups and queries can't span bundle boundaries, so executing
+        // the bundle never changes the pass's debug group depth or active query.
+        if state.debug_scope_depth != 0 {
+            return Err(RenderBundleErrorInner::UnbalancedDebugGroups(
+                state.debug_scope_depth,
+            ))
+            .map_pass_err(PassErrorScope::Bundle);
+        }
+        if let Some((_, query_index)) = state.active_query {
+            return Err(RenderBundleErrorInner::UnterminatedQuery { query_index })
+                .map_pass_err(PassErrorScope::Bundle);
+        }
+
         Ok(RenderBundle {
             base: BasePass {
                 label: desc.label.as_ref().map(|cow| cow.to_string()),
                 commands,
                 dynamic_offsets: state.flat_dynamic_offsets,
-                string_data: Vec::new(),
+                string_data,
                 push_constant_data: Vec::new(),
             },
             is_depth_read_only: self.is_depth_read_only,
@@ -646,6 +898,8 @@ impl RenderBundleEncoder {
             used: state.trackers,
             buffer_memory_init_actions,
             texture_memory_init_actions,
+            indirect_args,
+            indirect_size,
             context: self.context,
             life_guard: LifeGuard::new(desc.label.borrow_or_default()),
         })
@@ -694,6 +948,8 @@ pub enum ExecutionError {
     DestroyedBuffer(id::BufferId),
     #[error("using {0} in a render bundle is not implemented")]
     Unimplemented(&'static str),
+    #[error(transparent)]
+    QueryUse(#[from] QueryUseError),
 }
 impl PrettyError for ExecutionError {
     fn fmt_pretty(&self, fmt: &mut ErrorFormatter) {
@@ -702,7 +958,7 @@ impl PrettyError for ExecutionError {
             Self::DestroyedBuffer(id) => {
                 fmt.buffer_label(&id);
             }
-            Self::Unimplemented(_reason) => {}
+            Self::Unimplemented(_) | Self::QueryUse(_) => {}
         };
     }
 }
@@ -722,6 +978,10 @@ pub struct RenderBundle<A: HalApi> {
     pub(crate) used: RenderBundleScope<A>,
     pub(super) buffer_memory_init_actions: Vec<BufferInitTrackerAction>,
     pub(super) texture_memory_init_actions: Vec<TextureInitTrackerAction>,
+    /// Limits each indirect draw is checked against, with indirect validation.
+    indirect_args: Vec<IndirectArgs>,
+    /// Size of the checked arguments of the indirect draws.
+    pub(super) indirect_size: wgt::BufferAddress,
     pub(super) context: RenderPassContext,
     pub(crate) life_guard: LifeGuard,
 }
@@ -738,7 +998,35 @@ impl<A: HalApi> RenderBundle<A> {
     ///
     /// Note that the function isn't expected to fail, generally.
     /// All the validation has already been done by this point.
-    /// The only failure condition is if some of the used buffers are destroyed.
+    /// The only failure conditions are if some of the used buffers are destroyed,
+    /// or if the bundle's queries conflict with queries used by the pass
+    /// it is executed in, tracked by `query_reset_state` and `active_query`.
+    #[allow(clippy::too_many_arguments)]
+    /// Records the states the push constants go through in the bundle, when they are emulated.
+    ///
+    /// Returns `false` if the bundle doesn't set push constants.
+    pub(super) fn record_push_constant_states(&self, states: &mut PushConstantStates) -> bool {
+        let mut recorded = false;
+        for command in self.base.commands.iter() {
+            if let RenderCommand::SetPushConstant {
+                offset,
+                size_bytes,
+                values_offset,
+                ..
+            } = *command
+            {
+                let values = values_offset.map(|values_offset| {
+                    let values_end_offset =
+                        (values_offset + size_bytes / wgt::PUSH_CONSTANT_ALIGNMENT) as usize;
+                    &self.base.push_constant_data[(values_offset as usize)..values_end_offset]
+                });
+                states.update(offset, size_bytes, values);
+                recorded = true;
+            }
+        }
+        recorded
+    }
+
     pub(super) unsafe fn execute(
         &self,
         raw: &mut A::CommandEncoder,
@@ -748,10 +1036,17 @@ impl<A: HalApi> RenderBundle<A> {
         >,
         bind_group_guard: &Storage<crate::binding_model::BindGroup<A>, id::BindGroupId>,
         pipeline_guard: &Storage<crate::pipeline::RenderPipeline<A>, id::RenderPipelineId>,
+        query_set_guard: &Storage<crate::resource::QuerySet<A>, id::QuerySetId>,
         buffer_guard: &Storage<crate::resource::Buffer<A>, id::BufferId>,
+        query_reset_state: &mut QueryResetMap<A>,
+        active_query: &mut Option<(id::QuerySetId, u32)>,
+        mut push_constant_cursor: Option<&mut PushConstantCursor<A>>,
+        mut indirect_draws: Option<&mut IndirectDraws<A>>,
     ) -> Result<(), ExecutionError> {
         let mut offsets = self.base.dynamic_offsets.as_slice();
+        let mut indirect_args = self.indirect_args.iter();
         let mut pipeline_layout_id = None::<id::Valid<id::PipelineLayoutId>>;
+        let mut string_offset = 0;
         if let Some(ref label) = self.base.label {
             raw.begin_debug_marker(label);
         }
@@ -825,7 +1120,9 @@ impl<A: HalApi> RenderBundle<A> {
                     let pipeline_layout_id = pipeline_layout_id.unwrap();
                     let pipeline_layout = &pipeline_layout_guard[pipeline_layout_id];
 
-                    if let Some(values_offset) = values_offset {
+                    if let Some(ref mut cursor) = push_constant_cursor {
+                        cursor.advance(raw, &pipeline_layout.raw);
+                    } else if let Some(values_offset) = values_offset {
                         let values_end_offset =
                             (values_offset + size_bytes / wgt::PUSH_CONSTANT_ALIGNMENT) as usize;
                         let data_slice = &self.base.push_constant_data
@@ -873,8 +1170,8 @@ impl<A: HalApi> RenderBundle<A> {
                 RenderCommand::MultiDrawIndirect {
                     buffer_id,
                     offset,
-                    count: None,
-                    indexed: false,
+                    count,
+                    indexed,
                 } => {
                     let buffer = buffer_guard
                         .get(buffer_id)
@@ -882,13 +1179,27 @@ impl<A: HalApi> RenderBundle<A> {
                         .raw
                         .as_ref()
                         .ok_or(ExecutionError::DestroyedBuffer(buffer_id))?;
-                    raw.draw_indirect(buffer, offset, 1);
+                    let count = count.map_or(1, |c| c.get());
+                    let args = *indirect_args.next().unwrap();
+                    let (buffer, offset) = match indirect_draws {
+                        Some(ref mut draws) => (
+                            draws.arguments.buffer(),
+                            draws.check(buffer_id, offset, count, args),
+                        ),
+                        None => (buffer, offset),
+                    };
+                    match indexed {
+                        false => raw.draw_indirect(buffer, offset, count),
+                        true => raw.draw_indexed_indirect(buffer, offset, count),
+                    }
                 }
-                RenderCommand::MultiDrawIndirect {
+                RenderCommand::MultiDrawIndirectCount {
                     buffer_id,
                     offset,
-                    count: None,
-                    indexed: true,
+                    count_buffer_id,
+                    count_buffer_offset,
+                    max_count,
+                    indexed,
                 } => {
                     let buffer = buffer_guard
                         .get(buffer_id)
@@ -896,27 +1207,89 @@ impl<A: HalApi> RenderBundle<A> {
                         .raw
                         .as_ref()
                         .ok_or(ExecutionError::DestroyedBuffer(buffer_id))?;
-                    raw.draw_indexed_indirect(buffer, offset, 1);
+                    let count_buffer = buffer_guard
+                        .get(count_buffer_id)
+                        .unwrap()
+                        .raw
+                        .as_ref()
+                        .ok_or(ExecutionError::DestroyedBuffer(count_buffer_id))?;
+                    let args = *indirect_args.next().unwrap();
+                    let (buffer, offset) = match indirect_draws {
+                        Some(ref mut draws) if max_count != 0 => (
+                            draws.arguments.buffer(),
+                            draws.check(buffer_id, offset, max_count, args),
+                        ),
+                        _ => (buffer, offset),
+                    };
+                    match indexed {
+                        false => raw.draw_indirect_count(
+                            buffer,
+                            offset,
+                            count_buffer,
+                            count_buffer_offset,
+                            max_count,
+                        ),
+                        true => raw.draw_indexed_indirect_count(
+                            buffer,
+                            offset,
+                            count_buffer,
+                            count_buffer_offset,
+                            max_count,
+                        ),
+                    }
+                }
+                RenderCommand::PushDebugGroup { color: _, len } => {
+                    let label =
+                        str::from_utf8(&self.base.string_data[string_offset..string_offset + len])
+                            .unwrap();
+                    string_offset += len;
+                    raw.begin_debug_marker(label);
+                }
+                RenderCommand::PopDebugGroup => {
+                    raw.end_debug_marker();
                 }
-                RenderCommand::MultiDrawIndirect { .. }
-                | RenderCommand::MultiDrawIndirectCount { .. } => {
-                    return Err(ExecutionError::Unimplemented("multi-draw-indirect"))
+                RenderCommand::InsertDebugMarker { color: _, len } => {
+                    let label =
+                        str::from_utf8(&self.base.string_data[string_offset..string_offset + len])
+                            .unwrap();
+                    string_offset += len;
+                    raw.insert_debug_marker(label);
                 }
-                RenderCommand::PushDebugGroup { .. }
-                | RenderCommand::InsertDebugMarker { .. }
-                | RenderCommand::PopDebugGroup => {
-                    return Err(ExecutionError::Unimplemented("debug-markers"))
+                RenderCommand::WriteTimestamp {
+                    query_set_id,
+                    query_index,
+                } => {
+                    let query_set = query_set_guard.get(query_set_id).unwrap();
+                    query_set.validate_and_write_timestamp(
+                        raw,
+                        query_set_id,
+                        query_index,
+                        Some(query_reset_state),
+                    )?;
+                }
+                RenderCommand::BeginPipelineStatisticsQuery {
+                    query_set_id,
+                    query_index,
+                } => {
+                    let query_set = query_set_guard.get(query_set_id).unwrap();
+                    query_set.validate_and_begin_pipeline_statistics_query(
+                        raw,
+                        query_set_id,
+                        query_index,
+                        Some(query_reset_state),
+                        active_query,
+                    )?;
                 }
-                RenderCommand::WriteTimestamp { .. }
-                | RenderCommand::BeginPipelineStatisticsQuery { .. }
-                | RenderCommand::EndPipelineStatisticsQuery => {
-                    return Err(ExecutionError::Unimplemented("queries"))
+                RenderCommand::EndPipelineStatisticsQuery => {
+                    end_pipeline_statistics_query(raw, query_set_guard, active_query)?;
                 }
                 RenderCommand::ExecuteBundle(_)
                 | RenderCommand::SetBlendConstant(_)
                 | RenderCommand::SetStencilReference(_)
                 | RenderCommand::SetViewport { .. }
-                | RenderCommand::SetScissor(_) => unreachable!(),
+                | RenderCommand::SetScissor(_)
+                | RenderCommand::BeginOcclusionQuery { .. }
+                | RenderCommand::EndOcclusionQuery => unreachable!(),
             }
         }
 
@@ -1069,6 +1442,9 @@ struct PipelineState {
 
     /// The number of bind groups this pipeline uses.
     used_bind_groups: usize,
+
+    /// The format of the indices of strips this pipeline draws, if it draws strips.
+    strip_index_format: Option<wgt::IndexFormat>,
 }
 
 impl PipelineState {
@@ -1083,6 +1459,7 @@ impl PipelineState {
             steps: pipeline.vertex_steps.to_vec(),
             push_constant_ranges: layout.push_constant_ranges.iter().cloned().collect(),
             used_bind_groups: layout.bind_group_layout_ids.len(),
+            strip_index_format: pipeline.strip_index_format,
         }
     }
 
@@ -1143,6 +1520,15 @@ struct State<A: HalApi> {
     ///
     /// [`dynamic_offsets`]: BasePass::dynamic_offsets
     flat_dynamic_offsets: Vec<wgt::DynamicOffset>,
+
+    /// The number of debug groups pushed and not yet popped.
+    debug_scope_depth: u32,
+
+    /// Queries used by the bundle so far, to catch queries used twice.
+    query_reset_state: QueryResetMap<A>,
+
+    /// The pipeline statistics query that has been begun and not yet ended, if any.
+    active_query: Option<(id::QuerySetId, u32)>,
 }
 
 impl<A: HalApi> State<A> {
@@ -1175,6 +1561,21 @@ impl<A: HalApi> State<A> {
         vert_state
     }
 
+    /// Returns the limits indirect draws are checked against in this state.
+    fn indirect_args(&self, pipeline: &PipelineState, indexed: bool) -> IndirectArgs {
+        let vertex_limits = self.vertex_limits(pipeline);
+        match self.index {
+            Some(ref index) if indexed => IndirectArgs::DrawIndexed {
+                index_limit: index.limit(),
+                instance_limit: vertex_limits.instance_limit,
+            },
+            _ => IndirectArgs::Draw {
+                vertex_limit: vertex_limits.vertex_limit,
+                instance_limit: vertex_limits.instance_limit,
+            },
+        }
+    }
+
     /// Return the id of the current pipeline, if any.
     fn pipeline_id(&self) -> Option<id::RenderPipelineId> {
         self.pipeline.as_ref().map(|p| p.id)
@@ -1364,6 +1765,30 @@ pub(super) enum RenderBundleErrorInner {
     Draw(#[from] DrawError),
     #[error(transparent)]
     MissingDownlevelFlags(#[from] MissingDownlevelFlags),
+    #[error(transparent)]
+    MissingFeatures(#[from] MissingFeatures),
+    #[error(transparent)]
+    QueryUse(#[from] QueryUseError),
+    #[error("indirect draw uses bytes {offset}..{end_offset} {} which overruns indirect buffer of size {buffer_size}",
+        count.map_or_else(String::new, |v| format!("(using count {})", v)))]
+    IndirectBufferOverrun {
+        count: Option<NonZeroU32>,
+        offset: u64,
+        end_offset: u64,
+        buffer_size: u64,
+    },
+    #[error("indirect draw uses bytes {begin_count_offset}..{end_count_offset} which overruns indirect buffer of size {count_buffer_size}")]
+    IndirectCountBufferOverrun {
+        begin_count_offset: u64,
+        end_count_offset: u64,
+        count_buffer_size: u64,
+    },
+    #[error("cannot pop debug group, because number of pushed debug groups is zero")]
+    InvalidPopDebugGroup,
+    #[error("render bundle ends with {0} debug groups that were pushed but never popped")]
+    UnbalancedDebugGroups(u32),
+    #[error("render bundle ends while pipeline statistics query {query_index} is still active")]
+    UnterminatedQuery { query_index: u32 },
 }
 
 impl<T> From<T> for RenderBundleErrorInner
@@ -1414,7 +1839,7 @@ where
 pub mod bundle_ffi {
     use super::{RenderBundleEncoder, RenderCommand};
     use crate::{id, RawString};
-    use std::{convert::TryInto, slice};
+    use std::{convert::TryInto, ffi, num::NonZeroU32, slice};
     use wgt::{BufferAddress, BufferSize, DynamicOffset, IndexFormat};
 
     /// # Safety
@@ -1593,21 +2018,101 @@ pub mod bundle_ffi {
         });
     }
 
+    #[no_mangle]
+    pub extern "C" fn wgpu_render_bundle_multi_draw_indirect(
+        bundle: &mut RenderBundleEncoder,
+        buffer_id: id::BufferId,
+        offset: BufferAddress,
+        count: u32,
+    ) {
+        bundle.base.commands.push(RenderCommand::MultiDrawIndirect {
+            buffer_id,
+            offset,
+            count: NonZeroU32::new(count),
+            indexed: false,
+        });
+    }
+
+    #[no_mangle]
+    pub extern "C" fn wgpu_render_bundle_multi_draw_indexed_indirect(
+        bundle: &mut RenderBundleEncoder,
+        buffer_id: id::BufferId,
+        offset: BufferAddress,
+        count: u32,
+    ) {
+        bundle.base.commands.push(RenderCommand::MultiDrawIndirect {
+            buffer_id,
+            offset,
+            count: NonZeroU32::new(count),
+            indexed: true,
+        });
+    }
+
+    #[no_mangle]
+    pub extern "C" fn wgpu_render_bundle_multi_draw_indirect_count(
+        bundle: &mut RenderBundleEncoder,
+        buffer_id: id::BufferId,
+        offset: BufferAddress,
+        count_buffer_id: id::BufferId,
+        count_buffer_offset: BufferAddress,
+        max_count: u32,
+    ) {
+        bundle
+            .base
+            .commands
+            .push(RenderCommand::MultiDrawIndirectCount {
+                buffer_id,
+                offset,
+                count_buffer_id,
+                count_buffer_offset,
+                max_count,
+                indexed: false,
+            });
+    }
+
+    #[no_mangle]
+    pub extern "C" fn wgpu_render_bundle_multi_draw_indexed_indirect_count(
+        bundle: &mut RenderBundleEncoder,
+        buffer_id: id::BufferId,
+        offset: BufferAddress,
+        count_buffer_id: id::BufferId,
+        count_buffer_offset: BufferAddress,
+        max_count: u32,
+    ) {
+        bundle
+            .base
+            .commands
+            .push(RenderCommand::MultiDrawIndirectCount {
+                buffer_id,
+                offset,
+                count_buffer_id,
+                count_buffer_offset,
+                max_count,
+                indexed: true,
+            });
+    }
+
     /// # Safety
     ///
     /// This function is unsafe as there is no guarantee that the given `label`
     /// is a valid null-terminated string.
     #[no_mangle]
     pub unsafe extern "C" fn wgpu_render_bundle_push_debug_group(
-        _bundle: &mut RenderBundleEncoder,
-        _label: RawString,
+        bundle: &mut RenderBundleEncoder,
+        label: RawString,
     ) {
-        //TODO
+        let bytes = ffi::CStr::from_ptr(label).to_bytes();
+        bundle.base.string_data.extend_from_slice(bytes);
+
+        bundle.base.commands.push(RenderCommand::PushDebugGroup {
+            color: 0,
+            len: bytes.len(),
+        });
     }
 
     #[no_mangle]
-    pub extern "C" fn wgpu_render_bundle_pop_debug_group(_bundle: &mut RenderBundleEncoder) {
-        //TODO
+    pub extern "C" fn wgpu_render_bundle_pop_debug_group(bundle: &mut RenderBundleEncoder) {
+        bundle.base.commands.push(RenderCommand::PopDebugGroup);
     }
 
     /// # Safety
@@ -1616,9 +2121,52 @@ pub mod bundle_ffi {
     /// is a valid null-terminated string.
     #[no_mangle]
     pub unsafe extern "C" fn wgpu_render_bundle_insert_debug_marker(
-        _bundle: &mut RenderBundleEncoder,
-        _label: RawString,
+        bundle: &mut RenderBundleEncoder,
+        label: RawString,
     ) {
-        //TODO
+        let bytes = ffi::CStr::from_ptr(label).to_bytes();
+        bundle.base.string_data.extend_from_slice(bytes);
+
+        bundle.base.commands.push(RenderCommand::InsertDebugMarker {
+            color: 0,
+            len: bytes.len(),
+        });
+    }
+
+    #[no_mangle]
+    pub extern "C" fn wgpu_render_bundle_write_timestamp(
+        bundle: &mut RenderBundleEncoder,
+        query_set_id: id::QuerySetId,
+        query_index: u32,
+    ) {
+        bundle.base.commands.push(RenderCommand::WriteTimestamp {
+            query_set_id,
+            query_index,
+        });
+    }
+
+    #[no_mangle]
+    pub extern "C" fn wgpu_render_bundle_begin_pipeline_statistics_query(
+        bundle: &mut RenderBundleEncoder,
+        query_set_id: id::QuerySetId,
+        query_index: u32,
+    ) {
+        bundle
+            .base
+            .commands
+            .push(RenderCommand::BeginPipelineStatisticsQuery {
+                query_set_id,
+                query_index,
+            });
+    }
+
+    #[no_mangle]
+    pub extern "C" fn wgpu_render_bundle_end_pipeline_statistics_query(
+        bundle: &mut RenderBundleEncoder,
+    ) {
+        bundle
+            .base
+            .commands
+            .push(RenderCommand::EndPipelineStatisticsQuery);
     }
 }
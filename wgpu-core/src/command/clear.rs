@@ -3,7 +3,7 @@ use std::{num::NonZeroU32, ops::Range};
 #[cfg(feature = "trace")]
 use crate::device::trace::Command as TraceCommand;
 use crate::{
-    command::CommandBuffer,
+    command::{BarrierCause, BarrierRecorder, CommandBuffer},
     get_lowest_common_denom,
     hub::{self, Global, GlobalIdentityHandlerFactory, HalApi, Token},
     id::{BufferId, CommandEncoderId, DeviceId, TextureId, Valid},
@@ -79,6 +79,9 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         let (mut cmd_buf_guard, mut token) = hub.command_buffers.write(&mut token);
         let cmd_buf = CommandBuffer::get_encoder_mut(&mut *cmd_buf_guard, command_encoder_id)
             .map_err(|_| ClearError::InvalidCommandEncoder(command_encoder_id))?;
+        cmd_buf
+            .barriers
+            .set_cause(|| BarrierCause::Command("clear_buffer"));
         let (buffer_guard, _) = hub.buffers.read(&mut token);
 
         #[cfg(feature = "trace")]
@@ -126,6 +129,10 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             return Ok(());
         }
 
+        if let Some(ref contents) = dst_buffer.index_contents {
+            contents.lock().invalidate();
+        }
+
         // Mark dest as initialized.
         cmd_buf
             .buffer_memory_init_actions
@@ -135,7 +142,12 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                 MemoryInitKind::ImplicitlyInitialized,
             ));
         // actual hal barrier & operation
-        let dst_barrier = dst_pending.map(|pending| pending.into_hal(dst_buffer));
+        let dst_barrier = dst_pending.map(|pending| {
+            cmd_buf
+                .barriers
+                .buffer(&*buffer_guard, pending)
+                .into_hal(dst_buffer)
+        });
         let cmd_buf_raw = cmd_buf.encoder.open();
         unsafe {
             cmd_buf_raw.transition_buffers(dst_barrier.into_iter());
@@ -158,6 +170,9 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         let (mut cmd_buf_guard, mut token) = hub.command_buffers.write(&mut token);
         let cmd_buf = CommandBuffer::get_encoder_mut(&mut *cmd_buf_guard, command_encoder_id)
             .map_err(|_| ClearError::InvalidCommandEncoder(command_encoder_id))?;
+        cmd_buf
+            .barriers
+            .set_cause(|| BarrierCause::Command("clear_texture"));
         let (_, mut token) = hub.buffers.read(&mut token); // skip token
         let (texture_guard, _) = hub.textures.read(&mut token);
 
@@ -227,6 +242,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             },
             cmd_buf.encoder.open(),
             &mut cmd_buf.trackers.textures,
+            &mut cmd_buf.barriers,
             &device.alignments,
             &device.zero_buffer,
         )
@@ -239,6 +255,7 @@ pub(crate) fn clear_texture<A: HalApi>(
     range: TextureInitRange,
     encoder: &mut A::CommandEncoder,
     texture_tracker: &mut TextureTracker<A>,
+    barriers: &mut BarrierRecorder,
     alignments: &hal::Alignments,
     zero_buffer: &A::Buffer,
 ) -> Result<(), ClearError> {
@@ -276,7 +293,7 @@ pub(crate) fn clear_texture<A: HalApi>(
         .set_single(storage, dst_texture_id.0, selector, clear_usage)
         .unwrap()
         .1
-        .map(|pending| pending.into_hal(dst_texture));
+        .map(|pending| barriers.texture(storage, pending).into_hal(dst_texture));
     unsafe {
         encoder.transition_textures(dst_barrier.into_iter());
     }
@@ -302,7 +319,7 @@ pub(crate) fn clear_texture<A: HalApi>(
 }
 
 fn clear_texture_via_buffer_copies<A: hal::Api>(
-    texture_desc: &wgt::TextureDescriptor<()>,
+    texture_desc: &wgt::TextureDescriptor<(), Vec<wgt::TextureFormat>>,
     alignments: &hal::Alignments,
     zero_buffer: &A::Buffer, // Buffer of size device::ZERO_BUFFER_SIZE
     range: TextureInitRange,
@@ -440,6 +457,7 @@ fn clear_texture_via_render_passes<A: hal::Api>(
                     color_attachments,
                     depth_stencil_attachment,
                     multiview: None,
+                    occlusion_query_set: None,
                 });
                 encoder.end_render_pass();
             }
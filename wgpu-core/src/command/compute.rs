@@ -1,22 +1,28 @@
 use crate::{
     binding_model::{
-        BindError, BindGroup, LateMinBufferBindingSizeMismatch, PushConstantUploadError,
+        BindError, BindGroup, LateMinBufferBindingSizeMismatch, PipelineLayout,
+        PushConstantUploadError,
     },
     command::{
         bind::Binder,
         end_pipeline_statistics_query,
         memory_init::{fixup_discarded_surfaces, SurfacesInDiscardState},
-        BasePass, BasePassRef, BindGroupStateChange, CommandBuffer, CommandEncoderError,
-        CommandEncoderStatus, MapPassErr, PassErrorScope, QueryUseError, StateChange,
+        BarrierCause, BarrierRecorder, BasePass, BasePassRef, BindGroupStateChange, CommandBuffer,
+        CommandEncoderError, CommandEncoderStatus, MapPassErr, PassErrorScope, QueryUseError,
+        StateChange,
+    },
+    device::{
+        indirect_validation::{IndirectArgs, IndirectCheck},
+        push_constants::PushConstantCursor,
+        DeviceError, MissingDownlevelFlags, MissingFeatures,
     },
-    device::{MissingDownlevelFlags, MissingFeatures},
     error::{ErrorFormatter, PrettyError},
     hub::{Global, GlobalIdentityHandlerFactory, HalApi, Storage, Token},
     id,
     init_tracker::MemoryInitKind,
     pipeline,
     resource::{self, Buffer, Texture},
-    track::{Tracker, UsageConflict, UsageScope},
+    track::{Tracker, UsageConflict, UsageOrigin, UsageScope, UsageSource},
     validation::{check_buffer_usage, MissingBufferUsageError},
     Label,
 };
@@ -157,6 +163,8 @@ pub enum DispatchError {
 /// Error encountered when performing a compute pass.
 #[derive(Clone, Debug, Error)]
 pub enum ComputePassErrorInner {
+    #[error(transparent)]
+    Device(#[from] DeviceError),
     #[error(transparent)]
     Encoder(#[from] CommandEncoderError),
     #[error("bind group {0:?} is invalid")]
@@ -210,6 +218,9 @@ impl PrettyError for ComputePassErrorInner {
             Self::InvalidIndirectBuffer(id) => {
                 fmt.buffer_label(&id);
             }
+            Self::ResourceUsageConflict(ref conflict) => {
+                conflict.fmt_labels(fmt);
+            }
             _ => {}
         };
     }
@@ -249,6 +260,8 @@ struct State<A: HalApi> {
     pipeline: Option<id::ComputePipelineId>,
     scope: UsageScope<A>,
     debug_scope_depth: u32,
+    /// Values of the native push constants, to set them again after an internal dispatch.
+    push_constants: Vec<u32>,
 }
 
 impl<A: HalApi> State<A> {
@@ -269,19 +282,30 @@ impl<A: HalApi> State<A> {
     }
 
     // `extra_buffer` is there to represent the indirect buffer that is also part of the usage scope.
+    // `command_index` is the index of the dispatch, to which the uses of the bind groups are attributed.
     fn flush_states(
         &mut self,
         raw_encoder: &mut A::CommandEncoder,
         base_trackers: &mut Tracker<A>,
+        barriers: &mut BarrierRecorder,
         bind_group_guard: &Storage<BindGroup<A>, id::BindGroupId>,
         buffer_guard: &Storage<Buffer<A>, id::BufferId>,
         texture_guard: &Storage<Texture<A>, id::TextureId>,
         indirect_buffer: Option<id::Valid<id::BufferId>>,
+        command_index: usize,
     ) -> Result<(), UsageConflict> {
-        for id in self.binder.list_active() {
+        for (index, payload) in self.binder.list_active_payloads() {
+            let id = payload.group_id.as_ref().unwrap().value;
+            let source = UsageSource::BindGroup {
+                group: index as u32,
+                bind_group: id.0,
+            };
             unsafe {
-                self.scope
-                    .merge_bind_group(texture_guard, &bind_group_guard[id].used)?
+                self.scope.merge_bind_group(
+                    texture_guard,
+                    &bind_group_guard[id].used,
+                    UsageOrigin::new(source, Some(command_index)),
+                )?
             };
             // Note: stateless trackers are not merged: the lifetime reference
             // is held to the bind group itself.
@@ -306,9 +330,62 @@ impl<A: HalApi> State<A> {
 
         log::trace!("Encoding dispatch barriers");
 
-        CommandBuffer::drain_barriers(raw_encoder, base_trackers, buffer_guard, texture_guard);
+        CommandBuffer::drain_barriers(
+            raw_encoder,
+            base_trackers,
+            barriers,
+            buffer_guard,
+            texture_guard,
+        );
         Ok(())
     }
+
+    /// Sets the pipeline, bind groups and push constants of the pass again, after
+    /// an internal dispatch replaced them.
+    unsafe fn restore(
+        &self,
+        raw_encoder: &mut A::CommandEncoder,
+        pipeline_guard: &Storage<pipeline::ComputePipeline<A>, id::ComputePipelineId>,
+        pipeline_layout_guard: &Storage<PipelineLayout<A>, id::PipelineLayoutId>,
+        bind_group_guard: &Storage<BindGroup<A>, id::BindGroupId>,
+        push_constant_cursor: Option<&PushConstantCursor<A>>,
+    ) {
+        let pipeline = &pipeline_guard[id::Valid(self.pipeline.unwrap())];
+        raw_encoder.set_compute_pipeline(&pipeline.raw);
+
+        let pipeline_layout = &pipeline_layout_guard[self.binder.pipeline_layout_id.unwrap()];
+        for (index, payload) in self.binder.list_active_payloads() {
+            let raw_bg = &bind_group_guard[payload.group_id.as_ref().unwrap().value].raw;
+            raw_encoder.set_bind_group(
+                &pipeline_layout.raw,
+                index as u32,
+                raw_bg,
+                &payload.dynamic_offsets,
+            );
+        }
+
+        if pipeline_layout.push_constant_ranges.is_empty() {
+            return;
+        }
+        match push_constant_cursor {
+            Some(cursor) => cursor.bind(raw_encoder, &pipeline_layout.raw),
+            None => {
+                let non_overlapping = super::bind::compute_nonoverlapping_ranges(
+                    &pipeline_layout.push_constant_ranges,
+                );
+                for range in non_overlapping {
+                    let offset = range.range.start;
+                    let words = offset as usize / 4..range.range.end as usize / 4;
+                    raw_encoder.set_push_constants(
+                        &pipeline_layout.raw,
+                        wgt::ShaderStages::COMPUTE,
+                        offset,
+                        &self.push_constants[words],
+                    );
+                }
+            }
+        }
+    }
 }
 
 // Common routines between render/compute
@@ -368,6 +445,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             pipeline: None,
             scope: UsageScope::new(&*buffer_guard, &*texture_guard),
             debug_scope_depth: 0,
+            push_constants: vec![0; (cmd_buf.limits.max_push_constant_size / 4) as usize],
         };
         let mut temp_offsets = Vec::new();
         let mut dynamic_offset_count = 0;
@@ -386,6 +464,97 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             Some(&*query_set_guard),
         );
 
+        // With emulated push constants, all the states they go through in the
+        // pass are uploaded before it begins.
+        let mut emulated_push_constants = false;
+        let mut pass_push_constants = None;
+        if let Some(ref emulation) = device.push_constant_emulation {
+            let mut states = emulation.states();
+            let mut layout_id = None;
+            for command in base.commands {
+                match *command {
+                    ComputeCommand::SetPipeline(pipeline_id) => {
+                        let pipeline = match pipeline_guard.get(pipeline_id) {
+                            Ok(pipeline) => pipeline,
+                            Err(_) => continue,
+                        };
+                        if layout_id != Some(pipeline.layout_id.value) {
+                            layout_id = Some(pipeline.layout_id.value);
+                            states.reset();
+                            emulated_push_constants |= !pipeline_layout_guard
+                                [pipeline.layout_id.value]
+                                .push_constant_ranges
+                                .is_empty();
+                        }
+                    }
+                    ComputeCommand::SetPushConstant {
+                        offset,
+                        size_bytes,
+                        values_offset,
+                    } => {
+                        let values_end_offset =
+                            (values_offset + size_bytes / wgt::PUSH_CONSTANT_ALIGNMENT) as usize;
+                        states.update(
+                            offset,
+                            size_bytes,
+                            Some(
+                                &base.push_constant_data[values_offset as usize..values_end_offset],
+                            ),
+                        );
+                    }
+                    _ => {}
+                }
+            }
+            if emulated_push_constants {
+                pass_push_constants = Some(
+                    unsafe {
+                        emulation.upload(device, raw, &mut cmd_buf.emulated_push_constants, &states)
+                    }
+                    .map_pass_err(init_scope)?,
+                );
+            }
+        }
+        let mut push_constant_cursor =
+            pass_push_constants.map(|pass| cmd_buf.emulated_push_constants.cursor(pass));
+
+        // With indirect validation, dispatches read their checked arguments from
+        // a buffer of the pass. Each one is checked right before the dispatch, as
+        // previous dispatches of the pass may have written them.
+        let mut validate_indirect = false;
+        if let Some(ref validation) = device.indirect_validation {
+            let args = IndirectArgs::Dispatch;
+            let mut checks = Vec::new();
+            for command in base.commands {
+                if let ComputeCommand::DispatchIndirect { buffer_id, offset } = *command {
+                    // Dispatches without a check fail validation, ending the pass.
+                    let buffer = match buffer_guard.get(buffer_id) {
+                        Ok(buffer) if offset + args.size() <= buffer.size => buffer,
+                        _ => continue,
+                    };
+                    if let Some(ref buf_raw) = buffer.raw {
+                        let check = IndirectCheck {
+                            src_offset: offset,
+                            dst_offset: checks.len() as wgt::BufferAddress * args.size(),
+                            count: 1,
+                            args,
+                        };
+                        checks.push((buf_raw, check));
+                    }
+                }
+            }
+            if !checks.is_empty() {
+                let size = checks.len() as wgt::BufferAddress * args.size();
+                let arguments = unsafe { validation.create_arguments(&device.raw, size) }
+                    .map_pass_err(init_scope)?;
+                cmd_buf.indirect_arguments.push(arguments);
+                let arguments = cmd_buf.indirect_arguments.last_mut().unwrap();
+                unsafe { validation.prepare(&device.raw, raw, arguments, &checks) }
+                    .map_pass_err(init_scope)?;
+                validate_indirect = true;
+            }
+        }
+        let mut indirect_checks = 0;
+
         let hal_desc = hal::ComputePassDescriptor { label: base.label };
         unsafe {
             raw.begin_compute_pass(&hal_desc);
@@ -394,7 +563,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         // Immediate texture inits required because of prior discards. Need to be inserted before texture reads.
         let mut pending_discard_init_fixups = SurfacesInDiscardState::new();
 
-        for command in base.commands {
+        for (command_index, command) in base.commands.iter().enumerate() {
             match *command {
                 ComputeCommand::SetBindGroup {
                     index,
@@ -510,24 +679,31 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                         }
 
                         // Clear push constant ranges
-                        let non_overlapping = super::bind::compute_nonoverlapping_ranges(
-                            &pipeline_layout.push_constant_ranges,
-                        );
-                        for range in non_overlapping {
-                            let offset = range.range.start;
-                            let size_bytes = range.range.end - offset;
-                            super::push_constant_clear(
-                                offset,
-                                size_bytes,
-                                |clear_offset, clear_data| unsafe {
-                                    raw.set_push_constants(
-                                        &pipeline_layout.raw,
-                                        wgt::ShaderStages::COMPUTE,
-                                        clear_offset,
-                                        clear_data,
-                                    );
-                                },
+                        if let Some(ref mut cursor) = push_constant_cursor {
+                            if !pipeline_layout.push_constant_ranges.is_empty() {
+                                unsafe { cursor.reset(raw, &pipeline_layout.raw) };
+                            }
+                        } else {
+                            state.push_constants.fill(0);
+                            let non_overlapping = super::bind::compute_nonoverlapping_ranges(
+                                &pipeline_layout.push_constant_ranges,
                             );
+                            for range in non_overlapping {
+                                let offset = range.range.start;
+                                let size_bytes = range.range.end - offset;
+                                super::push_constant_clear(
+                                    offset,
+                                    size_bytes,
+                                    |clear_offset, clear_data| unsafe {
+                                        raw.set_push_constants(
+                                            &pipeline_layout.raw,
+                                            wgt::ShaderStages::COMPUTE,
+                                            clear_offset,
+                                            clear_data,
+                                        );
+                                    },
+                                );
+                            }
                         }
                     }
                 }
@@ -562,13 +738,23 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                         )
                         .map_pass_err(scope)?;
 
-                    unsafe {
-                        raw.set_push_constants(
-                            &pipeline_layout.raw,
-                            wgt::ShaderStages::COMPUTE,
-                            offset,
-                            data_slice,
-                        );
+                    match push_constant_cursor {
+                        Some(ref mut cursor) => unsafe {
+                            cursor.advance(raw, &pipeline_layout.raw)
+                        },
+                        None => {
+                            let start = offset as usize / 4;
+                            state.push_constants[start..start + data_slice.len()]
+                                .copy_from_slice(data_slice);
+                            unsafe {
+                                raw.set_push_constants(
+                                    &pipeline_layout.raw,
+                                    wgt::ShaderStages::COMPUTE,
+                                    offset,
+                                    data_slice,
+                                );
+                            }
+                        }
                     }
                 }
                 ComputeCommand::Dispatch(groups) => {
@@ -577,11 +763,16 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                         pipeline: state.pipeline,
                     };
 
+                    cmd_buf.barriers.set_cause(|| BarrierCause::ComputePass {
+                        label: base.label.map(str::to_string),
+                        command: command_index,
+                    });
                     fixup_discarded_surfaces(
                         pending_discard_init_fixups.drain(..),
                         raw,
                         &texture_guard,
                         &mut cmd_buf.trackers.textures,
+                        &mut cmd_buf.barriers,
                         device,
                     );
 
@@ -590,10 +781,12 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                         .flush_states(
                             raw,
                             &mut cmd_buf.trackers,
+                            &mut cmd_buf.barriers,
                             &*bind_group_guard,
                             &*buffer_guard,
                             &*texture_guard,
                             None,
+                            command_index,
                         )
                         .map_pass_err(scope)?;
 
@@ -631,7 +824,12 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                     let indirect_buffer: &Buffer<A> = state
                         .scope
                         .buffers
-                        .merge_single(&*buffer_guard, buffer_id, hal::BufferUses::INDIRECT)
+                        .merge_single(
+                            &*buffer_guard,
+                            buffer_id,
+                            hal::BufferUses::INDIRECT,
+                            UsageOrigin::new(UsageSource::IndirectBuffer, Some(command_index)),
+                        )
                         .map_pass_err(scope)?;
                     check_buffer_usage(indirect_buffer.usage, wgt::BufferUsages::INDIRECT)
                         .map_pass_err(scope)?;
@@ -662,18 +860,56 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                         ),
                     );
 
+                    cmd_buf.barriers.set_cause(|| BarrierCause::ComputePass {
+                        label: base.label.map(str::to_string),
+                        command: command_index,
+                    });
                     state
                         .flush_states(
                             raw,
                             &mut cmd_buf.trackers,
+                            &mut cmd_buf.barriers,
                             &*bind_group_guard,
                             &*buffer_guard,
                             &*texture_guard,
                             Some(id::Valid(buffer_id)),
+                            command_index,
                         )
                         .map_pass_err(scope)?;
-                    unsafe {
-                        raw.dispatch_indirect(buf_raw, offset);
+
+                    if validate_indirect {
+                        let validation = device.indirect_validation.as_ref().unwrap();
+                        let arguments = cmd_buf.indirect_arguments.last_mut().unwrap();
+                        let buf_state = cmd_buf
+                            .trackers
+                            .buffers
+                            .current_state(id::Valid(buffer_id))
+                            .unwrap_or(hal::BufferUses::INDIRECT);
+                        unsafe {
+                            validation.validate(
+                                raw,
+                                arguments,
+                                indirect_checks,
+                                buf_raw,
+                                buf_state,
+                            );
+                            state.restore(
+                                raw,
+                                &*pipeline_guard,
+                                &*pipeline_layout_guard,
+                                &*bind_group_guard,
+                                push_constant_cursor.as_ref(),
+                            );
+                            raw.dispatch_indirect(
+                                arguments.buffer(),
+                                indirect_checks as wgt::BufferAddress * stride,
+                            );
+                        }
+                        indirect_checks += 1;
+                    } else {
+                        unsafe {
+                            raw.dispatch_indirect(buf_raw, offset);
+                        }
                     }
                 }
                 ComputeCommand::PushDebugGroup { color: _, len } => {
@@ -765,11 +1001,13 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
 
         // There can be entries left in pending_discard_init_fixups if a bind group was set, but not used (i.e. no Dispatch occurred)
         // However, we already altered the discard/init_action state on this cmd_buf, so we need to apply the promised changes.
+        cmd_buf.barriers.set_cause(|| BarrierCause::MemoryInit);
         fixup_discarded_surfaces(
             pending_discard_init_fixups.into_iter(),
             raw,
             &texture_guard,
             &mut cmd_buf.trackers.textures,
+            &mut cmd_buf.barriers,
             device,
         );
 
@@ -44,6 +44,16 @@ pub enum DrawError {
     },
     #[error("index {last_index} extends beyond limit {index_limit}. Did you bind the correct index buffer?")]
     IndexBeyondLimit { last_index: u32, index_limit: u32 },
+    #[error("index {index} with base vertex {base_vertex} reads vertex {vertex}, outside of the limit {vertex_limit} imposed by the buffer in slot {slot}. Does the index buffer match the vertex buffers?")]
+    IndexedVertexOutOfRange {
+        index: u32,
+        base_vertex: i32,
+        vertex: i64,
+        vertex_limit: u32,
+        slot: u32,
+    },
+    #[error("the indices of the index buffer aren't known, so the vertices they select can't be validated. Write the index buffer with `Queue::write_buffer` or through a mapping, and don't give it `BufferUsages::STORAGE`")]
+    UnknownIndexContents,
     #[error(
         "pipeline index format ({pipeline:?}) and buffer index format ({buffer:?}) do not match"
     )]
@@ -106,11 +116,10 @@ impl crate::error::PrettyError for RenderCommandError {
             Self::InvalidPipeline(id) => {
                 fmt.render_pipeline_label(&id);
             }
-            Self::UsageConflict(UsageConflict::TextureInvalid { id }) => {
-                fmt.texture_label(&id);
+            Self::UsageConflict(ref conflict) => {
+                conflict.fmt_labels(fmt);
             }
-            Self::UsageConflict(UsageConflict::BufferInvalid { id })
-            | Self::DestroyedBuffer(id) => {
+            Self::DestroyedBuffer(id) => {
                 fmt.buffer_label(&id);
             }
             _ => {}
@@ -246,5 +255,9 @@ pub enum RenderCommand {
         query_index: u32,
     },
     EndPipelineStatisticsQuery,
+    BeginOcclusionQuery {
+        query_index: u32,
+    },
+    EndOcclusionQuery,
     ExecuteBundle(id::RenderBundleId),
 }
@@ -12,7 +12,10 @@ use crate::{
     FastHashMap,
 };
 
-use super::{clear::clear_texture, BakedCommands, DestroyedBufferError, DestroyedTextureError};
+use super::{
+    clear::clear_texture, BakedCommands, BarrierRecorder, DestroyedBufferError,
+    DestroyedTextureError,
+};
 
 /// Surface that was discarded by `StoreOp::Discard` of a preceding renderpass.
 /// Any read access to this surface needs to be preceded by a texture initialization.
@@ -25,7 +28,7 @@ pub(crate) struct TextureSurfaceDiscard {
 
 pub(crate) type SurfacesInDiscardState = Vec<TextureSurfaceDiscard>;
 
-#[derive(Default)]
+#[derive(Clone, Default)]
 pub(crate) struct CommandBufferTextureMemoryActions {
     // init actions describe the tracker actions that we need to be executed before the command buffer is executed
     init_actions: Vec<TextureInitTrackerAction>,
@@ -128,6 +131,7 @@ pub(crate) fn fixup_discarded_surfaces<
     encoder: &mut A::CommandEncoder,
     texture_guard: &Storage<Texture<A>, TextureId>,
     texture_tracker: &mut TextureTracker<A>,
+    barriers: &mut BarrierRecorder,
     device: &Device<A>,
 ) {
     for init in inits {
@@ -140,6 +144,7 @@ pub(crate) fn fixup_discarded_surfaces<
             },
             encoder,
             texture_tracker,
+            barriers,
             &device.alignments,
             &device.zero_buffer,
         )
@@ -152,6 +157,7 @@ impl<A: HalApi> BakedCommands<A> {
     pub(crate) fn initialize_buffer_memory(
         &mut self,
         device_tracker: &mut Tracker<A>,
+        barriers: &mut BarrierRecorder,
         buffer_guard: &mut Storage<Buffer<A>, id::BufferId>,
     ) -> Result<(), DestroyedBufferError> {
         // Gather init ranges for each buffer so we can collapse them.
@@ -207,7 +213,8 @@ impl<A: HalApi> BakedCommands<A> {
                 .buffers
                 .set_single(buffer_guard, buffer_id, hal::BufferUses::COPY_DST)
                 .unwrap()
-                .1;
+                .1
+                .map(|pending| barriers.buffer(buffer_guard, pending));
 
             let buffer = buffer_guard
                 .get_mut(buffer_id)
@@ -239,6 +246,7 @@ impl<A: HalApi> BakedCommands<A> {
     pub(crate) fn initialize_texture_memory(
         &mut self,
         device_tracker: &mut Tracker<A>,
+        barriers: &mut BarrierRecorder,
         texture_guard: &mut Storage<Texture<A>, TextureId>,
         device: &Device<A>,
     ) -> Result<(), DestroyedTextureError> {
@@ -283,6 +291,7 @@ impl<A: HalApi> BakedCommands<A> {
                     range,
                     &mut self.encoder,
                     &mut device_tracker.textures,
+                    barriers,
                     &device.alignments,
                     &device.zero_buffer,
                 )
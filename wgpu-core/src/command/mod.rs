@@ -1,3 +1,4 @@
+mod barrier_log;
 mod bind;
 mod bundle;
 mod clear;
@@ -8,23 +9,35 @@ mod query;
 mod render;
 mod transfer;
 
-use std::slice;
+use std::{mem, slice};
 
 pub(crate) use self::clear::clear_texture;
 pub use self::{
-    bundle::*, clear::ClearError, compute::*, draw::*, query::*, render::*, transfer::*,
+    barrier_log::{BarrierCause, BarrierLog, BarrierRecord, BarrierResource},
+    bundle::*,
+    clear::ClearError,
+    compute::*,
+    draw::*,
+    query::*,
+    render::*,
+    transfer::*,
 };
 
+pub(crate) use self::barrier_log::BarrierRecorder;
+
 use self::memory_init::CommandBufferTextureMemoryActions;
 
+use crate::device::indirect_validation::IndirectArguments;
+use crate::device::push_constants::EmulatedPushConstants;
 use crate::error::{ErrorFormatter, PrettyError};
 use crate::init_tracker::BufferInitTrackerAction;
 use crate::track::{Tracker, UsageScope};
 use crate::{
+    device::MissingFeatures,
     hub::{Global, GlobalIdentityHandlerFactory, HalApi, Storage, Token},
     id,
-    resource::{Buffer, Texture},
-    Label, Stored,
+    resource::{Buffer, Texture, TextureInner},
+    Label, Stored, SubmissionIndex,
 };
 
 use hal::CommandEncoder as _;
@@ -87,6 +100,10 @@ pub struct BakedCommands<A: HalApi> {
     pub(crate) trackers: Tracker<A>,
     buffer_memory_init_actions: Vec<BufferInitTrackerAction>,
     texture_memory_actions: CommandBufferTextureMemoryActions,
+    pub(crate) emulated_push_constants: EmulatedPushConstants<A>,
+    pub(crate) indirect_arguments: Vec<IndirectArguments<A>>,
+    pub(crate) label: Option<String>,
+    pub(crate) barriers: BarrierRecorder,
 }
 
 pub(crate) struct DestroyedBufferError(pub id::BufferId);
@@ -99,8 +116,21 @@ pub struct CommandBuffer<A: HalApi> {
     pub(crate) trackers: Tracker<A>,
     buffer_memory_init_actions: Vec<BufferInitTrackerAction>,
     texture_memory_actions: CommandBufferTextureMemoryActions,
+    /// Push constant states uploaded for the passes, when push constants are emulated.
+    pub(crate) emulated_push_constants: EmulatedPushConstants<A>,
+    /// Checked arguments of the indirect commands of the passes, when they are validated.
+    pub(crate) indirect_arguments: Vec<IndirectArguments<A>>,
+    /// Barriers recorded for the barrier log of the device, if enabled.
+    pub(crate) barriers: BarrierRecorder,
     limits: wgt::Limits,
     support_clear_texture: bool,
+    support_reusable: bool,
+    /// If this is true, the command buffer was finished with
+    /// [`Global::command_encoder_finish_reusable`] and stays registered after
+    /// being submitted.
+    reusable: bool,
+    /// Index of the last submission of the command buffer.
+    pub(crate) last_submission: SubmissionIndex,
     #[cfg(feature = "trace")]
     pub(crate) commands: Option<Vec<TraceCommand>>,
 }
@@ -113,6 +143,7 @@ impl<A: HalApi> CommandBuffer<A> {
         _downlevel: wgt::DownlevelCapabilities,
         features: wgt::Features,
         #[cfg(feature = "trace")] enable_tracing: bool,
+        enable_barrier_log: bool,
         label: &Label,
     ) -> Self {
         CommandBuffer {
@@ -127,8 +158,14 @@ impl<A: HalApi> CommandBuffer<A> {
             trackers: Tracker::new(),
             buffer_memory_init_actions: Default::default(),
             texture_memory_actions: Default::default(),
+            emulated_push_constants: EmulatedPushConstants::new(),
+            indirect_arguments: Vec::new(),
+            barriers: BarrierRecorder::new(enable_barrier_log),
             limits,
             support_clear_texture: features.contains(wgt::Features::CLEAR_TEXTURE),
+            support_reusable: features.contains(wgt::Features::REUSABLE_COMMAND_BUFFERS),
+            reusable: false,
+            last_submission: 0,
             #[cfg(feature = "trace")]
             commands: if enable_tracing {
                 Some(Vec::new())
@@ -142,6 +179,7 @@ impl<A: HalApi> CommandBuffer<A> {
         raw: &mut A::CommandEncoder,
         base: &mut Tracker<A>,
         head: &Tracker<A>,
+        barriers: &mut BarrierRecorder,
         buffer_guard: &Storage<Buffer<A>, id::BufferId>,
         texture_guard: &Storage<Texture<A>, id::TextureId>,
     ) {
@@ -151,13 +189,14 @@ impl<A: HalApi> CommandBuffer<A> {
         base.textures
             .set_from_tracker(&*texture_guard, &head.textures);
 
-        Self::drain_barriers(raw, base, buffer_guard, texture_guard);
+        Self::drain_barriers(raw, base, barriers, buffer_guard, texture_guard);
     }
 
     pub(crate) fn insert_barriers_from_scope(
         raw: &mut A::CommandEncoder,
         base: &mut Tracker<A>,
         head: &UsageScope<A>,
+        barriers: &mut BarrierRecorder,
         buffer_guard: &Storage<Buffer<A>, id::BufferId>,
         texture_guard: &Storage<Texture<A>, id::TextureId>,
     ) {
@@ -167,28 +206,33 @@ impl<A: HalApi> CommandBuffer<A> {
         base.textures
             .set_from_usage_scope(&*texture_guard, &head.textures);
 
-        Self::drain_barriers(raw, base, buffer_guard, texture_guard);
+        Self::drain_barriers(raw, base, barriers, buffer_guard, texture_guard);
     }
 
     pub(crate) fn drain_barriers(
         raw: &mut A::CommandEncoder,
         base: &mut Tracker<A>,
+        barriers: &mut BarrierRecorder,
         buffer_guard: &Storage<Buffer<A>, id::BufferId>,
         texture_guard: &Storage<Texture<A>, id::TextureId>,
     ) {
         profiling::scope!("drain_barriers");
 
         let buffer_barriers = base.buffers.drain().map(|pending| {
+            let pending = barriers.buffer(buffer_guard, pending);
             let buf = unsafe { &buffer_guard.get_unchecked(pending.id) };
             pending.into_hal(buf)
         });
+        unsafe {
+            raw.transition_buffers(buffer_barriers);
+        }
+
         let texture_barriers = base.textures.drain().map(|pending| {
+            let pending = barriers.texture(texture_guard, pending);
             let tex = unsafe { texture_guard.get_unchecked(pending.id) };
             pending.into_hal(tex)
         });
-
         unsafe {
-            raw.transition_buffers(buffer_barriers);
             raw.transition_textures(texture_barriers);
         }
     }
@@ -216,6 +260,39 @@ impl<A: HalApi> CommandBuffer<A> {
         }
     }
 
+    pub(crate) fn is_reusable(&self) -> bool {
+        self.reusable
+    }
+
+    /// The raw command buffers recorded by the encoder.
+    pub(crate) fn raw_list(&self) -> &[A::CommandBuffer] {
+        &self.encoder.list
+    }
+
+    /// Prepares a submission of a reusable command buffer.
+    ///
+    /// The returned commands record the transitions of the submission with
+    /// `encoder`, and hold no raw command buffers: these stay here, to be
+    /// submitted again. The trackers are moved out, and have to be given
+    /// back with [`CommandBuffer::restore_trackers`].
+    pub(crate) fn bake_for_submission(&mut self, encoder: A::CommandEncoder) -> BakedCommands<A> {
+        BakedCommands {
+            encoder,
+            list: Vec::new(),
+            trackers: mem::replace(&mut self.trackers, Tracker::new()),
+            buffer_memory_init_actions: self.buffer_memory_init_actions.clone(),
+            texture_memory_actions: self.texture_memory_actions.clone(),
+            emulated_push_constants: EmulatedPushConstants::new(),
+            indirect_arguments: Vec::new(),
+            label: self.encoder.label.clone(),
+            barriers: self.barriers.clone(),
+        }
+    }
+
+    pub(crate) fn restore_trackers(&mut self, trackers: Tracker<A>) {
+        self.trackers = trackers;
+    }
+
     pub(crate) fn into_baked(self) -> BakedCommands<A> {
         BakedCommands {
             encoder: self.encoder.raw,
@@ -223,10 +300,36 @@ impl<A: HalApi> CommandBuffer<A> {
             trackers: self.trackers,
             buffer_memory_init_actions: self.buffer_memory_init_actions,
             texture_memory_actions: self.texture_memory_actions,
+            emulated_push_constants: self.emulated_push_constants,
+            indirect_arguments: self.indirect_arguments,
+            label: self.encoder.label,
+            barriers: self.barriers,
         }
     }
 }
 
+impl<A: HalApi> CommandBuffer<A> {
+    fn check_reusable(
+        &self,
+        reusable: bool,
+        texture_guard: &Storage<Texture<A>, id::TextureId>,
+    ) -> Result<(), CommandEncoderError> {
+        if !reusable {
+            return Ok(());
+        }
+        if !self.support_reusable {
+            return Err(MissingFeatures(wgt::Features::REUSABLE_COMMAND_BUFFERS).into());
+        }
+        // Surface textures are replaced at every frame.
+        for id in self.trackers.textures.used() {
+            if let TextureInner::Surface { .. } = texture_guard[id].inner {
+                return Err(CommandEncoderError::ReusedSurfaceTexture(id.0));
+            }
+        }
+        Ok(())
+    }
+}
+
 impl<A: HalApi> crate::hub::Resource for CommandBuffer<A> {
     const TYPE: &'static str = "CommandBuffer";
 
@@ -260,7 +363,7 @@ pub struct BasePassRef<'a, C> {
 /// [`SetBindGroup`]: RenderCommand::SetBindGroup
 /// [`InsertDebugMarker`]: RenderCommand::InsertDebugMarker
 #[doc(hidden)]
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 #[cfg_attr(
     any(feature = "serial-pass", feature = "trace"),
     derive(serde::Serialize)
@@ -333,6 +436,10 @@ pub enum CommandEncoderError {
     Invalid,
     #[error("command encoder must be active")]
     NotRecording,
+    #[error(transparent)]
+    MissingFeatures(#[from] MissingFeatures),
+    #[error("texture {0:?} belongs to a surface, and can't be used by a reusable command buffer")]
+    ReusedSurfaceTexture(id::TextureId),
 }
 
 impl<G: GlobalIdentityHandlerFactory> Global<G> {
@@ -342,20 +449,55 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         _desc: &wgt::CommandBufferDescriptor<Label>,
     ) -> (id::CommandBufferId, Option<CommandEncoderError>) {
         profiling::scope!("CommandEncoder::finish");
+        self.command_encoder_finish_impl::<A>(encoder_id, false)
+    }
 
+    /// Finishes a command encoder into a command buffer that can be submitted
+    /// any number of times, until it is dropped.
+    ///
+    /// Requires [`wgt::Features::REUSABLE_COMMAND_BUFFERS`]. At each
+    /// submission, the state of the resources is transitioned to the one
+    /// the commands expect, and the resources are kept alive until the last
+    /// submission of the command buffer is done.
+    pub fn command_encoder_finish_reusable<A: HalApi>(
+        &self,
+        encoder_id: id::CommandEncoderId,
+        _desc: &wgt::CommandBufferDescriptor<Label>,
+    ) -> (id::CommandBufferId, Option<CommandEncoderError>) {
+        profiling::scope!("CommandEncoder::finish_reusable");
+        self.command_encoder_finish_impl::<A>(encoder_id, true)
+    }
+
+    fn command_encoder_finish_impl<A: HalApi>(
+        &self,
+        encoder_id: id::CommandEncoderId,
+        reusable: bool,
+    ) -> (id::CommandBufferId, Option<CommandEncoderError>) {
         let hub = A::hub(self);
         let mut token = Token::root();
-        let (mut cmd_buf_guard, _) = hub.command_buffers.write(&mut token);
+        let (mut cmd_buf_guard, mut token) = hub.command_buffers.write(&mut token);
+        let (_, mut token) = hub.buffers.read(&mut token); // skip token
+        let (texture_guard, _) = hub.textures.read(&mut token);
 
         let error = match cmd_buf_guard.get_mut(encoder_id) {
             Ok(cmd_buf) => match cmd_buf.status {
                 CommandEncoderStatus::Recording => {
-                    cmd_buf.encoder.close();
-                    cmd_buf.status = CommandEncoderStatus::Finished;
-                    //Note: if we want to stop tracking the swapchain texture view,
-                    // this is the place to do it.
-                    log::trace!("Command buffer {:?}", encoder_id);
-                    None
+                    match cmd_buf.check_reusable(reusable, &*texture_guard) {
+                        Ok(()) => {
+                            cmd_buf.encoder.close();
+                            cmd_buf.status = CommandEncoderStatus::Finished;
+                            cmd_buf.reusable = reusable;
+                            //Note: if we want to stop tracking the swapchain texture view,
+                            // this is the place to do it.
+                            log::trace!("Command buffer {:?}", encoder_id);
+                            None
+                        }
+                        Err(error) => {
+                            cmd_buf.encoder.discard();
+                            cmd_buf.status = CommandEncoderStatus::Error;
+                            Some(error)
+                        }
+                    }
                 }
                 CommandEncoderStatus::Finished => Some(CommandEncoderError::NotRecording),
                 CommandEncoderStatus::Error => {
@@ -580,6 +722,10 @@ pub enum PassErrorScope {
     BeginPipelineStatisticsQuery,
     #[error("In a end_pipeline_statistics_query command")]
     EndPipelineStatisticsQuery,
+    #[error("In a begin_occlusion_query command")]
+    BeginOcclusionQuery,
+    #[error("In a end_occlusion_query command")]
+    EndOcclusionQuery,
     #[error("In a execute_bundle command")]
     ExecuteBundle,
     #[error("In a dispatch command, indirect:{indirect}")]
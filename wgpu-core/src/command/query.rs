@@ -3,7 +3,7 @@ use hal::CommandEncoder as _;
 #[cfg(feature = "trace")]
 use crate::device::trace::Command as TraceCommand;
 use crate::{
-    command::{CommandBuffer, CommandEncoderError},
+    command::{BarrierCause, CommandBuffer, CommandEncoderError},
     hub::{Global, GlobalIdentityHandlerFactory, HalApi, Storage, Token},
     id::{self, Id, TypedId},
     init_tracker::MemoryInitKind,
@@ -161,21 +161,13 @@ pub enum ResolveError {
 }
 
 impl<A: HalApi> QuerySet<A> {
-    fn validate_query(
+    pub(super) fn validate_query(
         &self,
         query_set_id: id::QuerySetId,
         query_type: SimplifiedQueryType,
         query_index: u32,
         reset_state: Option<&mut QueryResetMap<A>>,
     ) -> Result<&A::QuerySet, QueryUseError> {
-        // We need to defer our resets because we are in a renderpass, add the usage to the reset map.
-        if let Some(reset) = reset_state {
-            let used = reset.use_query_set(query_set_id, self, query_index);
-            if used {
-                return Err(QueryUseError::UsedTwiceInsideRenderpass { query_index });
-            }
-        }
-
         let simple_set_type = SimplifiedQueryType::from(self.desc.ty);
         if simple_set_type != query_type {
             return Err(QueryUseError::IncompatibleType {
@@ -191,6 +183,14 @@ impl<A: HalApi> QuerySet<A> {
             });
         }
 
+        // We need to defer our resets because we are in a renderpass, add the usage to the reset map.
+        if let Some(reset) = reset_state {
+            let used = reset.use_query_set(query_set_id, self, query_index);
+            if used {
+                return Err(QueryUseError::UsedTwiceInsideRenderpass { query_index });
+            }
+        }
+
         Ok(&self.raw)
     }
 
@@ -228,13 +228,45 @@ impl<A: HalApi> QuerySet<A> {
         reset_state: Option<&mut QueryResetMap<A>>,
         active_query: &mut Option<(id::QuerySetId, u32)>,
     ) -> Result<(), QueryUseError> {
-        let needs_reset = reset_state.is_none();
-        let query_set = self.validate_query(
+        self.validate_and_begin_query(
+            raw_encoder,
             query_set_id,
             SimplifiedQueryType::PipelineStatistics,
             query_index,
             reset_state,
-        )?;
+            active_query,
+        )
+    }
+
+    pub(super) fn validate_and_begin_occlusion_query(
+        &self,
+        raw_encoder: &mut A::CommandEncoder,
+        query_set_id: id::QuerySetId,
+        query_index: u32,
+        reset_state: Option<&mut QueryResetMap<A>>,
+        active_query: &mut Option<(id::QuerySetId, u32)>,
+    ) -> Result<(), QueryUseError> {
+        self.validate_and_begin_query(
+            raw_encoder,
+            query_set_id,
+            SimplifiedQueryType::Occlusion,
+            query_index,
+            reset_state,
+            active_query,
+        )
+    }
+
+    fn validate_and_begin_query(
+        &self,
+        raw_encoder: &mut A::CommandEncoder,
+        query_set_id: id::QuerySetId,
+        query_type: SimplifiedQueryType,
+        query_index: u32,
+        reset_state: Option<&mut QueryResetMap<A>>,
+        active_query: &mut Option<(id::QuerySetId, u32)>,
+    ) -> Result<(), QueryUseError> {
+        let needs_reset = reset_state.is_none();
+        let query_set = self.validate_query(query_set_id, query_type, query_index, reset_state)?;
 
         if let Some((_old_id, old_idx)) = active_query.replace((query_set_id, query_index)) {
             return Err(QueryUseError::AlreadyStarted {
@@ -259,6 +291,22 @@ pub(super) fn end_pipeline_statistics_query<A: HalApi>(
     raw_encoder: &mut A::CommandEncoder,
     storage: &Storage<QuerySet<A>, id::QuerySetId>,
     active_query: &mut Option<(id::QuerySetId, u32)>,
+) -> Result<(), QueryUseError> {
+    end_query(raw_encoder, storage, active_query)
+}
+
+pub(super) fn end_occlusion_query<A: HalApi>(
+    raw_encoder: &mut A::CommandEncoder,
+    storage: &Storage<QuerySet<A>, id::QuerySetId>,
+    active_query: &mut Option<(id::QuerySetId, u32)>,
+) -> Result<(), QueryUseError> {
+    end_query(raw_encoder, storage, active_query)
+}
+
+fn end_query<A: HalApi>(
+    raw_encoder: &mut A::CommandEncoder,
+    storage: &Storage<QuerySet<A>, id::QuerySetId>,
+    active_query: &mut Option<(id::QuerySetId, u32)>,
 ) -> Result<(), QueryUseError> {
     if let Some((query_set_id, query_index)) = active_query.take() {
         // We can unwrap here as the validity was validated when the active query was set
@@ -324,6 +372,9 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         let (buffer_guard, _) = hub.buffers.read(&mut token);
 
         let cmd_buf = CommandBuffer::get_encoder_mut(&mut cmd_buf_guard, command_encoder_id)?;
+        cmd_buf
+            .barriers
+            .set_cause(|| BarrierCause::Command("resolve_query_set"));
         let raw_encoder = cmd_buf.encoder.open();
 
         #[cfg(feature = "trace")]
@@ -352,7 +403,12 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             .buffers
             .set_single(&*buffer_guard, destination, hal::BufferUses::COPY_DST)
             .ok_or(QueryError::InvalidBuffer(destination))?;
-        let dst_barrier = dst_pending.map(|pending| pending.into_hal(dst_buffer));
+        let dst_barrier = dst_pending.map(|pending| {
+            cmd_buf
+                .barriers
+                .buffer(&*buffer_guard, pending)
+                .into_hal(dst_buffer)
+        });
 
         if !dst_buffer.usage.contains(wgt::BufferUsages::COPY_DST) {
             return Err(ResolveError::MissingBufferUsage.into());
@@ -391,6 +447,10 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             .into());
         }
 
+        if let Some(ref contents) = dst_buffer.index_contents {
+            contents.lock().invalidate();
+        }
+
         cmd_buf
             .buffer_memory_init_actions
             .extend(dst_buffer.initialization_status.create_action(
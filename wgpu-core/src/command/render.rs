@@ -3,14 +3,17 @@ use crate::{
     command::{
         self,
         bind::Binder,
-        end_pipeline_statistics_query,
+        end_occlusion_query, end_pipeline_statistics_query,
         memory_init::{fixup_discarded_surfaces, SurfacesInDiscardState},
-        BasePass, BasePassRef, BindGroupStateChange, CommandBuffer, CommandEncoderError,
-        CommandEncoderStatus, DrawError, ExecutionError, MapPassErr, PassErrorScope, QueryResetMap,
-        QueryUseError, RenderCommand, RenderCommandError, StateChange,
+        BarrierCause, BasePass, BasePassRef, BindGroupStateChange, CommandBuffer,
+        CommandEncoderError, CommandEncoderStatus, DrawError, ExecutionError, MapPassErr,
+        PassErrorScope, QueryResetMap, QueryUseError, RenderCommand, RenderCommandError,
+        StateChange,
     },
     device::{
-        AttachmentData, Device, MissingDownlevelFlags, MissingFeatures,
+        index_range::IndexedDraw,
+        indirect_validation::{IndirectArgs, IndirectArguments, IndirectCheck},
+        AttachmentData, Device, DeviceError, MissingDownlevelFlags, MissingFeatures,
         RenderPassCompatibilityError, RenderPassContext,
     },
     error::{ErrorFormatter, PrettyError},
@@ -19,7 +22,7 @@ use crate::{
     init_tracker::{MemoryInitKind, TextureInitRange, TextureInitTrackerAction},
     pipeline::{self, PipelineFlags},
     resource::{self, Buffer, Texture, TextureView},
-    track::{TextureSelector, UsageConflict, UsageScope},
+    track::{TextureSelector, UsageConflict, UsageOrigin, UsageScope, UsageSource},
     validation::{
         check_buffer_usage, check_texture_usage, MissingBufferUsageError, MissingTextureUsageError,
     },
@@ -176,6 +179,8 @@ pub struct RenderPassDescriptor<'a> {
     pub color_attachments: Cow<'a, [Option<RenderPassColorAttachment>]>,
     /// The depth and stencil attachment of the render pass, if any.
     pub depth_stencil_attachment: Option<&'a RenderPassDepthStencilAttachment>,
+    /// The query set written by the occlusion queries of the render pass, if any.
+    pub occlusion_query_set: Option<id::QuerySetId>,
 }
 
 #[cfg_attr(feature = "serial-pass", derive(Deserialize, Serialize))]
@@ -184,6 +189,7 @@ pub struct RenderPass {
     parent_id: id::CommandEncoderId,
     color_targets: ArrayVec<Option<RenderPassColorAttachment>, { hal::MAX_COLOR_ATTACHMENTS }>,
     depth_stencil_target: Option<RenderPassDepthStencilAttachment>,
+    occlusion_query_set: Option<id::QuerySetId>,
 
     // Resource binding dedupe state.
     #[cfg_attr(feature = "serial-pass", serde(skip))]
@@ -199,6 +205,7 @@ impl RenderPass {
             parent_id,
             color_targets: desc.color_attachments.iter().cloned().collect(),
             depth_stencil_target: desc.depth_stencil_attachment.cloned(),
+            occlusion_query_set: desc.occlusion_query_set,
 
             current_bind_groups: BindGroupStateChange::new(),
             current_pipeline: StateChange::new(),
@@ -215,6 +222,7 @@ impl RenderPass {
             base: self.base,
             target_colors: self.color_targets.into_iter().collect(),
             target_depth_stencil: self.depth_stencil_target,
+            occlusion_query_set: self.occlusion_query_set,
         }
     }
 
@@ -238,10 +246,11 @@ impl fmt::Debug for RenderPass {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
             f,
-            "RenderPass {{ encoder_id: {:?}, color_targets: {:?}, depth_stencil_target: {:?}, data: {:?} commands, {:?} dynamic offsets, and {:?} push constant u32s }}",
+            "RenderPass {{ encoder_id: {:?}, color_targets: {:?}, depth_stencil_target: {:?}, occlusion_query_set: {:?}, data: {:?} commands, {:?} dynamic offsets, and {:?} push constant u32s }}",
             self.parent_id,
             self.color_targets,
             self.depth_stencil_target,
+            self.occlusion_query_set,
             self.base.commands.len(),
             self.base.dynamic_offsets.len(),
             self.base.push_constant_data.len(),
@@ -426,11 +435,86 @@ impl State {
         self.index.reset();
         self.vertex.reset();
     }
+
+    /// Checks the vertices an indexed draw reads against the vertex buffers, with
+    /// index range validation.
+    fn validate_indexed_vertices<A: HalApi>(
+        &self,
+        buffer_guard: &Storage<Buffer<A>, id::BufferId>,
+        first_index: u32,
+        index_count: u32,
+        base_vertex: i32,
+    ) -> Result<(), DrawError> {
+        let (buffer_id, offset) = match self.index.bound_buffer_view {
+            Some((buffer_id, ref range)) => (buffer_id, range.start),
+            None => return Ok(()),
+        };
+        let contents = match buffer_guard[buffer_id].index_contents {
+            Some(ref contents) => contents,
+            None => return Ok(()),
+        };
+        IndexedDraw {
+            format: self.index.format.unwrap(),
+            strip: self.index.pipeline_format.is_some(),
+            offset,
+            first_index,
+            index_count,
+            base_vertex,
+            vertex_limit: self.vertex.vertex_limit,
+            vertex_limit_slot: self.vertex.vertex_limit_slot,
+        }
+        .validate(&mut contents.lock())
+    }
+
+    /// Returns the limits indirect draws are checked against in this state.
+    fn indirect_args(&self, indexed: bool) -> IndirectArgs {
+        match indexed {
+            false => IndirectArgs::Draw {
+                vertex_limit: self.vertex.vertex_limit,
+                instance_limit: self.vertex.instance_limit,
+            },
+            true => IndirectArgs::DrawIndexed {
+                index_limit: self.index.limit,
+                instance_limit: self.vertex.instance_limit,
+            },
+        }
+    }
+}
+
+/// Indirect draws of a pass, when their arguments are validated.
+pub(super) struct IndirectDraws<'a, A: HalApi> {
+    pub(super) arguments: &'a IndirectArguments<A>,
+    checks: Vec<(id::Valid<id::BufferId>, IndirectCheck)>,
+    size: BufferAddress,
+}
+
+impl<A: HalApi> IndirectDraws<'_, A> {
+    /// Records the check of `count` draws at `offset` in a buffer, and returns the
+    /// offset of their checked arguments.
+    pub(super) fn check(
+        &mut self,
+        buffer_id: id::BufferId,
+        offset: BufferAddress,
+        count: u32,
+        args: IndirectArgs,
+    ) -> BufferAddress {
+        let check = IndirectCheck {
+            src_offset: offset,
+            dst_offset: self.size,
+            count,
+            args,
+        };
+        self.checks.push((id::Valid(buffer_id), check));
+        self.size += args.size() * count as BufferAddress;
+        check.dst_offset
+    }
 }
 
 /// Error encountered when performing a render pass.
 #[derive(Clone, Debug, Error)]
 pub enum RenderPassErrorInner {
+    #[error(transparent)]
+    Device(#[from] DeviceError),
     #[error(transparent)]
     Encoder(#[from] CommandEncoderError),
     #[error("attachment texture view {0:?} is invalid")]
@@ -521,13 +605,23 @@ pub enum RenderPassErrorInner {
         "multiview pass texture views with more than one array layer must have D2Array dimension"
     )]
     MultiViewDimensionMismatch,
+    #[error("occlusion queries require the render pass to have an occlusion query set")]
+    MissingOcclusionQuerySet,
+    #[error("occlusion query {query_index} is still active at the end of the render pass")]
+    UnterminatedOcclusionQuery { query_index: u32 },
 }
 
 impl PrettyError for RenderPassErrorInner {
     fn fmt_pretty(&self, fmt: &mut ErrorFormatter) {
         fmt.error(self);
-        if let Self::InvalidAttachment(id) = *self {
-            fmt.texture_view_label_with_key(&id, "attachment");
+        match *self {
+            Self::InvalidAttachment(id) => {
+                fmt.texture_view_label_with_key(&id, "attachment");
+            }
+            Self::ResourceUsageConflict(ref conflict) => {
+                conflict.fmt_labels(fmt);
+            }
+            _ => {}
         };
     }
 }
@@ -577,14 +671,20 @@ struct RenderAttachment<'a> {
     texture_id: &'a Stored<id::TextureId>,
     selector: &'a TextureSelector,
     usage: hal::TextureUses,
+    source: UsageSource,
 }
 
 impl<A: hal::Api> TextureView<A> {
-    fn to_render_attachment(&self, usage: hal::TextureUses) -> RenderAttachment {
+    fn to_render_attachment(
+        &self,
+        usage: hal::TextureUses,
+        source: UsageSource,
+    ) -> RenderAttachment {
         RenderAttachment {
             texture_id: &self.parent_id,
             selector: &self.selector,
             usage,
+            source,
         }
     }
 }
@@ -614,6 +714,9 @@ impl<'a, A: HalApi> RenderPassInfo<'a, A> {
         texture_guard: &Storage<Texture<A>, id::TextureId>,
         pending_discard_init_fixups: &mut SurfacesInDiscardState,
     ) {
+        if !Self::is_zero_initialized(view, texture_guard) {
+            return;
+        }
         if channel.load_op == LoadOp::Load {
             pending_discard_init_fixups.extend(texture_memory_actions.register_init_action(
                 &TextureInitTrackerAction {
@@ -643,11 +746,23 @@ impl<'a, A: HalApi> RenderPassInfo<'a, A> {
         }
     }
 
+    /// Whether the memory of the texture of `view` is zero-initialized, so passes
+    /// need to track its initialization.
+    fn is_zero_initialized(
+        view: &TextureView<A>,
+        texture_guard: &Storage<Texture<A>, id::TextureId>,
+    ) -> bool {
+        texture_guard
+            .get(view.parent_id.value.0)
+            .map_or(true, |texture| texture.zero_initialized)
+    }
+
     fn start(
         device: &Device<A>,
         label: Option<&str>,
         color_attachments: &[Option<RenderPassColorAttachment>],
         depth_stencil_attachment: Option<&RenderPassDepthStencilAttachment>,
+        occlusion_query_set: Option<&A::QuerySet>,
         cmd_buf: &mut CommandBuffer<A>,
         view_guard: &'a Storage<TextureView<A>, id::TextureViewId>,
         buffer_guard: &'a Storage<Buffer<A>, id::BufferId>,
@@ -749,7 +864,9 @@ impl<'a, A: HalApi> RenderPassInfo<'a, A> {
                 ));
             }
 
+            // Textures not zero-initialized don't need their aspects kept in sync.
             if !ds_aspects.contains(hal::FormatAspects::STENCIL)
+                || !Self::is_zero_initialized(view, texture_guard)
                 || (at.stencil.load_op == at.depth.load_op
                     && at.stencil.store_op == at.depth.store_op)
             {
@@ -830,7 +947,8 @@ impl<'a, A: HalApi> RenderPassInfo<'a, A> {
             } else {
                 hal::TextureUses::DEPTH_STENCIL_WRITE
             };
-            render_attachments.push(view.to_render_attachment(usage));
+            render_attachments
+                .push(view.to_render_attachment(usage, UsageSource::DepthStencilAttachment));
 
             depth_stencil = Some(hal::DepthStencilAttachment {
                 target: hal::Attachment {
@@ -875,8 +993,11 @@ impl<'a, A: HalApi> RenderPassInfo<'a, A> {
                 texture_guard,
                 &mut pending_discard_init_fixups,
             );
-            render_attachments
-                .push(color_view.to_render_attachment(hal::TextureUses::COLOR_TARGET));
+            let slot = colors.len() as u32;
+            render_attachments.push(color_view.to_render_attachment(
+                hal::TextureUses::COLOR_TARGET,
+                UsageSource::ColorAttachment { slot },
+            ));
 
             let mut hal_resolve_target = None;
             if let Some(resolve_target) = at.resolve_target {
@@ -920,8 +1041,10 @@ impl<'a, A: HalApi> RenderPassInfo<'a, A> {
                     TextureInitRange::from(resolve_view.selector.clone()),
                     texture_guard,
                 );
-                render_attachments
-                    .push(resolve_view.to_render_attachment(hal::TextureUses::COLOR_TARGET));
+                render_attachments.push(resolve_view.to_render_attachment(
+                    hal::TextureUses::COLOR_TARGET,
+                    UsageSource::ResolveTarget { slot },
+                ));
 
                 hal_resolve_target = Some(hal::Attachment {
                     view: &resolve_view.raw,
@@ -974,6 +1097,7 @@ impl<'a, A: HalApi> RenderPassInfo<'a, A> {
             color_attachments: &colors,
             depth_stencil_attachment: depth_stencil,
             multiview,
+            occlusion_query_set,
         };
         unsafe {
             cmd_buf.encoder.raw.begin_render_pass(&hal_desc);
@@ -1020,6 +1144,7 @@ impl<'a, A: HalApi> RenderPassInfo<'a, A> {
                         Some(ra.selector.clone()),
                         &ra.texture_id.ref_count,
                         ra.usage,
+                        Some(UsageOrigin::new(ra.source, None)),
                     )
                     .map_err(UsageConflict::from)?
             };
@@ -1056,6 +1181,7 @@ impl<'a, A: HalApi> RenderPassInfo<'a, A> {
                     clear_value: (0.0, 0),
                 }),
                 multiview: self.multiview,
+                occlusion_query_set: None,
             };
             unsafe {
                 raw.begin_render_pass(&desc);
@@ -1080,6 +1206,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             pass.base.as_ref(),
             &pass.color_targets,
             pass.depth_stencil_target.as_ref(),
+            pass.occlusion_query_set,
         )
     }
 
@@ -1090,6 +1217,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         base: BasePassRef<RenderCommand>,
         color_attachments: &[Option<RenderPassColorAttachment>],
         depth_stencil_attachment: Option<&RenderPassDepthStencilAttachment>,
+        occlusion_query_set_id: Option<id::QuerySetId>,
     ) -> Result<(), RenderPassError> {
         profiling::scope!("CommandEncoder::run_render_pass");
         let init_scope = PassErrorScope::Pass(encoder_id);
@@ -1098,7 +1226,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         let mut token = Token::root();
         let (device_guard, mut token) = hub.devices.read(&mut token);
 
-        let (scope, query_reset_state, pending_discard_init_fixups) = {
+        let (scope, query_reset_state, pending_discard_init_fixups, indirect_checks) = {
             let (mut cmb_guard, mut token) = hub.command_buffers.write(&mut token);
 
             // Spell out the type, to placate rust-analyzer.
@@ -1117,6 +1245,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                     base: BasePass::from_ref(base),
                     target_colors: color_attachments.to_vec(),
                     target_depth_stencil: depth_stencil_attachment.cloned(),
+                    occlusion_query_set: occlusion_query_set_id,
                 });
             }
 
@@ -1137,17 +1266,100 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                 encoder_id
             );
 
-            let mut info = RenderPassInfo::start(
-                device,
-                base.label,
-                color_attachments,
-                depth_stencil_attachment,
-                cmd_buf,
-                &*view_guard,
-                &*buffer_guard,
-                &*texture_guard,
-            )
-            .map_pass_err(init_scope)?;
+            // With emulated push constants, all the states they go through in the
+            // pass, bundles included, are uploaded before it begins.
+            let mut emulated_push_constants = false;
+            let mut pass_push_constants = None;
+            if let Some(ref emulation) = device.push_constant_emulation {
+                let mut states = emulation.states();
+                let mut layout_id = None;
+                for command in base.commands {
+                    match *command {
+                        RenderCommand::SetPipeline(pipeline_id) => {
+                            let pipeline = match render_pipeline_guard.get(pipeline_id) {
+                                Ok(pipeline) => pipeline,
+                                Err(_) => continue,
+                            };
+                            if layout_id != Some(pipeline.layout_id.value) {
+                                layout_id = Some(pipeline.layout_id.value);
+                                states.reset();
+                                emulated_push_constants |= !pipeline_layout_guard
+                                    [pipeline.layout_id.value]
+                                    .push_constant_ranges
+                                    .is_empty();
+                            }
+                        }
+                        RenderCommand::SetPushConstant {
+                            offset,
+                            size_bytes,
+                            values_offset: Some(values_offset),
+                            ..
+                        } => {
+                            let values_end_offset = (values_offset
+                                + size_bytes / wgt::PUSH_CONSTANT_ALIGNMENT)
+                                as usize;
+                            states.update(
+                                offset,
+                                size_bytes,
+                                Some(
+                                    &base.push_constant_data
+                                        [values_offset as usize..values_end_offset],
+                                ),
+                            );
+                        }
+                        RenderCommand::ExecuteBundle(bundle_id) => {
+                            if let Ok(bundle) = bundle_guard.get(bundle_id) {
+                                emulated_push_constants |=
+                                    bundle.record_push_constant_states(&mut states);
+                            }
+                            // The pipeline state is reset after a bundle.
+                            layout_id = None;
+                        }
+                        _ => {}
+                    }
+                }
+                if emulated_push_constants {
+                    pass_push_constants = Some(
+                        unsafe {
+                            emulation.upload(
+                                device,
+                                &mut cmd_buf.encoder.raw,
+                                &mut cmd_buf.emulated_push_constants,
+                                &states,
+                            )
+                        }
+                        .map_pass_err(init_scope)?,
+                    );
+                }
+            }
+
+            // With indirect validation, draws read their checked arguments from
+            // a buffer of the pass, filled before it begins.
+            let mut validate_indirect = false;
+            if let Some(ref validation) = device.indirect_validation {
+                let size = base
+                    .commands
+                    .iter()
+                    .map(|command| match *command {
+                        RenderCommand::MultiDrawIndirect { count, indexed, .. } => {
+                            IndirectArgs::draw_size(indexed) * count.map_or(1, |c| c.get()) as u64
+                        }
+                        RenderCommand::MultiDrawIndirectCount {
+                            max_count, indexed, ..
+                        } => IndirectArgs::draw_size(indexed) * max_count as u64,
+                        RenderCommand::ExecuteBundle(bundle_id) => bundle_guard
+                            .get(bundle_id)
+                            .map_or(0, |bundle| bundle.indirect_size),
+                        _ => 0,
+                    })
+                    .sum();
+                if size != 0 {
+                    let arguments = unsafe { validation.create_arguments(&device.raw, size) }
+                        .map_pass_err(init_scope)?;
+                    cmd_buf.indirect_arguments.push(arguments);
+                    validate_indirect = true;
+                }
+            }
 
             cmd_buf.trackers.set_size(
                 Some(&*buffer_guard),
@@ -1161,7 +1373,43 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                 Some(&*query_set_guard),
             );
 
+            let occlusion_query_set = match occlusion_query_set_id {
+                Some(query_set_id) => Some(
+                    cmd_buf
+                        .trackers
+                        .query_sets
+                        .add_single(&*query_set_guard, query_set_id)
+                        .ok_or(RenderCommandError::InvalidQuerySet(query_set_id))
+                        .map_pass_err(init_scope)?,
+                ),
+                None => None,
+            };
+
+            let mut info = RenderPassInfo::start(
+                device,
+                base.label,
+                color_attachments,
+                depth_stencil_attachment,
+                occlusion_query_set.map(|query_set| &query_set.raw),
+                cmd_buf,
+                &*view_guard,
+                &*buffer_guard,
+                &*texture_guard,
+            )
+            .map_pass_err(init_scope)?;
+
             let raw = &mut cmd_buf.encoder.raw;
+            let mut push_constant_cursor =
+                pass_push_constants.map(|pass| cmd_buf.emulated_push_constants.cursor(pass));
+            let mut indirect_draws = cmd_buf
+                .indirect_arguments
+                .last()
+                .filter(|_| validate_indirect)
+                .map(|arguments| IndirectDraws {
+                    arguments,
+                    checks: Vec::new(),
+                    size: 0,
+                });
 
             let mut state = State {
                 pipeline_flags: PipelineFlags::empty(),
@@ -1177,9 +1425,10 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             let mut dynamic_offset_count = 0;
             let mut string_offset = 0;
             let mut active_query = None;
+            let mut active_occlusion_query = None;
             let mut query_reset_state = QueryResetMap::new();
 
-            for command in base.commands {
+            for (command_index, command) in base.commands.iter().enumerate() {
                 match *command {
                     RenderCommand::SetBindGroup {
                         index,
@@ -1215,8 +1464,16 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
 
                         // merge the resource tracker in
                         unsafe {
+                            let source = UsageSource::BindGroup {
+                                group: index as u32,
+                                bind_group: bind_group_id,
+                            };
                             info.usage_scope
-                                .merge_bind_group(&*texture_guard, &bind_group.used)
+                                .merge_bind_group(
+                                    &*texture_guard,
+                                    &bind_group.used,
+                                    UsageOrigin::new(source, Some(command_index)),
+                                )
                                 .map_pass_err(scope)?;
                         }
                         //Note: stateless trackers are not merged: the lifetime reference
@@ -1330,24 +1587,30 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                             }
 
                             // Clear push constant ranges
-                            let non_overlapping = super::bind::compute_nonoverlapping_ranges(
-                                &pipeline_layout.push_constant_ranges,
-                            );
-                            for range in non_overlapping {
-                                let offset = range.range.start;
-                                let size_bytes = range.range.end - offset;
-                                super::push_constant_clear(
-                                    offset,
-                                    size_bytes,
-                                    |clear_offset, clear_data| unsafe {
-                                        raw.set_push_constants(
-                                            &pipeline_layout.raw,
-                                            range.stages,
-                                            clear_offset,
-                                            clear_data,
-                                        );
-                                    },
+                            if let Some(ref mut cursor) = push_constant_cursor {
+                                if !pipeline_layout.push_constant_ranges.is_empty() {
+                                    unsafe { cursor.reset(raw, &pipeline_layout.raw) };
+                                }
+                            } else {
+                                let non_overlapping = super::bind::compute_nonoverlapping_ranges(
+                                    &pipeline_layout.push_constant_ranges,
                                 );
+                                for range in non_overlapping {
+                                    let offset = range.range.start;
+                                    let size_bytes = range.range.end - offset;
+                                    super::push_constant_clear(
+                                        offset,
+                                        size_bytes,
+                                        |clear_offset, clear_data| unsafe {
+                                            raw.set_push_constants(
+                                                &pipeline_layout.raw,
+                                                range.stages,
+                                                clear_offset,
+                                                clear_data,
+                                            );
+                                        },
+                                    );
+                                }
                             }
                         }
 
@@ -1384,7 +1647,12 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                         let buffer: &Buffer<A> = info
                             .usage_scope
                             .buffers
-                            .merge_single(&*buffer_guard, buffer_id, hal::BufferUses::INDEX)
+                            .merge_single(
+                                &*buffer_guard,
+                                buffer_id,
+                                hal::BufferUses::INDEX,
+                                UsageOrigin::new(UsageSource::IndexBuffer, Some(command_index)),
+                            )
                             .map_pass_err(scope)?;
                         check_buffer_usage(buffer.usage, BufferUsages::INDEX)
                             .map_pass_err(scope)?;
@@ -1430,7 +1698,15 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                         let buffer: &Buffer<A> = info
                             .usage_scope
                             .buffers
-                            .merge_single(&*buffer_guard, buffer_id, hal::BufferUses::VERTEX)
+                            .merge_single(
+                                &*buffer_guard,
+                                buffer_id,
+                                hal::BufferUses::VERTEX,
+                                UsageOrigin::new(
+                                    UsageSource::VertexBuffer { slot },
+                                    Some(command_index),
+                                ),
+                            )
                             .map_pass_err(scope)?;
                         check_buffer_usage(buffer.usage, BufferUsages::VERTEX)
                             .map_pass_err(scope)?;
@@ -1552,8 +1828,18 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                             .map_err(RenderCommandError::from)
                             .map_pass_err(scope)?;
 
-                        unsafe {
-                            raw.set_push_constants(&pipeline_layout.raw, stages, offset, data_slice)
+                        match push_constant_cursor {
+                            Some(ref mut cursor) => unsafe {
+                                cursor.advance(raw, &pipeline_layout.raw)
+                            },
+                            None => unsafe {
+                                raw.set_push_constants(
+                                    &pipeline_layout.raw,
+                                    stages,
+                                    offset,
+                                    data_slice,
+                                )
+                            },
                         }
                     }
                     RenderCommand::SetScissor(ref rect) => {
@@ -1630,7 +1916,6 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                         };
                         state.is_ready(indexed).map_pass_err(scope)?;
 
-                        //TODO: validate that base_vertex + max_index() is within the provided range
                         let last_index = first_index + index_count;
                         let index_limit = state.index.limit;
                         if last_index > index_limit {
@@ -1640,6 +1925,14 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                             })
                             .map_pass_err(scope);
                         }
+                        state
+                            .validate_indexed_vertices(
+                                &*buffer_guard,
+                                first_index,
+                                index_count,
+                                base_vertex,
+                            )
+                            .map_pass_err(scope)?;
                         let last_instance = first_instance + instance_count;
                         let instance_limit = state.vertex.instance_limit;
                         if last_instance > instance_limit {
@@ -1691,7 +1984,12 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                         let indirect_buffer: &Buffer<A> = info
                             .usage_scope
                             .buffers
-                            .merge_single(&*buffer_guard, buffer_id, hal::BufferUses::INDIRECT)
+                            .merge_single(
+                                &*buffer_guard,
+                                buffer_id,
+                                hal::BufferUses::INDIRECT,
+                                UsageOrigin::new(UsageSource::IndirectBuffer, Some(command_index)),
+                            )
                             .map_pass_err(scope)?;
                         check_buffer_usage(indirect_buffer.usage, BufferUsages::INDIRECT)
                             .map_pass_err(scope)?;
@@ -1722,6 +2020,19 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                             ),
                         );
 
+                        let (indirect_raw, offset) = match indirect_draws {
+                            Some(ref mut draws) => (
+                                draws.arguments.buffer(),
+                                draws.check(
+                                    buffer_id,
+                                    offset,
+                                    actual_count,
+                                    state.indirect_args(indexed),
+                                ),
+                            ),
+                            None => (indirect_raw, offset),
+                        };
+
                         match indexed {
                             false => unsafe {
                                 raw.draw_indirect(indirect_raw, offset, actual_count);
@@ -1761,7 +2072,12 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                         let indirect_buffer: &Buffer<A> = info
                             .usage_scope
                             .buffers
-                            .merge_single(&*buffer_guard, buffer_id, hal::BufferUses::INDIRECT)
+                            .merge_single(
+                                &*buffer_guard,
+                                buffer_id,
+                                hal::BufferUses::INDIRECT,
+                                UsageOrigin::new(UsageSource::IndirectBuffer, Some(command_index)),
+                            )
                             .map_pass_err(scope)?;
                         check_buffer_usage(indirect_buffer.usage, BufferUsages::INDIRECT)
                             .map_pass_err(scope)?;
@@ -1778,6 +2094,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                                 &*buffer_guard,
                                 count_buffer_id,
                                 hal::BufferUses::INDIRECT,
+                                UsageOrigin::new(UsageSource::IndirectBuffer, Some(command_index)),
                             )
                             .map_pass_err(scope)?;
                         check_buffer_usage(count_buffer.usage, BufferUsages::INDIRECT)
@@ -1824,6 +2141,19 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                             ),
                         );
 
+                        let (indirect_raw, offset) = match indirect_draws {
+                            Some(ref mut draws) if max_count != 0 => (
+                                draws.arguments.buffer(),
+                                draws.check(
+                                    buffer_id,
+                                    offset,
+                                    max_count,
+                                    state.indirect_args(indexed),
+                                ),
+                            ),
+                            _ => (indirect_raw, offset),
+                        };
+
                         match indexed {
                             false => unsafe {
                                 raw.draw_indirect_count(
@@ -1930,6 +2260,30 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                         end_pipeline_statistics_query(raw, &*query_set_guard, &mut active_query)
                             .map_pass_err(scope)?;
                     }
+                    RenderCommand::BeginOcclusionQuery { query_index } => {
+                        let scope = PassErrorScope::BeginOcclusionQuery;
+
+                        let (query_set_id, query_set) = occlusion_query_set_id
+                            .zip(occlusion_query_set)
+                            .ok_or(RenderPassErrorInner::MissingOcclusionQuerySet)
+                            .map_pass_err(scope)?;
+
+                        query_set
+                            .validate_and_begin_occlusion_query(
+                                raw,
+                                query_set_id,
+                                query_index,
+                                Some(&mut query_reset_state),
+                                &mut active_occlusion_query,
+                            )
+                            .map_pass_err(scope)?;
+                    }
+                    RenderCommand::EndOcclusionQuery => {
+                        let scope = PassErrorScope::EndOcclusionQuery;
+
+                        end_occlusion_query(raw, &*query_set_guard, &mut active_occlusion_query)
+                            .map_pass_err(scope)?;
+                    }
                     RenderCommand::ExecuteBundle(bundle_id) => {
                         let scope = PassErrorScope::ExecuteBundle;
                         let bundle: &command::RenderBundle<A> = cmd_buf
@@ -1979,22 +2333,37 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                                 &*pipeline_layout_guard,
                                 &*bind_group_guard,
                                 &*render_pipeline_guard,
+                                &*query_set_guard,
                                 &*buffer_guard,
+                                &mut query_reset_state,
+                                &mut active_query,
+                                push_constant_cursor.as_mut(),
+                                indirect_draws.as_mut(),
                             )
                         }
                         .map_err(|e| match e {
                             ExecutionError::DestroyedBuffer(id) => {
-                                RenderCommandError::DestroyedBuffer(id)
+                                RenderCommandError::DestroyedBuffer(id).into()
                             }
                             ExecutionError::Unimplemented(what) => {
-                                RenderCommandError::Unimplemented(what)
+                                RenderCommandError::Unimplemented(what).into()
+                            }
+                            ExecutionError::QueryUse(error) => {
+                                RenderPassErrorInner::QueryUse(error)
                             }
                         })
                         .map_pass_err(scope)?;
 
                         unsafe {
                             info.usage_scope
-                                .merge_render_bundle(&*texture_guard, &bundle.used)
+                                .merge_render_bundle(
+                                    &*texture_guard,
+                                    &bundle.used,
+                                    UsageOrigin::new(
+                                        UsageSource::RenderBundle(bundle_id),
+                                        Some(command_index),
+                                    ),
+                                )
                                 .map_pass_err(scope)?;
                             cmd_buf
                                 .trackers
@@ -2006,12 +2375,24 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                 }
             }
 
+            if let Some((_, query_index)) = active_occlusion_query {
+                return Err(RenderPassErrorInner::UnterminatedOcclusionQuery { query_index })
+                    .map_pass_err(init_scope);
+            }
+
             log::trace!("Merging renderpass into cmd_buf {:?}", encoder_id);
             let (trackers, pending_discard_init_fixups) =
                 info.finish(raw, &*texture_guard).map_pass_err(init_scope)?;
 
+            let indirect_checks = indirect_draws.map_or_else(Vec::new, |draws| draws.checks);
+
             cmd_buf.encoder.close();
-            (trackers, query_reset_state, pending_discard_init_fixups)
+            (
+                trackers,
+                query_reset_state,
+                pending_discard_init_fixups,
+                indirect_checks,
+            )
         };
 
         let (mut cmb_guard, mut token) = hub.command_buffers.write(&mut token);
@@ -2023,11 +2404,15 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         {
             let transit = cmd_buf.encoder.open();
 
+            cmd_buf.barriers.set_cause(|| BarrierCause::RenderPass {
+                label: base.label.map(str::to_string),
+            });
             fixup_discarded_surfaces(
                 pending_discard_init_fixups.into_iter(),
                 transit,
                 &texture_guard,
                 &mut cmd_buf.trackers.textures,
+                &mut cmd_buf.barriers,
                 &device_guard[cmd_buf.device_id.value],
             );
 
@@ -2044,9 +2429,44 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                 transit,
                 &mut cmd_buf.trackers,
                 &scope,
+                &mut cmd_buf.barriers,
                 &*buffer_guard,
                 &*texture_guard,
             );
+
+            if !indirect_checks.is_empty() {
+                let device = &device_guard[cmd_buf.device_id.value];
+                let validation = device.indirect_validation.as_ref().unwrap();
+                let arguments = cmd_buf.indirect_arguments.last_mut().unwrap();
+                let mut checks = Vec::with_capacity(indirect_checks.len());
+                for &(buffer_id, check) in indirect_checks.iter() {
+                    let buffer = buffer_guard[buffer_id]
+                        .raw
+                        .as_ref()
+                        .ok_or(RenderCommandError::DestroyedBuffer(buffer_id.0))
+                        .map_pass_err(init_scope)?;
+                    checks.push((buffer, check));
+                }
+                unsafe {
+                    validation
+                        .prepare(&device.raw, transit, arguments, &checks)
+                        .map_pass_err(init_scope)?;
+                    transit.begin_compute_pass(&hal::ComputePassDescriptor {
+                        label: Some("(wgpu internal) indirect validation"),
+                    });
+                    for (index, (&(buffer_id, _), &(buffer, _))) in
+                        indirect_checks.iter().zip(checks.iter()).enumerate()
+                    {
+                        let state = cmd_buf
+                            .trackers
+                            .buffers
+                            .current_state(buffer_id)
+                            .unwrap_or(hal::BufferUses::INDIRECT);
+                        validation.validate(transit, arguments, index, buffer, state);
+                    }
+                    transit.end_compute_pass();
+                }
+            }
         }
 
         // Before we finish the auxiliary encoder, let's
@@ -2439,6 +2859,21 @@ pub mod render_ffi {
             .push(RenderCommand::EndPipelineStatisticsQuery);
     }
 
+    #[no_mangle]
+    pub extern "C" fn wgpu_render_pass_begin_occlusion_query(
+        pass: &mut RenderPass,
+        query_index: u32,
+    ) {
+        pass.base
+            .commands
+            .push(RenderCommand::BeginOcclusionQuery { query_index });
+    }
+
+    #[no_mangle]
+    pub extern "C" fn wgpu_render_pass_end_occlusion_query(pass: &mut RenderPass) {
+        pass.base.commands.push(RenderCommand::EndOcclusionQuery);
+    }
+
     /// # Safety
     ///
     /// This function is unsafe as there is no guarantee that the given pointer is
@@ -1,7 +1,7 @@
 #[cfg(feature = "trace")]
 use crate::device::trace::Command as TraceCommand;
 use crate::{
-    command::{clear_texture, CommandBuffer, CommandEncoderError},
+    command::{clear_texture, BarrierCause, CommandBuffer, CommandEncoderError},
     conv,
     device::{Device, MissingDownlevelFlags},
     error::{ErrorFormatter, PrettyError},
@@ -296,7 +296,7 @@ pub(crate) fn validate_linear_texture_data(
 /// Returns the HAL copy extent and the layer count.
 pub(crate) fn validate_texture_copy_range(
     texture_copy_view: &ImageCopyTexture,
-    desc: &wgt::TextureDescriptor<()>,
+    desc: &wgt::TextureDescriptor<(), Vec<wgt::TextureFormat>>,
     texture_side: CopySide,
     copy_size: &Extent3d,
 ) -> Result<(hal::CopyExtent, u32), TransferError> {
@@ -421,6 +421,7 @@ fn handle_texture_init<A: HalApi>(
                 },
                 cmd_buf_raw,
                 &mut cmd_buf.trackers.textures,
+                &mut cmd_buf.barriers,
                 &device.alignments,
                 &device.zero_buffer,
             )
@@ -507,6 +508,9 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
 
         let (mut cmd_buf_guard, mut token) = hub.command_buffers.write(&mut token);
         let cmd_buf = CommandBuffer::get_encoder_mut(&mut *cmd_buf_guard, command_encoder_id)?;
+        cmd_buf
+            .barriers
+            .set_cause(|| BarrierCause::Command("copy_buffer_to_buffer"));
         let (buffer_guard, _) = hub.buffers.read(&mut token);
 
         #[cfg(feature = "trace")]
@@ -533,7 +537,12 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             return Err(TransferError::MissingCopySrcUsageFlag.into());
         }
         // expecting only a single barrier
-        let src_barrier = src_pending.map(|pending| pending.into_hal(src_buffer));
+        let src_barrier = src_pending.map(|pending| {
+            cmd_buf
+                .barriers
+                .buffer(&*buffer_guard, pending)
+                .into_hal(src_buffer)
+        });
 
         let (dst_buffer, dst_pending) = cmd_buf
             .trackers
@@ -547,7 +556,12 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         if !dst_buffer.usage.contains(BufferUsages::COPY_DST) {
             return Err(TransferError::MissingCopyDstUsageFlag(Some(destination), None).into());
         }
-        let dst_barrier = dst_pending.map(|pending| pending.into_hal(dst_buffer));
+        let dst_barrier = dst_pending.map(|pending| {
+            cmd_buf
+                .barriers
+                .buffer(&*buffer_guard, pending)
+                .into_hal(dst_buffer)
+        });
 
         if size % wgt::COPY_BUFFER_ALIGNMENT != 0 {
             return Err(TransferError::UnalignedCopySize(size).into());
@@ -585,6 +599,10 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             return Ok(());
         }
 
+        if let Some(ref contents) = dst_buffer.index_contents {
+            contents.lock().invalidate();
+        }
+
         // Make sure source is initialized memory and mark dest as initialized.
         cmd_buf
             .buffer_memory_init_actions
@@ -629,6 +647,9 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         let (device_guard, mut token) = hub.devices.read(&mut token);
         let (mut cmd_buf_guard, mut token) = hub.command_buffers.write(&mut token);
         let cmd_buf = CommandBuffer::get_encoder_mut(&mut *cmd_buf_guard, command_encoder_id)?;
+        cmd_buf
+            .barriers
+            .set_cause(|| BarrierCause::Command("copy_buffer_to_texture"));
         let (buffer_guard, mut token) = hub.buffers.read(&mut token);
         let (texture_guard, _) = hub.textures.read(&mut token);
 
@@ -666,7 +687,12 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         if !src_buffer.usage.contains(BufferUsages::COPY_SRC) {
             return Err(TransferError::MissingCopySrcUsageFlag.into());
         }
-        let src_barrier = src_pending.map(|pending| pending.into_hal(src_buffer));
+        let src_barrier = src_pending.map(|pending| {
+            cmd_buf
+                .barriers
+                .buffer(&*buffer_guard, pending)
+                .into_hal(src_buffer)
+        });
 
         let (dst_texture, dst_pending) = cmd_buf
             .trackers
@@ -687,7 +713,12 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                 TransferError::MissingCopyDstUsageFlag(None, Some(destination.texture)).into(),
             );
         }
-        let dst_barrier = dst_pending.map(|pending| pending.into_hal(dst_texture));
+        let dst_barrier = dst_pending.map(|pending| {
+            cmd_buf
+                .barriers
+                .texture(&*texture_guard, pending)
+                .into_hal(dst_texture)
+        });
 
         let format_desc = dst_texture.desc.format.describe();
         let (hal_copy_size, array_layer_count) = validate_texture_copy_range(
@@ -756,6 +787,9 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         let (device_guard, mut token) = hub.devices.read(&mut token);
         let (mut cmd_buf_guard, mut token) = hub.command_buffers.write(&mut token);
         let cmd_buf = CommandBuffer::get_encoder_mut(&mut *cmd_buf_guard, command_encoder_id)?;
+        cmd_buf
+            .barriers
+            .set_cause(|| BarrierCause::Command("copy_texture_to_buffer"));
         let (buffer_guard, mut token) = hub.buffers.read(&mut token);
         let (texture_guard, _) = hub.textures.read(&mut token);
 
@@ -811,8 +845,6 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             }
             .into());
         }
-        let src_barrier = src_pending.map(|pending| pending.into_hal(src_texture));
-
         let (dst_buffer, dst_pending) = cmd_buf
             .trackers
             .buffers
@@ -831,7 +863,18 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                 TransferError::MissingCopyDstUsageFlag(Some(destination.buffer), None).into(),
             );
         }
-        let dst_barrier = dst_pending.map(|pending| pending.into_hal(dst_buffer));
+        let dst_barrier = dst_pending.map(|pending| {
+            cmd_buf
+                .barriers
+                .buffer(&*buffer_guard, pending)
+                .into_hal(dst_buffer)
+        });
+        let src_barrier = src_pending.map(|pending| {
+            cmd_buf
+                .barriers
+                .texture(&*texture_guard, pending)
+                .into_hal(src_texture)
+        });
 
         let format_desc = src_texture.desc.format.describe();
         let (hal_copy_size, array_layer_count) =
@@ -864,6 +907,10 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             .into());
         }
 
+        if let Some(ref contents) = dst_buffer.index_contents {
+            contents.lock().invalidate();
+        }
+
         cmd_buf
             .buffer_memory_init_actions
             .extend(dst_buffer.initialization_status.create_action(
@@ -913,6 +960,9 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         let (device_guard, mut token) = hub.devices.read(&mut token);
         let (mut cmd_buf_guard, mut token) = hub.command_buffers.write(&mut token);
         let cmd_buf = CommandBuffer::get_encoder_mut(&mut *cmd_buf_guard, command_encoder_id)?;
+        cmd_buf
+            .barriers
+            .set_cause(|| BarrierCause::Command("copy_texture_to_texture"));
         let (_, mut token) = hub.buffers.read(&mut token); // skip token
         let (texture_guard, _) = hub.textures.read(&mut token);
 
@@ -965,7 +1015,12 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         //TODO: try to avoid this the collection. It's needed because both
         // `src_pending` and `dst_pending` try to hold `trackers.textures` mutably.
         let mut barriers: ArrayVec<_, 2> = src_pending
-            .map(|pending| pending.into_hal(src_texture))
+            .map(|pending| {
+                cmd_buf
+                    .barriers
+                    .texture(&*texture_guard, pending)
+                    .into_hal(src_texture)
+            })
             .into_iter()
             .collect();
 
@@ -1001,7 +1056,12 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             .into());
         }
 
-        barriers.extend(dst_pending.map(|pending| pending.into_hal(dst_texture)));
+        barriers.extend(dst_pending.map(|pending| {
+            cmd_buf
+                .barriers
+                .texture(&*texture_guard, pending)
+                .into_hal(dst_texture)
+        }));
 
         let (src_copy_size, array_layer_count) =
             validate_texture_copy_range(source, &src_texture.desc, CopySide::Source, copy_size)?;
@@ -0,0 +1,716 @@
+//! Checks that only depend on the features, limits and downlevel capabilities
+//! of a device.
+//!
+//! They are shared by [`Device`](super::Device) and by the device-less
+//! validation of [`crate::offline`].
+
+use crate::{
+    binding_model,
+    device::{MissingDownlevelFlags, MissingFeatures, MAX_BINDING_INDEX},
+    pipeline, validation, FastHashMap,
+};
+
+use arrayvec::ArrayVec;
+
+use std::collections::HashMap;
+
+/// Features, limits and downlevel capabilities to check descriptors against.
+pub(crate) struct Capabilities<'a> {
+    pub features: wgt::Features,
+    pub limits: &'a wgt::Limits,
+    pub downlevel: &'a wgt::DownlevelCapabilities,
+}
+
+/// A shader module parsed into naga IR, before validation.
+pub(crate) struct ParsedShader {
+    pub module: naga::Module,
+    /// The WGSL source of the module, empty for naga modules.
+    pub source: String,
+    pub source_map: Option<wgt::ShaderSourceMap>,
+    /// Identity of the module in pipeline caches.
+    pub identity: hal::auxil::pipeline_cache::ModuleIdentity,
+    /// Pipeline-overridable constants the IR doesn't describe.
+    pub overrides: hal::auxil::overrides::ModuleOverrides,
+}
+
+impl ParsedShader {
+    pub(crate) fn new(
+        desc: &pipeline::ShaderModuleDescriptor,
+        source: pipeline::ShaderModuleSource,
+    ) -> Result<Self, pipeline::CreateShaderModuleError> {
+        let (code, source_map) = match source {
+            pipeline::ShaderModuleSource::Wgsl(code) => (Ok(code), None),
+            pipeline::ShaderModuleSource::WgslPreprocessed(code, map) => (Ok(code), Some(map)),
+            pipeline::ShaderModuleSource::Naga(module) => (Err(module), None),
+        };
+        Ok(match code {
+            Ok(code) => {
+                let (lowered, overrides) = super::overrides::lower(&code)
+                    .map_err(pipeline::CreateShaderModuleError::InvalidOverride)?;
+                profiling::scope!("naga::wgsl::parse_str");
+                let mut module = naga::front::wgsl::parse_str(&lowered).map_err(|inner| {
+                    pipeline::CreateShaderModuleError::Parsing(pipeline::ShaderError {
+                        source: code.to_string(),
+                        source_map: source_map.clone(),
+                        label: desc.label.as_ref().map(|l| l.to_string()),
+                        inner,
+                    })
+                })?;
+                let overrides = overrides
+                    .resolve(&mut module)
+                    .map_err(pipeline::CreateShaderModuleError::InvalidOverride)?;
+                let identity =
+                    hal::auxil::pipeline_cache::ModuleIdentity::from_source(code.as_bytes());
+                Self {
+                    module,
+                    source: code.into_owned(),
+                    source_map,
+                    identity,
+                    overrides,
+                }
+            }
+            Err(module) => Self {
+                module,
+                source: String::new(),
+                source_map,
+                identity: hal::auxil::pipeline_cache::ModuleIdentity::unique(),
+                overrides: Default::default(),
+            },
+        })
+    }
+
+    pub(crate) fn validation_error(
+        &self,
+        desc: &pipeline::ShaderModuleDescriptor,
+        inner: naga::WithSpan<naga::valid::ValidationError>,
+    ) -> pipeline::CreateShaderModuleError {
+        pipeline::CreateShaderModuleError::Validation(pipeline::ShaderError {
+            source: self.source.clone(),
+            source_map: self.source_map.clone(),
+            label: desc.label.as_ref().map(|l| l.to_string()),
+            inner,
+        })
+    }
+}
+
+/// Builds the entries of a bind group layout, keyed by binding.
+pub(crate) fn bind_entry_map(
+    entries: &[wgt::BindGroupLayoutEntry],
+) -> Result<binding_model::BindEntryMap, binding_model::CreateBindGroupLayoutError> {
+    let mut entry_map = FastHashMap::default();
+    for entry in entries.iter() {
+        if entry.binding > MAX_BINDING_INDEX {
+            return Err(
+                binding_model::CreateBindGroupLayoutError::InvalidBindingIndex {
+                    binding: entry.binding,
+                    maximum: MAX_BINDING_INDEX,
+                },
+            );
+        }
+        if entry_map.insert(entry.binding, *entry).is_some() {
+            return Err(binding_model::CreateBindGroupLayoutError::ConflictBinding(
+                entry.binding,
+            ));
+        }
+    }
+    Ok(entry_map)
+}
+
+impl Capabilities<'_> {
+    pub(crate) fn require_features(&self, feature: wgt::Features) -> Result<(), MissingFeatures> {
+        if self.features.contains(feature) {
+            Ok(())
+        } else {
+            Err(MissingFeatures(feature))
+        }
+    }
+
+    pub(crate) fn require_downlevel_flags(
+        &self,
+        flags: wgt::DownlevelFlags,
+    ) -> Result<(), MissingDownlevelFlags> {
+        if self.downlevel.flags.contains(flags) {
+            Ok(())
+        } else {
+            Err(MissingDownlevelFlags(flags))
+        }
+    }
+
+    /// Validates a parsed shader module, returning the validator to reuse on
+    /// rewritten versions of the module.
+    pub(crate) fn validate_shader(
+        &self,
+        desc: &pipeline::ShaderModuleDescriptor,
+        shader: &ParsedShader,
+    ) -> Result<(naga::valid::Validator, naga::valid::ModuleInfo), pipeline::CreateShaderModuleError>
+    {
+        for (_, var) in shader.module.global_variables.iter() {
+            match var.binding {
+                Some(ref br) if br.group >= self.limits.max_bind_groups => {
+                    return Err(pipeline::CreateShaderModuleError::InvalidGroupIndex {
+                        bind: br.clone(),
+                        group: br.group,
+                        limit: self.limits.max_bind_groups,
+                    });
+                }
+                _ => continue,
+            };
+        }
+
+        use naga::valid::Capabilities as Caps;
+        profiling::scope!("naga::validate");
+
+        let mut caps = Caps::empty();
+        caps.set(
+            Caps::PUSH_CONSTANT,
+            self.features
+                .intersects(wgt::Features::PUSH_CONSTANTS | wgt::Features::PUSH_CONSTANT_EMULATION),
+        );
+        caps.set(
+            Caps::FLOAT64,
+            self.features.contains(wgt::Features::SHADER_FLOAT64),
+        );
+        caps.set(
+            Caps::PRIMITIVE_INDEX,
+            self.features
+                .contains(wgt::Features::SHADER_PRIMITIVE_INDEX),
+        );
+        caps.set(
+            Caps::SAMPLED_TEXTURE_AND_STORAGE_BUFFER_ARRAY_NON_UNIFORM_INDEXING,
+            self.features.contains(
+                wgt::Features::SAMPLED_TEXTURE_AND_STORAGE_BUFFER_ARRAY_NON_UNIFORM_INDEXING,
+            ),
+        );
+        caps.set(
+            Caps::UNIFORM_BUFFER_AND_STORAGE_TEXTURE_ARRAY_NON_UNIFORM_INDEXING,
+            self.features.contains(
+                wgt::Features::UNIFORM_BUFFER_AND_STORAGE_TEXTURE_ARRAY_NON_UNIFORM_INDEXING,
+            ),
+        );
+        // TODO: This needs a proper wgpu feature
+        caps.set(
+            Caps::SAMPLER_NON_UNIFORM_INDEXING,
+            self.features.contains(
+                wgt::Features::SAMPLED_TEXTURE_AND_STORAGE_BUFFER_ARRAY_NON_UNIFORM_INDEXING,
+            ),
+        );
+        let mut validator = naga::valid::Validator::new(naga::valid::ValidationFlags::all(), caps);
+        let info = validator
+            .validate(&shader.module)
+            .map_err(|inner| shader.validation_error(desc, inner))?;
+        Ok((validator, info))
+    }
+
+    /// Checks the entries of a bind group layout, returning their binding counts.
+    pub(crate) fn check_bind_group_layout(
+        &self,
+        entry_map: &binding_model::BindEntryMap,
+    ) -> Result<
+        binding_model::BindingTypeMaxCountValidator,
+        binding_model::CreateBindGroupLayoutError,
+    > {
+        #[derive(PartialEq)]
+        enum WritableStorage {
+            Yes,
+            No,
+        }
+
+        for entry in entry_map.values() {
+            use wgt::BindingType as Bt;
+
+            let mut required_features = wgt::Features::empty();
+            let mut required_downlevel_flags = wgt::DownlevelFlags::empty();
+            let (array_feature, writable_storage) = match entry.ty {
+                Bt::Buffer {
+                    ty: wgt::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: _,
+                } => (
+                    Some(wgt::Features::BUFFER_BINDING_ARRAY),
+                    WritableStorage::No,
+                ),
+                Bt::Buffer {
+                    ty: wgt::BufferBindingType::Uniform,
+                    has_dynamic_offset: true,
+                    min_binding_size: _,
+                } => (
+                    Some(wgt::Features::BUFFER_BINDING_ARRAY),
+                    WritableStorage::No,
+                ),
+                Bt::Buffer {
+                    ty: wgt::BufferBindingType::Storage { read_only },
+                    ..
+                } => (
+                    Some(
+                        wgt::Features::BUFFER_BINDING_ARRAY
+                            | wgt::Features::STORAGE_RESOURCE_BINDING_ARRAY,
+                    ),
+                    match read_only {
+                        true => WritableStorage::No,
+                        false => WritableStorage::Yes,
+                    },
+                ),
+                Bt::Sampler { .. } => (
+                    Some(wgt::Features::TEXTURE_BINDING_ARRAY),
+                    WritableStorage::No,
+                ),
+                Bt::Texture { .. } => (
+                    Some(wgt::Features::TEXTURE_BINDING_ARRAY),
+                    WritableStorage::No,
+                ),
+                Bt::StorageTexture {
+                    access,
+                    view_dimension,
+                    format: _,
+                } => {
+                    match view_dimension {
+                        wgt::TextureViewDimension::Cube | wgt::TextureViewDimension::CubeArray => {
+                            return Err(binding_model::CreateBindGroupLayoutError::Entry {
+                                binding: entry.binding,
+                                error: binding_model::BindGroupLayoutEntryError::StorageTextureCube,
+                            })
+                        }
+                        _ => (),
+                    }
+                    match access {
+                        wgt::StorageTextureAccess::ReadOnly
+                        | wgt::StorageTextureAccess::ReadWrite
+                            if !self.features.contains(
+                                wgt::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES,
+                            ) =>
+                        {
+                            return Err(binding_model::CreateBindGroupLayoutError::Entry {
+                                binding: entry.binding,
+                                error: binding_model::BindGroupLayoutEntryError::StorageTextureReadWrite,
+                            });
+                        }
+                        _ => (),
+                    }
+                    (
+                        Some(
+                            wgt::Features::TEXTURE_BINDING_ARRAY
+                                | wgt::Features::STORAGE_RESOURCE_BINDING_ARRAY,
+                        ),
+                        match access {
+                            wgt::StorageTextureAccess::WriteOnly => WritableStorage::Yes,
+                            wgt::StorageTextureAccess::ReadOnly => {
+                                required_features |=
+                                    wgt::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES;
+                                WritableStorage::No
+                            }
+                            wgt::StorageTextureAccess::ReadWrite => {
+                                required_features |=
+                                    wgt::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES;
+                                WritableStorage::Yes
+                            }
+                        },
+                    )
+                }
+            };
+
+            // Validate the count parameter
+            if entry.count.is_some() {
+                required_features |= array_feature
+                    .ok_or(binding_model::BindGroupLayoutEntryError::ArrayUnsupported)
+                    .map_err(|error| binding_model::CreateBindGroupLayoutError::Entry {
+                        binding: entry.binding,
+                        error,
+                    })?;
+            }
+            if entry.visibility.contains(wgt::ShaderStages::VERTEX) {
+                if writable_storage == WritableStorage::Yes {
+                    required_features |= wgt::Features::VERTEX_WRITABLE_STORAGE;
+                }
+                if let Bt::Buffer {
+                    ty: wgt::BufferBindingType::Storage { .. },
+                    ..
+                } = entry.ty
+                {
+                    required_downlevel_flags |= wgt::DownlevelFlags::VERTEX_STORAGE;
+                }
+            }
+            if writable_storage == WritableStorage::Yes
+                && entry.visibility.contains(wgt::ShaderStages::FRAGMENT)
+            {
+                required_downlevel_flags |= wgt::DownlevelFlags::FRAGMENT_WRITABLE_STORAGE;
+            }
+
+            self.require_features(required_features)
+                .map_err(binding_model::BindGroupLayoutEntryError::MissingFeatures)
+                .map_err(|error| binding_model::CreateBindGroupLayoutError::Entry {
+                    binding: entry.binding,
+                    error,
+                })?;
+            self.require_downlevel_flags(required_downlevel_flags)
+                .map_err(binding_model::BindGroupLayoutEntryError::MissingDownlevelFlags)
+                .map_err(|error| binding_model::CreateBindGroupLayoutError::Entry {
+                    binding: entry.binding,
+                    error,
+                })?;
+        }
+
+        let mut count_validator = binding_model::BindingTypeMaxCountValidator::default();
+        for entry in entry_map.values() {
+            count_validator.add_binding(entry);
+        }
+        // If a single bind group layout violates limits, the pipeline layout is definitely
+        // going to violate limits too, lets catch it now.
+        count_validator
+            .validate(self.limits)
+            .map_err(binding_model::CreateBindGroupLayoutError::TooManyBindings)?;
+        Ok(count_validator)
+    }
+
+    /// Checks the group count and the push constant ranges of a pipeline layout.
+    pub(crate) fn check_pipeline_layout(
+        &self,
+        bind_group_layouts_count: usize,
+        push_constant_ranges: &[wgt::PushConstantRange],
+    ) -> Result<(), binding_model::CreatePipelineLayoutError> {
+        use crate::binding_model::CreatePipelineLayoutError as Error;
+
+        let device_max_bind_groups = self.limits.max_bind_groups as usize;
+        if bind_group_layouts_count > device_max_bind_groups {
+            return Err(Error::TooManyGroups {
+                actual: bind_group_layouts_count,
+                max: device_max_bind_groups,
+            });
+        }
+
+        if !push_constant_ranges.is_empty()
+            && !self
+                .features
+                .contains(wgt::Features::PUSH_CONSTANT_EMULATION)
+        {
+            self.require_features(wgt::Features::PUSH_CONSTANTS)?;
+        }
+
+        let mut used_stages = wgt::ShaderStages::empty();
+        for (index, pc) in push_constant_ranges.iter().enumerate() {
+            if pc.stages.intersects(used_stages) {
+                return Err(Error::MoreThanOnePushConstantRangePerStage {
+                    index,
+                    provided: pc.stages,
+                    intersected: pc.stages & used_stages,
+                });
+            }
+            used_stages |= pc.stages;
+
+            let device_max_pc_size = self.limits.max_push_constant_size;
+            if device_max_pc_size < pc.range.end {
+                return Err(Error::PushConstantRangeTooLarge {
+                    index,
+                    range: pc.range.clone(),
+                    max: device_max_pc_size,
+                });
+            }
+
+            if pc.range.start % wgt::PUSH_CONSTANT_ALIGNMENT != 0 {
+                return Err(Error::MisalignedPushConstantRange {
+                    index,
+                    bound: pc.range.start,
+                });
+            }
+            if pc.range.end % wgt::PUSH_CONSTANT_ALIGNMENT != 0 {
+                return Err(Error::MisalignedPushConstantRange {
+                    index,
+                    bound: pc.range.end,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks the fixed-function state of a render pipeline, returning the
+    /// vertex attributes as inputs of the vertex stage.
+    ///
+    /// `format_features` returns the features of the color and depth-stencil formats.
+    pub(crate) fn check_render_pipeline_state(
+        &self,
+        vertex_buffers: &[pipeline::VertexBufferLayout],
+        primitive: &wgt::PrimitiveState,
+        depth_stencil: Option<&wgt::DepthStencilState>,
+        multisample: &wgt::MultisampleState,
+        color_targets: &[Option<wgt::ColorTargetState>],
+        mut format_features: impl FnMut(
+            wgt::TextureFormat,
+        ) -> Result<wgt::TextureFormatFeatures, MissingFeatures>,
+    ) -> Result<validation::StageIo, pipeline::CreateRenderPipelineError> {
+        use wgt::TextureFormatFeatureFlags as Tfff;
+
+        if color_targets.len() > hal::MAX_COLOR_ATTACHMENTS {
+            return Err(
+                pipeline::CreateRenderPipelineError::TooManyColorAttachments {
+                    given: color_targets.len() as u32,
+                    limit: hal::MAX_COLOR_ATTACHMENTS as u32,
+                },
+            );
+        }
+
+        let mut cts = color_targets.iter().filter_map(|x| x.as_ref());
+        if let Some(first) = cts.next() {
+            if cts.any(|ct| ct.write_mask != first.write_mask || ct.blend != first.blend) {
+                log::info!("Color targets: {:?}", color_targets);
+                self.require_downlevel_flags(wgt::DownlevelFlags::INDEPENDENT_BLEND)?;
+            }
+        }
+
+        let mut io = validation::StageIo::default();
+        let mut used_vertex_buffers = 0;
+        let mut total_attributes = 0;
+        for (i, vb_state) in vertex_buffers.iter().enumerate() {
+            if vb_state.attributes.is_empty() {
+                continue;
+            }
+            if vb_state.array_stride > self.limits.max_vertex_buffer_array_stride as u64 {
+                return Err(pipeline::CreateRenderPipelineError::VertexStrideTooLarge {
+                    index: i as u32,
+                    given: vb_state.array_stride as u32,
+                    limit: self.limits.max_vertex_buffer_array_stride,
+                });
+            }
+            if vb_state.array_stride % wgt::VERTEX_STRIDE_ALIGNMENT != 0 {
+                return Err(pipeline::CreateRenderPipelineError::UnalignedVertexStride {
+                    index: i as u32,
+                    stride: vb_state.array_stride,
+                });
+            }
+            used_vertex_buffers += 1;
+
+            for attribute in vb_state.attributes.iter() {
+                if attribute.offset >= 0x10000000 {
+                    return Err(
+                        pipeline::CreateRenderPipelineError::InvalidVertexAttributeOffset {
+                            location: attribute.shader_location,
+                            offset: attribute.offset,
+                        },
+                    );
+                }
+
+                if let wgt::VertexFormat::Float64
+                | wgt::VertexFormat::Float64x2
+                | wgt::VertexFormat::Float64x3
+                | wgt::VertexFormat::Float64x4 = attribute.format
+                {
+                    self.require_features(wgt::Features::VERTEX_ATTRIBUTE_64BIT)?;
+                }
+
+                io.insert(
+                    attribute.shader_location,
+                    validation::InterfaceVar::vertex_attribute(attribute.format),
+                );
+            }
+            total_attributes += vb_state.attributes.len();
+        }
+
+        if used_vertex_buffers > self.limits.max_vertex_buffers {
+            return Err(pipeline::CreateRenderPipelineError::TooManyVertexBuffers {
+                given: used_vertex_buffers,
+                limit: self.limits.max_vertex_buffers,
+            });
+        }
+        if total_attributes > self.limits.max_vertex_attributes as usize {
+            return Err(
+                pipeline::CreateRenderPipelineError::TooManyVertexAttributes {
+                    given: total_attributes as u32,
+                    limit: self.limits.max_vertex_attributes,
+                },
+            );
+        }
+
+        if primitive.strip_index_format.is_some() && !primitive.topology.is_strip() {
+            return Err(
+                pipeline::CreateRenderPipelineError::StripIndexFormatForNonStripTopology {
+                    strip_index_format: primitive.strip_index_format,
+                    topology: primitive.topology,
+                },
+            );
+        }
+
+        if primitive.unclipped_depth {
+            self.require_features(wgt::Features::DEPTH_CLIP_CONTROL)?;
+        }
+
+        if primitive.polygon_mode == wgt::PolygonMode::Line {
+            self.require_features(wgt::Features::POLYGON_MODE_LINE)?;
+        }
+        if primitive.polygon_mode == wgt::PolygonMode::Point {
+            self.require_features(wgt::Features::POLYGON_MODE_POINT)?;
+        }
+
+        if primitive.conservative {
+            self.require_features(wgt::Features::CONSERVATIVE_RASTERIZATION)?;
+        }
+
+        if primitive.conservative && primitive.polygon_mode != wgt::PolygonMode::Fill {
+            return Err(
+                pipeline::CreateRenderPipelineError::ConservativeRasterizationNonFillPolygonMode,
+            );
+        }
+
+        for (i, cs) in color_targets.iter().enumerate() {
+            if let Some(cs) = cs.as_ref() {
+                let error = loop {
+                    let format_features = format_features(cs.format)?;
+                    if !format_features
+                        .allowed_usages
+                        .contains(wgt::TextureUsages::RENDER_ATTACHMENT)
+                    {
+                        break Some(pipeline::ColorStateError::FormatNotRenderable(cs.format));
+                    }
+                    if cs.blend.is_some() && !format_features.flags.contains(Tfff::FILTERABLE) {
+                        break Some(pipeline::ColorStateError::FormatNotBlendable(cs.format));
+                    }
+                    if !hal::FormatAspects::from(cs.format).contains(hal::FormatAspects::COLOR) {
+                        break Some(pipeline::ColorStateError::FormatNotColor(cs.format));
+                    }
+                    if multisample.count > 1 && !format_features.flags.contains(Tfff::MULTISAMPLE) {
+                        break Some(pipeline::ColorStateError::FormatNotMultisampled(cs.format));
+                    }
+
+                    break None;
+                };
+                if let Some(e) = error {
+                    return Err(pipeline::CreateRenderPipelineError::ColorState(i as u8, e));
+                }
+            }
+        }
+
+        if let Some(ds) = depth_stencil {
+            let error = loop {
+                let format_features = format_features(ds.format)?;
+                if !format_features
+                    .allowed_usages
+                    .contains(wgt::TextureUsages::RENDER_ATTACHMENT)
+                {
+                    break Some(pipeline::DepthStencilStateError::FormatNotRenderable(
+                        ds.format,
+                    ));
+                }
+
+                let aspect = hal::FormatAspects::from(ds.format);
+                if ds.is_depth_enabled() && !aspect.contains(hal::FormatAspects::DEPTH) {
+                    break Some(pipeline::DepthStencilStateError::FormatNotDepth(ds.format));
+                }
+                if ds.stencil.is_enabled() && !aspect.contains(hal::FormatAspects::STENCIL) {
+                    break Some(pipeline::DepthStencilStateError::FormatNotStencil(
+                        ds.format,
+                    ));
+                }
+                if multisample.count > 1 && !format_features.flags.contains(Tfff::MULTISAMPLE) {
+                    break Some(pipeline::DepthStencilStateError::FormatNotMultisampled(
+                        ds.format,
+                    ));
+                }
+
+                break None;
+            };
+            if let Some(e) = error {
+                return Err(pipeline::CreateRenderPipelineError::DepthStencilState(e));
+            }
+        }
+
+        let sc = multisample.count;
+        if sc == 0 || sc > 32 || !crate::conv::is_power_of_two_u32(sc) {
+            return Err(pipeline::CreateRenderPipelineError::InvalidSampleCount(sc));
+        }
+
+        Ok(io)
+    }
+}
+
+/// Checks that the outputs of the fragment stage are compatible with the color targets.
+pub(crate) fn check_fragment_outputs(
+    outputs: &validation::StageIo,
+    color_targets: &[Option<wgt::ColorTargetState>],
+    entry_point: &str,
+) -> Result<(), pipeline::CreateRenderPipelineError> {
+    for (i, output) in outputs.iter() {
+        match color_targets.get(*i as usize) {
+            Some(&Some(ref state)) => {
+                validation::check_texture_format(state.format, &output.ty).map_err(|pipeline| {
+                    pipeline::CreateRenderPipelineError::ColorState(
+                        *i as u8,
+                        pipeline::ColorStateError::IncompatibleFormat {
+                            pipeline,
+                            shader: output.ty,
+                        },
+                    )
+                })?;
+            }
+            _ => {
+                log::info!(
+                    "The fragment stage {:?} output @location({}) values are ignored",
+                    entry_point,
+                    i
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Bind group layouts the shader stages of a pipeline are checked against:
+/// those of the pipeline layout if one is given, or else those derived from
+/// the stages themselves.
+pub(crate) struct StageLayouts<'a> {
+    given: Option<ArrayVec<&'a binding_model::BindEntryMap, { hal::MAX_BIND_GROUPS }>>,
+    derived: ArrayVec<binding_model::BindEntryMap, { hal::MAX_BIND_GROUPS }>,
+    shader_binding_sizes: FastHashMap<naga::ResourceBinding, wgt::BufferSize>,
+}
+
+impl<'a> StageLayouts<'a> {
+    pub(crate) fn new(
+        given: Option<ArrayVec<&'a binding_model::BindEntryMap, { hal::MAX_BIND_GROUPS }>>,
+        limits: &wgt::Limits,
+    ) -> Self {
+        let mut derived = ArrayVec::new();
+        if given.is_none() {
+            for _ in 0..limits.max_bind_groups {
+                derived.push(binding_model::BindEntryMap::default());
+            }
+        }
+        Self {
+            given,
+            derived,
+            shader_binding_sizes: FastHashMap::default(),
+        }
+    }
+
+    /// Checks the entry point of a stage against the layouts, and returns its outputs.
+    pub(crate) fn check_stage(
+        &mut self,
+        interface: &validation::Interface,
+        stage: wgt::ShaderStages,
+        entry_point: &str,
+        constants: &HashMap<String, f64>,
+        inputs: validation::StageIo,
+        compare_function: Option<wgt::CompareFunction>,
+    ) -> Result<validation::StageIo, validation::StageError> {
+        interface.check_stage(
+            self.given.as_ref().map(|layouts| layouts.as_slice()),
+            &mut self.derived,
+            &mut self.shader_binding_sizes,
+            entry_point,
+            constants,
+            stage,
+            inputs,
+            compare_function,
+        )
+    }
+
+    /// Returns the bind group layouts derived from the stages, without the
+    /// trailing empty groups, and the minimum sizes of the buffer bindings.
+    pub(crate) fn finish(
+        self,
+    ) -> (
+        ArrayVec<binding_model::BindEntryMap, { hal::MAX_BIND_GROUPS }>,
+        FastHashMap<naga::ResourceBinding, wgt::BufferSize>,
+    ) {
+        let mut derived = self.derived;
+        while matches!(derived.last(), Some(map) if map.is_empty()) {
+            derived.pop();
+        }
+        (derived, self.shader_binding_sizes)
+    }
+}
@@ -0,0 +1,154 @@
+//! Validation of the vertices read by indexed draws.
+//!
+//! When a device is created with [`wgt::Features::INDEX_RANGE_VALIDATION`], buffers
+//! with [`wgt::BufferUsages::INDEX`] keep a copy of the contents written to them
+//! from the CPU. Indexed draws look up the smallest and largest index of the part
+//! of the buffer they read, and check the vertices these select against the bound
+//! vertex buffers, like non-indexed draws do.
+//!
+//! The bounds of a part are computed the first time it is drawn, and cached until
+//! it is written again. Contents written by the GPU aren't known: draws reading a
+//! buffer the GPU wrote to fail validation, until it is written whole again from
+//! the CPU. Buffers shaders can write to are never known, and aren't copied.
+//!
+//! Draws are checked when they are recorded, so writes made between recording and
+//! submission aren't taken into account.
+
+use crate::{command::DrawError, FastHashMap};
+use std::ops::{Range, RangeInclusive};
+
+/// Contents of an index buffer, as far as they are known on the CPU.
+#[derive(Debug)]
+pub(crate) struct IndexContents {
+    size: wgt::BufferAddress,
+    /// Set for buffers shaders can write to, whose contents are never known.
+    shader_writable: bool,
+    /// Copy of the contents of the buffer, `None` when they aren't known.
+    data: Option<Vec<u8>>,
+    /// Bounds of the indices of the parts of the buffer drawn so far, by format,
+    /// whether the primitive restart value is skipped, and byte range.
+    bounds: FastHashMap<(wgt::IndexFormat, bool, u64, u64), Option<RangeInclusive<u32>>>,
+}
+
+impl IndexContents {
+    /// Creates the contents of a new buffer, zeroed like the buffer is.
+    pub(crate) fn new(size: wgt::BufferAddress, shader_writable: bool) -> Self {
+        Self {
+            size,
+            shader_writable,
+            data: (!shader_writable).then(|| vec![0; size as usize]),
+            bounds: FastHashMap::default(),
+        }
+    }
+
+    /// Records a write of `data` at `offset`, from the CPU.
+    pub(crate) fn write(&mut self, offset: wgt::BufferAddress, data: &[u8]) {
+        if self.shader_writable {
+            return;
+        }
+        let range = offset..offset + data.len() as u64;
+        self.bounds
+            .retain(|&(_, _, start, end), _| end <= range.start || start >= range.end);
+        match self.data {
+            Some(ref mut contents) => {
+                contents[range.start as usize..range.end as usize].copy_from_slice(data)
+            }
+            None if range == (0..self.size) => self.data = Some(data.to_vec()),
+            None => {}
+        }
+    }
+
+    /// Records a write from the GPU, after which the contents aren't known.
+    pub(crate) fn invalidate(&mut self) {
+        self.data = None;
+        self.bounds.clear();
+    }
+
+    /// Returns the bounds of the indices in `range`, or `None` if there are none.
+    ///
+    /// The primitive restart value of `format` is skipped if `strip` is true.
+    fn bounds(
+        &mut self,
+        format: wgt::IndexFormat,
+        strip: bool,
+        range: Range<wgt::BufferAddress>,
+    ) -> Result<Option<RangeInclusive<u32>>, DrawError> {
+        let data = self.data.as_ref().ok_or(DrawError::UnknownIndexContents)?;
+        Ok(self
+            .bounds
+            .entry((format, strip, range.start, range.end))
+            .or_insert_with(|| {
+                let bytes = &data[range.start as usize..range.end as usize];
+                let restart = match format {
+                    _ if !strip => None,
+                    wgt::IndexFormat::Uint16 => Some(u16::MAX as u32),
+                    wgt::IndexFormat::Uint32 => Some(u32::MAX),
+                };
+                let indices: Box<dyn Iterator<Item = u32>> =
+                    match format {
+                        wgt::IndexFormat::Uint16 => Box::new(
+                            bytes
+                                .chunks_exact(2)
+                                .map(|index| u16::from_le_bytes([index[0], index[1]]) as u32),
+                        ),
+                        wgt::IndexFormat::Uint32 => Box::new(bytes.chunks_exact(4).map(|index| {
+                            u32::from_le_bytes([index[0], index[1], index[2], index[3]])
+                        })),
+                    };
+                indices
+                    .filter(|&index| Some(index) != restart)
+                    .fold(None, |bounds, index| match bounds {
+                        Some((min, max)) => Some((index.min(min), index.max(max))),
+                        None => Some((index, index)),
+                    })
+                    .map(|(min, max)| min..=max)
+            })
+            .clone())
+    }
+}
+
+/// An indexed draw, to check against the vertex buffers.
+pub(crate) struct IndexedDraw {
+    pub format: wgt::IndexFormat,
+    /// Whether the pipeline draws strips, so the primitive restart value isn't an index.
+    pub strip: bool,
+    /// Offset of the bound range of the index buffer.
+    pub offset: wgt::BufferAddress,
+    pub first_index: u32,
+    pub index_count: u32,
+    pub base_vertex: i32,
+    pub vertex_limit: u32,
+    pub vertex_limit_slot: u32,
+}
+
+impl IndexedDraw {
+    /// Checks that the vertices read by the draw are within the vertex buffers.
+    pub(crate) fn validate(self, contents: &mut IndexContents) -> Result<(), DrawError> {
+        if self.index_count == 0 {
+            return Ok(());
+        }
+        let index_size = match self.format {
+            wgt::IndexFormat::Uint16 => 2,
+            wgt::IndexFormat::Uint32 => 4,
+        };
+        let start = self.offset + self.first_index as u64 * index_size;
+        let range = start..start + self.index_count as u64 * index_size;
+        let bounds = match contents.bounds(self.format, self.strip, range)? {
+            Some(bounds) => bounds,
+            None => return Ok(()),
+        };
+        for index in [*bounds.start(), *bounds.end()] {
+            let vertex = self.base_vertex as i64 + index as i64;
+            if vertex < 0 || vertex >= self.vertex_limit as i64 {
+                return Err(DrawError::IndexedVertexOutOfRange {
+                    index,
+                    base_vertex: self.base_vertex,
+                    vertex,
+                    vertex_limit: self.vertex_limit,
+                    slot: self.vertex_limit_slot,
+                });
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,449 @@
+//! Validation of indirect arguments on the GPU.
+//!
+//! When a device is created with [`wgt::Features::INDIRECT_VALIDATION`], indirect
+//! commands of passes don't read their arguments from the buffer they are given,
+//! but from an internal buffer of the pass, filled by a compute shader. The shader
+//! copies the records whose values are in range, and zeroes the others:
+//!
+//! - a draw is in range if its vertices, indices and instances are within the
+//!   limits of the vertex and index buffers bound when it's recorded, and if it
+//!   starts at instance 0 without [`wgt::Features::INDIRECT_FIRST_INSTANCE`],
+//! - a dispatch is in range if none of its workgroup counts is over
+//!   [`wgt::Limits::max_compute_workgroups_per_dimension`].
+//!
+//! A render pass can't write to its indirect buffers, so all its draws, including
+//! those of the render bundles it executes, are validated before the pass begins. A compute pass can, so each indirect
+//! dispatch is validated right before it, and the pass state is restored after.
+
+use super::{queue::TempResource, DeviceError};
+use hal::{CommandEncoder as _, Device as _};
+use std::{iter, ptr};
+use wgt::BufferAddress;
+
+const SHADER: &str = include_str!("indirect_validation.wgsl");
+
+/// Size, in bytes, of the parameters of a check.
+const PARAMS_SIZE: u32 = 32;
+
+/// Number of records checked by a workgroup.
+const WORKGROUP_SIZE: u32 = 64;
+
+/// Kind of indirect arguments, with the limits they are checked against.
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum IndirectArgs {
+    Draw {
+        vertex_limit: u32,
+        instance_limit: u32,
+    },
+    DrawIndexed {
+        index_limit: u32,
+        instance_limit: u32,
+    },
+    Dispatch,
+}
+
+impl IndirectArgs {
+    /// Size, in bytes, of a draw record.
+    pub(crate) fn draw_size(indexed: bool) -> BufferAddress {
+        // 4 integers, or 5 with the base vertex of indexed draws.
+        match indexed {
+            false => 4 * 4,
+            true => 5 * 4,
+        }
+    }
+
+    /// Size, in bytes, of a record.
+    pub(crate) fn size(self) -> BufferAddress {
+        match self {
+            Self::Draw { .. } => Self::draw_size(false),
+            Self::DrawIndexed { .. } => Self::draw_size(true),
+            // 3 integers, x/y/z group size
+            Self::Dispatch => 3 * 4,
+        }
+    }
+}
+
+/// Consecutive records to check in a buffer, and where to copy them in the
+/// buffer of an [`IndirectArguments`].
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct IndirectCheck {
+    pub(crate) src_offset: BufferAddress,
+    pub(crate) dst_offset: BufferAddress,
+    pub(crate) count: u32,
+    pub(crate) args: IndirectArgs,
+}
+
+#[derive(Debug)]
+pub(crate) struct IndirectValidation<A: hal::Api> {
+    bind_group_layout: A::BindGroupLayout,
+    pipeline_layout: A::PipelineLayout,
+    pipeline: A::ComputePipeline,
+    /// Distance, in bytes, between the parameters of two checks.
+    params_stride: u32,
+    /// Alignment, in bytes, of the offset of checked buffers in bind groups.
+    storage_alignment: u32,
+    max_workgroups: u32,
+    first_instance: bool,
+}
+
+impl<A: hal::Api> IndirectValidation<A> {
+    pub(crate) unsafe fn new(
+        device: &A::Device,
+        limits: &wgt::Limits,
+        features: wgt::Features,
+    ) -> Result<Self, DeviceError> {
+        let module = naga::front::wgsl::parse_str(SHADER).unwrap();
+        let info = naga::valid::Validator::new(
+            naga::valid::ValidationFlags::all(),
+            naga::valid::Capabilities::empty(),
+        )
+        .validate(&module)
+        .unwrap();
+        let module = device
+            .create_shader_module(
+                &hal::ShaderModuleDescriptor {
+                    label: Some("(wgpu internal) indirect validation"),
+                    runtime_checks: true,
+                },
+                hal::ShaderInput::Naga(hal::NagaShader {
+                    module,
+                    info,
+                    identity: hal::auxil::pipeline_cache::ModuleIdentity::from_source(
+                        SHADER.as_bytes(),
+                    ),
+                    overrides: Default::default(),
+                }),
+            )
+            .map_err(|error| match error {
+                hal::ShaderError::Device(error) => DeviceError::from(error),
+                hal::ShaderError::Compilation(msg) => {
+                    log::error!("Indirect validation shader error: {}", msg);
+                    DeviceError::Lost
+                }
+            })?;
+
+        let buffer_entry = |binding, ty| wgt::BindGroupLayoutEntry {
+            binding,
+            visibility: wgt::ShaderStages::COMPUTE,
+            ty: wgt::BindingType::Buffer {
+                ty,
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+        let bind_group_layout =
+            device.create_bind_group_layout(&hal::BindGroupLayoutDescriptor {
+                label: Some("(wgpu internal) indirect validation"),
+                flags: hal::BindGroupLayoutFlags::empty(),
+                entries: &[
+                    buffer_entry(0, wgt::BufferBindingType::Uniform),
+                    buffer_entry(1, wgt::BufferBindingType::Storage { read_only: true }),
+                    buffer_entry(2, wgt::BufferBindingType::Storage { read_only: false }),
+                ],
+            })?;
+        let pipeline_layout = device.create_pipeline_layout(&hal::PipelineLayoutDescriptor {
+            label: Some("(wgpu internal) indirect validation"),
+            flags: hal::PipelineLayoutFlags::empty(),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        })?;
+        let pipeline = device.create_compute_pipeline(&hal::ComputePipelineDescriptor {
+            label: Some("(wgpu internal) indirect validation"),
+            layout: &pipeline_layout,
+            stage: hal::ProgrammableStage {
+                module: &module,
+                entry_point: "main",
+                constants: &Default::default(),
+            },
+            cache: None,
+        });
+        device.destroy_shader_module(module);
+        let pipeline = pipeline.map_err(|error| match error {
+            hal::PipelineError::Device(error) => DeviceError::from(error),
+            hal::PipelineError::Linkage(_, msg) => {
+                log::error!("Indirect validation pipeline error: {}", msg);
+                DeviceError::Lost
+            }
+            hal::PipelineError::EntryPoint(_) | hal::PipelineError::UnsupportedVertexFormat(_) => {
+                DeviceError::Lost
+            }
+        })?;
+
+        Ok(Self {
+            bind_group_layout,
+            pipeline_layout,
+            pipeline,
+            params_stride: hal::auxil::align_to(
+                PARAMS_SIZE,
+                limits.min_uniform_buffer_offset_alignment,
+            ),
+            storage_alignment: limits.min_storage_buffer_offset_alignment,
+            max_workgroups: limits.max_compute_workgroups_per_dimension,
+            first_instance: features.contains(wgt::Features::INDIRECT_FIRST_INSTANCE),
+        })
+    }
+
+    pub(crate) unsafe fn dispose(self, device: &A::Device) {
+        device.destroy_compute_pipeline(self.pipeline);
+        device.destroy_pipeline_layout(self.pipeline_layout);
+        device.destroy_bind_group_layout(self.bind_group_layout);
+    }
+
+    /// Creates the buffer `size` bytes of checked arguments are copied to.
+    pub(crate) unsafe fn create_arguments(
+        &self,
+        device: &A::Device,
+        size: BufferAddress,
+    ) -> Result<IndirectArguments<A>, DeviceError> {
+        let buffer = device.create_buffer(&hal::BufferDescriptor {
+            label: Some("(wgpu internal) indirect arguments"),
+            size,
+            usage: hal::BufferUses::STORAGE_READ_WRITE | hal::BufferUses::INDIRECT,
+            memory_flags: hal::MemoryFlags::empty(),
+        })?;
+        Ok(IndirectArguments {
+            buffer,
+            state: hal::BufferUses::empty(),
+            staging: None,
+            params: None,
+            bind_groups: Vec::new(),
+            counts: Vec::new(),
+        })
+    }
+
+    /// Uploads the parameters of `checks`, each reading from its buffer.
+    ///
+    /// The copy is recorded in `encoder`, which must not be in a pass.
+    pub(crate) unsafe fn prepare(
+        &self,
+        device: &A::Device,
+        encoder: &mut A::CommandEncoder,
+        arguments: &mut IndirectArguments<A>,
+        checks: &[(&A::Buffer, IndirectCheck)],
+    ) -> Result<(), DeviceError> {
+        let stride = self.params_stride as usize;
+        let size = (checks.len() * stride) as BufferAddress;
+        let staging = arguments
+            .staging
+            .insert(device.create_buffer(&hal::BufferDescriptor {
+                label: Some("(wgpu internal) Staging"),
+                size,
+                usage: hal::BufferUses::MAP_WRITE | hal::BufferUses::COPY_SRC,
+                memory_flags: hal::MemoryFlags::TRANSIENT,
+            })?);
+        let params = arguments
+            .params
+            .insert(device.create_buffer(&hal::BufferDescriptor {
+                label: Some("(wgpu internal) indirect validation parameters"),
+                size,
+                usage: hal::BufferUses::COPY_DST | hal::BufferUses::UNIFORM,
+                memory_flags: hal::MemoryFlags::empty(),
+            })?);
+
+        let mut data = vec![0; checks.len() * stride];
+        for (&(src, check), (index, bytes)) in
+            checks.iter().zip(data.chunks_mut(stride).enumerate())
+        {
+            let src_start = check.src_offset - check.src_offset % self.storage_alignment as u64;
+            let src_end = check.src_offset + check.args.size() * check.count as u64;
+            let (kind, limit, instance_limit) = match check.args {
+                IndirectArgs::Draw {
+                    vertex_limit,
+                    instance_limit,
+                } => (0, vertex_limit, instance_limit),
+                IndirectArgs::DrawIndexed {
+                    index_limit,
+                    instance_limit,
+                } => (1, index_limit, instance_limit),
+                IndirectArgs::Dispatch => (2, self.max_workgroups, 0),
+            };
+            let values = [
+                ((check.src_offset - src_start) / 4) as u32,
+                (check.dst_offset / 4) as u32,
+                check.count,
+                kind,
+                limit,
+                instance_limit,
+                self.first_instance as u32,
+                0,
+            ];
+            for (bytes, value) in bytes.chunks_mut(4).zip(values) {
+                bytes.copy_from_slice(&value.to_ne_bytes());
+            }
+
+            let bind_group = device.create_bind_group(&hal::BindGroupDescriptor {
+                label: Some("(wgpu internal) indirect validation"),
+                layout: &self.bind_group_layout,
+                buffers: &[
+                    hal::BufferBinding {
+                        buffer: params,
+                        offset: (index * stride) as BufferAddress,
+                        size: wgt::BufferSize::new(PARAMS_SIZE as u64),
+                    },
+                    hal::BufferBinding {
+                        buffer: src,
+                        offset: src_start,
+                        size: wgt::BufferSize::new(src_end - src_start),
+                    },
+                    hal::BufferBinding {
+                        buffer: &arguments.buffer,
+                        offset: 0,
+                        size: None,
+                    },
+                ],
+                samplers: &[],
+                textures: &[],
+                entries: &[
+                    hal::BindGroupEntry {
+                        binding: 0,
+                        resource_index: 0,
+                        count: 1,
+                    },
+                    hal::BindGroupEntry {
+                        binding: 1,
+                        resource_index: 1,
+                        count: 1,
+                    },
+                    hal::BindGroupEntry {
+                        binding: 2,
+                        resource_index: 2,
+                        count: 1,
+                    },
+                ],
+            })?;
+            arguments.bind_groups.push(bind_group);
+            arguments.counts.push(check.count);
+        }
+
+        let mapping = device.map_buffer(staging, 0..size)?;
+        ptr::copy_nonoverlapping(data.as_ptr(), mapping.ptr.as_ptr(), data.len());
+        if !mapping.is_coherent {
+            device.flush_mapped_ranges(staging, iter::once(0..size));
+        }
+        device.unmap_buffer(staging)?;
+
+        encoder.transition_buffers(
+            [
+                hal::BufferBarrier {
+                    buffer: &*staging,
+                    usage: hal::BufferUses::MAP_WRITE..hal::BufferUses::COPY_SRC,
+                },
+                hal::BufferBarrier {
+                    buffer: &*params,
+                    usage: hal::BufferUses::empty()..hal::BufferUses::COPY_DST,
+                },
+            ]
+            .into_iter(),
+        );
+        encoder.copy_buffer_to_buffer(
+            staging,
+            params,
+            iter::once(hal::BufferCopy {
+                src_offset: 0,
+                dst_offset: 0,
+                size: wgt::BufferSize::new(size).unwrap(),
+            }),
+        );
+        encoder.transition_buffers(iter::once(hal::BufferBarrier {
+            buffer: &*params,
+            usage: hal::BufferUses::COPY_DST..hal::BufferUses::UNIFORM,
+        }));
+        Ok(())
+    }
+
+    /// Records the `index`-th check given to [`Self::prepare`], reading from
+    /// `src`, currently in the `src_state` state.
+    ///
+    /// `encoder` must be in a compute pass. Its pipeline and first bind group
+    /// are replaced.
+    pub(crate) unsafe fn validate(
+        &self,
+        encoder: &mut A::CommandEncoder,
+        arguments: &mut IndirectArguments<A>,
+        index: usize,
+        src: &A::Buffer,
+        src_state: hal::BufferUses,
+    ) {
+        encoder.transition_buffers(
+            [
+                hal::BufferBarrier {
+                    buffer: src,
+                    usage: src_state..hal::BufferUses::STORAGE_READ,
+                },
+                hal::BufferBarrier {
+                    buffer: &arguments.buffer,
+                    usage: arguments.state..hal::BufferUses::STORAGE_READ_WRITE,
+                },
+            ]
+            .into_iter(),
+        );
+        encoder.set_compute_pipeline(&self.pipeline);
+        encoder.set_bind_group(&self.pipeline_layout, 0, &arguments.bind_groups[index], &[]);
+        let count = arguments.counts[index];
+        encoder.dispatch([
+            hal::auxil::align_to(count, WORKGROUP_SIZE) / WORKGROUP_SIZE,
+            1,
+            1,
+        ]);
+        encoder.transition_buffers(
+            [
+                hal::BufferBarrier {
+                    buffer: src,
+                    usage: hal::BufferUses::STORAGE_READ..src_state,
+                },
+                hal::BufferBarrier {
+                    buffer: &arguments.buffer,
+                    usage: hal::BufferUses::STORAGE_READ_WRITE..hal::BufferUses::INDIRECT,
+                },
+            ]
+            .into_iter(),
+        );
+        arguments.state = hal::BufferUses::INDIRECT;
+    }
+}
+
+/// Checked indirect arguments of a pass, with the resources used to check them.
+#[derive(Debug)]
+pub(crate) struct IndirectArguments<A: hal::Api> {
+    buffer: A::Buffer,
+    /// State of `buffer` at the end of the commands recorded so far.
+    state: hal::BufferUses,
+    staging: Option<A::Buffer>,
+    params: Option<A::Buffer>,
+    bind_groups: Vec<A::BindGroup>,
+    /// Number of records of each check.
+    counts: Vec<u32>,
+}
+
+impl<A: hal::Api> IndirectArguments<A> {
+    /// The buffer indirect commands read their checked arguments from.
+    pub(crate) fn buffer(&self) -> &A::Buffer {
+        &self.buffer
+    }
+
+    pub(crate) fn into_temp_resources(self) -> impl Iterator<Item = TempResource<A>> {
+        self.bind_groups
+            .into_iter()
+            .map(TempResource::BindGroup)
+            .chain(
+                iter::once(self.buffer)
+                    .chain(self.params)
+                    .chain(self.staging)
+                    .map(TempResource::Buffer),
+            )
+    }
+
+    pub(crate) unsafe fn destroy(self, device: &A::Device) {
+        for bind_group in self.bind_groups {
+            device.destroy_bind_group(bind_group);
+        }
+        device.destroy_buffer(self.buffer);
+        if let Some(params) = self.params {
+            device.destroy_buffer(params);
+        }
+        if let Some(staging) = self.staging {
+            device.destroy_buffer(staging);
+        }
+    }
+}
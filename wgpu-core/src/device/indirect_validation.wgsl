@@ -0,0 +1,66 @@
+// Copies indirect arguments to the buffer indirect commands read them from,
+// zeroing the records with values out of range.
+
+struct Params {
+    // Offset, in words, of the first record in `src`.
+    src_offset: u32,
+    // Offset, in words, of the first record in `dst`.
+    dst_offset: u32,
+    // Number of records.
+    count: u32,
+    // 0 for draws, 1 for indexed draws and 2 for dispatches.
+    kind: u32,
+    // Vertex or index limit of draws, workgroup limit of dispatches.
+    limit: u32,
+    // Instance limit of draws.
+    instance_limit: u32,
+    // Whether draws can start at an instance other than 0.
+    first_instance: u32,
+    padding: u32,
+};
+
+@group(0) @binding(0)
+var<uniform> params: Params;
+@group(0) @binding(1)
+var<storage, read> src: array<u32>;
+@group(0) @binding(2)
+var<storage, read_write> dst: array<u32>;
+
+fn in_range(first: u32, count: u32, limit: u32) -> bool {
+    return count <= limit && first <= limit - count;
+}
+
+@compute @workgroup_size(64)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    if (id.x >= params.count) {
+        return;
+    }
+
+    var size = 4u;
+    if (params.kind == 1u) {
+        size = 5u;
+    } else if (params.kind == 2u) {
+        size = 3u;
+    }
+    let src_offset = params.src_offset + id.x * size;
+    let dst_offset = params.dst_offset + id.x * size;
+
+    var valid = true;
+    if (params.kind == 2u) {
+        for (var i = 0u; i < 3u; i = i + 1u) {
+            valid = valid && src[src_offset + i] <= params.limit;
+        }
+    } else {
+        // Both kinds of draws start with the vertex or index count, then the
+        // instance count and the first vertex or index, and end with the
+        // first instance.
+        let first_instance = src[src_offset + size - 1u];
+        valid = in_range(src[src_offset + 2u], src[src_offset], params.limit)
+            && in_range(first_instance, src[src_offset + 1u], params.instance_limit)
+            && (params.first_instance != 0u || first_instance == 0u);
+    }
+
+    for (var i = 0u; i < size; i = i + 1u) {
+        dst[dst_offset + i] = select(0u, src[src_offset + i], valid);
+    }
+}
@@ -17,7 +17,7 @@ use hal::Device as _;
 use parking_lot::Mutex;
 use thiserror::Error;
 
-use std::mem;
+use std::{iter, mem};
 
 /// A struct that keeps lists of resources that are no longer needed by the user.
 #[derive(Debug, Default)]
@@ -31,6 +31,8 @@ pub(super) struct SuspectedResources {
     pub(super) render_pipelines: Vec<id::Valid<id::RenderPipelineId>>,
     pub(super) bind_group_layouts: Vec<id::Valid<id::BindGroupLayoutId>>,
     pub(super) pipeline_layouts: Vec<Stored<id::PipelineLayoutId>>,
+    pub(super) shader_modules: Vec<Stored<id::ShaderModuleId>>,
+    pub(super) pipeline_caches: Vec<Stored<id::PipelineCacheId>>,
     pub(super) render_bundles: Vec<id::Valid<id::RenderBundleId>>,
     pub(super) query_sets: Vec<id::Valid<id::QuerySetId>>,
 }
@@ -46,6 +48,8 @@ impl SuspectedResources {
         self.render_pipelines.clear();
         self.bind_group_layouts.clear();
         self.pipeline_layouts.clear();
+        self.shader_modules.clear();
+        self.pipeline_caches.clear();
         self.render_bundles.clear();
         self.query_sets.clear();
     }
@@ -64,6 +68,9 @@ impl SuspectedResources {
             .extend_from_slice(&other.bind_group_layouts);
         self.pipeline_layouts
             .extend_from_slice(&other.pipeline_layouts);
+        self.shader_modules.extend_from_slice(&other.shader_modules);
+        self.pipeline_caches
+            .extend_from_slice(&other.pipeline_caches);
         self.render_bundles.extend_from_slice(&other.render_bundles);
         self.query_sets.extend_from_slice(&other.query_sets);
     }
@@ -97,6 +104,8 @@ struct NonReferencedResources<A: hal::Api> {
     render_pipes: Vec<A::RenderPipeline>,
     bind_group_layouts: Vec<A::BindGroupLayout>,
     pipeline_layouts: Vec<A::PipelineLayout>,
+    shader_modules: Vec<A::ShaderModule>,
+    pipeline_caches: Vec<A::PipelineCache>,
     query_sets: Vec<A::QuerySet>,
 }
 
@@ -112,6 +121,8 @@ impl<A: hal::Api> NonReferencedResources<A> {
             render_pipes: Vec::new(),
             bind_group_layouts: Vec::new(),
             pipeline_layouts: Vec::new(),
+            shader_modules: Vec::new(),
+            pipeline_caches: Vec::new(),
             query_sets: Vec::new(),
         }
     }
@@ -127,6 +138,8 @@ impl<A: hal::Api> NonReferencedResources<A> {
         self.query_sets.extend(other.query_sets);
         assert!(other.bind_group_layouts.is_empty());
         assert!(other.pipeline_layouts.is_empty());
+        assert!(other.shader_modules.is_empty());
+        assert!(other.pipeline_caches.is_empty());
     }
 
     unsafe fn clean(&mut self, device: &A::Device) {
@@ -184,6 +197,18 @@ impl<A: hal::Api> NonReferencedResources<A> {
                 device.destroy_pipeline_layout(raw);
             }
         }
+        if !self.shader_modules.is_empty() {
+            profiling::scope!("destroy_shader_modules");
+            for raw in self.shader_modules.drain(..) {
+                device.destroy_shader_module(raw);
+            }
+        }
+        if !self.pipeline_caches.is_empty() {
+            profiling::scope!("destroy_pipeline_caches");
+            for raw in self.pipeline_caches.drain(..) {
+                device.destroy_pipeline_cache(raw);
+            }
+        }
         if !self.query_sets.is_empty() {
             profiling::scope!("destroy_query_sets");
             for raw in self.query_sets.drain(..) {
@@ -336,6 +361,7 @@ impl<A: hal::Api> LifetimeTracker<A> {
                     last_resources.textures.push(raw);
                     last_resources.texture_views.extend(views);
                 }
+                TempResource::BindGroup(raw) => last_resources.bind_groups.push(raw),
             }
         }
 
@@ -408,8 +434,7 @@ impl<A: hal::Api> LifetimeTracker<A> {
             self.free_resources.extend(a.last_resources);
             self.ready_to_map.extend(a.mapped);
             for encoder in a.encoders {
-                let raw = unsafe { encoder.land() };
-                command_allocator.lock().release_encoder(raw);
+                unsafe { encoder.land(&mut command_allocator.lock()) };
             }
             work_done_closures.extend(a.work_done_closures);
         }
@@ -439,9 +464,54 @@ impl<A: hal::Api> LifetimeTracker<A> {
                 resources.texture_views.extend(views);
                 resources.textures.push(raw);
             }
+            TempResource::BindGroup(raw) => resources.bind_groups.push(raw),
+        }
+    }
+
+    /// Keeps the encoder of a dropped reusable command buffer until the
+    /// submission `last_submit_index` is done.
+    ///
+    /// Returns the encoder back if that submission is already done.
+    pub(super) fn schedule_encoder_release(
+        &mut self,
+        encoder: EncoderInFlight<A>,
+        last_submit_index: SubmissionIndex,
+    ) -> Option<EncoderInFlight<A>> {
+        match self
+            .active
+            .iter_mut()
+            .find(|a| a.index == last_submit_index)
+        {
+            Some(a) => {
+                a.encoders.push(encoder);
+                None
+            }
+            None => Some(encoder),
         }
     }
 
+    fn resources_at(
+        &mut self,
+        last_submit_index: SubmissionIndex,
+    ) -> &mut NonReferencedResources<A> {
+        self.active
+            .iter_mut()
+            .find(|a| a.index == last_submit_index)
+            .map_or(&mut self.free_resources, |a| &mut a.last_resources)
+    }
+
+    /// Schedules raw pipelines replaced by a shader reload for destruction.
+    pub(super) fn schedule_pipeline_destruction(
+        &mut self,
+        compute_pipes: impl IntoIterator<Item = A::ComputePipeline>,
+        render_pipes: impl IntoIterator<Item = A::RenderPipeline>,
+        last_submit_index: SubmissionIndex,
+    ) {
+        let resources = self.resources_at(last_submit_index);
+        resources.compute_pipes.extend(compute_pipes);
+        resources.render_pipes.extend(render_pipes);
+    }
+
     pub fn add_work_done_closure(
         &mut self,
         closure: SubmittedWorkDoneClosure,
@@ -694,7 +764,7 @@ impl<A: HalApi> LifetimeTracker<A> {
                             .find(|a| a.index == submit_index)
                             .map_or(&mut self.free_resources, |a| &mut a.last_resources)
                             .compute_pipes
-                            .push(res.raw);
+                            .extend(iter::once(res.raw).chain(res.retired));
                     }
                 }
             }
@@ -719,7 +789,7 @@ impl<A: HalApi> LifetimeTracker<A> {
                             .find(|a| a.index == submit_index)
                             .map_or(&mut self.free_resources, |a| &mut a.last_resources)
                             .render_pipes
-                            .push(res.raw);
+                            .extend(iter::once(res.raw).chain(res.retired));
                     }
                 }
             }
@@ -751,6 +821,50 @@ impl<A: HalApi> LifetimeTracker<A> {
             }
         }
 
+        if !self.suspected_resources.shader_modules.is_empty() {
+            let (mut guard, _) = hub.shader_modules.write(token);
+
+            for Stored {
+                value: id,
+                ref_count,
+            } in self.suspected_resources.shader_modules.drain(..)
+            {
+                if ref_count.load() == 1 {
+                    log::debug!("Shader module {:?} will be destroyed", id);
+                    #[cfg(feature = "trace")]
+                    if let Some(t) = trace {
+                        t.lock().add(trace::Action::DestroyShaderModule(id.0));
+                    }
+
+                    if let Some(module) = hub.shader_modules.unregister_locked(id.0, &mut *guard) {
+                        self.free_resources.shader_modules.push(module.raw);
+                    }
+                }
+            }
+        }
+
+        if !self.suspected_resources.pipeline_caches.is_empty() {
+            let (mut guard, _) = hub.pipeline_caches.write(token);
+
+            for Stored {
+                value: id,
+                ref_count,
+            } in self.suspected_resources.pipeline_caches.drain(..)
+            {
+                if ref_count.load() == 1 {
+                    log::debug!("Pipeline cache {:?} will be destroyed", id);
+                    #[cfg(feature = "trace")]
+                    if let Some(t) = trace {
+                        t.lock().add(trace::Action::DestroyPipelineCache(id.0));
+                    }
+
+                    if let Some(cache) = hub.pipeline_caches.unregister_locked(id.0, &mut *guard) {
+                        self.free_resources.pipeline_caches.push(cache.raw);
+                    }
+                }
+            }
+        }
+
         if !self.suspected_resources.bind_group_layouts.is_empty() {
             let (mut guard, _) = hub.bind_group_layouts.write(token);
 
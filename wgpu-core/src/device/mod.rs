@@ -19,14 +19,26 @@ use crate::{
 use arrayvec::ArrayVec;
 use copyless::VecHelper as _;
 use hal::{CommandEncoder as _, Device as _};
-use parking_lot::{Mutex, MutexGuard};
+use parking_lot::{Mutex, MutexGuard, RwLockWriteGuard};
 use smallvec::SmallVec;
 use thiserror::Error;
 use wgt::{BufferAddress, TextureFormat, TextureViewDimension};
 
-use std::{borrow::Cow, iter, mem, num::NonZeroU32, ops::Range, ptr};
+use std::{
+    borrow::Cow,
+    iter, mem,
+    num::NonZeroU32,
+    ops::Range,
+    ptr,
+    sync::atomic::{AtomicBool, Ordering},
+};
 
+pub(crate) mod checks;
+pub(crate) mod index_range;
+pub(crate) mod indirect_validation;
 mod life;
+mod overrides;
+pub(crate) mod push_constants;
 pub mod queue;
 #[cfg(any(feature = "trace", feature = "replay"))]
 pub mod trace;
@@ -213,6 +225,11 @@ fn map_buffer<A: hal::Api>(
 
 struct CommandAllocator<A: hal::Api> {
     free_encoders: Vec<A::CommandEncoder>,
+    /// Blocks of emulated push constants no longer used by any submission.
+    free_push_constant_blocks: Vec<push_constants::PushConstantBlock<A>>,
+    /// Whether the encoders are created for reusable command buffers, which
+    /// is only known when they are finished.
+    reusable: bool,
 }
 
 impl<A: hal::Api> CommandAllocator<A> {
@@ -224,7 +241,11 @@ impl<A: hal::Api> CommandAllocator<A> {
         match self.free_encoders.pop() {
             Some(encoder) => Ok(encoder),
             None => unsafe {
-                let hal_desc = hal::CommandEncoderDescriptor { label: None, queue };
+                let hal_desc = hal::CommandEncoderDescriptor {
+                    label: None,
+                    queue,
+                    reusable: self.reusable,
+                };
                 device.create_command_encoder(&hal_desc)
             },
         }
@@ -234,6 +255,25 @@ impl<A: hal::Api> CommandAllocator<A> {
         self.free_encoders.push(encoder);
     }
 
+    /// Returns a free block of at least `size` bytes.
+    fn acquire_push_constant_block(
+        &mut self,
+        size: u32,
+    ) -> Option<push_constants::PushConstantBlock<A>> {
+        let index = self
+            .free_push_constant_blocks
+            .iter()
+            .position(|block| block.size() >= size)?;
+        Some(self.free_push_constant_blocks.swap_remove(index))
+    }
+
+    fn release_push_constant_blocks(
+        &mut self,
+        blocks: impl IntoIterator<Item = push_constants::PushConstantBlock<A>>,
+    ) {
+        self.free_push_constant_blocks.extend(blocks);
+    }
+
     fn dispose(self, device: &A::Device) {
         log::info!("Destroying {} command encoders", self.free_encoders.len());
         for cmd_encoder in self.free_encoders {
@@ -241,6 +281,11 @@ impl<A: hal::Api> CommandAllocator<A> {
                 device.destroy_command_encoder(cmd_encoder);
             }
         }
+        for block in self.free_push_constant_blocks {
+            unsafe {
+                block.destroy(device);
+            }
+        }
     }
 }
 
@@ -289,6 +334,16 @@ pub struct Device<A: HalApi> {
     pub(crate) limits: wgt::Limits,
     pub(crate) features: wgt::Features,
     pub(crate) downlevel: wgt::DownlevelCapabilities,
+    /// Set if push constants are emulated with uniform buffers.
+    pub(crate) push_constant_emulation: Option<push_constants::PushConstantEmulation<A>>,
+    /// Set if the arguments of indirect commands are checked on the GPU.
+    pub(crate) indirect_validation: Option<indirect_validation::IndirectValidation<A>>,
+    /// Set if new pipelines keep their descriptor, to be rebuilt when one of
+    /// their shader modules is replaced.
+    shader_hot_reload: AtomicBool,
+    /// Barrier logs of the submitted command buffers, once the barrier log is
+    /// enabled.
+    pub(crate) barrier_logs: Mutex<Option<Vec<command::BarrierLog>>>,
     //TODO: move this behind another mutex. This would allow several methods to switch
     // to borrow Device immutably, such as `write_buffer`, `write_texture`, and `buffer_unmap`.
     pending_writes: queue::PendingWrites<A>,
@@ -302,26 +357,30 @@ pub enum CreateDeviceError {
     OutOfMemory,
     #[error("failed to create internal buffer for initializing textures")]
     FailedToCreateZeroBuffer(#[from] DeviceError),
+    #[error("failed to create internal bind group layouts for emulating push constants")]
+    FailedToCreatePushConstantLayouts(#[source] DeviceError),
+    #[error("failed to create internal pipeline for validating indirect arguments")]
+    FailedToCreateIndirectValidation(#[source] DeviceError),
 }
 
 impl<A: HalApi> Device<A> {
-    pub(crate) fn require_features(&self, feature: wgt::Features) -> Result<(), MissingFeatures> {
-        if self.features.contains(feature) {
-            Ok(())
-        } else {
-            Err(MissingFeatures(feature))
+    pub(crate) fn capabilities(&self) -> checks::Capabilities<'_> {
+        checks::Capabilities {
+            features: self.features,
+            limits: &self.limits,
+            downlevel: &self.downlevel,
         }
     }
 
+    pub(crate) fn require_features(&self, feature: wgt::Features) -> Result<(), MissingFeatures> {
+        self.capabilities().require_features(feature)
+    }
+
     pub(crate) fn require_downlevel_flags(
         &self,
         flags: wgt::DownlevelFlags,
     ) -> Result<(), MissingDownlevelFlags> {
-        if self.downlevel.flags.contains(flags) {
-            Ok(())
-        } else {
-            Err(MissingDownlevelFlags(flags))
-        }
+        self.capabilities().require_downlevel_flags(flags)
     }
 }
 
@@ -343,6 +402,10 @@ impl<A: HalApi> Device<A> {
 
         let mut com_alloc = CommandAllocator {
             free_encoders: Vec::new(),
+            free_push_constant_blocks: Vec::new(),
+            reusable: desc
+                .features
+                .contains(wgt::Features::REUSABLE_COMMAND_BUFFERS),
         };
         let pending_encoder = com_alloc
             .acquire_encoder(&open.device, &open.queue)
@@ -379,6 +442,36 @@ impl<A: HalApi> Device<A> {
                 }));
         }
 
+        let push_constant_emulation = if desc
+            .features
+            .contains(wgt::Features::PUSH_CONSTANT_EMULATION)
+            && !desc.features.contains(wgt::Features::PUSH_CONSTANTS)
+        {
+            Some(unsafe {
+                push_constants::PushConstantEmulation::new(
+                    &open.device,
+                    desc.limits.max_bind_groups,
+                    &desc.limits,
+                )
+                .map_err(CreateDeviceError::FailedToCreatePushConstantLayouts)?
+            })
+        } else {
+            None
+        };
+
+        let indirect_validation = if desc.features.contains(wgt::Features::INDIRECT_VALIDATION) {
+            Some(unsafe {
+                indirect_validation::IndirectValidation::new(
+                    &open.device,
+                    &desc.limits,
+                    desc.features,
+                )
+                .map_err(CreateDeviceError::FailedToCreateIndirectValidation)?
+            })
+        } else {
+            None
+        };
+
         let life_guard = LifeGuard::new("<device>");
         let ref_count = life_guard.add_ref();
         Ok(Self {
@@ -412,6 +505,10 @@ impl<A: HalApi> Device<A> {
             limits: desc.limits.clone(),
             features: desc.features,
             downlevel,
+            push_constant_emulation,
+            indirect_validation,
+            shader_hot_reload: AtomicBool::new(false),
+            barrier_logs: Mutex::new(None),
             pending_writes,
         })
     }
@@ -583,6 +680,10 @@ impl<A: HalApi> Device<A> {
         }
 
         let mut usage = conv::map_buffer_usage(desc.usage);
+        if self.indirect_validation.is_some() && desc.usage.contains(wgt::BufferUsages::INDIRECT) {
+            // Indirect arguments are read by the validation shader.
+            usage |= hal::BufferUses::STORAGE_READ;
+        }
 
         if desc.usage.is_empty() {
             return Err(resource::CreateBufferError::EmptyUsage);
@@ -628,6 +729,16 @@ impl<A: HalApi> Device<A> {
         };
         let buffer = unsafe { self.raw.create_buffer(&hal_desc) }.map_err(DeviceError::from)?;
 
+        let index_contents = (self
+            .features
+            .contains(wgt::Features::INDEX_RANGE_VALIDATION)
+            && desc.usage.contains(wgt::BufferUsages::INDEX))
+        .then(|| {
+            // Shaders write to storage buffers behind our back.
+            let shader_writable = desc.usage.contains(wgt::BufferUsages::STORAGE);
+            Mutex::new(index_range::IndexContents::new(desc.size, shader_writable))
+        });
+
         Ok(resource::Buffer {
             raw: Some(buffer),
             device_id: Stored {
@@ -637,6 +748,7 @@ impl<A: HalApi> Device<A> {
             usage: desc.usage,
             size: desc.size,
             initialization_status: BufferInitTracker::new(desc.size),
+            index_contents,
             sync_mapped_writes: None,
             map_state: resource::BufferMapState::Idle,
             life_guard: LifeGuard::new(desc.label.borrow_or_default()),
@@ -669,6 +781,7 @@ impl<A: HalApi> Device<A> {
                 desc.mip_level_count,
                 desc.array_layer_count(),
             ),
+            zero_initialized: true,
             full_range: TextureSelector {
                 mips: 0..desc.mip_level_count,
                 layers: 0..desc.array_layer_count(),
@@ -810,7 +923,19 @@ impl<A: HalApi> Device<A> {
             ));
         }
 
-        // TODO: validate missing TextureDescriptor::view_formats.
+        if !desc.view_formats.is_empty() {
+            self.require_downlevel_flags(wgt::DownlevelFlags::VIEW_FORMATS)?;
+        }
+        let mut hal_view_formats = vec![];
+        for format in desc.view_formats.iter() {
+            if desc.format == *format {
+                continue;
+            }
+            if desc.format.remove_srgb_suffix() != format.remove_srgb_suffix() {
+                return Err(CreateTextureError::InvalidViewFormat(*format, desc.format));
+            }
+            hal_view_formats.push(*format);
+        }
 
         // Enforce having COPY_DST/DEPTH_STENCIL_WRIT/COLOR_TARGET otherwise we wouldn't be able to initialize the texture.
         let hal_usage = conv::map_texture_usage(desc.usage, desc.format.into())
@@ -841,6 +966,7 @@ impl<A: HalApi> Device<A> {
             format: desc.format,
             usage: hal_usage,
             memory_flags: hal::MemoryFlags::empty(),
+            view_formats: hal_view_formats,
         };
 
         let raw_texture = unsafe {
@@ -1042,7 +1168,7 @@ impl<A: HalApi> Device<A> {
             extent.depth_or_array_layers = view_layer_count;
         }
         let format = desc.format.unwrap_or(texture.desc.format);
-        if format != texture.desc.format {
+        if format != texture.desc.format && !texture.desc.view_formats.contains(&format) {
             return Err(resource::CreateTextureViewError::FormatReinterpretation {
                 texture: texture.desc.format,
                 view: format,
@@ -1191,81 +1317,34 @@ impl<A: HalApi> Device<A> {
         desc: &pipeline::ShaderModuleDescriptor<'a>,
         source: pipeline::ShaderModuleSource<'a>,
     ) -> Result<pipeline::ShaderModule<A>, pipeline::CreateShaderModuleError> {
-        let (module, source) = match source {
-            pipeline::ShaderModuleSource::Wgsl(code) => {
-                profiling::scope!("naga::wgsl::parse_str");
-                let module = naga::front::wgsl::parse_str(&code).map_err(|inner| {
-                    pipeline::CreateShaderModuleError::Parsing(pipeline::ShaderError {
-                        source: code.to_string(),
-                        label: desc.label.as_ref().map(|l| l.to_string()),
-                        inner,
-                    })
-                })?;
-                (module, code.into_owned())
+        let mut shader = checks::ParsedShader::new(desc, source)?;
+        let (mut validator, mut info) = self.capabilities().validate_shader(desc, &shader)?;
+        let interface = validation::Interface::new(
+            &shader.module,
+            &info,
+            &shader.overrides,
+            self.features,
+            self.limits.clone(),
+        );
+
+        // The interface is checked against pipeline layouts as written, but the
+        // backend gets the push constants as a uniform buffer in the reserved group.
+        if let Some(ref emulation) = self.push_constant_emulation {
+            if emulation.rewrite_module(&mut shader.module) {
+                info = validator
+                    .validate(&shader.module)
+                    .map_err(|inner| shader.validation_error(desc, inner))?;
+                shader
+                    .identity
+                    .amend(&format!("push constants in group {}", emulation.group));
             }
-            pipeline::ShaderModuleSource::Naga(module) => (module, String::new()),
-        };
-        for (_, var) in module.global_variables.iter() {
-            match var.binding {
-                Some(ref br) if br.group >= self.limits.max_bind_groups => {
-                    return Err(pipeline::CreateShaderModuleError::InvalidGroupIndex {
-                        bind: br.clone(),
-                        group: br.group,
-                        limit: self.limits.max_bind_groups,
-                    });
-                }
-                _ => continue,
-            };
         }
-
-        use naga::valid::Capabilities as Caps;
-        profiling::scope!("naga::validate");
-
-        let mut caps = Caps::empty();
-        caps.set(
-            Caps::PUSH_CONSTANT,
-            self.features.contains(wgt::Features::PUSH_CONSTANTS),
-        );
-        caps.set(
-            Caps::FLOAT64,
-            self.features.contains(wgt::Features::SHADER_FLOAT64),
-        );
-        caps.set(
-            Caps::PRIMITIVE_INDEX,
-            self.features
-                .contains(wgt::Features::SHADER_PRIMITIVE_INDEX),
-        );
-        caps.set(
-            Caps::SAMPLED_TEXTURE_AND_STORAGE_BUFFER_ARRAY_NON_UNIFORM_INDEXING,
-            self.features.contains(
-                wgt::Features::SAMPLED_TEXTURE_AND_STORAGE_BUFFER_ARRAY_NON_UNIFORM_INDEXING,
-            ),
-        );
-        caps.set(
-            Caps::UNIFORM_BUFFER_AND_STORAGE_TEXTURE_ARRAY_NON_UNIFORM_INDEXING,
-            self.features.contains(
-                wgt::Features::UNIFORM_BUFFER_AND_STORAGE_TEXTURE_ARRAY_NON_UNIFORM_INDEXING,
-            ),
-        );
-        // TODO: This needs a proper wgpu feature
-        caps.set(
-            Caps::SAMPLER_NON_UNIFORM_INDEXING,
-            self.features.contains(
-                wgt::Features::SAMPLED_TEXTURE_AND_STORAGE_BUFFER_ARRAY_NON_UNIFORM_INDEXING,
-            ),
-        );
-        let info = naga::valid::Validator::new(naga::valid::ValidationFlags::all(), caps)
-            .validate(&module)
-            .map_err(|inner| {
-                pipeline::CreateShaderModuleError::Validation(pipeline::ShaderError {
-                    source,
-                    label: desc.label.as_ref().map(|l| l.to_string()),
-                    inner,
-                })
-            })?;
-        let interface =
-            validation::Interface::new(&module, &info, self.features, self.limits.clone());
-        let hal_shader = hal::ShaderInput::Naga(hal::NagaShader { module, info });
+        let hal_shader = hal::ShaderInput::Naga(hal::NagaShader {
+            module: shader.module,
+            info,
+            identity: shader.identity,
+            overrides: shader.overrides,
+        });
 
         let hal_desc = hal::ShaderModuleDescriptor {
             label: desc.label.borrow_option(),
@@ -1293,8 +1372,7 @@ impl<A: HalApi> Device<A> {
                 ref_count: self.life_guard.add_ref(),
             },
             interface: Some(interface),
-            #[cfg(debug_assertions)]
-            label: desc.label.borrow_or_default().to_string(),
+            life_guard: LifeGuard::new(desc.label.borrow_or_default()),
         })
     }
 
@@ -1333,8 +1411,31 @@ impl<A: HalApi> Device<A> {
                 ref_count: self.life_guard.add_ref(),
             },
             interface: None,
-            #[cfg(debug_assertions)]
-            label: desc.label.borrow_or_default().to_string(),
+            life_guard: LifeGuard::new(desc.label.borrow_or_default()),
+        })
+    }
+
+    unsafe fn create_pipeline_cache(
+        &self,
+        self_id: id::DeviceId,
+        desc: &pipeline::PipelineCacheDescriptor,
+    ) -> Result<pipeline::PipelineCache<A>, pipeline::CreatePipelineCacheError> {
+        let hal_desc = hal::PipelineCacheDescriptor {
+            label: desc.label.borrow_option(),
+            data: desc.data.as_deref(),
+        };
+        let raw = self
+            .raw
+            .create_pipeline_cache(&hal_desc)
+            .map_err(DeviceError::from)?;
+
+        Ok(pipeline::PipelineCache {
+            raw,
+            device_id: Stored {
+                value: id::Valid(self_id),
+                ref_count: self.life_guard.add_ref(),
+            },
+            life_guard: LifeGuard::new(desc.label.borrow_or_default()),
         })
     }
 
@@ -1407,145 +1508,7 @@ impl<A: HalApi> Device<A> {
         label: Option<&str>,
         entry_map: binding_model::BindEntryMap,
     ) -> Result<binding_model::BindGroupLayout<A>, binding_model::CreateBindGroupLayoutError> {
-        #[derive(PartialEq)]
-        enum WritableStorage {
-            Yes,
-            No,
-        }
-
-        for entry in entry_map.values() {
-            use wgt::BindingType as Bt;
-
-            let mut required_features = wgt::Features::empty();
-            let mut required_downlevel_flags = wgt::DownlevelFlags::empty();
-            let (array_feature, writable_storage) = match entry.ty {
-                Bt::Buffer {
-                    ty: wgt::BufferBindingType::Uniform,
-                    has_dynamic_offset: false,
-                    min_binding_size: _,
-                } => (
-                    Some(wgt::Features::BUFFER_BINDING_ARRAY),
-                    WritableStorage::No,
-                ),
-                Bt::Buffer {
-                    ty: wgt::BufferBindingType::Uniform,
-                    has_dynamic_offset: true,
-                    min_binding_size: _,
-                } => (
-                    Some(wgt::Features::BUFFER_BINDING_ARRAY),
-                    WritableStorage::No,
-                ),
-                Bt::Buffer {
-                    ty: wgt::BufferBindingType::Storage { read_only },
-                    ..
-                } => (
-                    Some(
-                        wgt::Features::BUFFER_BINDING_ARRAY
-                            | wgt::Features::STORAGE_RESOURCE_BINDING_ARRAY,
-                    ),
-                    match read_only {
-                        true => WritableStorage::No,
-                        false => WritableStorage::Yes,
-                    },
-                ),
-                Bt::Sampler { .. } => (
-                    Some(wgt::Features::TEXTURE_BINDING_ARRAY),
-                    WritableStorage::No,
-                ),
-                Bt::Texture { .. } => (
-                    Some(wgt::Features::TEXTURE_BINDING_ARRAY),
-                    WritableStorage::No,
-                ),
-                Bt::StorageTexture {
-                    access,
-                    view_dimension,
-                    format: _,
-                } => {
-                    match view_dimension {
-                        wgt::TextureViewDimension::Cube | wgt::TextureViewDimension::CubeArray => {
-                            return Err(binding_model::CreateBindGroupLayoutError::Entry {
-                                binding: entry.binding,
-                                error: binding_model::BindGroupLayoutEntryError::StorageTextureCube,
-                            })
-                        }
-                        _ => (),
-                    }
-                    match access {
-                        wgt::StorageTextureAccess::ReadOnly
-                        | wgt::StorageTextureAccess::ReadWrite
-                            if !self.features.contains(
-                                wgt::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES,
-                            ) =>
-                        {
-                            return Err(binding_model::CreateBindGroupLayoutError::Entry {
-                                binding: entry.binding,
-                                error: binding_model::BindGroupLayoutEntryError::StorageTextureReadWrite,
-                            });
-                        }
-                        _ => (),
-                    }
-                    (
-                        Some(
-                            wgt::Features::TEXTURE_BINDING_ARRAY
-                                | wgt::Features::STORAGE_RESOURCE_BINDING_ARRAY,
-                        ),
-                        match access {
-                            wgt::StorageTextureAccess::WriteOnly => WritableStorage::Yes,
-                            wgt::StorageTextureAccess::ReadOnly => {
-                                required_features |=
-                                    wgt::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES;
-                                WritableStorage::No
-                            }
-                            wgt::StorageTextureAccess::ReadWrite => {
-                                required_features |=
-                                    wgt::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES;
-                                WritableStorage::Yes
-                            }
-                        },
-                    )
-                }
-            };
-
-            // Validate the count parameter
-            if entry.count.is_some() {
-                required_features |= array_feature
-                    .ok_or(binding_model::BindGroupLayoutEntryError::ArrayUnsupported)
-                    .map_err(|error| binding_model::CreateBindGroupLayoutError::Entry {
-                        binding: entry.binding,
-                        error,
-                    })?;
-            }
-            if entry.visibility.contains(wgt::ShaderStages::VERTEX) {
-                if writable_storage == WritableStorage::Yes {
-                    required_features |= wgt::Features::VERTEX_WRITABLE_STORAGE;
-                }
-                if let Bt::Buffer {
-                    ty: wgt::BufferBindingType::Storage { .. },
-                    ..
-                } = entry.ty
-                {
-                    required_downlevel_flags |= wgt::DownlevelFlags::VERTEX_STORAGE;
-                }
-            }
-            if writable_storage == WritableStorage::Yes
-                && entry.visibility.contains(wgt::ShaderStages::FRAGMENT)
-            {
-                required_downlevel_flags |= wgt::DownlevelFlags::FRAGMENT_WRITABLE_STORAGE;
-            }
-
-            self.require_features(required_features)
-                .map_err(binding_model::BindGroupLayoutEntryError::MissingFeatures)
-                .map_err(|error| binding_model::CreateBindGroupLayoutError::Entry {
-                    binding: entry.binding,
-                    error,
-                })?;
-            self.require_downlevel_flags(required_downlevel_flags)
-                .map_err(binding_model::BindGroupLayoutEntryError::MissingDownlevelFlags)
-                .map_err(|error| binding_model::CreateBindGroupLayoutError::Entry {
-                    binding: entry.binding,
-                    error,
-                })?;
-        }
+        let count_validator = self.capabilities().check_bind_group_layout(&entry_map)?;
 
         let bgl_flags = conv::bind_group_layout_flags(self.features);
 
@@ -1562,16 +1525,6 @@ impl<A: HalApi> Device<A> {
                 .map_err(DeviceError::from)?
         };
 
-        let mut count_validator = binding_model::BindingTypeMaxCountValidator::default();
-        for entry in entry_map.values() {
-            count_validator.add_binding(entry);
-        }
-        // If a single bind group layout violates limits, the pipeline layout is definitely
-        // going to violate limits too, lets catch it now.
-        count_validator
-            .validate(&self.limits)
-            .map_err(binding_model::CreateBindGroupLayoutError::TooManyBindings)?;
-
         Ok(binding_model::BindGroupLayout {
             raw,
             device_id: Stored {
@@ -1646,7 +1599,7 @@ impl<A: HalApi> Device<A> {
 
         let buffer = used
             .buffers
-            .add_single(storage, bb.buffer_id, internal_use)
+            .add_single(storage, bb.buffer_id, internal_use, binding)
             .ok_or(Error::InvalidBuffer(bb.buffer_id))?;
         check_buffer_usage(buffer.usage, pub_usage)?;
         let raw_buffer = buffer
@@ -1715,6 +1668,7 @@ impl<A: HalApi> Device<A> {
     }
 
     fn create_texture_binding(
+        binding: u32,
         view: &resource::TextureView<A>,
         texture_guard: &Storage<resource::Texture<A>, id::TextureId>,
         internal_use: hal::TextureUses,
@@ -1732,6 +1686,7 @@ impl<A: HalApi> Device<A> {
                 view.parent_id.ref_count.clone(),
                 Some(view.selector.clone()),
                 internal_use,
+                binding,
             )
             .ok_or(binding_model::CreateBindGroupError::InvalidTexture(
                 view.parent_id.value.0,
@@ -1909,6 +1864,7 @@ impl<A: HalApi> Device<A> {
                         "SampledTexture, ReadonlyStorageTexture or WriteonlyStorageTexture",
                     )?;
                     Self::create_texture_binding(
+                        binding,
                         view,
                         &texture_guard,
                         internal_use,
@@ -1937,6 +1893,7 @@ impl<A: HalApi> Device<A> {
                             Self::texture_use_parameters(binding, decl, view,
                                                          "SampledTextureArray, ReadonlyStorageTextureArray or WriteonlyStorageTextureArray")?;
                         Self::create_texture_binding(
+                            binding,
                             view,
                             &texture_guard,
                             internal_use,
@@ -2173,52 +2130,8 @@ impl<A: HalApi> Device<A> {
     ) -> Result<binding_model::PipelineLayout<A>, binding_model::CreatePipelineLayoutError> {
         use crate::binding_model::CreatePipelineLayoutError as Error;
 
-        let bind_group_layouts_count = desc.bind_group_layouts.len();
-        let device_max_bind_groups = self.limits.max_bind_groups as usize;
-        if bind_group_layouts_count > device_max_bind_groups {
-            return Err(Error::TooManyGroups {
-                actual: bind_group_layouts_count,
-                max: device_max_bind_groups,
-            });
-        }
-
-        if !desc.push_constant_ranges.is_empty() {
-            self.require_features(wgt::Features::PUSH_CONSTANTS)?;
-        }
-
-        let mut used_stages = wgt::ShaderStages::empty();
-        for (index, pc) in desc.push_constant_ranges.iter().enumerate() {
-            if pc.stages.intersects(used_stages) {
-                return Err(Error::MoreThanOnePushConstantRangePerStage {
-                    index,
-                    provided: pc.stages,
-                    intersected: pc.stages & used_stages,
-                });
-            }
-            used_stages |= pc.stages;
-
-            let device_max_pc_size = self.limits.max_push_constant_size;
-            if device_max_pc_size < pc.range.end {
-                return Err(Error::PushConstantRangeTooLarge {
-                    index,
-                    range: pc.range.clone(),
-                    max: device_max_pc_size,
-                });
-            }
-
-            if pc.range.start % wgt::PUSH_CONSTANT_ALIGNMENT != 0 {
-                return Err(Error::MisalignedPushConstantRange {
-                    index,
-                    bound: pc.range.start,
-                });
-            }
-            if pc.range.end % wgt::PUSH_CONSTANT_ALIGNMENT != 0 {
-                return Err(Error::MisalignedPushConstantRange {
-                    index,
-                    bound: pc.range.end,
-                });
-            }
-        }
+        self.capabilities()
+            .check_pipeline_layout(desc.bind_group_layouts.len(), &desc.push_constant_ranges)?;
 
         let mut count_validator = binding_model::BindingTypeMaxCountValidator::default();
 
@@ -2233,16 +2146,23 @@ impl<A: HalApi> Device<A> {
             .validate(&self.limits)
             .map_err(Error::TooManyBindings)?;
 
-        let bgl_vec = desc
+        let mut bgl_vec = desc
             .bind_group_layouts
             .iter()
             .map(|&id| &bgl_guard.get(id).unwrap().raw)
             .collect::<Vec<_>>();
+        let mut push_constant_ranges = desc.push_constant_ranges.as_ref();
+        if let Some(ref emulation) = self.push_constant_emulation {
+            if !push_constant_ranges.is_empty() {
+                bgl_vec.extend(emulation.extra_bind_group_layouts(bgl_vec.len()));
+                push_constant_ranges = &[];
+            }
+        }
         let hal_desc = hal::PipelineLayoutDescriptor {
             label: desc.label.borrow_option(),
             flags: hal::PipelineLayoutFlags::BASE_VERTEX_INSTANCE,
             bind_group_layouts: &bgl_vec,
-            push_constant_ranges: desc.push_constant_ranges.as_ref(),
+            push_constant_ranges,
         };
 
         let raw = unsafe {
@@ -2277,16 +2197,10 @@ impl<A: HalApi> Device<A> {
         &self,
         self_id: id::DeviceId,
         implicit_context: Option<ImplicitPipelineContext>,
-        mut derived_group_layouts: ArrayVec<binding_model::BindEntryMap, { hal::MAX_BIND_GROUPS }>,
+        derived_group_layouts: ArrayVec<binding_model::BindEntryMap, { hal::MAX_BIND_GROUPS }>,
         bgl_guard: &mut Storage<binding_model::BindGroupLayout<A>, id::BindGroupLayoutId>,
         pipeline_layout_guard: &mut Storage<binding_model::PipelineLayout<A>, id::PipelineLayoutId>,
     ) -> Result<id::PipelineLayoutId, pipeline::ImplicitLayoutError> {
-        while derived_group_layouts
-            .last()
-            .map_or(false, |map| map.is_empty())
-        {
-            derived_group_layouts.pop();
-        }
         let mut ids = implicit_context.ok_or(pipeline::ImplicitLayoutError::MissingIds(0))?;
         let group_count = derived_group_layouts.len();
         if ids.group_ids.len() < group_count {
@@ -2325,6 +2239,7 @@ impl<A: HalApi> Device<A> {
         self_id: id::DeviceId,
         desc: &pipeline::ComputePipelineDescriptor,
         implicit_context: Option<ImplicitPipelineContext>,
+        sources: Option<&PipelineSources>,
         hub: &Hub<A, G>,
         token: &mut Token<Self>,
     ) -> Result<pipeline::ComputePipeline<A>, pipeline::CreateComputePipelineError> {
@@ -2343,45 +2258,45 @@ impl<A: HalApi> Device<A> {
 
         self.require_downlevel_flags(wgt::DownlevelFlags::COMPUTE_SHADERS)?;
 
-        let mut derived_group_layouts =
-            ArrayVec::<binding_model::BindEntryMap, { hal::MAX_BIND_GROUPS }>::new();
-        let mut shader_binding_sizes = FastHashMap::default();
-
         let io = validation::StageIo::default();
-        let (shader_module_guard, _) = hub.shader_modules.read(&mut token);
+        let (shader_module_guard, mut token) = hub.shader_modules.read(&mut token);
+        let (cache_guard, _) = hub.pipeline_caches.read(&mut token);
 
         let shader_module = shader_module_guard
             .get(desc.stage.module)
             .map_err(|_| validation::StageError::InvalidModule)?;
+        let cache = match desc.cache {
+            Some(cache_id) => Some(
+                cache_guard
+                    .get(cache_id)
+                    .map_err(|_| pipeline::CreateComputePipelineError::InvalidCache)?,
+            ),
+            None => None,
+        };
 
-        {
-            let flag = wgt::ShaderStages::COMPUTE;
-            let provided_layouts = match desc.layout {
+        let mut layouts = checks::StageLayouts::new(
+            match desc.layout {
                 Some(pipeline_layout_id) => Some(Device::get_introspection_bind_group_layouts(
                     pipeline_layout_guard
                         .get(pipeline_layout_id)
                         .map_err(|_| pipeline::CreateComputePipelineError::InvalidLayout)?,
                     &*bgl_guard,
                 )),
-                None => {
-                    for _ in 0..self.limits.max_bind_groups {
-                        derived_group_layouts.push(binding_model::BindEntryMap::default());
-                    }
-                    None
-                }
-            };
-            if let Some(ref interface) = shader_module.interface {
-                let _ = interface.check_stage(
-                    provided_layouts.as_ref().map(|p| p.as_slice()),
-                    &mut derived_group_layouts,
-                    &mut shader_binding_sizes,
-                    &desc.stage.entry_point,
-                    flag,
-                    io,
-                    None,
-                )?;
-            }
+                None => None,
+            },
+            &self.limits,
+        );
+        if let Some(ref interface) = shader_module.interface {
+            let _ = layouts.check_stage(
+                interface,
+                wgt::ShaderStages::COMPUTE,
+                &desc.stage.entry_point,
+                &desc.stage.constants,
+                io,
+                None,
+            )?;
         }
+        let (derived_group_layouts, shader_binding_sizes) = layouts.finish();
 
         let pipeline_layout_id = match desc.layout {
             Some(id) => id,
@@ -2393,6 +2308,10 @@ impl<A: HalApi> Device<A> {
                 &mut *pipeline_layout_guard,
             )?,
         };
+        // Nothing is written past this point. Keep reading the layouts, but let
+        // other device calls through while the backend compiles the pipeline.
+        let pipeline_layout_guard = RwLockWriteGuard::downgrade(pipeline_layout_guard);
+        let bgl_guard = RwLockWriteGuard::downgrade(bgl_guard);
         let layout = pipeline_layout_guard
             .get(pipeline_layout_id)
             .map_err(|_| pipeline::CreateComputePipelineError::InvalidLayout)?;
@@ -2406,7 +2325,9 @@ impl<A: HalApi> Device<A> {
             stage: hal::ProgrammableStage {
                 entry_point: desc.stage.entry_point.as_ref(),
                 module: &shader_module.raw,
+                constants: desc.stage.constants.as_ref(),
             },
+            cache: cache.map(|cache| &cache.raw),
         };
 
         let raw =
@@ -2421,6 +2342,11 @@ impl<A: HalApi> Device<A> {
                     hal::PipelineError::EntryPoint(_stage) => {
                         pipeline::CreateComputePipelineError::Internal(EP_FAILURE.to_string())
                     }
+                    hal::PipelineError::UnsupportedVertexFormat(format) => {
+                        pipeline::CreateComputePipelineError::Internal(
+                            hal::PipelineError::UnsupportedVertexFormat(format).to_string(),
+                        )
+                    }
                 },
             )?;
 
@@ -2428,7 +2354,7 @@ impl<A: HalApi> Device<A> {
             raw,
             layout_id: Stored {
                 value: id::Valid(pipeline_layout_id),
-                ref_count: layout.life_guard.add_ref(),
+                ref_count: PipelineSources::layout_ref(sources, layout),
             },
             device_id: Stored {
                 value: id::Valid(self_id),
@@ -2436,6 +2362,8 @@ impl<A: HalApi> Device<A> {
             },
             late_sized_buffer_groups,
             life_guard: LifeGuard::new(desc.label.borrow_or_default()),
+            reload_desc: None,
+            retired: Vec::new(),
         };
         Ok(pipeline)
     }
@@ -2446,11 +2374,10 @@ impl<A: HalApi> Device<A> {
         adapter: &crate::instance::Adapter<A>,
         desc: &pipeline::RenderPipelineDescriptor,
         implicit_context: Option<ImplicitPipelineContext>,
+        sources: Option<&PipelineSources>,
         hub: &Hub<A, G>,
         token: &mut Token<Self>,
     ) -> Result<pipeline::RenderPipeline<A>, pipeline::CreateRenderPipelineError> {
-        use wgt::TextureFormatFeatureFlags as Tfff;
-
         //TODO: only lock mutable if the layout is derived
         let (mut pipeline_layout_guard, mut token) = hub.pipeline_layouts.write(token);
         let (mut bgl_guard, mut token) = hub.bind_group_layouts.write(&mut token);
@@ -2464,45 +2391,25 @@ impl<A: HalApi> Device<A> {
             }
         }
 
-        let mut derived_group_layouts =
-            ArrayVec::<binding_model::BindEntryMap, { hal::MAX_BIND_GROUPS }>::new();
-        let mut shader_binding_sizes = FastHashMap::default();
-
-        let num_attachments = desc.fragment.as_ref().map(|f| f.targets.len()).unwrap_or(0);
-        if num_attachments > hal::MAX_COLOR_ATTACHMENTS {
-            return Err(
-                pipeline::CreateRenderPipelineError::TooManyColorAttachments {
-                    given: num_attachments as u32,
-                    limit: hal::MAX_COLOR_ATTACHMENTS as u32,
-                },
-            );
-        }
-
         let color_targets = desc
             .fragment
             .as_ref()
             .map_or(&[][..], |fragment| &fragment.targets);
         let depth_stencil_state = desc.depth_stencil.as_ref();
 
-        let cts: ArrayVec<_, { hal::MAX_COLOR_ATTACHMENTS }> =
-            color_targets.iter().filter_map(|x| x.as_ref()).collect();
-        if !cts.is_empty() && {
-            let first = &cts[0];
-            cts[1..]
-                .iter()
-                .any(|ct| ct.write_mask != first.write_mask || ct.blend != first.blend)
-        } {
-            log::info!("Color targets: {:?}", color_targets);
-            self.require_downlevel_flags(wgt::DownlevelFlags::INDEPENDENT_BLEND)?;
-        }
-
-        let mut io = validation::StageIo::default();
+        let mut io = self.capabilities().check_render_pipeline_state(
+            &desc.vertex.buffers,
+            &desc.primitive,
+            depth_stencil_state,
+            &desc.multisample,
+            color_targets,
+            |format| self.describe_format_features(adapter, format),
+        )?;
         let mut validated_stages = wgt::ShaderStages::empty();
 
         let mut vertex_steps = Vec::with_capacity(desc.vertex.buffers.len());
         let mut vertex_buffers = Vec::with_capacity(desc.vertex.buffers.len());
-        let mut total_attributes = 0;
-        for (i, vb_state) in desc.vertex.buffers.iter().enumerate() {
+        for vb_state in desc.vertex.buffers.iter() {
             vertex_steps.alloc().init(pipeline::VertexStep {
                 stride: vb_state.array_stride,
                 mode: vb_state.step_mode,
@@ -2510,224 +2417,73 @@ impl<A: HalApi> Device<A> {
             if vb_state.attributes.is_empty() {
                 continue;
             }
-            if vb_state.array_stride > self.limits.max_vertex_buffer_array_stride as u64 {
-                return Err(pipeline::CreateRenderPipelineError::VertexStrideTooLarge {
-                    index: i as u32,
-                    given: vb_state.array_stride as u32,
-                    limit: self.limits.max_vertex_buffer_array_stride,
-                });
-            }
-            if vb_state.array_stride % wgt::VERTEX_STRIDE_ALIGNMENT != 0 {
-                return Err(pipeline::CreateRenderPipelineError::UnalignedVertexStride {
-                    index: i as u32,
-                    stride: vb_state.array_stride,
-                });
-            }
             vertex_buffers.alloc().init(hal::VertexBufferLayout {
                 array_stride: vb_state.array_stride,
                 step_mode: vb_state.step_mode,
                 attributes: vb_state.attributes.as_ref(),
             });
-
-            for attribute in vb_state.attributes.iter() {
-                if attribute.offset >= 0x10000000 {
-                    return Err(
-                        pipeline::CreateRenderPipelineError::InvalidVertexAttributeOffset {
-                            location: attribute.shader_location,
-                            offset: attribute.offset,
-                        },
-                    );
-                }
-
-                if let wgt::VertexFormat::Float64
-                | wgt::VertexFormat::Float64x2
-                | wgt::VertexFormat::Float64x3
-                | wgt::VertexFormat::Float64x4 = attribute.format
-                {
-                    self.require_features(wgt::Features::VERTEX_ATTRIBUTE_64BIT)?;
-                }
-
-                io.insert(
-                    attribute.shader_location,
-                    validation::InterfaceVar::vertex_attribute(attribute.format),
-                );
-            }
-            total_attributes += vb_state.attributes.len();
-        }
-
-        if vertex_buffers.len() > self.limits.max_vertex_buffers as usize {
-            return Err(pipeline::CreateRenderPipelineError::TooManyVertexBuffers {
-                given: vertex_buffers.len() as u32,
-                limit: self.limits.max_vertex_buffers,
-            });
-        }
-        if total_attributes > self.limits.max_vertex_attributes as usize {
-            return Err(
-                pipeline::CreateRenderPipelineError::TooManyVertexAttributes {
-                    given: total_attributes as u32,
-                    limit: self.limits.max_vertex_attributes,
-                },
-            );
         }
 
-        if desc.primitive.strip_index_format.is_some() && !desc.primitive.topology.is_strip() {
-            return Err(
-                pipeline::CreateRenderPipelineError::StripIndexFormatForNonStripTopology {
-                    strip_index_format: desc.primitive.strip_index_format,
-                    topology: desc.primitive.topology,
-                },
-            );
-        }
+        let mut layouts = checks::StageLayouts::new(
+            match desc.layout {
+                Some(pipeline_layout_id) => Some(Device::get_introspection_bind_group_layouts(
+                    pipeline_layout_guard
+                        .get(pipeline_layout_id)
+                        .map_err(|_| pipeline::CreateRenderPipelineError::InvalidLayout)?,
+                    &*bgl_guard,
+                )),
+                None => None,
+            },
+            &self.limits,
+        );
 
-        if desc.primitive.unclipped_depth {
-            self.require_features(wgt::Features::DEPTH_CLIP_CONTROL)?;
-        }
+        let samples = desc.multisample.count;
 
-        if desc.primitive.polygon_mode == wgt::PolygonMode::Line {
-            self.require_features(wgt::Features::POLYGON_MODE_LINE)?;
-        }
-        if desc.primitive.polygon_mode == wgt::PolygonMode::Point {
-            self.require_features(wgt::Features::POLYGON_MODE_POINT)?;
-        }
+        let (shader_module_guard, mut token) = hub.shader_modules.read(&mut token);
+        let (cache_guard, _) = hub.pipeline_caches.read(&mut token);
+        let cache = match desc.cache {
+            Some(cache_id) => Some(
+                cache_guard
+                    .get(cache_id)
+                    .map_err(|_| pipeline::CreateRenderPipelineError::InvalidCache)?,
+            ),
+            None => None,
+        };
 
-        if desc.primitive.conservative {
-            self.require_features(wgt::Features::CONSERVATIVE_RASTERIZATION)?;
-        }
+        let vertex_stage = {
+            let stage = &desc.vertex.stage;
+            let flag = wgt::ShaderStages::VERTEX;
 
-        if desc.primitive.conservative && desc.primitive.polygon_mode != wgt::PolygonMode::Fill {
-            return Err(
-                pipeline::CreateRenderPipelineError::ConservativeRasterizationNonFillPolygonMode,
-            );
-        }
+            let shader_module = shader_module_guard.get(stage.module).map_err(|_| {
+                pipeline::CreateRenderPipelineError::Stage {
+                    stage: flag,
+                    error: validation::StageError::InvalidModule,
+                }
+            })?;
 
-        for (i, cs) in color_targets.iter().enumerate() {
-            if let Some(cs) = cs.as_ref() {
-                let error = loop {
-                    let format_features = self.describe_format_features(adapter, cs.format)?;
-                    if !format_features
-                        .allowed_usages
-                        .contains(wgt::TextureUsages::RENDER_ATTACHMENT)
-                    {
-                        break Some(pipeline::ColorStateError::FormatNotRenderable(cs.format));
-                    }
-                    if cs.blend.is_some() && !format_features.flags.contains(Tfff::FILTERABLE) {
-                        break Some(pipeline::ColorStateError::FormatNotBlendable(cs.format));
-                    }
-                    if !hal::FormatAspects::from(cs.format).contains(hal::FormatAspects::COLOR) {
-                        break Some(pipeline::ColorStateError::FormatNotColor(cs.format));
-                    }
-                    if desc.multisample.count > 1
-                        && !format_features.flags.contains(Tfff::MULTISAMPLE)
-                    {
-                        break Some(pipeline::ColorStateError::FormatNotMultisampled(cs.format));
-                    }
+            if let Some(ref interface) = shader_module.interface {
+                io = layouts
+                    .check_stage(
+                        interface,
+                        flag,
+                        &stage.entry_point,
+                        &stage.constants,
+                        io,
+                        desc.depth_stencil.as_ref().map(|d| d.depth_compare),
+                    )
+                    .map_err(|error| pipeline::CreateRenderPipelineError::Stage {
+                        stage: flag,
+                        error,
+                    })?;
+                validated_stages |= flag;
+            }
 
-                    break None;
-                };
-                if let Some(e) = error {
-                    return Err(pipeline::CreateRenderPipelineError::ColorState(i as u8, e));
-                }
+            hal::ProgrammableStage {
+                module: &shader_module.raw,
+                entry_point: stage.entry_point.as_ref(),
+                constants: stage.constants.as_ref(),
             }
-        }
-
-        if let Some(ds) = depth_stencil_state {
-            let error = loop {
-                let format_features = self.describe_format_features(adapter, ds.format)?;
-                if !format_features
-                    .allowed_usages
-                    .contains(wgt::TextureUsages::RENDER_ATTACHMENT)
-                {
-                    break Some(pipeline::DepthStencilStateError::FormatNotRenderable(
-                        ds.format,
-                    ));
-                }
-
-                let aspect = hal::FormatAspects::from(ds.format);
-                if ds.is_depth_enabled() && !aspect.contains(hal::FormatAspects::DEPTH) {
-                    break Some(pipeline::DepthStencilStateError::FormatNotDepth(ds.format));
-                }
-                if ds.stencil.is_enabled() && !aspect.contains(hal::FormatAspects::STENCIL) {
-                    break Some(pipeline::DepthStencilStateError::FormatNotStencil(
-                        ds.format,
-                    ));
-                }
-                if desc.multisample.count > 1 && !format_features.flags.contains(Tfff::MULTISAMPLE)
-                {
-                    break Some(pipeline::DepthStencilStateError::FormatNotMultisampled(
-                        ds.format,
-                    ));
-                }
-
-                break None;
-            };
-            if let Some(e) = error {
-                return Err(pipeline::CreateRenderPipelineError::DepthStencilState(e));
-            }
-        }
-
-        if desc.layout.is_none() {
-            for _ in 0..self.limits.max_bind_groups {
-                derived_group_layouts.push(binding_model::BindEntryMap::default());
-            }
-        }
-
-        let samples = {
-            let sc = desc.multisample.count;
-            if sc == 0 || sc > 32 || !conv::is_power_of_two_u32(sc) {
-                return Err(pipeline::CreateRenderPipelineError::InvalidSampleCount(sc));
-            }
-            sc
-        };
-
-        let (shader_module_guard, _) = hub.shader_modules.read(&mut token);
-
-        let vertex_stage = {
-            let stage = &desc.vertex.stage;
-            let flag = wgt::ShaderStages::VERTEX;
-
-            let shader_module = shader_module_guard.get(stage.module).map_err(|_| {
-                pipeline::CreateRenderPipelineError::Stage {
-                    stage: flag,
-                    error: validation::StageError::InvalidModule,
-                }
-            })?;
-
-            let provided_layouts = match desc.layout {
-                Some(pipeline_layout_id) => {
-                    let pipeline_layout = pipeline_layout_guard
-                        .get(pipeline_layout_id)
-                        .map_err(|_| pipeline::CreateRenderPipelineError::InvalidLayout)?;
-                    Some(Device::get_introspection_bind_group_layouts(
-                        pipeline_layout,
-                        &*bgl_guard,
-                    ))
-                }
-                None => None,
-            };
-
-            if let Some(ref interface) = shader_module.interface {
-                io = interface
-                    .check_stage(
-                        provided_layouts.as_ref().map(|p| p.as_slice()),
-                        &mut derived_group_layouts,
-                        &mut shader_binding_sizes,
-                        &stage.entry_point,
-                        flag,
-                        io,
-                        desc.depth_stencil.as_ref().map(|d| d.depth_compare),
-                    )
-                    .map_err(|error| pipeline::CreateRenderPipelineError::Stage {
-                        stage: flag,
-                        error,
-                    })?;
-                validated_stages |= flag;
-            }
-
-            hal::ProgrammableStage {
-                module: &shader_module.raw,
-                entry_point: stage.entry_point.as_ref(),
-            }
-        };
+        };
 
         let fragment_stage = match desc.fragment {
             Some(ref fragment) => {
@@ -2741,25 +2497,14 @@ impl<A: HalApi> Device<A> {
                             error: validation::StageError::InvalidModule,
                         })?;
 
-                let provided_layouts = match desc.layout {
-                    Some(pipeline_layout_id) => Some(Device::get_introspection_bind_group_layouts(
-                        pipeline_layout_guard
-                            .get(pipeline_layout_id)
-                            .map_err(|_| pipeline::CreateRenderPipelineError::InvalidLayout)?,
-                        &*bgl_guard,
-                    )),
-                    None => None,
-                };
-
                 if validated_stages == wgt::ShaderStages::VERTEX {
                     if let Some(ref interface) = shader_module.interface {
-                        io = interface
+                        io = layouts
                             .check_stage(
-                                provided_layouts.as_ref().map(|p| p.as_slice()),
-                                &mut derived_group_layouts,
-                                &mut shader_binding_sizes,
-                                &fragment.stage.entry_point,
+                                interface,
                                 flag,
+                                &fragment.stage.entry_point,
+                                &fragment.stage.constants,
                                 io,
                                 desc.depth_stencil.as_ref().map(|d| d.depth_compare),
                             )
@@ -2774,38 +2519,20 @@ impl<A: HalApi> Device<A> {
                 Some(hal::ProgrammableStage {
                     module: &shader_module.raw,
                     entry_point: fragment.stage.entry_point.as_ref(),
+                    constants: fragment.stage.constants.as_ref(),
                 })
             }
             None => None,
         };
 
         if validated_stages.contains(wgt::ShaderStages::FRAGMENT) {
-            for (i, output) in io.iter() {
-                match color_targets.get(*i as usize) {
-                    Some(&Some(ref state)) => {
-                        validation::check_texture_format(state.format, &output.ty).map_err(
-                            |pipeline| {
-                                pipeline::CreateRenderPipelineError::ColorState(
-                                    *i as u8,
-                                    pipeline::ColorStateError::IncompatibleFormat {
-                                        pipeline,
-                                        shader: output.ty,
-                                    },
-                                )
-                            },
-                        )?;
-                    }
-                    _ => {
-                        log::info!(
-                            "The fragment stage {:?} output @location({}) values are ignored",
-                            fragment_stage
-                                .as_ref()
-                                .map_or("", |stage| stage.entry_point),
-                            i
-                        );
-                    }
-                }
-            }
+            checks::check_fragment_outputs(
+                &io,
+                color_targets,
+                fragment_stage
+                    .as_ref()
+                    .map_or("", |stage| stage.entry_point),
+            )?;
         }
         let last_stage = match desc.fragment {
             Some(_) => wgt::ShaderStages::FRAGMENT,
@@ -2814,6 +2541,7 @@ impl<A: HalApi> Device<A> {
         if desc.layout.is_none() && !validated_stages.contains(last_stage) {
             return Err(pipeline::ImplicitLayoutError::ReflectionError(last_stage).into());
         }
+        let (derived_group_layouts, shader_binding_sizes) = layouts.finish();
 
         let pipeline_layout_id = match desc.layout {
             Some(id) => id,
@@ -2825,6 +2553,10 @@ impl<A: HalApi> Device<A> {
                 &mut *pipeline_layout_guard,
             )?,
         };
+        // Nothing is written past this point. Keep reading the layouts, but let
+        // other device calls through while the backend compiles the pipeline.
+        let pipeline_layout_guard = RwLockWriteGuard::downgrade(pipeline_layout_guard);
+        let bgl_guard = RwLockWriteGuard::downgrade(bgl_guard);
         let layout = pipeline_layout_guard
             .get(pipeline_layout_id)
             .map_err(|_| pipeline::CreateRenderPipelineError::InvalidLayout)?;
@@ -2856,6 +2588,7 @@ impl<A: HalApi> Device<A> {
             fragment_stage,
             color_targets,
             multiview: desc.multiview,
+            cache: cache.map(|cache| &cache.raw),
         };
         let raw =
             unsafe { self.raw.create_render_pipeline(&pipeline_desc) }.map_err(
@@ -2872,6 +2605,9 @@ impl<A: HalApi> Device<A> {
                             error: EP_FAILURE.to_string(),
                         }
                     }
+                    hal::PipelineError::UnsupportedVertexFormat(format) => {
+                        pipeline::CreateRenderPipelineError::UnsupportedVertexFormat(format)
+                    }
                 },
             )?;
 
@@ -2912,7 +2648,7 @@ impl<A: HalApi> Device<A> {
             raw,
             layout_id: Stored {
                 value: id::Valid(pipeline_layout_id),
-                ref_count: layout.life_guard.add_ref(),
+                ref_count: PipelineSources::layout_ref(sources, layout),
             },
             device_id: Stored {
                 value: id::Valid(self_id),
@@ -2924,6 +2660,8 @@ impl<A: HalApi> Device<A> {
             vertex_steps,
             late_sized_buffer_groups,
             life_guard: LifeGuard::new(desc.label.borrow_or_default()),
+            reload_desc: None,
+            retired: Vec::new(),
         };
         Ok(pipeline)
     }
@@ -3032,11 +2770,45 @@ impl<A: HalApi> Device<A> {
         unsafe {
             baked.encoder.reset_all(baked.list.into_iter());
         }
+        // The command buffer was never submitted, so its blocks are free.
+        self.command_allocator
+            .lock()
+            .release_push_constant_blocks(baked.emulated_push_constants.into_blocks());
+        for arguments in baked.indirect_arguments {
+            unsafe {
+                arguments.destroy(&self.raw);
+            }
+        }
         unsafe {
             self.raw.destroy_command_encoder(baked.encoder);
         }
     }
 
+    /// Releases the raw command buffers and temporary resources of a dropped
+    /// reusable command buffer, once its last submission is done.
+    fn release_reusable_command_buffer<'this, 'token: 'this>(
+        &'this self,
+        cmd_buf: command::CommandBuffer<A>,
+        token: &mut Token<'token, Self>,
+    ) {
+        let last_submission = cmd_buf.last_submission;
+        let baked = cmd_buf.into_baked();
+        let mut life_tracker = self.lock_life(token);
+        for arguments in baked.indirect_arguments {
+            for resource in arguments.into_temp_resources() {
+                life_tracker.schedule_resource_destruction(resource, last_submission);
+            }
+        }
+        let encoder = queue::EncoderInFlight::new(
+            baked.encoder,
+            baked.list,
+            baked.emulated_push_constants.into_blocks(),
+        );
+        if let Some(encoder) = life_tracker.schedule_encoder_release(encoder, last_submission) {
+            unsafe { encoder.land(&mut self.command_allocator.lock()) };
+        }
+    }
+
     /// Wait for idle and remove resources that we can, before we die.
     pub(crate) fn prepare_to_die(&mut self) {
         self.pending_writes.deactivate();
@@ -3058,6 +2830,12 @@ impl<A: HalApi> Device<A> {
         self.command_allocator.into_inner().dispose(&self.raw);
         unsafe {
             self.raw.destroy_buffer(self.zero_buffer);
+            if let Some(emulation) = self.push_constant_emulation {
+                emulation.dispose(&self.raw);
+            }
+            if let Some(validation) = self.indirect_validation {
+                validation.dispose(&self.raw);
+            }
             self.raw.destroy_fence(self.fence);
             self.raw.exit(self.queue);
         }
@@ -3132,6 +2910,137 @@ impl<G: GlobalIdentityHandlerFactory> ImplicitPipelineIds<'_, G> {
     }
 }
 
+/// References to the resources a pipeline descriptor refers to, held while
+/// the pipeline is waiting to be created.
+///
+/// Dropping one of these resources doesn't destroy it until the sources are
+/// released by the creation of the pipeline.
+#[derive(Debug)]
+pub struct PipelineSources {
+    device_id: id::Valid<id::DeviceId>,
+    layout: Option<Stored<id::PipelineLayoutId>>,
+    modules: ArrayVec<Stored<id::ShaderModuleId>, 2>,
+    cache: Option<Stored<id::PipelineCacheId>>,
+}
+
+impl PipelineSources {
+    /// Returns a reference to the pipeline layout, which the user may have
+    /// dropped while the pipeline was pending.
+    fn layout_ref<A: HalApi>(
+        sources: Option<&Self>,
+        layout: &binding_model::PipelineLayout<A>,
+    ) -> RefCount {
+        match sources.and_then(|sources| sources.layout.as_ref()) {
+            Some(layout) => layout.ref_count.clone(),
+            None => layout.life_guard.add_ref(),
+        }
+    }
+}
+
+fn validate_surface_configuration(
+    config: &mut hal::SurfaceConfiguration,
+    caps: &hal::SurfaceCapabilities,
+) -> Result<(), present::ConfigureSurfaceError> {
+    use present::ConfigureSurfaceError as E;
+
+    let width = config.extent.width;
+    let height = config.extent.height;
+    if width < caps.extents.start().width
+        || width > caps.extents.end().width
+        || height < caps.extents.start().height
+        || height > caps.extents.end().height
+    {
+        log::warn!(
+            "Requested size {}x{} is outside of the supported range: {:?}",
+            width,
+            height,
+            caps.extents
+        );
+    }
+    if !caps.present_modes.contains(&config.present_mode) {
+        let new_mode = 'b: loop {
+            // Automatic present mode checks.
+            //
+            // The "Automatic" modes are never supported by the backends.
+            let fallbacks = match config.present_mode {
+                wgt::PresentMode::AutoVsync => {
+                    &[wgt::PresentMode::FifoRelaxed, wgt::PresentMode::Fifo][..]
+                }
+                // Always end in FIFO to make sure it's always supported
+                wgt::PresentMode::AutoNoVsync => &[
+                    wgt::PresentMode::Immediate,
+                    wgt::PresentMode::Mailbox,
+                    wgt::PresentMode::Fifo,
+                ][..],
+                _ => {
+                    return Err(E::UnsupportedPresentMode {
+                        requested: config.present_mode,
+                        available: caps.present_modes.clone(),
+                    });
+                }
+            };
+
+            for &fallback in fallbacks {
+                if caps.present_modes.contains(&fallback) {
+                    break 'b fallback;
+                }
+            }
+
+            unreachable!("Fallback system failed to choose present mode. This is a bug. Mode: {:?}, Options: {:?}", config.present_mode, &caps.present_modes);
+        };
+
+        log::info!(
+            "Automatically choosing presentation mode by rule {:?}. Chose {new_mode:?}",
+            config.present_mode
+        );
+        config.present_mode = new_mode;
+    }
+    if config.composite_alpha_mode == wgt::CompositeAlphaMode::Auto {
+        config.composite_alpha_mode = if caps
+            .composite_alpha_modes
+            .contains(&wgt::CompositeAlphaMode::Opaque)
+        {
+            wgt::CompositeAlphaMode::Opaque
+        } else if caps
+            .composite_alpha_modes
+            .contains(&wgt::CompositeAlphaMode::Inherit)
+        {
+            wgt::CompositeAlphaMode::Inherit
+        } else {
+            return Err(E::UnsupportedAlphaMode {
+                requested: config.composite_alpha_mode,
+                available: caps.composite_alpha_modes.clone(),
+            });
+        };
+        log::info!(
+            "Automatically choosing alpha mode. Chose {:?}",
+            config.composite_alpha_mode
+        );
+    }
+    if !caps
+        .composite_alpha_modes
+        .contains(&config.composite_alpha_mode)
+    {
+        return Err(E::UnsupportedAlphaMode {
+            requested: config.composite_alpha_mode,
+            available: caps.composite_alpha_modes.clone(),
+        });
+    }
+    if !caps.formats.contains(&config.format) {
+        return Err(E::UnsupportedFormat {
+            requested: config.format,
+            available: caps.formats.clone(),
+        });
+    }
+    if !caps.usage.contains(config.usage) {
+        return Err(E::UnsupportedUsage);
+    }
+    if width == 0 || height == 0 {
+        return Err(E::ZeroArea);
+    }
+    Ok(())
+}
+
 impl<G: GlobalIdentityHandlerFactory> Global<G> {
     pub fn adapter_is_surface_supported<A: HalApi>(
         &self,
@@ -3191,6 +3100,26 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
 
         surface.get_supported_modes(adapter)
     }
+    pub fn surface_get_supported_alpha_modes<A: HalApi>(
+        &self,
+        surface_id: id::SurfaceId,
+        adapter_id: id::AdapterId,
+    ) -> Result<Vec<wgt::CompositeAlphaMode>, instance::GetSurfaceSupportError> {
+        profiling::scope!("Surface::get_supported_alpha_modes");
+        let hub = A::hub(self);
+        let mut token = Token::root();
+
+        let (surface_guard, mut token) = self.surfaces.read(&mut token);
+        let (adapter_guard, mut _token) = hub.adapters.read(&mut token);
+        let adapter = adapter_guard
+            .get(adapter_id)
+            .map_err(|_| instance::GetSurfaceSupportError::InvalidAdapter)?;
+        let surface = surface_guard
+            .get(surface_id)
+            .map_err(|_| instance::GetSurfaceSupportError::InvalidSurface)?;
+
+        surface.get_supported_alpha_modes(adapter)
+    }
 
     pub fn device_features<A: HalApi>(
         &self,
@@ -3233,6 +3162,33 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         device_id: id::DeviceId,
         desc: &resource::BufferDescriptor,
         id_in: Input<G, id::BufferId>,
+    ) -> (id::BufferId, Option<resource::CreateBufferError>) {
+        self.create_buffer_impl::<A>(device_id, desc, id_in, true)
+    }
+
+    /// Creates a buffer whose memory isn't zero-initialized.
+    ///
+    /// Requires [`wgt::Features::UNINITIALIZED_RESOURCES`].
+    ///
+    /// # Safety
+    ///
+    /// - Parts of the buffer that weren't written must not be read, or the values
+    ///   read are undefined.
+    pub unsafe fn device_create_buffer_uninitialized<A: HalApi>(
+        &self,
+        device_id: id::DeviceId,
+        desc: &resource::BufferDescriptor,
+        id_in: Input<G, id::BufferId>,
+    ) -> (id::BufferId, Option<resource::CreateBufferError>) {
+        self.create_buffer_impl::<A>(device_id, desc, id_in, false)
+    }
+
+    fn create_buffer_impl<A: HalApi>(
+        &self,
+        device_id: id::DeviceId,
+        desc: &resource::BufferDescriptor,
+        id_in: Input<G, id::BufferId>,
+        zero_initialized: bool,
     ) -> (id::BufferId, Option<resource::CreateBufferError>) {
         profiling::scope!("Device::create_buffer");
 
@@ -3253,15 +3209,27 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                 if mapped_at_creation && !desc.usage.contains(wgt::BufferUsages::MAP_WRITE) {
                     desc.usage |= wgt::BufferUsages::COPY_DST;
                 }
-                trace
-                    .lock()
-                    .add(trace::Action::CreateBuffer(fid.id(), desc));
+                trace.lock().add(if zero_initialized {
+                    trace::Action::CreateBuffer(fid.id(), desc)
+                } else {
+                    trace::Action::CreateUninitializedBuffer(fid.id(), desc)
+                });
+            }
+
+            if !zero_initialized {
+                if let Err(error) = device.require_features(wgt::Features::UNINITIALIZED_RESOURCES)
+                {
+                    break error.into();
+                }
             }
 
             let mut buffer = match device.create_buffer(device_id, desc, false) {
                 Ok(buffer) => buffer,
                 Err(e) => break e,
             };
+            if !zero_initialized {
+                buffer.initialization_status = BufferInitTracker::new(0);
+            }
             let ref_count = buffer.life_guard.add_ref();
 
             let buffer_use = if !desc.mapped_at_creation {
@@ -3653,6 +3621,33 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         device_id: id::DeviceId,
         desc: &resource::TextureDescriptor,
         id_in: Input<G, id::TextureId>,
+    ) -> (id::TextureId, Option<resource::CreateTextureError>) {
+        self.create_texture_impl::<A>(device_id, desc, id_in, true)
+    }
+
+    /// Creates a texture whose memory isn't zero-initialized.
+    ///
+    /// Requires [`wgt::Features::UNINITIALIZED_RESOURCES`].
+    ///
+    /// # Safety
+    ///
+    /// - Parts of the texture that weren't written, or that were discarded by a
+    ///   render pass, must not be read, or the values read are undefined.
+    pub unsafe fn device_create_texture_uninitialized<A: HalApi>(
+        &self,
+        device_id: id::DeviceId,
+        desc: &resource::TextureDescriptor,
+        id_in: Input<G, id::TextureId>,
+    ) -> (id::TextureId, Option<resource::CreateTextureError>) {
+        self.create_texture_impl::<A>(device_id, desc, id_in, false)
+    }
+
+    fn create_texture_impl<A: HalApi>(
+        &self,
+        device_id: id::DeviceId,
+        desc: &resource::TextureDescriptor,
+        id_in: Input<G, id::TextureId>,
+        zero_initialized: bool,
     ) -> (id::TextureId, Option<resource::CreateTextureError>) {
         profiling::scope!("Device::create_texture");
 
@@ -3669,16 +3664,29 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             };
             #[cfg(feature = "trace")]
             if let Some(ref trace) = device.trace {
-                trace
-                    .lock()
-                    .add(trace::Action::CreateTexture(fid.id(), desc.clone()));
+                trace.lock().add(if zero_initialized {
+                    trace::Action::CreateTexture(fid.id(), desc.clone())
+                } else {
+                    trace::Action::CreateUninitializedTexture(fid.id(), desc.clone())
+                });
+            }
+
+            if !zero_initialized {
+                if let Err(error) = device.require_features(wgt::Features::UNINITIALIZED_RESOURCES)
+                {
+                    break resource::CreateTextureError::UninitializedMissingFeatures(error);
+                }
             }
 
             let adapter = &adapter_guard[device.adapter_id.value];
-            let texture = match device.create_texture(device_id, adapter, desc) {
+            let mut texture = match device.create_texture(device_id, adapter, desc) {
                 Ok(texture) => texture,
                 Err(error) => break error,
             };
+            if !zero_initialized {
+                texture.initialization_status = TextureInitTracker::new(desc.mip_level_count, 0);
+                texture.zero_initialized = false;
+            }
             let ref_count = texture.life_guard.add_ref();
 
             let id = fid.assign(texture, &mut token);
@@ -4074,7 +4082,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         let hub = A::hub(self);
         let fid = hub.bind_group_layouts.prepare(id_in);
 
-        let error = 'outer: loop {
+        let error = loop {
             let (device_guard, mut token) = hub.devices.read(&mut token);
             let device = match device_guard.get(device_id) {
                 Ok(device) => device,
@@ -4087,20 +4095,10 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                     .add(trace::Action::CreateBindGroupLayout(fid.id(), desc.clone()));
             }
 
-            let mut entry_map = FastHashMap::default();
-            for entry in desc.entries.iter() {
-                if entry.binding > MAX_BINDING_INDEX {
-                    break 'outer binding_model::CreateBindGroupLayoutError::InvalidBindingIndex {
-                        binding: entry.binding,
-                        maximum: MAX_BINDING_INDEX,
-                    };
-                }
-                if entry_map.insert(entry.binding, *entry).is_some() {
-                    break 'outer binding_model::CreateBindGroupLayoutError::ConflictBinding(
-                        entry.binding,
-                    );
-                }
-            }
+            let entry_map = match checks::bind_entry_map(&desc.entries) {
+                Ok(entry_map) => entry_map,
+                Err(e) => break e,
+            };
 
             // If there is an equivalent BGL, just bump the refcount and return it.
             // This is only applicable for identity filters that are generating new IDs,
@@ -4354,17 +4352,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             #[cfg(feature = "trace")]
             if let Some(ref trace) = device.trace {
                 let mut trace = trace.lock();
-                let data = match source {
-                    pipeline::ShaderModuleSource::Wgsl(ref code) => {
-                        trace.make_binary("wgsl", code.as_bytes())
-                    }
-                    pipeline::ShaderModuleSource::Naga(ref module) => {
-                        let string =
-                            ron::ser::to_string_pretty(module, ron::ser::PrettyConfig::default())
-                                .unwrap();
-                        trace.make_binary("ron", string.as_bytes())
-                    }
-                };
+                let data = trace.make_shader_binary(&source);
                 trace.add(trace::Action::CreateShaderModule {
                     id: fid.id(),
                     desc: desc.clone(),
@@ -4440,6 +4428,22 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         A::hub(self).shader_modules.label_for_resource(id)
     }
 
+    /// Returns the inputs of the vertex entry point `entry_point`, sorted by
+    /// location, or `None` if the module is invalid, has no such entry point,
+    /// or wasn't reflected.
+    pub fn shader_module_vertex_inputs<A: HalApi>(
+        &self,
+        shader_module_id: id::ShaderModuleId,
+        entry_point: &str,
+    ) -> Option<Vec<wgt::VertexInput>> {
+        let hub = A::hub(self);
+        let mut token = Token::root();
+        let (_, mut token) = hub.devices.read(&mut token);
+        let (module_guard, _) = hub.shader_modules.read(&mut token);
+        let module = module_guard.get(shader_module_id).ok()?;
+        module.interface.as_ref()?.vertex_inputs(entry_point)
+    }
+
     pub fn shader_module_drop<A: HalApi>(&self, shader_module_id: id::ShaderModuleId) {
         profiling::scope!("ShaderModule::drop");
         log::debug!("shader module {:?} is dropped", shader_module_id);
@@ -4447,38 +4451,434 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         let hub = A::hub(self);
         let mut token = Token::root();
         let (device_guard, mut token) = hub.devices.read(&mut token);
-        let (module, _) = hub.shader_modules.unregister(shader_module_id, &mut token);
-        if let Some(module) = module {
-            let device = &device_guard[module.device_id.value];
-            #[cfg(feature = "trace")]
-            if let Some(ref trace) = device.trace {
-                trace
-                    .lock()
-                    .add(trace::Action::DestroyShaderModule(shader_module_id));
-            }
-            unsafe {
-                device.raw.destroy_shader_module(module.raw);
+        let (device_id, ref_count) = {
+            let (mut module_guard, _) = hub.shader_modules.write(&mut token);
+            match module_guard.get_mut(shader_module_id) {
+                Ok(module) => (
+                    module.device_id.value,
+                    module.life_guard.ref_count.take().unwrap(),
+                ),
+                Err(InvalidId) => {
+                    hub.shader_modules
+                        .unregister_locked(shader_module_id, &mut *module_guard);
+                    return;
+                }
             }
-        }
+        };
+
+        device_guard[device_id]
+            .lock_life(&mut token)
+            .suspected_resources
+            .shader_modules
+            .push(Stored {
+                value: id::Valid(shader_module_id),
+                ref_count,
+            });
     }
 
-    pub fn device_create_command_encoder<A: HalApi>(
+    /// Makes the pipelines created on the device from now on follow their
+    /// shader modules when those are replaced with `shader_module_replace`.
+    pub fn device_enable_shader_hot_reload<A: HalApi>(
         &self,
         device_id: id::DeviceId,
-        desc: &wgt::CommandEncoderDescriptor<Label>,
-        id_in: Input<G, id::CommandEncoderId>,
-    ) -> (id::CommandEncoderId, Option<DeviceError>) {
-        profiling::scope!("Device::create_command_encoder");
-
+    ) -> Result<(), InvalidDevice> {
         let hub = A::hub(self);
         let mut token = Token::root();
-        let fid = hub.command_buffers.prepare(id_in);
+        let (device_guard, _) = hub.devices.read(&mut token);
+        let device = device_guard.get(device_id).map_err(|_| InvalidDevice)?;
+        #[cfg(feature = "trace")]
+        if let Some(ref trace) = device.trace {
+            trace.lock().add(trace::Action::EnableShaderHotReload);
+        }
+        device.shader_hot_reload.store(true, Ordering::Relaxed);
+        Ok(())
+    }
 
-        let (device_guard, mut token) = hub.devices.read(&mut token);
-        let error = loop {
-            let device = match device_guard.get(device_id) {
-                Ok(device) => device,
-                Err(_) => break DeviceError::Invalid,
+    /// Makes the command encoders created on the device from now on record the
+    /// barriers they emit, for [`Global::device_take_barrier_logs`].
+    ///
+    /// See the [`BarrierLog`](command::BarrierLog) documentation.
+    pub fn device_enable_barrier_log<A: HalApi>(
+        &self,
+        device_id: id::DeviceId,
+    ) -> Result<(), InvalidDevice> {
+        let hub = A::hub(self);
+        let mut token = Token::root();
+        let (device_guard, _) = hub.devices.read(&mut token);
+        let device = device_guard.get(device_id).map_err(|_| InvalidDevice)?;
+        device.barrier_logs.lock().get_or_insert_with(Vec::new);
+        Ok(())
+    }
+
+    /// Takes the barrier logs of the command buffers submitted since the last
+    /// call, in submission order.
+    ///
+    /// Returns an empty list if the barrier log isn't enabled.
+    pub fn device_take_barrier_logs<A: HalApi>(
+        &self,
+        device_id: id::DeviceId,
+    ) -> Result<Vec<command::BarrierLog>, InvalidDevice> {
+        let hub = A::hub(self);
+        let mut token = Token::root();
+        let (device_guard, _) = hub.devices.read(&mut token);
+        let device = device_guard.get(device_id).map_err(|_| InvalidDevice)?;
+        let logs = device
+            .barrier_logs
+            .lock()
+            .as_mut()
+            .map_or_else(Vec::new, mem::take);
+        Ok(logs)
+    }
+
+    /// Replaces the source of a shader module, and rebuilds the pipelines
+    /// created from it since shader hot reload was enabled on its device.
+    ///
+    /// Either the module and all of these pipelines are updated, or none of
+    /// them is. Commands recorded before the replacement keep using the old
+    /// pipelines.
+    pub fn shader_module_replace<A: HalApi>(
+        &self,
+        shader_module_id: id::ShaderModuleId,
+        desc: &pipeline::ShaderModuleDescriptor,
+        source: pipeline::ShaderModuleSource,
+    ) -> Result<(), pipeline::ReplaceShaderModuleError> {
+        profiling::scope!("ShaderModule::replace");
+
+        let hub = A::hub(self);
+        let mut token = Token::root();
+        let (adapter_guard, mut token) = hub.adapters.read(&mut token);
+        let (device_guard, mut token) = hub.devices.read(&mut token);
+
+        let device_id = {
+            let (module_guard, _) = hub.shader_modules.read(&mut token);
+            module_guard
+                .get(shader_module_id)
+                .map_err(|_| pipeline::ReplaceShaderModuleError::InvalidModule)?
+                .device_id
+                .value
+        };
+        let device = &device_guard[device_id];
+        let adapter = &adapter_guard[device.adapter_id.value];
+        #[cfg(feature = "trace")]
+        if let Some(ref trace) = device.trace {
+            let mut trace = trace.lock();
+            let data = trace.make_shader_binary(&source);
+            trace.add(trace::Action::ReplaceShaderModule {
+                id: shader_module_id,
+                desc: desc.clone(),
+                data,
+            });
+        }
+
+        let mut module = device.create_shader_module(device_id.0, desc, source)?;
+
+        // Gather the live pipelines using the module.
+        let uses_module =
+            |stage: &pipeline::ProgrammableStageDescriptor| stage.module == shader_module_id;
+        let mut render_descs = Vec::new();
+        let mut compute_descs = Vec::new();
+        {
+            let (compute_pipe_guard, mut token) = hub.compute_pipelines.read(&mut token);
+            let (render_pipe_guard, _) = hub.render_pipelines.read(&mut token);
+            for (id, pipeline) in render_pipe_guard.iter(A::VARIANT) {
+                match pipeline.reload_desc {
+                    Some(ref desc)
+                        if pipeline.life_guard.ref_count.is_some()
+                            && (uses_module(&desc.vertex.stage)
+                                || desc
+                                    .fragment
+                                    .as_ref()
+                                    .map_or(false, |f| uses_module(&f.stage))) =>
+                    {
+                        render_descs.push((id, desc.as_ref().clone()));
+                    }
+                    _ => {}
+                }
+            }
+            for (id, pipeline) in compute_pipe_guard.iter(A::VARIANT) {
+                match pipeline.reload_desc {
+                    Some(ref desc)
+                        if pipeline.life_guard.ref_count.is_some() && uses_module(&desc.stage) =>
+                    {
+                        compute_descs.push((id, desc.as_ref().clone()));
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        // Swap the new module in, so that the pipelines are built from it.
+        let mut old_module = {
+            let (mut module_guard, mut token) = hub.shader_modules.write(&mut token);
+            let (cache_guard, _) = hub.pipeline_caches.read(&mut token);
+            // A module dropped by the user can still be registered, if a pending
+            // pipeline holds on to it.
+            let is_live = |stage: &pipeline::ProgrammableStageDescriptor| {
+                matches!(
+                    module_guard.get(stage.module),
+                    Ok(module) if module.life_guard.ref_count.is_some()
+                )
+            };
+            render_descs.retain(|&(id, ref desc)| {
+                let live = is_live(&desc.vertex.stage)
+                    && desc.fragment.as_ref().map_or(true, |f| is_live(&f.stage));
+                if !live {
+                    log::warn!(
+                        "Not reloading render pipeline {:?}: a shader module was dropped",
+                        id
+                    );
+                }
+                live
+            });
+            compute_descs.retain(|&(id, ref desc)| {
+                let live = is_live(&desc.stage);
+                if !live {
+                    log::warn!(
+                        "Not reloading compute pipeline {:?}: a shader module was dropped",
+                        id
+                    );
+                }
+                live
+            });
+            for desc in render_descs.iter_mut().map(|&mut (_, ref mut desc)| desc) {
+                if desc.cache.map_or(false, |id| !cache_guard.contains(id)) {
+                    desc.cache = None;
+                }
+            }
+            for desc in compute_descs.iter_mut().map(|&mut (_, ref mut desc)| desc) {
+                if desc.cache.map_or(false, |id| !cache_guard.contains(id)) {
+                    desc.cache = None;
+                }
+            }
+            // The module keeps its identity, and with it the references to it.
+            let current = &mut module_guard[id::Valid(shader_module_id)];
+            mem::swap(&mut module.life_guard, &mut current.life_guard);
+            mem::replace(current, module)
+        };
+
+        let mut render_pipes = Vec::with_capacity(render_descs.len());
+        let mut compute_pipes = Vec::with_capacity(compute_descs.len());
+        let mut error = None;
+        for &(id, ref desc) in render_descs.iter() {
+            match device.create_render_pipeline(
+                device_id.0,
+                adapter,
+                desc,
+                None,
+                None,
+                hub,
+                &mut token,
+            ) {
+                Ok(pipeline) => render_pipes.push((id, pipeline)),
+                Err(e) => {
+                    error = Some(pipeline::ReplaceShaderModuleError::RenderPipeline(id, e));
+                    break;
+                }
+            }
+        }
+        if error.is_none() {
+            for &(id, ref desc) in compute_descs.iter() {
+                match device.create_compute_pipeline(device_id.0, desc, None, None, hub, &mut token)
+                {
+                    Ok(pipeline) => compute_pipes.push((id, pipeline)),
+                    Err(e) => {
+                        error = Some(pipeline::ReplaceShaderModuleError::ComputePipeline(id, e));
+                        break;
+                    }
+                }
+            }
+        }
+
+        if let Some(error) = error {
+            let (mut module_guard, _) = hub.shader_modules.write(&mut token);
+            let current = &mut module_guard[id::Valid(shader_module_id)];
+            mem::swap(&mut old_module.life_guard, &mut current.life_guard);
+            let module = mem::replace(current, old_module);
+            unsafe {
+                for (_, pipeline) in render_pipes {
+                    device.raw.destroy_render_pipeline(pipeline.raw);
+                }
+                for (_, pipeline) in compute_pipes {
+                    device.raw.destroy_compute_pipeline(pipeline.raw);
+                }
+                device.raw.destroy_shader_module(module.raw);
+            }
+            return Err(error);
+        }
+
+        // Retired raw pipelines that no command buffer refers to can go as
+        // soon as their last submission is done.
+        let mut destroyed_render = Vec::new();
+        let mut destroyed_compute = Vec::new();
+        {
+            let (mut compute_pipe_guard, mut token) = hub.compute_pipelines.write(&mut token);
+            let (mut render_pipe_guard, _) = hub.render_pipelines.write(&mut token);
+            for (id, new) in render_pipes {
+                if !render_pipe_guard.contains(id) {
+                    unsafe { device.raw.destroy_render_pipeline(new.raw) };
+                    continue;
+                }
+                let pipeline = &mut render_pipe_guard[id::Valid(id)];
+                let old_raw = mem::replace(&mut pipeline.raw, new.raw);
+                pipeline.retired.push(old_raw);
+                pipeline.pass_context = new.pass_context;
+                pipeline.flags = new.flags;
+                pipeline.strip_index_format = new.strip_index_format;
+                pipeline.vertex_steps = new.vertex_steps;
+                pipeline.late_sized_buffer_groups = new.late_sized_buffer_groups;
+                if pipeline.life_guard.ref_count.as_ref().map(|rc| rc.load()) == Some(2) {
+                    destroyed_render.push((
+                        mem::take(&mut pipeline.retired),
+                        pipeline.life_guard.life_count(),
+                    ));
+                }
+            }
+            for (id, new) in compute_pipes {
+                if !compute_pipe_guard.contains(id) {
+                    unsafe { device.raw.destroy_compute_pipeline(new.raw) };
+                    continue;
+                }
+                let pipeline = &mut compute_pipe_guard[id::Valid(id)];
+                let old_raw = mem::replace(&mut pipeline.raw, new.raw);
+                pipeline.retired.push(old_raw);
+                pipeline.late_sized_buffer_groups = new.late_sized_buffer_groups;
+                if pipeline.life_guard.ref_count.as_ref().map(|rc| rc.load()) == Some(2) {
+                    destroyed_compute.push((
+                        mem::take(&mut pipeline.retired),
+                        pipeline.life_guard.life_count(),
+                    ));
+                }
+            }
+        }
+
+        let mut life_lock = device.lock_life(&mut token);
+        for (raws, last_submit_index) in destroyed_render {
+            life_lock.schedule_pipeline_destruction(None, raws, last_submit_index);
+        }
+        for (raws, last_submit_index) in destroyed_compute {
+            life_lock.schedule_pipeline_destruction(raws, None, last_submit_index);
+        }
+        drop(life_lock);
+        unsafe {
+            device.raw.destroy_shader_module(old_module.raw);
+        }
+        Ok(())
+    }
+
+    #[allow(unused_unsafe)]
+    /// # Safety
+    ///
+    /// `desc.data` must have been returned by `pipeline_cache_get_data`, on an adapter
+    /// of the same backend. Any other data may result in a driver crash.
+    pub unsafe fn device_create_pipeline_cache<A: HalApi>(
+        &self,
+        device_id: id::DeviceId,
+        desc: &pipeline::PipelineCacheDescriptor,
+        id_in: Input<G, id::PipelineCacheId>,
+    ) -> (
+        id::PipelineCacheId,
+        Option<pipeline::CreatePipelineCacheError>,
+    ) {
+        profiling::scope!("Device::create_pipeline_cache");
+
+        let hub = A::hub(self);
+        let mut token = Token::root();
+        let fid = hub.pipeline_caches.prepare(id_in);
+
+        let (device_guard, mut token) = hub.devices.read(&mut token);
+        let error = loop {
+            let device = match device_guard.get(device_id) {
+                Ok(device) => device,
+                Err(_) => break DeviceError::Invalid.into(),
+            };
+            #[cfg(feature = "trace")]
+            if let Some(ref trace) = device.trace {
+                trace.lock().add(trace::Action::CreatePipelineCache {
+                    id: fid.id(),
+                    desc: desc.clone(),
+                });
+            }
+
+            let cache = match unsafe { device.create_pipeline_cache(device_id, desc) } {
+                Ok(cache) => cache,
+                Err(e) => break e,
+            };
+            let id = fid.assign(cache, &mut token);
+            return (id.0, None);
+        };
+
+        let id = fid.assign_error(desc.label.borrow_or_default(), &mut token);
+        (id, Some(error))
+    }
+
+    /// Serializes the contents of the pipeline cache, to be passed to
+    /// `device_create_pipeline_cache` later.
+    ///
+    /// Returns `None` if the cache is invalid, or if the backend has nothing to cache.
+    pub fn pipeline_cache_get_data<A: HalApi>(
+        &self,
+        pipeline_cache_id: id::PipelineCacheId,
+    ) -> Option<Vec<u8>> {
+        profiling::scope!("PipelineCache::get_data");
+
+        let hub = A::hub(self);
+        let mut token = Token::root();
+        let (device_guard, mut token) = hub.devices.read(&mut token);
+        let (cache_guard, _) = hub.pipeline_caches.read(&mut token);
+        let cache = cache_guard.get(pipeline_cache_id).ok()?;
+        let device = &device_guard[cache.device_id.value];
+        unsafe { device.raw.pipeline_cache_get_data(&cache.raw) }
+    }
+
+    pub fn pipeline_cache_drop<A: HalApi>(&self, pipeline_cache_id: id::PipelineCacheId) {
+        profiling::scope!("PipelineCache::drop");
+        log::debug!("pipeline cache {:?} is dropped", pipeline_cache_id);
+
+        let hub = A::hub(self);
+        let mut token = Token::root();
+        let (device_guard, mut token) = hub.devices.read(&mut token);
+        let (device_id, ref_count) = {
+            let (mut cache_guard, _) = hub.pipeline_caches.write(&mut token);
+            match cache_guard.get_mut(pipeline_cache_id) {
+                Ok(cache) => (
+                    cache.device_id.value,
+                    cache.life_guard.ref_count.take().unwrap(),
+                ),
+                Err(InvalidId) => {
+                    hub.pipeline_caches
+                        .unregister_locked(pipeline_cache_id, &mut *cache_guard);
+                    return;
+                }
+            }
+        };
+
+        device_guard[device_id]
+            .lock_life(&mut token)
+            .suspected_resources
+            .pipeline_caches
+            .push(Stored {
+                value: id::Valid(pipeline_cache_id),
+                ref_count,
+            });
+    }
+
+    pub fn device_create_command_encoder<A: HalApi>(
+        &self,
+        device_id: id::DeviceId,
+        desc: &wgt::CommandEncoderDescriptor<Label>,
+        id_in: Input<G, id::CommandEncoderId>,
+    ) -> (id::CommandEncoderId, Option<DeviceError>) {
+        profiling::scope!("Device::create_command_encoder");
+
+        let hub = A::hub(self);
+        let mut token = Token::root();
+        let fid = hub.command_buffers.prepare(id_in);
+
+        let (device_guard, mut token) = hub.devices.read(&mut token);
+        let error = loop {
+            let device = match device_guard.get(device_id) {
+                Ok(device) => device,
+                Err(_) => break DeviceError::Invalid,
             };
             let dev_stored = Stored {
                 value: id::Valid(device_id),
@@ -4500,6 +4900,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                 device.features,
                 #[cfg(feature = "trace")]
                 device.trace.is_some(),
+                device.barrier_logs.lock().is_some(),
                 &desc.label,
             );
 
@@ -4529,6 +4930,9 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         if let Some(cmdbuf) = cmdbuf {
             let device = &mut device_guard[cmdbuf.device_id.value];
             device.untrack::<G>(hub, &cmdbuf.trackers, &mut token);
+            if cmdbuf.is_reusable() {
+                device.release_reusable_command_buffer(cmdbuf, &mut token);
+            }
         }
     }
 
@@ -4714,6 +5118,72 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             .push(id::Valid(query_set_id));
     }
 
+    /// Takes references to the resources a pipeline descriptor refers to, so
+    /// that the pipeline can still be created after the user drops them.
+    ///
+    /// `modules` are the shader modules of the stages. Invalid ids are skipped.
+    /// The references are released by [`Global::device_create_pending_render_pipeline`]
+    /// or [`Global::device_create_pending_compute_pipeline`].
+    pub fn device_retain_pipeline_sources<A: HalApi>(
+        &self,
+        device_id: id::DeviceId,
+        layout: Option<id::PipelineLayoutId>,
+        modules: &[id::ShaderModuleId],
+        cache: Option<id::PipelineCacheId>,
+    ) -> PipelineSources {
+        let hub = A::hub(self);
+        let mut token = Token::root();
+        let (_, mut token) = hub.devices.read(&mut token);
+        let layout = {
+            let (pipeline_layout_guard, _) = hub.pipeline_layouts.read(&mut token);
+            layout.and_then(|id| {
+                Some(Stored {
+                    value: id::Valid(id),
+                    ref_count: pipeline_layout_guard.get(id).ok()?.life_guard.add_ref(),
+                })
+            })
+        };
+        let (module_guard, mut token) = hub.shader_modules.read(&mut token);
+        let (cache_guard, _) = hub.pipeline_caches.read(&mut token);
+        let modules = modules
+            .iter()
+            .filter_map(|&id| {
+                Some(Stored {
+                    value: id::Valid(id),
+                    ref_count: module_guard.get(id).ok()?.life_guard.add_ref(),
+                })
+            })
+            .collect();
+        let cache = cache.and_then(|id| {
+            Some(Stored {
+                value: id::Valid(id),
+                ref_count: cache_guard.get(id).ok()?.life_guard.add_ref(),
+            })
+        });
+
+        PipelineSources {
+            device_id: id::Valid(device_id),
+            layout,
+            modules,
+            cache,
+        }
+    }
+
+    /// Lets the resources retained by `sources` be destroyed, if the user
+    /// dropped them.
+    fn release_pipeline_sources<A: HalApi>(&self, sources: PipelineSources) {
+        let hub = A::hub(self);
+        let mut token = Token::root();
+        let (device_guard, mut token) = hub.devices.read(&mut token);
+        if let Ok(device) = device_guard.get(sources.device_id.0) {
+            let mut life_lock = device.lock_life(&mut token);
+            let suspected = &mut life_lock.suspected_resources;
+            suspected.pipeline_layouts.extend(sources.layout);
+            suspected.shader_modules.extend(sources.modules);
+            suspected.pipeline_caches.extend(sources.cache);
+        }
+    }
+
     pub fn device_create_render_pipeline<A: HalApi>(
         &self,
         device_id: id::DeviceId,
@@ -4723,6 +5193,44 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
     ) -> (
         id::RenderPipelineId,
         Option<pipeline::CreateRenderPipelineError>,
+    ) {
+        self.create_render_pipeline_impl::<A>(device_id, desc, id_in, implicit_pipeline_ids, None)
+    }
+
+    /// Creates a render pipeline from sources retained with
+    /// [`Global::device_retain_pipeline_sources`], and releases them.
+    pub fn device_create_pending_render_pipeline<A: HalApi>(
+        &self,
+        device_id: id::DeviceId,
+        desc: &pipeline::RenderPipelineDescriptor,
+        id_in: Input<G, id::RenderPipelineId>,
+        implicit_pipeline_ids: Option<ImplicitPipelineIds<G>>,
+        sources: PipelineSources,
+    ) -> (
+        id::RenderPipelineId,
+        Option<pipeline::CreateRenderPipelineError>,
+    ) {
+        let result = self.create_render_pipeline_impl::<A>(
+            device_id,
+            desc,
+            id_in,
+            implicit_pipeline_ids,
+            Some(&sources),
+        );
+        self.release_pipeline_sources::<A>(sources);
+        result
+    }
+
+    fn create_render_pipeline_impl<A: HalApi>(
+        &self,
+        device_id: id::DeviceId,
+        desc: &pipeline::RenderPipelineDescriptor,
+        id_in: Input<G, id::RenderPipelineId>,
+        implicit_pipeline_ids: Option<ImplicitPipelineIds<G>>,
+        sources: Option<&PipelineSources>,
+    ) -> (
+        id::RenderPipelineId,
+        Option<pipeline::CreateRenderPipelineError>,
     ) {
         profiling::scope!("Device::create_render_pipeline");
 
@@ -4749,17 +5257,23 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                 });
             }
 
-            let pipeline = match device.create_render_pipeline(
+            let mut pipeline = match device.create_render_pipeline(
                 device_id,
                 adapter,
                 desc,
                 implicit_context,
+                sources,
                 hub,
                 &mut token,
             ) {
                 Ok(pair) => pair,
                 Err(e) => break e,
             };
+            if device.shader_hot_reload.load(Ordering::Relaxed) {
+                let mut reload_desc = desc.to_static();
+                reload_desc.layout = Some(pipeline.layout_id.value.0);
+                pipeline.reload_desc = Some(Box::new(reload_desc));
+            }
             let ref_count = pipeline.life_guard.add_ref();
 
             let id = fid.assign(pipeline, &mut token);
@@ -4867,6 +5381,44 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
     ) -> (
         id::ComputePipelineId,
         Option<pipeline::CreateComputePipelineError>,
+    ) {
+        self.create_compute_pipeline_impl::<A>(device_id, desc, id_in, implicit_pipeline_ids, None)
+    }
+
+    /// Creates a compute pipeline from sources retained with
+    /// [`Global::device_retain_pipeline_sources`], and releases them.
+    pub fn device_create_pending_compute_pipeline<A: HalApi>(
+        &self,
+        device_id: id::DeviceId,
+        desc: &pipeline::ComputePipelineDescriptor,
+        id_in: Input<G, id::ComputePipelineId>,
+        implicit_pipeline_ids: Option<ImplicitPipelineIds<G>>,
+        sources: PipelineSources,
+    ) -> (
+        id::ComputePipelineId,
+        Option<pipeline::CreateComputePipelineError>,
+    ) {
+        let result = self.create_compute_pipeline_impl::<A>(
+            device_id,
+            desc,
+            id_in,
+            implicit_pipeline_ids,
+            Some(&sources),
+        );
+        self.release_pipeline_sources::<A>(sources);
+        result
+    }
+
+    fn create_compute_pipeline_impl<A: HalApi>(
+        &self,
+        device_id: id::DeviceId,
+        desc: &pipeline::ComputePipelineDescriptor,
+        id_in: Input<G, id::ComputePipelineId>,
+        implicit_pipeline_ids: Option<ImplicitPipelineIds<G>>,
+        sources: Option<&PipelineSources>,
+    ) -> (
+        id::ComputePipelineId,
+        Option<pipeline::CreateComputePipelineError>,
     ) {
         profiling::scope!("Device::create_compute_pipeline");
 
@@ -4891,16 +5443,22 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                 });
             }
 
-            let pipeline = match device.create_compute_pipeline(
+            let mut pipeline = match device.create_compute_pipeline(
                 device_id,
                 desc,
                 implicit_context,
+                sources,
                 hub,
                 &mut token,
             ) {
                 Ok(pair) => pair,
                 Err(e) => break e,
             };
+            if device.shader_hot_reload.load(Ordering::Relaxed) {
+                let mut reload_desc = desc.to_static();
+                reload_desc.layout = Some(pipeline.layout_id.value.0);
+                pipeline.reload_desc = Some(Box::new(reload_desc));
+            }
             let ref_count = pipeline.life_guard.add_ref();
 
             let id = fid.assign(pipeline, &mut token);
@@ -5002,83 +5560,12 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         &self,
         surface_id: id::SurfaceId,
         device_id: id::DeviceId,
-        config: &wgt::SurfaceConfiguration,
+        config: &wgt::SurfaceConfiguration<Vec<wgt::TextureFormat>>,
     ) -> Option<present::ConfigureSurfaceError> {
         use hal::{Adapter as _, Surface as _};
         use present::ConfigureSurfaceError as E;
         profiling::scope!("surface_configure");
 
-        fn validate_surface_configuration(
-            config: &mut hal::SurfaceConfiguration,
-            caps: &hal::SurfaceCapabilities,
-        ) -> Result<(), E> {
-            let width = config.extent.width;
-            let height = config.extent.height;
-            if width < caps.extents.start().width
-                || width > caps.extents.end().width
-                || height < caps.extents.start().height
-                || height > caps.extents.end().height
-            {
-                log::warn!(
-                    "Requested size {}x{} is outside of the supported range: {:?}",
-                    width,
-                    height,
-                    caps.extents
-                );
-            }
-            if !caps.present_modes.contains(&config.present_mode) {
-                let new_mode = 'b: loop {
-                    // Automatic present mode checks.
-                    //
-                    // The "Automatic" modes are never supported by the backends.
-                    let fallbacks = match config.present_mode {
-                        wgt::PresentMode::AutoVsync => {
-                            &[wgt::PresentMode::FifoRelaxed, wgt::PresentMode::Fifo][..]
-                        }
-                        // Always end in FIFO to make sure it's always supported
-                        wgt::PresentMode::AutoNoVsync => &[
-                            wgt::PresentMode::Immediate,
-                            wgt::PresentMode::Mailbox,
-                            wgt::PresentMode::Fifo,
-                        ][..],
-                        _ => {
-                            return Err(E::UnsupportedPresentMode {
-                                requested: config.present_mode,
-                                available: caps.present_modes.clone(),
-                            });
-                        }
-                    };
-
-                    for &fallback in fallbacks {
-                        if caps.present_modes.contains(&fallback) {
-                            break 'b fallback;
-                        }
-                    }
-
-                    unreachable!("Fallback system failed to choose present mode. This is a bug. Mode: {:?}, Options: {:?}", config.present_mode, &caps.present_modes);
-                };
-
-                log::info!(
-                    "Automatically choosing presentation mode by rule {:?}. Chose {new_mode:?}",
-                    config.present_mode
-                );
-                config.present_mode = new_mode;
-            }
-            if !caps.formats.contains(&config.format) {
-                return Err(E::UnsupportedFormat {
-                    requested: config.format,
-                    available: caps.formats.clone(),
-                });
-            }
-            if !caps.usage.contains(config.usage) {
-                return Err(E::UnsupportedUsage);
-            }
-            if width == 0 || height == 0 {
-                return Err(E::ZeroArea);
-            }
-            Ok(())
-        }
-
         log::info!("configuring surface with {:?}", config);
         let hub = A::hub(self);
         let mut token = Token::root();
@@ -5087,7 +5574,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         let (adapter_guard, mut token) = hub.adapters.read(&mut token);
         let (device_guard, _token) = hub.devices.read(&mut token);
 
-        let error = loop {
+        let error = 'outer: loop {
             let device = match device_guard.get(device_id) {
                 Ok(device) => device,
                 Err(_) => break DeviceError::Invalid.into(),
@@ -5113,13 +5600,32 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                 }
             };
 
+            let mut hal_view_formats = vec![];
+            for format in config.view_formats.iter() {
+                if *format == config.format {
+                    continue;
+                }
+                if config.format.remove_srgb_suffix() != format.remove_srgb_suffix() {
+                    break 'outer E::InvalidViewFormat(*format, config.format);
+                }
+                hal_view_formats.push(*format);
+            }
+
+            if !hal_view_formats.is_empty() {
+                if let Err(missing_flag) =
+                    device.require_downlevel_flags(wgt::DownlevelFlags::VIEW_FORMATS)
+                {
+                    break 'outer E::MissingDownlevelFlags(missing_flag);
+                }
+            }
+
             let num_frames = present::DESIRED_NUM_FRAMES
                 .max(*caps.swap_chain_sizes.start())
                 .min(*caps.swap_chain_sizes.end());
             let mut hal_config = hal::SurfaceConfiguration {
                 swap_chain_size: num_frames,
                 present_mode: config.present_mode,
-                composite_alpha_mode: hal::CompositeAlphaMode::Opaque,
+                composite_alpha_mode: config.alpha_mode,
                 format: config.format,
                 extent: wgt::Extent3d {
                     width: config.width,
@@ -5127,6 +5633,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                     depth_or_array_layers: 1,
                 },
                 usage: conv::map_texture_usage(config.usage, hal::FormatAspects::COLOR),
+                view_formats: hal_view_formats,
             };
 
             if let Err(error) = validate_surface_configuration(&mut hal_config, &caps) {
@@ -5604,7 +6111,11 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                         queued: true,
                     });
                 }
-                let _ = ptr;
+                if let Some(ref contents) = buffer.index_contents {
+                    let data =
+                        unsafe { std::slice::from_raw_parts(ptr.as_ptr(), buffer.size as usize) };
+                    contents.lock().write(0, data);
+                }
                 if needs_flush {
                     unsafe {
                         device
@@ -5665,7 +6176,15 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                             queued: false,
                         });
                     }
-                    let _ = (ptr, range);
+                    if let Some(ref contents) = buffer.index_contents {
+                        let data = unsafe {
+                            std::slice::from_raw_parts(
+                                ptr.as_ptr(),
+                                (range.end - range.start) as usize,
+                            )
+                        };
+                        contents.lock().write(range.start, data);
+                    }
                 }
                 unsafe {
                     device
@@ -5707,3 +6226,94 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn capabilities(
+        composite_alpha_modes: Vec<wgt::CompositeAlphaMode>,
+    ) -> hal::SurfaceCapabilities {
+        let extent = wgt::Extent3d {
+            width: 1,
+            height: 1,
+            depth_or_array_layers: 1,
+        };
+        hal::SurfaceCapabilities {
+            formats: vec![TextureFormat::Bgra8UnormSrgb],
+            swap_chain_sizes: 2..=3,
+            current_extent: None,
+            extents: extent..=extent,
+            usage: hal::TextureUses::COLOR_TARGET,
+            present_modes: vec![wgt::PresentMode::Fifo],
+            composite_alpha_modes,
+        }
+    }
+
+    fn configuration(composite_alpha_mode: wgt::CompositeAlphaMode) -> hal::SurfaceConfiguration {
+        hal::SurfaceConfiguration {
+            swap_chain_size: 2,
+            present_mode: wgt::PresentMode::Fifo,
+            composite_alpha_mode,
+            format: TextureFormat::Bgra8UnormSrgb,
+            extent: wgt::Extent3d {
+                width: 1,
+                height: 1,
+                depth_or_array_layers: 1,
+            },
+            usage: hal::TextureUses::COLOR_TARGET,
+            view_formats: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn surface_alpha_mode_reported_first_is_accepted() {
+        use wgt::CompositeAlphaMode as M;
+        for modes in [
+            vec![M::Opaque, M::PreMultiplied],
+            vec![M::PreMultiplied, M::Opaque],
+            vec![M::PostMultiplied],
+            vec![M::Inherit, M::PreMultiplied],
+        ] {
+            let caps = capabilities(modes.clone());
+            let mut config = configuration(modes[0]);
+            assert!(validate_surface_configuration(&mut config, &caps).is_ok());
+            assert_eq!(config.composite_alpha_mode, modes[0]);
+        }
+    }
+
+    #[test]
+    fn surface_alpha_mode_auto() {
+        use wgt::CompositeAlphaMode as M;
+        for (modes, chosen) in [
+            (vec![M::PreMultiplied, M::Opaque], Some(M::Opaque)),
+            (vec![M::Inherit], Some(M::Inherit)),
+            (vec![M::PreMultiplied], None),
+        ] {
+            let caps = capabilities(modes);
+            let mut config = configuration(M::Auto);
+            let result = validate_surface_configuration(&mut config, &caps);
+            match chosen {
+                Some(mode) => {
+                    assert!(result.is_ok());
+                    assert_eq!(config.composite_alpha_mode, mode);
+                }
+                None => assert!(matches!(
+                    result,
+                    Err(present::ConfigureSurfaceError::UnsupportedAlphaMode { .. })
+                )),
+            }
+        }
+    }
+
+    #[test]
+    fn surface_alpha_mode_unsupported() {
+        use wgt::CompositeAlphaMode as M;
+        let caps = capabilities(vec![M::Opaque]);
+        let mut config = configuration(M::PostMultiplied);
+        assert!(matches!(
+            validate_surface_configuration(&mut config, &caps),
+            Err(present::ConfigureSurfaceError::UnsupportedAlphaMode { .. })
+        ));
+    }
+}
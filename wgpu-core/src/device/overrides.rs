@@ -0,0 +1,355 @@
+//! Pipeline-overridable constants of WGSL shaders.
+//!
+//! The WGSL front end of naga 0.9 parses neither `override` declarations nor
+//! workgroup sizes given by constants. Before a WGSL module is parsed,
+//! [`lower`] turns every `override` into a module-scope `let`, and every
+//! constant in a `@workgroup_size` attribute into a placeholder literal.
+//! Offsets in the source are kept, so that errors still point at the text the
+//! user wrote, except inside an `override` without an initializer, which gets
+//! a placeholder initializer.
+//!
+//! Once the module is parsed, [`WgslOverrides::resolve`] marks these constants
+//! as pipeline-overridable, and writes the workgroup sizes they give in the
+//! entry points. Backends then write the values of a pipeline into the module
+//! with [`hal::auxil::overrides::apply`].
+
+use hal::auxil::overrides::ModuleOverrides;
+use std::{borrow::Cow, collections::HashSet};
+
+#[derive(Debug)]
+struct Override {
+    name: String,
+    id: Option<u32>,
+    /// Set if the declaration has no initializer.
+    required: bool,
+}
+
+#[derive(Debug)]
+struct WorkgroupSize {
+    function: String,
+    /// Name of the constant giving each dimension, if it isn't a literal.
+    dimensions: [Option<String>; 3],
+}
+
+/// Pipeline-overridable constants and workgroup sizes found by [`lower`].
+#[derive(Debug, Default)]
+pub(crate) struct WgslOverrides {
+    overrides: Vec<Override>,
+    workgroup_sizes: Vec<WorkgroupSize>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Token<'a> {
+    Word(&'a str),
+    Number(&'a str),
+    Punct(u8),
+}
+
+struct Lexer<'a> {
+    source: &'a str,
+    pos: usize,
+}
+
+impl<'a> Lexer<'a> {
+    fn skip_trivia(&mut self) {
+        let bytes = self.source.as_bytes();
+        loop {
+            let rest = &bytes[self.pos..];
+            if rest.starts_with(b"//") {
+                while self.pos < bytes.len() && bytes[self.pos] != b'\n' {
+                    self.pos += 1;
+                }
+            } else if rest.starts_with(b"/*") {
+                let mut depth = 0;
+                while self.pos < bytes.len() {
+                    let rest = &bytes[self.pos..];
+                    if rest.starts_with(b"/*") {
+                        depth += 1;
+                        self.pos += 2;
+                    } else if rest.starts_with(b"*/") {
+                        depth -= 1;
+                        self.pos += 2;
+                        if depth == 0 {
+                            break;
+                        }
+                    } else {
+                        self.pos += 1;
+                    }
+                }
+            } else if matches!(rest.first(), Some(c) if c.is_ascii_whitespace()) {
+                self.pos += 1;
+            } else {
+                return;
+            }
+        }
+    }
+
+    /// Returns the next token and its byte range.
+    fn next(&mut self) -> Option<(Token<'a>, usize, usize)> {
+        self.skip_trivia();
+        let bytes = self.source.as_bytes();
+        let start = self.pos;
+        let first = *bytes.get(start)?;
+        let is_word = |c: u8| c == b'_' || c.is_ascii_alphanumeric() || !c.is_ascii();
+        let token = if first == b'_' || first.is_ascii_alphabetic() || !first.is_ascii() {
+            while self.pos < bytes.len() && is_word(bytes[self.pos]) {
+                self.pos += 1;
+            }
+            Token::Word(&self.source[start..self.pos])
+        } else if first.is_ascii_digit() {
+            while self.pos < bytes.len() && (is_word(bytes[self.pos]) || bytes[self.pos] == b'.') {
+                self.pos += 1;
+            }
+            Token::Number(&self.source[start..self.pos])
+        } else {
+            self.pos += 1;
+            Token::Punct(first)
+        };
+        Some((token, start, self.pos))
+    }
+
+    fn expect(&mut self, punct: u8) -> Result<usize, String> {
+        match self.next() {
+            Some((Token::Punct(p), _, end)) if p == punct => Ok(end),
+            _ => Err(format!(
+                "expected '{}' at offset {}",
+                punct as char, self.pos
+            )),
+        }
+    }
+
+    fn word(&mut self) -> Result<(&'a str, usize, usize), String> {
+        match self.next() {
+            Some((Token::Word(word), start, end)) => Ok((word, start, end)),
+            _ => Err(format!("expected an identifier at offset {}", self.pos)),
+        }
+    }
+}
+
+fn parse_id(literal: &str) -> Option<u32> {
+    let digits = literal.trim_end_matches(&['u', 'i'][..]);
+    match digits
+        .strip_prefix("0x")
+        .or_else(|| digits.strip_prefix("0X"))
+    {
+        Some(hex) => u32::from_str_radix(hex, 16).ok(),
+        None => digits.parse().ok(),
+    }
+}
+
+/// Initializer given to an `override` without one, by type.
+///
+/// It must be at most 4 bytes long, to fit in the space left by replacing
+/// `override` with `let`, along with the `=`.
+fn placeholder(ty: &str) -> Option<&'static str> {
+    Some(match ty {
+        "bool" => "true",
+        "i32" => "1",
+        "u32" => "1u",
+        "f32" => "0.0",
+        _ => return None,
+    })
+}
+
+/// Rewrites the pipeline-overridable constants of a WGSL module into WGSL
+/// naga parses.
+pub(crate) fn lower(source: &str) -> Result<(Cow<'_, str>, WgslOverrides), String> {
+    let mut overrides = WgslOverrides::default();
+    if !source.contains("override") && !source.contains("workgroup_size") {
+        return Ok((Cow::Borrowed(source), overrides));
+    }
+
+    let mut lowered = source.as_bytes().to_vec();
+    let mut lexer = Lexer { source, pos: 0 };
+    let mut pending_id = None;
+    let mut pending_size = None;
+    while let Some((token, start, _)) = lexer.next() {
+        match token {
+            Token::Punct(b'@') => match lexer.word()?.0 {
+                "id" => {
+                    lexer.expect(b'(')?;
+                    let id = match lexer.next() {
+                        Some((Token::Number(literal), _, _)) => parse_id(literal),
+                        _ => None,
+                    }
+                    .ok_or_else(|| format!("invalid @id at offset {}", start))?;
+                    let end = lexer.expect(b')')?;
+                    for byte in lowered[start..end].iter_mut() {
+                        *byte = b' ';
+                    }
+                    pending_id = Some(id);
+                }
+                "workgroup_size" => {
+                    lexer.expect(b'(')?;
+                    let mut dimensions = [None, None, None];
+                    for dimension in dimensions.iter_mut() {
+                        match lexer.next() {
+                            Some((Token::Word(name), start, end)) => {
+                                lowered[start] = b'1';
+                                for byte in lowered[start + 1..end].iter_mut() {
+                                    *byte = b' ';
+                                }
+                                *dimension = Some(name.to_string());
+                            }
+                            Some((Token::Number(_), _, _)) => {}
+                            _ => {
+                                return Err(format!("invalid @workgroup_size at offset {}", start))
+                            }
+                        }
+                        match lexer.next() {
+                            Some((Token::Punct(b')'), _, _)) => break,
+                            Some((Token::Punct(b','), _, _)) => {}
+                            _ => {
+                                return Err(format!("invalid @workgroup_size at offset {}", start))
+                            }
+                        }
+                    }
+                    pending_size = Some(dimensions);
+                }
+                _ => {}
+            },
+            Token::Word("override") => {
+                let (name, _, _) = lexer.word()?;
+                let mut ty = None;
+                let mut next = lexer.next();
+                if let Some((Token::Punct(b':'), _, _)) = next {
+                    ty = Some(lexer.word()?.0);
+                    next = lexer.next();
+                }
+                let required = match next {
+                    Some((Token::Punct(b'='), _, _)) => {
+                        lowered[start..start + 8].copy_from_slice(b"let     ");
+                        false
+                    }
+                    Some((Token::Punct(b';'), semicolon, end)) => {
+                        let value = ty.and_then(placeholder).ok_or_else(|| {
+                            format!("override '{}' needs a scalar type or an initializer", name)
+                        })?;
+                        let declaration = format!(
+                            "let{}={};",
+                            &source[start + "override".len()..semicolon],
+                            value
+                        );
+                        lowered[start..end].fill(b' ');
+                        lowered[start..start + declaration.len()]
+                            .copy_from_slice(declaration.as_bytes());
+                        true
+                    }
+                    _ => return Err(format!("invalid declaration of override '{}'", name)),
+                };
+                overrides.overrides.push(Override {
+                    name: name.to_string(),
+                    id: pending_id.take(),
+                    required,
+                });
+            }
+            Token::Word(keyword @ ("fn" | "var" | "let" | "struct" | "type")) => {
+                if pending_id.take().is_some() {
+                    return Err("@id can only be applied to an override".to_string());
+                }
+                if keyword == "fn" {
+                    let (function, _, _) = lexer.word()?;
+                    if let Some(dimensions) = pending_size.take() {
+                        if dimensions.iter().any(Option::is_some) {
+                            overrides.workgroup_sizes.push(WorkgroupSize {
+                                function: function.to_string(),
+                                dimensions,
+                            });
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let lowered = String::from_utf8(lowered).map_err(|e| e.to_string())?;
+    Ok((Cow::Owned(lowered), overrides))
+}
+
+impl WgslOverrides {
+    /// Marks the constants of `module` declared by `override` as
+    /// pipeline-overridable, and writes the workgroup sizes given by constants.
+    pub(crate) fn resolve(self, module: &mut naga::Module) -> Result<ModuleOverrides, String> {
+        let mut resolved = ModuleOverrides::default();
+
+        let mut ids = HashSet::new();
+        for ov in self.overrides.iter() {
+            if let Some(id) = ov.id {
+                if !ids.insert(id) {
+                    return Err(format!("@id({}) is used by several overrides", id));
+                }
+            }
+        }
+        let mut next_id = 0;
+        for ov in self.overrides {
+            let id = match ov.id {
+                Some(id) => id,
+                None => {
+                    while ids.contains(&next_id) {
+                        next_id += 1;
+                    }
+                    ids.insert(next_id);
+                    next_id
+                }
+            };
+            let (handle, constant) = module
+                .constants
+                .iter_mut()
+                .find(|item| item.1.name.as_deref() == Some(ov.name.as_str()))
+                .ok_or_else(|| format!("override '{}' is not a constant", ov.name))?;
+            if let naga::ConstantInner::Composite { .. } = constant.inner {
+                return Err(format!("override '{}' is not a scalar", ov.name));
+            }
+            constant.specialization = Some(id);
+            if ov.required {
+                resolved.required.push(handle);
+            }
+        }
+
+        for size in self.workgroup_sizes {
+            let index = module
+                .entry_points
+                .iter()
+                .position(|ep| ep.name == size.function)
+                .ok_or_else(|| format!("function '{}' is not an entry point", size.function))?;
+            let mut dimensions = [None; 3];
+            for (i, name) in size.dimensions.iter().enumerate() {
+                let name = match *name {
+                    Some(ref name) => name,
+                    None => continue,
+                };
+                let (handle, constant) = module
+                    .constants
+                    .iter()
+                    .find(|item| item.1.name.as_deref() == Some(name.as_str()))
+                    .ok_or_else(|| format!("workgroup size '{}' is not a constant", name))?;
+                let value = match constant.inner {
+                    naga::ConstantInner::Scalar {
+                        value: naga::ScalarValue::Sint(v),
+                        ..
+                    } if v > 0 => v as u32,
+                    naga::ConstantInner::Scalar {
+                        value: naga::ScalarValue::Uint(v),
+                        ..
+                    } if v > 0 => v as u32,
+                    _ => {
+                        return Err(format!(
+                            "workgroup size '{}' is not a positive integer",
+                            name
+                        ))
+                    }
+                };
+                if constant.specialization.is_some() {
+                    dimensions[i] = Some(handle);
+                }
+                module.entry_points[index].workgroup_size[i] = value;
+            }
+            if dimensions.iter().any(Option::is_some) {
+                resolved.workgroup_sizes.push((index, dimensions));
+            }
+        }
+
+        Ok(resolved)
+    }
+}
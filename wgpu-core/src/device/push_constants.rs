@@ -0,0 +1,406 @@
+//! Emulation of push constants with uniform buffers.
+//!
+//! When a device is created with [`wgt::Features::PUSH_CONSTANT_EMULATION`] but
+//! without [`wgt::Features::PUSH_CONSTANTS`], the group right after the last one
+//! exposed by the device is reserved:
+//!
+//! - push constant variables of shader modules are rewritten into a uniform
+//!   buffer at binding 0 of the reserved group,
+//! - pipeline layouts with push constant ranges get a layout with that uniform
+//!   buffer, with a dynamic offset, for the reserved group,
+//! - before a pass begins, every state the push constants go through in the
+//!   pass is uploaded to a [`PushConstantBlock`]. Commands of the pass then
+//!   select the current state by binding the reserved group at the matching
+//!   offset.
+//!
+//! The passes of a command buffer share its blocks, and blocks are reused by
+//! other command buffers once the submissions reading them are done, so the
+//! blocks of a device work as a ring.
+
+use super::{Device, DeviceError};
+use crate::hub::HalApi;
+use hal::{CommandEncoder as _, Device as _};
+use std::{iter, ptr};
+
+/// Size, in bytes, of the emulated push constants.
+pub(crate) const EMULATED_PUSH_CONSTANT_SIZE: u32 = 256;
+
+/// Minimum size, in bytes, of the blocks push constant states are uploaded to.
+const BLOCK_SIZE: u32 = 64 * 1024;
+
+#[derive(Debug)]
+pub(crate) struct PushConstantEmulation<A: hal::Api> {
+    /// Index of the reserved group.
+    pub(crate) group: u32,
+    /// Layout of the reserved group.
+    bind_group_layout: A::BindGroupLayout,
+    /// Layout of the groups between the ones of a pipeline layout and the reserved group.
+    empty_bind_group_layout: A::BindGroupLayout,
+    /// Distance, in bytes, between two states in a block.
+    stride: u32,
+}
+
+impl<A: hal::Api> PushConstantEmulation<A> {
+    pub(crate) unsafe fn new(
+        device: &A::Device,
+        group: u32,
+        limits: &wgt::Limits,
+    ) -> Result<Self, DeviceError> {
+        let entry = wgt::BindGroupLayoutEntry {
+            binding: 0,
+            visibility: wgt::ShaderStages::all(),
+            ty: wgt::BindingType::Buffer {
+                ty: wgt::BufferBindingType::Uniform,
+                has_dynamic_offset: true,
+                min_binding_size: wgt::BufferSize::new(EMULATED_PUSH_CONSTANT_SIZE as u64),
+            },
+            count: None,
+        };
+        let bind_group_layout =
+            device.create_bind_group_layout(&hal::BindGroupLayoutDescriptor {
+                label: Some("(wgpu internal) push constants"),
+                flags: hal::BindGroupLayoutFlags::empty(),
+                entries: &[entry],
+            })?;
+        let empty_bind_group_layout =
+            device.create_bind_group_layout(&hal::BindGroupLayoutDescriptor {
+                label: Some("(wgpu internal) empty"),
+                flags: hal::BindGroupLayoutFlags::empty(),
+                entries: &[],
+            })?;
+
+        Ok(Self {
+            group,
+            bind_group_layout,
+            empty_bind_group_layout,
+            stride: hal::auxil::align_to(
+                EMULATED_PUSH_CONSTANT_SIZE,
+                limits.min_uniform_buffer_offset_alignment,
+            ),
+        })
+    }
+
+    pub(crate) unsafe fn dispose(self, device: &A::Device) {
+        device.destroy_bind_group_layout(self.bind_group_layout);
+        device.destroy_bind_group_layout(self.empty_bind_group_layout);
+    }
+
+    /// Turns the push constant variables of `module` into uniform buffers in the reserved group.
+    ///
+    /// Returns `false` if the module has no push constants.
+    pub(crate) fn rewrite_module(&self, module: &mut naga::Module) -> bool {
+        let mut rewritten = false;
+        for (_, var) in module.global_variables.iter_mut() {
+            if var.space == naga::AddressSpace::PushConstant {
+                var.space = naga::AddressSpace::Uniform;
+                var.binding = Some(naga::ResourceBinding {
+                    group: self.group,
+                    binding: 0,
+                });
+                rewritten = true;
+            }
+        }
+        rewritten
+    }
+
+    /// Returns the layouts following the `group_count` ones of a pipeline layout
+    /// with push constant ranges.
+    pub(crate) fn extra_bind_group_layouts(
+        &self,
+        group_count: usize,
+    ) -> impl Iterator<Item = &A::BindGroupLayout> {
+        (group_count..self.group as usize)
+            .map(move |_| &self.empty_bind_group_layout)
+            .chain(iter::once(&self.bind_group_layout))
+    }
+
+    pub(crate) fn states(&self) -> PushConstantStates {
+        PushConstantStates {
+            current: [0; EMULATED_PUSH_CONSTANT_SIZE as usize],
+            data: vec![0; self.stride as usize],
+            stride: self.stride as usize,
+        }
+    }
+
+    unsafe fn create_block(
+        &self,
+        device: &A::Device,
+        size: u32,
+    ) -> Result<PushConstantBlock<A>, DeviceError> {
+        let staging = device.create_buffer(&hal::BufferDescriptor {
+            label: Some("(wgpu internal) Staging"),
+            size: size as wgt::BufferAddress,
+            usage: hal::BufferUses::MAP_WRITE | hal::BufferUses::COPY_SRC,
+            memory_flags: hal::MemoryFlags::empty(),
+        })?;
+        let buffer = device.create_buffer(&hal::BufferDescriptor {
+            label: Some("(wgpu internal) push constants"),
+            size: size as wgt::BufferAddress,
+            usage: hal::BufferUses::COPY_DST | hal::BufferUses::UNIFORM,
+            memory_flags: hal::MemoryFlags::empty(),
+        })?;
+        let bind_group = device.create_bind_group(&hal::BindGroupDescriptor {
+            label: Some("(wgpu internal) push constants"),
+            layout: &self.bind_group_layout,
+            buffers: &[hal::BufferBinding {
+                buffer: &buffer,
+                offset: 0,
+                size: wgt::BufferSize::new(EMULATED_PUSH_CONSTANT_SIZE as u64),
+            }],
+            samplers: &[],
+            textures: &[],
+            entries: &[hal::BindGroupEntry {
+                binding: 0,
+                resource_index: 0,
+                count: 1,
+            }],
+        })?;
+        Ok(PushConstantBlock {
+            staging,
+            buffer,
+            bind_group,
+            size,
+        })
+    }
+}
+
+impl<A: HalApi> PushConstantEmulation<A> {
+    /// Uploads the states of the push constants of a pass, after the ones of
+    /// the previous passes of the command buffer in `uploaded`.
+    ///
+    /// The copy is recorded in `encoder`, which must not be in a pass.
+    pub(crate) unsafe fn upload(
+        &self,
+        device: &Device<A>,
+        encoder: &mut A::CommandEncoder,
+        uploaded: &mut EmulatedPushConstants<A>,
+        states: &PushConstantStates,
+    ) -> Result<PassPushConstants, DeviceError> {
+        let size = states.data.len() as u32;
+        let fits = matches!(
+            uploaded.blocks.last(),
+            Some(block) if uploaded.used + size <= block.size
+        );
+        if !fits {
+            let recycled = device
+                .command_allocator
+                .lock()
+                .acquire_push_constant_block(size);
+            let block = match recycled {
+                Some(block) => block,
+                None => self.create_block(&device.raw, size.max(BLOCK_SIZE))?,
+            };
+            uploaded.blocks.push(block);
+            uploaded.used = 0;
+        }
+        let index = uploaded.blocks.len() - 1;
+        let block = &uploaded.blocks[index];
+        let offset = uploaded.used;
+        let range = offset as wgt::BufferAddress..(offset + size) as wgt::BufferAddress;
+
+        // The whole block is mapped, so that the flushed range is relative to
+        // both the buffer and the mapping.
+        let mapping = device
+            .raw
+            .map_buffer(&block.staging, 0..block.size as wgt::BufferAddress)?;
+        ptr::copy_nonoverlapping(
+            states.data.as_ptr(),
+            mapping.ptr.as_ptr().add(offset as usize),
+            states.data.len(),
+        );
+        if !mapping.is_coherent {
+            device
+                .raw
+                .flush_mapped_ranges(&block.staging, iter::once(range));
+        }
+        device.raw.unmap_buffer(&block.staging)?;
+
+        // The states of the previous passes may be read before the copy.
+        let previous_usage = if offset == 0 {
+            hal::BufferUses::empty()
+        } else {
+            hal::BufferUses::UNIFORM
+        };
+        encoder.transition_buffers(
+            [
+                hal::BufferBarrier {
+                    buffer: &block.staging,
+                    usage: hal::BufferUses::MAP_WRITE..hal::BufferUses::COPY_SRC,
+                },
+                hal::BufferBarrier {
+                    buffer: &block.buffer,
+                    usage: previous_usage..hal::BufferUses::COPY_DST,
+                },
+            ]
+            .into_iter(),
+        );
+        encoder.copy_buffer_to_buffer(
+            &block.staging,
+            &block.buffer,
+            iter::once(hal::BufferCopy {
+                src_offset: offset as wgt::BufferAddress,
+                dst_offset: offset as wgt::BufferAddress,
+                size: wgt::BufferSize::new(size as u64).unwrap(),
+            }),
+        );
+        encoder.transition_buffers(iter::once(hal::BufferBarrier {
+            buffer: &block.buffer,
+            usage: hal::BufferUses::COPY_DST..hal::BufferUses::UNIFORM,
+        }));
+        uploaded.used = offset + size;
+
+        Ok(PassPushConstants {
+            block: index,
+            offset,
+            group: self.group,
+            stride: self.stride,
+        })
+    }
+}
+
+/// States the push constants of a pass go through, in order.
+#[derive(Debug)]
+pub(crate) struct PushConstantStates {
+    current: [u8; EMULATED_PUSH_CONSTANT_SIZE as usize],
+    /// States, `stride` bytes apart. The first one is all zeros.
+    data: Vec<u8>,
+    stride: usize,
+}
+
+impl PushConstantStates {
+    /// Clears the push constants, after a change of pipeline layout.
+    ///
+    /// The zeroed state is the first one, so it isn't recorded again.
+    pub(crate) fn reset(&mut self) {
+        self.current = [0; EMULATED_PUSH_CONSTANT_SIZE as usize];
+    }
+
+    /// Records a new state, with `size_bytes` bytes at `offset` set to `values`, or cleared.
+    pub(crate) fn update(&mut self, offset: u32, size_bytes: u32, values: Option<&[u32]>) {
+        let start = (offset as usize).min(self.current.len());
+        let end = (offset as usize + size_bytes as usize).min(self.current.len());
+        let target = &mut self.current[start..end];
+        match values {
+            Some(values) => {
+                for (bytes, value) in target.chunks_mut(4).zip(values) {
+                    bytes.copy_from_slice(&value.to_ne_bytes()[..bytes.len()]);
+                }
+            }
+            None => target.fill(0),
+        }
+
+        let state = self.data.len();
+        self.data.resize(state + self.stride, 0);
+        self.data[state..state + self.current.len()].copy_from_slice(&self.current);
+    }
+}
+
+/// Buffers the push constant states of passes are uploaded to.
+///
+/// Blocks are recycled by the command allocator of the device once the
+/// submissions using them are done.
+#[derive(Debug)]
+pub(crate) struct PushConstantBlock<A: hal::Api> {
+    staging: A::Buffer,
+    buffer: A::Buffer,
+    bind_group: A::BindGroup,
+    size: u32,
+}
+
+impl<A: hal::Api> PushConstantBlock<A> {
+    pub(super) fn size(&self) -> u32 {
+        self.size
+    }
+
+    pub(super) unsafe fn destroy(self, device: &A::Device) {
+        device.destroy_bind_group(self.bind_group);
+        device.destroy_buffer(self.buffer);
+        device.destroy_buffer(self.staging);
+    }
+}
+
+/// Push constant states uploaded by the passes of a command buffer.
+///
+/// The passes take their states one after the other in the blocks, and only
+/// move on to a new block when the last one is full.
+#[derive(Debug)]
+pub(crate) struct EmulatedPushConstants<A: hal::Api> {
+    blocks: Vec<PushConstantBlock<A>>,
+    /// Bytes taken in the last block.
+    used: u32,
+}
+
+impl<A: hal::Api> EmulatedPushConstants<A> {
+    pub(crate) fn new() -> Self {
+        Self {
+            blocks: Vec::new(),
+            used: 0,
+        }
+    }
+
+    pub(crate) fn cursor(&self, pass: PassPushConstants) -> PushConstantCursor<'_, A> {
+        PushConstantCursor {
+            bind_group: &self.blocks[pass.block].bind_group,
+            pass,
+            current: 0,
+            last: 0,
+        }
+    }
+
+    pub(crate) fn into_blocks(self) -> Vec<PushConstantBlock<A>> {
+        self.blocks
+    }
+}
+
+/// Location of the push constant states of a pass, returned by
+/// [`PushConstantEmulation::upload`].
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct PassPushConstants {
+    block: usize,
+    /// Offset, in bytes, of the first state in the block.
+    offset: u32,
+    group: u32,
+    stride: u32,
+}
+
+/// Selects the push constant states of a pass while it's being encoded, in
+/// the order they were recorded in [`PushConstantStates`].
+pub(crate) struct PushConstantCursor<'a, A: hal::Api> {
+    bind_group: &'a A::BindGroup,
+    pass: PassPushConstants,
+    current: u32,
+    last: u32,
+}
+
+impl<A: hal::Api> PushConstantCursor<'_, A> {
+    /// Binds the zeroed state, after a change of pipeline layout.
+    pub(crate) unsafe fn reset(
+        &mut self,
+        encoder: &mut A::CommandEncoder,
+        layout: &A::PipelineLayout,
+    ) {
+        self.current = 0;
+        self.bind(encoder, layout);
+    }
+
+    /// Binds the next state, after an update of the push constants.
+    pub(crate) unsafe fn advance(
+        &mut self,
+        encoder: &mut A::CommandEncoder,
+        layout: &A::PipelineLayout,
+    ) {
+        self.last += 1;
+        self.current = self.last;
+        self.bind(encoder, layout);
+    }
+
+    /// Binds the current state.
+    pub(crate) unsafe fn bind(&self, encoder: &mut A::CommandEncoder, layout: &A::PipelineLayout) {
+        encoder.set_bind_group(
+            layout,
+            self.pass.group,
+            self.bind_group,
+            &[self.pass.offset + self.current * self.pass.stride],
+        );
+    }
+}
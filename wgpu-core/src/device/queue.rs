@@ -3,15 +3,16 @@ use crate::device::trace::Action;
 use crate::{
     command::{
         extract_texture_selector, validate_linear_texture_data, validate_texture_copy_range,
-        ClearError, CommandBuffer, CopySide, ImageCopyTexture, TransferError,
+        BarrierCause, BarrierRecorder, ClearError, CommandBuffer, CopySide, ImageCopyTexture,
+        TransferError,
     },
     conv,
-    device::{DeviceError, WaitIdleError},
+    device::{push_constants::PushConstantBlock, DeviceError, WaitIdleError},
     get_lowest_common_denom,
-    hub::{Global, GlobalIdentityHandlerFactory, HalApi, Input, Token},
+    hub::{Global, GlobalIdentityHandlerFactory, HalApi, Input, Storage, Token},
     id,
     init_tracker::{has_copy_partial_init_tracker_coverage, TextureInitRange},
-    resource::{BufferAccessError, BufferMapState, StagingBuffer, TextureInner},
+    resource::{BufferAccessError, BufferMapState, StagingBuffer, Texture, TextureInner},
     track, FastHashSet, SubmissionIndex,
 };
 
@@ -103,18 +104,36 @@ pub struct WrappedSubmissionIndex {
 pub enum TempResource<A: hal::Api> {
     Buffer(A::Buffer),
     Texture(A::Texture, SmallVec<[A::TextureView; 1]>),
+    BindGroup(A::BindGroup),
 }
 
 /// A queue execution for a particular command encoder.
 pub(super) struct EncoderInFlight<A: hal::Api> {
     raw: A::CommandEncoder,
     cmd_buffers: Vec<A::CommandBuffer>,
+    /// Blocks of emulated push constants read by the command buffers.
+    push_constant_blocks: Vec<PushConstantBlock<A>>,
 }
 
 impl<A: hal::Api> EncoderInFlight<A> {
-    pub(super) unsafe fn land(mut self) -> A::CommandEncoder {
+    pub(super) fn new(
+        raw: A::CommandEncoder,
+        cmd_buffers: Vec<A::CommandBuffer>,
+        push_constant_blocks: Vec<PushConstantBlock<A>>,
+    ) -> Self {
+        Self {
+            raw,
+            cmd_buffers,
+            push_constant_blocks,
+        }
+    }
+
+    /// Resets the encoder once its execution is done, and gives it back to
+    /// `command_allocator` along with the blocks it used.
+    pub(super) unsafe fn land(mut self, command_allocator: &mut super::CommandAllocator<A>) {
         self.raw.reset_all(self.cmd_buffers.into_iter());
-        self.raw
+        command_allocator.release_encoder(self.raw);
+        command_allocator.release_push_constant_blocks(self.push_constant_blocks);
     }
 }
 
@@ -174,6 +193,9 @@ impl<A: hal::Api> PendingWrites<A> {
                     }
                     device.destroy_texture(texture);
                 },
+                TempResource::BindGroup(bind_group) => unsafe {
+                    device.destroy_bind_group(bind_group);
+                },
             }
         }
     }
@@ -215,6 +237,7 @@ impl<A: hal::Api> PendingWrites<A> {
             Some(EncoderInFlight {
                 raw: mem::replace(&mut self.command_encoder, new_encoder),
                 cmd_buffers: mem::take(&mut self.executing_command_buffers),
+                push_constant_blocks: Vec::new(),
             })
         } else {
             None
@@ -369,6 +392,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             &staging_buffer,
             buffer_id,
             buffer_offset,
+            Some(data),
         );
 
         device.pending_writes.consume(staging_buffer);
@@ -436,6 +460,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             &staging_buffer,
             buffer_id,
             buffer_offset,
+            None,
         );
 
         device.pending_writes.consume(staging_buffer);
@@ -502,6 +527,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         staging_buffer: &StagingBuffer<A>,
         buffer_id: id::BufferId,
         buffer_offset: u64,
+        data: Option<&[u8]>,
     ) -> Result<(), QueueWriteError> {
         let hub = A::hub(self);
 
@@ -548,6 +574,15 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             let dst = buffer_guard.get_mut(buffer_id).unwrap();
             dst.initialization_status
                 .drain(buffer_offset..(buffer_offset + src_buffer_size));
+
+            if let Some(ref contents) = dst.index_contents {
+                let mut contents = contents.lock();
+                match data {
+                    Some(data) => contents.write(buffer_offset, data),
+                    // Staging buffers the user wrote to aren't read back.
+                    None => contents.invalidate(),
+                }
+            }
         }
 
         Ok(())
@@ -588,8 +623,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         }
 
         let (mut texture_guard, _) = hub.textures.write(&mut token); // For clear we need write access to the texture. TODO: Can we acquire write lock later?
-        let (selector, dst_base, texture_format) =
-            extract_texture_selector(destination, size, &*texture_guard)?;
+        let (_, _, texture_format) = extract_texture_selector(destination, size, &*texture_guard)?;
         let format_desc = texture_format.describe();
         //Note: `_source_bytes_per_array_layer` is ignored since we have a staging copy,
         // and it can have a different value.
@@ -603,9 +637,6 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             false,
         )?;
 
-        if !conv::is_valid_copy_dst_texture_format(texture_format) {
-            return Err(TransferError::CopyToForbiddenTextureFormat(texture_format).into());
-        }
         let (block_width, block_height) = format_desc.block_dimensions;
         let width_blocks = size.width / block_width as u32;
         let height_blocks = size.height / block_height as u32;
@@ -631,6 +662,174 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             (size.depth_or_array_layers - 1) * block_rows_per_image + height_blocks;
         let stage_size = stage_bytes_per_row as u64 * block_rows_in_copy as u64;
 
+        let bytes_per_row = if let Some(bytes_per_row) = data_layout.bytes_per_row {
+            bytes_per_row.get()
+        } else {
+            width_blocks * format_desc.block_size as u32
+        };
+
+        // Platform validation requires that the staging buffer always be
+        // freed, even if an error occurs. All paths from here must call
+        // `device.pending_writes.consume`.
+        let (staging_buffer, staging_buffer_ptr) =
+            prepare_staging_buffer(&mut device.raw, stage_size)?;
+
+        if stage_bytes_per_row == bytes_per_row {
+            profiling::scope!("copy aligned");
+            // Fast path if the data is already being aligned optimally.
+            unsafe {
+                ptr::copy_nonoverlapping(
+                    data.as_ptr().offset(data_layout.offset as isize),
+                    staging_buffer_ptr,
+                    stage_size as usize,
+                );
+            }
+        } else {
+            profiling::scope!("copy chunked");
+            // Copy row by row into the optimal alignment.
+            let copy_bytes_per_row = stage_bytes_per_row.min(bytes_per_row) as usize;
+            for layer in 0..size.depth_or_array_layers {
+                let rows_offset = layer * block_rows_per_image;
+                for row in 0..height_blocks {
+                    unsafe {
+                        ptr::copy_nonoverlapping(
+                            data.as_ptr().offset(
+                                data_layout.offset as isize
+                                    + (rows_offset + row) as isize * bytes_per_row as isize,
+                            ),
+                            staging_buffer_ptr.offset(
+                                (rows_offset + row) as isize * stage_bytes_per_row as isize,
+                            ),
+                            copy_bytes_per_row,
+                        );
+                    }
+                }
+            }
+        }
+
+        if let Err(e) = unsafe { staging_buffer.flush(&device.raw) } {
+            device.pending_writes.consume(staging_buffer);
+            return Err(e.into());
+        }
+
+        let stage_layout = wgt::ImageDataLayout {
+            offset: 0,
+            bytes_per_row: NonZeroU32::new(stage_bytes_per_row),
+            rows_per_image: NonZeroU32::new(block_rows_per_image),
+        };
+        let result = self.queue_write_staging_texture_impl(
+            device,
+            &mut texture_guard,
+            destination,
+            &staging_buffer,
+            &stage_layout,
+            size,
+            false,
+        );
+
+        device.pending_writes.consume(staging_buffer);
+        result
+    }
+
+    pub fn queue_write_staging_texture<A: HalApi>(
+        &self,
+        queue_id: id::QueueId,
+        destination: &ImageCopyTexture,
+        data_layout: &wgt::ImageDataLayout,
+        size: &wgt::Extent3d,
+        staging_buffer_id: id::StagingBufferId,
+    ) -> Result<(), QueueWriteError> {
+        profiling::scope!("Queue::write_texture_with");
+
+        let hub = A::hub(self);
+        let root_token = &mut Token::root();
+
+        let (mut device_guard, ref mut device_token) = hub.devices.write(root_token);
+        let device = device_guard
+            .get_mut(queue_id)
+            .map_err(|_| DeviceError::Invalid)?;
+
+        let staging_buffer = hub
+            .staging_buffers
+            .unregister(staging_buffer_id, device_token)
+            .0
+            .ok_or(TransferError::InvalidTexture(destination.texture))?;
+
+        // At this point, we have taken ownership of the staging_buffer from the
+        // user. Platform validation requires that the staging buffer always
+        // be freed, even if an error occurs. All paths from here must call
+        // `device.pending_writes.consume`.
+        if let Err(flush_error) = unsafe { staging_buffer.flush(&device.raw) } {
+            device.pending_writes.consume(staging_buffer);
+            return Err(flush_error.into());
+        }
+
+        if size.width == 0 || size.height == 0 || size.depth_or_array_layers == 0 {
+            log::trace!("Ignoring write_texture_with of size 0");
+            device.pending_writes.consume(staging_buffer);
+            return Ok(());
+        }
+
+        let mut texture_guard = hub.textures.write(device_token).0;
+        let result = self.queue_write_staging_texture_impl(
+            device,
+            &mut texture_guard,
+            destination,
+            &staging_buffer,
+            data_layout,
+            size,
+            true,
+        );
+
+        device.pending_writes.consume(staging_buffer);
+        result
+    }
+
+    /// Copies `size` texels from `staging_buffer`, laid out as described by
+    /// `stage_layout`, to `destination`.
+    ///
+    /// Rows of `stage_layout` must be aligned to `COPY_BYTES_PER_ROW_ALIGNMENT`
+    /// if `need_copy_aligned_rows` is set.
+    #[allow(clippy::too_many_arguments)]
+    fn queue_write_staging_texture_impl<A: HalApi>(
+        &self,
+        device: &mut super::Device<A>,
+        texture_guard: &mut Storage<Texture<A>, id::TextureId>,
+        destination: &ImageCopyTexture,
+        staging_buffer: &StagingBuffer<A>,
+        stage_layout: &wgt::ImageDataLayout,
+        size: &wgt::Extent3d,
+        need_copy_aligned_rows: bool,
+    ) -> Result<(), QueueWriteError> {
+        let (selector, dst_base, texture_format) =
+            extract_texture_selector(destination, size, &*texture_guard)?;
+        let format_desc = texture_format.describe();
+        validate_linear_texture_data(
+            stage_layout,
+            texture_format,
+            staging_buffer.size,
+            CopySide::Source,
+            format_desc.block_size as wgt::BufferAddress,
+            size,
+            need_copy_aligned_rows,
+        )?;
+
+        if !conv::is_valid_copy_dst_texture_format(texture_format) {
+            return Err(TransferError::CopyToForbiddenTextureFormat(texture_format).into());
+        }
+        let (block_width, block_height) = format_desc.block_dimensions;
+        let width_blocks = size.width / block_width as u32;
+        let height_blocks = size.height / block_height as u32;
+
+        let stage_bytes_per_row = match stage_layout.bytes_per_row {
+            Some(bytes_per_row) => bytes_per_row.get(),
+            None => width_blocks * format_desc.block_size as u32,
+        };
+        let block_rows_per_image = match stage_layout.rows_per_image {
+            Some(rows_per_image) => rows_per_image.get(),
+            None => height_blocks,
+        };
+
         let dst = texture_guard.get_mut(destination.texture).unwrap();
         if !dst.desc.usage.contains(wgt::TextureUsages::COPY_DST) {
             return Err(
@@ -667,6 +866,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                         },
                         encoder,
                         &mut trackers.textures,
+                        &mut BarrierRecorder::default(),
                         &device.alignments,
                         &device.zero_buffer,
                     )
@@ -697,64 +897,15 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             .as_raw()
             .ok_or(TransferError::InvalidTexture(destination.texture))?;
 
-        let bytes_per_row = if let Some(bytes_per_row) = data_layout.bytes_per_row {
-            bytes_per_row.get()
-        } else {
-            width_blocks * format_desc.block_size as u32
-        };
-
-        // Platform validation requires that the staging buffer always be
-        // freed, even if an error occurs. All paths from here must call
-        // `device.pending_writes.consume`.
-        let (staging_buffer, staging_buffer_ptr) =
-            prepare_staging_buffer(&mut device.raw, stage_size)?;
-
-        if stage_bytes_per_row == bytes_per_row {
-            profiling::scope!("copy aligned");
-            // Fast path if the data is already being aligned optimally.
-            unsafe {
-                ptr::copy_nonoverlapping(
-                    data.as_ptr().offset(data_layout.offset as isize),
-                    staging_buffer_ptr,
-                    stage_size as usize,
-                );
-            }
-        } else {
-            profiling::scope!("copy chunked");
-            // Copy row by row into the optimal alignment.
-            let copy_bytes_per_row = stage_bytes_per_row.min(bytes_per_row) as usize;
-            for layer in 0..size.depth_or_array_layers {
-                let rows_offset = layer * block_rows_per_image;
-                for row in 0..height_blocks {
-                    unsafe {
-                        ptr::copy_nonoverlapping(
-                            data.as_ptr().offset(
-                                data_layout.offset as isize
-                                    + (rows_offset + row) as isize * bytes_per_row as isize,
-                            ),
-                            staging_buffer_ptr.offset(
-                                (rows_offset + row) as isize * stage_bytes_per_row as isize,
-                            ),
-                            copy_bytes_per_row,
-                        );
-                    }
-                }
-            }
-        }
-
-        if let Err(e) = unsafe { staging_buffer.flush(&device.raw) } {
-            device.pending_writes.consume(staging_buffer);
-            return Err(e.into());
-        }
-
         let regions = (0..array_layer_count).map(|rel_array_layer| {
             let mut texture_base = dst_base.clone();
             texture_base.array_layer += rel_array_layer;
             hal::BufferTextureCopy {
                 buffer_layout: wgt::ImageDataLayout {
-                    offset: rel_array_layer as u64
-                        * block_rows_per_image as u64
-                        * stage_bytes_per_row as u64,
+                    offset: stage_layout.offset
+                        + rel_array_layer as u64
+                            * block_rows_per_image as u64
+                            * stage_bytes_per_row as u64,
                     bytes_per_row: NonZeroU32::new(stage_bytes_per_row),
                     rows_per_image: NonZeroU32::new(block_rows_per_image),
                 },
@@ -774,7 +925,6 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             encoder.copy_buffer_to_texture(&staging_buffer.raw, dst_raw, regions);
         }
 
-        device.pending_writes.consume(staging_buffer);
         device
             .pending_writes
             .dst_textures
@@ -802,6 +952,9 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             device.active_submission_index += 1;
             let submit_index = device.active_submission_index;
             let mut active_executions = Vec::new();
+            // The reusable command buffers submitted by each execution, if any.
+            // Their raw command buffers stay in the command buffer.
+            let mut reused_command_buffers = Vec::new();
             let mut used_surface_textures = track::TextureUsageScope::new();
 
             {
@@ -833,23 +986,36 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                         // we reset the used surface textures every time we use it, so make sure to set_size on it.
                         used_surface_textures.set_size(texture_guard.len());
 
-                        #[allow(unused_mut)]
-                        let mut cmdbuf = match hub
-                            .command_buffers
-                            .unregister_locked(cmb_id, &mut *command_buffer_guard)
-                        {
-                            Some(cmdbuf) => cmdbuf,
-                            None => continue,
+                        // Reusable command buffers stay registered, to be submitted again.
+                        let reusable = match command_buffer_guard.get(cmb_id) {
+                            Ok(cmdbuf) => cmdbuf.is_reusable(),
+                            Err(_) => false,
+                        };
+                        let mut owned_cmdbuf = None;
+                        let cmdbuf = if reusable {
+                            command_buffer_guard.get_mut(cmb_id).unwrap()
+                        } else {
+                            match hub
+                                .command_buffers
+                                .unregister_locked(cmb_id, &mut *command_buffer_guard)
+                            {
+                                Some(cmdbuf) => owned_cmdbuf.insert(cmdbuf),
+                                None => continue,
+                            }
                         };
                         #[cfg(feature = "trace")]
                         if let Some(ref trace) = device.trace {
-                            trace.lock().add(Action::Submit(
-                                submit_index,
-                                cmdbuf.commands.take().unwrap(),
-                            ));
+                            let commands = if reusable {
+                                cmdbuf.commands.clone()
+                            } else {
+                                cmdbuf.commands.take()
+                            };
+                            trace
+                                .lock()
+                                .add(Action::Submit(submit_index, commands.unwrap()));
                         }
                         if !cmdbuf.is_finished() {
-                            device.destroy_command_buffer(cmdbuf);
+                            device.destroy_command_buffer(owned_cmdbuf.take().unwrap());
                             continue;
                         }
 
@@ -906,6 +1072,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                                             None,
                                             ref_count,
                                             hal::TextureUses::PRESENT,
+                                            None,
                                         )
                                         .unwrap();
                                 };
@@ -963,7 +1130,17 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                             }
                         }
 
-                        let mut baked = cmdbuf.into_baked();
+                        let mut baked = if reusable {
+                            cmdbuf.last_submission = submit_index;
+                            let encoder = device
+                                .command_allocator
+                                .lock()
+                                .acquire_encoder(&device.raw, &device.queue)
+                                .map_err(DeviceError::from)?;
+                            cmdbuf.bake_for_submission(encoder)
+                        } else {
+                            owned_cmdbuf.take().unwrap().into_baked()
+                        };
                         // execute resource transitions
                         unsafe {
                             baked
@@ -972,21 +1149,38 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                                 .map_err(DeviceError::from)?
                         };
                         log::trace!("Stitching command buffer {:?} before submission", cmb_id);
+                        // The barriers of the transit run first, so they come first in the log.
+                        let mut transit_barriers =
+                            BarrierRecorder::new(baked.barriers.is_enabled());
+                        transit_barriers.set_cause(|| BarrierCause::MemoryInit);
                         baked
-                            .initialize_buffer_memory(&mut *trackers, &mut *buffer_guard)
+                            .initialize_buffer_memory(
+                                &mut *trackers,
+                                &mut transit_barriers,
+                                &mut *buffer_guard,
+                            )
                             .map_err(|err| QueueSubmitError::DestroyedBuffer(err.0))?;
                         baked
-                            .initialize_texture_memory(&mut *trackers, &mut *texture_guard, device)
+                            .initialize_texture_memory(
+                                &mut *trackers,
+                                &mut transit_barriers,
+                                &mut *texture_guard,
+                                device,
+                            )
                             .map_err(|err| QueueSubmitError::DestroyedTexture(err.0))?;
                         //Note: stateless trackers are not merged:
                         // device already knows these resources exist.
+                        transit_barriers.set_cause(|| BarrierCause::Submit);
                         CommandBuffer::insert_barriers_from_tracker(
                             &mut baked.encoder,
                             &mut *trackers,
                             &baked.trackers,
+                            &mut transit_barriers,
                             &*buffer_guard,
                             &*texture_guard,
                         );
+                        transit_barriers.append(mem::take(&mut baked.barriers));
+                        baked.barriers = transit_barriers;
 
                         let transit = unsafe { baked.encoder.end_encoding().unwrap() };
                         baked.list.insert(0, transit);
@@ -1004,7 +1198,10 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                             trackers
                                 .textures
                                 .set_from_usage_scope(&*texture_guard, &used_surface_textures);
+                            baked.barriers.set_cause(|| BarrierCause::Present);
+                            let barriers = &mut baked.barriers;
                             let texture_barriers = trackers.textures.drain().map(|pending| {
+                                let pending = barriers.texture(&*texture_guard, pending);
                                 let tex = unsafe { texture_guard.get_unchecked(pending.id) };
                                 pending.into_hal(tex)
                             });
@@ -1016,10 +1213,38 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                             used_surface_textures = track::TextureUsageScope::new();
                         }
 
+                        // The checked indirect arguments are released with the other
+                        // temporary resources of this submission, while the blocks of
+                        // emulated push constants are recycled with the encoder.
+                        for arguments in baked.indirect_arguments.drain(..) {
+                            device
+                                .pending_writes
+                                .temp_resources
+                                .extend(arguments.into_temp_resources());
+                        }
+
+                        if let Some(log) = baked
+                            .barriers
+                            .finish(baked.label.as_deref().unwrap_or_default())
+                        {
+                            if let Some(ref mut logs) = *device.barrier_logs.lock() {
+                                logs.push(log);
+                            }
+                        }
+
+                        if reusable {
+                            command_buffer_guard[id::Valid(cmb_id)]
+                                .restore_trackers(baked.trackers);
+                            reused_command_buffers.push(Some(cmb_id));
+                        } else {
+                            reused_command_buffers.push(None);
+                        }
+
                         // done
                         active_executions.push(EncoderInFlight {
                             raw: baked.encoder,
                             cmd_buffers: baked.list,
+                            push_constant_blocks: baked.emulated_push_constants.into_blocks(),
                         });
                     }
 
@@ -1065,6 +1290,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                                             None,
                                             &ref_count,
                                             hal::TextureUses::PRESENT,
+                                            None,
                                         )
                                         .unwrap()
                                 };
@@ -1091,13 +1317,23 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                     }
                 }
 
+                // The raw command buffers of a reusable command buffer go between
+                // the transitions of its execution and the presentation ones.
                 let refs = pending_writes
                     .pre_submit()
                     .into_iter()
                     .chain(
                         active_executions
                             .iter()
-                            .flat_map(|pool_execution| pool_execution.cmd_buffers.iter()),
+                            .zip(reused_command_buffers.iter())
+                            .flat_map(|(pool_execution, reused)| {
+                                let (transit, rest) =
+                                    pool_execution.cmd_buffers.split_first().unwrap();
+                                let body = reused.map_or(&[][..], |id| {
+                                    command_buffer_guard[id::Valid(id)].raw_list()
+                                });
+                                iter::once(transit).chain(body).chain(rest)
+                            }),
                     )
                     .collect::<Vec<_>>();
                 unsafe {
@@ -40,11 +40,16 @@ pub enum Action<'a> {
         desc: crate::device::DeviceDescriptor<'a>,
         backend: wgt::Backend,
     },
-    ConfigureSurface(id::SurfaceId, wgt::SurfaceConfiguration),
+    ConfigureSurface(
+        id::SurfaceId,
+        wgt::SurfaceConfiguration<Vec<wgt::TextureFormat>>,
+    ),
     CreateBuffer(id::BufferId, crate::resource::BufferDescriptor<'a>),
+    CreateUninitializedBuffer(id::BufferId, crate::resource::BufferDescriptor<'a>),
     FreeBuffer(id::BufferId),
     DestroyBuffer(id::BufferId),
     CreateTexture(id::TextureId, crate::resource::TextureDescriptor<'a>),
+    CreateUninitializedTexture(id::TextureId, crate::resource::TextureDescriptor<'a>),
     FreeTexture(id::TextureId),
     DestroyTexture(id::TextureId),
     CreateTextureView {
@@ -76,12 +81,23 @@ pub enum Action<'a> {
         crate::binding_model::BindGroupDescriptor<'a>,
     ),
     DestroyBindGroup(id::BindGroupId),
+    EnableShaderHotReload,
     CreateShaderModule {
         id: id::ShaderModuleId,
         desc: crate::pipeline::ShaderModuleDescriptor<'a>,
         data: FileName,
     },
     DestroyShaderModule(id::ShaderModuleId),
+    ReplaceShaderModule {
+        id: id::ShaderModuleId,
+        desc: crate::pipeline::ShaderModuleDescriptor<'a>,
+        data: FileName,
+    },
+    CreatePipelineCache {
+        id: id::PipelineCacheId,
+        desc: crate::pipeline::PipelineCacheDescriptor<'a>,
+    },
+    DestroyPipelineCache(id::PipelineCacheId),
     CreateComputePipeline {
         id: id::ComputePipelineId,
         desc: crate::pipeline::ComputePipelineDescriptor<'a>,
@@ -122,7 +138,7 @@ pub enum Action<'a> {
     Submit(crate::SubmissionIndex, Vec<Command>),
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 #[cfg_attr(feature = "trace", derive(serde::Serialize))]
 #[cfg_attr(feature = "replay", derive(serde::Deserialize))]
 pub enum Command {
@@ -178,6 +194,8 @@ pub enum Command {
         base: crate::command::BasePass<crate::command::RenderCommand>,
         target_colors: Vec<Option<crate::command::RenderPassColorAttachment>>,
         target_depth_stencil: Option<crate::command::RenderPassDepthStencilAttachment>,
+        #[cfg_attr(feature = "replay", serde(default))]
+        occlusion_query_set: Option<id::QuerySetId>,
     },
 }
 
@@ -211,6 +229,23 @@ impl Trace {
         name
     }
 
+    pub(crate) fn make_shader_binary(
+        &mut self,
+        source: &crate::pipeline::ShaderModuleSource,
+    ) -> FileName {
+        match *source {
+            crate::pipeline::ShaderModuleSource::Wgsl(ref code)
+            | crate::pipeline::ShaderModuleSource::WgslPreprocessed(ref code, _) => {
+                self.make_binary("wgsl", code.as_bytes())
+            }
+            crate::pipeline::ShaderModuleSource::Naga(ref module) => {
+                let string =
+                    ron::ser::to_string_pretty(module, ron::ser::PrettyConfig::default()).unwrap();
+                self.make_binary("ron", string.as_bytes())
+            }
+        }
+    }
+
     pub(crate) fn add(&mut self, action: Action) {
         match ron::ser::to_string_pretty(&action, self.config.clone()) {
             Ok(string) => {
@@ -26,10 +26,14 @@ impl<'a> ErrorFormatter<'a> {
         }
     }
 
-    pub fn bind_group_label(&mut self, id: &crate::id::BindGroupId) {
+    pub fn bind_group_label_with_key(&mut self, id: &crate::id::BindGroupId, key: &str) {
         let global = self.global;
         let label = gfx_select!(id => global.bind_group_label(*id));
-        self.label("bind group", &label);
+        self.label(key, &label);
+    }
+
+    pub fn bind_group_label(&mut self, id: &crate::id::BindGroupId) {
+        self.bind_group_label_with_key(id, "bind group");
     }
 
     pub fn bind_group_layout_label(&mut self, id: &crate::id::BindGroupLayoutId) {
@@ -86,6 +90,12 @@ impl<'a> ErrorFormatter<'a> {
         self.label("sampler", &label);
     }
 
+    pub fn render_bundle_label_with_key(&mut self, id: &crate::id::RenderBundleId, key: &str) {
+        let global = self.global;
+        let label = gfx_select!(id => global.render_bundle_label(*id));
+        self.label(key, &label);
+    }
+
     pub fn command_buffer_label(&mut self, id: &crate::id::CommandBufferId) {
         let global = self.global;
         let label = gfx_select!(id => global.command_buffer_label(*id));
@@ -121,6 +131,9 @@ pub fn format_pretty_any(
     {
         return pretty_err.fmt_pretty(&mut fmt);
     }
+    if let Some(pretty_err) = error.downcast_ref::<crate::pipeline::ReplaceShaderModuleError>() {
+        return pretty_err.fmt_pretty(&mut fmt);
+    }
     if let Some(pretty_err) = error.downcast_ref::<crate::command::ExecutionError>() {
         return pretty_err.fmt_pretty(&mut fmt);
     }
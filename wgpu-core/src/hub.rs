@@ -4,7 +4,7 @@ use crate::{
     device::Device,
     id,
     instance::{Adapter, HalSurface, Instance, Surface},
-    pipeline::{ComputePipeline, RenderPipeline, ShaderModule},
+    pipeline::{ComputePipeline, PipelineCache, RenderPipeline, ShaderModule},
     resource::{Buffer, QuerySet, Sampler, StagingBuffer, Texture, TextureClearMode, TextureView},
     Epoch, Index,
 };
@@ -224,6 +224,16 @@ impl<T, I: id::TypedId> Storage<T, I> {
         }
     }
 
+    /// Get the id of the live resource at `index`.
+    /// Panics if the entry is empty or invalid.
+    pub(crate) fn id_at(&self, index: u32, backend: Backend) -> I {
+        match self.map[index as usize] {
+            Element::Occupied(_, epoch) => I::zip(index, epoch, backend),
+            Element::Vacant => panic!("{}[{}] does not exist", self.kind, index),
+            Element::Error(_, _) => panic!("{}[{}] is invalid", self.kind, index),
+        }
+    }
+
     pub(crate) fn label_for_invalid_id(&self, id: I) -> &str {
         let (index, _, _) = id.unzip();
         match self.map.get(index as usize) {
@@ -365,6 +375,8 @@ impl<A: HalApi> Access<QuerySet<A>> for ComputePipeline<A> {}
 impl<A: HalApi> Access<QuerySet<A>> for Sampler<A> {}
 impl<A: HalApi> Access<ShaderModule<A>> for Device<A> {}
 impl<A: HalApi> Access<ShaderModule<A>> for BindGroupLayout<A> {}
+impl<A: HalApi> Access<PipelineCache<A>> for Device<A> {}
+impl<A: HalApi> Access<PipelineCache<A>> for ShaderModule<A> {}
 impl<A: HalApi> Access<Buffer<A>> for Root {}
 impl<A: HalApi> Access<Buffer<A>> for Device<A> {}
 impl<A: HalApi> Access<Buffer<A>> for BindGroupLayout<A> {}
@@ -467,6 +479,7 @@ pub trait GlobalIdentityHandlerFactory:
     + IdentityHandlerFactory<id::DeviceId>
     + IdentityHandlerFactory<id::PipelineLayoutId>
     + IdentityHandlerFactory<id::ShaderModuleId>
+    + IdentityHandlerFactory<id::PipelineCacheId>
     + IdentityHandlerFactory<id::BindGroupLayoutId>
     + IdentityHandlerFactory<id::BindGroupId>
     + IdentityHandlerFactory<id::CommandBufferId>
@@ -631,6 +644,7 @@ pub struct HubReport {
     pub devices: StorageReport,
     pub pipeline_layouts: StorageReport,
     pub shader_modules: StorageReport,
+    pub pipeline_caches: StorageReport,
     pub bind_group_layouts: StorageReport,
     pub bind_groups: StorageReport,
     pub command_buffers: StorageReport,
@@ -655,6 +669,7 @@ pub struct Hub<A: HalApi, F: GlobalIdentityHandlerFactory> {
     pub devices: Registry<Device<A>, id::DeviceId, F>,
     pub pipeline_layouts: Registry<PipelineLayout<A>, id::PipelineLayoutId, F>,
     pub shader_modules: Registry<ShaderModule<A>, id::ShaderModuleId, F>,
+    pub pipeline_caches: Registry<PipelineCache<A>, id::PipelineCacheId, F>,
     pub bind_group_layouts: Registry<BindGroupLayout<A>, id::BindGroupLayoutId, F>,
     pub bind_groups: Registry<BindGroup<A>, id::BindGroupId, F>,
     pub command_buffers: Registry<CommandBuffer<A>, id::CommandBufferId, F>,
@@ -676,6 +691,7 @@ impl<A: HalApi, F: GlobalIdentityHandlerFactory> Hub<A, F> {
             devices: Registry::new(A::VARIANT, factory),
             pipeline_layouts: Registry::new(A::VARIANT, factory),
             shader_modules: Registry::new(A::VARIANT, factory),
+            pipeline_caches: Registry::new(A::VARIANT, factory),
             bind_group_layouts: Registry::new(A::VARIANT, factory),
             bind_groups: Registry::new(A::VARIANT, factory),
             command_buffers: Registry::new(A::VARIANT, factory),
@@ -772,6 +788,14 @@ impl<A: HalApi, F: GlobalIdentityHandlerFactory> Hub<A, F> {
                 }
             }
         }
+        for element in self.pipeline_caches.data.write().map.drain(..) {
+            if let Element::Occupied(cache, _) = element {
+                let device = &devices[cache.device_id.value];
+                unsafe {
+                    device.raw.destroy_pipeline_cache(cache.raw);
+                }
+            }
+        }
         for element in self.bind_group_layouts.data.write().map.drain(..) {
             if let Element::Occupied(bgl, _) = element {
                 let device = &devices[bgl.device_id.value];
@@ -793,6 +817,9 @@ impl<A: HalApi, F: GlobalIdentityHandlerFactory> Hub<A, F> {
                 let device = &devices[pipeline.device_id.value];
                 unsafe {
                     device.raw.destroy_compute_pipeline(pipeline.raw);
+                    for raw in pipeline.retired {
+                        device.raw.destroy_compute_pipeline(raw);
+                    }
                 }
             }
         }
@@ -801,6 +828,9 @@ impl<A: HalApi, F: GlobalIdentityHandlerFactory> Hub<A, F> {
                 let device = &devices[pipeline.device_id.value];
                 unsafe {
                     device.raw.destroy_render_pipeline(pipeline.raw);
+                    for raw in pipeline.retired {
+                        device.raw.destroy_render_pipeline(raw);
+                    }
                 }
             }
         }
@@ -853,6 +883,7 @@ impl<A: HalApi, F: GlobalIdentityHandlerFactory> Hub<A, F> {
             devices: self.devices.data.read().generate_report(),
             pipeline_layouts: self.pipeline_layouts.data.read().generate_report(),
             shader_modules: self.shader_modules.data.read().generate_report(),
+            pipeline_caches: self.pipeline_caches.data.read().generate_report(),
             bind_group_layouts: self.bind_group_layouts.data.read().generate_report(),
             bind_groups: self.bind_groups.data.read().generate_report(),
             command_buffers: self.command_buffers.data.read().generate_report(),
@@ -206,6 +206,7 @@ pub type PipelineLayoutId = Id<crate::binding_model::PipelineLayout<Dummy>>;
 pub type BindGroupId = Id<crate::binding_model::BindGroup<Dummy>>;
 // Pipeline
 pub type ShaderModuleId = Id<crate::pipeline::ShaderModule<Dummy>>;
+pub type PipelineCacheId = Id<crate::pipeline::PipelineCache<Dummy>>;
 pub type RenderPipelineId = Id<crate::pipeline::RenderPipeline<Dummy>>;
 pub type ComputePipelineId = Id<crate::pipeline::ComputePipeline<Dummy>>;
 // Command
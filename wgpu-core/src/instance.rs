@@ -188,6 +188,23 @@ impl Surface {
 
         Ok(caps.present_modes)
     }
+
+    pub fn get_supported_alpha_modes<A: HalApi>(
+        &self,
+        adapter: &Adapter<A>,
+    ) -> Result<Vec<wgt::CompositeAlphaMode>, GetSurfaceSupportError> {
+        let suf = A::get_surface(self);
+        let caps = unsafe {
+            profiling::scope!("surface_capabilities");
+            adapter
+                .raw
+                .adapter
+                .surface_capabilities(&suf.raw)
+                .ok_or(GetSurfaceSupportError::UnsupportedQueueFamily)?
+        };
+
+        Ok(caps.composite_alpha_modes)
+    }
 }
 
 pub struct Adapter<A: hal::Api> {
@@ -196,7 +213,23 @@ pub struct Adapter<A: hal::Api> {
 }
 
 impl<A: HalApi> Adapter<A> {
-    fn new(raw: hal::ExposedAdapter<A>) -> Self {
+    fn new(mut raw: hal::ExposedAdapter<A>) -> Self {
+        // Push constant emulation is implemented here. It reserves the last bind
+        // group of the adapter, so it needs at least one more for the user.
+        if raw.capabilities.limits.max_bind_groups > 1 {
+            raw.features |= wgt::Features::PUSH_CONSTANT_EMULATION;
+        }
+        // Indirect arguments are checked with a compute shader.
+        if raw.capabilities.downlevel.flags.contains(
+            wgt::DownlevelFlags::COMPUTE_SHADERS | wgt::DownlevelFlags::INDIRECT_EXECUTION,
+        ) {
+            raw.features |= wgt::Features::INDIRECT_VALIDATION;
+        }
+        // Indexed draws are checked on the CPU, against a copy of the indices.
+        raw.features |= wgt::Features::INDEX_RANGE_VALIDATION;
+        // Zero-initialization is done by wgpu-core, so it can always be skipped.
+        raw.features |= wgt::Features::UNINITIALIZED_RESOURCES;
+
         Self {
             raw,
             life_guard: LifeGuard::new("<Adapter>"),
@@ -328,16 +361,35 @@ impl<A: HalApi> Adapter<A> {
             //TODO
         }
 
-        if let Some(failed) = check_limits(&desc.limits, &caps.limits).pop() {
+        let mut allowed_limits = caps.limits.clone();
+        let mut hal_limits = desc.limits.clone();
+        if desc
+            .features
+            .contains(wgt::Features::PUSH_CONSTANT_EMULATION)
+            && !desc.features.contains(wgt::Features::PUSH_CONSTANTS)
+        {
+            // The group after the last one is reserved for the push constants.
+            allowed_limits.max_bind_groups = allowed_limits
+                .max_bind_groups
+                .min(hal::MAX_BIND_GROUPS as u32)
+                - 1;
+            allowed_limits.max_push_constant_size =
+                crate::device::push_constants::EMULATED_PUSH_CONSTANT_SIZE;
+            hal_limits.max_bind_groups += 1;
+            hal_limits.max_push_constant_size = 0;
+        }
+
+        if let Some(failed) = check_limits(&desc.limits, &allowed_limits).pop() {
             return Err(RequestDeviceError::LimitsExceeded(failed));
         }
 
-        let open = unsafe { self.raw.adapter.open(desc.features, &desc.limits) }.map_err(
-            |err| match err {
-                hal::DeviceError::Lost => RequestDeviceError::DeviceLost,
-                hal::DeviceError::OutOfMemory => RequestDeviceError::OutOfMemory,
-            },
-        )?;
+        let open =
+            unsafe { self.raw.adapter.open(desc.features, &hal_limits) }.map_err(
+                |err| match err {
+                    hal::DeviceError::Lost => RequestDeviceError::DeviceLost,
+                    hal::DeviceError::OutOfMemory => RequestDeviceError::OutOfMemory,
+                },
+            )?;
 
         self.create_device_from_hal(self_id, open, desc, trace_path)
     }
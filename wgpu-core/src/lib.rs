@@ -45,6 +45,7 @@ pub mod hub;
 pub mod id;
 mod init_tracker;
 pub mod instance;
+pub mod offline;
 pub mod pipeline;
 pub mod present;
 pub mod resource;
@@ -0,0 +1,544 @@
+//! Validation of shaders and pipelines without a device.
+//!
+//! [`Validator`] runs the checks of shader module, bind group layout, pipeline
+//! layout and pipeline creation against a set of features, limits and downlevel
+//! capabilities, such as [`wgt::Limits::downlevel_webgl2_defaults`], without
+//! creating any backend object. It's meant for checking shaders and pipeline
+//! descriptions ahead of time, e.g. in CI on machines without a GPU.
+//!
+//! Backends may still reject pipelines that pass these checks: the format
+//! features of adapters that aren't WebGPU compliant, and the translation of
+//! shaders to the backend language, are only known with a device.
+
+use crate::{
+    binding_model,
+    device::checks::{self, Capabilities, ParsedShader, StageLayouts},
+    pipeline, validation,
+};
+
+use arrayvec::ArrayVec;
+
+use std::collections::HashMap;
+
+/// Checks shaders and pipelines against the features, limits and downlevel
+/// capabilities of a hypothetical device.
+#[derive(Clone, Debug)]
+pub struct Validator {
+    features: wgt::Features,
+    limits: wgt::Limits,
+    downlevel: wgt::DownlevelCapabilities,
+}
+
+/// A shader module that passed validation.
+#[derive(Debug)]
+pub struct ShaderModule {
+    interface: validation::Interface,
+}
+
+/// A bind group layout that passed validation.
+#[derive(Debug)]
+pub struct BindGroupLayout {
+    entries: binding_model::BindEntryMap,
+    count_validator: binding_model::BindingTypeMaxCountValidator,
+}
+
+/// A pipeline layout that passed validation.
+#[derive(Debug)]
+pub struct PipelineLayout {
+    bind_group_layouts: Vec<binding_model::BindEntryMap>,
+}
+
+/// Describes a programmable pipeline stage, see [`pipeline::ProgrammableStageDescriptor`].
+#[derive(Clone, Debug)]
+pub struct ProgrammableStage<'a> {
+    pub module: &'a ShaderModule,
+    pub entry_point: &'a str,
+    pub constants: &'a HashMap<String, f64>,
+}
+
+/// Describes the vertex process in a render pipeline, see [`pipeline::VertexState`].
+#[derive(Clone, Debug)]
+pub struct VertexState<'a> {
+    pub stage: ProgrammableStage<'a>,
+    pub buffers: &'a [pipeline::VertexBufferLayout<'a>],
+}
+
+/// Describes fragment processing in a render pipeline, see [`pipeline::FragmentState`].
+#[derive(Clone, Debug)]
+pub struct FragmentState<'a> {
+    pub stage: ProgrammableStage<'a>,
+    pub targets: &'a [Option<wgt::ColorTargetState>],
+}
+
+/// Describes a render pipeline, see [`pipeline::RenderPipelineDescriptor`].
+///
+/// Without a layout, the layout is derived from the shaders, like with
+/// implicit pipeline layouts.
+#[derive(Clone, Debug)]
+pub struct RenderPipelineDescriptor<'a> {
+    pub layout: Option<&'a PipelineLayout>,
+    pub vertex: VertexState<'a>,
+    pub primitive: wgt::PrimitiveState,
+    pub depth_stencil: Option<wgt::DepthStencilState>,
+    pub multisample: wgt::MultisampleState,
+    pub fragment: Option<FragmentState<'a>>,
+}
+
+/// Describes a compute pipeline, see [`pipeline::ComputePipelineDescriptor`].
+#[derive(Clone, Debug)]
+pub struct ComputePipelineDescriptor<'a> {
+    pub layout: Option<&'a PipelineLayout>,
+    pub stage: ProgrammableStage<'a>,
+}
+
+impl Validator {
+    pub fn new(
+        features: wgt::Features,
+        limits: wgt::Limits,
+        downlevel: wgt::DownlevelCapabilities,
+    ) -> Self {
+        Self {
+            features,
+            limits,
+            downlevel,
+        }
+    }
+
+    fn capabilities(&self) -> Capabilities<'_> {
+        Capabilities {
+            features: self.features,
+            limits: &self.limits,
+            downlevel: &self.downlevel,
+        }
+    }
+
+    pub fn create_shader_module(
+        &self,
+        desc: &pipeline::ShaderModuleDescriptor,
+        source: pipeline::ShaderModuleSource,
+    ) -> Result<ShaderModule, pipeline::CreateShaderModuleError> {
+        let shader = ParsedShader::new(desc, source)?;
+        let (_, info) = self.capabilities().validate_shader(desc, &shader)?;
+        Ok(ShaderModule {
+            interface: validation::Interface::new(
+                &shader.module,
+                &info,
+                &shader.overrides,
+                self.features,
+                self.limits.clone(),
+            ),
+        })
+    }
+
+    pub fn create_bind_group_layout(
+        &self,
+        desc: &binding_model::BindGroupLayoutDescriptor,
+    ) -> Result<BindGroupLayout, binding_model::CreateBindGroupLayoutError> {
+        let entries = checks::bind_entry_map(&desc.entries)?;
+        let count_validator = self.capabilities().check_bind_group_layout(&entries)?;
+        Ok(BindGroupLayout {
+            entries,
+            count_validator,
+        })
+    }
+
+    pub fn create_pipeline_layout(
+        &self,
+        bind_group_layouts: &[&BindGroupLayout],
+        push_constant_ranges: &[wgt::PushConstantRange],
+    ) -> Result<PipelineLayout, binding_model::CreatePipelineLayoutError> {
+        self.capabilities()
+            .check_pipeline_layout(bind_group_layouts.len(), push_constant_ranges)?;
+
+        let mut count_validator = binding_model::BindingTypeMaxCountValidator::default();
+        for bgl in bind_group_layouts {
+            count_validator.merge(&bgl.count_validator);
+        }
+        count_validator
+            .validate(&self.limits)
+            .map_err(binding_model::CreatePipelineLayoutError::TooManyBindings)?;
+
+        Ok(PipelineLayout {
+            bind_group_layouts: bind_group_layouts
+                .iter()
+                .map(|bgl| bgl.entries.clone())
+                .collect(),
+        })
+    }
+
+    /// Checks a layout derived from the shaders, as [`Self::create_bind_group_layout`]
+    /// and [`Self::create_pipeline_layout`] would.
+    fn check_derived_layout(
+        &self,
+        derived_group_layouts: ArrayVec<binding_model::BindEntryMap, { hal::MAX_BIND_GROUPS }>,
+    ) -> Result<(), pipeline::ImplicitLayoutError> {
+        let mut count_validator = binding_model::BindingTypeMaxCountValidator::default();
+        for entries in derived_group_layouts.iter() {
+            count_validator.merge(&self.capabilities().check_bind_group_layout(entries)?);
+        }
+        count_validator
+            .validate(&self.limits)
+            .map_err(binding_model::CreatePipelineLayoutError::TooManyBindings)?;
+        Ok(())
+    }
+
+    pub fn validate_render_pipeline(
+        &self,
+        desc: &RenderPipelineDescriptor,
+    ) -> Result<(), pipeline::CreateRenderPipelineError> {
+        let color_targets = desc.fragment.as_ref().map_or(&[][..], |f| f.targets);
+        let capabilities = self.capabilities();
+        let mut io = capabilities.check_render_pipeline_state(
+            desc.vertex.buffers,
+            &desc.primitive,
+            desc.depth_stencil.as_ref(),
+            &desc.multisample,
+            color_targets,
+            |format| {
+                let format_desc = format.describe();
+                capabilities.require_features(format_desc.required_features)?;
+                Ok(format_desc.guaranteed_format_features)
+            },
+        )?;
+
+        let mut layouts = StageLayouts::new(given_layouts(desc.layout), &self.limits);
+        let mut stages = vec![(wgt::ShaderStages::VERTEX, &desc.vertex.stage)];
+        if let Some(ref fragment) = desc.fragment {
+            stages.push((wgt::ShaderStages::FRAGMENT, &fragment.stage));
+        }
+        for (flag, stage) in stages {
+            io = layouts
+                .check_stage(
+                    &stage.module.interface,
+                    flag,
+                    stage.entry_point,
+                    stage.constants,
+                    io,
+                    desc.depth_stencil.as_ref().map(|d| d.depth_compare),
+                )
+                .map_err(|error| pipeline::CreateRenderPipelineError::Stage {
+                    stage: flag,
+                    error,
+                })?;
+        }
+
+        if let Some(ref fragment) = desc.fragment {
+            checks::check_fragment_outputs(&io, color_targets, fragment.stage.entry_point)?;
+        }
+        if desc.layout.is_none() {
+            self.check_derived_layout(layouts.finish().0)?;
+        }
+        Ok(())
+    }
+
+    pub fn validate_compute_pipeline(
+        &self,
+        desc: &ComputePipelineDescriptor,
+    ) -> Result<(), pipeline::CreateComputePipelineError> {
+        self.capabilities()
+            .require_downlevel_flags(wgt::DownlevelFlags::COMPUTE_SHADERS)?;
+
+        let mut layouts = StageLayouts::new(given_layouts(desc.layout), &self.limits);
+        layouts.check_stage(
+            &desc.stage.module.interface,
+            wgt::ShaderStages::COMPUTE,
+            desc.stage.entry_point,
+            desc.stage.constants,
+            validation::StageIo::default(),
+            None,
+        )?;
+
+        if desc.layout.is_none() {
+            self.check_derived_layout(layouts.finish().0)?;
+        }
+        Ok(())
+    }
+}
+
+fn given_layouts(
+    layout: Option<&PipelineLayout>,
+) -> Option<ArrayVec<&binding_model::BindEntryMap, { hal::MAX_BIND_GROUPS }>> {
+    layout.map(|layout| layout.bind_group_layouts.iter().collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::borrow::Cow;
+
+    const SHADER: &str = "
+@group(0) @binding(0)
+var<uniform> scale: vec4<f32>;
+
+@vertex
+fn vs_main(@location(0) position: vec4<f32>) -> @builtin(position) vec4<f32> {
+    return position * scale;
+}
+
+@fragment
+fn fs_main() -> @location(0) vec4<f32> {
+    return vec4<f32>(1.0);
+}
+
+@group(0) @binding(1)
+var<storage, read_write> output: array<u32>;
+
+@compute @workgroup_size(1)
+fn cs_main() {
+    output[0] = 1u;
+}
+";
+
+    fn validator(limits: wgt::Limits, flags: wgt::DownlevelFlags) -> Validator {
+        Validator::new(
+            wgt::Features::empty(),
+            limits,
+            wgt::DownlevelCapabilities {
+                flags,
+                ..Default::default()
+            },
+        )
+    }
+
+    fn shader(validator: &Validator) -> ShaderModule {
+        validator
+            .create_shader_module(
+                &pipeline::ShaderModuleDescriptor {
+                    label: None,
+                    shader_bound_checks: wgt::ShaderBoundChecks::default(),
+                },
+                pipeline::ShaderModuleSource::Wgsl(Cow::Borrowed(SHADER)),
+            )
+            .unwrap()
+    }
+
+    fn render_pipeline<'a>(
+        layout: Option<&'a PipelineLayout>,
+        module: &'a ShaderModule,
+        constants: &'a HashMap<String, f64>,
+        buffers: &'a [pipeline::VertexBufferLayout<'a>],
+        targets: &'a [Option<wgt::ColorTargetState>],
+    ) -> RenderPipelineDescriptor<'a> {
+        RenderPipelineDescriptor {
+            layout,
+            vertex: VertexState {
+                stage: ProgrammableStage {
+                    module,
+                    entry_point: "vs_main",
+                    constants,
+                },
+                buffers,
+            },
+            primitive: wgt::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgt::MultisampleState::default(),
+            fragment: Some(FragmentState {
+                stage: ProgrammableStage {
+                    module,
+                    entry_point: "fs_main",
+                    constants,
+                },
+                targets,
+            }),
+        }
+    }
+
+    const ATTRIBUTES: [wgt::VertexAttribute; 1] = [wgt::VertexAttribute {
+        format: wgt::VertexFormat::Float32x4,
+        offset: 0,
+        shader_location: 0,
+    }];
+
+    fn vertex_buffers() -> [pipeline::VertexBufferLayout<'static>; 1] {
+        [pipeline::VertexBufferLayout {
+            array_stride: 16,
+            step_mode: wgt::VertexStepMode::Vertex,
+            attributes: Cow::Borrowed(&ATTRIBUTES),
+        }]
+    }
+
+    #[test]
+    fn render_pipeline_with_implicit_layout() {
+        let validator = validator(
+            wgt::Limits::downlevel_webgl2_defaults(),
+            wgt::DownlevelFlags::empty(),
+        );
+        let module = shader(&validator);
+        let constants = HashMap::new();
+        let buffers = vertex_buffers();
+        let targets = [Some(wgt::TextureFormat::Rgba8Unorm.into())];
+        validator
+            .validate_render_pipeline(&render_pipeline(
+                None, &module, &constants, &buffers, &targets,
+            ))
+            .unwrap();
+    }
+
+    #[test]
+    fn render_pipeline_layout_mismatch() {
+        let validator = validator(wgt::Limits::default(), wgt::DownlevelFlags::all());
+        let module = shader(&validator);
+        let bgl = validator
+            .create_bind_group_layout(&binding_model::BindGroupLayoutDescriptor {
+                label: None,
+                entries: Cow::Owned(vec![wgt::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgt::ShaderStages::FRAGMENT,
+                    ty: wgt::BindingType::Buffer {
+                        ty: wgt::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                }]),
+            })
+            .unwrap();
+        let layout = validator.create_pipeline_layout(&[&bgl], &[]).unwrap();
+        let constants = HashMap::new();
+        let buffers = vertex_buffers();
+        let targets = [Some(wgt::TextureFormat::Rgba8Unorm.into())];
+        let result = validator.validate_render_pipeline(&render_pipeline(
+            Some(&layout),
+            &module,
+            &constants,
+            &buffers,
+            &targets,
+        ));
+        assert!(matches!(
+            result,
+            Err(pipeline::CreateRenderPipelineError::Stage {
+                stage: wgt::ShaderStages::VERTEX,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn render_pipeline_target_format() {
+        let validator = validator(wgt::Limits::default(), wgt::DownlevelFlags::all());
+        let module = shader(&validator);
+        let constants = HashMap::new();
+        let buffers = vertex_buffers();
+        // Depth formats can't be color targets.
+        let targets = [Some(wgt::TextureFormat::Depth32Float.into())];
+        let result = validator.validate_render_pipeline(&render_pipeline(
+            None, &module, &constants, &buffers, &targets,
+        ));
+        assert!(matches!(
+            result,
+            Err(pipeline::CreateRenderPipelineError::ColorState(0, _))
+        ));
+    }
+
+    #[test]
+    fn compute_pipeline_downlevel() {
+        let constants = HashMap::new();
+        let webgl2 = validator(
+            wgt::Limits::downlevel_webgl2_defaults(),
+            wgt::DownlevelFlags::empty(),
+        );
+        let module = shader(&webgl2);
+        let desc = ComputePipelineDescriptor {
+            layout: None,
+            stage: ProgrammableStage {
+                module: &module,
+                entry_point: "cs_main",
+                constants: &constants,
+            },
+        };
+        assert!(matches!(
+            webgl2.validate_compute_pipeline(&desc),
+            Err(pipeline::CreateComputePipelineError::MissingDownlevelFlags(
+                _
+            ))
+        ));
+
+        let downlevel = validator(
+            wgt::Limits::downlevel_defaults(),
+            wgt::DownlevelFlags::all(),
+        );
+        let module = shader(&downlevel);
+        let desc = ComputePipelineDescriptor {
+            stage: ProgrammableStage {
+                module: &module,
+                ..desc.stage
+            },
+            ..desc
+        };
+        downlevel.validate_compute_pipeline(&desc).unwrap();
+    }
+
+    #[test]
+    fn float64_vertex_inputs() {
+        const FLOAT64_SHADER: &str = "
+@vertex
+fn vs_main(@location(0) position: vec4<f64>) -> @builtin(position) vec4<f32> {
+    return vec4<f32>(position);
+}
+";
+        let module_desc = pipeline::ShaderModuleDescriptor {
+            label: None,
+            shader_bound_checks: wgt::ShaderBoundChecks::default(),
+        };
+        let float64_validator = |features| {
+            Validator::new(
+                features,
+                wgt::Limits::default(),
+                wgt::DownlevelCapabilities::default(),
+            )
+        };
+        let create_module = |validator: &Validator, source: &'static str| {
+            validator.create_shader_module(
+                &module_desc,
+                pipeline::ShaderModuleSource::Wgsl(Cow::Borrowed(source)),
+            )
+        };
+        let validate = |validator: &Validator, module: &ShaderModule, format| {
+            let attributes = [wgt::VertexAttribute {
+                format,
+                offset: 0,
+                shader_location: 0,
+            }];
+            let buffers = [pipeline::VertexBufferLayout {
+                array_stride: 32,
+                step_mode: wgt::VertexStepMode::Vertex,
+                attributes: Cow::Borrowed(&attributes),
+            }];
+            let constants = HashMap::new();
+            let mut desc = render_pipeline(None, module, &constants, &buffers, &[]);
+            desc.fragment = None;
+            validator.validate_render_pipeline(&desc)
+        };
+
+        assert!(matches!(
+            create_module(
+                &validator(wgt::Limits::default(), wgt::DownlevelFlags::all()),
+                FLOAT64_SHADER
+            ),
+            Err(pipeline::CreateShaderModuleError::Validation(_))
+        ));
+
+        let shader_only = float64_validator(wgt::Features::SHADER_FLOAT64);
+        let module = create_module(&shader_only, FLOAT64_SHADER).unwrap();
+        assert!(matches!(
+            validate(&shader_only, &module, wgt::VertexFormat::Float64x4),
+            Err(pipeline::CreateRenderPipelineError::MissingFeatures(_))
+        ));
+
+        let vertex_64bit = float64_validator(
+            wgt::Features::SHADER_FLOAT64 | wgt::Features::VERTEX_ATTRIBUTE_64BIT,
+        );
+        let module = create_module(&vertex_64bit, FLOAT64_SHADER).unwrap();
+        validate(&vertex_64bit, &module, wgt::VertexFormat::Float64x4).unwrap();
+        // The width of the format has to match the input.
+        assert!(matches!(
+            validate(&vertex_64bit, &module, wgt::VertexFormat::Float32x4),
+            Err(pipeline::CreateRenderPipelineError::Stage { .. })
+        ));
+        let module = create_module(&vertex_64bit, SHADER).unwrap();
+        assert!(matches!(
+            validate(&vertex_64bit, &module, wgt::VertexFormat::Float64x4),
+            Err(pipeline::CreateRenderPipelineError::Stage { .. })
+        ));
+    }
+}
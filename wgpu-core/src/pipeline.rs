@@ -1,12 +1,16 @@
 use crate::{
     binding_model::{CreateBindGroupLayoutError, CreatePipelineLayoutError},
     device::{DeviceError, MissingDownlevelFlags, MissingFeatures, RenderPassContext},
+    error::{ErrorFormatter, PrettyError},
     hub::Resource,
-    id::{DeviceId, PipelineLayoutId, ShaderModuleId},
+    id::{
+        ComputePipelineId, DeviceId, PipelineCacheId, PipelineLayoutId, RenderPipelineId,
+        ShaderModuleId,
+    },
     validation, Label, LifeGuard, Stored,
 };
 use arrayvec::ArrayVec;
-use std::{borrow::Cow, error::Error, fmt, num::NonZeroU32};
+use std::{borrow::Cow, collections::HashMap, error::Error, fmt, num::NonZeroU32};
 use thiserror::Error;
 
 /// Information about buffer bindings, which
@@ -21,6 +25,8 @@ pub(crate) struct LateSizedBufferGroup {
 #[allow(clippy::large_enum_variant)]
 pub enum ShaderModuleSource<'a> {
     Wgsl(Cow<'a, str>),
+    /// WGSL produced by a preprocessor, with the origin of its lines.
+    WgslPreprocessed(Cow<'a, str>, wgt::ShaderSourceMap),
     Naga(naga::Module),
 }
 
@@ -38,35 +44,145 @@ pub struct ShaderModule<A: hal::Api> {
     pub(crate) raw: A::ShaderModule,
     pub(crate) device_id: Stored<DeviceId>,
     pub(crate) interface: Option<validation::Interface>,
-    #[cfg(debug_assertions)]
-    pub(crate) label: String,
+    pub(crate) life_guard: LifeGuard,
 }
 
 impl<A: hal::Api> Resource for ShaderModule<A> {
     const TYPE: &'static str = "ShaderModule";
 
     fn life_guard(&self) -> &LifeGuard {
-        unreachable!()
-    }
-
-    fn label(&self) -> &str {
-        #[cfg(debug_assertions)]
-        return &self.label;
-        #[cfg(not(debug_assertions))]
-        return "";
+        &self.life_guard
     }
 }
 
 #[derive(Clone, Debug)]
 pub struct ShaderError<E> {
     pub source: String,
+    /// Origin of the lines of `source`, if it was preprocessed.
+    pub source_map: Option<wgt::ShaderSourceMap>,
     pub label: Option<String>,
     pub inner: E,
 }
+
+/// Preprocessed source, as seen by `codespan_reporting`: lines are reported
+/// with the file and line number they come from.
+struct MappedFiles<'a> {
+    label: &'a str,
+    source: &'a str,
+    map: &'a wgt::ShaderSourceMap,
+    line_starts: Vec<usize>,
+}
+
+impl<'a> MappedFiles<'a> {
+    fn new(label: &'a str, source: &'a str, map: &'a wgt::ShaderSourceMap) -> Self {
+        Self {
+            label,
+            source,
+            map,
+            line_starts: codespan_reporting::files::line_starts(source).collect(),
+        }
+    }
+
+    /// Returns the id of the file the byte at `offset` comes from.
+    fn file_id(&self, offset: usize) -> usize {
+        self.map
+            .find_line(offset as u32)
+            .map_or(0, |line| line.file as usize)
+    }
+
+    /// Emits a diagnostic whose labels are ranges of the preprocessed source.
+    fn emit(
+        &self,
+        message: &str,
+        labels: impl Iterator<Item = (std::ops::Range<usize>, String)>,
+    ) -> String {
+        use codespan_reporting::{
+            diagnostic::{Diagnostic, Label},
+            term,
+        };
+
+        let diagnostic = Diagnostic::error().with_message(message).with_labels(
+            labels
+                .map(|(range, text)| {
+                    Label::primary(self.file_id(range.start), range).with_message(text)
+                })
+                .collect(),
+        );
+        let config = term::Config::default();
+        let mut writer = term::termcolor::Ansi::new(Vec::new());
+        term::emit(&mut writer, &config, self, &diagnostic).expect("cannot write error");
+        String::from_utf8_lossy(&writer.into_inner()).into_owned()
+    }
+}
+
+impl<'a> codespan_reporting::files::Files<'a> for MappedFiles<'_> {
+    type FileId = usize;
+    type Name = &'a str;
+    type Source = &'a str;
+
+    fn name(&'a self, id: usize) -> Result<&'a str, codespan_reporting::files::Error> {
+        Ok(self.map.files.get(id).map_or(self.label, String::as_str))
+    }
+
+    fn source(&'a self, _id: usize) -> Result<&'a str, codespan_reporting::files::Error> {
+        Ok(self.source)
+    }
+
+    fn line_index(
+        &'a self,
+        _id: usize,
+        byte_index: usize,
+    ) -> Result<usize, codespan_reporting::files::Error> {
+        Ok(self
+            .line_starts
+            .binary_search(&byte_index)
+            .unwrap_or_else(|next| next - 1))
+    }
+
+    fn line_number(
+        &'a self,
+        _id: usize,
+        line_index: usize,
+    ) -> Result<usize, codespan_reporting::files::Error> {
+        Ok(self
+            .map
+            .lines
+            .get(line_index)
+            .map_or(line_index + 1, |line| line.line_number as usize))
+    }
+
+    fn line_range(
+        &'a self,
+        _id: usize,
+        line_index: usize,
+    ) -> Result<std::ops::Range<usize>, codespan_reporting::files::Error> {
+        let start = *self.line_starts.get(line_index).ok_or(
+            codespan_reporting::files::Error::LineTooLarge {
+                given: line_index,
+                max: self.line_starts.len() - 1,
+            },
+        )?;
+        let end = self
+            .line_starts
+            .get(line_index + 1)
+            .copied()
+            .unwrap_or(self.source.len());
+        Ok(start..end)
+    }
+}
+
 impl fmt::Display for ShaderError<naga::front::wgsl::ParseError> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let label = self.label.as_deref().unwrap_or_default();
-        let string = self.inner.emit_to_string(&self.source);
+        let string = match self.source_map {
+            Some(ref map) => MappedFiles::new(label, &self.source, map).emit(
+                self.inner.message(),
+                self.inner
+                    .labels()
+                    .map(|(range, text)| (range, text.to_string())),
+            ),
+            None => self.inner.emit_to_string(&self.source),
+        };
         write!(f, "\nShader '{}' parsing {}", label, string)
     }
 }
@@ -79,6 +195,15 @@ impl fmt::Display for ShaderError<naga::WithSpan<naga::valid::ValidationError>>
         };
 
         let label = self.label.as_deref().unwrap_or_default();
+        if let Some(ref map) = self.source_map {
+            let string = MappedFiles::new(label, &self.source, map).emit(
+                "",
+                self.inner
+                    .spans()
+                    .filter_map(|&(span, ref desc)| Some((span.to_range()?, desc.clone()))),
+            );
+            return write!(f, "\nShader validation {}", string);
+        }
         let files = SimpleFile::new(label, &self.source);
         let config = term::Config::default();
         let mut writer = term::termcolor::Ansi::new(Vec::new());
@@ -132,6 +257,8 @@ pub enum CreateShaderModuleError {
         group: u32,
         limit: u32,
     },
+    #[error("invalid pipeline-overridable constant: {0}")]
+    InvalidOverride(String),
 }
 
 impl CreateShaderModuleError {
@@ -142,6 +269,75 @@ impl CreateShaderModuleError {
             _ => None,
         }
     }
+
+    /// Breaks the error down into messages attached to spans of the shader source.
+    ///
+    /// The first message is always the error itself. Secondary spans of the
+    /// error follow it as [`wgt::CompilationMessageType::Info`] messages.
+    pub fn compilation_info(&self) -> wgt::CompilationInfo {
+        fn location<E>(err: &ShaderError<E>, range: std::ops::Range<usize>) -> wgt::SourceLocation {
+            let location =
+                naga::Span::new(range.start as u32, range.end as u32).location(&err.source);
+            let location = wgt::SourceLocation {
+                line_number: location.line_number,
+                line_position: location.line_position,
+                offset: location.offset,
+                length: location.length,
+                file: None,
+            };
+            match err.source_map {
+                Some(ref map) => map.map_location(&location),
+                None => location,
+            }
+        }
+        fn info(message: &str, location: wgt::SourceLocation) -> wgt::CompilationMessage {
+            wgt::CompilationMessage {
+                message: message.to_string(),
+                message_type: wgt::CompilationMessageType::Info,
+                location: Some(location),
+            }
+        }
+
+        let mut messages = Vec::new();
+        match *self {
+            CreateShaderModuleError::Parsing(ref err) => {
+                let mut labels = err
+                    .inner
+                    .labels()
+                    .map(|(span, text)| (location(err, span), text));
+                messages.push(wgt::CompilationMessage {
+                    message: err.inner.message().to_string(),
+                    message_type: wgt::CompilationMessageType::Error,
+                    location: labels.next().map(|(location, _)| location),
+                });
+                messages.extend(labels.map(|(location, text)| info(text, location)));
+            }
+            CreateShaderModuleError::Validation(ref err) => {
+                let mut message = err.inner.to_string();
+                let mut cause = err.inner.source();
+                while let Some(inner) = cause {
+                    message = format!("{}: {}", message, inner);
+                    cause = inner.source();
+                }
+                let mut spans = err
+                    .inner
+                    .spans()
+                    .filter_map(|&(span, ref text)| Some((location(err, span.to_range()?), text)));
+                messages.push(wgt::CompilationMessage {
+                    message,
+                    message_type: wgt::CompilationMessageType::Error,
+                    location: spans.next().map(|(location, _)| location),
+                });
+                messages.extend(spans.map(|(location, text)| info(text, location)));
+            }
+            _ => messages.push(wgt::CompilationMessage {
+                message: self.to_string(),
+                message_type: wgt::CompilationMessageType::Error,
+                location: None,
+            }),
+        }
+        wgt::CompilationInfo { messages }
+    }
 }
 
 /// Describes a programmable pipeline stage.
@@ -154,6 +350,20 @@ pub struct ProgrammableStageDescriptor<'a> {
     /// The name of the entry point in the compiled shader. There must be a function with this name
     /// in the shader.
     pub entry_point: Cow<'a, str>,
+    /// Values of the pipeline-overridable constants of the module, keyed by name or by
+    /// numeric id. Constants that aren't listed keep the value declared in the shader.
+    #[cfg_attr(feature = "replay", serde(default))]
+    pub constants: Cow<'a, HashMap<String, f64>>,
+}
+
+impl ProgrammableStageDescriptor<'_> {
+    pub(crate) fn to_static(&self) -> ProgrammableStageDescriptor<'static> {
+        ProgrammableStageDescriptor {
+            module: self.module,
+            entry_point: Cow::Owned(self.entry_point.to_string()),
+            constants: Cow::Owned(self.constants.as_ref().clone()),
+        }
+    }
 }
 
 /// Number of implicit bind groups derived at pipeline creation.
@@ -171,6 +381,41 @@ pub enum ImplicitLayoutError {
     Pipeline(#[from] CreatePipelineLayoutError),
 }
 
+/// Describes a pipeline cache.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "trace", derive(serde::Serialize))]
+#[cfg_attr(feature = "replay", derive(serde::Deserialize))]
+pub struct PipelineCacheDescriptor<'a> {
+    pub label: Label<'a>,
+    /// Data previously returned by `pipeline_cache_get_data`.
+    ///
+    /// It's not recorded in traces: the cache only affects how fast pipelines
+    /// are created, not what they do.
+    #[cfg_attr(any(feature = "replay", feature = "trace"), serde(skip))]
+    pub data: Option<Cow<'a, [u8]>>,
+}
+
+#[derive(Clone, Debug, Error)]
+pub enum CreatePipelineCacheError {
+    #[error(transparent)]
+    Device(#[from] DeviceError),
+}
+
+#[derive(Debug)]
+pub struct PipelineCache<A: hal::Api> {
+    pub(crate) raw: A::PipelineCache,
+    pub(crate) device_id: Stored<DeviceId>,
+    pub(crate) life_guard: LifeGuard,
+}
+
+impl<A: hal::Api> Resource for PipelineCache<A> {
+    const TYPE: &'static str = "PipelineCache";
+
+    fn life_guard(&self) -> &LifeGuard {
+        &self.life_guard
+    }
+}
+
 /// Describes a compute pipeline.
 #[derive(Clone, Debug)]
 #[cfg_attr(feature = "trace", derive(serde::Serialize))]
@@ -181,6 +426,23 @@ pub struct ComputePipelineDescriptor<'a> {
     pub layout: Option<PipelineLayoutId>,
     /// The compiled compute stage and its entry point.
     pub stage: ProgrammableStageDescriptor<'a>,
+    /// The pipeline cache to use when creating this pipeline.
+    #[cfg_attr(any(feature = "replay", feature = "trace"), serde(default))]
+    pub cache: Option<PipelineCacheId>,
+}
+
+impl ComputePipelineDescriptor<'_> {
+    pub(crate) fn to_static(&self) -> ComputePipelineDescriptor<'static> {
+        ComputePipelineDescriptor {
+            label: self
+                .label
+                .as_ref()
+                .map(|label| Cow::Owned(label.to_string())),
+            layout: self.layout,
+            stage: self.stage.to_static(),
+            cache: self.cache,
+        }
+    }
 }
 
 #[derive(Clone, Debug, Error)]
@@ -189,6 +451,8 @@ pub enum CreateComputePipelineError {
     Device(#[from] DeviceError),
     #[error("pipeline layout is invalid")]
     InvalidLayout,
+    #[error("pipeline cache is invalid")]
+    InvalidCache,
     #[error("unable to derive an implicit layout")]
     Implicit(#[from] ImplicitLayoutError),
     #[error("error matching shader requirements against the pipeline")]
@@ -206,6 +470,12 @@ pub struct ComputePipeline<A: hal::Api> {
     pub(crate) device_id: Stored<DeviceId>,
     pub(crate) late_sized_buffer_groups: ArrayVec<LateSizedBufferGroup, { hal::MAX_BIND_GROUPS }>,
     pub(crate) life_guard: LifeGuard,
+    /// The descriptor to rebuild the pipeline from when one of its shader
+    /// modules is replaced, if shader hot reload is enabled on the device.
+    pub(crate) reload_desc: Option<Box<ComputePipelineDescriptor<'static>>>,
+    /// Raw pipelines replaced by a reload while command buffers could still
+    /// be using them. They are destroyed with the pipeline.
+    pub(crate) retired: Vec<A::ComputePipeline>,
 }
 
 impl<A: hal::Api> Resource for ComputePipeline<A> {
@@ -276,6 +546,43 @@ pub struct RenderPipelineDescriptor<'a> {
     /// If the pipeline will be used with a multiview render pass, this indicates how many array
     /// layers the attachments will have.
     pub multiview: Option<NonZeroU32>,
+    /// The pipeline cache to use when creating this pipeline.
+    #[cfg_attr(any(feature = "replay", feature = "trace"), serde(default))]
+    pub cache: Option<PipelineCacheId>,
+}
+
+impl RenderPipelineDescriptor<'_> {
+    pub(crate) fn to_static(&self) -> RenderPipelineDescriptor<'static> {
+        RenderPipelineDescriptor {
+            label: self
+                .label
+                .as_ref()
+                .map(|label| Cow::Owned(label.to_string())),
+            layout: self.layout,
+            vertex: VertexState {
+                stage: self.vertex.stage.to_static(),
+                buffers: self
+                    .vertex
+                    .buffers
+                    .iter()
+                    .map(|buffer| VertexBufferLayout {
+                        array_stride: buffer.array_stride,
+                        step_mode: buffer.step_mode,
+                        attributes: Cow::Owned(buffer.attributes.to_vec()),
+                    })
+                    .collect(),
+            },
+            primitive: self.primitive,
+            depth_stencil: self.depth_stencil.clone(),
+            multisample: self.multisample,
+            fragment: self.fragment.as_ref().map(|fragment| FragmentState {
+                stage: fragment.stage.to_static(),
+                targets: Cow::Owned(fragment.targets.to_vec()),
+            }),
+            multiview: self.multiview,
+            cache: self.cache,
+        }
+    }
 }
 
 #[derive(Clone, Debug, Error)]
@@ -315,6 +622,8 @@ pub enum CreateRenderPipelineError {
     Device(#[from] DeviceError),
     #[error("pipeline layout is invalid")]
     InvalidLayout,
+    #[error("pipeline cache is invalid")]
+    InvalidCache,
     #[error("unable to derive an implicit layout")]
     Implicit(#[from] ImplicitLayoutError),
     #[error("color state [{0}] is invalid")]
@@ -363,6 +672,8 @@ pub enum CreateRenderPipelineError {
         stage: wgt::ShaderStages,
         error: String,
     },
+    #[error("vertex format {0:?} is not supported by the backend")]
+    UnsupportedVertexFormat(wgt::VertexFormat),
 }
 
 bitflags::bitflags! {
@@ -405,6 +716,12 @@ pub struct RenderPipeline<A: hal::Api> {
     pub(crate) vertex_steps: Vec<VertexStep>,
     pub(crate) late_sized_buffer_groups: ArrayVec<LateSizedBufferGroup, { hal::MAX_BIND_GROUPS }>,
     pub(crate) life_guard: LifeGuard,
+    /// The descriptor to rebuild the pipeline from when one of its shader
+    /// modules is replaced, if shader hot reload is enabled on the device.
+    pub(crate) reload_desc: Option<Box<RenderPipelineDescriptor<'static>>>,
+    /// Raw pipelines replaced by a reload while command buffers could still
+    /// be using them. They are destroyed with the pipeline.
+    pub(crate) retired: Vec<A::RenderPipeline>,
 }
 
 impl<A: hal::Api> Resource for RenderPipeline<A> {
@@ -414,3 +731,31 @@ impl<A: hal::Api> Resource for RenderPipeline<A> {
         &self.life_guard
     }
 }
+
+/// Error encountered when replacing the source of a shader module.
+///
+/// The module and its dependent pipelines are left untouched.
+#[derive(Debug, Error)]
+pub enum ReplaceShaderModuleError {
+    #[error(transparent)]
+    Device(#[from] DeviceError),
+    #[error("shader module is invalid")]
+    InvalidModule,
+    #[error(transparent)]
+    Module(#[from] CreateShaderModuleError),
+    #[error("failed to rebuild a dependent render pipeline")]
+    RenderPipeline(RenderPipelineId, #[source] CreateRenderPipelineError),
+    #[error("failed to rebuild a dependent compute pipeline")]
+    ComputePipeline(ComputePipelineId, #[source] CreateComputePipelineError),
+}
+
+impl PrettyError for ReplaceShaderModuleError {
+    fn fmt_pretty(&self, fmt: &mut ErrorFormatter) {
+        fmt.error(self);
+        match *self {
+            Self::RenderPipeline(id, _) => fmt.render_pipeline_label(&id),
+            Self::ComputePipeline(id, _) => fmt.compute_pipeline_label(&id),
+            _ => {}
+        }
+    }
+}
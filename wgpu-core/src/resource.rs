@@ -1,5 +1,7 @@
 use crate::{
-    device::{DeviceError, HostMap, MissingFeatures},
+    device::{
+        index_range::IndexContents, DeviceError, HostMap, MissingDownlevelFlags, MissingFeatures,
+    },
     hub::{Global, GlobalIdentityHandlerFactory, HalApi, Resource, Token},
     id::{AdapterId, DeviceId, SurfaceId, TextureId, Valid},
     init_tracker::{BufferInitTracker, TextureInitTracker},
@@ -8,6 +10,7 @@ use crate::{
     Label, LifeGuard, RefCount, Stored,
 };
 
+use parking_lot::Mutex;
 use smallvec::SmallVec;
 use thiserror::Error;
 
@@ -195,6 +198,9 @@ pub struct Buffer<A: hal::Api> {
     pub(crate) usage: wgt::BufferUsages,
     pub(crate) size: wgt::BufferAddress,
     pub(crate) initialization_status: BufferInitTracker,
+    /// Contents known on the CPU, for index buffers of devices with
+    /// [`wgt::Features::INDEX_RANGE_VALIDATION`].
+    pub(crate) index_contents: Option<Mutex<IndexContents>>,
     pub(crate) sync_mapped_writes: Option<hal::MemoryRange>,
     pub(crate) life_guard: LifeGuard,
     pub(crate) map_state: BufferMapState<A>,
@@ -214,6 +220,8 @@ pub enum CreateBufferError {
     UsageMismatch(wgt::BufferUsages),
     #[error("Buffer size {requested} is greater than the maximum buffer size ({maximum})")]
     MaxBufferSize { requested: u64, maximum: u64 },
+    #[error(transparent)]
+    MissingFeatures(#[from] MissingFeatures),
 }
 
 impl<A: hal::Api> Resource for Buffer<A> {
@@ -261,7 +269,7 @@ impl<A: hal::Api> Resource for StagingBuffer<A> {
     }
 }
 
-pub type TextureDescriptor<'a> = wgt::TextureDescriptor<Label<'a>>;
+pub type TextureDescriptor<'a> = wgt::TextureDescriptor<Label<'a>, Vec<wgt::TextureFormat>>;
 
 #[derive(Debug)]
 pub(crate) enum TextureInner<A: hal::Api> {
@@ -302,10 +310,14 @@ pub enum TextureClearMode<A: hal::Api> {
 pub struct Texture<A: hal::Api> {
     pub(crate) inner: TextureInner<A>,
     pub(crate) device_id: Stored<DeviceId>,
-    pub(crate) desc: wgt::TextureDescriptor<()>,
+    pub(crate) desc: wgt::TextureDescriptor<(), Vec<wgt::TextureFormat>>,
     pub(crate) hal_usage: hal::TextureUses,
     pub(crate) format_features: wgt::TextureFormatFeatures,
     pub(crate) initialization_status: TextureInitTracker,
+    /// Whether parts of the texture read before being written are zeroed, false for
+    /// textures created with [`wgt::Features::UNINITIALIZED_RESOURCES`]. Discards
+    /// don't make the memory of these textures uninitialized again.
+    pub(crate) zero_initialized: bool,
     pub(crate) full_range: TextureSelector,
     pub(crate) life_guard: LifeGuard,
     pub(crate) clear_mode: TextureClearMode<A>,
@@ -462,6 +474,12 @@ pub enum CreateTextureError {
     MultisampledNotRenderAttachment,
     #[error("Texture format {0:?} can't be used due to missing features.")]
     MissingFeatures(wgt::TextureFormat, #[source] MissingFeatures),
+    #[error("Textures can't be created uninitialized due to missing features.")]
+    UninitializedMissingFeatures(#[source] MissingFeatures),
+    #[error("The view format {0:?} is not compatible with texture format {1:?}, only changing srgb-ness is allowed.")]
+    InvalidViewFormat(wgt::TextureFormat, wgt::TextureFormat),
+    #[error(transparent)]
+    MissingDownlevelFlags(#[from] MissingDownlevelFlags),
 }
 
 impl<A: hal::Api> Resource for Texture<A> {
@@ -486,7 +504,7 @@ pub struct TextureViewDescriptor<'a> {
     /// Debug label of the texture view. This will show up in graphics debuggers for easy identification.
     pub label: Label<'a>,
     /// Format of the texture view, or `None` for the same format as the texture itself.
-    /// At this time, it must be the same the underlying format of the texture.
+    /// It must be the format of the texture or one of its `view_formats`.
     pub format: Option<wgt::TextureFormat>,
     /// The dimension of the texture view. For 1D textures, this must be `1D`. For 2D textures it must be one of
     /// `D2`, `D2Array`, `Cube`, and `CubeArray`. For 3D textures it must be `3D`
@@ -557,7 +575,7 @@ pub enum CreateTextureViewError {
         texture_format: wgt::TextureFormat,
         requested_aspect: wgt::TextureAspect,
     },
-    #[error("Unable to view texture {texture:?} as {view:?}")]
+    #[error("Unable to view texture {texture:?} as {view:?}, the format is not in the texture's `view_formats`")]
     FormatReinterpretation {
         texture: wgt::TextureFormat,
         view: wgt::TextureFormat,
@@ -14,7 +14,7 @@ use crate::{
     resource::Buffer,
     track::{
         invalid_resource_state, iterate_bitvec_indices, skip_barrier, ResourceMetadata,
-        ResourceMetadataProvider, ResourceUses, UsageConflict,
+        ResourceMetadataProvider, ResourceUses, UsageConflict, UsageOrigin, UsageOrigins,
     },
     LifeGuard, RefCount,
 };
@@ -39,9 +39,9 @@ impl ResourceUses for BufferUses {
     }
 }
 
-/// Stores all the buffers that a bind group stores.
+/// Stores all the buffers that a bind group stores, with their binding.
 pub(crate) struct BufferBindGroupState<A: hub::HalApi> {
-    buffers: Vec<(Valid<BufferId>, RefCount, BufferUses)>,
+    buffers: Vec<(Valid<BufferId>, RefCount, BufferUses, u32)>,
 
     _phantom: PhantomData<A>,
 }
@@ -60,25 +60,26 @@ impl<A: hub::HalApi> BufferBindGroupState<A> {
     /// accesses will be in a constant assending order.
     pub(crate) fn optimize(&mut self) {
         self.buffers
-            .sort_unstable_by_key(|&(id, _, _)| id.0.unzip().0);
+            .sort_unstable_by_key(|&(id, _, _, _)| id.0.unzip().0);
     }
 
     /// Returns a list of all buffers tracked. May contain duplicates.
     pub fn used(&self) -> impl Iterator<Item = Valid<BufferId>> + '_ {
-        self.buffers.iter().map(|&(id, _, _)| id)
+        self.buffers.iter().map(|&(id, _, _, _)| id)
     }
 
-    /// Adds the given resource with the given state.
+    /// Adds the given resource with the given state, bound at `binding`.
     pub fn add_single<'a>(
         &mut self,
         storage: &'a hub::Storage<Buffer<A>, BufferId>,
         id: BufferId,
         state: BufferUses,
+        binding: u32,
     ) -> Option<&'a Buffer<A>> {
         let buffer = storage.get(id).ok()?;
 
         self.buffers
-            .push((Valid(id), buffer.life_guard.add_ref(), state));
+            .push((Valid(id), buffer.life_guard.add_ref(), state, binding));
 
         Some(buffer)
     }
@@ -88,6 +89,7 @@ impl<A: hub::HalApi> BufferBindGroupState<A> {
 #[derive(Debug)]
 pub(crate) struct BufferUsageScope<A: hub::HalApi> {
     state: Vec<BufferUses>,
+    origins: UsageOrigins,
 
     metadata: ResourceMetadata<A>,
 }
@@ -96,6 +98,7 @@ impl<A: hub::HalApi> BufferUsageScope<A> {
     pub fn new() -> Self {
         Self {
             state: Vec::new(),
+            origins: UsageOrigins::default(),
 
             metadata: ResourceMetadata::new(),
         }
@@ -112,6 +115,7 @@ impl<A: hub::HalApi> BufferUsageScope<A> {
     /// all unsafe functions are called.
     pub fn set_size(&mut self, size: usize) {
         self.state.resize(size, BufferUses::empty());
+        self.origins.set_size(size);
         self.metadata.set_size(size);
     }
 
@@ -130,7 +134,8 @@ impl<A: hub::HalApi> BufferUsageScope<A> {
     /// Merge the list of buffer states in the given bind group into this usage scope.
     ///
     /// If any of the resulting states is invalid, stops the merge and returns a usage
-    /// conflict with the details of the invalid state.
+    /// conflict with the details of the invalid state. `origin` tells where the bind
+    /// group is used, and is completed with the binding of each buffer.
     ///
     /// Because bind groups do not check if the union of all their states is valid,
     /// this method is allowed to return Err on the first bind group bound.
@@ -142,12 +147,14 @@ impl<A: hub::HalApi> BufferUsageScope<A> {
     pub unsafe fn merge_bind_group(
         &mut self,
         bind_group: &BufferBindGroupState<A>,
+        origin: UsageOrigin,
     ) -> Result<(), UsageConflict> {
-        for &(id, ref ref_count, state) in &bind_group.buffers {
+        for &(id, ref ref_count, state, binding) in &bind_group.buffers {
             let (index32, epoch, _) = id.0.unzip();
             let index = index32 as usize;
 
-            insert_or_merge(
+            let was_used = self.metadata.owned.get(index).unwrap_unchecked();
+            let result = insert_or_merge(
                 None,
                 None,
                 &mut self.state,
@@ -159,7 +166,12 @@ impl<A: hub::HalApi> BufferUsageScope<A> {
                     epoch,
                     ref_count: Cow::Borrowed(ref_count),
                 },
-            )?;
+            );
+            let origin = UsageOrigin {
+                binding: Some(binding),
+                ..origin
+            };
+            self.origins.merged(index, was_used, Some(origin), result)?;
         }
 
         Ok(())
@@ -168,11 +180,16 @@ impl<A: hub::HalApi> BufferUsageScope<A> {
     /// Merge the list of buffer states in the given usage scope into this UsageScope.
     ///
     /// If any of the resulting states is invalid, stops the merge and returns a usage
-    /// conflict with the details of the invalid state.
+    /// conflict with the details of the invalid state. `origin` tells where the given
+    /// scope is used.
     ///
     /// If the given tracker uses IDs higher than the length of internal vectors,
     /// the vectors will be extended. A call to set_size is not needed.
-    pub fn merge_usage_scope(&mut self, scope: &Self) -> Result<(), UsageConflict> {
+    pub fn merge_usage_scope(
+        &mut self,
+        scope: &Self,
+        origin: UsageOrigin,
+    ) -> Result<(), UsageConflict> {
         let incoming_size = scope.state.len();
         if incoming_size > self.state.len() {
             self.set_size(incoming_size);
@@ -183,7 +200,8 @@ impl<A: hub::HalApi> BufferUsageScope<A> {
             scope.tracker_assert_in_bounds(index);
 
             unsafe {
-                insert_or_merge(
+                let was_used = self.metadata.owned.get(index).unwrap_unchecked();
+                let result = insert_or_merge(
                     None,
                     None,
                     &mut self.state,
@@ -196,7 +214,8 @@ impl<A: hub::HalApi> BufferUsageScope<A> {
                     ResourceMetadataProvider::Indirect {
                         metadata: &scope.metadata,
                     },
-                )?;
+                );
+                self.origins.merged(index, was_used, Some(origin), result)?;
             };
         }
 
@@ -215,6 +234,7 @@ impl<A: hub::HalApi> BufferUsageScope<A> {
         storage: &'a hub::Storage<Buffer<A>, BufferId>,
         id: BufferId,
         new_state: BufferUses,
+        origin: UsageOrigin,
     ) -> Result<&'a Buffer<A>, UsageConflict> {
         let buffer = storage
             .get(id)
@@ -228,7 +248,8 @@ impl<A: hub::HalApi> BufferUsageScope<A> {
         self.tracker_assert_in_bounds(index);
 
         unsafe {
-            insert_or_merge(
+            let was_used = self.metadata.owned.get(index).unwrap_unchecked();
+            let result = insert_or_merge(
                 Some(&buffer.life_guard),
                 None,
                 &mut self.state,
@@ -237,7 +258,8 @@ impl<A: hub::HalApi> BufferUsageScope<A> {
                 index,
                 BufferStateProvider::Direct { state: new_state },
                 ResourceMetadataProvider::Resource { epoch },
-            )?;
+            );
+            self.origins.merged(index, was_used, Some(origin), result)?;
         }
 
         Ok(buffer)
@@ -299,6 +321,16 @@ impl<A: hub::HalApi> BufferTracker<A> {
         self.temp.drain(..)
     }
 
+    /// Returns the state of the given buffer at the end of the tracked commands,
+    /// if it's tracked.
+    pub fn current_state(&self, id: Valid<BufferId>) -> Option<BufferUses> {
+        let index = id.0.unzip().0 as usize;
+        match self.metadata.owned.get(index) {
+            Some(true) => Some(self.end[index]),
+            _ => None,
+        }
+    }
+
     /// Inserts a single buffer and its state into the resource tracker.
     ///
     /// If the resource already exists in the tracker, this will panic.
@@ -43,6 +43,8 @@
  * a single [`UsageScope`] in the spec. When a use is added to a usage scope,
  * it is merged with all other uses of that resource in that scope. If there
  * is a usage conflict, merging will fail and an error will be reported.
+ * Usage scopes also remember where each resource was first used in them, as a
+ * [`UsageOrigin`], so that the error can tell which uses conflict.
  *
  * Full trackers represent a before and after state of a resource. These
  * are used for tracking on the device and on command buffers. The before
@@ -95,7 +97,9 @@ mod stateless;
 mod texture;
 
 use crate::{
-    binding_model, command, conv, hub,
+    binding_model, command, conv,
+    error::ErrorFormatter,
+    hub,
     id::{self, TypedId},
     pipeline, resource, Epoch, LifeGuard, RefCount,
 };
@@ -268,6 +272,8 @@ impl UsageConflict {
             invalid_use: InvalidUse {
                 current_state,
                 new_state,
+                current_origin: None,
+                new_origin: None,
             },
         }
     }
@@ -285,9 +291,168 @@ impl UsageConflict {
             invalid_use: InvalidUse {
                 current_state,
                 new_state,
+                current_origin: None,
+                new_origin: None,
             },
         }
     }
+
+    /// Sets where the conflicting uses come from.
+    fn with_origins(mut self, current: Option<UsageOrigin>, new: Option<UsageOrigin>) -> Self {
+        match self {
+            Self::Buffer {
+                ref mut invalid_use,
+                ..
+            } => invalid_use.set_origins(current, new),
+            Self::Texture {
+                ref mut invalid_use,
+                ..
+            } => invalid_use.set_origins(current, new),
+            Self::BufferInvalid { .. } | Self::TextureInvalid { .. } => {}
+        }
+        self
+    }
+
+    /// Writes the labels of the resource and of the bind groups and render bundles
+    /// the conflicting uses come from.
+    pub(crate) fn fmt_labels(&self, fmt: &mut ErrorFormatter) {
+        let (current_origin, new_origin) = match *self {
+            Self::BufferInvalid { id } => return fmt.buffer_label(&id),
+            Self::TextureInvalid { id } => return fmt.texture_label(&id),
+            Self::Buffer {
+                id,
+                ref invalid_use,
+            } => {
+                fmt.buffer_label(&id);
+                (invalid_use.current_origin(), invalid_use.new_origin())
+            }
+            Self::Texture {
+                id,
+                ref invalid_use,
+                ..
+            } => {
+                fmt.texture_label(&id);
+                (invalid_use.current_origin(), invalid_use.new_origin())
+            }
+        };
+        for (origin, key) in [(current_origin, "current"), (new_origin, "new")] {
+            match origin.map(|origin| origin.source) {
+                Some(UsageSource::BindGroup { bind_group, .. }) => {
+                    fmt.bind_group_label_with_key(&bind_group, &format!("{} bind group", key))
+                }
+                Some(UsageSource::RenderBundle(bundle)) => {
+                    fmt.render_bundle_label_with_key(&bundle, &format!("{} render bundle", key))
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// How a resource is used in a usage scope.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum UsageSource {
+    /// A bind group, set at index `group`.
+    BindGroup {
+        group: u32,
+        bind_group: id::BindGroupId,
+    },
+    VertexBuffer {
+        slot: u32,
+    },
+    IndexBuffer,
+    IndirectBuffer,
+    RenderBundle(id::RenderBundleId),
+    ColorAttachment {
+        slot: u32,
+    },
+    ResolveTarget {
+        slot: u32,
+    },
+    DepthStencilAttachment,
+}
+
+/// Where a resource is used in a usage scope.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct UsageOrigin {
+    pub source: UsageSource,
+    /// Binding of the resource in the bind group, for [`UsageSource::BindGroup`].
+    pub binding: Option<u32>,
+    /// Index of the pass or render bundle command using the resource, `None` for
+    /// attachments.
+    pub command: Option<usize>,
+}
+
+impl UsageOrigin {
+    pub(crate) fn new(source: UsageSource, command: Option<usize>) -> Self {
+        Self {
+            source,
+            binding: None,
+            command,
+        }
+    }
+}
+
+impl fmt::Display for UsageOrigin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(binding) = self.binding {
+            write!(f, "binding {} of ", binding)?;
+        }
+        match self.source {
+            UsageSource::BindGroup { group, bind_group } => {
+                write!(f, "bind group {:?} at index {}", bind_group, group)?
+            }
+            UsageSource::VertexBuffer { slot } => write!(f, "vertex buffer slot {}", slot)?,
+            UsageSource::IndexBuffer => write!(f, "index buffer")?,
+            UsageSource::IndirectBuffer => write!(f, "indirect buffer")?,
+            UsageSource::RenderBundle(bundle) => write!(f, "render bundle {:?}", bundle)?,
+            UsageSource::ColorAttachment { slot } => write!(f, "color attachment {}", slot)?,
+            UsageSource::ResolveTarget { slot } => {
+                write!(f, "resolve target of color attachment {}", slot)?
+            }
+            UsageSource::DepthStencilAttachment => write!(f, "depth stencil attachment")?,
+        }
+        if let Some(command) = self.command {
+            write!(f, " in command {}", command)?;
+        }
+        Ok(())
+    }
+}
+
+/// The [`UsageOrigin`] of each resource of a usage scope, stored alongside its state.
+#[derive(Debug, Default)]
+struct UsageOrigins {
+    origins: Vec<Option<UsageOrigin>>,
+}
+
+impl UsageOrigins {
+    fn set_size(&mut self, size: usize) {
+        self.origins.resize(size, None);
+    }
+
+    /// Records `origin` for the resource at `index`, if it wasn't used in the scope
+    /// before, or adds the origins of the conflicting uses to the error of the merge.
+    ///
+    /// # Safety
+    ///
+    /// `index` must be in bounds.
+    unsafe fn merged(
+        &mut self,
+        index: usize,
+        was_used: bool,
+        origin: Option<UsageOrigin>,
+        result: Result<(), UsageConflict>,
+    ) -> Result<(), UsageConflict> {
+        let current = self.origins.get_unchecked_mut(index);
+        match result {
+            Ok(()) if !was_used => {
+                *current = origin;
+                Ok(())
+            }
+            Ok(()) => Ok(()),
+            Err(conflict) => Err(conflict.with_origins(*current, origin)),
+        }
+    }
 }
 
 /// Pretty print helper that shows helpful descriptions of a conflicting usage.
@@ -295,6 +460,26 @@ impl UsageConflict {
 pub struct InvalidUse<T> {
     current_state: T,
     new_state: T,
+    // Boxed to keep the errors containing usage conflicts small.
+    current_origin: Option<Box<UsageOrigin>>,
+    new_origin: Option<Box<UsageOrigin>>,
+}
+
+impl<T> InvalidUse<T> {
+    fn set_origins(&mut self, current: Option<UsageOrigin>, new: Option<UsageOrigin>) {
+        self.current_origin = current.map(Box::new);
+        self.new_origin = new.map(Box::new);
+    }
+
+    /// Where the usage already in the scope comes from, if known.
+    pub fn current_origin(&self) -> Option<UsageOrigin> {
+        self.current_origin.as_deref().copied()
+    }
+
+    /// Where the conflicting usage comes from, if known.
+    pub fn new_origin(&self) -> Option<UsageOrigin> {
+        self.new_origin.as_deref().copied()
+    }
 }
 
 impl<T: ResourceUses> fmt::Display for InvalidUse<T> {
@@ -308,10 +493,17 @@ impl<T: ResourceUses> fmt::Display for InvalidUse<T> {
         let exclusive = current_exclusive | new_exclusive;
 
         // The text starts with "tried to use X resource with {self}"
+        write!(f, "conflicting usages. Current usage {current:?}")?;
+        if let Some(origin) = self.current_origin() {
+            write!(f, " by {}", origin)?;
+        }
+        write!(f, " and new usage {new:?}")?;
+        if let Some(origin) = self.new_origin() {
+            write!(f, " by {}", origin)?;
+        }
         write!(
             f,
-            "conflicting usages. Current usage {current:?} and new usage {new:?}. \
-            {exclusive:?} is an exclusive usage and cannot be used with any other\
+            ". {exclusive:?} is an exclusive usage and cannot be used with any other \
             usages within the usage scope (renderpass or compute dispatch)"
         )
     }
@@ -538,10 +730,11 @@ impl<A: hub::HalApi> RenderBundleScope<A> {
         &mut self,
         textures: &hub::Storage<resource::Texture<A>, id::TextureId>,
         bind_group: &BindGroupStates<A>,
+        origin: UsageOrigin,
     ) -> Result<(), UsageConflict> {
-        self.buffers.merge_bind_group(&bind_group.buffers)?;
+        self.buffers.merge_bind_group(&bind_group.buffers, origin)?;
         self.textures
-            .merge_bind_group(textures, &bind_group.textures)?;
+            .merge_bind_group(textures, &bind_group.textures, origin)?;
 
         Ok(())
     }
@@ -585,10 +778,11 @@ impl<A: hub::HalApi> UsageScope<A> {
         &mut self,
         textures: &hub::Storage<resource::Texture<A>, id::TextureId>,
         bind_group: &BindGroupStates<A>,
+        origin: UsageOrigin,
     ) -> Result<(), UsageConflict> {
-        self.buffers.merge_bind_group(&bind_group.buffers)?;
+        self.buffers.merge_bind_group(&bind_group.buffers, origin)?;
         self.textures
-            .merge_bind_group(textures, &bind_group.textures)?;
+            .merge_bind_group(textures, &bind_group.textures, origin)?;
 
         Ok(())
     }
@@ -606,10 +800,12 @@ impl<A: hub::HalApi> UsageScope<A> {
         &mut self,
         textures: &hub::Storage<resource::Texture<A>, id::TextureId>,
         render_bundle: &RenderBundleScope<A>,
+        origin: UsageOrigin,
     ) -> Result<(), UsageConflict> {
-        self.buffers.merge_usage_scope(&render_bundle.buffers)?;
+        self.buffers
+            .merge_usage_scope(&render_bundle.buffers, origin)?;
         self.textures
-            .merge_usage_scope(textures, &render_bundle.textures)?;
+            .merge_usage_scope(textures, &render_bundle.textures, origin)?;
 
         Ok(())
     }
@@ -26,7 +26,7 @@ use crate::{
     resource::Texture,
     track::{
         invalid_resource_state, iterate_bitvec_indices, skip_barrier, ResourceMetadata,
-        ResourceMetadataProvider, ResourceUses, UsageConflict,
+        ResourceMetadataProvider, ResourceUses, UsageConflict, UsageOrigin, UsageOrigins,
     },
     LifeGuard, RefCount,
 };
@@ -146,14 +146,18 @@ impl ComplexTextureState {
     }
 }
 
-/// Stores all the textures that a bind group stores.
+/// A texture used by a bind group: its selector, state and binding.
+type TextureBindGroupEntry = (
+    Valid<TextureId>,
+    Option<TextureSelector>,
+    RefCount,
+    TextureUses,
+    u32,
+);
+
+/// Stores all the textures that a bind group stores, with their binding.
 pub(crate) struct TextureBindGroupState<A: hub::HalApi> {
-    textures: Vec<(
-        Valid<TextureId>,
-        Option<TextureSelector>,
-        RefCount,
-        TextureUses,
-    )>,
+    textures: Vec<TextureBindGroupEntry>,
 
     _phantom: PhantomData<A>,
 }
@@ -172,15 +176,15 @@ impl<A: hub::HalApi> TextureBindGroupState<A> {
     /// accesses will be in a constant assending order.
     pub(crate) fn optimize(&mut self) {
         self.textures
-            .sort_unstable_by_key(|&(id, _, _, _)| id.0.unzip().0);
+            .sort_unstable_by_key(|&(id, _, _, _, _)| id.0.unzip().0);
     }
 
     /// Returns a list of all buffers tracked. May contain duplicates.
     pub fn used(&self) -> impl Iterator<Item = Valid<TextureId>> + '_ {
-        self.textures.iter().map(|&(id, _, _, _)| id)
+        self.textures.iter().map(|&(id, _, _, _, _)| id)
     }
 
-    /// Adds the given resource with the given state.
+    /// Adds the given resource with the given state, bound at `binding`.
     pub fn add_single<'a>(
         &mut self,
         storage: &'a hub::Storage<Texture<A>, TextureId>,
@@ -188,10 +192,12 @@ impl<A: hub::HalApi> TextureBindGroupState<A> {
         ref_count: RefCount,
         selector: Option<TextureSelector>,
         state: TextureUses,
+        binding: u32,
     ) -> Option<&'a Texture<A>> {
         let value = storage.get(id).ok()?;
 
-        self.textures.push((Valid(id), selector, ref_count, state));
+        self.textures
+            .push((Valid(id), selector, ref_count, state, binding));
 
         Some(value)
     }
@@ -220,6 +226,7 @@ impl TextureStateSet {
 #[derive(Debug)]
 pub(crate) struct TextureUsageScope<A: hub::HalApi> {
     set: TextureStateSet,
+    origins: UsageOrigins,
 
     metadata: ResourceMetadata<A>,
 }
@@ -228,6 +235,7 @@ impl<A: hub::HalApi> TextureUsageScope<A> {
     pub fn new() -> Self {
         Self {
             set: TextureStateSet::new(),
+            origins: UsageOrigins::default(),
 
             metadata: ResourceMetadata::new(),
         }
@@ -253,6 +261,7 @@ impl<A: hub::HalApi> TextureUsageScope<A> {
     /// all unsafe functions are called.
     pub fn set_size(&mut self, size: usize) {
         self.set.set_size(size);
+        self.origins.set_size(size);
         self.metadata.set_size(size);
     }
 
@@ -271,7 +280,8 @@ impl<A: hub::HalApi> TextureUsageScope<A> {
     /// Merge the list of texture states in the given usage scope into this UsageScope.
     ///
     /// If any of the resulting states is invalid, stops the merge and returns a usage
-    /// conflict with the details of the invalid state.
+    /// conflict with the details of the invalid state. `origin` tells where the given
+    /// scope is used.
     ///
     /// If the given tracker uses IDs higher than the length of internal vectors,
     /// the vectors will be extended. A call to set_size is not needed.
@@ -279,6 +289,7 @@ impl<A: hub::HalApi> TextureUsageScope<A> {
         &mut self,
         storage: &hub::Storage<Texture<A>, TextureId>,
         scope: &Self,
+        origin: UsageOrigin,
     ) -> Result<(), UsageConflict> {
         let incoming_size = scope.set.simple.len();
         if incoming_size > self.set.simple.len() {
@@ -292,7 +303,8 @@ impl<A: hub::HalApi> TextureUsageScope<A> {
             scope.tracker_assert_in_bounds(index);
 
             unsafe {
-                insert_or_merge(
+                let was_used = self.metadata.owned.get(index).unwrap_unchecked();
+                let result = insert_or_merge(
                     texture_data_from_texture(storage, index32),
                     &mut self.set,
                     &mut self.metadata,
@@ -302,7 +314,8 @@ impl<A: hub::HalApi> TextureUsageScope<A> {
                     ResourceMetadataProvider::Indirect {
                         metadata: &scope.metadata,
                     },
-                )?
+                );
+                self.origins.merged(index, was_used, Some(origin), result)?
             };
         }
 
@@ -312,7 +325,8 @@ impl<A: hub::HalApi> TextureUsageScope<A> {
     /// Merge the list of texture states in the given bind group into this usage scope.
     ///
     /// If any of the resulting states is invalid, stops the merge and returns a usage
-    /// conflict with the details of the invalid state.
+    /// conflict with the details of the invalid state. `origin` tells where the bind
+    /// group is used, and is completed with the binding of each texture.
     ///
     /// Because bind groups do not check if the union of all their states is valid,
     /// this method is allowed to return Err on the first bind group bound.
@@ -325,9 +339,21 @@ impl<A: hub::HalApi> TextureUsageScope<A> {
         &mut self,
         storage: &hub::Storage<Texture<A>, TextureId>,
         bind_group: &TextureBindGroupState<A>,
+        origin: UsageOrigin,
     ) -> Result<(), UsageConflict> {
-        for &(id, ref selector, ref ref_count, state) in &bind_group.textures {
-            self.merge_single(storage, id, selector.clone(), ref_count, state)?;
+        for &(id, ref selector, ref ref_count, state, binding) in &bind_group.textures {
+            let origin = UsageOrigin {
+                binding: Some(binding),
+                ..origin
+            };
+            self.merge_single(
+                storage,
+                id,
+                selector.clone(),
+                ref_count,
+                state,
+                Some(origin),
+            )?;
         }
 
         Ok(())
@@ -336,7 +362,8 @@ impl<A: hub::HalApi> TextureUsageScope<A> {
     /// Merge a single state into the UsageScope.
     ///
     /// If the resulting state is invalid, returns a usage
-    /// conflict with the details of the invalid state.
+    /// conflict with the details of the invalid state. `origin` tells
+    /// where the texture is used, if it is used by a pass.
     ///
     /// # Safety
     ///
@@ -353,13 +380,15 @@ impl<A: hub::HalApi> TextureUsageScope<A> {
         selector: Option<TextureSelector>,
         ref_count: &RefCount,
         new_state: TextureUses,
+        origin: Option<UsageOrigin>,
     ) -> Result<(), UsageConflict> {
         let (index32, epoch, _) = id.0.unzip();
         let index = index32 as usize;
 
         self.tracker_assert_in_bounds(index);
 
-        insert_or_merge(
+        let was_used = self.metadata.owned.get(index).unwrap_unchecked();
+        let result = insert_or_merge(
             texture_data_from_texture(storage, index32),
             &mut self.set,
             &mut self.metadata,
@@ -370,9 +399,9 @@ impl<A: hub::HalApi> TextureUsageScope<A> {
                 epoch,
                 ref_count: Cow::Borrowed(ref_count),
             },
-        )?;
+        );
 
-        Ok(())
+        self.origins.merged(index, was_used, origin, result)
     }
 }
 
@@ -671,7 +700,7 @@ impl<A: hub::HalApi> TextureTracker<A> {
             self.set_size(incoming_size);
         }
 
-        for &(id, _, _, _) in bind_group_state.textures.iter() {
+        for &(id, _, _, _, _) in bind_group_state.textures.iter() {
             let (index32, _, _) = id.0.unzip();
             let index = index32 as usize;
             scope.tracker_assert_in_bounds(index);
@@ -1,6 +1,9 @@
 use crate::{binding_model::BindEntryMap, FastHashMap, FastHashSet};
 use naga::valid::GlobalUse;
-use std::{collections::hash_map::Entry, fmt};
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    fmt,
+};
 use thiserror::Error;
 use wgt::{BindGroupLayoutEntry, BindingType};
 
@@ -101,11 +104,24 @@ enum Varying {
     BuiltIn(naga::BuiltIn),
 }
 
-#[allow(unused)]
+/// A pipeline-overridable constant of the module.
 #[derive(Debug)]
-struct SpecializationConstant {
+struct Override {
+    handle: naga::Handle<naga::Constant>,
     id: u32,
-    ty: NumericType,
+    name: Option<String>,
+    value: naga::ScalarValue,
+    width: naga::Bytes,
+    /// Set if the constant has no value in the shader.
+    required: bool,
+}
+
+impl Override {
+    /// Returns true if the constant is identified by `key`, either through its
+    /// name or through its numeric id.
+    fn is_key(&self, key: &str) -> bool {
+        self.name.as_deref() == Some(key) || self.id.to_string() == key
+    }
 }
 
 #[derive(Debug, Default)]
@@ -113,10 +129,10 @@ struct EntryPoint {
     inputs: Vec<Varying>,
     outputs: Vec<Varying>,
     resources: Vec<(naga::Handle<Resource>, GlobalUse)>,
-    #[allow(unused)]
-    spec_constants: Vec<SpecializationConstant>,
     sampling_pairs: FastHashSet<(naga::Handle<Resource>, naga::Handle<Resource>)>,
     workgroup_size: [u32; 3],
+    /// Pipeline-overridable constants giving dimensions of the workgroup size.
+    workgroup_size_overrides: [Option<naga::Handle<naga::Constant>>; 3],
 }
 
 #[derive(Debug)]
@@ -124,6 +140,7 @@ pub struct Interface {
     features: wgt::Features,
     limits: wgt::Limits,
     resources: naga::Arena<Resource>,
+    overrides: Vec<Override>,
     entry_points: FastHashMap<(naga::ShaderStage, String), EntryPoint>,
 }
 
@@ -265,6 +282,14 @@ pub enum StageError {
     },
     #[error("location[{location}] is provided by the previous stage output but is not consumed as input by this stage.")]
     InputNotConsumed { location: wgt::ShaderLocation },
+    #[error("pipeline-overridable constant '{0}' is not declared in the shader")]
+    MissingOverride(String),
+    #[error("pipeline-overridable constant '{0}' has no value in the shader, and none is given")]
+    MissingOverrideValue(String),
+    #[error(
+        "value {value} of pipeline-overridable constant '{key}' is not representable in its type"
+    )]
+    InvalidOverrideValue { key: String, value: f64 },
 }
 
 fn map_storage_format_to_naga(format: wgt::TextureFormat) -> Option<naga::StorageFormat> {
@@ -667,6 +692,37 @@ impl NumericType {
         }
     }
 
+    /// Returns the 32-bit (or 64-bit) vertex format with this type, if any.
+    fn to_vertex_format(self) -> Option<wgt::VertexFormat> {
+        use naga::{ScalarKind as Sk, VectorSize as Vs};
+        use wgt::VertexFormat as Vf;
+
+        let size = match self.dim {
+            NumericDimension::Scalar => None,
+            NumericDimension::Vector(size) => Some(size),
+            NumericDimension::Matrix(..) => return None,
+        };
+        Some(match (self.kind, self.width, size) {
+            (Sk::Uint, 4, None) => Vf::Uint32,
+            (Sk::Uint, 4, Some(Vs::Bi)) => Vf::Uint32x2,
+            (Sk::Uint, 4, Some(Vs::Tri)) => Vf::Uint32x3,
+            (Sk::Uint, 4, Some(Vs::Quad)) => Vf::Uint32x4,
+            (Sk::Sint, 4, None) => Vf::Sint32,
+            (Sk::Sint, 4, Some(Vs::Bi)) => Vf::Sint32x2,
+            (Sk::Sint, 4, Some(Vs::Tri)) => Vf::Sint32x3,
+            (Sk::Sint, 4, Some(Vs::Quad)) => Vf::Sint32x4,
+            (Sk::Float, 4, None) => Vf::Float32,
+            (Sk::Float, 4, Some(Vs::Bi)) => Vf::Float32x2,
+            (Sk::Float, 4, Some(Vs::Tri)) => Vf::Float32x3,
+            (Sk::Float, 4, Some(Vs::Quad)) => Vf::Float32x4,
+            (Sk::Float, 8, None) => Vf::Float64,
+            (Sk::Float, 8, Some(Vs::Bi)) => Vf::Float64x2,
+            (Sk::Float, 8, Some(Vs::Tri)) => Vf::Float64x3,
+            (Sk::Float, 8, Some(Vs::Quad)) => Vf::Float64x4,
+            _ => return None,
+        })
+    }
+
     fn from_texture_format(format: wgt::TextureFormat) -> Self {
         use naga::{ScalarKind as Sk, VectorSize as Vs};
         use wgt::TextureFormat as Tf;
@@ -770,6 +826,10 @@ impl NumericType {
         if self.kind != other.kind {
             return false;
         }
+        // 64-bit vertex formats can only feed 64-bit inputs, and the other way around.
+        if (self.width == 8) != (other.width == 8) {
+            return false;
+        }
         match (self.dim, other.dim) {
             (NumericDimension::Scalar, NumericDimension::Scalar) => true,
             (NumericDimension::Scalar, NumericDimension::Vector(_)) => true,
@@ -863,6 +923,7 @@ impl Interface {
     pub fn new(
         module: &naga::Module,
         info: &naga::valid::ModuleInfo,
+        module_overrides: &hal::auxil::overrides::ModuleOverrides,
         features: wgt::Features,
         limits: wgt::Limits,
     ) -> Self {
@@ -910,6 +971,24 @@ impl Interface {
             resource_mapping.insert(var_handle, handle);
         }
 
+        let overrides = module
+            .constants
+            .iter()
+            .filter_map(
+                |(handle, constant)| match (constant.specialization, &constant.inner) {
+                    (Some(id), &naga::ConstantInner::Scalar { width, value }) => Some(Override {
+                        handle,
+                        id,
+                        name: constant.name.clone(),
+                        value,
+                        width,
+                        required: module_overrides.required.contains(&handle),
+                    }),
+                    _ => None,
+                },
+            )
+            .collect();
+
         let mut entry_points = FastHashMap::default();
         entry_points.reserve(module.entry_points.len());
         for (index, entry_point) in module.entry_points.iter().enumerate() {
@@ -943,6 +1022,13 @@ impl Interface {
             }
 
             ep.workgroup_size = entry_point.workgroup_size;
+            if let Some(&(_, dimensions)) = module_overrides
+                .workgroup_sizes
+                .iter()
+                .find(|&&(ep_index, _)| ep_index == index)
+            {
+                ep.workgroup_size_overrides = dimensions;
+            }
 
             entry_points.insert((entry_point.stage, entry_point.name.clone()), ep);
         }
@@ -951,16 +1037,44 @@ impl Interface {
             features,
             limits,
             resources,
+            overrides,
             entry_points,
         }
     }
 
+    /// Returns the inputs of a vertex entry point, sorted by location, or
+    /// `None` if there is no such entry point.
+    pub fn vertex_inputs(&self, entry_point_name: &str) -> Option<Vec<wgt::VertexInput>> {
+        let pair = (naga::ShaderStage::Vertex, entry_point_name.to_string());
+        let entry_point = self.entry_points.get(&pair)?;
+        let mut inputs = entry_point
+            .inputs
+            .iter()
+            .filter_map(|input| match *input {
+                Varying::Local { location, ref iv } => match iv.ty.to_vertex_format() {
+                    Some(format) => Some(wgt::VertexInput {
+                        shader_location: location,
+                        format,
+                    }),
+                    None => {
+                        log::warn!("Vertex input {} has no vertex format: {}", location, iv.ty);
+                        None
+                    }
+                },
+                Varying::BuiltIn(_) => None,
+            })
+            .collect::<Vec<_>>();
+        inputs.sort_by_key(|input| input.shader_location);
+        Some(inputs)
+    }
+
     pub fn check_stage(
         &self,
         given_layouts: Option<&[&BindEntryMap]>,
         derived_layouts: &mut [BindEntryMap],
         shader_binding_sizes: &mut FastHashMap<naga::ResourceBinding, wgt::BufferSize>,
         entry_point_name: &str,
+        constants: &HashMap<String, f64>,
         stage_bit: wgt::ShaderStages,
         inputs: StageIo,
         compare_function: Option<wgt::CompareFunction>,
@@ -979,6 +1093,31 @@ impl Interface {
             .get(&pair)
             .ok_or(StageError::MissingEntryPoint(pair.1))?;
 
+        // check pipeline-overridable constants
+        let mut override_values = FastHashMap::default();
+        for (key, &value) in constants.iter() {
+            let ov = self
+                .overrides
+                .iter()
+                .find(|ov| ov.is_key(key))
+                .ok_or_else(|| StageError::MissingOverride(key.clone()))?;
+            let converted = hal::auxil::overrides::convert_value(value, ov.value, ov.width)
+                .ok_or_else(|| StageError::InvalidOverrideValue {
+                    key: key.clone(),
+                    value,
+                })?;
+            override_values.insert(ov.handle, converted);
+        }
+        if let Some(ov) = self
+            .overrides
+            .iter()
+            .find(|ov| ov.required && !override_values.contains_key(&ov.handle))
+        {
+            return Err(StageError::MissingOverrideValue(
+                ov.name.clone().unwrap_or_else(|| ov.id.to_string()),
+            ));
+        }
+
         // check resources visibility
         for &(handle, usage) in entry_point.resources.iter() {
             let res = &self.resources[handle];
@@ -1080,23 +1219,39 @@ impl Interface {
             }
         }
 
-        // check workgroup size limits
+        // check workgroup size limits, with the sizes given by pipeline-overridable
+        // constants
         if shader_stage == naga::ShaderStage::Compute {
+            let mut workgroup_size = entry_point.workgroup_size;
+            for (size, handle) in workgroup_size
+                .iter_mut()
+                .zip(entry_point.workgroup_size_overrides.iter())
+            {
+                *size = match handle.and_then(|handle| override_values.get(&handle)) {
+                    Some(&naga::ScalarValue::Sint(v)) if v > 0 => v as u32,
+                    Some(&naga::ScalarValue::Uint(v)) => v.min(u32::MAX as u64) as u32,
+                    Some(_) => 0,
+                    None => *size,
+                };
+            }
+
             let max_workgroup_size_limits = [
                 self.limits.max_compute_workgroup_size_x,
                 self.limits.max_compute_workgroup_size_y,
                 self.limits.max_compute_workgroup_size_z,
             ];
-            let total_invocations = entry_point.workgroup_size.iter().product::<u32>();
+            let total_invocations = workgroup_size
+                .iter()
+                .fold(1u32, |total, &s| total.saturating_mul(s));
 
-            if entry_point.workgroup_size.iter().any(|&s| s == 0)
+            if workgroup_size.contains(&0)
                 || total_invocations > self.limits.max_compute_invocations_per_workgroup
-                || entry_point.workgroup_size[0] > max_workgroup_size_limits[0]
-                || entry_point.workgroup_size[1] > max_workgroup_size_limits[1]
-                || entry_point.workgroup_size[2] > max_workgroup_size_limits[2]
+                || workgroup_size[0] > max_workgroup_size_limits[0]
+                || workgroup_size[1] > max_workgroup_size_limits[1]
+                || workgroup_size[2] > max_workgroup_size_limits[2]
             {
                 return Err(StageError::InvalidWorkgroupSize {
-                    current: entry_point.workgroup_size,
+                    current: workgroup_size,
                     limit: max_workgroup_size_limits,
                     total: self.limits.max_compute_invocations_per_workgroup,
                 });
@@ -117,7 +117,7 @@ impl<A: hal::Api> Example<A> {
                 .max(*surface_caps.swap_chain_sizes.start())
                 .min(*surface_caps.swap_chain_sizes.end()),
             present_mode: wgt::PresentMode::Fifo,
-            composite_alpha_mode: hal::CompositeAlphaMode::Opaque,
+            composite_alpha_mode: wgt::CompositeAlphaMode::Opaque,
             format: wgt::TextureFormat::Bgra8UnormSrgb,
             extent: wgt::Extent3d {
                 width: window_size.0,
@@ -125,6 +125,7 @@ impl<A: hal::Api> Example<A> {
                 depth_or_array_layers: 1,
             },
             usage: hal::TextureUses::COLOR_TARGET,
+            view_formats: vec![],
         };
         unsafe {
             surface.configure(&device, &surface_config).unwrap();
@@ -143,7 +144,14 @@ impl<A: hal::Api> Example<A> {
             )
             .validate(&module)
             .unwrap();
-            hal::NagaShader { module, info }
+            let identity =
+                hal::auxil::pipeline_cache::ModuleIdentity::from_source(source.as_bytes());
+            hal::NagaShader {
+                module,
+                info,
+                identity,
+                overrides: Default::default(),
+            }
         };
         let shader_desc = hal::ShaderModuleDescriptor {
             label: None,
@@ -226,11 +234,13 @@ impl<A: hal::Api> Example<A> {
             vertex_stage: hal::ProgrammableStage {
                 module: &shader,
                 entry_point: "vs_main",
+                constants: &Default::default(),
             },
             vertex_buffers: &[],
             fragment_stage: Some(hal::ProgrammableStage {
                 module: &shader,
                 entry_point: "fs_main",
+                constants: &Default::default(),
             }),
             primitive: wgt::PrimitiveState {
                 topology: wgt::PrimitiveTopology::TriangleStrip,
@@ -244,6 +254,7 @@ impl<A: hal::Api> Example<A> {
                 write_mask: wgt::ColorWrites::default(),
             })],
             multiview: None,
+            cache: None,
         };
         let pipeline = unsafe { device.create_render_pipeline(&pipeline_desc).unwrap() };
 
@@ -282,12 +293,14 @@ impl<A: hal::Api> Example<A> {
             format: wgt::TextureFormat::Rgba8UnormSrgb,
             usage: hal::TextureUses::COPY_DST | hal::TextureUses::RESOURCE,
             memory_flags: hal::MemoryFlags::empty(),
+            view_formats: vec![],
         };
         let texture = unsafe { device.create_texture(&texture_desc).unwrap() };
 
         let cmd_encoder_desc = hal::CommandEncoderDescriptor {
             label: None,
             queue: &queue,
+            reusable: false,
         };
         let mut cmd_encoder = unsafe { device.create_command_encoder(&cmd_encoder_desc).unwrap() };
         unsafe { cmd_encoder.begin_encoding(Some("init")).unwrap() };
@@ -662,6 +675,7 @@ impl<A: hal::Api> Example<A> {
             })],
             depth_stencil_attachment: None,
             multiview: None,
+            occlusion_query_set: None,
         };
         unsafe {
             ctx.encoder.begin_render_pass(&pass_desc);
@@ -712,6 +726,7 @@ impl<A: hal::Api> Example<A> {
                 let hal_desc = hal::CommandEncoderDescriptor {
                     label: None,
                     queue: &self.queue,
+                    reusable: false,
                 };
                 self.contexts.push(unsafe {
                     ExecutionContext {
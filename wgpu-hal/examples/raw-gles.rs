@@ -152,6 +152,7 @@ fn fill_screen(exposed: &hal::ExposedAdapter<hal::api::Gles>, width: u32, height
             .create_command_encoder(&hal::CommandEncoderDescriptor {
                 label: None,
                 queue: &od.queue,
+                reusable: false,
             })
             .unwrap()
     };
@@ -174,6 +175,7 @@ fn fill_screen(exposed: &hal::ExposedAdapter<hal::api::Gles>, width: u32, height
         })],
         depth_stencil_attachment: None,
         multiview: None,
+        occlusion_query_set: None,
     };
     unsafe {
         encoder.begin_encoding(None).unwrap();
@@ -99,6 +99,20 @@ pub fn map_texture_format_nosrgb(format: wgt::TextureFormat) -> dxgiformat::DXGI
     }
 }
 
+// Note: views can only reinterpret the format of a resource created with a typeless format,
+// so textures that allow views with a different srgb-ness are created as typeless.
+pub fn map_texture_format_srgb_typeless(format: wgt::TextureFormat) -> dxgiformat::DXGI_FORMAT {
+    match format.remove_srgb_suffix() {
+        wgt::TextureFormat::Rgba8Unorm => dxgiformat::DXGI_FORMAT_R8G8B8A8_TYPELESS,
+        wgt::TextureFormat::Bgra8Unorm => dxgiformat::DXGI_FORMAT_B8G8R8A8_TYPELESS,
+        wgt::TextureFormat::Bc1RgbaUnorm => dxgiformat::DXGI_FORMAT_BC1_TYPELESS,
+        wgt::TextureFormat::Bc2RgbaUnorm => dxgiformat::DXGI_FORMAT_BC2_TYPELESS,
+        wgt::TextureFormat::Bc3RgbaUnorm => dxgiformat::DXGI_FORMAT_BC3_TYPELESS,
+        wgt::TextureFormat::Bc7RgbaUnorm => dxgiformat::DXGI_FORMAT_BC7_TYPELESS,
+        _ => map_texture_format(format),
+    }
+}
+
 //Note: SRV and UAV can't use the depth formats directly
 //TODO: stencil views?
 pub fn map_texture_format_nodepth(format: wgt::TextureFormat) -> dxgiformat::DXGI_FORMAT {
@@ -138,11 +152,12 @@ pub fn map_index_format(format: wgt::IndexFormat) -> dxgiformat::DXGI_FORMAT {
     }
 }
 
-pub fn map_vertex_format(format: wgt::VertexFormat) -> dxgiformat::DXGI_FORMAT {
+/// Returns `None` for the 64-bit formats, which DXGI has no vertex format for.
+pub fn map_vertex_format(format: wgt::VertexFormat) -> Option<dxgiformat::DXGI_FORMAT> {
     use wgt::VertexFormat as Vf;
     use winapi::shared::dxgiformat::*;
 
-    match format {
+    Some(match format {
         Vf::Unorm8x2 => DXGI_FORMAT_R8G8_UNORM,
         Vf::Snorm8x2 => DXGI_FORMAT_R8G8_SNORM,
         Vf::Uint8x2 => DXGI_FORMAT_R8G8_UINT,
@@ -173,15 +188,17 @@ pub fn map_vertex_format(format: wgt::VertexFormat) -> dxgiformat::DXGI_FORMAT {
         Vf::Uint32x4 => DXGI_FORMAT_R32G32B32A32_UINT,
         Vf::Sint32x4 => DXGI_FORMAT_R32G32B32A32_SINT,
         Vf::Float32x4 => DXGI_FORMAT_R32G32B32A32_FLOAT,
-        Vf::Float64 | Vf::Float64x2 | Vf::Float64x3 | Vf::Float64x4 => unimplemented!(),
-    }
+        Vf::Float64 | Vf::Float64x2 | Vf::Float64x3 | Vf::Float64x4 => return None,
+    })
 }
 
-pub fn map_acomposite_alpha_mode(mode: crate::CompositeAlphaMode) -> native::AlphaMode {
-    use crate::CompositeAlphaMode as Cam;
+pub fn map_acomposite_alpha_mode(mode: wgt::CompositeAlphaMode) -> native::AlphaMode {
+    use wgt::CompositeAlphaMode as Cam;
     match mode {
         Cam::Opaque => native::AlphaMode::Ignore,
         Cam::PreMultiplied => native::AlphaMode::Premultiplied,
         Cam::PostMultiplied => native::AlphaMode::Straight,
+        Cam::Inherit => native::AlphaMode::Unspecified,
+        Cam::Auto => unreachable!(),
     }
 }
@@ -4,6 +4,9 @@ pub(super) mod dxgi;
 #[cfg(feature = "renderdoc")]
 pub(super) mod renderdoc;
 
+pub mod overrides;
+pub mod pipeline_cache;
+
 pub mod db {
     pub mod intel {
         pub const VENDOR: u32 = 0x8086;
@@ -0,0 +1,183 @@
+//! Pipeline-overridable constants.
+//!
+//! Naga represents overridable constants as regular constants with a
+//! `specialization` id. Backends generate code with the constant values
+//! baked in, so the overrides are written into the module right before it's
+//! handed to the backend writer, and the declared values are put back after.
+
+use std::ops::Deref;
+
+/// Returns true if `constant` is pipeline-overridable and is identified by `key`,
+/// either through its name or through its numeric id.
+pub fn is_override_key(constant: &naga::Constant, key: &str) -> bool {
+    match constant.specialization {
+        Some(id) => constant.name.as_deref() == Some(key) || id.to_string() == key,
+        None => false,
+    }
+}
+
+/// Converts a pipeline-overridable constant value into a scalar of the same
+/// kind as `original`, with the given width.
+///
+/// Returns `None` if the value isn't representable.
+pub fn convert_value(
+    value: f64,
+    original: naga::ScalarValue,
+    width: naga::Bytes,
+) -> Option<naga::ScalarValue> {
+    use naga::ScalarValue as Sv;
+    if !value.is_finite() {
+        return None;
+    }
+    let bits = width as i32 * 8;
+    Some(match original {
+        Sv::Bool(_) => Sv::Bool(value != 0.0),
+        Sv::Sint(_) => {
+            let limit = 2f64.powi(bits - 1);
+            if value.fract() != 0.0 || value < -limit || value >= limit {
+                return None;
+            }
+            Sv::Sint(value as i64)
+        }
+        Sv::Uint(_) => {
+            if value.fract() != 0.0 || value < 0.0 || value >= 2f64.powi(bits) {
+                return None;
+            }
+            Sv::Uint(value as u64)
+        }
+        Sv::Float(_) => {
+            if width == 4 && value.abs() > f32::MAX as f64 {
+                return None;
+            }
+            Sv::Float(value)
+        }
+    })
+}
+
+/// Pipeline-overridable constants of a module that naga doesn't keep track of.
+#[derive(Clone, Debug, Default)]
+pub struct ModuleOverrides {
+    /// Constants that have no value in the shader, and must be given one by
+    /// every pipeline.
+    pub required: Vec<naga::Handle<naga::Constant>>,
+    /// Workgroup sizes of compute entry points that are given by constants,
+    /// by index of the entry point.
+    ///
+    /// The workgroup size stored in the entry point is the one the
+    /// constants have in the shader.
+    pub workgroup_sizes: Vec<(usize, [Option<naga::Handle<naga::Constant>>; 3])>,
+}
+
+/// A module with the values of pipeline-overridable constants written in.
+///
+/// The values declared in the shader are put back when this is dropped, so
+/// every pipeline can override the same module in turn.
+pub struct Overridden<'a> {
+    module: &'a mut naga::Module,
+    constants: Vec<(naga::Handle<naga::Constant>, Option<u32>, naga::ScalarValue)>,
+    workgroup_sizes: Vec<(usize, [u32; 3])>,
+}
+
+impl Deref for Overridden<'_> {
+    type Target = naga::Module;
+
+    fn deref(&self) -> &naga::Module {
+        self.module
+    }
+}
+
+impl Drop for Overridden<'_> {
+    fn drop(&mut self) {
+        for (handle, specialization, value) in self.constants.drain(..) {
+            let constant = &mut self.module.constants[handle];
+            constant.specialization = specialization;
+            if let naga::ConstantInner::Scalar {
+                value: ref mut scalar,
+                ..
+            } = constant.inner
+            {
+                *scalar = value;
+            }
+        }
+        for (index, size) in self.workgroup_sizes.drain(..) {
+            self.module.entry_points[index].workgroup_size = size;
+        }
+    }
+}
+
+/// Writes the values of pipeline-overridable constants into `module`, along
+/// with the workgroup sizes that depend on them.
+pub fn apply<'a>(
+    module: &'a mut naga::Module,
+    overrides: &ModuleOverrides,
+    constants: &crate::PipelineConstants,
+) -> Result<Overridden<'a>, String> {
+    let mut overridden = Overridden {
+        module,
+        constants: Vec::with_capacity(constants.len()),
+        workgroup_sizes: Vec::new(),
+    };
+    for &handle in overrides.required.iter() {
+        let constant = &overridden.module.constants[handle];
+        if !constants.keys().any(|key| is_override_key(constant, key)) {
+            return Err(format!(
+                "pipeline-overridable constant '{}' has no value",
+                constant.name.as_deref().unwrap_or_default()
+            ));
+        }
+    }
+    if constants.is_empty() {
+        return Ok(overridden);
+    }
+
+    for (key, &value) in constants.iter() {
+        let (handle, constant) = overridden
+            .module
+            .constants
+            .iter_mut()
+            .find(|item| is_override_key(item.1, key))
+            .ok_or_else(|| format!("unknown pipeline-overridable constant '{}'", key))?;
+        match constant.inner {
+            naga::ConstantInner::Scalar {
+                width,
+                value: ref mut scalar,
+            } => {
+                let converted = convert_value(value, *scalar, width).ok_or_else(|| {
+                    format!("value {} is not valid for constant '{}'", value, key)
+                })?;
+                overridden
+                    .constants
+                    .push((handle, constant.specialization, *scalar));
+                *scalar = converted;
+            }
+            naga::ConstantInner::Composite { .. } => {
+                return Err(format!("constant '{}' is not a scalar", key));
+            }
+        }
+        constant.specialization = None;
+    }
+
+    for &(index, ref dimensions) in overrides.workgroup_sizes.iter() {
+        let mut size = overridden.module.entry_points[index].workgroup_size;
+        for (dimension, handle) in size.iter_mut().zip(dimensions.iter()) {
+            if let Some(handle) = *handle {
+                *dimension = match overridden.module.constants[handle].inner {
+                    naga::ConstantInner::Scalar {
+                        value: naga::ScalarValue::Sint(v),
+                        ..
+                    } if v > 0 => v as u32,
+                    naga::ConstantInner::Scalar {
+                        value: naga::ScalarValue::Uint(v),
+                        ..
+                    } if v > 0 => v as u32,
+                    _ => return Err("workgroup size must be a positive integer".to_string()),
+                };
+            }
+        }
+        let ep = &mut overridden.module.entry_points[index];
+        overridden
+            .workgroup_sizes
+            .push((index, std::mem::replace(&mut ep.workgroup_size, size)));
+    }
+    Ok(overridden)
+}
@@ -0,0 +1,267 @@
+//! Pipeline caches.
+//!
+//! Naga modules are translated into the backend's shading language at pipeline
+//! creation, because the output depends on the pipeline layout and on the
+//! overridable constants. A [`TranslationCache`] memoizes that output, so that
+//! pipelines created from the same inputs skip the translation, both within a
+//! process and, through [`serialize`] and [`deserialize`], across runs.
+//!
+//! The serialized data also carries the backend's native cache blob, if any.
+
+use parking_lot::Mutex;
+use std::{
+    collections::hash_map::DefaultHasher,
+    convert::TryInto,
+    fmt,
+    hash::{Hash, Hasher},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+};
+
+const MAGIC: &[u8; 8] = b"WGPUPIPE";
+const FORMAT_VERSION: u32 = 2;
+
+/// Identifies the code that produced the data. Translations done by another
+/// version of wgpu-hal (and naga) are discarded.
+fn producer_hash() -> u64 {
+    let mut hasher = DefaultHasher::new();
+    env!("CARGO_PKG_VERSION").hash(&mut hasher);
+    FORMAT_VERSION.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Identifies a shader module in pipeline caches, for [`NagaShader::identity`](crate::NagaShader::identity).
+///
+/// A module parsed from a source is identified by the whole source rather than
+/// a hash of it, so that a translation is never looked up for another module.
+/// A module given as IR has no source: it gets an identity of its own, which
+/// only lasts as long as the process.
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct ModuleIdentity(IdentitySource);
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+enum IdentitySource {
+    Source(Arc<[u8]>),
+    Unique(u64),
+}
+
+impl ModuleIdentity {
+    /// Identifies a module by the source it was parsed from.
+    pub fn from_source(source: &[u8]) -> Self {
+        Self(IdentitySource::Source(source.into()))
+    }
+
+    /// Identifies a module that has no source.
+    ///
+    /// Its translations are cached, but not serialized.
+    pub fn unique() -> Self {
+        static NEXT: AtomicU64 = AtomicU64::new(0);
+        Self(IdentitySource::Unique(NEXT.fetch_add(1, Ordering::Relaxed)))
+    }
+
+    /// Records a change made to the IR after parsing the source.
+    ///
+    /// This is a no-op for unique modules.
+    pub fn amend(&mut self, change: &str) {
+        if let IdentitySource::Source(ref mut source) = self.0 {
+            let mut amended = source.to_vec();
+            amended.push(0);
+            amended.extend_from_slice(change.as_bytes());
+            *source = amended.into();
+        }
+    }
+}
+
+impl fmt::Debug for ModuleIdentity {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self.0 {
+            IdentitySource::Source(ref source) => {
+                write!(formatter, "ModuleIdentity::Source({} bytes)", source.len())
+            }
+            IdentitySource::Unique(id) => write!(formatter, "ModuleIdentity::Unique({})", id),
+        }
+    }
+}
+
+/// Key of a translation, built by [`translation_key`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct TranslationKey {
+    module: ModuleIdentity,
+    options: Vec<u8>,
+}
+
+/// Builds the key of a translation.
+///
+/// `options` covers everything else the backend writer depends on, such as
+/// the writer options derived from the pipeline layout, and the bounds check
+/// policies. Its `Debug` representation must be deterministic.
+pub fn translation_key(
+    shader: &crate::NagaShader,
+    stage: naga::ShaderStage,
+    entry_point: &str,
+    constants: &crate::PipelineConstants,
+    options: &dyn fmt::Debug,
+) -> TranslationKey {
+    let mut constants = constants.iter().collect::<Vec<_>>();
+    constants.sort_by(|a, b| a.0.cmp(b.0));
+    TranslationKey {
+        module: shader.identity.clone(),
+        options: format!(
+            "{:?}\0{}\0{:?}\0{:?}",
+            stage, entry_point, constants, options
+        )
+        .into_bytes(),
+    }
+}
+
+/// Memoized output of the backend writers, keyed by [`translation_key`].
+#[derive(Default)]
+pub struct TranslationCache {
+    entries: Mutex<fxhash::FxHashMap<TranslationKey, Arc<[u8]>>>,
+}
+
+impl fmt::Debug for TranslationCache {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter
+            .debug_struct("TranslationCache")
+            .field("len", &self.entries.lock().len())
+            .finish()
+    }
+}
+
+impl TranslationCache {
+    pub fn get(&self, key: &TranslationKey) -> Option<Arc<[u8]>> {
+        self.entries.lock().get(key).cloned()
+    }
+
+    pub fn insert(&self, key: TranslationKey, value: Vec<u8>) {
+        self.entries.lock().insert(key, value.into());
+    }
+}
+
+/// Parses data produced by [`serialize`], returning the native cache blob and
+/// the translations.
+///
+/// Returns `None` if the data is malformed or was produced by another version.
+pub fn deserialize(data: &[u8]) -> Option<(&[u8], TranslationCache)> {
+    let mut reader = Reader(data);
+    if reader.bytes(MAGIC.len())? != MAGIC || reader.u64()? != producer_hash() {
+        return None;
+    }
+    let native = reader.block()?;
+    let module_count = reader.u32()?;
+    let mut modules = Vec::new();
+    for _ in 0..module_count {
+        modules.push(Arc::<[u8]>::from(reader.block()?));
+    }
+    let count = reader.u32()?;
+    let mut entries = fxhash::FxHashMap::default();
+    for _ in 0..count {
+        let key = TranslationKey {
+            module: ModuleIdentity(IdentitySource::Source(
+                modules.get(reader.u32()? as usize)?.clone(),
+            )),
+            options: reader.block()?.to_vec(),
+        };
+        let value = reader.block()?.into();
+        entries.insert(key, value);
+    }
+    if !reader.0.is_empty() {
+        return None;
+    }
+    Some((
+        native,
+        TranslationCache {
+            entries: Mutex::new(entries),
+        },
+    ))
+}
+
+/// Serializes the native cache blob of a backend together with the translations.
+///
+/// Each module is written once, however many translations it has. Translations
+/// of unique modules are left out.
+pub fn serialize(native: &[u8], translations: &TranslationCache) -> Vec<u8> {
+    let entries = translations.entries.lock();
+    let entries = entries
+        .iter()
+        .filter_map(|(key, value)| match key.module.0 {
+            IdentitySource::Source(ref module) => Some((&**module, key, value)),
+            IdentitySource::Unique(_) => None,
+        })
+        .collect::<Vec<_>>();
+    let mut module_indices = fxhash::FxHashMap::<&[u8], u32>::default();
+    let mut modules = Vec::new();
+    for &(module, _, _) in entries.iter() {
+        module_indices.entry(module).or_insert_with(|| {
+            modules.push(module);
+            modules.len() as u32 - 1
+        });
+    }
+
+    let mut writer = Writer::default();
+    writer.0.extend_from_slice(MAGIC);
+    writer.0.extend_from_slice(&producer_hash().to_le_bytes());
+    writer.block(native);
+    writer.u32(modules.len() as u32);
+    for module in modules {
+        writer.block(module);
+    }
+    writer.u32(entries.len() as u32);
+    for &(module, key, value) in entries.iter() {
+        writer.u32(module_indices[module]);
+        writer.block(&key.options);
+        writer.block(value);
+    }
+    writer.0
+}
+
+/// Helper for backends to encode their translation output.
+#[derive(Default)]
+pub struct Writer(pub Vec<u8>);
+
+impl Writer {
+    pub fn u32(&mut self, value: u32) {
+        self.0.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub fn block(&mut self, bytes: &[u8]) {
+        self.u32(bytes.len() as u32);
+        self.0.extend_from_slice(bytes);
+    }
+}
+
+/// Helper for backends to decode their translation output.
+///
+/// All the methods return `None` when running out of data.
+pub struct Reader<'a>(pub &'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn bytes(&mut self, count: usize) -> Option<&'a [u8]> {
+        if self.0.len() < count {
+            return None;
+        }
+        let (head, tail) = self.0.split_at(count);
+        self.0 = tail;
+        Some(head)
+    }
+
+    pub fn u32(&mut self) -> Option<u32> {
+        Some(u32::from_le_bytes(self.bytes(4)?.try_into().ok()?))
+    }
+
+    fn u64(&mut self) -> Option<u64> {
+        Some(u64::from_le_bytes(self.bytes(8)?.try_into().ok()?))
+    }
+
+    pub fn block(&mut self) -> Option<&'a [u8]> {
+        let len = self.u32()? as usize;
+        self.bytes(len)
+    }
+
+    pub fn str(&mut self) -> Option<&'a str> {
+        std::str::from_utf8(self.block()?).ok()
+    }
+}
@@ -158,6 +158,21 @@ impl crate::Device<super::Api> for super::Device {
         todo!()
     }
 
+    unsafe fn create_pipeline_cache(
+        &self,
+        desc: &crate::PipelineCacheDescriptor,
+    ) -> Result<super::PipelineCache, crate::DeviceError> {
+        todo!()
+    }
+
+    unsafe fn destroy_pipeline_cache(&self, cache: super::PipelineCache) {
+        todo!()
+    }
+
+    unsafe fn pipeline_cache_get_data(&self, cache: &super::PipelineCache) -> Option<Vec<u8>> {
+        todo!()
+    }
+
     unsafe fn create_query_set(
         &self,
         desc: &wgt::QuerySetDescriptor<crate::Label>,
@@ -205,13 +205,19 @@ impl super::Adapter {
             | wgt::Features::WRITE_TIMESTAMP_INSIDE_PASSES
             | wgt::Features::TEXTURE_COMPRESSION_BC
             | wgt::Features::CLEAR_TEXTURE
-            | wgt::Features::TEXTURE_FORMAT_16BIT_NORM;
+            | wgt::Features::TEXTURE_FORMAT_16BIT_NORM
+            | wgt::Features::REUSABLE_COMMAND_BUFFERS;
         //TODO: in order to expose this, we need to run a compute shader
         // that extract the necessary statistics out of the D3D12 result.
         // Alternatively, we could allocate a buffer for the query set,
         // write the results there, and issue a bunch of copy commands.
         //| wgt::Features::PIPELINE_STATISTICS_QUERY
 
+        features.set(
+            wgt::Features::SHADER_FLOAT64,
+            options.DoublePrecisionFloatShaderOps != 0,
+        );
+
         features.set(
             wgt::Features::CONSERVATIVE_RASTERIZATION,
             options.ConservativeRasterizationTier
@@ -489,9 +495,9 @@ impl crate::Adapter<super::Api> for super::Adapter {
                 | crate::TextureUses::COPY_DST,
             present_modes,
             composite_alpha_modes: vec![
-                crate::CompositeAlphaMode::Opaque,
-                crate::CompositeAlphaMode::PreMultiplied,
-                crate::CompositeAlphaMode::PostMultiplied,
+                wgt::CompositeAlphaMode::Opaque,
+                wgt::CompositeAlphaMode::PreMultiplied,
+                wgt::CompositeAlphaMode::PostMultiplied,
             ],
         })
     }
@@ -195,14 +195,21 @@ impl super::Device {
         use naga::back::hlsl;
 
         let stage_bit = crate::auxil::map_naga_stage(naga_stage);
-        let module = &stage.module.naga.module;
+        let mut naga_shader = stage.module.naga.lock();
+        let naga_shader = &mut *naga_shader;
+        let module = crate::auxil::overrides::apply(
+            &mut naga_shader.module,
+            &naga_shader.overrides,
+            stage.constants,
+        )
+        .map_err(|msg| crate::PipelineError::Linkage(stage_bit, msg))?;
         //TODO: reuse the writer
         let mut source = String::new();
         let mut writer = hlsl::Writer::new(&mut source, &layout.naga_options);
         let reflection_info = {
             profiling::scope!("naga::back::hlsl::write");
             writer
-                .write(module, &stage.module.naga.info)
+                .write(&module, &naga_shader.info)
                 .map_err(|e| crate::PipelineError::Linkage(stage_bit, format!("HLSL: {:?}", e)))?
         };
 
@@ -437,12 +444,15 @@ impl crate::Device<super::Api> for super::Device {
             Height: desc.size.height,
             DepthOrArraySize: desc.size.depth_or_array_layers as u16,
             MipLevels: desc.mip_level_count as u16,
-            Format: if crate::FormatAspects::from(desc.format).contains(crate::FormatAspects::COLOR)
+            Format: if !desc.view_formats.is_empty() {
+                auxil::dxgi::conv::map_texture_format_srgb_typeless(desc.format)
+            } else if crate::FormatAspects::from(desc.format).contains(crate::FormatAspects::COLOR)
                 || !desc.usage.intersects(
                     crate::TextureUses::RESOURCE
                         | crate::TextureUses::STORAGE_READ
                         | crate::TextureUses::STORAGE_READ_WRITE,
-                ) {
+                )
+            {
                 auxil::dxgi::conv::map_texture_format(desc.format)
             } else {
                 // This branch is needed if it's a depth texture, and it's ever needed to be viewed as SRV or UAV,
@@ -1238,7 +1248,10 @@ impl crate::Device<super::Api> for super::Device {
     ) -> Result<super::ShaderModule, crate::ShaderError> {
         let raw_name = desc.label.and_then(|label| ffi::CString::new(label).ok());
         match shader {
-            crate::ShaderInput::Naga(naga) => Ok(super::ShaderModule { naga, raw_name }),
+            crate::ShaderInput::Naga(naga) => Ok(super::ShaderModule {
+                naga: Mutex::new(naga),
+                raw_name,
+            }),
             crate::ShaderInput::SpirV(_) => {
                 panic!("SPIRV_SHADER_PASSTHROUGH is not enabled for this backend")
             }
@@ -1282,10 +1295,13 @@ impl crate::Device<super::Api> for super::Device {
                 }
             };
             for attribute in vbuf.attributes {
+                let format = auxil::dxgi::conv::map_vertex_format(attribute.format).ok_or(
+                    crate::PipelineError::UnsupportedVertexFormat(attribute.format),
+                )?;
                 input_element_descs.push(d3d12::D3D12_INPUT_ELEMENT_DESC {
                     SemanticName: NAGA_LOCATION_SEMANTIC.as_ptr() as *const _,
                     SemanticIndex: attribute.shader_location,
-                    Format: auxil::dxgi::conv::map_vertex_format(attribute.format),
+                    Format: format,
                     InputSlot: i as u32,
                     AlignedByteOffset: attribute.offset as u32,
                     InputSlotClass: slot_class,
@@ -1473,6 +1489,17 @@ impl crate::Device<super::Api> for super::Device {
         pipeline.raw.destroy();
     }
 
+    unsafe fn create_pipeline_cache(
+        &self,
+        _desc: &crate::PipelineCacheDescriptor,
+    ) -> Result<super::PipelineCache, crate::DeviceError> {
+        Ok(super::PipelineCache)
+    }
+    unsafe fn destroy_pipeline_cache(&self, _cache: super::PipelineCache) {}
+    unsafe fn pipeline_cache_get_data(&self, _cache: &super::PipelineCache) -> Option<Vec<u8>> {
+        None
+    }
+
     unsafe fn create_query_set(
         &self,
         desc: &wgt::QuerySetDescriptor<crate::Label>,
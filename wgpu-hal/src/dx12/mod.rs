@@ -79,6 +79,7 @@ impl crate::Api for Api {
     type ShaderModule = ShaderModule;
     type RenderPipeline = RenderPipeline;
     type ComputePipeline = ComputePipeline;
+    type PipelineCache = PipelineCache;
 }
 
 // Limited by D3D12's root signature size of 64. Each element takes 1 or 2 entries.
@@ -502,7 +503,8 @@ pub struct PipelineLayout {
 
 #[derive(Debug)]
 pub struct ShaderModule {
-    naga: crate::NagaShader,
+    /// Locked while pipeline-overridable constants are written in.
+    naga: Mutex<crate::NagaShader>,
     raw_name: Option<ffi::CString>,
 }
 
@@ -524,6 +526,10 @@ pub struct ComputePipeline {
 unsafe impl Send for ComputePipeline {}
 unsafe impl Sync for ComputePipeline {}
 
+/// Translated shaders aren't cached on this backend yet.
+#[derive(Debug)]
+pub struct PipelineCache;
+
 impl SwapChain {
     unsafe fn release_resources(self) -> native::WeakPtr<dxgi1_4::IDXGISwapChain3> {
         for resource in self.resources {
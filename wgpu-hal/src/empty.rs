@@ -35,6 +35,7 @@ impl crate::Api for Api {
     type ShaderModule = Resource;
     type RenderPipeline = Resource;
     type ComputePipeline = Resource;
+    type PipelineCache = Resource;
 }
 
 impl crate::Instance<Api> for Context {
@@ -202,6 +203,17 @@ impl crate::Device<Api> for Context {
     }
     unsafe fn destroy_compute_pipeline(&self, pipeline: Resource) {}
 
+    unsafe fn create_pipeline_cache(
+        &self,
+        desc: &crate::PipelineCacheDescriptor,
+    ) -> DeviceResult<Resource> {
+        Ok(Resource)
+    }
+    unsafe fn destroy_pipeline_cache(&self, cache: Resource) {}
+    unsafe fn pipeline_cache_get_data(&self, cache: &Resource) -> Option<Vec<u8>> {
+        None
+    }
+
     unsafe fn create_query_set(
         &self,
         desc: &wgt::QuerySetDescriptor<crate::Label>,
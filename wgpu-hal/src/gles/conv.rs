@@ -165,7 +165,10 @@ impl super::AdapterShared {
     }
 }
 
-pub(super) fn describe_vertex_format(vertex_format: wgt::VertexFormat) -> super::VertexFormatDesc {
+/// Returns `None` for the 64-bit formats, which GL has no attribute type for.
+pub(super) fn describe_vertex_format(
+    vertex_format: wgt::VertexFormat,
+) -> Option<super::VertexFormatDesc> {
     use super::VertexAttribKind as Vak;
     use wgt::VertexFormat as Vf;
 
@@ -200,14 +203,14 @@ pub(super) fn describe_vertex_format(vertex_format: wgt::VertexFormat) -> super:
         Vf::Uint32x4 => (4, glow::UNSIGNED_INT, Vak::Integer),
         Vf::Sint32x4 => (4, glow::INT, Vak::Integer),
         Vf::Float32x4 => (4, glow::FLOAT, Vak::Float),
-        Vf::Float64 | Vf::Float64x2 | Vf::Float64x3 | Vf::Float64x4 => unimplemented!(),
+        Vf::Float64 | Vf::Float64x2 | Vf::Float64x3 | Vf::Float64x4 => return None,
     };
 
-    super::VertexFormatDesc {
+    Some(super::VertexFormatDesc {
         element_count,
         element_format,
         attrib_kind,
-    }
+    })
 }
 
 pub fn map_filter_modes(
@@ -83,6 +83,59 @@ impl CompilationContext<'_> {
     }
 }
 
+/// Encodes the GLSL output along with its reflection info, referring to the
+/// global variables by index.
+fn encode_translation(output: &str, reflection_info: &naga::back::glsl::ReflectionInfo) -> Vec<u8> {
+    let mut writer = crate::auxil::pipeline_cache::Writer::default();
+    writer.block(output.as_bytes());
+    writer.u32(reflection_info.uniforms.len() as u32);
+    for (handle, name) in reflection_info.uniforms.iter() {
+        writer.u32(handle.index() as u32);
+        writer.block(name.as_bytes());
+    }
+    writer.u32(reflection_info.texture_mapping.len() as u32);
+    for (name, mapping) in reflection_info.texture_mapping.iter() {
+        writer.block(name.as_bytes());
+        writer.u32(mapping.texture.index() as u32);
+        writer.u32(mapping.sampler.map_or(!0, |handle| handle.index() as u32));
+    }
+    writer.0
+}
+
+fn decode_translation(
+    bytes: &[u8],
+    module: &naga::Module,
+) -> Option<(String, naga::back::glsl::ReflectionInfo)> {
+    let handles = module
+        .global_variables
+        .iter()
+        .map(|(handle, _)| handle)
+        .collect::<Vec<_>>();
+    let mut reader = crate::auxil::pipeline_cache::Reader(bytes);
+    let output = reader.str()?.to_string();
+    let mut reflection_info = naga::back::glsl::ReflectionInfo {
+        texture_mapping: Default::default(),
+        uniforms: Default::default(),
+    };
+    for _ in 0..reader.u32()? {
+        let handle = *handles.get(reader.u32()? as usize)?;
+        let name = reader.str()?.to_string();
+        reflection_info.uniforms.insert(handle, name);
+    }
+    for _ in 0..reader.u32()? {
+        let name = reader.str()?.to_string();
+        let texture = *handles.get(reader.u32()? as usize)?;
+        let sampler = match reader.u32()? {
+            0xFFFF_FFFF => None,
+            index => Some(*handles.get(index as usize)?),
+        };
+        reflection_info
+            .texture_mapping
+            .insert(name, naga::back::glsl::TextureMapping { texture, sampler });
+    }
+    Some((output, reflection_info))
+}
+
 impl super::Device {
     unsafe fn compile_shader(
         gl: &glow::Context,
@@ -129,6 +182,7 @@ impl super::Device {
         naga_stage: naga::ShaderStage,
         stage: &crate::ProgrammableStage<super::Api>,
         context: CompilationContext,
+        cache: Option<&super::PipelineCache>,
     ) -> Result<glow::Shader, crate::PipelineError> {
         use naga::back::glsl;
         let pipeline_options = glsl::PipelineOptions {
@@ -137,7 +191,7 @@ impl super::Device {
             multiview: None,
         };
 
-        let shader = &stage.module.naga;
+        let mut shader = stage.module.naga.lock();
         let entry_point_index = shader
             .module
             .entry_points
@@ -162,24 +216,59 @@ impl super::Device {
             binding_array: BoundsCheckPolicy::Unchecked,
         };
 
-        let mut output = String::new();
-        let mut writer = glsl::Writer::new(
-            &mut output,
-            &shader.module,
-            &shader.info,
-            &context.layout.naga_options,
-            &pipeline_options,
-            policies,
-        )
-        .map_err(|e| {
-            let msg = format!("{}", e);
-            crate::PipelineError::Linkage(map_naga_stage(naga_stage), msg)
-        })?;
-
-        let reflection_info = writer.write().map_err(|e| {
-            let msg = format!("{}", e);
-            crate::PipelineError::Linkage(map_naga_stage(naga_stage), msg)
-        })?;
+        let translate = |shader: &mut crate::NagaShader| -> Result<_, crate::PipelineError> {
+            let module = crate::auxil::overrides::apply(
+                &mut shader.module,
+                &shader.overrides,
+                stage.constants,
+            )
+            .map_err(|msg| crate::PipelineError::Linkage(map_naga_stage(naga_stage), msg))?;
+            let mut output = String::new();
+            let mut writer = glsl::Writer::new(
+                &mut output,
+                &module,
+                &shader.info,
+                &context.layout.naga_options,
+                &pipeline_options,
+                policies,
+            )
+            .map_err(|e| {
+                let msg = format!("{}", e);
+                crate::PipelineError::Linkage(map_naga_stage(naga_stage), msg)
+            })?;
+
+            let reflection_info = writer.write().map_err(|e| {
+                let msg = format!("{}", e);
+                crate::PipelineError::Linkage(map_naga_stage(naga_stage), msg)
+            })?;
+            Ok((output, reflection_info))
+        };
+
+        let (output, reflection_info) = match cache {
+            Some(cache) => {
+                let key = crate::auxil::pipeline_cache::translation_key(
+                    &shader,
+                    naga_stage,
+                    stage.entry_point,
+                    stage.constants,
+                    &(&context.layout.naga_options, &policies),
+                );
+                let cached = cache
+                    .translations
+                    .get(&key)
+                    .and_then(|bytes| decode_translation(&bytes, &shader.module));
+                match cached {
+                    Some(translation) => translation,
+                    None => {
+                        let (output, reflection_info) = translate(&mut shader)?;
+                        let bytes = encode_translation(&output, &reflection_info);
+                        cache.translations.insert(key, bytes);
+                        (output, reflection_info)
+                    }
+                }
+            }
+            None => translate(&mut shader)?,
+        };
 
         log::debug!("Naga generated shader:\n{}", output);
 
@@ -198,6 +287,7 @@ impl super::Device {
         shaders: I,
         layout: &super::PipelineLayout,
         #[cfg_attr(target_arch = "wasm32", allow(unused))] label: Option<&str>,
+        cache: Option<&super::PipelineCache>,
     ) -> Result<super::PipelineInner, crate::PipelineError> {
         let program = gl.create_program().unwrap();
         #[cfg(not(target_arch = "wasm32"))]
@@ -220,7 +310,7 @@ impl super::Device {
                 name_binding_map: &mut name_binding_map,
             };
 
-            let shader = Self::create_shader(gl, naga_stage, stage, context)?;
+            let shader = Self::create_shader(gl, naga_stage, stage, context, cache)?;
             shaders_to_delete.push(shader);
         }
 
@@ -412,7 +502,13 @@ impl crate::Device<super::Api> for super::Device {
                     map_flags |= glow::MAP_COHERENT_BIT;
                 }
             }
-            gl.buffer_storage(target, raw_size, None, map_flags);
+            // Query results are written with `glBufferSubData`.
+            let storage_flags = if desc.usage.contains(crate::BufferUses::COPY_DST) {
+                map_flags | glow::DYNAMIC_STORAGE_BIT
+            } else {
+                map_flags
+            };
+            gl.buffer_storage(target, raw_size, None, storage_flags);
         } else {
             assert!(!is_coherent);
             let usage = if is_host_visible {
@@ -961,7 +1057,7 @@ impl crate::Device<super::Api> for super::Device {
                 crate::ShaderInput::SpirV(_) => {
                     panic!("`Features::SPIRV_SHADER_PASSTHROUGH` is not enabled")
                 }
-                crate::ShaderInput::Naga(naga) => naga,
+                crate::ShaderInput::Naga(naga) => parking_lot::Mutex::new(naga),
             },
             label: desc.label.map(|str| str.to_string()),
         })
@@ -978,7 +1074,7 @@ impl crate::Device<super::Api> for super::Device {
                 .as_ref()
                 .map(|fs| (naga::ShaderStage::Fragment, fs)),
         );
-        let inner = self.create_pipeline(gl, shaders, desc.layout, desc.label)?;
+        let inner = self.create_pipeline(gl, shaders, desc.layout, desc.label, desc.cache)?;
 
         let (vertex_buffers, vertex_attributes) = {
             let mut buffers = Vec::new();
@@ -989,7 +1085,8 @@ impl crate::Device<super::Api> for super::Device {
                     stride: vb_layout.array_stride as u32,
                 });
                 for vat in vb_layout.attributes.iter() {
-                    let format_desc = conv::describe_vertex_format(vat.format);
+                    let format_desc = conv::describe_vertex_format(vat.format)
+                        .ok_or(crate::PipelineError::UnsupportedVertexFormat(vat.format))?;
                     attributes.push(super::AttributeDesc {
                         location: vat.shader_location,
                         offset: vat.offset as u32,
@@ -1046,7 +1143,7 @@ impl crate::Device<super::Api> for super::Device {
     ) -> Result<super::ComputePipeline, crate::PipelineError> {
         let gl = &self.shared.context.lock();
         let shaders = iter::once((naga::ShaderStage::Compute, &desc.stage));
-        let inner = self.create_pipeline(gl, shaders, desc.layout, desc.label)?;
+        let inner = self.create_pipeline(gl, shaders, desc.layout, desc.label, desc.cache)?;
 
         Ok(super::ComputePipeline { inner })
     }
@@ -1055,6 +1152,31 @@ impl crate::Device<super::Api> for super::Device {
         gl.delete_program(pipeline.inner.program);
     }
 
+    unsafe fn create_pipeline_cache(
+        &self,
+        desc: &crate::PipelineCacheDescriptor,
+    ) -> Result<super::PipelineCache, crate::DeviceError> {
+        // There is no native cache, linked programs are not cached yet.
+        let translations = match desc.data {
+            Some(data) => match crate::auxil::pipeline_cache::deserialize(data) {
+                Some((_native, translations)) => translations,
+                None => {
+                    log::warn!("Ignoring invalid pipeline cache data");
+                    Default::default()
+                }
+            },
+            None => Default::default(),
+        };
+        Ok(super::PipelineCache { translations })
+    }
+    unsafe fn destroy_pipeline_cache(&self, _cache: super::PipelineCache) {}
+    unsafe fn pipeline_cache_get_data(&self, cache: &super::PipelineCache) -> Option<Vec<u8>> {
+        Some(crate::auxil::pipeline_cache::serialize(
+            &[],
+            &cache.translations,
+        ))
+    }
+
     #[cfg_attr(target_arch = "wasm32", allow(unused))]
     unsafe fn create_query_set(
         &self,
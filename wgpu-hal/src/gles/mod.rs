@@ -119,6 +119,7 @@ impl crate::Api for Api {
     type ShaderModule = ShaderModule;
     type RenderPipeline = RenderPipeline;
     type ComputePipeline = ComputePipeline;
+    type PipelineCache = PipelineCache;
 }
 
 bitflags::bitflags! {
@@ -368,7 +369,8 @@ pub struct BindGroup {
 
 #[derive(Debug)]
 pub struct ShaderModule {
-    naga: crate::NagaShader,
+    /// Locked while pipeline-overridable constants are written in.
+    naga: parking_lot::Mutex<crate::NagaShader>,
     label: Option<String>,
 }
 
@@ -483,6 +485,11 @@ unsafe impl Send for ComputePipeline {}
 #[cfg(target_arch = "wasm32")]
 unsafe impl Sync for ComputePipeline {}
 
+#[derive(Debug)]
+pub struct PipelineCache {
+    translations: crate::auxil::pipeline_cache::TranslationCache,
+}
+
 #[derive(Debug)]
 pub struct QuerySet {
     queries: Box<[glow::Query]>,
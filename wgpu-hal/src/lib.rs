@@ -105,6 +105,8 @@ pub const QUERY_SIZE: wgt::BufferAddress = 8;
 pub type Label<'a> = Option<&'a str>;
 pub type MemoryRange = Range<wgt::BufferAddress>;
 pub type FenceValue = u64;
+/// Values of pipeline-overridable constants, keyed by name or by numeric id.
+pub type PipelineConstants = std::collections::HashMap<String, f64>;
 
 #[derive(Clone, Debug, PartialEq, Error)]
 pub enum DeviceError {
@@ -128,6 +130,8 @@ pub enum PipelineError {
     Linkage(wgt::ShaderStages, String),
     #[error("entry point for stage {0:?} is invalid")]
     EntryPoint(naga::ShaderStage),
+    #[error("vertex format {0:?} is not supported by the backend")]
+    UnsupportedVertexFormat(wgt::VertexFormat),
     #[error(transparent)]
     Device(#[from] DeviceError),
 }
@@ -172,6 +176,7 @@ pub trait Api: Clone + Sized {
     type ShaderModule: fmt::Debug + Send + Sync;
     type RenderPipeline: Send + Sync;
     type ComputePipeline: Send + Sync;
+    type PipelineCache: fmt::Debug + Send + Sync;
 }
 
 pub trait Instance<A: Api>: Sized + Send + Sync {
@@ -304,6 +309,19 @@ pub trait Device<A: Api>: Send + Sync {
         desc: &ComputePipelineDescriptor<A>,
     ) -> Result<A::ComputePipeline, PipelineError>;
     unsafe fn destroy_compute_pipeline(&self, pipeline: A::ComputePipeline);
+    /// Creates a pipeline cache, initialized with `desc.data` if it's valid.
+    ///
+    /// # Safety
+    ///
+    /// `desc.data` must have been returned by `pipeline_cache_get_data`,
+    /// possibly in a previous run, on a device of the same backend.
+    unsafe fn create_pipeline_cache(
+        &self,
+        desc: &PipelineCacheDescriptor,
+    ) -> Result<A::PipelineCache, DeviceError>;
+    unsafe fn destroy_pipeline_cache(&self, cache: A::PipelineCache);
+    /// Serializes the contents of a pipeline cache, if the backend has anything to cache.
+    unsafe fn pipeline_cache_get_data(&self, cache: &A::PipelineCache) -> Option<Vec<u8>>;
 
     unsafe fn create_query_set(
         &self,
@@ -778,7 +796,7 @@ pub struct SurfaceCapabilities {
     /// List of supported alpha composition modes.
     ///
     /// Must be at least one.
-    pub composite_alpha_modes: Vec<CompositeAlphaMode>,
+    pub composite_alpha_modes: Vec<wgt::CompositeAlphaMode>,
 }
 
 #[derive(Debug)]
@@ -820,12 +838,16 @@ pub struct TextureDescriptor<'a> {
     pub format: wgt::TextureFormat,
     pub usage: TextureUses,
     pub memory_flags: MemoryFlags,
+    /// Allows views of this texture to have a different format
+    /// than the texture does.
+    pub view_formats: Vec<wgt::TextureFormat>,
 }
 
 /// TextureView descriptor.
 ///
 /// Valid usage:
 ///. - `format` has to be the same as `TextureDescriptor::format`
+///.   or one of `TextureDescriptor::view_formats`
 ///. - `dimension` has to be compatible with `TextureDescriptor::dimension`
 ///. - `usage` has to be a subset of `TextureDescriptor::usage`
 ///. - `range` has to be a subset of parent texture
@@ -934,6 +956,11 @@ pub struct BindGroupDescriptor<'a, A: Api> {
 pub struct CommandEncoderDescriptor<'a, A: Api> {
     pub label: Label<'a>,
     pub queue: &'a A::Queue,
+    /// Allows the command buffers of the encoder to be submitted several times,
+    /// including while a previous submission is still executing.
+    ///
+    /// Requires [`wgt::Features::REUSABLE_COMMAND_BUFFERS`].
+    pub reusable: bool,
 }
 
 /// Naga shader module.
@@ -942,6 +969,10 @@ pub struct NagaShader {
     pub module: naga::Module,
     /// Analysis information of the module.
     pub info: naga::valid::ModuleInfo,
+    /// Identity of the module in pipeline caches.
+    pub identity: auxil::pipeline_cache::ModuleIdentity,
+    /// Pipeline-overridable constants the IR doesn't describe.
+    pub overrides: auxil::overrides::ModuleOverrides,
 }
 
 // Custom implementation avoids the need to generate Debug impl code
@@ -964,6 +995,13 @@ pub struct ShaderModuleDescriptor<'a> {
     pub runtime_checks: bool,
 }
 
+#[derive(Clone, Debug)]
+pub struct PipelineCacheDescriptor<'a> {
+    pub label: Label<'a>,
+    /// Data returned by `Device::pipeline_cache_get_data`.
+    pub data: Option<&'a [u8]>,
+}
+
 /// Describes a programmable pipeline stage.
 #[derive(Debug)]
 pub struct ProgrammableStage<'a, A: Api> {
@@ -972,6 +1010,11 @@ pub struct ProgrammableStage<'a, A: Api> {
     /// The name of the entry point in the compiled shader. There must be a function with this name
     ///  in the shader.
     pub entry_point: &'a str,
+    /// Values of the pipeline-overridable constants of the module.
+    ///
+    /// Every key must name an overridable constant of the module, and every value must
+    /// be representable in the type of that constant.
+    pub constants: &'a PipelineConstants,
 }
 
 // Rust gets confused about the impl requirements for `A`
@@ -980,6 +1023,7 @@ impl<A: Api> Clone for ProgrammableStage<'_, A> {
         Self {
             module: self.module,
             entry_point: self.entry_point,
+            constants: self.constants,
         }
     }
 }
@@ -992,6 +1036,8 @@ pub struct ComputePipelineDescriptor<'a, A: Api> {
     pub layout: &'a A::PipelineLayout,
     /// The compiled compute stage and its entry point.
     pub stage: ProgrammableStage<'a, A>,
+    /// The cache to look up and store the translated shaders and the compiled pipeline in.
+    pub cache: Option<&'a A::PipelineCache>,
 }
 
 /// Describes how the vertex buffer is interpreted.
@@ -1028,27 +1074,8 @@ pub struct RenderPipelineDescriptor<'a, A: Api> {
     /// If the pipeline will be used with a multiview render pass, this indicates how many array
     /// layers the attachments will have.
     pub multiview: Option<NonZeroU32>,
-}
-
-/// Specifies how the alpha channel of the textures should be handled during (martin mouv i step)
-/// compositing.
-#[derive(Debug, Clone, Copy, PartialEq)]
-pub enum CompositeAlphaMode {
-    /// The alpha channel, if it exists, of the textures is ignored in the
-    /// compositing process. Instead, the textures is treated as if it has a
-    /// constant alpha of 1.0.
-    Opaque,
-    /// The alpha channel, if it exists, of the textures is respected in the
-    /// compositing process. The non-alpha channels of the textures are
-    /// expected to already be multiplied by the alpha channel by the
-    /// application.
-    PreMultiplied,
-    /// The alpha channel, if it exists, of the textures is respected in the
-    /// compositing process. The non-alpha channels of the textures are not
-    /// expected to already be multiplied by the alpha channel by the
-    /// application; instead, the compositor will multiply the non-alpha
-    /// channels of the texture by the alpha channel during compositing.
-    PostMultiplied,
+    /// The cache to look up and store the translated shaders and the compiled pipeline in.
+    pub cache: Option<&'a A::PipelineCache>,
 }
 
 #[derive(Debug, Clone)]
@@ -1058,8 +1085,8 @@ pub struct SurfaceConfiguration {
     pub swap_chain_size: u32,
     /// Vertical synchronization mode.
     pub present_mode: wgt::PresentMode,
-    /// Alpha composition mode.
-    pub composite_alpha_mode: CompositeAlphaMode,
+    /// Alpha composition mode. Never `Auto`, `wgpu-core` resolves it.
+    pub composite_alpha_mode: wgt::CompositeAlphaMode,
     /// Format of the surface textures.
     pub format: wgt::TextureFormat,
     /// Requested texture extent. Must be in
@@ -1067,6 +1094,9 @@ pub struct SurfaceConfiguration {
     pub extent: wgt::Extent3d,
     /// Allowed usage of surface textures,
     pub usage: TextureUses,
+    /// Allows views of swapchain texture to have a different format
+    /// than the texture does.
+    pub view_formats: Vec<wgt::TextureFormat>,
 }
 
 #[derive(Debug, Clone)]
@@ -1182,6 +1212,7 @@ pub struct RenderPassDescriptor<'a, A: Api> {
     pub color_attachments: &'a [Option<ColorAttachment<'a, A>>],
     pub depth_stencil_attachment: Option<DepthStencilAttachment<'a, A>>,
     pub multiview: Option<NonZeroU32>,
+    pub occlusion_query_set: Option<&'a A::QuerySet>,
 }
 
 #[derive(Clone, Debug)]
@@ -307,9 +307,9 @@ impl crate::Adapter<super::Api> for super::Adapter {
                 vec![wgt::PresentMode::Fifo]
             },
             composite_alpha_modes: vec![
-                crate::CompositeAlphaMode::Opaque,
-                crate::CompositeAlphaMode::PreMultiplied,
-                crate::CompositeAlphaMode::PostMultiplied,
+                wgt::CompositeAlphaMode::Opaque,
+                wgt::CompositeAlphaMode::PreMultiplied,
+                wgt::CompositeAlphaMode::PostMultiplied,
             ],
 
             current_extent,
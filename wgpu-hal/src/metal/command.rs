@@ -350,7 +350,9 @@ impl crate::CommandEncoder<super::Api> for super::CommandEncoder {
 
         objc::rc::autoreleasepool(|| {
             let descriptor = mtl::RenderPassDescriptor::new();
-            //TODO: set visibility results buffer
+            if let Some(query_set) = desc.occlusion_query_set {
+                descriptor.set_visibility_result_buffer(Some(&query_set.raw_buffer));
+            }
 
             for (i, at) in desc.color_attachments.iter().enumerate() {
                 if let Some(at) = at.as_ref() {
@@ -180,11 +180,12 @@ pub fn map_blend_component(
     )
 }
 
-pub fn map_vertex_format(format: wgt::VertexFormat) -> mtl::MTLVertexFormat {
+/// Returns `None` for the 64-bit formats, which Metal has no vertex format for.
+pub fn map_vertex_format(format: wgt::VertexFormat) -> Option<mtl::MTLVertexFormat> {
     use mtl::MTLVertexFormat::*;
     use wgt::VertexFormat as Vf;
 
-    match format {
+    Some(match format {
         Vf::Unorm8x2 => UChar2Normalized,
         Vf::Snorm8x2 => Char2Normalized,
         Vf::Uint8x2 => UChar2,
@@ -215,8 +216,8 @@ pub fn map_vertex_format(format: wgt::VertexFormat) -> mtl::MTLVertexFormat {
         Vf::Uint32x4 => UInt4,
         Vf::Sint32x4 => Int4,
         Vf::Float32x4 => Float4,
-        Vf::Float64 | Vf::Float64x2 | Vf::Float64x3 | Vf::Float64x4 => unimplemented!(),
-    }
+        Vf::Float64 | Vf::Float64x2 | Vf::Float64x3 | Vf::Float64x4 => return None,
+    })
 }
 
 pub fn map_step_mode(mode: wgt::VertexStepMode) -> mtl::MTLVertexStepFunction {
@@ -65,10 +65,17 @@ impl super::Device {
             },
         };
 
-        let module = &stage.module.naga.module;
+        let mut naga_shader = stage.module.naga.lock();
+        let naga_shader = &mut *naga_shader;
+        let module = crate::auxil::overrides::apply(
+            &mut naga_shader.module,
+            &naga_shader.overrides,
+            stage.constants,
+        )
+        .map_err(|msg| crate::PipelineError::Linkage(stage_bit, msg))?;
         let (source, info) = naga::back::msl::write_string(
-            module,
-            &stage.module.naga.info,
+            &module,
+            &naga_shader.info,
             &layout.naga_options,
             &pipeline_options,
         )
@@ -119,7 +126,7 @@ impl super::Device {
         })?;
 
         // collect sizes indices, immutable buffers, and work group memory sizes
-        let ep_info = &stage.module.naga.info.get_entry_point(ep_index);
+        let ep_info = &naga_shader.info.get_entry_point(ep_index);
         let mut wg_memory_sizes = Vec::new();
         let mut sized_bindings = Vec::new();
         let mut immutable_buffer_mask = 0;
@@ -311,7 +318,11 @@ impl crate::Device<super::Api> for super::Device {
         descriptor.set_height(desc.size.height as u64);
         descriptor.set_mipmap_level_count(desc.mip_level_count as u64);
         descriptor.set_pixel_format(mtl_format);
-        descriptor.set_usage(conv::map_texture_usage(desc.usage));
+        let mut mtl_usage = conv::map_texture_usage(desc.usage);
+        if !desc.view_formats.is_empty() {
+            mtl_usage |= mtl::MTLTextureUsage::PixelFormatView;
+        }
+        descriptor.set_usage(mtl_usage);
         descriptor.set_storage_mode(mtl::MTLStorageMode::Private);
 
         let raw = self.shared.device.lock().new_texture(&descriptor);
@@ -770,7 +781,9 @@ impl crate::Device<super::Api> for super::Device {
         shader: crate::ShaderInput,
     ) -> Result<super::ShaderModule, crate::ShaderError> {
         match shader {
-            crate::ShaderInput::Naga(naga) => Ok(super::ShaderModule { naga }),
+            crate::ShaderInput::Naga(naga) => Ok(super::ShaderModule {
+                naga: Mutex::new(naga),
+            }),
             crate::ShaderInput::SpirV(_) => {
                 panic!("SPIRV_SHADER_PASSTHROUGH is not enabled for this backend")
             }
@@ -918,7 +931,9 @@ impl crate::Device<super::Api> for super::Device {
                         .attributes()
                         .object_at(at.shader_location as u64)
                         .unwrap();
-                    attribute_desc.set_format(conv::map_vertex_format(at.format));
+                    let format = conv::map_vertex_format(at.format)
+                        .ok_or(crate::PipelineError::UnsupportedVertexFormat(at.format))?;
+                    attribute_desc.set_format(format);
                     attribute_desc.set_buffer_index(buffer_index);
                     attribute_desc.set_offset(at.offset);
                 }
@@ -1029,6 +1044,17 @@ impl crate::Device<super::Api> for super::Device {
     }
     unsafe fn destroy_compute_pipeline(&self, _pipeline: super::ComputePipeline) {}
 
+    unsafe fn create_pipeline_cache(
+        &self,
+        _desc: &crate::PipelineCacheDescriptor,
+    ) -> Result<super::PipelineCache, crate::DeviceError> {
+        Ok(super::PipelineCache)
+    }
+    unsafe fn destroy_pipeline_cache(&self, _cache: super::PipelineCache) {}
+    unsafe fn pipeline_cache_get_data(&self, _cache: &super::PipelineCache) -> Option<Vec<u8>> {
+        None
+    }
+
     unsafe fn create_query_set(
         &self,
         desc: &wgt::QuerySetDescriptor<crate::Label>,
@@ -59,6 +59,7 @@ impl crate::Api for Api {
     type ShaderModule = ShaderModule;
     type RenderPipeline = RenderPipeline;
     type ComputePipeline = ComputePipeline;
+    type PipelineCache = PipelineCache;
 }
 
 pub struct Instance {
@@ -598,7 +599,8 @@ unsafe impl Sync for BindGroup {}
 
 #[derive(Debug)]
 pub struct ShaderModule {
-    naga: crate::NagaShader,
+    /// Locked while pipeline-overridable constants are written in.
+    naga: Mutex<crate::NagaShader>,
 }
 
 #[derive(Debug, Default)]
@@ -654,6 +656,10 @@ pub struct ComputePipeline {
 unsafe impl Send for ComputePipeline {}
 unsafe impl Sync for ComputePipeline {}
 
+/// Translated shaders aren't cached on this backend yet.
+#[derive(Debug)]
+pub struct PipelineCache;
+
 #[derive(Debug)]
 pub struct QuerySet {
     raw_buffer: mtl::Buffer,
@@ -354,7 +354,8 @@ impl PhysicalDeviceFeatures {
             | F::TIMESTAMP_QUERY
             | F::WRITE_TIMESTAMP_INSIDE_PASSES
             | F::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES
-            | F::CLEAR_TEXTURE;
+            | F::CLEAR_TEXTURE
+            | F::REUSABLE_COMMAND_BUFFERS;
         let mut dl_flags = Df::all();
 
         dl_flags.set(Df::CUBE_ARRAY_TEXTURES, self.core.image_cube_array != 0);
@@ -569,6 +570,11 @@ impl PhysicalDeviceFeatures {
             ),
         );
 
+        features.set(
+            F::VERTEX_ATTRIBUTE_64BIT,
+            features.contains(F::SHADER_FLOAT64) && is_format_64bit_vertex_supported(caps),
+        );
+
         (features, dl_flags)
     }
 
@@ -607,6 +613,13 @@ impl PhysicalDeviceCapabilities {
             .any(|ep| unsafe { CStr::from_ptr(ep.extension_name.as_ptr()) } == extension)
     }
 
+    fn supports_buffer_format(&self, format: vk::Format, features: vk::FormatFeatureFlags) -> bool {
+        self.formats
+            .get(format.as_raw() as usize)
+            .map(|properties| properties.buffer_features.contains(features))
+            .unwrap()
+    }
+
     fn supports_format(
         &self,
         format: vk::Format,
@@ -629,6 +642,11 @@ impl PhysicalDeviceCapabilities {
 
         extensions.push(khr::Swapchain::name());
 
+        // Optional, allows swapchain images to be viewed with a different srgb-ness
+        if self.supports_extension(vk::KhrSwapchainMutableFormatFn::name()) {
+            extensions.push(vk::KhrSwapchainMutableFormatFn::name());
+        }
+
         if self.properties.api_version < vk::API_VERSION_1_1 {
             extensions.push(vk::KhrMaintenance1Fn::name());
             extensions.push(vk::KhrMaintenance2Fn::name());
@@ -651,6 +669,9 @@ impl PhysicalDeviceCapabilities {
             if self.supports_extension(vk::KhrImagelessFramebufferFn::name()) {
                 extensions.push(vk::KhrImagelessFramebufferFn::name());
                 extensions.push(vk::KhrImageFormatListFn::name()); // Required for `KhrImagelessFramebufferFn`
+            } else if self.supports_extension(vk::KhrImageFormatListFn::name()) {
+                // Optional, narrows down the formats of images with mutable formats
+                extensions.push(vk::KhrImageFormatListFn::name());
             }
 
             extensions.push(vk::ExtSamplerFilterMinmaxFn::name());
@@ -1056,6 +1077,10 @@ impl super::Instance {
             },
             image_view_usage: phd_capabilities.properties.api_version >= vk::API_VERSION_1_1
                 || phd_capabilities.supports_extension(vk::KhrMaintenance2Fn::name()),
+            image_format_list: phd_capabilities.properties.api_version >= vk::API_VERSION_1_2
+                || phd_capabilities.supports_extension(vk::KhrImageFormatListFn::name()),
+            swapchain_mutable_format: phd_capabilities
+                .supports_extension(vk::KhrSwapchainMutableFormatFn::name()),
             timeline_semaphores: match phd_features.vulkan_1_2 {
                 Some(features) => features.timeline_semaphore == vk::TRUE,
                 None => phd_features
@@ -1664,3 +1689,14 @@ fn is_format_16bit_norm_supported(caps: &PhysicalDeviceCapabilities) -> bool {
 
     r16unorm && r16snorm && rg16unorm && rg16snorm && rgba16unorm && rgba16snorm
 }
+
+fn is_format_64bit_vertex_supported(caps: &PhysicalDeviceCapabilities) -> bool {
+    [
+        vk::Format::R64_SFLOAT,
+        vk::Format::R64G64_SFLOAT,
+        vk::Format::R64G64B64_SFLOAT,
+        vk::Format::R64G64B64A64_SFLOAT,
+    ]
+    .iter()
+    .all(|&format| caps.supports_buffer_format(format, vk::FormatFeatureFlags::VERTEX_BUFFER))
+}
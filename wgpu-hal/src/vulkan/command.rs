@@ -67,9 +67,12 @@ impl crate::CommandEncoder<super::Api> for super::CommandEncoder {
         // Reset this in case the last renderpass was never ended.
         self.rpass_debug_marker_active = false;
 
-        let vk_info = vk::CommandBufferBeginInfo::builder()
-            .flags(vk::CommandBufferUsageFlags::ONE_TIME_SUBMIT)
-            .build();
+        let usage = if self.reusable {
+            vk::CommandBufferUsageFlags::SIMULTANEOUS_USE
+        } else {
+            vk::CommandBufferUsageFlags::ONE_TIME_SUBMIT
+        };
+        let vk_info = vk::CommandBufferBeginInfo::builder().flags(usage).build();
         self.device.raw.begin_command_buffer(raw, &vk_info)?;
         self.active = raw;
 
@@ -220,13 +220,19 @@ impl super::DeviceShared {
                     .iter()
                     .enumerate()
                     .map(|(i, at)| {
+                        // The format list has to match the one the image was created with.
+                        let view_formats = if at.raw_view_formats.is_empty() {
+                            &vk_view_formats[i..i + 1]
+                        } else {
+                            &at.raw_view_formats[..]
+                        };
                         vk::FramebufferAttachmentImageInfo::builder()
                             .usage(conv::map_texture_usage(at.view_usage))
                             .flags(at.raw_image_flags)
                             .width(e.key().extent.width)
                             .height(e.key().extent.height)
                             .layer_count(e.key().extent.depth_or_array_layers)
-                            .view_formats(&vk_view_formats[i..i + 1])
+                            .view_formats(view_formats)
                             .build()
                     })
                     .collect::<ArrayVec<_, { super::MAX_TOTAL_ATTACHMENTS }>>();
@@ -528,6 +534,23 @@ impl super::Device {
             None => vk::SwapchainKHR::null(),
         };
 
+        let mut raw_flags = vk::SwapchainCreateFlagsKHR::empty();
+        let mut raw_view_formats = Vec::new();
+        if !config.view_formats.is_empty() {
+            if !self.shared.private_caps.swapchain_mutable_format {
+                return Err(crate::SurfaceError::Other(
+                    "Swapchain view formats require VK_KHR_swapchain_mutable_format",
+                ));
+            }
+            raw_flags |= vk::SwapchainCreateFlagsKHR::MUTABLE_FORMAT;
+            raw_view_formats = config
+                .view_formats
+                .iter()
+                .chain(Some(&config.format))
+                .map(|&format| self.shared.private_caps.map_texture_format(format))
+                .collect();
+        }
+
         let color_space = if config.format == wgt::TextureFormat::Rgba16Float {
             // Enable wide color gamut mode
             // Vulkan swapchain for Android only supports DISPLAY_P3_NONLINEAR_EXT and EXTENDED_SRGB_LINEAR_EXT
@@ -535,8 +558,9 @@ impl super::Device {
         } else {
             vk::ColorSpaceKHR::SRGB_NONLINEAR
         };
-        let info = vk::SwapchainCreateInfoKHR::builder()
-            .flags(vk::SwapchainCreateFlagsKHR::empty())
+        let mut format_list_info = vk::ImageFormatListCreateInfo::builder();
+        let mut info = vk::SwapchainCreateInfoKHR::builder()
+            .flags(raw_flags)
             .surface(surface.raw)
             .min_image_count(config.swap_chain_size)
             .image_format(self.shared.private_caps.map_texture_format(config.format))
@@ -554,6 +578,11 @@ impl super::Device {
             .clipped(true)
             .old_swapchain(old_swapchain);
 
+        if !raw_view_formats.is_empty() {
+            format_list_info = format_list_info.view_formats(&raw_view_formats);
+            info = info.push_next(&mut format_list_info);
+        }
+
         let result = {
             profiling::scope!("vkCreateSwapchainKHR");
             functor.create_swapchain(&info, None)
@@ -608,6 +637,14 @@ impl super::Device {
         desc: &crate::TextureDescriptor,
         drop_guard: Option<super::DropGuard>,
     ) -> super::Texture {
+        let mut raw_flags = vk::ImageCreateFlags::empty();
+        let mut view_formats = vec![];
+        if !desc.view_formats.is_empty() {
+            raw_flags |= vk::ImageCreateFlags::MUTABLE_FORMAT;
+            view_formats = desc.view_formats.clone();
+            view_formats.push(desc.format);
+        }
+
         super::Texture {
             raw: vk_image,
             drop_guard,
@@ -615,8 +652,9 @@ impl super::Device {
             usage: desc.usage,
             aspects: crate::FormatAspects::from(desc.format),
             format_info: desc.format.describe(),
-            raw_flags: vk::ImageCreateFlags::empty(),
+            raw_flags,
             copy_size: conv::map_extent_to_copy_size(&desc.size, desc.dimension),
+            view_formats,
         }
     }
 
@@ -640,10 +678,16 @@ impl super::Device {
         stage: &crate::ProgrammableStage<super::Api>,
         naga_stage: naga::ShaderStage,
         binding_map: &naga::back::spv::BindingMap,
+        cache: Option<&super::PipelineCache>,
     ) -> Result<CompiledStage, crate::PipelineError> {
         let stage_flags = crate::auxil::map_naga_stage(naga_stage);
         let vk_module = match *stage.module {
-            super::ShaderModule::Raw(raw) => raw,
+            super::ShaderModule::Raw(raw) => {
+                if !stage.constants.is_empty() {
+                    log::warn!("Pipeline-overridable constants are ignored for SPIR-V passthrough");
+                }
+                raw
+            }
             super::ShaderModule::Intermediate {
                 ref naga_shader,
                 runtime_checks,
@@ -671,16 +715,50 @@ impl super::Device {
                 } else {
                     &self.naga_options
                 };
-                let spv = {
+                let mut naga_shader = naga_shader.lock();
+                let write_spv = |naga_shader: &mut crate::NagaShader| {
+                    let module = crate::auxil::overrides::apply(
+                        &mut naga_shader.module,
+                        &naga_shader.overrides,
+                        stage.constants,
+                    )
+                    .map_err(|msg| crate::PipelineError::Linkage(stage_flags, msg))?;
                     profiling::scope!("naga::spv::write_vec");
                     naga::back::spv::write_vec(
-                        &naga_shader.module,
+                        &module,
                         &naga_shader.info,
                         options,
                         Some(&pipeline_options),
                     )
-                }
-                .map_err(|e| crate::PipelineError::Linkage(stage_flags, format!("{}", e)))?;
+                    .map_err(|e| crate::PipelineError::Linkage(stage_flags, format!("{}", e)))
+                };
+                let spv = match cache {
+                    Some(cache) => {
+                        let key = crate::auxil::pipeline_cache::translation_key(
+                            &naga_shader,
+                            naga_stage,
+                            stage.entry_point,
+                            stage.constants,
+                            options,
+                        );
+                        match cache.translations.get(&key) {
+                            Some(bytes) => bytes
+                                .chunks_exact(4)
+                                .map(|word| {
+                                    u32::from_le_bytes([word[0], word[1], word[2], word[3]])
+                                })
+                                .collect(),
+                            None => {
+                                let spv = write_spv(&mut naga_shader)?;
+                                let bytes =
+                                    spv.iter().flat_map(|word| word.to_le_bytes()).collect();
+                                cache.translations.insert(key, bytes);
+                                spv
+                            }
+                        }
+                    }
+                    None => write_spv(&mut naga_shader)?,
+                };
                 self.create_shader_module_impl(&spv)?
             }
         };
@@ -863,10 +941,28 @@ impl crate::Device<super::Api> for super::Device {
             raw_flags |= vk::ImageCreateFlags::CUBE_COMPATIBLE;
         }
 
-        let vk_info = vk::ImageCreateInfo::builder()
+        let original_format = self.shared.private_caps.map_texture_format(desc.format);
+        let mut vk_view_formats = vec![];
+        let mut wgt_view_formats = vec![];
+        if !desc.view_formats.is_empty() {
+            raw_flags |= vk::ImageCreateFlags::MUTABLE_FORMAT;
+            wgt_view_formats = desc.view_formats.clone();
+            wgt_view_formats.push(desc.format);
+
+            if self.shared.private_caps.image_format_list {
+                vk_view_formats = desc
+                    .view_formats
+                    .iter()
+                    .map(|f| self.shared.private_caps.map_texture_format(*f))
+                    .collect();
+                vk_view_formats.push(original_format)
+            }
+        }
+
+        let mut vk_info = vk::ImageCreateInfo::builder()
             .flags(raw_flags)
             .image_type(conv::map_texture_dimension(desc.dimension))
-            .format(self.shared.private_caps.map_texture_format(desc.format))
+            .format(original_format)
             .extent(vk::Extent3D {
                 width: copy_size.width,
                 height: copy_size.height,
@@ -880,6 +976,12 @@ impl crate::Device<super::Api> for super::Device {
             .sharing_mode(vk::SharingMode::EXCLUSIVE)
             .initial_layout(vk::ImageLayout::UNDEFINED);
 
+        let mut format_list_info = vk::ImageFormatListCreateInfo::builder();
+        if !vk_view_formats.is_empty() {
+            format_list_info = format_list_info.view_formats(&vk_view_formats);
+            vk_info = vk_info.push_next(&mut format_list_info);
+        }
+
         let raw = self.shared.raw.create_image(&vk_info, None)?;
         let req = self.shared.raw.get_image_memory_requirements(raw);
 
@@ -911,6 +1013,7 @@ impl crate::Device<super::Api> for super::Device {
             format_info: desc.format.describe(),
             raw_flags,
             copy_size,
+            view_formats: wgt_view_formats,
         })
     }
     unsafe fn destroy_texture(&self, texture: super::Texture) {
@@ -964,6 +1067,11 @@ impl crate::Device<super::Api> for super::Device {
             raw_image_flags: texture.raw_flags,
             view_usage,
             view_format: desc.format,
+            raw_view_formats: texture
+                .view_formats
+                .iter()
+                .map(|&format| self.shared.private_caps.map_texture_format(format))
+                .collect(),
         };
 
         Ok(super::TextureView {
@@ -1055,6 +1163,7 @@ impl crate::Device<super::Api> for super::Device {
             free: Vec::new(),
             discarded: Vec::new(),
             rpass_debug_marker_active: false,
+            reusable: desc.reusable,
         })
     }
     unsafe fn destroy_command_encoder(&self, cmd_encoder: super::CommandEncoder) {
@@ -1396,13 +1505,20 @@ impl crate::Device<super::Api> for super::Device {
     ) -> Result<super::ShaderModule, crate::ShaderError> {
         let spv = match shader {
             crate::ShaderInput::Naga(naga_shader) => {
-                if self
-                    .shared
-                    .workarounds
-                    .contains(super::Workarounds::SEPARATE_ENTRY_POINTS)
+                // Overridable constants are only known at pipeline creation.
+                let has_overrides = naga_shader
+                    .module
+                    .constants
+                    .iter()
+                    .any(|(_, constant)| constant.specialization.is_some());
+                if has_overrides
+                    || self
+                        .shared
+                        .workarounds
+                        .contains(super::Workarounds::SEPARATE_ENTRY_POINTS)
                 {
                     return Ok(super::ShaderModule::Intermediate {
-                        naga_shader,
+                        naga_shader: Mutex::new(naga_shader),
                         runtime_checks: desc.runtime_checks,
                     });
                 }
@@ -1498,6 +1614,7 @@ impl crate::Device<super::Api> for super::Device {
             &desc.vertex_stage,
             naga::ShaderStage::Vertex,
             &desc.layout.binding_arrays,
+            desc.cache,
         )?;
         stages.push(compiled_vs.create_info);
         let compiled_fs = match desc.fragment_stage {
@@ -1506,6 +1623,7 @@ impl crate::Device<super::Api> for super::Device {
                     stage,
                     naga::ShaderStage::Fragment,
                     &desc.layout.binding_arrays,
+                    desc.cache,
                 )?;
                 stages.push(compiled.create_info);
                 Some(compiled)
@@ -1655,11 +1773,14 @@ impl crate::Device<super::Api> for super::Device {
                 .build()
         }];
 
+        let raw_cache = desc
+            .cache
+            .map_or(vk::PipelineCache::null(), |cache| cache.raw);
         let mut raw_vec = {
             profiling::scope!("vkCreateGraphicsPipelines");
             self.shared
                 .raw
-                .create_graphics_pipelines(vk::PipelineCache::null(), &vk_infos, None)
+                .create_graphics_pipelines(raw_cache, &vk_infos, None)
                 .map_err(|(_, e)| crate::DeviceError::from(e))?
         };
 
@@ -1694,6 +1815,7 @@ impl crate::Device<super::Api> for super::Device {
             &desc.stage,
             naga::ShaderStage::Compute,
             &desc.layout.binding_arrays,
+            desc.cache,
         )?;
 
         let vk_infos = [{
@@ -1703,11 +1825,14 @@ impl crate::Device<super::Api> for super::Device {
                 .build()
         }];
 
+        let raw_cache = desc
+            .cache
+            .map_or(vk::PipelineCache::null(), |cache| cache.raw);
         let mut raw_vec = {
             profiling::scope!("vkCreateComputePipelines");
             self.shared
                 .raw
-                .create_compute_pipelines(vk::PipelineCache::null(), &vk_infos, None)
+                .create_compute_pipelines(raw_cache, &vk_infos, None)
                 .map_err(|(_, e)| crate::DeviceError::from(e))?
         };
 
@@ -1727,6 +1852,44 @@ impl crate::Device<super::Api> for super::Device {
         self.shared.raw.destroy_pipeline(pipeline.raw, None);
     }
 
+    unsafe fn create_pipeline_cache(
+        &self,
+        desc: &crate::PipelineCacheDescriptor,
+    ) -> Result<super::PipelineCache, crate::DeviceError> {
+        use crate::auxil::pipeline_cache;
+
+        let (initial_data, translations) = match desc.data {
+            Some(data) => pipeline_cache::deserialize(data).unwrap_or_else(|| {
+                log::warn!("Ignoring invalid pipeline cache data");
+                (&[], pipeline_cache::TranslationCache::default())
+            }),
+            None => (&[][..], pipeline_cache::TranslationCache::default()),
+        };
+        let vk_info = vk::PipelineCacheCreateInfo::builder().initial_data(initial_data);
+        let raw = self.shared.raw.create_pipeline_cache(&vk_info, None)?;
+        if let Some(label) = desc.label {
+            self.shared
+                .set_object_name(vk::ObjectType::PIPELINE_CACHE, raw, label);
+        }
+        Ok(super::PipelineCache { raw, translations })
+    }
+    unsafe fn destroy_pipeline_cache(&self, cache: super::PipelineCache) {
+        self.shared.raw.destroy_pipeline_cache(cache.raw, None);
+    }
+    unsafe fn pipeline_cache_get_data(&self, cache: &super::PipelineCache) -> Option<Vec<u8>> {
+        let native = match self.shared.raw.get_pipeline_cache_data(cache.raw) {
+            Ok(native) => native,
+            Err(err) => {
+                log::error!("Unable to get the pipeline cache data: {:?}", err);
+                Vec::new()
+            }
+        };
+        Some(crate::auxil::pipeline_cache::serialize(
+            &native,
+            &cache.translations,
+        ))
+    }
+
     unsafe fn create_query_set(
         &self,
         desc: &wgt::QuerySetDescriptor<crate::Label>,
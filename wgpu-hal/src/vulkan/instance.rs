@@ -765,6 +765,17 @@ impl crate::Surface<super::Api> for super::Surface {
             .reset_fences(fences)
             .map_err(crate::DeviceError::from)?;
 
+        let (raw_flags, view_formats) = if sc.config.view_formats.is_empty() {
+            (vk::ImageCreateFlags::empty(), Vec::new())
+        } else {
+            // Images of a mutable-format swapchain are created with these flags.
+            let mut view_formats = sc.config.view_formats.clone();
+            view_formats.push(sc.config.format);
+            (
+                vk::ImageCreateFlags::MUTABLE_FORMAT | vk::ImageCreateFlags::EXTENDED_USAGE,
+                view_formats,
+            )
+        };
         let texture = super::SurfaceTexture {
             index,
             texture: super::Texture {
@@ -774,11 +785,12 @@ impl crate::Surface<super::Api> for super::Surface {
                 usage: sc.config.usage,
                 aspects: crate::FormatAspects::COLOR,
                 format_info: sc.config.format.describe(),
-                raw_flags: vk::ImageCreateFlags::empty(),
+                raw_flags,
                 copy_size: conv::map_extent_to_copy_size(
                     &sc.config.extent,
                     wgt::TextureDimension::D2,
                 ),
+                view_formats,
             },
         };
         Ok(Some(crate::AcquiredSurfaceTexture {
@@ -72,6 +72,7 @@ impl crate::Api for Api {
     type ShaderModule = ShaderModule;
     type RenderPipeline = RenderPipeline;
     type ComputePipeline = ComputePipeline;
+    type PipelineCache = PipelineCache;
 }
 
 struct DebugUtils {
@@ -159,6 +160,8 @@ struct PrivateCapabilities {
     flip_y_requires_shift: bool,
     imageless_framebuffers: bool,
     image_view_usage: bool,
+    image_format_list: bool,
+    swapchain_mutable_format: bool,
     timeline_semaphores: bool,
     texture_d24: bool,
     texture_d24_s8: bool,
@@ -225,6 +228,8 @@ struct FramebufferAttachment {
     raw_image_flags: vk::ImageCreateFlags,
     view_usage: crate::TextureUses,
     view_format: wgt::TextureFormat,
+    /// All the formats the image can be viewed with, if it has a mutable format.
+    raw_view_formats: Vec<vk::Format>,
 }
 
 #[derive(Clone, Eq, Hash, PartialEq)]
@@ -369,6 +374,9 @@ pub struct Texture {
     format_info: wgt::TextureFormatInfo,
     raw_flags: vk::ImageCreateFlags,
     copy_size: crate::CopyExtent,
+    /// The texture format and all the formats it can be viewed with,
+    /// or empty if the image doesn't have a mutable format.
+    view_formats: Vec<wgt::TextureFormat>,
 }
 
 impl Texture {
@@ -456,6 +464,8 @@ pub struct CommandEncoder {
     /// If this is true, the active renderpass enabled a debug span,
     /// and needs to be disabled on renderpass close.
     rpass_debug_marker_active: bool,
+    /// If this is true, the command buffers may be submitted several times.
+    reusable: bool,
 }
 
 pub struct CommandBuffer {
@@ -467,11 +477,18 @@ pub struct CommandBuffer {
 pub enum ShaderModule {
     Raw(vk::ShaderModule),
     Intermediate {
-        naga_shader: crate::NagaShader,
+        /// Locked while pipeline-overridable constants are written in.
+        naga_shader: Mutex<crate::NagaShader>,
         runtime_checks: bool,
     },
 }
 
+#[derive(Debug)]
+pub struct PipelineCache {
+    raw: vk::PipelineCache,
+    translations: crate::auxil::pipeline_cache::TranslationCache,
+}
+
 #[derive(Debug)]
 pub struct RenderPipeline {
     raw: vk::Pipeline,
@@ -472,6 +472,8 @@ bitflags::bitflags! {
         /// - DX11 (emulated with uniforms)
         /// - OpenGL (emulated with uniforms)
         ///
+        /// See [`Features::PUSH_CONSTANT_EMULATION`] for other platforms.
+        ///
         /// This is a native only feature.
         const PUSH_CONSTANTS = 1 << 25;
         /// Allows the use of [`AddressMode::ClampToBorder`] with a border color
@@ -519,21 +521,26 @@ bitflags::bitflags! {
         ///
         /// This is a native-only feature.
         const TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES = 1 << 29;
-        /// Enables 64-bit floating point types in SPIR-V shaders.
+        /// Enables 64-bit floating point types (`f64`) in shaders.
+        ///
+        /// Without this feature, shaders using them fail validation.
         ///
         /// Note: even when supported by GPU hardware, 64-bit floating point operations are
         /// frequently between 16 and 64 _times_ slower than equivalent operations on 32-bit floats.
         ///
         /// Supported Platforms:
         /// - Vulkan
+        /// - DX12
         ///
         /// This is a native-only feature.
         const SHADER_FLOAT64 = 1 << 30;
         /// Enables using 64-bit types for vertex attributes.
         ///
-        /// Requires SHADER_FLOAT64.
+        /// Requires SHADER_FLOAT64. The `Float64*` vertex formats can only be used
+        /// with 64-bit shader inputs, and 32-bit formats with 32-bit inputs.
         ///
-        /// Supported Platforms: N/A
+        /// Supported Platforms:
+        /// - Vulkan (when the device can fetch all the 64-bit formats)
         ///
         /// This is a native-only feature.
         const VERTEX_ATTRIBUTE_64BIT = 1 << 31;
@@ -637,6 +644,111 @@ bitflags::bitflags! {
         /// - DX12
         /// - Metal (Intel and AMD GPUs)
         const WRITE_TIMESTAMP_INSIDE_PASSES = 1 << 41;
+        /// Allows the use of push constants on devices without [`Features::PUSH_CONSTANTS`].
+        ///
+        /// Push constants are emulated with a uniform buffer, bound in a group reserved by
+        /// wgpu: the group right after the last one allowed by [`Limits::max_bind_groups`].
+        /// Emulation therefore costs one bind group: the requested `max_bind_groups` must be
+        /// lower than the adapter's, so the adapter's last bind group is left for wgpu.
+        /// Adapters report this feature when they support at least two bind groups.
+        /// [`Limits::max_push_constant_size`] can be up to 256 bytes, even if the limits of the
+        /// adapter report less.
+        ///
+        /// Push constant blocks of shaders are rewritten into uniform buffers, so they must
+        /// follow the layout rules of uniform buffers. Setting push constants inside a pass
+        /// is slower than with native push constants, as every update is uploaded to a
+        /// buffer before the pass begins.
+        ///
+        /// If [`Features::PUSH_CONSTANTS`] is requested as well, native push constants are
+        /// used instead, making the emulation a fallback for adapters that don't support them.
+        ///
+        /// Supported platforms:
+        /// - All native platforms
+        ///
+        /// This is a native only feature: the emulation is done by wgpu-core, which the
+        /// WebGPU backend doesn't use, so it is never reported on the web.
+        const PUSH_CONSTANT_EMULATION = 1 << 42;
+        /// Checks the arguments of indirect draws and dispatches on the GPU.
+        ///
+        /// Before they are used, the arguments are copied to an internal buffer by a
+        /// compute shader, which zeroes the draws and dispatches that are out of range,
+        /// making them do nothing:
+        /// - draws with vertices or instances beyond the vertex buffers bound when they
+        ///   are recorded,
+        /// - indexed draws with indices beyond the bound index buffer, or with instances
+        ///   beyond the bound vertex buffers,
+        /// - draws with a first instance other than 0, without
+        ///   [`Features::INDIRECT_FIRST_INSTANCE`],
+        /// - dispatches with more workgroups than
+        ///   [`Limits::max_compute_workgroups_per_dimension`] in a dimension.
+        ///
+        /// Draws of render bundles are checked against the buffers bound in the bundle.
+        /// The base vertex of indexed draws isn't checked. Buffers with
+        /// [`BufferUsages::INDIRECT`] also get bound as read-only storage buffers by
+        /// wgpu, so the range an indirect command reads must fit in
+        /// [`Limits::max_storage_buffer_binding_size`].
+        ///
+        /// Supported platforms:
+        /// - All native platforms with [`DownlevelFlags::COMPUTE_SHADERS`] and
+        ///   [`DownlevelFlags::INDIRECT_EXECUTION`]
+        ///
+        /// This is a native only feature.
+        const INDIRECT_VALIDATION = 1 << 43;
+        /// Checks that indexed draws only read vertices within the bound vertex buffers.
+        ///
+        /// Buffers with [`BufferUsages::INDEX`] keep a copy of the indices written to them
+        /// with `Queue::write_buffer` or through a mapping. Indexed draws look up the
+        /// smallest and largest index they read, and fail validation if these select
+        /// vertices outside of the bound vertex buffers once the base vertex is added.
+        /// The bounds of the indices of a range are computed the first time it is drawn,
+        /// and kept until the range is written again.
+        ///
+        /// The checks are done on the CPU, which has these costs and gaps:
+        /// - Every index buffer keeps a copy of its whole contents in memory, except
+        ///   buffers with [`BufferUsages::STORAGE`].
+        /// - Draws are checked against the indices written when they are recorded.
+        ///   Indices written with `Queue::write_buffer` after a draw is recorded, but
+        ///   before it is submitted, aren't checked, even though the draw reads them.
+        /// - Indices written by the GPU, by copies, clears and query resolves, or with
+        ///   `Queue::write_buffer_with` aren't known. Indexed draws reading a buffer
+        ///   written that way fail validation until the whole buffer is written again
+        ///   with `Queue::write_buffer`. Indexed draws reading buffers with
+        ///   [`BufferUsages::STORAGE`] always fail validation.
+        /// - Indirect draws aren't checked.
+        ///
+        /// Supported platforms:
+        /// - All native platforms
+        ///
+        /// This is a native only feature.
+        const INDEX_RANGE_VALIDATION = 1 << 44;
+        /// Allows creating buffers and textures whose memory isn't zero-initialized, with
+        /// the unsafe `Device::create_buffer_uninitialized` and `Device::create_texture_uninitialized`.
+        ///
+        /// wgpu zeroes the parts of resources that are read before being written,
+        /// inserting clears into submissions. Resources created uninitialized skip
+        /// this: reading parts of them that weren't written returns undefined values,
+        /// which may be data of other resources. Render passes not storing attachments
+        /// of such textures leave their contents undefined too.
+        ///
+        /// Supported platforms:
+        /// - All native platforms
+        ///
+        /// This is a native only feature.
+        const UNINITIALIZED_RESOURCES = 1 << 45;
+        /// Allows finishing command encoders with `CommandEncoder::finish_reusable`, into
+        /// command buffers that can be submitted any number of times with `Queue::submit_reusable`.
+        ///
+        /// The commands are recorded once, and each submission only inserts the barriers
+        /// taking the resources they use from their current states to the ones the commands
+        /// expect. The resources are kept alive as long as the command buffer is.
+        ///
+        /// Supported platforms:
+        /// - Vulkan
+        /// - DX12
+        /// - OpenGL
+        ///
+        /// This is a native only feature.
+        const REUSABLE_COMMAND_BUFFERS = 1 << 46;
     }
 }
 
@@ -738,7 +850,8 @@ pub struct Limits {
     /// Defaults to 2048. Higher is "better".
     pub max_vertex_buffer_array_stride: u32,
     /// Amount of storage available for push constants in bytes. Defaults to 0. Higher is "better".
-    /// Requesting more than 0 during device creation requires [`Features::PUSH_CONSTANTS`] to be enabled.
+    /// Requesting more than 0 during device creation requires [`Features::PUSH_CONSTANTS`] or
+    /// [`Features::PUSH_CONSTANT_EMULATION`] to be enabled.
     ///
     /// Expect the size to be:
     /// - Vulkan: 128-256 bytes
@@ -1015,6 +1128,19 @@ impl DownlevelCapabilities {
             && self.limits == DownlevelLimits::default()
             && self.shader_model >= ShaderModel::Sm5
     }
+
+    /// These capabilities are guarenteed by WebGL2, and go with [`Limits::downlevel_webgl2_defaults`].
+    ///
+    /// The GL backend reports them on every adapter, and more flags where the context supports them.
+    pub fn downlevel_webgl2_defaults() -> Self {
+        Self {
+            flags: DownlevelFlags::NON_POWER_OF_TWO_MIPMAPPED_TEXTURES
+                | DownlevelFlags::CUBE_ARRAY_TEXTURES
+                | DownlevelFlags::COMPARISON_SAMPLERS,
+            limits: DownlevelLimits::default(),
+            shader_model: ShaderModel::Sm5,
+        }
+    }
 }
 
 bitflags::bitflags! {
@@ -1083,6 +1209,12 @@ bitflags::bitflags! {
         ///
         /// WebGL doesn't support this.
         const BUFFER_BINDINGS_NOT_16_BYTE_ALIGNED = 1 << 15;
+
+        /// Supports creating texture views with a format other than the format of the texture,
+        /// as listed in [`TextureDescriptor::view_formats`] and [`SurfaceConfiguration::view_formats`].
+        ///
+        /// GLES/WebGL don't support this.
+        const VIEW_FORMATS = 1 << 16;
     }
 }
 
@@ -2118,6 +2250,52 @@ pub enum TextureFormat {
 }
 
 impl TextureFormat {
+    /// Strips the `Srgb` suffix from the given texture format.
+    pub fn remove_srgb_suffix(&self) -> TextureFormat {
+        match *self {
+            Self::Rgba8UnormSrgb => Self::Rgba8Unorm,
+            Self::Bgra8UnormSrgb => Self::Bgra8Unorm,
+            Self::Bc1RgbaUnormSrgb => Self::Bc1RgbaUnorm,
+            Self::Bc2RgbaUnormSrgb => Self::Bc2RgbaUnorm,
+            Self::Bc3RgbaUnormSrgb => Self::Bc3RgbaUnorm,
+            Self::Bc7RgbaUnormSrgb => Self::Bc7RgbaUnorm,
+            Self::Etc2Rgb8UnormSrgb => Self::Etc2Rgb8Unorm,
+            Self::Etc2Rgb8A1UnormSrgb => Self::Etc2Rgb8A1Unorm,
+            Self::Etc2Rgba8UnormSrgb => Self::Etc2Rgba8Unorm,
+            Self::Astc {
+                block,
+                channel: AstcChannel::UnormSrgb,
+            } => Self::Astc {
+                block,
+                channel: AstcChannel::Unorm,
+            },
+            _ => *self,
+        }
+    }
+
+    /// Adds an `Srgb` suffix to the given texture format, if the format supports it.
+    pub fn add_srgb_suffix(&self) -> TextureFormat {
+        match *self {
+            Self::Rgba8Unorm => Self::Rgba8UnormSrgb,
+            Self::Bgra8Unorm => Self::Bgra8UnormSrgb,
+            Self::Bc1RgbaUnorm => Self::Bc1RgbaUnormSrgb,
+            Self::Bc2RgbaUnorm => Self::Bc2RgbaUnormSrgb,
+            Self::Bc3RgbaUnorm => Self::Bc3RgbaUnormSrgb,
+            Self::Bc7RgbaUnorm => Self::Bc7RgbaUnormSrgb,
+            Self::Etc2Rgb8Unorm => Self::Etc2Rgb8UnormSrgb,
+            Self::Etc2Rgb8A1Unorm => Self::Etc2Rgb8A1UnormSrgb,
+            Self::Etc2Rgba8Unorm => Self::Etc2Rgba8UnormSrgb,
+            Self::Astc {
+                block,
+                channel: AstcChannel::Unorm,
+            } => Self::Astc {
+                block,
+                channel: AstcChannel::UnormSrgb,
+            },
+            _ => *self,
+        }
+    }
+
     /// Get useful information about the texture format.
     pub fn describe(&self) -> TextureFormatInfo {
         // Features
@@ -2736,6 +2914,22 @@ pub struct VertexAttribute {
     pub shader_location: ShaderLocation,
 }
 
+/// Vertex input of a shader entry point, as returned by reflection.
+///
+/// The format is the 32-bit (or 64-bit for `f64` inputs) format of the input
+/// type; any format with the same number of components and scalar kind can be
+/// used for the attribute instead.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "trace", derive(Serialize))]
+#[cfg_attr(feature = "replay", derive(Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct VertexInput {
+    /// Location of the input in the shader.
+    pub shader_location: ShaderLocation,
+    /// Format matching the type of the input.
+    pub format: VertexFormat,
+}
+
 /// Vertex Format for a [`VertexAttribute`] (input).
 ///
 /// Corresponds to [WebGPU `GPUVertexFormat`](
@@ -3032,6 +3226,44 @@ impl Default for PresentMode {
     }
 }
 
+/// Specifies how the alpha channel of the textures should be handled during
+/// compositing.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "trace", derive(Serialize))]
+#[cfg_attr(feature = "replay", derive(Deserialize))]
+pub enum CompositeAlphaMode {
+    /// Chooses either `Opaque` or `Inherit` automatically, depending on the
+    /// `alpha_mode` that the current surface can support.
+    Auto = 0,
+    /// The alpha channel, if it exists, of the textures is ignored in the
+    /// compositing process. Instead, the textures is treated as if it has a
+    /// constant alpha of 1.0.
+    Opaque = 1,
+    /// The alpha channel, if it exists, of the textures is respected in the
+    /// compositing process. The non-alpha channels of the textures are
+    /// expected to already be multiplied by the alpha channel by the
+    /// application.
+    PreMultiplied = 2,
+    /// The alpha channel, if it exists, of the textures is respected in the
+    /// compositing process. The non-alpha channels of the textures are not
+    /// expected to already be multiplied by the alpha channel by the
+    /// application; instead, the compositor will multiply the non-alpha
+    /// channels of the texture by the alpha channel during compositing.
+    PostMultiplied = 3,
+    /// The alpha channel, if it exists, of the textures is unknown for processing
+    /// during compositing. Instead, the application is responsible for setting
+    /// the composite alpha blending mode using native WSI command. If not set,
+    /// then a platform-specific default will be used.
+    Inherit = 4,
+}
+
+impl Default for CompositeAlphaMode {
+    fn default() -> Self {
+        Self::Auto
+    }
+}
+
 bitflags::bitflags! {
     /// Different ways that you can use a texture.
     ///
@@ -3067,7 +3299,7 @@ bitflags_serde_shim::impl_serde_for_bitflags!(TextureUsages);
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "trace", derive(Serialize))]
 #[cfg_attr(feature = "replay", derive(Deserialize))]
-pub struct SurfaceConfiguration {
+pub struct SurfaceConfiguration<V> {
     /// The usage of the swap chain. The only supported usage is `RENDER_ATTACHMENT`.
     pub usage: TextureUsages,
     /// The texture format of the swap chain. The only formats that are guaranteed are
@@ -3082,6 +3314,33 @@ pub struct SurfaceConfiguration {
     /// AutoNoVsync will gracefully do a designed sets of fallbacks if their primary modes are
     /// unsupported.
     pub present_mode: PresentMode,
+    /// Specifies how the alpha channel of the textures should be handled during compositing.
+    ///
+    /// `Auto` picks `Opaque` or `Inherit`, whichever the surface supports. Other modes must be
+    /// in the list returned by `Surface::get_supported_alpha_modes`.
+    pub alpha_mode: CompositeAlphaMode,
+    /// Specifies what view formats will be allowed when calling create_view() on texture returned by get_current_texture().
+    ///
+    /// View formats of the same format as the texture are always allowed.
+    ///
+    /// Note: currently, only the srgb-ness is allowed to change. (ex: Rgba8Unorm texture + Rgba8UnormSrgb view)
+    /// This requires [`DownlevelFlags::VIEW_FORMATS`] if the list is not empty.
+    pub view_formats: V,
+}
+
+impl<V: Clone> SurfaceConfiguration<V> {
+    /// Map `view_formats` of the surface configuration into another type.
+    pub fn map_view_formats<M>(&self, fun: impl FnOnce(V) -> M) -> SurfaceConfiguration<M> {
+        SurfaceConfiguration {
+            usage: self.usage,
+            format: self.format,
+            width: self.width,
+            height: self.height,
+            present_mode: self.present_mode,
+            alpha_mode: self.alpha_mode,
+            view_formats: fun(self.view_formats.clone()),
+        }
+    }
 }
 
 /// Status of the recieved surface image.
@@ -3406,7 +3665,7 @@ fn test_max_mips() {
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "trace", derive(Serialize))]
 #[cfg_attr(feature = "replay", derive(Deserialize))]
-pub struct TextureDescriptor<L> {
+pub struct TextureDescriptor<L, V> {
     /// Debug label of the texture. This will show up in graphics debuggers for easy identification.
     pub label: L,
     /// Size of the texture. All components must be greater than zero. For a
@@ -3423,12 +3682,18 @@ pub struct TextureDescriptor<L> {
     pub format: TextureFormat,
     /// Allowed usages of the texture. If used in other ways, the operation will panic.
     pub usage: TextureUsages,
-    // TODO: missing view_formats https://www.w3.org/TR/webgpu/#dom-gputexturedescriptor-viewformats
+    /// Specifies what view formats will be allowed when calling create_view() on this texture.
+    ///
+    /// View formats of the same format as the texture are always allowed.
+    ///
+    /// Note: currently, only the srgb-ness is allowed to change. (ex: Rgba8Unorm texture + Rgba8UnormSrgb view)
+    /// This requires [`DownlevelFlags::VIEW_FORMATS`] if the list is not empty.
+    pub view_formats: V,
 }
 
-impl<L> TextureDescriptor<L> {
+impl<L, V: Clone> TextureDescriptor<L, V> {
     ///
-    pub fn map_label<K>(&self, fun: impl FnOnce(&L) -> K) -> TextureDescriptor<K> {
+    pub fn map_label<K>(&self, fun: impl FnOnce(&L) -> K) -> TextureDescriptor<K, V> {
         TextureDescriptor {
             label: fun(&self.label),
             size: self.size,
@@ -3437,6 +3702,27 @@ impl<L> TextureDescriptor<L> {
             dimension: self.dimension,
             format: self.format,
             usage: self.usage,
+            view_formats: self.view_formats.clone(),
+        }
+    }
+}
+
+impl<L, V> TextureDescriptor<L, V> {
+    ///
+    pub fn map_label_and_view_formats<K, M>(
+        &self,
+        l_fun: impl FnOnce(&L) -> K,
+        v_fun: impl FnOnce(&V) -> M,
+    ) -> TextureDescriptor<K, M> {
+        TextureDescriptor {
+            label: l_fun(&self.label),
+            size: self.size,
+            mip_level_count: self.mip_level_count,
+            sample_count: self.sample_count,
+            dimension: self.dimension,
+            format: self.format,
+            usage: self.usage,
+            view_formats: v_fun(&self.view_formats),
         }
     }
 
@@ -3457,6 +3743,7 @@ impl<L> TextureDescriptor<L> {
     ///   dimension: wgpu::TextureDimension::D3,
     ///   format: wgpu::TextureFormat::Rgba8Sint,
     ///   usage: wgpu::TextureUsages::empty(),
+    ///   view_formats: &[wgpu::TextureFormat::Rgba8Sint],
     /// };
     ///
     /// assert_eq!(desc.mip_level_size(0), Some(wgpu::Extent3d { width: 100, height: 60, depth_or_array_layers: 1 }));
@@ -4139,7 +4426,7 @@ pub struct ImageSubresourceRange {
 
 impl ImageSubresourceRange {
     /// Returns the mip level range of a subresource range describes for a specific texture.
-    pub fn mip_range<L>(&self, texture_desc: &TextureDescriptor<L>) -> Range<u32> {
+    pub fn mip_range<L, V>(&self, texture_desc: &TextureDescriptor<L, V>) -> Range<u32> {
         self.base_mip_level..match self.mip_level_count {
             Some(mip_level_count) => self.base_mip_level + mip_level_count.get(),
             None => texture_desc.mip_level_count,
@@ -4147,7 +4434,7 @@ impl ImageSubresourceRange {
     }
 
     /// Returns the layer range of a subresource range describes for a specific texture.
-    pub fn layer_range<L>(&self, texture_desc: &TextureDescriptor<L>) -> Range<u32> {
+    pub fn layer_range<L, V>(&self, texture_desc: &TextureDescriptor<L, V>) -> Range<u32> {
         self.base_array_layer..match self.array_layer_count {
             Some(array_layer_count) => self.base_array_layer + array_layer_count.get(),
             None => {
@@ -4358,3 +4645,146 @@ impl Default for ShaderBoundChecks {
         Self::new()
     }
 }
+
+/// Severity of a [`CompilationMessage`].
+///
+/// Corresponds to [WebGPU `GPUCompilationMessageType`](
+/// https://gpuweb.github.io/gpuweb/#enumdef-gpucompilationmessagetype).
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "trace", derive(Serialize))]
+#[cfg_attr(feature = "replay", derive(Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "kebab-case"))]
+pub enum CompilationMessageType {
+    /// The shader module failed to compile.
+    Error,
+    /// The shader module compiled, but something in it is suspicious.
+    Warning,
+    /// Additional information, such as a secondary span of an error.
+    Info,
+}
+
+/// Position of a [`CompilationMessage`] in the shader source.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "trace", derive(Serialize))]
+#[cfg_attr(feature = "replay", derive(Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct SourceLocation {
+    /// 1-based line number.
+    pub line_number: u32,
+    /// 1-based column, in characters, of the start of the span.
+    pub line_position: u32,
+    /// 0-based offset, in bytes, of the start of the span.
+    pub offset: u32,
+    /// Length, in bytes, of the span.
+    pub length: u32,
+    /// Name of the file the position is in, for preprocessed sources.
+    ///
+    /// The position is relative to that file, see [`ShaderSourceMap`].
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub file: Option<String>,
+}
+
+/// A message produced while compiling a shader module.
+///
+/// Corresponds to [WebGPU `GPUCompilationMessage`](
+/// https://gpuweb.github.io/gpuweb/#gpucompilationmessage).
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "trace", derive(Serialize))]
+#[cfg_attr(feature = "replay", derive(Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "camelCase"))]
+pub struct CompilationMessage {
+    /// Human-readable description of the message.
+    pub message: String,
+    /// Severity of the message.
+    pub message_type: CompilationMessageType,
+    /// Part of the source the message refers to, if any.
+    pub location: Option<SourceLocation>,
+}
+
+/// Messages produced while compiling a shader module.
+///
+/// Corresponds to [WebGPU `GPUCompilationInfo`](
+/// https://gpuweb.github.io/gpuweb/#gpucompilationinfo).
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "trace", derive(Serialize))]
+#[cfg_attr(feature = "replay", derive(Deserialize))]
+pub struct CompilationInfo {
+    /// All messages, in the order they were produced.
+    pub messages: Vec<CompilationMessage>,
+}
+
+impl CompilationInfo {
+    /// Returns true if any of the messages is an error.
+    pub fn has_errors(&self) -> bool {
+        self.messages
+            .iter()
+            .any(|message| message.message_type == CompilationMessageType::Error)
+    }
+}
+
+/// Origin of a line of preprocessed shader source.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SourceLine {
+    /// Index of the file in [`ShaderSourceMap::files`].
+    pub file: u32,
+    /// 1-based line number in the file.
+    pub line_number: u32,
+    /// Offset, in bytes, of the start of the line in the file.
+    pub file_offset: u32,
+    /// Offset, in bytes, of the start of the line in the preprocessed source.
+    pub offset: u32,
+}
+
+/// Maps the lines of a preprocessed shader source back to the files they come from.
+///
+/// Lines are copied verbatim by the preprocessor, so a position only needs its
+/// line to be mapped.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct ShaderSourceMap {
+    /// Names of the source files, the first one being the main file.
+    pub files: Vec<String>,
+    /// Origin of every line of the preprocessed source, in order.
+    pub lines: Vec<SourceLine>,
+}
+
+impl ShaderSourceMap {
+    /// Returns the origin of the line containing `offset`, a byte offset in the preprocessed source.
+    pub fn find_line(&self, offset: u32) -> Option<&SourceLine> {
+        let index = match self.lines.binary_search_by_key(&offset, |line| line.offset) {
+            Ok(index) => index,
+            Err(0) => return None,
+            Err(next) => next - 1,
+        };
+        self.lines.get(index)
+    }
+
+    /// Maps a position in the preprocessed source to the file it comes from.
+    pub fn map_location(&self, location: &SourceLocation) -> SourceLocation {
+        match self.find_line(location.offset) {
+            Some(line) => SourceLocation {
+                line_number: line.line_number,
+                line_position: location.line_position,
+                offset: line.file_offset + (location.offset - line.offset),
+                length: location.length,
+                file: self.files.get(line.file as usize).cloned(),
+            },
+            None => location.clone(),
+        }
+    }
+
+    /// Maps the positions of all the messages to the files they come from.
+    pub fn map_compilation_info(&self, info: CompilationInfo) -> CompilationInfo {
+        CompilationInfo {
+            messages: info
+                .messages
+                .into_iter()
+                .map(|message| CompilationMessage {
+                    location: message
+                        .location
+                        .map(|location| self.map_location(&location)),
+                    ..message
+                })
+                .collect(),
+        }
+    }
+}
@@ -0,0 +1,258 @@
+//! Offline validation of WGSL shaders and pipeline descriptions.
+//!
+//! Runs the checks of `wgpu-core` against a device profile, without any GPU.
+//! See the README for the format of the pipeline description.
+
+use serde::Deserialize;
+use wgc::{binding_model, offline, pipeline};
+
+use std::{borrow::Cow, collections::HashMap, error::Error, fs, process::exit};
+
+const USAGE: &str = "\
+Usage: wgpu-validate <shader.wgsl> [pipeline.ron] [options]
+
+Options:
+    --profile <default|downlevel|webgl2>  Limits and capabilities to validate against
+    --features <FEATURE,...>              Features to enable, e.g. PUSH_CONSTANTS";
+
+/// A pipeline layout, with the entries of each bind group layout.
+#[derive(Debug, Deserialize)]
+struct Layout {
+    bind_group_layouts: Vec<Vec<wgt::BindGroupLayoutEntry>>,
+    #[serde(default)]
+    push_constant_ranges: Vec<wgt::PushConstantRange>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Stage {
+    entry_point: String,
+    #[serde(default)]
+    constants: HashMap<String, f64>,
+}
+
+/// A pipeline using the shader. Without a layout, the layout is derived from
+/// the shader.
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, Deserialize)]
+enum Pipeline {
+    Render {
+        layout: Option<Layout>,
+        vertex: Stage,
+        #[serde(default)]
+        buffers: Vec<pipeline::VertexBufferLayout<'static>>,
+        #[serde(default)]
+        primitive: wgt::PrimitiveState,
+        depth_stencil: Option<wgt::DepthStencilState>,
+        #[serde(default)]
+        multisample: wgt::MultisampleState,
+        fragment: Option<Stage>,
+        #[serde(default)]
+        targets: Vec<Option<wgt::ColorTargetState>>,
+    },
+    Compute {
+        layout: Option<Layout>,
+        stage: Stage,
+    },
+}
+
+struct Args {
+    shader: String,
+    pipeline: Option<String>,
+    profile: String,
+    features: wgt::Features,
+}
+
+fn parse_features(names: &str) -> Result<wgt::Features, String> {
+    let mut features = wgt::Features::empty();
+    for name in names
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+    {
+        let feature = (0..64)
+            .filter_map(|bit| wgt::Features::from_bits(1 << bit))
+            .find(|feature| format!("{:?}", feature) == name)
+            .ok_or_else(|| format!("unknown feature {:?}", name))?;
+        features |= feature;
+    }
+    Ok(features)
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut positional = Vec::new();
+    let mut profile = "default".to_string();
+    let mut features = wgt::Features::empty();
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--profile" => profile = args.next().ok_or("missing value for --profile")?,
+            "--features" => {
+                features = parse_features(&args.next().ok_or("missing value for --features")?)?
+            }
+            "-h" | "--help" => return Err(String::new()),
+            _ if arg.starts_with("--") => return Err(format!("unknown option {}", arg)),
+            _ => positional.push(arg),
+        }
+    }
+
+    let mut positional = positional.into_iter();
+    let shader = positional.next().ok_or("missing shader path")?;
+    let pipeline = positional.next();
+    if positional.next().is_some() {
+        return Err("too many arguments".to_string());
+    }
+    Ok(Args {
+        shader,
+        pipeline,
+        profile,
+        features,
+    })
+}
+
+fn validator(profile: &str, features: wgt::Features) -> Result<offline::Validator, String> {
+    let (limits, downlevel) = match profile {
+        "default" => (
+            wgt::Limits::default(),
+            wgt::DownlevelCapabilities::default(),
+        ),
+        "downlevel" => (
+            wgt::Limits::downlevel_defaults(),
+            wgt::DownlevelCapabilities::default(),
+        ),
+        "webgl2" => (
+            wgt::Limits::downlevel_webgl2_defaults(),
+            wgt::DownlevelCapabilities::downlevel_webgl2_defaults(),
+        ),
+        _ => return Err(format!("unknown profile {:?}", profile)),
+    };
+    Ok(offline::Validator::new(features, limits, downlevel))
+}
+
+fn create_layout(
+    validator: &offline::Validator,
+    layout: &Layout,
+) -> Result<offline::PipelineLayout, Box<dyn Error>> {
+    let bind_group_layouts = layout
+        .bind_group_layouts
+        .iter()
+        .map(|entries| {
+            validator.create_bind_group_layout(&binding_model::BindGroupLayoutDescriptor {
+                label: None,
+                entries: Cow::Borrowed(entries),
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let bind_group_layouts = bind_group_layouts.iter().collect::<Vec<_>>();
+    Ok(validator.create_pipeline_layout(&bind_group_layouts, &layout.push_constant_ranges)?)
+}
+
+fn validate(
+    validator: &offline::Validator,
+    module: &offline::ShaderModule,
+    pipeline: &Pipeline,
+) -> Result<(), Box<dyn Error>> {
+    fn stage<'a>(
+        module: &'a offline::ShaderModule,
+        stage: &'a Stage,
+    ) -> offline::ProgrammableStage<'a> {
+        offline::ProgrammableStage {
+            module,
+            entry_point: &stage.entry_point,
+            constants: &stage.constants,
+        }
+    }
+
+    match *pipeline {
+        Pipeline::Render {
+            ref layout,
+            ref vertex,
+            ref buffers,
+            primitive,
+            ref depth_stencil,
+            multisample,
+            ref fragment,
+            ref targets,
+        } => {
+            let layout = layout
+                .as_ref()
+                .map(|layout| create_layout(validator, layout))
+                .transpose()?;
+            validator.validate_render_pipeline(&offline::RenderPipelineDescriptor {
+                layout: layout.as_ref(),
+                vertex: offline::VertexState {
+                    stage: stage(module, vertex),
+                    buffers,
+                },
+                primitive,
+                depth_stencil: depth_stencil.clone(),
+                multisample,
+                fragment: fragment.as_ref().map(|fragment| offline::FragmentState {
+                    stage: stage(module, fragment),
+                    targets,
+                }),
+            })?;
+        }
+        Pipeline::Compute {
+            ref layout,
+            stage: ref compute,
+        } => {
+            let layout = layout
+                .as_ref()
+                .map(|layout| create_layout(validator, layout))
+                .transpose()?;
+            validator.validate_compute_pipeline(&offline::ComputePipelineDescriptor {
+                layout: layout.as_ref(),
+                stage: stage(module, compute),
+            })?;
+        }
+    }
+    Ok(())
+}
+
+fn report(error: &dyn Error) -> ! {
+    eprintln!("error: {}", error);
+    let mut source = error.source();
+    while let Some(error) = source {
+        eprintln!("  caused by: {}", error);
+        source = error.source();
+    }
+    exit(1);
+}
+
+fn main() {
+    env_logger::init();
+
+    let args = parse_args().unwrap_or_else(|message| {
+        if !message.is_empty() {
+            eprintln!("error: {}\n", message);
+        }
+        eprintln!("{}", USAGE);
+        exit(2);
+    });
+    let validator = validator(&args.profile, args.features).unwrap_or_else(|message| {
+        eprintln!("error: {}", message);
+        exit(2);
+    });
+
+    let source = fs::read_to_string(&args.shader).unwrap_or_else(|e| report(&e));
+    let module = validator
+        .create_shader_module(
+            &pipeline::ShaderModuleDescriptor {
+                label: Some(Cow::Borrowed(&args.shader)),
+                shader_bound_checks: wgt::ShaderBoundChecks::default(),
+            },
+            pipeline::ShaderModuleSource::Wgsl(Cow::Owned(source)),
+        )
+        .unwrap_or_else(|e| report(&e));
+
+    if let Some(ref path) = args.pipeline {
+        let description = fs::read_to_string(path).unwrap_or_else(|e| report(&e));
+        let pipeline: Pipeline = ron::de::from_str(&description).unwrap_or_else(|e| report(&e));
+        if let Err(e) = validate(&validator, &module, &pipeline) {
+            report(e.as_ref());
+        }
+    }
+
+    println!("{}: ok", args.shader);
+}
@@ -114,6 +114,8 @@ async fn execute_gpu_inner(
         layout: None,
         module: &cs_module,
         entry_point: "main",
+        constants: None,
+        cache: None,
     });
 
     // Instantiates the bind group, once again specifying the binding of buffers.
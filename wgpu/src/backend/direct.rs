@@ -1,17 +1,20 @@
 use crate::{
     AdapterInfo, BindGroupDescriptor, BindGroupLayoutDescriptor, BindingResource, BufferBinding,
-    CommandEncoderDescriptor, ComputePassDescriptor, ComputePipelineDescriptor,
-    DownlevelCapabilities, Features, Label, Limits, LoadOp, MapMode, Operations,
-    PipelineLayoutDescriptor, RenderBundleEncoderDescriptor, RenderPipelineDescriptor,
-    SamplerDescriptor, ShaderModuleDescriptor, ShaderModuleDescriptorSpirV, ShaderSource,
-    SurfaceStatus, TextureDescriptor, TextureFormat, TextureViewDescriptor,
+    CommandEncoderDescriptor, CompilationInfo, CompositeAlphaMode, ComputePassDescriptor,
+    ComputePipelineDescriptor, DownlevelCapabilities, Features, Label, Limits, LoadOp, MapMode,
+    Operations, PipelineCacheDescriptor, PipelineLayoutDescriptor, RenderBundleEncoderDescriptor,
+    RenderPipelineDescriptor, SamplerDescriptor, ShaderModuleDescriptor,
+    ShaderModuleDescriptorSpirV, ShaderSource, SurfaceStatus, TextureDescriptor, TextureFormat,
+    TextureViewDescriptor,
 };
 
+use super::worker::{JobFuture, WorkerPool};
+
 use arrayvec::ArrayVec;
 use parking_lot::Mutex;
 use smallvec::SmallVec;
 use std::{
-    borrow::Cow::Borrowed,
+    borrow::Cow::{Borrowed, Owned},
     error::Error,
     fmt,
     future::{ready, Ready},
@@ -24,7 +27,10 @@ use wgt::PresentMode;
 
 const LABEL: &str = "label";
 
-pub struct Context(wgc::hub::Global<wgc::hub::IdentityManagerFactory>);
+pub struct Context(
+    wgc::hub::Global<wgc::hub::IdentityManagerFactory>,
+    WorkerPool,
+);
 
 impl Drop for Context {
     fn drop(&mut self) {
@@ -41,11 +47,14 @@ impl fmt::Debug for Context {
 impl Context {
     #[cfg(any(not(target_arch = "wasm32"), feature = "emscripten"))]
     pub unsafe fn from_hal_instance<A: wgc::hub::HalApi>(hal_instance: A::Instance) -> Self {
-        Self(wgc::hub::Global::from_hal_instance::<A>(
-            "wgpu",
-            wgc::hub::IdentityManagerFactory,
-            hal_instance,
-        ))
+        Self(
+            wgc::hub::Global::from_hal_instance::<A>(
+                "wgpu",
+                wgc::hub::IdentityManagerFactory,
+                hal_instance,
+            ),
+            WorkerPool::new(),
+        )
     }
 
     pub unsafe fn instance_as_hal<A: wgc::hub::HalApi, F: FnOnce(Option<&A::Instance>) -> R, R>(
@@ -56,10 +65,10 @@ impl Context {
     }
 
     pub unsafe fn from_core_instance(core_instance: wgc::instance::Instance) -> Self {
-        Self(wgc::hub::Global::from_instance(
-            wgc::hub::IdentityManagerFactory,
-            core_instance,
-        ))
+        Self(
+            wgc::hub::Global::from_instance(wgc::hub::IdentityManagerFactory, core_instance),
+            WorkerPool::new(),
+        )
     }
 
     pub(crate) fn global(&self) -> &wgc::hub::Global<wgc::hub::IdentityManagerFactory> {
@@ -129,7 +138,7 @@ impl Context {
         let (id, error) = global.create_texture_from_hal::<A>(
             hal_texture,
             device.id,
-            &desc.map_label(|l| l.map(Borrowed)),
+            &desc.map_label_and_view_formats(|l| l.map(Borrowed), |v| v.to_vec()),
             PhantomData,
         );
         if let Some(cause) = error {
@@ -147,6 +156,95 @@ impl Context {
         }
     }
 
+    #[cfg(any(not(target_arch = "wasm32"), feature = "emscripten"))]
+    pub unsafe fn create_buffer_uninitialized(
+        &self,
+        device: &Device,
+        desc: &crate::BufferDescriptor<'_>,
+    ) -> Buffer {
+        let global = &self.0;
+        let (id, error) = wgc::gfx_select!(device.id => global.device_create_buffer_uninitialized(
+            device.id,
+            &desc.map_label(|l| l.map(Borrowed)),
+            PhantomData
+        ));
+        if let Some(cause) = error {
+            self.handle_error(
+                &device.error_sink,
+                cause,
+                LABEL,
+                desc.label,
+                "Device::create_buffer_uninitialized",
+            );
+        }
+        Buffer {
+            id,
+            error_sink: Arc::clone(&device.error_sink),
+        }
+    }
+
+    #[cfg(any(not(target_arch = "wasm32"), feature = "emscripten"))]
+    pub unsafe fn create_texture_uninitialized(
+        &self,
+        device: &Device,
+        desc: &TextureDescriptor,
+    ) -> Texture {
+        let global = &self.0;
+        let (id, error) = wgc::gfx_select!(device.id => global.device_create_texture_uninitialized(
+            device.id,
+            &desc.map_label_and_view_formats(|l| l.map(Borrowed), |v| v.to_vec()),
+            PhantomData
+        ));
+        if let Some(cause) = error {
+            self.handle_error(
+                &device.error_sink,
+                cause,
+                LABEL,
+                desc.label,
+                "Device::create_texture_uninitialized",
+            );
+        }
+        Texture {
+            id,
+            error_sink: Arc::clone(&device.error_sink),
+        }
+    }
+
+    #[cfg(any(not(target_arch = "wasm32"), feature = "emscripten"))]
+    pub fn command_encoder_finish_reusable(
+        &self,
+        mut encoder: CommandEncoder,
+    ) -> wgc::id::CommandBufferId {
+        let descriptor = wgt::CommandBufferDescriptor::default();
+        encoder.open = false; // prevent the drop
+        let global = &self.0;
+        let (id, error) = wgc::gfx_select!(
+            encoder.id => global.command_encoder_finish_reusable(encoder.id, &descriptor)
+        );
+        if let Some(cause) = error {
+            self.handle_error_nolabel(&encoder.error_sink, cause, "a CommandEncoder");
+        }
+        id
+    }
+
+    #[cfg(any(not(target_arch = "wasm32"), feature = "emscripten"))]
+    pub fn device_enable_barrier_log(&self, device: &Device) {
+        let global = &self.0;
+        if let Err(err) = wgc::gfx_select!(device.id => global.device_enable_barrier_log(device.id))
+        {
+            self.handle_error_fatal(err, "Device::enable_barrier_log");
+        }
+    }
+
+    #[cfg(any(not(target_arch = "wasm32"), feature = "emscripten"))]
+    pub fn device_take_barrier_logs(&self, device: &Device) -> Vec<wgc::command::BarrierLog> {
+        let global = &self.0;
+        match wgc::gfx_select!(device.id => global.device_take_barrier_logs(device.id)) {
+            Ok(logs) => logs,
+            Err(err) => self.handle_error_fatal(err, "Device::take_barrier_logs"),
+        }
+    }
+
     #[cfg(any(not(target_arch = "wasm32"), feature = "emscripten"))]
     pub unsafe fn device_as_hal<A: wgc::hub::HalApi, F: FnOnce(Option<&A::Device>) -> R, R>(
         &self,
@@ -238,30 +336,40 @@ impl Context {
         label: Label,
         string: &'static str,
     ) {
+        let error = self.make_error(cause, label_key, label, string);
+        sink_mutex.lock().handle_error(error);
+    }
+
+    fn make_error(
+        &self,
+        cause: impl Error + Send + Sync + 'static,
+        label_key: &'static str,
+        label: Label,
+        string: &'static str,
+    ) -> crate::Error {
         let error = wgc::error::ContextError {
             string,
             cause: Box::new(cause),
             label: label.unwrap_or_default().to_string(),
             label_key,
         };
-        let mut sink = sink_mutex.lock();
         let mut source_opt: Option<&(dyn Error + 'static)> = Some(&error);
         while let Some(source) = source_opt {
             if let Some(wgc::device::DeviceError::OutOfMemory) =
                 source.downcast_ref::<wgc::device::DeviceError>()
             {
-                return sink.handle_error(crate::Error::OutOfMemory {
+                return crate::Error::OutOfMemory {
                     source: Box::new(error),
-                });
+                };
             }
             source_opt = source.source();
         }
 
         // Otherwise, it is a validation error
-        sink.handle_error(crate::Error::Validation {
+        crate::Error::Validation {
             description: self.format_error(&error),
             source: Box::new(error),
-        });
+        }
     }
 
     fn handle_error_nolabel(
@@ -524,29 +632,6 @@ mod pass_impl {
                 max_count,
             )
         }
-    }
-
-    impl crate::RenderPassInner<Context> for wgc::command::RenderPass {
-        fn set_blend_constant(&mut self, color: wgt::Color) {
-            wgpu_render_pass_set_blend_constant(self, &color)
-        }
-        fn set_scissor_rect(&mut self, x: u32, y: u32, width: u32, height: u32) {
-            wgpu_render_pass_set_scissor_rect(self, x, y, width, height)
-        }
-        fn set_viewport(
-            &mut self,
-            x: f32,
-            y: f32,
-            width: f32,
-            height: f32,
-            min_depth: f32,
-            max_depth: f32,
-        ) {
-            wgpu_render_pass_set_viewport(self, x, y, width, height, min_depth, max_depth)
-        }
-        fn set_stencil_reference(&mut self, reference: u32) {
-            wgpu_render_pass_set_stencil_reference(self, reference)
-        }
 
         fn insert_debug_marker(&mut self, label: &str) {
             unsafe {
@@ -581,6 +666,29 @@ mod pass_impl {
         fn end_pipeline_statistics_query(&mut self) {
             wgpu_render_pass_end_pipeline_statistics_query(self)
         }
+    }
+
+    impl crate::RenderPassInner<Context> for wgc::command::RenderPass {
+        fn set_blend_constant(&mut self, color: wgt::Color) {
+            wgpu_render_pass_set_blend_constant(self, &color)
+        }
+        fn set_scissor_rect(&mut self, x: u32, y: u32, width: u32, height: u32) {
+            wgpu_render_pass_set_scissor_rect(self, x, y, width, height)
+        }
+        fn set_viewport(
+            &mut self,
+            x: f32,
+            y: f32,
+            width: f32,
+            height: f32,
+            min_depth: f32,
+            max_depth: f32,
+        ) {
+            wgpu_render_pass_set_viewport(self, x, y, width, height, min_depth, max_depth)
+        }
+        fn set_stencil_reference(&mut self, reference: u32) {
+            wgpu_render_pass_set_stencil_reference(self, reference)
+        }
 
         fn execute_bundles<'a, I: Iterator<Item = &'a wgc::id::RenderBundleId>>(
             &mut self,
@@ -595,6 +703,14 @@ mod pass_impl {
                 )
             }
         }
+
+        fn begin_occlusion_query(&mut self, query_index: u32) {
+            wgpu_render_pass_begin_occlusion_query(self, query_index)
+        }
+
+        fn end_occlusion_query(&mut self) {
+            wgpu_render_pass_end_occlusion_query(self)
+        }
     }
 
     impl crate::RenderInner<Context> for wgc::command::RenderBundleEncoder {
@@ -682,39 +798,92 @@ mod pass_impl {
         }
         fn multi_draw_indirect(
             &mut self,
-            _indirect_buffer: &super::Buffer,
-            _indirect_offset: wgt::BufferAddress,
-            _count: u32,
+            indirect_buffer: &super::Buffer,
+            indirect_offset: wgt::BufferAddress,
+            count: u32,
         ) {
-            unimplemented!()
+            wgpu_render_bundle_multi_draw_indirect(self, indirect_buffer.id, indirect_offset, count)
         }
         fn multi_draw_indexed_indirect(
             &mut self,
-            _indirect_buffer: &super::Buffer,
-            _indirect_offset: wgt::BufferAddress,
-            _count: u32,
+            indirect_buffer: &super::Buffer,
+            indirect_offset: wgt::BufferAddress,
+            count: u32,
         ) {
-            unimplemented!()
+            wgpu_render_bundle_multi_draw_indexed_indirect(
+                self,
+                indirect_buffer.id,
+                indirect_offset,
+                count,
+            )
         }
         fn multi_draw_indirect_count(
             &mut self,
-            _indirect_buffer: &super::Buffer,
-            _indirect_offset: wgt::BufferAddress,
-            _count_buffer: &super::Buffer,
-            _count_buffer_offset: wgt::BufferAddress,
-            _max_count: u32,
+            indirect_buffer: &super::Buffer,
+            indirect_offset: wgt::BufferAddress,
+            count_buffer: &super::Buffer,
+            count_buffer_offset: wgt::BufferAddress,
+            max_count: u32,
         ) {
-            unimplemented!()
+            wgpu_render_bundle_multi_draw_indirect_count(
+                self,
+                indirect_buffer.id,
+                indirect_offset,
+                count_buffer.id,
+                count_buffer_offset,
+                max_count,
+            )
         }
         fn multi_draw_indexed_indirect_count(
             &mut self,
-            _indirect_buffer: &super::Buffer,
-            _indirect_offset: wgt::BufferAddress,
-            _count_buffer: &super::Buffer,
-            _count_buffer_offset: wgt::BufferAddress,
-            _max_count: u32,
+            indirect_buffer: &super::Buffer,
+            indirect_offset: wgt::BufferAddress,
+            count_buffer: &super::Buffer,
+            count_buffer_offset: wgt::BufferAddress,
+            max_count: u32,
+        ) {
+            wgpu_render_bundle_multi_draw_indexed_indirect_count(
+                self,
+                indirect_buffer.id,
+                indirect_offset,
+                count_buffer.id,
+                count_buffer_offset,
+                max_count,
+            )
+        }
+
+        fn insert_debug_marker(&mut self, label: &str) {
+            unsafe {
+                let label = std::ffi::CString::new(label).unwrap();
+                wgpu_render_bundle_insert_debug_marker(self, label.as_ptr());
+            }
+        }
+
+        fn push_debug_group(&mut self, group_label: &str) {
+            unsafe {
+                let label = std::ffi::CString::new(group_label).unwrap();
+                wgpu_render_bundle_push_debug_group(self, label.as_ptr());
+            }
+        }
+
+        fn pop_debug_group(&mut self) {
+            wgpu_render_bundle_pop_debug_group(self);
+        }
+
+        fn write_timestamp(&mut self, query_set: &wgc::id::QuerySetId, query_index: u32) {
+            wgpu_render_bundle_write_timestamp(self, *query_set, query_index)
+        }
+
+        fn begin_pipeline_statistics_query(
+            &mut self,
+            query_set: &wgc::id::QuerySetId,
+            query_index: u32,
         ) {
-            unimplemented!()
+            wgpu_render_bundle_begin_pipeline_statistics_query(self, *query_set, query_index)
+        }
+
+        fn end_pipeline_statistics_query(&mut self) {
+            wgpu_render_bundle_end_pipeline_statistics_query(self)
         }
     }
 }
@@ -774,6 +943,45 @@ fn map_pass_channel<V: Copy + Default>(
     }
 }
 
+fn shader_module_source(source: ShaderSource) -> wgc::pipeline::ShaderModuleSource {
+    match source {
+        #[cfg(feature = "spirv")]
+        ShaderSource::SpirV(ref spv) => {
+            // Parse the given shader code and store its representation.
+            let options = naga::front::spv::Options {
+                adjust_coordinate_space: false, // we require NDC_Y_UP feature
+                strict_capabilities: true,
+                block_ctx_dump_prefix: None,
+            };
+            let parser = naga::front::spv::Parser::new(spv.iter().cloned(), &options);
+            let module = parser.parse().unwrap();
+            wgc::pipeline::ShaderModuleSource::Naga(module)
+        }
+        #[cfg(feature = "glsl")]
+        ShaderSource::Glsl {
+            ref shader,
+            stage,
+            ref defines,
+        } => {
+            // Parse the given shader code and store its representation.
+            let options = naga::front::glsl::Options {
+                stage,
+                defines: defines.clone(),
+            };
+            let mut parser = naga::front::glsl::Parser::default();
+            let module = parser.parse(&options, shader).unwrap();
+
+            wgc::pipeline::ShaderModuleSource::Naga(module)
+        }
+        ShaderSource::Wgsl(code) => wgc::pipeline::ShaderModuleSource::Wgsl(code),
+        ShaderSource::WgslPreprocessed { source, source_map } => {
+            wgc::pipeline::ShaderModuleSource::WgslPreprocessed(source, source_map)
+        }
+        #[cfg(feature = "naga")]
+        ShaderSource::Naga(module) => wgc::pipeline::ShaderModuleSource::Naga(module),
+    }
+}
+
 #[derive(Debug)]
 pub struct Surface {
     id: wgc::id::SurfaceId,
@@ -801,6 +1009,12 @@ pub struct Texture {
     error_sink: ErrorSink,
 }
 
+#[derive(Debug)]
+pub struct ShaderModule {
+    id: wgc::id::ShaderModuleId,
+    compilation_info: CompilationInfo,
+}
+
 #[derive(Debug)]
 pub(crate) struct CommandEncoder {
     id: wgc::id::CommandEncoderId,
@@ -812,7 +1026,7 @@ impl crate::Context for Context {
     type AdapterId = wgc::id::AdapterId;
     type DeviceId = Device;
     type QueueId = wgc::id::QueueId;
-    type ShaderModuleId = wgc::id::ShaderModuleId;
+    type ShaderModuleId = ShaderModule;
     type BindGroupLayoutId = wgc::id::BindGroupLayoutId;
     type BindGroupId = wgc::id::BindGroupId;
     type TextureViewId = wgc::id::TextureViewId;
@@ -823,6 +1037,7 @@ impl crate::Context for Context {
     type PipelineLayoutId = wgc::id::PipelineLayoutId;
     type RenderPipelineId = wgc::id::RenderPipelineId;
     type ComputePipelineId = wgc::id::ComputePipelineId;
+    type PipelineCacheId = wgc::id::PipelineCacheId;
     type CommandEncoderId = CommandEncoder;
     type ComputePassId = wgc::command::ComputePass;
     type RenderPassId = wgc::command::RenderPass;
@@ -839,13 +1054,15 @@ impl crate::Context for Context {
     type RequestDeviceFuture =
         Ready<Result<(Self::DeviceId, Self::QueueId), crate::RequestDeviceError>>;
     type PopErrorScopeFuture = Ready<Option<crate::Error>>;
+    type CompilationInfoFuture = Ready<CompilationInfo>;
+    type CreateRenderPipelineFuture = JobFuture<Result<Self::RenderPipelineId, crate::Error>>;
+    type CreateComputePipelineFuture = JobFuture<Result<Self::ComputePipelineId, crate::Error>>;
 
     fn init(backends: wgt::Backends) -> Self {
-        Self(wgc::hub::Global::new(
-            "wgpu",
-            wgc::hub::IdentityManagerFactory,
-            backends,
-        ))
+        Self(
+            wgc::hub::Global::new("wgpu", wgc::hub::IdentityManagerFactory, backends),
+            WorkerPool::new(),
+        )
     }
 
     fn instance_create_surface(
@@ -881,6 +1098,10 @@ impl crate::Context for Context {
         }
     }
 
+    fn instance_set_worker_thread_count(&self, count: usize) {
+        self.1.set_thread_count(count);
+    }
+
     fn adapter_request_device(
         &self,
         adapter: &Self::AdapterId,
@@ -992,11 +1213,25 @@ impl crate::Context for Context {
         }
     }
 
+    fn surface_get_supported_alpha_modes(
+        &self,
+        surface: &Self::SurfaceId,
+        adapter: &Self::AdapterId,
+    ) -> Vec<CompositeAlphaMode> {
+        let global = &self.0;
+        match wgc::gfx_select!(adapter => global.surface_get_supported_alpha_modes(surface.id, *adapter))
+        {
+            Ok(modes) => modes,
+            Err(wgc::instance::GetSurfaceSupportError::UnsupportedQueueFamily) => vec![],
+            Err(err) => self.handle_error_fatal(err, "Surface::get_supported_alpha_modes"),
+        }
+    }
+
     fn surface_configure(
         &self,
         surface: &Self::SurfaceId,
         device: &Self::DeviceId,
-        config: &wgt::SurfaceConfiguration,
+        config: &crate::SurfaceConfiguration,
     ) {
         let global = &self.0;
         let error =
@@ -1093,52 +1328,28 @@ impl crate::Context for Context {
             label: desc.label.map(Borrowed),
             shader_bound_checks,
         };
-        let source = match desc.source {
-            #[cfg(feature = "spirv")]
-            ShaderSource::SpirV(ref spv) => {
-                // Parse the given shader code and store its representation.
-                let options = naga::front::spv::Options {
-                    adjust_coordinate_space: false, // we require NDC_Y_UP feature
-                    strict_capabilities: true,
-                    block_ctx_dump_prefix: None,
-                };
-                let parser = naga::front::spv::Parser::new(spv.iter().cloned(), &options);
-                let module = parser.parse().unwrap();
-                wgc::pipeline::ShaderModuleSource::Naga(module)
-            }
-            #[cfg(feature = "glsl")]
-            ShaderSource::Glsl {
-                ref shader,
-                stage,
-                ref defines,
-            } => {
-                // Parse the given shader code and store its representation.
-                let options = naga::front::glsl::Options {
-                    stage,
-                    defines: defines.clone(),
-                };
-                let mut parser = naga::front::glsl::Parser::default();
-                let module = parser.parse(&options, shader).unwrap();
-
-                wgc::pipeline::ShaderModuleSource::Naga(module)
-            }
-            ShaderSource::Wgsl(ref code) => wgc::pipeline::ShaderModuleSource::Wgsl(Borrowed(code)),
-            #[cfg(feature = "naga")]
-            ShaderSource::Naga(module) => wgc::pipeline::ShaderModuleSource::Naga(module),
-        };
+        let source = shader_module_source(desc.source);
         let (id, error) = wgc::gfx_select!(
             device.id => global.device_create_shader_module(device.id, &descriptor, source, PhantomData)
         );
-        if let Some(cause) = error {
-            self.handle_error(
-                &device.error_sink,
-                cause,
-                LABEL,
-                desc.label,
-                "Device::create_shader_module",
-            );
+        let compilation_info = match error {
+            Some(cause) => {
+                let compilation_info = cause.compilation_info();
+                self.handle_error(
+                    &device.error_sink,
+                    cause,
+                    LABEL,
+                    desc.label,
+                    "Device::create_shader_module",
+                );
+                compilation_info
+            }
+            None => CompilationInfo::default(),
+        };
+        ShaderModule {
+            id,
+            compilation_info,
         }
-        id
     }
 
     unsafe fn device_create_shader_module_spirv(
@@ -1156,13 +1367,46 @@ impl crate::Context for Context {
         let (id, error) = wgc::gfx_select!(
             device.id => global.device_create_shader_module_spirv(device.id, &descriptor, Borrowed(&desc.source), PhantomData)
         );
+        let compilation_info = match error {
+            Some(cause) => {
+                let compilation_info = cause.compilation_info();
+                self.handle_error(
+                    &device.error_sink,
+                    cause,
+                    LABEL,
+                    desc.label,
+                    "Device::create_shader_module_spirv",
+                );
+                compilation_info
+            }
+            None => CompilationInfo::default(),
+        };
+        ShaderModule {
+            id,
+            compilation_info,
+        }
+    }
+
+    unsafe fn device_create_pipeline_cache(
+        &self,
+        device: &Self::DeviceId,
+        desc: &PipelineCacheDescriptor,
+    ) -> Self::PipelineCacheId {
+        let global = &self.0;
+        let descriptor = wgc::pipeline::PipelineCacheDescriptor {
+            label: desc.label.map(Borrowed),
+            data: desc.data.map(Borrowed),
+        };
+        let (id, error) = wgc::gfx_select!(
+            device.id => global.device_create_pipeline_cache(device.id, &descriptor, PhantomData)
+        );
         if let Some(cause) = error {
             self.handle_error(
                 &device.error_sink,
                 cause,
                 LABEL,
                 desc.label,
-                "Device::create_shader_module_spirv",
+                "Device::create_pipeline_cache",
             );
         }
         id
@@ -1367,8 +1611,12 @@ impl crate::Context for Context {
             layout: desc.layout.map(|l| l.id),
             vertex: pipe::VertexState {
                 stage: pipe::ProgrammableStageDescriptor {
-                    module: desc.vertex.module.id,
+                    module: desc.vertex.module.id.id,
                     entry_point: Borrowed(desc.vertex.entry_point),
+                    constants: desc
+                        .vertex
+                        .constants
+                        .map_or_else(Default::default, Borrowed),
                 },
                 buffers: Borrowed(&vertex_buffers),
             },
@@ -1377,12 +1625,14 @@ impl crate::Context for Context {
             multisample: desc.multisample,
             fragment: desc.fragment.as_ref().map(|frag| pipe::FragmentState {
                 stage: pipe::ProgrammableStageDescriptor {
-                    module: frag.module.id,
+                    module: frag.module.id.id,
                     entry_point: Borrowed(frag.entry_point),
+                    constants: frag.constants.map_or_else(Default::default, Borrowed),
                 },
                 targets: Borrowed(frag.targets),
             }),
             multiview: desc.multiview,
+            cache: desc.cache.map(|cache| cache.id),
         };
 
         let global = &self.0;
@@ -1426,9 +1676,11 @@ impl crate::Context for Context {
             label: desc.label.map(Borrowed),
             layout: desc.layout.map(|l| l.id),
             stage: pipe::ProgrammableStageDescriptor {
-                module: desc.module.id,
+                module: desc.module.id.id,
                 entry_point: Borrowed(desc.entry_point),
+                constants: desc.constants.map_or_else(Default::default, Borrowed),
             },
+            cache: desc.cache.map(|cache| cache.id),
         };
 
         let global = &self.0;
@@ -1458,6 +1710,164 @@ impl crate::Context for Context {
         id
     }
 
+    fn device_create_render_pipeline_async(
+        self: Arc<Self>,
+        device: &Self::DeviceId,
+        desc: &RenderPipelineDescriptor,
+    ) -> Self::CreateRenderPipelineFuture {
+        use wgc::pipeline as pipe;
+
+        // The descriptor has to outlive the call, so it's copied.
+        let vertex_buffers = desc
+            .vertex
+            .buffers
+            .iter()
+            .map(|vbuf| pipe::VertexBufferLayout {
+                array_stride: vbuf.array_stride,
+                step_mode: vbuf.step_mode,
+                attributes: Owned(vbuf.attributes.to_vec()),
+            })
+            .collect();
+        let descriptor = pipe::RenderPipelineDescriptor {
+            label: desc.label.map(|label| Owned(label.to_string())),
+            layout: desc.layout.map(|l| l.id),
+            vertex: pipe::VertexState {
+                stage: pipe::ProgrammableStageDescriptor {
+                    module: desc.vertex.module.id.id,
+                    entry_point: Owned(desc.vertex.entry_point.to_string()),
+                    constants: Owned(desc.vertex.constants.cloned().unwrap_or_default()),
+                },
+                buffers: Owned(vertex_buffers),
+            },
+            primitive: desc.primitive,
+            depth_stencil: desc.depth_stencil.clone(),
+            multisample: desc.multisample,
+            fragment: desc.fragment.as_ref().map(|frag| pipe::FragmentState {
+                stage: pipe::ProgrammableStageDescriptor {
+                    module: frag.module.id.id,
+                    entry_point: Owned(frag.entry_point.to_string()),
+                    constants: Owned(frag.constants.cloned().unwrap_or_default()),
+                },
+                targets: Owned(frag.targets.to_vec()),
+            }),
+            multiview: desc.multiview,
+            cache: desc.cache.map(|cache| cache.id),
+        };
+        let device_id = device.id;
+        let modules: ArrayVec<_, 2> = Some(descriptor.vertex.stage.module)
+            .into_iter()
+            .chain(descriptor.fragment.as_ref().map(|frag| frag.stage.module))
+            .collect();
+        let global = &self.0;
+        let sources = wgc::gfx_select!(device_id => global.device_retain_pipeline_sources(
+            device_id,
+            descriptor.layout,
+            &modules,
+            descriptor.cache
+        ));
+
+        let context = Arc::clone(&self);
+        self.1.spawn(move || {
+            let implicit_pipeline_ids = match descriptor.layout {
+                Some(_) => None,
+                None => Some(wgc::device::ImplicitPipelineIds {
+                    root_id: PhantomData,
+                    group_ids: &[PhantomData; wgc::MAX_BIND_GROUPS],
+                }),
+            };
+            let global = &context.0;
+            let (id, error) = wgc::gfx_select!(device_id => global.device_create_pending_render_pipeline(
+                device_id,
+                &descriptor,
+                PhantomData,
+                implicit_pipeline_ids,
+                sources
+            ));
+            match error {
+                Some(cause) => {
+                    if let pipe::CreateRenderPipelineError::Internal { stage, ref error } = cause {
+                        log::error!("Shader translation error for stage {:?}: {}", stage, error);
+                        log::error!("Please report it to https://github.com/gfx-rs/naga");
+                    }
+                    context.render_pipeline_drop(&id);
+                    Err(context.make_error(
+                        cause,
+                        LABEL,
+                        descriptor.label.as_deref(),
+                        "Device::create_render_pipeline_async",
+                    ))
+                }
+                None => Ok(id),
+            }
+        })
+    }
+
+    fn device_create_compute_pipeline_async(
+        self: Arc<Self>,
+        device: &Self::DeviceId,
+        desc: &ComputePipelineDescriptor,
+    ) -> Self::CreateComputePipelineFuture {
+        use wgc::pipeline as pipe;
+
+        let descriptor = pipe::ComputePipelineDescriptor {
+            label: desc.label.map(|label| Owned(label.to_string())),
+            layout: desc.layout.map(|l| l.id),
+            stage: pipe::ProgrammableStageDescriptor {
+                module: desc.module.id.id,
+                entry_point: Owned(desc.entry_point.to_string()),
+                constants: Owned(desc.constants.cloned().unwrap_or_default()),
+            },
+            cache: desc.cache.map(|cache| cache.id),
+        };
+        let device_id = device.id;
+        let global = &self.0;
+        let sources = wgc::gfx_select!(device_id => global.device_retain_pipeline_sources(
+            device_id,
+            descriptor.layout,
+            &[descriptor.stage.module],
+            descriptor.cache
+        ));
+
+        let context = Arc::clone(&self);
+        self.1.spawn(move || {
+            let implicit_pipeline_ids = match descriptor.layout {
+                Some(_) => None,
+                None => Some(wgc::device::ImplicitPipelineIds {
+                    root_id: PhantomData,
+                    group_ids: &[PhantomData; wgc::MAX_BIND_GROUPS],
+                }),
+            };
+            let global = &context.0;
+            let (id, error) = wgc::gfx_select!(device_id => global.device_create_pending_compute_pipeline(
+                device_id,
+                &descriptor,
+                PhantomData,
+                implicit_pipeline_ids,
+                sources
+            ));
+            match error {
+                Some(cause) => {
+                    if let pipe::CreateComputePipelineError::Internal(ref error) = cause {
+                        log::warn!(
+                            "Shader translation error for stage {:?}: {}",
+                            wgt::ShaderStages::COMPUTE,
+                            error
+                        );
+                        log::warn!("Please report it to https://github.com/gfx-rs/naga");
+                    }
+                    context.compute_pipeline_drop(&id);
+                    Err(context.make_error(
+                        cause,
+                        LABEL,
+                        descriptor.label.as_deref(),
+                        "Device::create_compute_pipeline_async",
+                    ))
+                }
+                None => Ok(id),
+            }
+        })
+    }
+
     fn device_create_buffer(
         &self,
         device: &Self::DeviceId,
@@ -1492,7 +1902,7 @@ impl crate::Context for Context {
         let global = &self.0;
         let (id, error) = wgc::gfx_select!(device.id => global.device_create_texture(
             device.id,
-            &desc.map_label(|l| l.map(Borrowed)),
+            &desc.map_label_and_view_formats(|l| l.map(Borrowed), |v| v.to_vec()),
             PhantomData
         ));
         if let Some(cause) = error {
@@ -1649,6 +2059,15 @@ impl crate::Context for Context {
         error_sink.uncaptured_handler = Box::new(handler);
     }
 
+    fn device_enable_shader_hot_reload(&self, device: &Self::DeviceId) {
+        let global = &self.0;
+        if let Err(err) =
+            wgc::gfx_select!(device.id => global.device_enable_shader_hot_reload(device.id))
+        {
+            self.handle_error_fatal(err, "Device::enable_shader_hot_reload");
+        }
+    }
+
     fn device_push_error_scope(&self, device: &Self::DeviceId, filter: crate::ErrorFilter) {
         let mut error_sink = device.error_sink.lock();
         error_sink.scopes.push(ErrorScope {
@@ -1663,6 +2082,44 @@ impl crate::Context for Context {
         ready(scope.error)
     }
 
+    fn shader_module_get_compilation_info(
+        &self,
+        shader_module: &Self::ShaderModuleId,
+    ) -> Self::CompilationInfoFuture {
+        ready(shader_module.compilation_info.clone())
+    }
+
+    fn shader_module_get_vertex_inputs(
+        &self,
+        shader_module: &Self::ShaderModuleId,
+        entry_point: &str,
+    ) -> Option<Vec<wgt::VertexInput>> {
+        let global = &self.0;
+        wgc::gfx_select!(shader_module.id => global.shader_module_vertex_inputs(shader_module.id, entry_point))
+    }
+
+    fn shader_module_replace(
+        &self,
+        shader_module: &Self::ShaderModuleId,
+        desc: ShaderModuleDescriptor,
+    ) -> Result<(), crate::Error> {
+        let global = &self.0;
+        let descriptor = wgc::pipeline::ShaderModuleDescriptor {
+            label: desc.label.map(Borrowed),
+            shader_bound_checks: wgt::ShaderBoundChecks::new(),
+        };
+        let source = shader_module_source(desc.source);
+        wgc::gfx_select!(
+            shader_module.id => global.shader_module_replace(shader_module.id, &descriptor, source)
+        )
+        .map_err(|cause| self.make_error(cause, LABEL, desc.label, "ShaderModule::replace"))
+    }
+
+    fn pipeline_cache_get_data(&self, cache: &Self::PipelineCacheId) -> Option<Vec<u8>> {
+        let global = &self.0;
+        wgc::gfx_select!(*cache => global.pipeline_cache_get_data(*cache))
+    }
+
     fn buffer_map_async<F>(
         &self,
         buffer: &Self::BufferId,
@@ -1817,7 +2274,11 @@ impl crate::Context for Context {
     }
     fn shader_module_drop(&self, shader_module: &Self::ShaderModuleId) {
         let global = &self.0;
-        wgc::gfx_select!(*shader_module => global.shader_module_drop(*shader_module))
+        wgc::gfx_select!(shader_module.id => global.shader_module_drop(shader_module.id))
+    }
+    fn pipeline_cache_drop(&self, cache: &Self::PipelineCacheId) {
+        let global = &self.0;
+        wgc::gfx_select!(*cache => global.pipeline_cache_drop(*cache))
     }
     fn command_encoder_drop(&self, command_encoder: &Self::CommandEncoderId) {
         if command_encoder.open {
@@ -2070,6 +2531,7 @@ impl crate::Context for Context {
                 label: desc.label.map(Borrowed),
                 color_attachments: Borrowed(&colors),
                 depth_stencil_attachment: depth_stencil.as_ref(),
+                occlusion_query_set: desc.occlusion_query_set.map(|query_set| query_set.id),
             },
         )
     }
@@ -2259,6 +2721,27 @@ impl crate::Context for Context {
         }
     }
 
+    fn queue_write_staging_texture(
+        &self,
+        queue: &Self::QueueId,
+        texture: crate::ImageCopyTexture,
+        data_layout: wgt::ImageDataLayout,
+        size: wgt::Extent3d,
+        staging_buffer: &QueueWriteBuffer,
+    ) {
+        let global = &self.0;
+        match wgc::gfx_select!(*queue => global.queue_write_staging_texture(
+            *queue,
+            &map_texture_copy_view(texture),
+            &data_layout,
+            &size,
+            staging_buffer.buffer_id
+        )) {
+            Ok(()) => (),
+            Err(err) => self.handle_error_fatal(err, "Queue::write_texture_with"),
+        }
+    }
+
     fn queue_write_texture(
         &self,
         queue: &Self::QueueId,
@@ -7,3 +7,5 @@ pub(crate) use web::{BufferMappedRange, Context, QueueWriteBuffer};
 mod direct;
 #[cfg(any(not(target_arch = "wasm32"), feature = "webgl"))]
 pub(crate) use direct::{BufferMappedRange, Context, QueueWriteBuffer};
+#[cfg(any(not(target_arch = "wasm32"), feature = "webgl"))]
+mod worker;
@@ -3,11 +3,13 @@
 use js_sys::Promise;
 use std::{
     cell::RefCell,
+    collections::HashMap,
     fmt,
     future::Future,
     ops::Range,
     pin::Pin,
     rc::Rc,
+    sync::Arc,
     task::{self, Poll},
 };
 use wasm_bindgen::{prelude::*, JsCast};
@@ -25,6 +27,13 @@ pub(crate) struct Sendable<T>(T);
 unsafe impl<T> Send for Sendable<T> {}
 unsafe impl<T> Sync for Sendable<T> {}
 
+/// Shader module, along with the source map of preprocessed sources.
+#[derive(Debug)]
+pub(crate) struct ShaderModule {
+    module: web_sys::GpuShaderModule,
+    source_map: Option<Arc<crate::ShaderSourceMap>>,
+}
+
 pub(crate) struct Context(web_sys::Gpu);
 unsafe impl Send for Context {}
 unsafe impl Sync for Context {}
@@ -299,6 +308,33 @@ impl crate::RenderInner<Context> for RenderPass {
     ) {
         panic!("MULTI_DRAW_INDIRECT_COUNT feature must be enabled to call multi_draw_indexed_indirect_count")
     }
+
+    fn insert_debug_marker(&mut self, _label: &str) {
+        // Not available in gecko yet
+        // self.0.insert_debug_marker(label);
+    }
+
+    fn push_debug_group(&mut self, _group_label: &str) {
+        // Not available in gecko yet
+        // self.0.push_debug_group(group_label);
+    }
+
+    fn pop_debug_group(&mut self) {
+        // Not available in gecko yet
+        // self.0.pop_debug_group();
+    }
+
+    fn write_timestamp(&mut self, _query_set: &(), _query_index: u32) {
+        panic!("WRITE_TIMESTAMP_INSIDE_PASSES feature must be enabled to call write_timestamp in a render pass")
+    }
+
+    fn begin_pipeline_statistics_query(&mut self, _query_set: &(), _query_index: u32) {
+        // Not available in gecko yet
+    }
+
+    fn end_pipeline_statistics_query(&mut self) {
+        // Not available in gecko yet
+    }
 }
 
 impl crate::RenderInner<Context> for RenderBundleEncoder {
@@ -443,6 +479,33 @@ impl crate::RenderInner<Context> for RenderBundleEncoder {
     ) {
         panic!("MULTI_DRAW_INDIRECT_COUNT feature must be enabled to call multi_draw_indexed_indirect_count")
     }
+
+    fn insert_debug_marker(&mut self, _label: &str) {
+        // Not available in gecko yet
+        // self.0.insert_debug_marker(label);
+    }
+
+    fn push_debug_group(&mut self, _group_label: &str) {
+        // Not available in gecko yet
+        // self.0.push_debug_group(group_label);
+    }
+
+    fn pop_debug_group(&mut self) {
+        // Not available in gecko yet
+        // self.0.pop_debug_group();
+    }
+
+    fn write_timestamp(&mut self, _query_set: &(), _query_index: u32) {
+        panic!("WRITE_TIMESTAMP_INSIDE_PASSES feature must be enabled to call write_timestamp in a render bundle")
+    }
+
+    fn begin_pipeline_statistics_query(&mut self, _query_set: &(), _query_index: u32) {
+        // Not available in gecko yet
+    }
+
+    fn end_pipeline_statistics_query(&mut self) {
+        // Not available in gecko yet
+    }
 }
 
 impl crate::RenderPassInner<Context> for RenderPass {
@@ -469,21 +532,6 @@ impl crate::RenderPassInner<Context> for RenderPass {
         self.0.set_stencil_reference(reference);
     }
 
-    fn insert_debug_marker(&mut self, _label: &str) {
-        // Not available in gecko yet
-        // self.0.insert_debug_marker(label);
-    }
-
-    fn push_debug_group(&mut self, _group_label: &str) {
-        // Not available in gecko yet
-        // self.0.push_debug_group(group_label);
-    }
-
-    fn pop_debug_group(&mut self) {
-        // Not available in gecko yet
-        // self.0.pop_debug_group();
-    }
-
     fn execute_bundles<'a, I: Iterator<Item = &'a Sendable<web_sys::GpuRenderBundle>>>(
         &mut self,
         render_bundles: I,
@@ -494,15 +542,11 @@ impl crate::RenderPassInner<Context> for RenderPass {
         self.0.execute_bundles(&mapped);
     }
 
-    fn write_timestamp(&mut self, _query_set: &(), _query_index: u32) {
-        panic!("WRITE_TIMESTAMP_INSIDE_PASSES feature must be enabled to call write_timestamp in a compute pass")
-    }
-
-    fn begin_pipeline_statistics_query(&mut self, _query_set: &(), _query_index: u32) {
+    fn begin_occlusion_query(&mut self, _query_index: u32) {
         // Not available in gecko yet
     }
 
-    fn end_pipeline_statistics_query(&mut self) {
+    fn end_occlusion_query(&mut self) {
         // Not available in gecko yet
     }
 }
@@ -588,6 +632,19 @@ fn map_front_face(front_face: wgt::FrontFace) -> web_sys::GpuFrontFace {
     }
 }
 
+// `web-sys` doesn't expose `GPUProgrammableStage.constants` yet.
+fn set_pipeline_constants(stage: &js_sys::Object, constants: Option<&HashMap<String, f64>>) {
+    let constants = match constants {
+        Some(constants) if !constants.is_empty() => constants,
+        _ => return,
+    };
+    let mapped = js_sys::Object::new();
+    for (key, &value) in constants.iter() {
+        js_sys::Reflect::set(&mapped, &JsValue::from_str(key), &JsValue::from_f64(value)).unwrap();
+    }
+    js_sys::Reflect::set(stage, &JsValue::from_str("constants"), &mapped).unwrap();
+}
+
 fn map_primitive_state(primitive: &wgt::PrimitiveState) -> web_sys::GpuPrimitiveState {
     use web_sys::GpuPrimitiveTopology as pt;
     use wgt::PrimitiveTopology;
@@ -906,6 +963,180 @@ fn future_pop_error_scope(result: JsFutureResult) -> Option<crate::Error> {
     }
 }
 
+fn map_render_pipeline_descriptor(
+    desc: &crate::RenderPipelineDescriptor,
+) -> web_sys::GpuRenderPipelineDescriptor {
+    let mut mapped_vertex_state =
+        web_sys::GpuVertexState::new(desc.vertex.entry_point, &desc.vertex.module.id.0.module);
+    set_pipeline_constants(&mapped_vertex_state, desc.vertex.constants);
+
+    let buffers = desc
+        .vertex
+        .buffers
+        .iter()
+        .map(|vbuf| {
+            let mapped_attributes = vbuf
+                .attributes
+                .iter()
+                .map(|attr| {
+                    web_sys::GpuVertexAttribute::new(
+                        map_vertex_format(attr.format),
+                        attr.offset as f64,
+                        attr.shader_location,
+                    )
+                })
+                .collect::<js_sys::Array>();
+
+            let mut mapped_vbuf =
+                web_sys::GpuVertexBufferLayout::new(vbuf.array_stride as f64, &mapped_attributes);
+            mapped_vbuf.step_mode(map_vertex_step_mode(vbuf.step_mode));
+            mapped_vbuf
+        })
+        .collect::<js_sys::Array>();
+
+    mapped_vertex_state.buffers(&buffers);
+
+    let auto_layout = wasm_bindgen::JsValue::from(web_sys::GpuAutoLayoutMode::Auto);
+    let mut mapped_desc = web_sys::GpuRenderPipelineDescriptor::new(
+        match desc.layout {
+            Some(layout) => &layout.id.0,
+            None => &auto_layout,
+        },
+        &mapped_vertex_state,
+    );
+
+    if let Some(label) = desc.label {
+        mapped_desc.label(label);
+    }
+
+    if let Some(ref depth_stencil) = desc.depth_stencil {
+        mapped_desc.depth_stencil(&map_depth_stencil_state(depth_stencil));
+    }
+
+    if let Some(ref frag) = desc.fragment {
+        let targets = frag
+            .targets
+            .iter()
+            .map(|target| match target {
+                Some(target) => {
+                    let mapped_format = map_texture_format(target.format);
+                    let mut mapped_color_state = web_sys::GpuColorTargetState::new(mapped_format);
+                    if let Some(ref bs) = target.blend {
+                        let alpha = map_blend_component(&bs.alpha);
+                        let color = map_blend_component(&bs.color);
+                        let mapped_blend_state = web_sys::GpuBlendState::new(&alpha, &color);
+                        mapped_color_state.blend(&mapped_blend_state);
+                    }
+                    mapped_color_state.write_mask(target.write_mask.bits());
+                    wasm_bindgen::JsValue::from(mapped_color_state)
+                }
+                None => wasm_bindgen::JsValue::null(),
+            })
+            .collect::<js_sys::Array>();
+        let mapped_fragment_desc =
+            web_sys::GpuFragmentState::new(frag.entry_point, &frag.module.id.0.module, &targets);
+        set_pipeline_constants(&mapped_fragment_desc, frag.constants);
+        mapped_desc.fragment(&mapped_fragment_desc);
+    }
+
+    let mut mapped_multisample = web_sys::GpuMultisampleState::new();
+    mapped_multisample.count(desc.multisample.count);
+    mapped_multisample.mask(desc.multisample.mask as u32);
+    mapped_multisample.alpha_to_coverage_enabled(desc.multisample.alpha_to_coverage_enabled);
+    mapped_desc.multisample(&mapped_multisample);
+
+    let mapped_primitive = map_primitive_state(&desc.primitive);
+    mapped_desc.primitive(&mapped_primitive);
+
+    mapped_desc
+}
+
+fn map_compute_pipeline_descriptor(
+    desc: &crate::ComputePipelineDescriptor,
+) -> web_sys::GpuComputePipelineDescriptor {
+    let mapped_compute_stage =
+        web_sys::GpuProgrammableStage::new(desc.entry_point, &desc.module.id.0.module);
+    set_pipeline_constants(&mapped_compute_stage, desc.constants);
+    let auto_layout = wasm_bindgen::JsValue::from(web_sys::GpuAutoLayoutMode::Auto);
+    let mut mapped_desc = web_sys::GpuComputePipelineDescriptor::new(
+        match desc.layout {
+            Some(layout) => &layout.id.0,
+            None => &auto_layout,
+        },
+        &mapped_compute_stage,
+    );
+    if let Some(label) = desc.label {
+        mapped_desc.label(label);
+    }
+    mapped_desc
+}
+
+fn map_pipeline_error(js_error: JsValue) -> crate::Error {
+    let description = match js_error.dyn_ref::<js_sys::Error>() {
+        Some(error) => String::from(error.message()),
+        None => format!("{:?}", js_error),
+    };
+    crate::Error::Validation {
+        source: Box::<dyn std::error::Error + Send + Sync>::from("<WebGPU Error>"),
+        description,
+    }
+}
+
+fn future_create_render_pipeline(
+    result: JsFutureResult,
+) -> Result<Sendable<web_sys::GpuRenderPipeline>, crate::Error> {
+    result
+        .map(|js_value| Sendable(web_sys::GpuRenderPipeline::from(js_value)))
+        .map_err(map_pipeline_error)
+}
+
+fn future_create_compute_pipeline(
+    result: JsFutureResult,
+) -> Result<Sendable<web_sys::GpuComputePipeline>, crate::Error> {
+    result
+        .map(|js_value| Sendable(web_sys::GpuComputePipeline::from(js_value)))
+        .map_err(map_pipeline_error)
+}
+
+fn future_compilation_info(result: JsFutureResult) -> crate::CompilationInfo {
+    let info = match result {
+        Ok(js_value) => web_sys::GpuCompilationInfo::from(js_value),
+        Err(_) => return crate::CompilationInfo::default(),
+    };
+    let messages = info
+        .messages()
+        .iter()
+        .map(|js_value| {
+            let message = web_sys::GpuCompilationMessage::from(js_value);
+            let message_type = match message.type_() {
+                web_sys::GpuCompilationMessageType::Error => crate::CompilationMessageType::Error,
+                web_sys::GpuCompilationMessageType::Warning => {
+                    crate::CompilationMessageType::Warning
+                }
+                _ => crate::CompilationMessageType::Info,
+            };
+            // A line number of 0 means the message doesn't refer to a specific position.
+            let location = if message.line_num() > 0.0 {
+                Some(crate::SourceLocation {
+                    line_number: message.line_num() as u32,
+                    line_position: message.line_pos() as u32,
+                    offset: message.offset() as u32,
+                    length: message.length() as u32,
+                    file: None,
+                })
+            } else {
+                None
+            };
+            crate::CompilationMessage {
+                message: message.message(),
+                message_type,
+                location,
+            }
+        })
+        .collect();
+    crate::CompilationInfo { messages }
+}
+
 /// Calls `callback(success_value)` when the promise completes successfully, calls `callback(failure_value)`
 /// when the promise completes unsuccessfully.
 fn register_then_closures<F, T>(promise: &Promise, callback: F, success_value: T, failure_value: T)
@@ -982,7 +1213,7 @@ impl crate::Context for Context {
     type AdapterId = Sendable<web_sys::GpuAdapter>;
     type DeviceId = Sendable<web_sys::GpuDevice>;
     type QueueId = Sendable<web_sys::GpuQueue>;
-    type ShaderModuleId = Sendable<web_sys::GpuShaderModule>;
+    type ShaderModuleId = Sendable<ShaderModule>;
     type BindGroupLayoutId = Sendable<web_sys::GpuBindGroupLayout>;
     type BindGroupId = Sendable<web_sys::GpuBindGroup>;
     type TextureViewId = Sendable<web_sys::GpuTextureView>;
@@ -993,6 +1224,7 @@ impl crate::Context for Context {
     type PipelineLayoutId = Sendable<web_sys::GpuPipelineLayout>;
     type RenderPipelineId = Sendable<web_sys::GpuRenderPipeline>;
     type ComputePipelineId = Sendable<web_sys::GpuComputePipeline>;
+    type PipelineCacheId = ();
     type CommandEncoderId = web_sys::GpuCommandEncoder;
     type ComputePassId = ComputePass;
     type RenderPassId = RenderPass;
@@ -1014,6 +1246,18 @@ impl crate::Context for Context {
     >;
     type PopErrorScopeFuture =
         MakeSendFuture<wasm_bindgen_futures::JsFuture, fn(JsFutureResult) -> Option<crate::Error>>;
+    type CompilationInfoFuture = MakeSendFuture<
+        wasm_bindgen_futures::JsFuture,
+        Box<dyn Fn(JsFutureResult) -> crate::CompilationInfo>,
+    >;
+    type CreateRenderPipelineFuture = MakeSendFuture<
+        wasm_bindgen_futures::JsFuture,
+        fn(JsFutureResult) -> Result<Self::RenderPipelineId, crate::Error>,
+    >;
+    type CreateComputePipelineFuture = MakeSendFuture<
+        wasm_bindgen_futures::JsFuture,
+        fn(JsFutureResult) -> Result<Self::ComputePipelineId, crate::Error>,
+    >;
 
     fn init(_backends: wgt::Backends) -> Self {
         Context(web_sys::window().unwrap().navigator().gpu())
@@ -1075,6 +1319,10 @@ impl crate::Context for Context {
         true
     }
 
+    fn instance_set_worker_thread_count(&self, _count: usize) {
+        // The browser schedules asynchronous work itself.
+    }
+
     fn adapter_request_device(
         &self,
         adapter: &Self::AdapterId,
@@ -1221,11 +1469,24 @@ impl crate::Context for Context {
         vec![wgt::PresentMode::Fifo]
     }
 
+    fn surface_get_supported_alpha_modes(
+        &self,
+        _surface: &Self::SurfaceId,
+        _adapter: &Self::AdapterId,
+    ) -> Vec<wgt::CompositeAlphaMode> {
+        // Canvas contexts only have opaque and premultiplied compositing, so these are the only
+        // modes `surface_configure` can map. Never report anything else here.
+        vec![
+            wgt::CompositeAlphaMode::Opaque,
+            wgt::CompositeAlphaMode::PreMultiplied,
+        ]
+    }
+
     fn surface_configure(
         &self,
         surface: &Self::SurfaceId,
         device: &Self::DeviceId,
-        config: &wgt::SurfaceConfiguration,
+        config: &crate::SurfaceConfiguration,
     ) {
         if let wgt::PresentMode::Mailbox | wgt::PresentMode::Immediate = config.present_mode {
             panic!("Only FIFO/Auto* is supported on web");
@@ -1233,6 +1494,24 @@ impl crate::Context for Context {
         let mut mapped =
             web_sys::GpuCanvasConfiguration::new(&device.0, map_texture_format(config.format));
         mapped.usage(config.usage.bits());
+        mapped.compositing_alpha_mode(match config.alpha_mode {
+            wgt::CompositeAlphaMode::Auto | wgt::CompositeAlphaMode::Opaque => {
+                web_sys::GpuCanvasCompositingAlphaMode::Opaque
+            }
+            wgt::CompositeAlphaMode::PreMultiplied => {
+                web_sys::GpuCanvasCompositingAlphaMode::Premultiplied
+            }
+            wgt::CompositeAlphaMode::PostMultiplied | wgt::CompositeAlphaMode::Inherit => panic!(
+                "Alpha mode {:?} is not one of the supported modes reported for web surfaces",
+                config.alpha_mode
+            ),
+        });
+        let mapped_view_formats = config
+            .view_formats
+            .iter()
+            .map(|format| wasm_bindgen::JsValue::from(map_texture_format(*format)))
+            .collect::<js_sys::Array>();
+        mapped.view_formats(&mapped_view_formats);
         surface.0.configure(&mapped);
     }
 
@@ -1284,6 +1563,7 @@ impl crate::Context for Context {
         desc: crate::ShaderModuleDescriptor,
         _shader_bound_checks: wgt::ShaderBoundChecks,
     ) -> Self::ShaderModuleId {
+        let mut source_map = None;
         let mut descriptor = match desc.source {
             #[cfg(feature = "spirv")]
             crate::ShaderSource::SpirV(ref spv) => {
@@ -1337,6 +1617,13 @@ impl crate::Context for Context {
                 web_sys::GpuShaderModuleDescriptor::new(wgsl_text.as_str())
             }
             crate::ShaderSource::Wgsl(ref code) => web_sys::GpuShaderModuleDescriptor::new(code),
+            crate::ShaderSource::WgslPreprocessed {
+                ref source,
+                source_map: ref map,
+            } => {
+                source_map = Some(Arc::new(map.clone()));
+                web_sys::GpuShaderModuleDescriptor::new(source)
+            }
             #[cfg(feature = "naga")]
             crate::ShaderSource::Naga(module) => {
                 use naga::{back, valid};
@@ -1356,7 +1643,10 @@ impl crate::Context for Context {
         if let Some(label) = desc.label {
             descriptor.label(label);
         }
-        Sendable(device.0.create_shader_module(&descriptor))
+        Sendable(ShaderModule {
+            module: device.0.create_shader_module(&descriptor),
+            source_map,
+        })
     }
 
     fn device_create_bind_group_layout(
@@ -1465,6 +1755,14 @@ impl crate::Context for Context {
         unreachable!("SPIRV_SHADER_PASSTHROUGH is not enabled for this backend")
     }
 
+    unsafe fn device_create_pipeline_cache(
+        &self,
+        _device: &Self::DeviceId,
+        _desc: &crate::PipelineCacheDescriptor,
+    ) -> Self::PipelineCacheId {
+        // The browser manages its own caches.
+    }
+
     fn device_create_bind_group(
         &self,
         device: &Self::DeviceId,
@@ -1537,89 +1835,7 @@ impl crate::Context for Context {
         device: &Self::DeviceId,
         desc: &crate::RenderPipelineDescriptor,
     ) -> Self::RenderPipelineId {
-        let mut mapped_vertex_state =
-            web_sys::GpuVertexState::new(desc.vertex.entry_point, &desc.vertex.module.id.0);
-
-        let buffers = desc
-            .vertex
-            .buffers
-            .iter()
-            .map(|vbuf| {
-                let mapped_attributes = vbuf
-                    .attributes
-                    .iter()
-                    .map(|attr| {
-                        web_sys::GpuVertexAttribute::new(
-                            map_vertex_format(attr.format),
-                            attr.offset as f64,
-                            attr.shader_location,
-                        )
-                    })
-                    .collect::<js_sys::Array>();
-
-                let mut mapped_vbuf = web_sys::GpuVertexBufferLayout::new(
-                    vbuf.array_stride as f64,
-                    &mapped_attributes,
-                );
-                mapped_vbuf.step_mode(map_vertex_step_mode(vbuf.step_mode));
-                mapped_vbuf
-            })
-            .collect::<js_sys::Array>();
-
-        mapped_vertex_state.buffers(&buffers);
-
-        let auto_layout = wasm_bindgen::JsValue::from(web_sys::GpuAutoLayoutMode::Auto);
-        let mut mapped_desc = web_sys::GpuRenderPipelineDescriptor::new(
-            match desc.layout {
-                Some(layout) => &layout.id.0,
-                None => &auto_layout,
-            },
-            &mapped_vertex_state,
-        );
-
-        if let Some(label) = desc.label {
-            mapped_desc.label(label);
-        }
-
-        if let Some(ref depth_stencil) = desc.depth_stencil {
-            mapped_desc.depth_stencil(&map_depth_stencil_state(depth_stencil));
-        }
-
-        if let Some(ref frag) = desc.fragment {
-            let targets = frag
-                .targets
-                .iter()
-                .map(|target| match target {
-                    Some(target) => {
-                        let mapped_format = map_texture_format(target.format);
-                        let mut mapped_color_state =
-                            web_sys::GpuColorTargetState::new(mapped_format);
-                        if let Some(ref bs) = target.blend {
-                            let alpha = map_blend_component(&bs.alpha);
-                            let color = map_blend_component(&bs.color);
-                            let mapped_blend_state = web_sys::GpuBlendState::new(&alpha, &color);
-                            mapped_color_state.blend(&mapped_blend_state);
-                        }
-                        mapped_color_state.write_mask(target.write_mask.bits());
-                        wasm_bindgen::JsValue::from(mapped_color_state)
-                    }
-                    None => wasm_bindgen::JsValue::null(),
-                })
-                .collect::<js_sys::Array>();
-            let mapped_fragment_desc =
-                web_sys::GpuFragmentState::new(frag.entry_point, &frag.module.id.0, &targets);
-            mapped_desc.fragment(&mapped_fragment_desc);
-        }
-
-        let mut mapped_multisample = web_sys::GpuMultisampleState::new();
-        mapped_multisample.count(desc.multisample.count);
-        mapped_multisample.mask(desc.multisample.mask as u32);
-        mapped_multisample.alpha_to_coverage_enabled(desc.multisample.alpha_to_coverage_enabled);
-        mapped_desc.multisample(&mapped_multisample);
-
-        let mapped_primitive = map_primitive_state(&desc.primitive);
-        mapped_desc.primitive(&mapped_primitive);
-
+        let mapped_desc = map_render_pipeline_descriptor(desc);
         Sendable(device.0.create_render_pipeline(&mapped_desc))
     }
 
@@ -1628,22 +1844,36 @@ impl crate::Context for Context {
         device: &Self::DeviceId,
         desc: &crate::ComputePipelineDescriptor,
     ) -> Self::ComputePipelineId {
-        let mapped_compute_stage =
-            web_sys::GpuProgrammableStage::new(desc.entry_point, &desc.module.id.0);
-        let auto_layout = wasm_bindgen::JsValue::from(web_sys::GpuAutoLayoutMode::Auto);
-        let mut mapped_desc = web_sys::GpuComputePipelineDescriptor::new(
-            match desc.layout {
-                Some(layout) => &layout.id.0,
-                None => &auto_layout,
-            },
-            &mapped_compute_stage,
-        );
-        if let Some(label) = desc.label {
-            mapped_desc.label(label);
-        }
+        let mapped_desc = map_compute_pipeline_descriptor(desc);
         Sendable(device.0.create_compute_pipeline(&mapped_desc))
     }
 
+    fn device_create_render_pipeline_async(
+        self: Arc<Self>,
+        device: &Self::DeviceId,
+        desc: &crate::RenderPipelineDescriptor,
+    ) -> Self::CreateRenderPipelineFuture {
+        let mapped_desc = map_render_pipeline_descriptor(desc);
+        let pipeline_promise = device.0.create_render_pipeline_async(&mapped_desc);
+        MakeSendFuture::new(
+            wasm_bindgen_futures::JsFuture::from(pipeline_promise),
+            future_create_render_pipeline,
+        )
+    }
+
+    fn device_create_compute_pipeline_async(
+        self: Arc<Self>,
+        device: &Self::DeviceId,
+        desc: &crate::ComputePipelineDescriptor,
+    ) -> Self::CreateComputePipelineFuture {
+        let mapped_desc = map_compute_pipeline_descriptor(desc);
+        let pipeline_promise = device.0.create_compute_pipeline_async(&mapped_desc);
+        MakeSendFuture::new(
+            wasm_bindgen_futures::JsFuture::from(pipeline_promise),
+            future_create_compute_pipeline,
+        )
+    }
+
     fn device_create_buffer(
         &self,
         device: &Self::DeviceId,
@@ -1674,6 +1904,12 @@ impl crate::Context for Context {
         mapped_desc.dimension(map_texture_dimension(desc.dimension));
         mapped_desc.mip_level_count(desc.mip_level_count);
         mapped_desc.sample_count(desc.sample_count);
+        let mapped_view_formats = desc
+            .view_formats
+            .iter()
+            .map(|format| wasm_bindgen::JsValue::from(map_texture_format(*format)))
+            .collect::<js_sys::Array>();
+        mapped_desc.view_formats(&mapped_view_formats);
         Sendable(device.0.create_texture(&mapped_desc))
     }
 
@@ -1772,6 +2008,10 @@ impl crate::Context for Context {
         f.forget();
     }
 
+    fn device_enable_shader_hot_reload(&self, _device: &Self::DeviceId) {
+        // Shader modules can't be replaced on the web.
+    }
+
     fn device_push_error_scope(&self, device: &Self::DeviceId, filter: crate::ErrorFilter) {
         device.0.push_error_scope(match filter {
             crate::ErrorFilter::OutOfMemory => web_sys::GpuErrorFilter::OutOfMemory,
@@ -1787,6 +2027,48 @@ impl crate::Context for Context {
         )
     }
 
+    fn shader_module_get_compilation_info(
+        &self,
+        shader_module: &Self::ShaderModuleId,
+    ) -> Self::CompilationInfoFuture {
+        let info_promise = shader_module.0.module.compilation_info();
+        let source_map = shader_module.0.source_map.clone();
+        MakeSendFuture::new(
+            wasm_bindgen_futures::JsFuture::from(info_promise),
+            Box::new(move |result| {
+                let info = future_compilation_info(result);
+                match source_map {
+                    Some(ref map) => map.map_compilation_info(info),
+                    None => info,
+                }
+            }),
+        )
+    }
+
+    fn shader_module_get_vertex_inputs(
+        &self,
+        _shader_module: &Self::ShaderModuleId,
+        _entry_point: &str,
+    ) -> Option<Vec<wgt::VertexInput>> {
+        None
+    }
+
+    fn shader_module_replace(
+        &self,
+        _shader_module: &Self::ShaderModuleId,
+        _desc: crate::ShaderModuleDescriptor,
+    ) -> Result<(), crate::Error> {
+        let description = "Shader modules can't be replaced on the web".to_string();
+        Err(crate::Error::Validation {
+            source: Box::<dyn std::error::Error + Send + Sync>::from(description.clone()),
+            description,
+        })
+    }
+
+    fn pipeline_cache_get_data(&self, _cache: &Self::PipelineCacheId) -> Option<Vec<u8>> {
+        None
+    }
+
     fn buffer_map_async<F>(
         &self,
         buffer: &Self::BufferId,
@@ -1905,6 +2187,8 @@ impl crate::Context for Context {
         // Dropped automatically
     }
 
+    fn pipeline_cache_drop(&self, _cache: &Self::PipelineCacheId) {}
+
     fn command_encoder_drop(&self, _command_encoder: &Self::CommandEncoderId) {
         // Dropped automatically
     }
@@ -2242,6 +2526,17 @@ impl crate::Context for Context {
         self.queue_write_buffer(queue, buffer, offset, staging_buffer)
     }
 
+    fn queue_write_staging_texture(
+        &self,
+        queue: &Self::QueueId,
+        texture: crate::ImageCopyTexture,
+        data_layout: wgt::ImageDataLayout,
+        size: wgt::Extent3d,
+        staging_buffer: &QueueWriteBuffer,
+    ) {
+        self.queue_write_texture(queue, texture, &staging_buffer.0, data_layout, size)
+    }
+
     fn queue_write_texture(
         &self,
         queue: &Self::QueueId,
@@ -0,0 +1,141 @@
+//! Worker threads running the asynchronous parts of the direct backend,
+//! such as `Device::create_render_pipeline_async`.
+
+use parking_lot::Mutex;
+use std::{
+    future::Future,
+    panic::{self, AssertUnwindSafe},
+    pin::Pin,
+    sync::{mpsc, Arc},
+    task::{self, Poll, Waker},
+    thread,
+};
+
+type Job = Box<dyn FnOnce() + Send>;
+
+#[derive(Debug)]
+struct PoolState {
+    thread_count: usize,
+    /// Started lazily, on the first job.
+    sender: Option<mpsc::Sender<Job>>,
+}
+
+#[derive(Debug)]
+pub(crate) struct WorkerPool {
+    state: Mutex<PoolState>,
+}
+
+impl WorkerPool {
+    pub fn new() -> Self {
+        let thread_count = thread::available_parallelism()
+            .map(|count| count.get())
+            .unwrap_or(1);
+        Self {
+            state: Mutex::new(PoolState {
+                thread_count,
+                sender: None,
+            }),
+        }
+    }
+
+    /// Sets the number of worker threads.
+    ///
+    /// Threads of the previous pool finish the jobs that are already queued, then exit.
+    pub fn set_thread_count(&self, count: usize) {
+        let mut state = self.state.lock();
+        state.thread_count = count.max(1);
+        state.sender = None;
+    }
+
+    /// Runs `job` on a worker thread, returning a future resolving to its result.
+    pub fn spawn<T, F>(&self, job: F) -> JobFuture<T>
+    where
+        T: Send + 'static,
+        F: FnOnce() -> T + Send + 'static,
+    {
+        let shared = Arc::new(Mutex::new(JobState {
+            result: None,
+            waker: None,
+        }));
+        let job_shared = Arc::clone(&shared);
+        let job: Job = Box::new(move || {
+            let result = panic::catch_unwind(AssertUnwindSafe(job));
+            let waker = {
+                let mut state = job_shared.lock();
+                state.result = Some(result);
+                state.waker.take()
+            };
+            if let Some(waker) = waker {
+                waker.wake();
+            }
+        });
+
+        // There are no threads on the web, run the job right away.
+        if cfg!(target_arch = "wasm32") {
+            job();
+            return JobFuture { shared };
+        }
+
+        let mut state = self.state.lock();
+        let thread_count = state.thread_count;
+        let sender = state
+            .sender
+            .get_or_insert_with(|| Self::start(thread_count));
+        if let Err(mpsc::SendError(job)) = sender.send(job) {
+            // All the workers are gone, start a new pool.
+            let sender = Self::start(thread_count);
+            sender.send(job).unwrap();
+            state.sender = Some(sender);
+        }
+        JobFuture { shared }
+    }
+
+    fn start(thread_count: usize) -> mpsc::Sender<Job> {
+        let (sender, receiver) = mpsc::channel::<Job>();
+        let receiver = Arc::new(Mutex::new(receiver));
+        for index in 0..thread_count {
+            let receiver = Arc::clone(&receiver);
+            thread::Builder::new()
+                .name(format!("wgpu worker {}", index))
+                .spawn(move || loop {
+                    let job = receiver.lock().recv();
+                    match job {
+                        Ok(job) => job(),
+                        Err(mpsc::RecvError) => break,
+                    }
+                })
+                .expect("failed to spawn a worker thread");
+        }
+        sender
+    }
+}
+
+#[derive(Debug)]
+struct JobState<T> {
+    result: Option<thread::Result<T>>,
+    waker: Option<Waker>,
+}
+
+/// Result of a job running on a [`WorkerPool`].
+///
+/// A panic inside the job is resumed when the future is polled.
+#[derive(Debug)]
+pub(crate) struct JobFuture<T> {
+    shared: Arc<Mutex<JobState<T>>>,
+}
+
+impl<T> Future for JobFuture<T> {
+    type Output = T;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut task::Context) -> Poll<T> {
+        let mut state = self.shared.lock();
+        match state.result.take() {
+            Some(Ok(value)) => Poll::Ready(value),
+            Some(Err(payload)) => panic::resume_unwind(payload),
+            None => {
+                state.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
@@ -12,7 +12,9 @@ pub mod util;
 mod macros;
 
 use std::{
+    any::Any,
     borrow::Cow,
+    collections::HashMap,
     error,
     fmt::{Debug, Display},
     future::Future,
@@ -29,16 +31,17 @@ pub use wgt::{
     AdapterInfo, AddressMode, AstcBlock, AstcChannel, Backend, Backends, BindGroupLayoutEntry,
     BindingType, BlendComponent, BlendFactor, BlendOperation, BlendState, BufferAddress,
     BufferBindingType, BufferSize, BufferUsages, Color, ColorTargetState, ColorWrites,
-    CommandBufferDescriptor, CompareFunction, DepthBiasState, DepthStencilState, DeviceType,
+    CommandBufferDescriptor, CompareFunction, CompilationInfo, CompilationMessage,
+    CompilationMessageType, CompositeAlphaMode, DepthBiasState, DepthStencilState, DeviceType,
     DownlevelCapabilities, DownlevelFlags, DynamicOffset, Extent3d, Face, Features, FilterMode,
     FrontFace, ImageDataLayout, ImageSubresourceRange, IndexFormat, Limits, MultisampleState,
     Origin3d, PipelineStatisticsTypes, PolygonMode, PowerPreference, PresentMode, PrimitiveState,
     PrimitiveTopology, PushConstantRange, QueryType, RenderBundleDepthStencil, SamplerBindingType,
-    SamplerBorderColor, ShaderLocation, ShaderModel, ShaderStages, StencilFaceState,
-    StencilOperation, StencilState, StorageTextureAccess, SurfaceConfiguration, SurfaceStatus,
-    TextureAspect, TextureDimension, TextureFormat, TextureFormatFeatureFlags,
+    SamplerBorderColor, ShaderLocation, ShaderModel, ShaderSourceMap, ShaderStages, SourceLine,
+    SourceLocation, StencilFaceState, StencilOperation, StencilState, StorageTextureAccess,
+    SurfaceStatus, TextureAspect, TextureDimension, TextureFormat, TextureFormatFeatureFlags,
     TextureFormatFeatures, TextureSampleType, TextureUsages, TextureViewDimension, VertexAttribute,
-    VertexFormat, VertexStepMode, COPY_BUFFER_ALIGNMENT, COPY_BYTES_PER_ROW_ALIGNMENT,
+    VertexFormat, VertexInput, VertexStepMode, COPY_BUFFER_ALIGNMENT, COPY_BYTES_PER_ROW_ALIGNMENT,
     MAP_ALIGNMENT, PUSH_CONSTANT_ALIGNMENT, QUERY_RESOLVE_BUFFER_ALIGNMENT, QUERY_SET_MAX_QUERIES,
     QUERY_SIZE, VERTEX_STRIDE_ALIGNMENT,
 };
@@ -136,6 +139,12 @@ trait RenderInner<Ctx: Context> {
         count_buffer_offset: BufferAddress,
         max_count: u32,
     );
+    fn insert_debug_marker(&mut self, label: &str);
+    fn push_debug_group(&mut self, group_label: &str);
+    fn pop_debug_group(&mut self);
+    fn write_timestamp(&mut self, query_set: &Ctx::QuerySetId, query_index: u32);
+    fn begin_pipeline_statistics_query(&mut self, query_set: &Ctx::QuerySetId, query_index: u32);
+    fn end_pipeline_statistics_query(&mut self);
 }
 
 trait RenderPassInner<Ctx: Context>: RenderInner<Ctx> {
@@ -151,16 +160,12 @@ trait RenderPassInner<Ctx: Context>: RenderInner<Ctx> {
         max_depth: f32,
     );
     fn set_stencil_reference(&mut self, reference: u32);
-    fn insert_debug_marker(&mut self, label: &str);
-    fn push_debug_group(&mut self, group_label: &str);
-    fn pop_debug_group(&mut self);
-    fn write_timestamp(&mut self, query_set: &Ctx::QuerySetId, query_index: u32);
-    fn begin_pipeline_statistics_query(&mut self, query_set: &Ctx::QuerySetId, query_index: u32);
-    fn end_pipeline_statistics_query(&mut self);
     fn execute_bundles<'a, I: Iterator<Item = &'a Ctx::RenderBundleId>>(
         &mut self,
         render_bundles: I,
     );
+    fn begin_occlusion_query(&mut self, query_index: u32);
+    fn end_occlusion_query(&mut self);
 }
 
 trait Context: Debug + Send + Sized + Sync {
@@ -178,6 +183,7 @@ trait Context: Debug + Send + Sized + Sync {
     type PipelineLayoutId: Debug + Send + Sync + 'static;
     type RenderPipelineId: Debug + Send + Sync + 'static;
     type ComputePipelineId: Debug + Send + Sync + 'static;
+    type PipelineCacheId: Debug + Send + Sync + 'static;
     type CommandEncoderId: Debug;
     type ComputePassId: Debug + ComputePassInner<Self>;
     type RenderPassId: Debug + RenderPassInner<Self>;
@@ -193,6 +199,9 @@ trait Context: Debug + Send + Sized + Sync {
     type RequestDeviceFuture: Future<Output = Result<(Self::DeviceId, Self::QueueId), RequestDeviceError>>
         + Send;
     type PopErrorScopeFuture: Future<Output = Option<Error>> + Send;
+    type CompilationInfoFuture: Future<Output = CompilationInfo> + Send;
+    type CreateRenderPipelineFuture: Future<Output = Result<Self::RenderPipelineId, Error>> + Send;
+    type CreateComputePipelineFuture: Future<Output = Result<Self::ComputePipelineId, Error>> + Send;
 
     fn init(backends: Backends) -> Self;
     fn instance_create_surface(
@@ -210,6 +219,7 @@ trait Context: Debug + Send + Sized + Sync {
         trace_dir: Option<&std::path::Path>,
     ) -> Self::RequestDeviceFuture;
     fn instance_poll_all_devices(&self, force_wait: bool) -> bool;
+    fn instance_set_worker_thread_count(&self, count: usize);
     fn adapter_is_surface_supported(
         &self,
         adapter: &Self::AdapterId,
@@ -235,6 +245,11 @@ trait Context: Debug + Send + Sized + Sync {
         surface: &Self::SurfaceId,
         adapter: &Self::AdapterId,
     ) -> Vec<PresentMode>;
+    fn surface_get_supported_alpha_modes(
+        &self,
+        surface: &Self::SurfaceId,
+        adapter: &Self::AdapterId,
+    ) -> Vec<CompositeAlphaMode>;
     fn surface_configure(
         &self,
         surface: &Self::SurfaceId,
@@ -270,6 +285,11 @@ trait Context: Debug + Send + Sized + Sync {
         device: &Self::DeviceId,
         desc: &ShaderModuleDescriptorSpirV,
     ) -> Self::ShaderModuleId;
+    unsafe fn device_create_pipeline_cache(
+        &self,
+        device: &Self::DeviceId,
+        desc: &PipelineCacheDescriptor,
+    ) -> Self::PipelineCacheId;
     fn device_create_bind_group_layout(
         &self,
         device: &Self::DeviceId,
@@ -295,6 +315,16 @@ trait Context: Debug + Send + Sized + Sync {
         device: &Self::DeviceId,
         desc: &ComputePipelineDescriptor,
     ) -> Self::ComputePipelineId;
+    fn device_create_render_pipeline_async(
+        self: Arc<Self>,
+        device: &Self::DeviceId,
+        desc: &RenderPipelineDescriptor,
+    ) -> Self::CreateRenderPipelineFuture;
+    fn device_create_compute_pipeline_async(
+        self: Arc<Self>,
+        device: &Self::DeviceId,
+        desc: &ComputePipelineDescriptor,
+    ) -> Self::CreateComputePipelineFuture;
     fn device_create_buffer(
         &self,
         device: &Self::DeviceId,
@@ -332,9 +362,26 @@ trait Context: Debug + Send + Sized + Sync {
         device: &Self::DeviceId,
         handler: impl UncapturedErrorHandler,
     );
+    fn device_enable_shader_hot_reload(&self, device: &Self::DeviceId);
     fn device_push_error_scope(&self, device: &Self::DeviceId, filter: ErrorFilter);
     fn device_pop_error_scope(&self, device: &Self::DeviceId) -> Self::PopErrorScopeFuture;
 
+    fn shader_module_get_compilation_info(
+        &self,
+        shader_module: &Self::ShaderModuleId,
+    ) -> Self::CompilationInfoFuture;
+    fn shader_module_get_vertex_inputs(
+        &self,
+        shader_module: &Self::ShaderModuleId,
+        entry_point: &str,
+    ) -> Option<Vec<VertexInput>>;
+    fn shader_module_replace(
+        &self,
+        shader_module: &Self::ShaderModuleId,
+        desc: ShaderModuleDescriptor,
+    ) -> Result<(), Error>;
+    fn pipeline_cache_get_data(&self, cache: &Self::PipelineCacheId) -> Option<Vec<u8>>;
+
     fn buffer_map_async<F>(
         &self,
         buffer: &Self::BufferId,
@@ -371,6 +418,7 @@ trait Context: Debug + Send + Sized + Sync {
     fn bind_group_layout_drop(&self, bind_group_layout: &Self::BindGroupLayoutId);
     fn pipeline_layout_drop(&self, pipeline_layout: &Self::PipelineLayoutId);
     fn shader_module_drop(&self, shader_module: &Self::ShaderModuleId);
+    fn pipeline_cache_drop(&self, cache: &Self::PipelineCacheId);
     fn command_encoder_drop(&self, command_encoder: &Self::CommandEncoderId);
     fn command_buffer_drop(&self, command_buffer: &Self::CommandBufferId);
     fn render_bundle_drop(&self, render_bundle: &Self::RenderBundleId);
@@ -506,6 +554,14 @@ trait Context: Debug + Send + Sized + Sync {
         offset: BufferAddress,
         staging_buffer: &QueueWriteBuffer,
     );
+    fn queue_write_staging_texture(
+        &self,
+        queue: &Self::QueueId,
+        texture: ImageCopyTexture,
+        data_layout: ImageDataLayout,
+        size: Extent3d,
+        staging_buffer: &QueueWriteBuffer,
+    );
     fn queue_write_texture(
         &self,
         queue: &Self::QueueId,
@@ -809,6 +865,44 @@ impl Drop for ShaderModule {
     }
 }
 
+impl ShaderModule {
+    /// Returns the errors and other messages produced while compiling this module.
+    ///
+    /// Messages are kept for invalid modules too, with the location of the
+    /// offending source when it's known.
+    pub fn get_compilation_info(&self) -> impl Future<Output = CompilationInfo> + Send {
+        self.context.shader_module_get_compilation_info(&self.id)
+    }
+
+    /// Returns the inputs of the vertex entry point `entry_point`, sorted by location.
+    ///
+    /// Returns `None` if the module is invalid, has no such vertex entry point,
+    /// or was created with [`Device::create_shader_module_spirv`]. Reflection
+    /// isn't available on the web backend, where this always returns `None`.
+    ///
+    /// See [`util::vertex_buffer_layouts`] to build vertex buffer layouts from them.
+    pub fn get_vertex_inputs(&self, entry_point: &str) -> Option<Vec<VertexInput>> {
+        self.context
+            .shader_module_get_vertex_inputs(&self.id, entry_point)
+    }
+
+    /// Replaces the source of this module, and rebuilds the pipelines created from
+    /// it since [`Device::enable_shader_hot_reload`] was called.
+    ///
+    /// Either the module and all of these pipelines are updated, or, if the new
+    /// source or any of the rebuilt pipelines is invalid, none of them is and the
+    /// error is returned. It isn't reported to the device's error handlers.
+    /// Commands recorded before the replacement keep using the old pipelines.
+    ///
+    /// [`ShaderModule::get_compilation_info`] keeps describing the original
+    /// source. Not supported on the web backend, where this always fails.
+    ///
+    /// See [`util::ShaderWatcher`] to reload modules when their file changes.
+    pub fn replace(&self, desc: ShaderModuleDescriptor) -> Result<(), Error> {
+        Context::shader_module_replace(&*self.context, &self.id, desc)
+    }
+}
+
 /// Source of a shader module.
 ///
 /// The source will be parsed and validated.
@@ -838,6 +932,18 @@ pub enum ShaderSource<'a> {
     },
     /// WGSL module as a string slice.
     Wgsl(Cow<'a, str>),
+    /// WGSL module produced by a preprocessor.
+    ///
+    /// Parsing errors and [`CompilationInfo`] messages are reported against the
+    /// original files, as described by `source_map`.
+    ///
+    /// See also: [`util::WgslPreprocessor`]
+    WgslPreprocessed {
+        /// The preprocessed source code of the shader.
+        source: Cow<'a, str>,
+        /// Origin of the lines of `source`.
+        source_map: ShaderSourceMap,
+    },
     /// Naga module.
     #[cfg(feature = "naga")]
     #[cfg_attr(docsrs, doc(cfg(feature = "naga")))]
@@ -863,6 +969,56 @@ pub struct ShaderModuleDescriptorSpirV<'a> {
     pub source: Cow<'a, [u32]>,
 }
 
+/// Handle to a pipeline cache.
+///
+/// A `PipelineCache` holds the results of shader translation and, on backends
+/// that have one, the driver's own cache of compiled pipelines. Pipelines created
+/// with the same cache and the same inputs are faster to create. Its contents can
+/// be saved with [`PipelineCache::get_data`] and loaded in a later run with
+/// [`Device::create_pipeline_cache`].
+///
+/// Shader translations are cached on Vulkan and OpenGL ES, and Vulkan also uses
+/// a native `VkPipelineCache`. On other backends the cache has no effect.
+/// Translations of modules created from `ShaderSource::Naga` are only kept
+/// for the lifetime of the process, and left out of [`PipelineCache::get_data`].
+#[derive(Debug)]
+pub struct PipelineCache {
+    context: Arc<C>,
+    id: <C as Context>::PipelineCacheId,
+}
+
+impl Drop for PipelineCache {
+    fn drop(&mut self) {
+        if !thread::panicking() {
+            self.context.pipeline_cache_drop(&self.id);
+        }
+    }
+}
+
+impl PipelineCache {
+    /// Serializes the contents of the cache, to be passed to
+    /// [`Device::create_pipeline_cache`] later, typically by saving it to a file.
+    ///
+    /// Returns `None` if the backend has nothing to cache.
+    pub fn get_data(&self) -> Option<Vec<u8>> {
+        self.context.pipeline_cache_get_data(&self.id)
+    }
+}
+
+/// Describes a [`PipelineCache`].
+///
+/// For use with [`Device::create_pipeline_cache`].
+#[derive(Clone, Debug)]
+pub struct PipelineCacheDescriptor<'a> {
+    /// Debug label of the pipeline cache. This will show up in graphics debuggers for easy identification.
+    pub label: Label<'a>,
+    /// Data to initialize the cache with.
+    ///
+    /// It must have been returned by [`PipelineCache::get_data`] on a compatible
+    /// adapter, see the safety section of [`Device::create_pipeline_cache`].
+    pub data: Option<&'a [u8]>,
+}
+
 /// Handle to a pipeline layout.
 ///
 /// A `PipelineLayout` object describes the available binding groups of a pipeline.
@@ -972,6 +1128,28 @@ impl Drop for CommandBuffer {
     }
 }
 
+/// Handle to a command buffer that can be submitted several times.
+///
+/// A `ReusableCommandBuffer` is obtained with [`CommandEncoder::finish_reusable`], and submitted
+/// with [`Queue::submit_reusable`] as many times as needed, for workloads that don't change from
+/// frame to frame. The resources it uses are kept alive until it is dropped and its last
+/// submission is done.
+#[cfg(any(not(target_arch = "wasm32"), feature = "emscripten"))]
+#[derive(Debug)]
+pub struct ReusableCommandBuffer {
+    context: Arc<C>,
+    id: <C as Context>::CommandBufferId,
+}
+
+#[cfg(any(not(target_arch = "wasm32"), feature = "emscripten"))]
+impl Drop for ReusableCommandBuffer {
+    fn drop(&mut self) {
+        if !thread::panicking() {
+            self.context.command_buffer_drop(&self.id);
+        }
+    }
+}
+
 /// Encodes a series of GPU operations.
 ///
 /// A command encoder can record [`RenderPass`]es, [`ComputePass`]es,
@@ -1021,6 +1199,52 @@ pub struct ComputePass<'a> {
     parent: &'a mut CommandEncoder,
 }
 
+/// Resources used by an owned pass, kept alive until the pass is run.
+#[derive(Default)]
+struct PassResources(HashMap<usize, Arc<dyn Any + Send + Sync>>);
+
+impl PassResources {
+    fn keep<T: Any + Send + Sync>(&mut self, resource: &Arc<T>) {
+        self.0
+            .entry(Arc::as_ptr(resource) as *const () as usize)
+            .or_insert_with(|| resource.clone());
+    }
+}
+
+impl Debug for PassResources {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} resources", self.0.len())
+    }
+}
+
+/// Recording of a render pass that doesn't borrow its encoder or resources.
+///
+/// It can be created with [`CommandEncoder::begin_owned_render_pass`], and its
+/// commands are added to an encoder by [`CommandEncoder::run_render_pass`]. In
+/// between, it can be sent to other threads or stored away: the resources it uses
+/// are held by `Arc`s until it's run. Dropping it without running it discards the
+/// commands.
+///
+/// On the web, passes are recorded into their encoder as soon as they begin, so
+/// owned passes must be run on the encoder that created them, in the order they
+/// were begun, and without other passes in between.
+#[derive(Debug)]
+pub struct OwnedRenderPass {
+    id: <C as Context>::RenderPassId,
+    resources: PassResources,
+}
+
+/// Recording of a compute pass that doesn't borrow its encoder or resources.
+///
+/// It can be created with [`CommandEncoder::begin_owned_compute_pass`], and its
+/// commands are added to an encoder by [`CommandEncoder::run_compute_pass`].
+/// See [`OwnedRenderPass`] for details.
+#[derive(Debug)]
+pub struct OwnedComputePass {
+    id: <C as Context>::ComputePassId,
+    resources: PassResources,
+}
+
 /// Encodes a series of GPU operations into a reusable "render bundle".
 ///
 /// It only supports a handful of render commands, but it makes them reusable.
@@ -1070,6 +1294,7 @@ impl Drop for RenderBundle {
 /// It can be created with [`Device::create_query_set`].
 ///
 /// Corresponds to [WebGPU `GPUQuerySet`](https://gpuweb.github.io/gpuweb/#queryset).
+#[derive(Debug)]
 pub struct QuerySet {
     context: Arc<C>,
     id: <C as Context>::QuerySetId,
@@ -1231,6 +1456,32 @@ pub struct RenderPassDepthStencilAttachment<'tex> {
     pub stencil_ops: Option<Operations<u32>>,
 }
 
+/// Describes a color attachment to an [`OwnedRenderPass`].
+///
+/// For use with [`OwnedRenderPassDescriptor`].
+#[derive(Clone, Debug)]
+pub struct OwnedRenderPassColorAttachment {
+    /// The view to use as an attachment.
+    pub view: Arc<TextureView>,
+    /// The view that will receive the resolved output if multisampling is used.
+    pub resolve_target: Option<Arc<TextureView>>,
+    /// What operations will be performed on this color attachment.
+    pub ops: Operations<Color>,
+}
+
+/// Describes a depth/stencil attachment to an [`OwnedRenderPass`].
+///
+/// For use with [`OwnedRenderPassDescriptor`].
+#[derive(Clone, Debug)]
+pub struct OwnedRenderPassDepthStencilAttachment {
+    /// The view to use as an attachment.
+    pub view: Arc<TextureView>,
+    /// What operations will be performed on the depth part of the attachment.
+    pub depth_ops: Option<Operations<f32>>,
+    /// What operations will be performed on the stencil part of the attachment.
+    pub stencil_ops: Option<Operations<u32>>,
+}
+
 // The underlying types are also exported so that documentation shows up for them
 
 /// Object debugging label.
@@ -1277,7 +1528,14 @@ pub type RenderBundleDescriptor<'a> = wgt::RenderBundleDescriptor<Label<'a>>;
 ///
 /// Corresponds to [WebGPU `GPUTextureDescriptor`](
 /// https://gpuweb.github.io/gpuweb/#dictdef-gputexturedescriptor).
-pub type TextureDescriptor<'a> = wgt::TextureDescriptor<Label<'a>>;
+pub type TextureDescriptor<'a> = wgt::TextureDescriptor<Label<'a>, &'a [TextureFormat]>;
+/// Describes a [`Surface`] configuration.
+///
+/// For use with [`Surface::configure`].
+///
+/// Corresponds to [WebGPU `GPUCanvasConfiguration`](
+/// https://gpuweb.github.io/gpuweb/#canvas-configuration).
+pub type SurfaceConfiguration = wgt::SurfaceConfiguration<Vec<TextureFormat>>;
 /// Describes a [`QuerySet`].
 ///
 /// For use with [`Device::create_query_set`].
@@ -1337,7 +1595,8 @@ pub struct PipelineLayoutDescriptor<'a> {
     /// must define the range in push constant memory that corresponds to its single `layout(push_constant)`
     /// uniform block.
     ///
-    /// If this array is non-empty, the [`Features::PUSH_CONSTANTS`] must be enabled.
+    /// If this array is non-empty, [`Features::PUSH_CONSTANTS`] or
+    /// [`Features::PUSH_CONSTANT_EMULATION`] must be enabled.
     pub push_constant_ranges: &'a [PushConstantRange],
 }
 
@@ -1441,6 +1700,25 @@ pub struct RenderPassDescriptor<'tex, 'desc> {
     pub color_attachments: &'desc [Option<RenderPassColorAttachment<'tex>>],
     /// The depth and stencil attachment of the render pass, if any.
     pub depth_stencil_attachment: Option<RenderPassDepthStencilAttachment<'tex>>,
+    /// The query set written by the occlusion queries of the render pass, if any.
+    /// It must be of type [`QueryType::Occlusion`].
+    pub occlusion_query_set: Option<&'tex QuerySet>,
+}
+
+/// Describes the attachments of an [`OwnedRenderPass`].
+///
+/// For use with [`CommandEncoder::begin_owned_render_pass`].
+#[derive(Clone, Debug, Default)]
+pub struct OwnedRenderPassDescriptor<'a> {
+    /// Debug label of the render pass. This will show up in graphics debuggers for easy identification.
+    pub label: Label<'a>,
+    /// The color attachments of the render pass.
+    pub color_attachments: &'a [Option<OwnedRenderPassColorAttachment>],
+    /// The depth and stencil attachment of the render pass, if any.
+    pub depth_stencil_attachment: Option<OwnedRenderPassDepthStencilAttachment>,
+    /// The query set written by the occlusion queries of the render pass, if any.
+    /// It must be of type [`QueryType::Occlusion`].
+    pub occlusion_query_set: Option<Arc<QuerySet>>,
 }
 
 /// Describes how the vertex buffer is interpreted.
@@ -1472,6 +1750,9 @@ pub struct VertexState<'a> {
     /// The name of the entry point in the compiled shader. There must be a function with this name
     /// in the shader.
     pub entry_point: &'a str,
+    /// Values of the pipeline-overridable constants of the module, keyed by name or by
+    /// numeric id. Constants that aren't listed keep the value declared in the shader.
+    pub constants: Option<&'a HashMap<String, f64>>,
     /// The format of any vertex buffers used with this pipeline.
     pub buffers: &'a [VertexBufferLayout<'a>],
 }
@@ -1489,6 +1770,9 @@ pub struct FragmentState<'a> {
     /// The name of the entry point in the compiled shader. There must be a function with this name
     /// in the shader.
     pub entry_point: &'a str,
+    /// Values of the pipeline-overridable constants of the module, keyed by name or by
+    /// numeric id. Constants that aren't listed keep the value declared in the shader.
+    pub constants: Option<&'a HashMap<String, f64>>,
     /// The color state of the render targets.
     pub targets: &'a [Option<ColorTargetState>],
 }
@@ -1518,6 +1802,8 @@ pub struct RenderPipelineDescriptor<'a> {
     /// If the pipeline will be used with a multiview render pass, this indicates how many array
     /// layers the attachments will have.
     pub multiview: Option<NonZeroU32>,
+    /// The pipeline cache to look up and store the translated shaders and the compiled pipeline in.
+    pub cache: Option<&'a PipelineCache>,
 }
 
 /// Describes the attachments of a compute pass.
@@ -1549,6 +1835,11 @@ pub struct ComputePipelineDescriptor<'a> {
     /// The name of the entry point in the compiled shader. There must be a function with this name
     /// and no return value in the shader.
     pub entry_point: &'a str,
+    /// Values of the pipeline-overridable constants of the module, keyed by name or by
+    /// numeric id. Constants that aren't listed keep the value declared in the shader.
+    pub constants: Option<&'a HashMap<String, f64>>,
+    /// The pipeline cache to look up and store the translated shaders and the compiled pipeline in.
+    pub cache: Option<&'a PipelineCache>,
 }
 
 pub use wgt::ImageCopyBuffer as ImageCopyBufferBase;
@@ -1841,6 +2132,17 @@ impl Instance {
         self.context.instance_poll_all_devices(force_wait)
     }
 
+    /// Sets the number of threads running asynchronous work, such as
+    /// [`Device::create_render_pipeline_async`].
+    ///
+    /// Defaults to the number of CPU cores. Work that is already queued still
+    /// runs on the previous threads.
+    ///
+    /// On the web, this is a no-op.
+    pub fn set_worker_thread_count(&self, count: usize) {
+        self.context.instance_set_worker_thread_count(count)
+    }
+
     /// Generates memory report.
     #[cfg(any(not(target_arch = "wasm32"), feature = "emscripten"))]
     pub fn generate_report(&self) -> wgc::hub::GlobalReport {
@@ -2057,6 +2359,20 @@ impl Device {
         }
     }
 
+    /// Creates a [`PipelineCache`], initialized with `desc.data` if it's valid.
+    ///
+    /// # Safety
+    ///
+    /// `desc.data` must have been returned by [`PipelineCache::get_data`], on an adapter
+    /// of the same backend. The cached shaders are passed to the driver without being
+    /// validated, so corrupted data can result in a driver crash or bogus behaviour.
+    pub unsafe fn create_pipeline_cache(&self, desc: &PipelineCacheDescriptor) -> PipelineCache {
+        PipelineCache {
+            context: Arc::clone(&self.context),
+            id: Context::device_create_pipeline_cache(&*self.context, &self.id, desc),
+        }
+    }
+
     /// Creates an empty [`CommandEncoder`].
     pub fn create_command_encoder(&self, desc: &CommandEncoderDescriptor) -> CommandEncoder {
         CommandEncoder {
@@ -2122,6 +2438,45 @@ impl Device {
         }
     }
 
+    /// Creates a [`RenderPipeline`] without blocking the calling thread.
+    ///
+    /// Validation, shader translation and the driver compilation run on a
+    /// worker thread, see [`Instance::set_worker_thread_count`]. Errors are
+    /// returned by the future instead of being reported to the error scopes.
+    /// The layout and shader modules of `desc` may be dropped before the
+    /// future resolves.
+    ///
+    /// Corresponds to [WebGPU `GPUDevice.createRenderPipelineAsync`](
+    /// https://gpuweb.github.io/gpuweb/#dom-gpudevice-createrenderpipelineasync).
+    pub fn create_render_pipeline_async(
+        &self,
+        desc: &RenderPipelineDescriptor,
+    ) -> impl Future<Output = Result<RenderPipeline, Error>> + Send {
+        let context = Arc::clone(&self.context);
+        let pipeline =
+            Context::device_create_render_pipeline_async(Arc::clone(&self.context), &self.id, desc);
+        async move { pipeline.await.map(|id| RenderPipeline { context, id }) }
+    }
+
+    /// Creates a [`ComputePipeline`] without blocking the calling thread.
+    ///
+    /// See [`Device::create_render_pipeline_async`].
+    ///
+    /// Corresponds to [WebGPU `GPUDevice.createComputePipelineAsync`](
+    /// https://gpuweb.github.io/gpuweb/#dom-gpudevice-createcomputepipelineasync).
+    pub fn create_compute_pipeline_async(
+        &self,
+        desc: &ComputePipelineDescriptor,
+    ) -> impl Future<Output = Result<ComputePipeline, Error>> + Send {
+        let context = Arc::clone(&self.context);
+        let pipeline = Context::device_create_compute_pipeline_async(
+            Arc::clone(&self.context),
+            &self.id,
+            desc,
+        );
+        async move { pipeline.await.map(|id| ComputePipeline { context, id }) }
+    }
+
     /// Creates a [`Buffer`].
     pub fn create_buffer(&self, desc: &BufferDescriptor) -> Buffer {
         let mut map_context = MapContext::new(desc.size);
@@ -2137,6 +2492,31 @@ impl Device {
         }
     }
 
+    /// Creates a [`Buffer`] whose memory isn't zero-initialized.
+    ///
+    /// Requires [`Features::UNINITIALIZED_RESOURCES`]. Saves the clears wgpu inserts
+    /// before the first read of each part of buffers not written yet, like large
+    /// scratch buffers.
+    ///
+    /// # Safety
+    ///
+    /// - Parts of the buffer that weren't written must not be read, or the values
+    ///   read are undefined.
+    #[cfg(any(not(target_arch = "wasm32"), feature = "emscripten"))]
+    pub unsafe fn create_buffer_uninitialized(&self, desc: &BufferDescriptor) -> Buffer {
+        let mut map_context = MapContext::new(desc.size);
+        if desc.mapped_at_creation {
+            map_context.initial_range = 0..desc.size;
+        }
+        Buffer {
+            context: Arc::clone(&self.context),
+            id: self.context.create_buffer_uninitialized(&self.id, desc),
+            map_context: Mutex::new(map_context),
+            size: desc.size,
+            usage: desc.usage,
+        }
+    }
+
     /// Creates a new [`Texture`].
     ///
     /// `desc` specifies the general format of the texture.
@@ -2148,6 +2528,26 @@ impl Device {
         }
     }
 
+    /// Creates a [`Texture`] whose memory isn't zero-initialized.
+    ///
+    /// Requires [`Features::UNINITIALIZED_RESOURCES`]. Saves the clears wgpu inserts
+    /// before the first read of each part of textures not written yet, like render
+    /// targets that are always fully overwritten.
+    ///
+    /// # Safety
+    ///
+    /// - Parts of the texture that weren't written, or that were discarded by a render
+    ///   pass with [`Operations::store`] set to false, must not be read, or the values
+    ///   read are undefined.
+    #[cfg(any(not(target_arch = "wasm32"), feature = "emscripten"))]
+    pub unsafe fn create_texture_uninitialized(&self, desc: &TextureDescriptor) -> Texture {
+        Texture {
+            context: Arc::clone(&self.context),
+            id: self.context.create_texture_uninitialized(&self.id, desc),
+            owned: true,
+        }
+    }
+
     /// Creates a [`Texture`] from a wgpu-hal Texture.
     ///
     /// # Safety
@@ -2203,6 +2603,37 @@ impl Device {
         self.context.device_pop_error_scope(&self.id)
     }
 
+    /// Makes render and compute pipelines created from now on follow their shader
+    /// modules when those are replaced with [`ShaderModule::replace`].
+    ///
+    /// Each pipeline then keeps a copy of its descriptor, so this is meant for
+    /// development builds. Has no effect on the web backend.
+    pub fn enable_shader_hot_reload(&self) {
+        Context::device_enable_shader_hot_reload(&*self.context, &self.id)
+    }
+
+    /// Makes the command encoders created from now on record the resource state
+    /// transitions they emit, to be retrieved with [`Device::take_barrier_logs`]
+    /// once their command buffers are submitted.
+    ///
+    /// Meant for debugging wgpu itself, or synchronization issues.
+    #[cfg(any(not(target_arch = "wasm32"), feature = "emscripten"))]
+    pub fn enable_barrier_log(&self) {
+        self.context.device_enable_barrier_log(&self.id)
+    }
+
+    /// Takes the barrier logs of the command buffers submitted since the last
+    /// call, in submission order.
+    ///
+    /// Each log lists the buffer and texture barriers wgpu emitted for a command
+    /// buffer, with the command that caused them, and can be dumped with
+    /// [`to_json`](wgc::command::BarrierLog::to_json). Empty unless
+    /// [`Device::enable_barrier_log`] was called.
+    #[cfg(any(not(target_arch = "wasm32"), feature = "emscripten"))]
+    pub fn take_barrier_logs(&self) -> Vec<wgc::command::BarrierLog> {
+        self.context.device_take_barrier_logs(&self.id)
+    }
+
     /// Starts frame capture.
     pub fn start_capture(&self) {
         Context::device_start_capture(&*self.context, &self.id)
@@ -2588,6 +3019,20 @@ impl CommandEncoder {
         }
     }
 
+    /// Finishes recording and returns a [`ReusableCommandBuffer`], that can be submitted
+    /// any number of times with [`Queue::submit_reusable`].
+    ///
+    /// Requires [`Features::REUSABLE_COMMAND_BUFFERS`]. The commands can't use surface textures.
+    #[cfg(any(not(target_arch = "wasm32"), feature = "emscripten"))]
+    pub fn finish_reusable(mut self) -> ReusableCommandBuffer {
+        ReusableCommandBuffer {
+            context: Arc::clone(&self.context),
+            id: self
+                .context
+                .command_encoder_finish_reusable(self.id.take().unwrap()),
+        }
+    }
+
     /// Begins recording of a render pass.
     ///
     /// This function returns a [`RenderPass`] object which records a single render pass.
@@ -2613,6 +3058,93 @@ impl CommandEncoder {
         }
     }
 
+    /// Begins recording of a render pass that can outlive this borrow of the encoder.
+    ///
+    /// The returned [`OwnedRenderPass`] is added to an encoder with [`run_render_pass`].
+    ///
+    /// [`run_render_pass`]: Self::run_render_pass
+    pub fn begin_owned_render_pass(&self, desc: &OwnedRenderPassDescriptor) -> OwnedRenderPass {
+        let mut resources = PassResources::default();
+        let color_attachments = desc
+            .color_attachments
+            .iter()
+            .map(|at| {
+                at.as_ref().map(|at| {
+                    resources.keep(&at.view);
+                    if let Some(ref resolve_target) = at.resolve_target {
+                        resources.keep(resolve_target);
+                    }
+                    RenderPassColorAttachment {
+                        view: &at.view,
+                        resolve_target: at.resolve_target.as_deref(),
+                        ops: at.ops,
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+        let depth_stencil_attachment = desc.depth_stencil_attachment.as_ref().map(|at| {
+            resources.keep(&at.view);
+            RenderPassDepthStencilAttachment {
+                view: &at.view,
+                depth_ops: at.depth_ops,
+                stencil_ops: at.stencil_ops,
+            }
+        });
+        if let Some(ref query_set) = desc.occlusion_query_set {
+            resources.keep(query_set);
+        }
+
+        let id = Context::command_encoder_begin_render_pass(
+            &*self.context,
+            self.id.as_ref().unwrap(),
+            &RenderPassDescriptor {
+                label: desc.label,
+                color_attachments: &color_attachments,
+                depth_stencil_attachment,
+                occlusion_query_set: desc.occlusion_query_set.as_deref(),
+            },
+        );
+        OwnedRenderPass { id, resources }
+    }
+
+    /// Begins recording of a compute pass that can outlive this borrow of the encoder.
+    ///
+    /// The returned [`OwnedComputePass`] is added to an encoder with [`run_compute_pass`].
+    ///
+    /// [`run_compute_pass`]: Self::run_compute_pass
+    pub fn begin_owned_compute_pass(&self, desc: &ComputePassDescriptor) -> OwnedComputePass {
+        OwnedComputePass {
+            id: Context::command_encoder_begin_compute_pass(
+                &*self.context,
+                self.id.as_ref().unwrap(),
+                desc,
+            ),
+            resources: PassResources::default(),
+        }
+    }
+
+    /// Adds the commands of an owned render pass to the encoder.
+    ///
+    /// Passes are added in the order they're run, not the order they were begun.
+    pub fn run_render_pass(&mut self, mut pass: OwnedRenderPass) {
+        Context::command_encoder_end_render_pass(
+            &*self.context,
+            self.id.as_ref().unwrap(),
+            &mut pass.id,
+        );
+    }
+
+    /// Adds the commands of an owned compute pass to the encoder.
+    ///
+    /// Passes are added in the order they're run, not the order they were begun.
+    pub fn run_compute_pass(&mut self, mut pass: OwnedComputePass) {
+        Context::command_encoder_end_compute_pass(
+            &*self.context,
+            self.id.as_ref().unwrap(),
+            &mut pass.id,
+        );
+    }
+
     /// Copy data from one buffer to another.
     ///
     /// # Panics
@@ -2956,6 +3488,22 @@ impl<'a> RenderPass<'a> {
         self.id
             .execute_bundles(render_bundles.into_iter().map(|rb| &rb.id))
     }
+
+    /// Start an occlusion query on this render pass. It can be ended with
+    /// `end_occlusion_query`. Occlusion queries may not be nested.
+    ///
+    /// The result is written at `query_index` in the pass's
+    /// [`RenderPassDescriptor::occlusion_query_set`]: it is non-zero if any sample
+    /// passed the depth and stencil tests between the beginning and the end of the query.
+    pub fn begin_occlusion_query(&mut self, query_index: u32) {
+        self.id.begin_occlusion_query(query_index);
+    }
+
+    /// End the occlusion query on this render pass. It can be started with
+    /// `begin_occlusion_query`. Occlusion queries may not be nested.
+    pub fn end_occlusion_query(&mut self) {
+        self.id.end_occlusion_query();
+    }
 }
 
 /// [`Features::MULTI_DRAW_INDIRECT`] must be enabled on the device in order to call these functions.
@@ -3077,7 +3625,8 @@ impl<'a> RenderPass<'a> {
     }
 }
 
-/// [`Features::PUSH_CONSTANTS`] must be enabled on the device in order to call these functions.
+/// [`Features::PUSH_CONSTANTS`] or [`Features::PUSH_CONSTANT_EMULATION`] must be enabled on the
+/// device in order to call these functions.
 impl<'a> RenderPass<'a> {
     /// Set push constant data for subsequent draw calls.
     ///
@@ -3224,7 +3773,8 @@ impl<'a> ComputePass<'a> {
     }
 }
 
-/// [`Features::PUSH_CONSTANTS`] must be enabled on the device in order to call these functions.
+/// [`Features::PUSH_CONSTANTS`] or [`Features::PUSH_CONSTANT_EMULATION`] must be enabled on the
+/// device in order to call these functions.
 impl<'a> ComputePass<'a> {
     /// Set push constant data for subsequent dispatch calls.
     ///
@@ -3279,36 +3829,414 @@ impl<'a> Drop for ComputePass<'a> {
     }
 }
 
-impl<'a> RenderBundleEncoder<'a> {
-    /// Finishes recording and returns a [`RenderBundle`] that can be executed in other render passes.
-    pub fn finish(self, desc: &RenderBundleDescriptor) -> RenderBundle {
-        RenderBundle {
-            context: Arc::clone(&self.context),
-            id: Context::render_bundle_encoder_finish(&*self.context, self.id, desc),
-        }
-    }
-
-    /// Sets the active bind group for a given bind group index. The bind group layout
-    /// in the active pipeline when any `draw()` function is called must match the layout of this bind group.
+/// The methods of [`OwnedRenderPass`] match the ones of [`RenderPass`], but take
+/// resources by `Arc` and buffer ranges instead of [`BufferSlice`]s.
+impl OwnedRenderPass {
+    /// Sets the active bind group for a given bind group index.
     ///
-    /// If the bind group have dynamic offsets, provide them in the binding order.
+    /// See [`RenderPass::set_bind_group`].
     pub fn set_bind_group(
         &mut self,
         index: u32,
-        bind_group: &'a BindGroup,
+        bind_group: &Arc<BindGroup>,
         offsets: &[DynamicOffset],
     ) {
+        self.resources.keep(bind_group);
         RenderInner::set_bind_group(&mut self.id, index, &bind_group.id, offsets)
     }
 
     /// Sets the active render pipeline.
     ///
     /// Subsequent draw calls will exhibit the behavior defined by `pipeline`.
-    pub fn set_pipeline(&mut self, pipeline: &'a RenderPipeline) {
+    pub fn set_pipeline(&mut self, pipeline: &Arc<RenderPipeline>) {
+        self.resources.keep(pipeline);
         RenderInner::set_pipeline(&mut self.id, &pipeline.id)
     }
 
-    /// Sets the active index buffer.
+    /// Sets the blend color as used by some of the blending modes.
+    ///
+    /// Subsequent blending tests will test against this value.
+    pub fn set_blend_constant(&mut self, color: Color) {
+        self.id.set_blend_constant(color)
+    }
+
+    /// Sets the `bounds` of `buffer` as the active index buffer.
+    ///
+    /// See [`RenderPass::set_index_buffer`].
+    pub fn set_index_buffer<S: RangeBounds<BufferAddress>>(
+        &mut self,
+        buffer: &Arc<Buffer>,
+        bounds: S,
+        index_format: IndexFormat,
+    ) {
+        self.resources.keep(buffer);
+        let (offset, size) = range_to_offset_size(bounds);
+        RenderInner::set_index_buffer(&mut self.id, &buffer.id, index_format, offset, size)
+    }
+
+    /// Assigns the `bounds` of `buffer` to a vertex buffer slot.
+    ///
+    /// See [`RenderPass::set_vertex_buffer`].
+    pub fn set_vertex_buffer<S: RangeBounds<BufferAddress>>(
+        &mut self,
+        slot: u32,
+        buffer: &Arc<Buffer>,
+        bounds: S,
+    ) {
+        self.resources.keep(buffer);
+        let (offset, size) = range_to_offset_size(bounds);
+        RenderInner::set_vertex_buffer(&mut self.id, slot, &buffer.id, offset, size)
+    }
+
+    /// Sets the scissor region.
+    ///
+    /// Subsequent draw calls will discard any fragments that fall outside this region.
+    pub fn set_scissor_rect(&mut self, x: u32, y: u32, width: u32, height: u32) {
+        self.id.set_scissor_rect(x, y, width, height);
+    }
+
+    /// Sets the viewport region.
+    ///
+    /// Subsequent draw calls will draw any fragments in this region.
+    pub fn set_viewport(&mut self, x: f32, y: f32, w: f32, h: f32, min_depth: f32, max_depth: f32) {
+        self.id.set_viewport(x, y, w, h, min_depth, max_depth);
+    }
+
+    /// Sets the stencil reference.
+    ///
+    /// Subsequent stencil tests will test against this value.
+    pub fn set_stencil_reference(&mut self, reference: u32) {
+        self.id.set_stencil_reference(reference);
+    }
+
+    /// Draws primitives from the active vertex buffer(s).
+    ///
+    /// See [`RenderPass::draw`].
+    pub fn draw(&mut self, vertices: Range<u32>, instances: Range<u32>) {
+        RenderInner::draw(&mut self.id, vertices, instances)
+    }
+
+    /// Draws indexed primitives using the active index buffer and the active vertex buffers.
+    ///
+    /// See [`RenderPass::draw_indexed`].
+    pub fn draw_indexed(&mut self, indices: Range<u32>, base_vertex: i32, instances: Range<u32>) {
+        RenderInner::draw_indexed(&mut self.id, indices, base_vertex, instances);
+    }
+
+    /// Draws primitives from the active vertex buffer(s) based on the contents of the `indirect_buffer`.
+    ///
+    /// See [`RenderPass::draw_indirect`].
+    pub fn draw_indirect(&mut self, indirect_buffer: &Arc<Buffer>, indirect_offset: BufferAddress) {
+        self.resources.keep(indirect_buffer);
+        self.id.draw_indirect(&indirect_buffer.id, indirect_offset);
+    }
+
+    /// Draws indexed primitives using the active index buffer and the active vertex buffers,
+    /// based on the contents of the `indirect_buffer`.
+    ///
+    /// See [`RenderPass::draw_indexed_indirect`].
+    pub fn draw_indexed_indirect(
+        &mut self,
+        indirect_buffer: &Arc<Buffer>,
+        indirect_offset: BufferAddress,
+    ) {
+        self.resources.keep(indirect_buffer);
+        self.id
+            .draw_indexed_indirect(&indirect_buffer.id, indirect_offset);
+    }
+
+    /// Execute a [render bundle][RenderBundle], which is a set of pre-recorded commands
+    /// that can be run together.
+    pub fn execute_bundles<'a, I: IntoIterator<Item = &'a Arc<RenderBundle>>>(
+        &mut self,
+        render_bundles: I,
+    ) {
+        let render_bundles = render_bundles.into_iter().collect::<Vec<_>>();
+        for render_bundle in render_bundles.iter() {
+            self.resources.keep(render_bundle);
+        }
+        self.id
+            .execute_bundles(render_bundles.iter().map(|rb| &rb.id))
+    }
+
+    /// Start an occlusion query on this render pass.
+    ///
+    /// See [`RenderPass::begin_occlusion_query`].
+    pub fn begin_occlusion_query(&mut self, query_index: u32) {
+        self.id.begin_occlusion_query(query_index);
+    }
+
+    /// End the occlusion query on this render pass.
+    pub fn end_occlusion_query(&mut self) {
+        self.id.end_occlusion_query();
+    }
+
+    /// Inserts debug marker.
+    pub fn insert_debug_marker(&mut self, label: &str) {
+        self.id.insert_debug_marker(label);
+    }
+
+    /// Start record commands and group it into debug marker group.
+    pub fn push_debug_group(&mut self, label: &str) {
+        self.id.push_debug_group(label);
+    }
+
+    /// Stops command recording and creates debug group.
+    pub fn pop_debug_group(&mut self) {
+        self.id.pop_debug_group();
+    }
+}
+
+/// [`Features::MULTI_DRAW_INDIRECT`] must be enabled on the device in order to call these functions.
+impl OwnedRenderPass {
+    /// Dispatches multiple draw calls from the active vertex buffer(s) based on the contents of the `indirect_buffer`.
+    ///
+    /// See [`RenderPass::multi_draw_indirect`].
+    pub fn multi_draw_indirect(
+        &mut self,
+        indirect_buffer: &Arc<Buffer>,
+        indirect_offset: BufferAddress,
+        count: u32,
+    ) {
+        self.resources.keep(indirect_buffer);
+        self.id
+            .multi_draw_indirect(&indirect_buffer.id, indirect_offset, count);
+    }
+
+    /// Dispatches multiple draw calls from the active index buffer and the active vertex buffers,
+    /// based on the contents of the `indirect_buffer`.
+    ///
+    /// See [`RenderPass::multi_draw_indexed_indirect`].
+    pub fn multi_draw_indexed_indirect(
+        &mut self,
+        indirect_buffer: &Arc<Buffer>,
+        indirect_offset: BufferAddress,
+        count: u32,
+    ) {
+        self.resources.keep(indirect_buffer);
+        self.id
+            .multi_draw_indexed_indirect(&indirect_buffer.id, indirect_offset, count);
+    }
+}
+
+/// [`Features::MULTI_DRAW_INDIRECT_COUNT`] must be enabled on the device in order to call these functions.
+impl OwnedRenderPass {
+    /// Dispatches multiple draw calls from the active vertex buffer(s) based on the contents of the `indirect_buffer`.
+    /// The count buffer is read to determine how many draws to issue.
+    ///
+    /// See [`RenderPass::multi_draw_indirect_count`].
+    pub fn multi_draw_indirect_count(
+        &mut self,
+        indirect_buffer: &Arc<Buffer>,
+        indirect_offset: BufferAddress,
+        count_buffer: &Arc<Buffer>,
+        count_offset: BufferAddress,
+        max_count: u32,
+    ) {
+        self.resources.keep(indirect_buffer);
+        self.resources.keep(count_buffer);
+        self.id.multi_draw_indirect_count(
+            &indirect_buffer.id,
+            indirect_offset,
+            &count_buffer.id,
+            count_offset,
+            max_count,
+        );
+    }
+
+    /// Dispatches multiple draw calls from the active index buffer and the active vertex buffers,
+    /// based on the contents of the `indirect_buffer`. The count buffer is read to determine how many draws to issue.
+    ///
+    /// See [`RenderPass::multi_draw_indexed_indirect_count`].
+    pub fn multi_draw_indexed_indirect_count(
+        &mut self,
+        indirect_buffer: &Arc<Buffer>,
+        indirect_offset: BufferAddress,
+        count_buffer: &Arc<Buffer>,
+        count_offset: BufferAddress,
+        max_count: u32,
+    ) {
+        self.resources.keep(indirect_buffer);
+        self.resources.keep(count_buffer);
+        self.id.multi_draw_indexed_indirect_count(
+            &indirect_buffer.id,
+            indirect_offset,
+            &count_buffer.id,
+            count_offset,
+            max_count,
+        );
+    }
+}
+
+/// [`Features::PUSH_CONSTANTS`] or [`Features::PUSH_CONSTANT_EMULATION`] must be enabled on the
+/// device in order to call these functions.
+impl OwnedRenderPass {
+    /// Set push constant data for subsequent draw calls.
+    ///
+    /// See [`RenderPass::set_push_constants`].
+    pub fn set_push_constants(&mut self, stages: ShaderStages, offset: u32, data: &[u8]) {
+        self.id.set_push_constants(stages, offset, data);
+    }
+}
+
+/// [`Features::WRITE_TIMESTAMP_INSIDE_PASSES`] must be enabled on the device in order to call these functions.
+impl OwnedRenderPass {
+    /// Issue a timestamp command at this point in the queue.
+    ///
+    /// See [`RenderPass::write_timestamp`].
+    pub fn write_timestamp(&mut self, query_set: &Arc<QuerySet>, query_index: u32) {
+        self.resources.keep(query_set);
+        self.id.write_timestamp(&query_set.id, query_index)
+    }
+}
+
+/// [`Features::PIPELINE_STATISTICS_QUERY`] must be enabled on the device in order to call these functions.
+impl OwnedRenderPass {
+    /// Start a pipeline statistics query on this render pass. It can be ended with
+    /// `end_pipeline_statistics_query`. Pipeline statistics queries may not be nested.
+    pub fn begin_pipeline_statistics_query(&mut self, query_set: &Arc<QuerySet>, query_index: u32) {
+        self.resources.keep(query_set);
+        self.id
+            .begin_pipeline_statistics_query(&query_set.id, query_index);
+    }
+
+    /// End the pipeline statistics query on this render pass. It can be started with
+    /// `begin_pipeline_statistics_query`. Pipeline statistics queries may not be nested.
+    pub fn end_pipeline_statistics_query(&mut self) {
+        self.id.end_pipeline_statistics_query();
+    }
+}
+
+/// The methods of [`OwnedComputePass`] match the ones of [`ComputePass`], but take
+/// resources by `Arc`.
+impl OwnedComputePass {
+    /// Sets the active bind group for a given bind group index.
+    ///
+    /// See [`ComputePass::set_bind_group`].
+    pub fn set_bind_group(
+        &mut self,
+        index: u32,
+        bind_group: &Arc<BindGroup>,
+        offsets: &[DynamicOffset],
+    ) {
+        self.resources.keep(bind_group);
+        ComputePassInner::set_bind_group(&mut self.id, index, &bind_group.id, offsets);
+    }
+
+    /// Sets the active compute pipeline.
+    pub fn set_pipeline(&mut self, pipeline: &Arc<ComputePipeline>) {
+        self.resources.keep(pipeline);
+        ComputePassInner::set_pipeline(&mut self.id, &pipeline.id);
+    }
+
+    /// Inserts debug marker.
+    pub fn insert_debug_marker(&mut self, label: &str) {
+        self.id.insert_debug_marker(label);
+    }
+
+    /// Start record commands and group it into debug marker group.
+    pub fn push_debug_group(&mut self, label: &str) {
+        self.id.push_debug_group(label);
+    }
+
+    /// Stops command recording and creates debug group.
+    pub fn pop_debug_group(&mut self) {
+        self.id.pop_debug_group();
+    }
+
+    /// Dispatches compute work operations.
+    ///
+    /// `x`, `y` and `z` denote the number of work groups to dispatch in each dimension.
+    pub fn dispatch_workgroups(&mut self, x: u32, y: u32, z: u32) {
+        ComputePassInner::dispatch_workgroups(&mut self.id, x, y, z);
+    }
+
+    /// Dispatches compute work operations, based on the contents of the `indirect_buffer`.
+    ///
+    /// See [`ComputePass::dispatch_workgroups_indirect`].
+    pub fn dispatch_workgroups_indirect(
+        &mut self,
+        indirect_buffer: &Arc<Buffer>,
+        indirect_offset: BufferAddress,
+    ) {
+        self.resources.keep(indirect_buffer);
+        ComputePassInner::dispatch_workgroups_indirect(
+            &mut self.id,
+            &indirect_buffer.id,
+            indirect_offset,
+        );
+    }
+}
+
+/// [`Features::PUSH_CONSTANTS`] or [`Features::PUSH_CONSTANT_EMULATION`] must be enabled on the
+/// device in order to call these functions.
+impl OwnedComputePass {
+    /// Set push constant data for subsequent dispatch calls.
+    ///
+    /// See [`ComputePass::set_push_constants`].
+    pub fn set_push_constants(&mut self, offset: u32, data: &[u8]) {
+        self.id.set_push_constants(offset, data);
+    }
+}
+
+/// [`Features::WRITE_TIMESTAMP_INSIDE_PASSES`] must be enabled on the device in order to call these functions.
+impl OwnedComputePass {
+    /// Issue a timestamp command at this point in the queue.
+    ///
+    /// See [`ComputePass::write_timestamp`].
+    pub fn write_timestamp(&mut self, query_set: &Arc<QuerySet>, query_index: u32) {
+        self.resources.keep(query_set);
+        self.id.write_timestamp(&query_set.id, query_index)
+    }
+}
+
+/// [`Features::PIPELINE_STATISTICS_QUERY`] must be enabled on the device in order to call these functions.
+impl OwnedComputePass {
+    /// Start a pipeline statistics query on this compute pass. It can be ended with
+    /// `end_pipeline_statistics_query`. Pipeline statistics queries may not be nested.
+    pub fn begin_pipeline_statistics_query(&mut self, query_set: &Arc<QuerySet>, query_index: u32) {
+        self.resources.keep(query_set);
+        self.id
+            .begin_pipeline_statistics_query(&query_set.id, query_index);
+    }
+
+    /// End the pipeline statistics query on this compute pass. It can be started with
+    /// `begin_pipeline_statistics_query`. Pipeline statistics queries may not be nested.
+    pub fn end_pipeline_statistics_query(&mut self) {
+        self.id.end_pipeline_statistics_query();
+    }
+}
+
+impl<'a> RenderBundleEncoder<'a> {
+    /// Finishes recording and returns a [`RenderBundle`] that can be executed in other render passes.
+    pub fn finish(self, desc: &RenderBundleDescriptor) -> RenderBundle {
+        RenderBundle {
+            context: Arc::clone(&self.context),
+            id: Context::render_bundle_encoder_finish(&*self.context, self.id, desc),
+        }
+    }
+
+    /// Sets the active bind group for a given bind group index. The bind group layout
+    /// in the active pipeline when any `draw()` function is called must match the layout of this bind group.
+    ///
+    /// If the bind group have dynamic offsets, provide them in the binding order.
+    pub fn set_bind_group(
+        &mut self,
+        index: u32,
+        bind_group: &'a BindGroup,
+        offsets: &[DynamicOffset],
+    ) {
+        RenderInner::set_bind_group(&mut self.id, index, &bind_group.id, offsets)
+    }
+
+    /// Sets the active render pipeline.
+    ///
+    /// Subsequent draw calls will exhibit the behavior defined by `pipeline`.
+    pub fn set_pipeline(&mut self, pipeline: &'a RenderPipeline) {
+        RenderInner::set_pipeline(&mut self.id, &pipeline.id)
+    }
+
+    /// Sets the active index buffer.
     ///
     /// Subsequent calls to [`draw_indexed`](RenderBundleEncoder::draw_indexed) on this [`RenderBundleEncoder`] will
     /// use `buffer` as the source index buffer.
@@ -3349,6 +4277,23 @@ impl<'a> RenderBundleEncoder<'a> {
         RenderInner::draw(&mut self.id, vertices, instances)
     }
 
+    /// Inserts debug marker.
+    pub fn insert_debug_marker(&mut self, label: &str) {
+        self.id.insert_debug_marker(label);
+    }
+
+    /// Start record commands and group it into debug marker group.
+    ///
+    /// Every debug group pushed in a bundle must be popped before [`RenderBundleEncoder::finish`].
+    pub fn push_debug_group(&mut self, label: &str) {
+        self.id.push_debug_group(label);
+    }
+
+    /// Stops command recording and creates debug group.
+    pub fn pop_debug_group(&mut self) {
+        self.id.pop_debug_group();
+    }
+
     /// Draws indexed primitives using the active index buffer and the active vertex buffers.
     ///
     /// The active index buffer can be set with [`RenderBundleEncoder::set_index_buffer`], while the active
@@ -3383,7 +4328,118 @@ impl<'a> RenderBundleEncoder<'a> {
     }
 }
 
-/// [`Features::PUSH_CONSTANTS`] must be enabled on the device in order to call these functions.
+/// [`Features::MULTI_DRAW_INDIRECT`] must be enabled on the device in order to call these functions.
+impl<'a> RenderBundleEncoder<'a> {
+    /// Dispatches multiple draw calls from the active vertex buffer(s) based on the contents of the `indirect_buffer`.
+    /// `count` draw calls are issued.
+    ///
+    /// The active vertex buffers can be set with [`RenderBundleEncoder::set_vertex_buffer`].
+    ///
+    /// The structure expected in `indirect_buffer` must conform to [`DrawIndirect`](crate::util::DrawIndirect).
+    ///
+    /// These draw structures are expected to be tightly packed.
+    pub fn multi_draw_indirect(
+        &mut self,
+        indirect_buffer: &'a Buffer,
+        indirect_offset: BufferAddress,
+        count: u32,
+    ) {
+        self.id
+            .multi_draw_indirect(&indirect_buffer.id, indirect_offset, count);
+    }
+
+    /// Dispatches multiple draw calls from the active index buffer and the active vertex buffers,
+    /// based on the contents of the `indirect_buffer`. `count` draw calls are issued.
+    ///
+    /// The active index buffer can be set with [`RenderBundleEncoder::set_index_buffer`], while the active
+    /// vertex buffers can be set with [`RenderBundleEncoder::set_vertex_buffer`].
+    ///
+    /// The structure expected in `indirect_buffer` must conform to [`DrawIndexedIndirect`](crate::util::DrawIndexedIndirect).
+    ///
+    /// These draw structures are expected to be tightly packed.
+    pub fn multi_draw_indexed_indirect(
+        &mut self,
+        indirect_buffer: &'a Buffer,
+        indirect_offset: BufferAddress,
+        count: u32,
+    ) {
+        self.id
+            .multi_draw_indexed_indirect(&indirect_buffer.id, indirect_offset, count);
+    }
+}
+
+/// [`Features::MULTI_DRAW_INDIRECT_COUNT`] must be enabled on the device in order to call these functions.
+impl<'a> RenderBundleEncoder<'a> {
+    /// Dispatches multiple draw calls from the active vertex buffer(s) based on the contents of the `indirect_buffer`.
+    /// The count buffer is read to determine how many draws to issue.
+    ///
+    /// The indirect buffer must be long enough to account for `max_count` draws, however only `count`
+    /// draws will be read. If `count` is greater than `max_count`, `max_count` will be used.
+    ///
+    /// The count buffer is read when the bundle is executed, so its contents may be updated
+    /// between executions without re-recording the bundle.
+    ///
+    /// The active vertex buffers can be set with [`RenderBundleEncoder::set_vertex_buffer`].
+    ///
+    /// The structure expected in `indirect_buffer` must conform to [`DrawIndirect`](crate::util::DrawIndirect).
+    ///
+    /// These draw structures are expected to be tightly packed.
+    ///
+    /// The structure expected in `count_buffer` is a single `u32` holding the number of draw calls to issue.
+    pub fn multi_draw_indirect_count(
+        &mut self,
+        indirect_buffer: &'a Buffer,
+        indirect_offset: BufferAddress,
+        count_buffer: &'a Buffer,
+        count_offset: BufferAddress,
+        max_count: u32,
+    ) {
+        self.id.multi_draw_indirect_count(
+            &indirect_buffer.id,
+            indirect_offset,
+            &count_buffer.id,
+            count_offset,
+            max_count,
+        );
+    }
+
+    /// Dispatches multiple draw calls from the active index buffer and the active vertex buffers,
+    /// based on the contents of the `indirect_buffer`. The count buffer is read to determine how many draws to issue.
+    ///
+    /// The indirect buffer must be long enough to account for `max_count` draws, however only `count`
+    /// draws will be read. If `count` is greater than `max_count`, `max_count` will be used.
+    ///
+    /// The count buffer is read when the bundle is executed, so its contents may be updated
+    /// between executions without re-recording the bundle.
+    ///
+    /// The active index buffer can be set with [`RenderBundleEncoder::set_index_buffer`], while the active
+    /// vertex buffers can be set with [`RenderBundleEncoder::set_vertex_buffer`].
+    ///
+    /// The structure expected in `indirect_buffer` must conform to [`DrawIndexedIndirect`](crate::util::DrawIndexedIndirect).
+    ///
+    /// These draw structures are expected to be tightly packed.
+    ///
+    /// The structure expected in `count_buffer` is a single `u32` holding the number of draw calls to issue.
+    pub fn multi_draw_indexed_indirect_count(
+        &mut self,
+        indirect_buffer: &'a Buffer,
+        indirect_offset: BufferAddress,
+        count_buffer: &'a Buffer,
+        count_offset: BufferAddress,
+        max_count: u32,
+    ) {
+        self.id.multi_draw_indexed_indirect_count(
+            &indirect_buffer.id,
+            indirect_offset,
+            &count_buffer.id,
+            count_offset,
+            max_count,
+        );
+    }
+}
+
+/// [`Features::PUSH_CONSTANTS`] or [`Features::PUSH_CONSTANT_EMULATION`] must be enabled on the
+/// device in order to call these functions.
 impl<'a> RenderBundleEncoder<'a> {
     /// Set push constant data.
     ///
@@ -3418,6 +4474,41 @@ impl<'a> RenderBundleEncoder<'a> {
     }
 }
 
+/// [`Features::WRITE_TIMESTAMP_INSIDE_PASSES`] must be enabled on the device in order to call these functions.
+impl<'a> RenderBundleEncoder<'a> {
+    /// Issue a timestamp command at this point in the bundle. The
+    /// timestamp will be written to the specified query set, at the specified index.
+    ///
+    /// A query may only be written once per render pass, so a bundle writing
+    /// timestamps can only be executed once per pass.
+    ///
+    /// Must be multiplied by [`Queue::get_timestamp_period`] to get
+    /// the value in nanoseconds. Absolute values have no meaning,
+    /// but timestamps can be subtracted to get the time it takes
+    /// for a string of operations to complete.
+    pub fn write_timestamp(&mut self, query_set: &QuerySet, query_index: u32) {
+        self.id.write_timestamp(&query_set.id, query_index)
+    }
+}
+
+/// [`Features::PIPELINE_STATISTICS_QUERY`] must be enabled on the device in order to call these functions.
+impl<'a> RenderBundleEncoder<'a> {
+    /// Start a pipeline statistics query on this render bundle. It must be ended with
+    /// `end_pipeline_statistics_query` before the bundle is finished. Pipeline statistics
+    /// queries may not be nested, so the bundle can't be executed while the render pass
+    /// has a query active.
+    pub fn begin_pipeline_statistics_query(&mut self, query_set: &QuerySet, query_index: u32) {
+        self.id
+            .begin_pipeline_statistics_query(&query_set.id, query_index);
+    }
+
+    /// End the pipeline statistics query on this render bundle. It can be started with
+    /// `begin_pipeline_statistics_query`. Pipeline statistics queries may not be nested.
+    pub fn end_pipeline_statistics_query(&mut self) {
+        self.id.end_pipeline_statistics_query();
+    }
+}
+
 /// A write-only view into a staging buffer
 pub struct QueueWriteBufferView<'a> {
     queue: &'a Queue,
@@ -3452,6 +4543,42 @@ impl<'a> Drop for QueueWriteBufferView<'a> {
     }
 }
 
+/// A write-only view into a staging buffer, whose contents are copied to a texture
+pub struct QueueWriteTextureView<'a> {
+    queue: &'a Queue,
+    texture: ImageCopyTexture<'a>,
+    data_layout: ImageDataLayout,
+    size: Extent3d,
+    inner: QueueWriteBuffer,
+}
+
+impl<'a> std::ops::Deref for QueueWriteTextureView<'a> {
+    type Target = [u8];
+
+    fn deref(&self) -> &Self::Target {
+        panic!("QueueWriteTextureView is write-only!");
+    }
+}
+
+impl<'a> std::ops::DerefMut for QueueWriteTextureView<'a> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.inner
+    }
+}
+
+impl<'a> Drop for QueueWriteTextureView<'a> {
+    fn drop(&mut self) {
+        Context::queue_write_staging_texture(
+            &*self.queue.context,
+            &self.queue.id,
+            self.texture.clone(),
+            self.data_layout,
+            self.size,
+            &self.inner,
+        );
+    }
+}
+
 impl Queue {
     /// Schedule a data write into `buffer` starting at `offset`.
     ///
@@ -3507,6 +4634,55 @@ impl Queue {
         Context::queue_write_texture(&*self.context, &self.id, texture, data, data_layout, size)
     }
 
+    /// Schedule a data write into `texture` via the returned [QueueWriteTextureView].
+    ///
+    /// The returned value can be dereferenced to a `&mut [u8]` laid out as described by
+    /// `data_layout`; dereferencing it to a `&[u8]` panics! It is copied to the texture
+    /// when dropped, without going through an intermediate copy like [`Queue::write_texture`].
+    ///
+    /// `data_layout.bytes_per_row` must be a multiple of [`COPY_BYTES_PER_ROW_ALIGNMENT`].
+    ///
+    /// This method is intended to have low performance costs.
+    /// As such, the write is not immediately submitted, and instead enqueued
+    /// internally to happen at the start of the next `submit()` call.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `data_layout.bytes_per_row` is `None`.
+    #[must_use]
+    pub fn write_texture_with<'a>(
+        &'a self,
+        texture: ImageCopyTexture<'a>,
+        data_layout: ImageDataLayout,
+        size: Extent3d,
+    ) -> QueueWriteTextureView<'a> {
+        let bytes_per_row = data_layout
+            .bytes_per_row
+            .expect("write_texture_with requires bytes_per_row")
+            .get() as BufferAddress;
+        let rows_per_image = data_layout
+            .rows_per_image
+            .map_or(size.height, NonZeroU32::get) as BufferAddress;
+        // Rows are counted in texels, which covers the block rows of compressed formats.
+        let rows = match size.depth_or_array_layers {
+            0 => 0,
+            layers => rows_per_image * (layers as BufferAddress - 1) + size.height as BufferAddress,
+        };
+        let staging_size = (data_layout.offset + bytes_per_row * rows).max(COPY_BUFFER_ALIGNMENT);
+        let staging_buffer = Context::queue_create_staging_buffer(
+            &*self.context,
+            &self.id,
+            BufferSize::new(staging_size).unwrap(),
+        );
+        QueueWriteTextureView {
+            queue: self,
+            texture,
+            data_layout,
+            size,
+            inner: staging_buffer,
+        }
+    }
+
     /// Submits a series of finished command buffers for execution.
     pub fn submit<I: IntoIterator<Item = CommandBuffer>>(
         &self,
@@ -3523,6 +4699,24 @@ impl Queue {
         SubmissionIndex(raw)
     }
 
+    /// Submits a series of reusable command buffers for execution.
+    ///
+    /// Unlike [`Queue::submit`], this doesn't consume the command buffers, which can be
+    /// submitted again, even before this submission is done.
+    #[cfg(any(not(target_arch = "wasm32"), feature = "emscripten"))]
+    pub fn submit_reusable<'a, I: IntoIterator<Item = &'a ReusableCommandBuffer>>(
+        &self,
+        command_buffers: I,
+    ) -> SubmissionIndex {
+        let raw = Context::queue_submit(
+            &*self.context,
+            &self.id,
+            command_buffers.into_iter().map(|comb| comb.id),
+        );
+
+        SubmissionIndex(raw)
+    }
+
     /// Gets the amount of nanoseconds each tick of a timestamp query represents.
     ///
     /// Returns zero if timestamp queries are unsupported.
@@ -3584,6 +4778,14 @@ impl Surface {
         Context::surface_get_supported_modes(&*self.context, &self.id, &adapter.id)
     }
 
+    /// Returns a vec of supported alpha modes to use for the [`Surface`] with this adapter.
+    ///
+    /// Will return at least one element, CompositeAlphaMode::Opaque or CompositeAlphaMode::Inherit,
+    /// unless the surface is incompatible with the adapter, in which case the vector is empty.
+    pub fn get_supported_alpha_modes(&self, adapter: &Adapter) -> Vec<CompositeAlphaMode> {
+        Context::surface_get_supported_alpha_modes(&*self.context, &self.id, &adapter.id)
+    }
+
     /// Initializes [`Surface`] for presentation.
     ///
     /// # Panics
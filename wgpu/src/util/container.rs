@@ -0,0 +1,669 @@
+use std::fmt;
+
+use crate::{
+    AstcBlock, AstcChannel, Extent3d, Features, Label, TextureDescriptor, TextureDimension,
+    TextureFormat, TextureUsages,
+};
+
+const KTX2_IDENTIFIER: [u8; 12] = [
+    0xAB, 0x4B, 0x54, 0x58, 0x20, 0x32, 0x30, 0xBB, 0x0D, 0x0A, 0x1A, 0x0A,
+];
+const DDS_MAGIC: &[u8; 4] = b"DDS ";
+
+/// Error returned when reading a [`TextureContainer`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TextureContainerError {
+    /// The data is neither a KTX2 nor a DDS file.
+    UnknownContainer,
+    /// The data ends before the end of the header or of the texture it describes.
+    Truncated,
+    /// The header describes a texture that can't be created. Contains the reason.
+    InvalidHeader(&'static str),
+    /// The pixel format has no [`TextureFormat`] equivalent. Contains a description of the format.
+    UnsupportedFormat(String),
+    /// The KTX2 file uses a supercompression scheme, like Basis Universal or Zstandard.
+    /// Contains the identifier of the scheme.
+    Supercompressed(u32),
+    /// The format needs features the device doesn't have.
+    MissingFeatures(Features),
+}
+
+impl fmt::Display for TextureContainerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TextureContainerError::UnknownContainer => {
+                write!(f, "Texture data is neither a KTX2 nor a DDS file")
+            }
+            TextureContainerError::Truncated => write!(f, "Texture file is truncated"),
+            TextureContainerError::InvalidHeader(reason) => {
+                write!(f, "Texture file header is invalid: {}", reason)
+            }
+            TextureContainerError::UnsupportedFormat(format) => {
+                write!(f, "Texture format {} is not supported", format)
+            }
+            TextureContainerError::Supercompressed(scheme) => write!(
+                f,
+                "KTX2 supercompression scheme {} is not supported",
+                scheme
+            ),
+            TextureContainerError::MissingFeatures(features) => write!(
+                f,
+                "Features {:?} are required by the texture format but not enabled on the device",
+                features
+            ),
+        }
+    }
+}
+
+impl std::error::Error for TextureContainerError {}
+
+/// A texture read from a KTX2 or DDS file.
+///
+/// Reading the file needs no device: the texture can then be created with
+/// [`DeviceExt::create_texture_with_data`], from [`TextureContainer::descriptor`]
+/// and [`TextureContainer::data`].
+///
+/// Uncompressed formats, as well as BC, ETC2, EAC and ASTC compressed ones, are supported.
+/// Cube maps become 2D textures with 6 array layers per cube, in the order +X, -X, +Y, -Y,
+/// +Z, -Z.
+///
+/// ```no_run
+/// # fn load(device: &wgpu::Device, queue: &wgpu::Queue, file: &[u8]) -> Result<wgpu::Texture, wgpu::util::TextureContainerError> {
+/// use wgpu::util::{DeviceExt, TextureContainer};
+///
+/// let container = TextureContainer::parse(file)?;
+/// container.check_features(device.features())?;
+/// let desc = container.descriptor(Some("skybox"), wgpu::TextureUsages::TEXTURE_BINDING);
+/// Ok(device.create_texture_with_data(queue, &desc, container.data()))
+/// # }
+/// ```
+///
+/// [`DeviceExt::create_texture_with_data`]: super::DeviceExt::create_texture_with_data
+#[derive(Clone, Debug)]
+pub struct TextureContainer {
+    size: Extent3d,
+    mip_level_count: u32,
+    dimension: TextureDimension,
+    format: TextureFormat,
+    is_cube: bool,
+    data: Vec<u8>,
+}
+
+impl TextureContainer {
+    /// Reads a KTX2 or DDS file, depending on its first bytes.
+    pub fn parse(data: &[u8]) -> Result<Self, TextureContainerError> {
+        if data.starts_with(&KTX2_IDENTIFIER) {
+            Self::parse_ktx2(data)
+        } else if data.starts_with(DDS_MAGIC) {
+            Self::parse_dds(data)
+        } else {
+            Err(TextureContainerError::UnknownContainer)
+        }
+    }
+
+    /// Reads a KTX2 file.
+    pub fn parse_ktx2(data: &[u8]) -> Result<Self, TextureContainerError> {
+        if !data.starts_with(&KTX2_IDENTIFIER) {
+            return Err(TextureContainerError::UnknownContainer);
+        }
+        let vk_format = read_u32(data, 12)?;
+        let width = read_u32(data, 20)?;
+        let height = read_u32(data, 24)?;
+        let depth = read_u32(data, 28)?;
+        let layer_count = read_u32(data, 32)?.max(1);
+        let face_count = read_u32(data, 36)?;
+        // A level count of 0 asks the loader to generate the mips.
+        let mip_level_count = read_u32(data, 40)?.max(1);
+        let supercompression = read_u32(data, 44)?;
+
+        if supercompression != 0 {
+            return Err(TextureContainerError::Supercompressed(supercompression));
+        }
+        let format = ktx2_format(vk_format)?;
+        if width == 0 {
+            return Err(TextureContainerError::InvalidHeader("the width is 0"));
+        }
+        let is_cube = match face_count {
+            1 => false,
+            6 => true,
+            _ => return Err(TextureContainerError::InvalidHeader("invalid face count")),
+        };
+        let (dimension, size) = if depth != 0 {
+            if is_cube || layer_count > 1 {
+                return Err(TextureContainerError::InvalidHeader(
+                    "3D textures can't be cube maps or arrays",
+                ));
+            }
+            (TextureDimension::D3, extent(width, height.max(1), depth))
+        } else if height == 0 {
+            (TextureDimension::D1, extent(width, 1, layer_count))
+        } else {
+            (
+                TextureDimension::D2,
+                extent(width, height, layer_count * face_count),
+            )
+        };
+
+        let mut texture = Self::new(size, mip_level_count, dimension, format, is_cube)?;
+
+        // KTX2 stores the images level by level, and each level holds all the
+        // layers and faces, so the images are gathered layer by layer.
+        let layers = texture.layer_count();
+        let mut levels = Vec::new();
+        for mip in 0..mip_level_count {
+            let index = 80 + 24 * mip as usize;
+            let offset = to_usize(read_u64(data, index)?)?;
+            let length = to_usize(read_u64(data, index + 8)?)?;
+            let image_size = texture.image_size(mip);
+            if length < image_size.saturating_mul(layers as usize) {
+                return Err(TextureContainerError::InvalidHeader(
+                    "a mip level is smaller than its images",
+                ));
+            }
+            levels.push((offset, image_size));
+        }
+        let mut out = Vec::new();
+        for layer in 0..layers as usize {
+            for &(offset, image_size) in levels.iter() {
+                let start = offset
+                    .checked_add(layer * image_size)
+                    .ok_or(TextureContainerError::Truncated)?;
+                out.extend_from_slice(slice(data, start, image_size)?);
+            }
+        }
+        texture.data = out;
+        Ok(texture)
+    }
+
+    /// Reads a DDS file, with or without the DX10 header extension.
+    pub fn parse_dds(data: &[u8]) -> Result<Self, TextureContainerError> {
+        const DDSD_MIPMAPCOUNT: u32 = 0x2_0000;
+        const DDPF_FOURCC: u32 = 0x4;
+        const DDSCAPS2_CUBEMAP: u32 = 0x200;
+        const DDSCAPS2_CUBEMAP_ALL_FACES: u32 = 0xFC00;
+        const DDSCAPS2_VOLUME: u32 = 0x20_0000;
+        const RESOURCE_MISC_TEXTURECUBE: u32 = 0x4;
+
+        if !data.starts_with(DDS_MAGIC) {
+            return Err(TextureContainerError::UnknownContainer);
+        }
+        if read_u32(data, 4)? != 124 {
+            return Err(TextureContainerError::InvalidHeader("invalid header size"));
+        }
+        let flags = read_u32(data, 8)?;
+        let height = read_u32(data, 12)?;
+        let width = read_u32(data, 16)?;
+        let depth = read_u32(data, 24)?;
+        let mip_level_count = if flags & DDSD_MIPMAPCOUNT != 0 {
+            read_u32(data, 28)?.max(1)
+        } else {
+            1
+        };
+        let pixel_flags = read_u32(data, 80)?;
+        let four_cc = read_u32(data, 84)?;
+        let caps2 = read_u32(data, 112)?;
+        if width == 0 || height == 0 {
+            return Err(TextureContainerError::InvalidHeader("the size is 0"));
+        }
+
+        let (format, dimension, is_cube, array_size, data_offset) =
+            if pixel_flags & DDPF_FOURCC != 0 && four_cc == u32::from_le_bytes(*b"DX10") {
+                let format = dxgi_format(read_u32(data, 128)?)?;
+                let dimension = match read_u32(data, 132)? {
+                    2 => TextureDimension::D1,
+                    3 => TextureDimension::D2,
+                    4 => TextureDimension::D3,
+                    _ => {
+                        return Err(TextureContainerError::InvalidHeader(
+                            "invalid resource dimension",
+                        ))
+                    }
+                };
+                let is_cube = read_u32(data, 136)? & RESOURCE_MISC_TEXTURECUBE != 0;
+                let array_size = read_u32(data, 140)?.max(1);
+                (format, dimension, is_cube, array_size, 148)
+            } else {
+                let format = dds_legacy_format(data, pixel_flags, four_cc)?;
+                let dimension = if caps2 & DDSCAPS2_VOLUME != 0 {
+                    TextureDimension::D3
+                } else {
+                    TextureDimension::D2
+                };
+                let is_cube = caps2 & DDSCAPS2_CUBEMAP != 0;
+                if is_cube && caps2 & DDSCAPS2_CUBEMAP_ALL_FACES != DDSCAPS2_CUBEMAP_ALL_FACES {
+                    return Err(TextureContainerError::InvalidHeader(
+                        "cube maps must have all their faces",
+                    ));
+                }
+                (format, dimension, is_cube, 1, 128)
+            };
+
+        let size = match dimension {
+            TextureDimension::D1 => extent(width, 1, array_size),
+            TextureDimension::D2 if is_cube => extent(width, height, array_size * 6),
+            TextureDimension::D2 => extent(width, height, array_size),
+            TextureDimension::D3 => {
+                if is_cube || array_size > 1 {
+                    return Err(TextureContainerError::InvalidHeader(
+                        "3D textures can't be cube maps or arrays",
+                    ));
+                }
+                extent(width, height, depth.max(1))
+            }
+        };
+
+        let mut texture = Self::new(size, mip_level_count, dimension, format, is_cube)?;
+        // DDS already stores the mips of each layer one after the other.
+        let byte_size = texture.byte_size();
+        texture.data = slice(data, data_offset, byte_size)?.to_vec();
+        Ok(texture)
+    }
+
+    fn new(
+        size: Extent3d,
+        mip_level_count: u32,
+        dimension: TextureDimension,
+        format: TextureFormat,
+        is_cube: bool,
+    ) -> Result<Self, TextureContainerError> {
+        if mip_level_count > size.max_mips(dimension) {
+            return Err(TextureContainerError::InvalidHeader("too many mip levels"));
+        }
+        Ok(Self {
+            size,
+            mip_level_count,
+            dimension,
+            format,
+            is_cube,
+            data: Vec::new(),
+        })
+    }
+
+    /// Size of the texture. For cube maps, the number of array layers is 6 times the
+    /// number of cubes.
+    pub fn size(&self) -> Extent3d {
+        self.size
+    }
+
+    /// Number of mip levels of the texture.
+    pub fn mip_level_count(&self) -> u32 {
+        self.mip_level_count
+    }
+
+    /// Dimension of the texture.
+    pub fn dimension(&self) -> TextureDimension {
+        self.dimension
+    }
+
+    /// Format of the texture.
+    pub fn format(&self) -> TextureFormat {
+        self.format
+    }
+
+    /// Returns `true` if the texture is a cube map, or an array of cube maps.
+    pub fn is_cube(&self) -> bool {
+        self.is_cube
+    }
+
+    /// Contents of the texture, in the layout [`DeviceExt::create_texture_with_data`] expects:
+    /// all the mips of the first layer, then all the mips of the second one, and so on.
+    ///
+    /// [`DeviceExt::create_texture_with_data`]: super::DeviceExt::create_texture_with_data
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Takes the contents of the texture, see [`TextureContainer::data`].
+    pub fn into_data(self) -> Vec<u8> {
+        self.data
+    }
+
+    /// Describes the texture, with the given label and usages.
+    pub fn descriptor<'a>(&self, label: Label<'a>, usage: TextureUsages) -> TextureDescriptor<'a> {
+        TextureDescriptor {
+            label,
+            size: self.size,
+            mip_level_count: self.mip_level_count,
+            sample_count: 1,
+            dimension: self.dimension,
+            format: self.format,
+            usage,
+            view_formats: &[],
+        }
+    }
+
+    /// Features a device needs to create the texture.
+    pub fn required_features(&self) -> Features {
+        self.format.describe().required_features
+    }
+
+    /// Checks that the features of a device, like [`Device::features`], allow creating the texture.
+    ///
+    /// [`Device::features`]: crate::Device::features
+    pub fn check_features(&self, features: Features) -> Result<(), TextureContainerError> {
+        let missing = self.required_features() - features;
+        if missing.is_empty() {
+            Ok(())
+        } else {
+            Err(TextureContainerError::MissingFeatures(missing))
+        }
+    }
+
+    /// Number of layers stored one after the other in the data.
+    fn layer_count(&self) -> u32 {
+        match self.dimension {
+            TextureDimension::D3 => 1,
+            _ => self.size.depth_or_array_layers,
+        }
+    }
+
+    /// Size in bytes of one layer of a mip level, or of the whole mip level of a 3D texture.
+    fn image_size(&self, mip: u32) -> usize {
+        let info = self.format.describe();
+        let (block_width, block_height) = (
+            info.block_dimensions.0 as u32,
+            info.block_dimensions.1 as u32,
+        );
+        let width = (self.size.width >> mip).max(1);
+        let height = (self.size.height >> mip).max(1);
+        let depth = match self.dimension {
+            TextureDimension::D3 => (self.size.depth_or_array_layers >> mip).max(1),
+            _ => 1,
+        };
+        // Sizes are at least 1, and partial blocks count as whole ones.
+        let width_blocks = (width - 1) / block_width + 1;
+        let height_blocks = (height - 1) / block_height + 1;
+        // Saturates on sizes that can't fit in memory, and so in the file.
+        (width_blocks as usize)
+            .saturating_mul(height_blocks as usize)
+            .saturating_mul(depth as usize)
+            .saturating_mul(info.block_size as usize)
+    }
+
+    fn byte_size(&self) -> usize {
+        (0..self.mip_level_count)
+            .fold(0usize, |sum, mip| sum.saturating_add(self.image_size(mip)))
+            .saturating_mul(self.layer_count() as usize)
+    }
+}
+
+fn extent(width: u32, height: u32, depth_or_array_layers: u32) -> Extent3d {
+    Extent3d {
+        width,
+        height,
+        depth_or_array_layers,
+    }
+}
+
+fn slice(data: &[u8], offset: usize, len: usize) -> Result<&[u8], TextureContainerError> {
+    offset
+        .checked_add(len)
+        .and_then(|end| data.get(offset..end))
+        .ok_or(TextureContainerError::Truncated)
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Result<u32, TextureContainerError> {
+    let bytes = slice(data, offset, 4)?;
+    Ok(u32::from_le_bytes(bytes.try_into().unwrap()))
+}
+
+fn read_u64(data: &[u8], offset: usize) -> Result<u64, TextureContainerError> {
+    let bytes = slice(data, offset, 8)?;
+    Ok(u64::from_le_bytes(bytes.try_into().unwrap()))
+}
+
+fn to_usize(value: u64) -> Result<usize, TextureContainerError> {
+    value
+        .try_into()
+        .map_err(|_| TextureContainerError::Truncated)
+}
+
+/// Maps a `VkFormat` of a KTX2 file.
+fn ktx2_format(vk_format: u32) -> Result<TextureFormat, TextureContainerError> {
+    use TextureFormat as Tf;
+
+    const ASTC_BLOCKS: [AstcBlock; 14] = [
+        AstcBlock::B4x4,
+        AstcBlock::B5x4,
+        AstcBlock::B5x5,
+        AstcBlock::B6x5,
+        AstcBlock::B6x6,
+        AstcBlock::B8x5,
+        AstcBlock::B8x6,
+        AstcBlock::B8x8,
+        AstcBlock::B10x5,
+        AstcBlock::B10x6,
+        AstcBlock::B10x8,
+        AstcBlock::B10x10,
+        AstcBlock::B12x10,
+        AstcBlock::B12x12,
+    ];
+    // VK_FORMAT_ASTC_4x4_SFLOAT_BLOCK, from VK_EXT_texture_compression_astc_hdr.
+    const ASTC_SFLOAT_START: u32 = 1_000_066_000;
+
+    Ok(match vk_format {
+        9 => Tf::R8Unorm,
+        10 => Tf::R8Snorm,
+        13 => Tf::R8Uint,
+        14 => Tf::R8Sint,
+        16 => Tf::Rg8Unorm,
+        17 => Tf::Rg8Snorm,
+        20 => Tf::Rg8Uint,
+        21 => Tf::Rg8Sint,
+        37 => Tf::Rgba8Unorm,
+        38 => Tf::Rgba8Snorm,
+        41 => Tf::Rgba8Uint,
+        42 => Tf::Rgba8Sint,
+        43 => Tf::Rgba8UnormSrgb,
+        44 => Tf::Bgra8Unorm,
+        50 => Tf::Bgra8UnormSrgb,
+        64 => Tf::Rgb10a2Unorm,
+        70 => Tf::R16Unorm,
+        71 => Tf::R16Snorm,
+        74 => Tf::R16Uint,
+        75 => Tf::R16Sint,
+        76 => Tf::R16Float,
+        77 => Tf::Rg16Unorm,
+        78 => Tf::Rg16Snorm,
+        81 => Tf::Rg16Uint,
+        82 => Tf::Rg16Sint,
+        83 => Tf::Rg16Float,
+        91 => Tf::Rgba16Unorm,
+        92 => Tf::Rgba16Snorm,
+        95 => Tf::Rgba16Uint,
+        96 => Tf::Rgba16Sint,
+        97 => Tf::Rgba16Float,
+        98 => Tf::R32Uint,
+        99 => Tf::R32Sint,
+        100 => Tf::R32Float,
+        101 => Tf::Rg32Uint,
+        102 => Tf::Rg32Sint,
+        103 => Tf::Rg32Float,
+        107 => Tf::Rgba32Uint,
+        108 => Tf::Rgba32Sint,
+        109 => Tf::Rgba32Float,
+        122 => Tf::Rg11b10Float,
+        123 => Tf::Rgb9e5Ufloat,
+        // BC1 blocks without alpha are read the same way.
+        131 | 133 => Tf::Bc1RgbaUnorm,
+        132 | 134 => Tf::Bc1RgbaUnormSrgb,
+        135 => Tf::Bc2RgbaUnorm,
+        136 => Tf::Bc2RgbaUnormSrgb,
+        137 => Tf::Bc3RgbaUnorm,
+        138 => Tf::Bc3RgbaUnormSrgb,
+        139 => Tf::Bc4RUnorm,
+        140 => Tf::Bc4RSnorm,
+        141 => Tf::Bc5RgUnorm,
+        142 => Tf::Bc5RgSnorm,
+        143 => Tf::Bc6hRgbUfloat,
+        144 => Tf::Bc6hRgbSfloat,
+        145 => Tf::Bc7RgbaUnorm,
+        146 => Tf::Bc7RgbaUnormSrgb,
+        147 => Tf::Etc2Rgb8Unorm,
+        148 => Tf::Etc2Rgb8UnormSrgb,
+        149 => Tf::Etc2Rgb8A1Unorm,
+        150 => Tf::Etc2Rgb8A1UnormSrgb,
+        151 => Tf::Etc2Rgba8Unorm,
+        152 => Tf::Etc2Rgba8UnormSrgb,
+        153 => Tf::EacR11Unorm,
+        154 => Tf::EacR11Snorm,
+        155 => Tf::EacRg11Unorm,
+        156 => Tf::EacRg11Snorm,
+        157..=184 => Tf::Astc {
+            block: ASTC_BLOCKS[(vk_format - 157) as usize / 2],
+            // The UNORM formats have odd values, and are followed by their SRGB variant.
+            channel: if vk_format % 2 == 1 {
+                AstcChannel::Unorm
+            } else {
+                AstcChannel::UnormSrgb
+            },
+        },
+        _ if (ASTC_SFLOAT_START..ASTC_SFLOAT_START + 14).contains(&vk_format) => Tf::Astc {
+            block: ASTC_BLOCKS[(vk_format - ASTC_SFLOAT_START) as usize],
+            channel: AstcChannel::Hdr,
+        },
+        _ => {
+            return Err(TextureContainerError::UnsupportedFormat(format!(
+                "VkFormat {}",
+                vk_format
+            )))
+        }
+    })
+}
+
+/// Maps a `DXGI_FORMAT` of the DX10 extension of a DDS file.
+fn dxgi_format(dxgi_format: u32) -> Result<TextureFormat, TextureContainerError> {
+    use TextureFormat as Tf;
+
+    Ok(match dxgi_format {
+        2 => Tf::Rgba32Float,
+        3 => Tf::Rgba32Uint,
+        4 => Tf::Rgba32Sint,
+        10 => Tf::Rgba16Float,
+        11 => Tf::Rgba16Unorm,
+        12 => Tf::Rgba16Uint,
+        13 => Tf::Rgba16Snorm,
+        14 => Tf::Rgba16Sint,
+        16 => Tf::Rg32Float,
+        17 => Tf::Rg32Uint,
+        18 => Tf::Rg32Sint,
+        24 => Tf::Rgb10a2Unorm,
+        26 => Tf::Rg11b10Float,
+        28 => Tf::Rgba8Unorm,
+        29 => Tf::Rgba8UnormSrgb,
+        30 => Tf::Rgba8Uint,
+        31 => Tf::Rgba8Snorm,
+        32 => Tf::Rgba8Sint,
+        34 => Tf::Rg16Float,
+        35 => Tf::Rg16Unorm,
+        36 => Tf::Rg16Uint,
+        37 => Tf::Rg16Snorm,
+        38 => Tf::Rg16Sint,
+        41 => Tf::R32Float,
+        42 => Tf::R32Uint,
+        43 => Tf::R32Sint,
+        49 => Tf::Rg8Unorm,
+        50 => Tf::Rg8Uint,
+        51 => Tf::Rg8Snorm,
+        52 => Tf::Rg8Sint,
+        54 => Tf::R16Float,
+        56 => Tf::R16Unorm,
+        57 => Tf::R16Uint,
+        58 => Tf::R16Snorm,
+        59 => Tf::R16Sint,
+        61 => Tf::R8Unorm,
+        62 => Tf::R8Uint,
+        63 => Tf::R8Snorm,
+        64 => Tf::R8Sint,
+        67 => Tf::Rgb9e5Ufloat,
+        71 => Tf::Bc1RgbaUnorm,
+        72 => Tf::Bc1RgbaUnormSrgb,
+        74 => Tf::Bc2RgbaUnorm,
+        75 => Tf::Bc2RgbaUnormSrgb,
+        77 => Tf::Bc3RgbaUnorm,
+        78 => Tf::Bc3RgbaUnormSrgb,
+        80 => Tf::Bc4RUnorm,
+        81 => Tf::Bc4RSnorm,
+        83 => Tf::Bc5RgUnorm,
+        84 => Tf::Bc5RgSnorm,
+        87 => Tf::Bgra8Unorm,
+        91 => Tf::Bgra8UnormSrgb,
+        95 => Tf::Bc6hRgbUfloat,
+        96 => Tf::Bc6hRgbSfloat,
+        98 => Tf::Bc7RgbaUnorm,
+        99 => Tf::Bc7RgbaUnormSrgb,
+        _ => {
+            return Err(TextureContainerError::UnsupportedFormat(format!(
+                "DXGI_FORMAT {}",
+                dxgi_format
+            )))
+        }
+    })
+}
+
+/// Maps the pixel format of a DDS file without the DX10 extension.
+fn dds_legacy_format(
+    data: &[u8],
+    pixel_flags: u32,
+    four_cc: u32,
+) -> Result<TextureFormat, TextureContainerError> {
+    use TextureFormat as Tf;
+
+    const DDPF_ALPHAPIXELS: u32 = 0x1;
+    const DDPF_FOURCC: u32 = 0x4;
+
+    if pixel_flags & DDPF_FOURCC != 0 {
+        return Ok(match &four_cc.to_le_bytes() {
+            b"DXT1" => Tf::Bc1RgbaUnorm,
+            b"DXT2" | b"DXT3" => Tf::Bc2RgbaUnorm,
+            b"DXT4" | b"DXT5" => Tf::Bc3RgbaUnorm,
+            b"ATI1" | b"BC4U" => Tf::Bc4RUnorm,
+            b"BC4S" => Tf::Bc4RSnorm,
+            b"ATI2" | b"BC5U" => Tf::Bc5RgUnorm,
+            b"BC5S" => Tf::Bc5RgSnorm,
+            // Direct3D 9 formats, stored as numbers in the FourCC.
+            _ => match four_cc {
+                36 => Tf::Rgba16Unorm,
+                111 => Tf::R16Float,
+                112 => Tf::Rg16Float,
+                113 => Tf::Rgba16Float,
+                114 => Tf::R32Float,
+                115 => Tf::Rg32Float,
+                116 => Tf::Rgba32Float,
+                _ => {
+                    return Err(TextureContainerError::UnsupportedFormat(format!(
+                        "FourCC {:?}",
+                        String::from_utf8_lossy(&four_cc.to_le_bytes())
+                    )))
+                }
+            },
+        });
+    }
+
+    let bit_count = read_u32(data, 88)?;
+    let masks = [
+        read_u32(data, 92)?,
+        read_u32(data, 96)?,
+        read_u32(data, 100)?,
+        if pixel_flags & DDPF_ALPHAPIXELS != 0 {
+            read_u32(data, 104)?
+        } else {
+            0
+        },
+    ];
+    Ok(match (bit_count, masks) {
+        (32, [0xFF, 0xFF00, 0xFF_0000, 0xFF00_0000]) => Tf::Rgba8Unorm,
+        (32, [0xFF_0000, 0xFF00, 0xFF, 0xFF00_0000]) => Tf::Bgra8Unorm,
+        (32, [0x3FF, 0xF_FC00, 0x3FF0_0000, 0xC000_0000]) => Tf::Rgb10a2Unorm,
+        (32, [0xFFFF, 0xFFFF_0000, 0, 0]) => Tf::Rg16Unorm,
+        (16, [0xFF, 0xFF00, 0, 0]) => Tf::Rg8Unorm,
+        (16, [0xFFFF, 0, 0, 0]) => Tf::R16Unorm,
+        (8, [0xFF, 0, 0, 0]) => Tf::R8Unorm,
+        _ => {
+            return Err(TextureContainerError::UnsupportedFormat(format!(
+                "{} bits per pixel with masks {:#x?}",
+                bit_count, masks
+            )))
+        }
+    })
+}
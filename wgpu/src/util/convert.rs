@@ -0,0 +1,492 @@
+use std::{fmt, num::NonZeroU32};
+
+use crate::{
+    Extent3d, ImageCopyTexture, ImageDataLayout, Queue, TextureFormat, COPY_BYTES_PER_ROW_ALIGNMENT,
+};
+
+/// Layout of the pixels of an image in memory, converted by [`QueueExt::write_texture_converted`].
+///
+/// 8 and 16 bit channels are unsigned normalized integers, 32 bit channels are floats.
+/// Multi-byte channels are little-endian.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PixelLayout {
+    /// Red channel only. 8 bit integer.
+    R8,
+    /// Red and green channels. 8 bit integers.
+    Rg8,
+    /// Red, green, and blue channels. 8 bit integers.
+    Rgb8,
+    /// Red, green, blue, and alpha channels. 8 bit integers.
+    Rgba8,
+    /// Blue, green, red, and alpha channels. 8 bit integers.
+    Bgra8,
+    /// Red channel only. 16 bit integer.
+    R16,
+    /// Red and green channels. 16 bit integers.
+    Rg16,
+    /// Red, green, and blue channels. 16 bit integers.
+    Rgb16,
+    /// Red, green, blue, and alpha channels. 16 bit integers.
+    Rgba16,
+    /// Red channel only. 32 bit float.
+    R32Float,
+    /// Red and green channels. 32 bit floats.
+    Rg32Float,
+    /// Red, green, and blue channels. 32 bit floats.
+    Rgb32Float,
+    /// Red, green, blue, and alpha channels. 32 bit floats.
+    Rgba32Float,
+}
+
+impl PixelLayout {
+    /// Number of channels of a pixel.
+    pub fn channels(self) -> usize {
+        match self {
+            Self::R8 | Self::R16 | Self::R32Float => 1,
+            Self::Rg8 | Self::Rg16 | Self::Rg32Float => 2,
+            Self::Rgb8 | Self::Rgb16 | Self::Rgb32Float => 3,
+            Self::Rgba8 | Self::Bgra8 | Self::Rgba16 | Self::Rgba32Float => 4,
+        }
+    }
+
+    /// Size of a pixel, in bytes.
+    pub fn bytes_per_pixel(self) -> usize {
+        self.channels() * self.bytes_per_channel()
+    }
+
+    fn bytes_per_channel(self) -> usize {
+        match self {
+            Self::R8 | Self::Rg8 | Self::Rgb8 | Self::Rgba8 | Self::Bgra8 => 1,
+            Self::R16 | Self::Rg16 | Self::Rgb16 | Self::Rgba16 => 2,
+            Self::R32Float | Self::Rg32Float | Self::Rgb32Float | Self::Rgba32Float => 4,
+        }
+    }
+
+    /// Value of a channel at full intensity, which integer formats are scaled by.
+    fn max_value(self) -> f64 {
+        match self.bytes_per_channel() {
+            1 => u8::MAX as f64,
+            2 => u16::MAX as f64,
+            _ => 1.0,
+        }
+    }
+
+    /// Reads the channels of `pixel` as RGBA, normalized to the `0.0..=1.0` range for
+    /// integers. Missing color channels are 0, and missing alpha is 1.
+    fn decode(self, pixel: &[u8]) -> [f64; 4] {
+        let mut rgba = [0.0, 0.0, 0.0, 1.0];
+        let size = self.bytes_per_channel();
+        for (value, bytes) in rgba.iter_mut().zip(pixel.chunks_exact(size)) {
+            *value = match *bytes {
+                [x] => x as f64 / u8::MAX as f64,
+                [x, y] => u16::from_le_bytes([x, y]) as f64 / u16::MAX as f64,
+                [x, y, z, w] => f32::from_le_bytes([x, y, z, w]) as f64,
+                _ => unreachable!(),
+            };
+        }
+        if self == Self::Bgra8 {
+            rgba.swap(0, 2);
+        }
+        rgba
+    }
+}
+
+/// Error returned by [`QueueExt::write_texture_converted`] and [`convert_pixels`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TextureConversionError {
+    /// The format is compressed, or a depth or stencil one, so pixels can't be converted to it.
+    UnsupportedFormat(TextureFormat),
+    /// The data ends before the last pixel of the copy.
+    DataTooShort {
+        /// Number of bytes the copy needs.
+        required: u64,
+        /// Length of the data.
+        len: u64,
+    },
+    /// The data layout doesn't fit the copy. Contains the reason.
+    InvalidLayout(&'static str),
+}
+
+impl fmt::Display for TextureConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TextureConversionError::UnsupportedFormat(format) => {
+                write!(
+                    f,
+                    "Pixels can't be converted to texture format {:?}",
+                    format
+                )
+            }
+            TextureConversionError::DataTooShort { required, len } => write!(
+                f,
+                "Texture write needs {} bytes of data, but only {} were given",
+                required, len
+            ),
+            TextureConversionError::InvalidLayout(reason) => {
+                write!(f, "Texture data layout is invalid: {}", reason)
+            }
+        }
+    }
+}
+
+impl std::error::Error for TextureConversionError {}
+
+/// How the channels of a texel are stored.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Encoding {
+    Unorm8,
+    Snorm8,
+    Uint8,
+    Sint8,
+    Unorm16,
+    Snorm16,
+    Uint16,
+    Sint16,
+    Float16,
+    Uint32,
+    Sint32,
+    Float32,
+    Rgb10a2Unorm,
+    Rg11b10Float,
+    Rgb9e5Ufloat,
+}
+
+/// Returns the encoding and channel count of `format`, and whether its color channels are
+/// stored in reverse order.
+fn encoding(format: TextureFormat) -> Result<(Encoding, usize, bool), TextureConversionError> {
+    use Encoding as E;
+    use TextureFormat as Tf;
+
+    Ok(match format {
+        Tf::R8Unorm => (E::Unorm8, 1, false),
+        Tf::R8Snorm => (E::Snorm8, 1, false),
+        Tf::R8Uint => (E::Uint8, 1, false),
+        Tf::R8Sint => (E::Sint8, 1, false),
+        Tf::R16Unorm => (E::Unorm16, 1, false),
+        Tf::R16Snorm => (E::Snorm16, 1, false),
+        Tf::R16Uint => (E::Uint16, 1, false),
+        Tf::R16Sint => (E::Sint16, 1, false),
+        Tf::R16Float => (E::Float16, 1, false),
+        Tf::Rg8Unorm => (E::Unorm8, 2, false),
+        Tf::Rg8Snorm => (E::Snorm8, 2, false),
+        Tf::Rg8Uint => (E::Uint8, 2, false),
+        Tf::Rg8Sint => (E::Sint8, 2, false),
+        Tf::R32Uint => (E::Uint32, 1, false),
+        Tf::R32Sint => (E::Sint32, 1, false),
+        Tf::R32Float => (E::Float32, 1, false),
+        Tf::Rg16Unorm => (E::Unorm16, 2, false),
+        Tf::Rg16Snorm => (E::Snorm16, 2, false),
+        Tf::Rg16Uint => (E::Uint16, 2, false),
+        Tf::Rg16Sint => (E::Sint16, 2, false),
+        Tf::Rg16Float => (E::Float16, 2, false),
+        Tf::Rgba8Unorm | Tf::Rgba8UnormSrgb => (E::Unorm8, 4, false),
+        Tf::Rgba8Snorm => (E::Snorm8, 4, false),
+        Tf::Rgba8Uint => (E::Uint8, 4, false),
+        Tf::Rgba8Sint => (E::Sint8, 4, false),
+        Tf::Bgra8Unorm | Tf::Bgra8UnormSrgb => (E::Unorm8, 4, true),
+        Tf::Rgb10a2Unorm => (E::Rgb10a2Unorm, 4, false),
+        Tf::Rg11b10Float => (E::Rg11b10Float, 3, false),
+        Tf::Rg32Uint => (E::Uint32, 2, false),
+        Tf::Rg32Sint => (E::Sint32, 2, false),
+        Tf::Rg32Float => (E::Float32, 2, false),
+        Tf::Rgba16Uint => (E::Uint16, 4, false),
+        Tf::Rgba16Sint => (E::Sint16, 4, false),
+        Tf::Rgba16Unorm => (E::Unorm16, 4, false),
+        Tf::Rgba16Snorm => (E::Snorm16, 4, false),
+        Tf::Rgba16Float => (E::Float16, 4, false),
+        Tf::Rgba32Uint => (E::Uint32, 4, false),
+        Tf::Rgba32Sint => (E::Sint32, 4, false),
+        Tf::Rgba32Float => (E::Float32, 4, false),
+        Tf::Rgb9e5Ufloat => (E::Rgb9e5Ufloat, 3, false),
+        _ => return Err(TextureConversionError::UnsupportedFormat(format)),
+    })
+}
+
+/// Rounds `x / 2^shift` to the nearest integer, ties to even.
+fn round_shift(x: u32, shift: u32) -> u32 {
+    if shift == 0 {
+        return x;
+    }
+    if shift >= 32 {
+        return 0;
+    }
+    let quotient = x >> shift;
+    let remainder = x & ((1 << shift) - 1);
+    let half = 1 << (shift - 1);
+    if remainder > half || (remainder == half && quotient & 1 == 1) {
+        quotient + 1
+    } else {
+        quotient
+    }
+}
+
+/// Encodes the magnitude of `value` as a float with a 5 bit exponent, like the ones of
+/// `f16` and of the channels of `Rg11b10Float`, with `mantissa_bits` bits of mantissa.
+fn encode_small_float(value: f32, mantissa_bits: u32) -> u32 {
+    let infinity = 0x1f << mantissa_bits;
+    if value.is_nan() {
+        return infinity | 1 << (mantissa_bits - 1);
+    }
+    let bits = value.abs().to_bits();
+    let exponent = (bits >> 23) as i32 - 127;
+    let mantissa = bits & 0x7f_ffff;
+    if exponent > 15 {
+        infinity
+    } else if exponent >= -14 {
+        // Rounding may carry into the exponent, up to infinity.
+        let biased = ((exponent + 15) as u32) << 23 | mantissa;
+        round_shift(biased, 23 - mantissa_bits).min(infinity)
+    } else {
+        // Subnormal, with the implicit leading 1 of the source made explicit.
+        let shift = (9 - mantissa_bits as i32 - exponent) as u32;
+        round_shift(mantissa | 0x80_0000, shift)
+    }
+}
+
+fn encode_f16(value: f32) -> u16 {
+    let sign = if value.is_sign_negative() && !value.is_nan() {
+        0x8000
+    } else {
+        0
+    };
+    sign | encode_small_float(value, 10) as u16
+}
+
+/// Encodes an unsigned float with a 5 bit exponent, clamping negative values to 0.
+fn encode_ufloat(value: f32, mantissa_bits: u32) -> u32 {
+    if value > 0.0 || value.is_nan() {
+        encode_small_float(value, mantissa_bits)
+    } else {
+        0
+    }
+}
+
+/// Encodes RGB with the shared exponent of `Rgb9e5Ufloat`.
+fn encode_rgb9e5(rgb: [f64; 3]) -> u32 {
+    const MANTISSA_BITS: i32 = 9;
+    const EXPONENT_BIAS: i32 = 15;
+    const MAX_EXPONENT: i32 = 31;
+    let max_value = ((1 << MANTISSA_BITS) - 1) as f64 / (1 << MANTISSA_BITS) as f64
+        * 2f64.powi(MAX_EXPONENT - EXPONENT_BIAS);
+
+    let rgb = rgb.map(|value| {
+        if value > 0.0 {
+            value.min(max_value)
+        } else {
+            0.0
+        }
+    });
+    let max_channel = rgb[0].max(rgb[1]).max(rgb[2]);
+    let mut exponent =
+        (max_channel.log2().floor() as i32).max(-EXPONENT_BIAS - 1) + 1 + EXPONENT_BIAS;
+    let scale = |exponent: i32| 2f64.powi(exponent - EXPONENT_BIAS - MANTISSA_BITS);
+    if (max_channel / scale(exponent) + 0.5).floor() as i32 == 1 << MANTISSA_BITS {
+        exponent += 1;
+    }
+    let [r, g, b] = rgb.map(|value| (value / scale(exponent) + 0.5).floor() as u32);
+    r | g << 9 | b << 18 | (exponent as u32) << 27
+}
+
+/// Writes the RGBA `texel` to `out`. Integer formats get the source values unnormalized
+/// by `max_value`.
+fn encode(encoding: Encoding, channels: usize, texel: [f64; 4], max_value: f64, out: &mut [u8]) {
+    let [r, g, b, a] = texel;
+    let packed = match encoding {
+        Encoding::Rgb10a2Unorm => {
+            let [r, g, b] =
+                [r, g, b].map(|value| (value * 1023.0).round().clamp(0.0, 1023.0) as u32);
+            let a = (a * 3.0).round().clamp(0.0, 3.0) as u32;
+            Some(r | g << 10 | b << 20 | a << 30)
+        }
+        Encoding::Rg11b10Float => Some(
+            encode_ufloat(r as f32, 6)
+                | encode_ufloat(g as f32, 6) << 11
+                | encode_ufloat(b as f32, 5) << 22,
+        ),
+        Encoding::Rgb9e5Ufloat => Some(encode_rgb9e5([r, g, b])),
+        _ => None,
+    };
+    if let Some(packed) = packed {
+        out.copy_from_slice(&packed.to_le_bytes());
+        return;
+    }
+
+    // Float to integer casts saturate, which clamps values out of range.
+    let size = out.len() / channels;
+    for (value, out) in texel.iter().zip(out.chunks_exact_mut(size)) {
+        match encoding {
+            Encoding::Unorm8 => out[0] = (value * 255.0).round() as u8,
+            Encoding::Snorm8 => out[0] = (value * 127.0).round().max(-127.0) as i8 as u8,
+            Encoding::Uint8 => out[0] = (value * max_value).round() as u8,
+            Encoding::Sint8 => out[0] = (value * max_value).round() as i8 as u8,
+            Encoding::Unorm16 => {
+                out.copy_from_slice(&((value * 65535.0).round() as u16).to_le_bytes())
+            }
+            Encoding::Snorm16 => {
+                out.copy_from_slice(&((value * 32767.0).round().max(-32767.0) as i16).to_le_bytes())
+            }
+            Encoding::Uint16 => {
+                out.copy_from_slice(&((value * max_value).round() as u16).to_le_bytes())
+            }
+            Encoding::Sint16 => {
+                out.copy_from_slice(&((value * max_value).round() as i16).to_le_bytes())
+            }
+            Encoding::Float16 => out.copy_from_slice(&encode_f16(*value as f32).to_le_bytes()),
+            Encoding::Uint32 => {
+                out.copy_from_slice(&((value * max_value).round() as u32).to_le_bytes())
+            }
+            Encoding::Sint32 => {
+                out.copy_from_slice(&((value * max_value).round() as i32).to_le_bytes())
+            }
+            Encoding::Float32 => out.copy_from_slice(&(*value as f32).to_le_bytes()),
+            Encoding::Rgb10a2Unorm | Encoding::Rg11b10Float | Encoding::Rgb9e5Ufloat => {
+                unreachable!()
+            }
+        }
+    }
+}
+
+/// Converts a row of `source` pixels to texels of `format`.
+///
+/// `out` holds as many texels as `data` holds pixels.
+fn convert_row(source: PixelLayout, data: &[u8], format: TextureFormat, out: &mut [u8]) {
+    let (encoding, channels, bgra) = encoding(format).unwrap();
+    let texel_size = format.describe().block_size as usize;
+    let max_value = source.max_value();
+    for (pixel, out) in data
+        .chunks_exact(source.bytes_per_pixel())
+        .zip(out.chunks_exact_mut(texel_size))
+    {
+        let mut texel = source.decode(pixel);
+        if bgra {
+            texel.swap(0, 2);
+        }
+        encode(encoding, channels, texel, max_value, out);
+    }
+}
+
+/// Converts tightly packed `source` pixels to texels of `format`.
+///
+/// Like [`QueueExt::write_texture_converted`], which streams the texels to a texture instead.
+pub fn convert_pixels(
+    source: PixelLayout,
+    data: &[u8],
+    format: TextureFormat,
+) -> Result<Vec<u8>, TextureConversionError> {
+    encoding(format)?;
+    let pixel_size = source.bytes_per_pixel();
+    if !data.chunks_exact(pixel_size).remainder().is_empty() {
+        return Err(TextureConversionError::InvalidLayout(
+            "data length isn't a multiple of the pixel size",
+        ));
+    }
+    let mut out = vec![0; data.len() / pixel_size * format.describe().block_size as usize];
+    convert_row(source, data, format, &mut out);
+    Ok(out)
+}
+
+/// Utility methods not meant to be in the main API.
+pub trait QueueExt {
+    /// Schedule a write of `data` into `texture`, converting its pixels from `source`
+    /// to `format`, which must be the format of `texture`.
+    ///
+    /// Any uncompressed color format is supported. Integer pixels are normalized, except
+    /// for integer formats, which get their value unchanged. Channels missing from
+    /// `source` are 0, and alpha is fully opaque. Values are converted as they are:
+    /// data uploaded to an sRGB format is expected to be sRGB encoded already.
+    ///
+    /// `data_layout` describes the rows of `data`, in pixels of `source`. The texels are
+    /// converted directly into the staging memory of [`Queue::write_texture_with`].
+    fn write_texture_converted(
+        &self,
+        texture: ImageCopyTexture,
+        format: TextureFormat,
+        source: PixelLayout,
+        data: &[u8],
+        data_layout: ImageDataLayout,
+        size: Extent3d,
+    ) -> Result<(), TextureConversionError>;
+}
+
+impl QueueExt for Queue {
+    fn write_texture_converted(
+        &self,
+        texture: ImageCopyTexture,
+        format: TextureFormat,
+        source: PixelLayout,
+        data: &[u8],
+        data_layout: ImageDataLayout,
+        size: Extent3d,
+    ) -> Result<(), TextureConversionError> {
+        encoding(format)?;
+
+        let width = size.width as u64;
+        let height = size.height as u64;
+        let layers = size.depth_or_array_layers as u64;
+        let row_len = width * source.bytes_per_pixel() as u64;
+        let bytes_per_row = data_layout
+            .bytes_per_row
+            .map_or(row_len, |bytes_per_row| bytes_per_row.get() as u64);
+        let rows_per_image = data_layout
+            .rows_per_image
+            .map_or(height, |rows_per_image| rows_per_image.get() as u64);
+        if bytes_per_row < row_len {
+            return Err(TextureConversionError::InvalidLayout(
+                "bytes_per_row is smaller than a row of pixels",
+            ));
+        }
+        if rows_per_image < height {
+            return Err(TextureConversionError::InvalidLayout(
+                "rows_per_image is smaller than the height",
+            ));
+        }
+        if width == 0 || height == 0 || layers == 0 {
+            return Ok(());
+        }
+
+        let required = data_layout.offset
+            + bytes_per_row * (rows_per_image * (layers - 1) + height - 1)
+            + row_len;
+        if (data.len() as u64) < required {
+            return Err(TextureConversionError::DataTooShort {
+                required,
+                len: data.len() as u64,
+            });
+        }
+
+        let texel_row_len = width * format.describe().block_size as u64;
+        let stage_bytes_per_row =
+            super::align_to(texel_row_len, COPY_BYTES_PER_ROW_ALIGNMENT as u64);
+        let stage_bytes_per_row = u32::try_from(stage_bytes_per_row)
+            .ok()
+            .and_then(NonZeroU32::new)
+            .ok_or(TextureConversionError::InvalidLayout(
+                "rows are too long to be copied",
+            ))?;
+        let mut view = self.write_texture_with(
+            texture,
+            ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(stage_bytes_per_row),
+                rows_per_image: NonZeroU32::new(size.height),
+            },
+            size,
+        );
+
+        for layer in 0..layers {
+            for row in 0..height {
+                let src =
+                    (data_layout.offset + (layer * rows_per_image + row) * bytes_per_row) as usize;
+                let dst = ((layer * height + row) * stage_bytes_per_row.get() as u64) as usize;
+                convert_row(
+                    source,
+                    &data[src..src + row_len as usize],
+                    format,
+                    &mut view[dst..dst + texel_row_len as usize],
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -66,7 +66,8 @@ pub trait RenderEncoder<'a> {
         indirect_offset: BufferAddress,
     );
 
-    /// [`wgt::Features::PUSH_CONSTANTS`] must be enabled on the device in order to call this function.
+    /// [`wgt::Features::PUSH_CONSTANTS`] or [`wgt::Features::PUSH_CONSTANT_EMULATION`] must be
+    /// enabled on the device in order to call this function.
     ///
     /// Set push constant data.
     ///
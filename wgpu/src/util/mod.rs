@@ -1,10 +1,17 @@
 //! Utility structures and functions.
 
 mod belt;
+mod container;
+mod convert;
 mod device;
 mod encoder;
 mod indirect;
 mod init;
+mod preprocess;
+mod profiler;
+mod vertex;
+#[cfg(not(target_arch = "wasm32"))]
+mod watcher;
 
 use std::ops::{Add, Rem, Sub};
 use std::sync::Arc;
@@ -15,10 +22,20 @@ use std::{
 };
 
 pub use belt::StagingBelt;
+pub use container::{TextureContainer, TextureContainerError};
+pub use convert::{convert_pixels, PixelLayout, QueueExt, TextureConversionError};
 pub use device::{BufferInitDescriptor, DeviceExt};
 pub use encoder::RenderEncoder;
 pub use indirect::*;
 pub use init::*;
+pub use preprocess::{PreprocessError, PreprocessErrorKind, WgslPreprocessor};
+pub use profiler::{
+    write_chrome_trace, GpuProfiler, GpuProfilerError, GpuProfilerFrame, GpuTimerScopeResult,
+    ProfilerCommandRecorder, ProfilerTimer,
+};
+pub use vertex::{vertex_buffer_layouts, VertexBufferLayouts, VertexPacking};
+#[cfg(not(target_arch = "wasm32"))]
+pub use watcher::ShaderWatcher;
 
 /// Treat the given byte slice as a SPIR-V module.
 ///
@@ -0,0 +1,285 @@
+use std::{borrow::Cow, collections::HashSet, fmt};
+
+use crate::{ShaderSource, ShaderSourceMap, SourceLine};
+
+/// Loads the source of an included file.
+type Resolver<'a> = Box<dyn FnMut(&str) -> Option<Cow<'a, str>> + 'a>;
+
+/// Preprocessor for WGSL sources.
+///
+/// Lines whose first non-whitespace character is `#` are directives:
+///
+/// - `#define NAME` and `#undef NAME` set and clear a flag,
+/// - `#ifdef NAME`, `#ifndef NAME`, `#else` and `#endif` keep or drop the lines
+///   in between, depending on the flags,
+/// - `#include "path"` inserts the source returned by the resolver for `path`.
+///   Every file is included at most once, further includes of it are ignored.
+///
+/// Directives and dropped lines are removed from the output, the other lines are
+/// kept verbatim. The resulting [`ShaderSource::WgslPreprocessed`] carries a
+/// [`ShaderSourceMap`], so that errors and compilation messages point to the
+/// file and line the code comes from.
+///
+/// ```
+/// # use std::borrow::Cow;
+/// let mut preprocessor = wgpu::util::WgslPreprocessor::new(|path| match path {
+///     "constants.wgsl" => Some(Cow::Borrowed("let SCALE: f32 = 2.0;\n")),
+///     _ => None,
+/// })
+/// .define("SCALED");
+/// let source = preprocessor
+///     .preprocess(
+///         "main.wgsl",
+///         "#include \"constants.wgsl\"\n#ifdef SCALED\nlet factor = SCALE;\n#endif\n",
+///     )
+///     .unwrap();
+/// ```
+pub struct WgslPreprocessor<'a> {
+    resolver: Resolver<'a>,
+    defines: HashSet<String>,
+}
+
+impl fmt::Debug for WgslPreprocessor<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WgslPreprocessor")
+            .field("defines", &self.defines)
+            .finish()
+    }
+}
+
+impl<'a> WgslPreprocessor<'a> {
+    /// Creates a preprocessor that loads the sources of `#include` directives with `resolver`.
+    ///
+    /// The resolver returns `None` if there is no file at the given path.
+    pub fn new(resolver: impl FnMut(&str) -> Option<Cow<'a, str>> + 'a) -> Self {
+        Self {
+            resolver: Box::new(resolver),
+            defines: HashSet::new(),
+        }
+    }
+
+    /// Sets the flag `name` before preprocessing, like a `#define` at the top of the source.
+    pub fn define(mut self, name: impl Into<String>) -> Self {
+        self.defines.insert(name.into());
+        self
+    }
+
+    /// Preprocesses `source`, the content of the file `name`.
+    ///
+    /// Flags set by `#define` in a source don't carry over to the next call.
+    pub fn preprocess(
+        &mut self,
+        name: &str,
+        source: &str,
+    ) -> Result<ShaderSource<'static>, PreprocessError> {
+        let mut state = State {
+            resolver: &mut self.resolver,
+            defines: self.defines.clone(),
+            output: String::new(),
+            map: ShaderSourceMap::default(),
+            included: HashSet::new(),
+            stack: Vec::new(),
+        };
+        state.process(name, source)?;
+        Ok(ShaderSource::WgslPreprocessed {
+            source: Cow::Owned(state.output),
+            source_map: state.map,
+        })
+    }
+}
+
+/// A conditional block being processed.
+struct Conditional {
+    /// Whether the lines of the current branch are kept.
+    active: bool,
+    /// Whether the lines of the enclosing block are kept.
+    parent_active: bool,
+    /// Whether `#else` has been seen.
+    in_else: bool,
+}
+
+struct State<'r, 'a> {
+    resolver: &'r mut Resolver<'a>,
+    defines: HashSet<String>,
+    output: String,
+    map: ShaderSourceMap,
+    included: HashSet<String>,
+    /// Files being processed, to detect recursive includes.
+    stack: Vec<String>,
+}
+
+impl State<'_, '_> {
+    fn process(&mut self, name: &str, source: &str) -> Result<(), PreprocessError> {
+        let file = self.map.files.len() as u32;
+        self.map.files.push(name.to_string());
+        self.included.insert(name.to_string());
+        self.stack.push(name.to_string());
+
+        let mut conditionals = Vec::<Conditional>::new();
+        let mut file_offset = 0;
+        for (index, line) in source.split_inclusive('\n').enumerate() {
+            let line_number = index as u32 + 1;
+            let error = |kind| PreprocessError {
+                file: name.to_string(),
+                line_number,
+                kind,
+            };
+            let active = conditionals.iter().all(|c| c.active);
+
+            let trimmed = line.trim();
+            if let Some(directive) = trimmed.strip_prefix('#') {
+                let (keyword, argument) = match directive.split_once(char::is_whitespace) {
+                    Some((keyword, argument)) => (keyword, argument.trim()),
+                    None => (directive, ""),
+                };
+                let flag = || {
+                    if argument.is_empty() || argument.contains(char::is_whitespace) {
+                        Err(error(PreprocessErrorKind::InvalidDirective(
+                            trimmed.to_string(),
+                        )))
+                    } else {
+                        Ok(argument)
+                    }
+                };
+                match keyword {
+                    "define" => {
+                        let name = flag()?;
+                        if active {
+                            self.defines.insert(name.to_string());
+                        }
+                    }
+                    "undef" => {
+                        let name = flag()?;
+                        if active {
+                            self.defines.remove(name);
+                        }
+                    }
+                    "ifdef" | "ifndef" => {
+                        let defined = self.defines.contains(flag()?);
+                        conditionals.push(Conditional {
+                            active: active && defined == (keyword == "ifdef"),
+                            parent_active: active,
+                            in_else: false,
+                        });
+                    }
+                    "else" => match conditionals.last_mut() {
+                        Some(c) if !c.in_else => {
+                            c.in_else = true;
+                            c.active = c.parent_active && !c.active;
+                        }
+                        _ => {
+                            return Err(error(PreprocessErrorKind::UnexpectedDirective(
+                                trimmed.to_string(),
+                            )))
+                        }
+                    },
+                    "endif" => {
+                        if conditionals.pop().is_none() {
+                            return Err(error(PreprocessErrorKind::UnexpectedDirective(
+                                trimmed.to_string(),
+                            )));
+                        }
+                    }
+                    "include" if active => {
+                        let path = argument
+                            .strip_prefix('"')
+                            .and_then(|path| path.strip_suffix('"'))
+                            .ok_or_else(|| {
+                                error(PreprocessErrorKind::InvalidDirective(trimmed.to_string()))
+                            })?;
+                        if self.stack.iter().any(|file| file == path) {
+                            return Err(error(PreprocessErrorKind::RecursiveInclude(
+                                path.to_string(),
+                            )));
+                        }
+                        if !self.included.contains(path) {
+                            let source = (self.resolver)(path).ok_or_else(|| {
+                                error(PreprocessErrorKind::IncludeNotFound(path.to_string()))
+                            })?;
+                            self.process(path, &source)?;
+                        }
+                    }
+                    "include" => {}
+                    _ => {
+                        return Err(error(PreprocessErrorKind::InvalidDirective(
+                            trimmed.to_string(),
+                        )))
+                    }
+                }
+            } else if active {
+                self.map.lines.push(SourceLine {
+                    file,
+                    line_number,
+                    file_offset,
+                    offset: self.output.len() as u32,
+                });
+                self.output.push_str(line);
+                if !line.ends_with('\n') {
+                    self.output.push('\n');
+                }
+            }
+            file_offset += line.len() as u32;
+        }
+
+        if !conditionals.is_empty() {
+            return Err(PreprocessError {
+                file: name.to_string(),
+                line_number: source.split_inclusive('\n').count() as u32,
+                kind: PreprocessErrorKind::UnterminatedConditional,
+            });
+        }
+        self.stack.pop();
+        Ok(())
+    }
+}
+
+/// Error returned by [`WgslPreprocessor::preprocess`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PreprocessError {
+    /// Name of the file containing the error.
+    pub file: String,
+    /// 1-based line number of the error in the file.
+    pub line_number: u32,
+    /// What went wrong.
+    pub kind: PreprocessErrorKind,
+}
+
+/// Kind of a [`PreprocessError`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PreprocessErrorKind {
+    /// The directive is unknown or its argument is malformed.
+    InvalidDirective(String),
+    /// `#else` or `#endif` without a matching `#ifdef` or `#ifndef`.
+    UnexpectedDirective(String),
+    /// The file ends inside an `#ifdef` or `#ifndef` block.
+    UnterminatedConditional,
+    /// The resolver returned no source for the included path.
+    IncludeNotFound(String),
+    /// The file includes itself, directly or not.
+    RecursiveInclude(String),
+}
+
+impl fmt::Display for PreprocessError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: ", self.file, self.line_number)?;
+        match self.kind {
+            PreprocessErrorKind::InvalidDirective(ref line) => {
+                write!(f, "Invalid directive `{}`", line)
+            }
+            PreprocessErrorKind::UnexpectedDirective(ref line) => {
+                write!(f, "`{}` without a matching `#ifdef` or `#ifndef`", line)
+            }
+            PreprocessErrorKind::UnterminatedConditional => {
+                write!(f, "Missing `#endif` at the end of the file")
+            }
+            PreprocessErrorKind::IncludeNotFound(ref path) => {
+                write!(f, "Included file {:?} not found", path)
+            }
+            PreprocessErrorKind::RecursiveInclude(ref path) => {
+                write!(f, "File {:?} is included recursively", path)
+            }
+        }
+    }
+}
+
+impl std::error::Error for PreprocessError {}
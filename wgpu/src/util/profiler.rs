@@ -0,0 +1,640 @@
+use crate::{
+    Buffer, BufferDescriptor, BufferUsages, CommandEncoder, ComputePass, Device, Features, MapMode,
+    QuerySet, QuerySetDescriptor, QueryType, Queue, RenderPass, QUERY_SET_MAX_QUERIES, QUERY_SIZE,
+};
+use std::{
+    collections::VecDeque,
+    fmt, io,
+    ops::Range,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+/// Number of queries in the first query set allocated by a [`GpuProfiler`].
+///
+/// Later query sets double in size, up to [`QUERY_SET_MAX_QUERIES`].
+const INITIAL_QUERY_POOL_SIZE: u32 = 64;
+
+/// Anything a [`GpuProfiler`] scope can be opened on.
+///
+/// Implemented for [`CommandEncoder`], [`RenderPass`] and [`ComputePass`].
+pub trait ProfilerCommandRecorder {
+    /// Returns `true` if timestamps written here need
+    /// [`Features::WRITE_TIMESTAMP_INSIDE_PASSES`].
+    fn is_pass(&self) -> bool;
+    /// Writes a timestamp into `query_set` at `query_index`.
+    fn write_timestamp(&mut self, query_set: &QuerySet, query_index: u32);
+    /// Opens a debug group, so that profiler scopes show up in capture tools.
+    fn push_debug_group(&mut self, label: &str);
+    /// Closes the debug group opened by [`ProfilerCommandRecorder::push_debug_group`].
+    fn pop_debug_group(&mut self);
+}
+
+impl ProfilerCommandRecorder for CommandEncoder {
+    fn is_pass(&self) -> bool {
+        false
+    }
+    fn write_timestamp(&mut self, query_set: &QuerySet, query_index: u32) {
+        CommandEncoder::write_timestamp(self, query_set, query_index)
+    }
+    fn push_debug_group(&mut self, label: &str) {
+        CommandEncoder::push_debug_group(self, label)
+    }
+    fn pop_debug_group(&mut self) {
+        CommandEncoder::pop_debug_group(self)
+    }
+}
+
+impl<'a> ProfilerCommandRecorder for RenderPass<'a> {
+    fn is_pass(&self) -> bool {
+        true
+    }
+    fn write_timestamp(&mut self, query_set: &QuerySet, query_index: u32) {
+        RenderPass::write_timestamp(self, query_set, query_index)
+    }
+    fn push_debug_group(&mut self, label: &str) {
+        RenderPass::push_debug_group(self, label)
+    }
+    fn pop_debug_group(&mut self) {
+        RenderPass::pop_debug_group(self)
+    }
+}
+
+impl<'a> ProfilerCommandRecorder for ComputePass<'a> {
+    fn is_pass(&self) -> bool {
+        true
+    }
+    fn write_timestamp(&mut self, query_set: &QuerySet, query_index: u32) {
+        ComputePass::write_timestamp(self, query_set, query_index)
+    }
+    fn push_debug_group(&mut self, label: &str) {
+        ComputePass::push_debug_group(self, label)
+    }
+    fn pop_debug_group(&mut self) {
+        ComputePass::pop_debug_group(self)
+    }
+}
+
+/// Where the timings of a [`GpuProfilerFrame`] come from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProfilerTimer {
+    /// Timestamp queries executed on the GPU timeline.
+    GpuTimestamp,
+    /// CPU clock readings taken while the commands were recorded.
+    ///
+    /// Used when [`Features::TIMESTAMP_QUERY`] is not enabled on the device.
+    Cpu,
+}
+
+/// Timing of a single profiler scope, with all scopes nested in it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GpuTimerScopeResult {
+    /// Label passed to [`GpuProfiler::begin_scope`].
+    pub label: String,
+    /// Start and end of the scope, in milliseconds.
+    ///
+    /// Absolute values have no meaning, only differences between timings of
+    /// the same [`ProfilerTimer`] do.
+    pub time: Range<f64>,
+    /// Scopes that were opened and closed inside this one.
+    pub nested_scopes: Vec<GpuTimerScopeResult>,
+}
+
+impl GpuTimerScopeResult {
+    /// Duration of the scope in milliseconds.
+    pub fn duration(&self) -> f64 {
+        self.time.end - self.time.start
+    }
+}
+
+/// All top level scopes recorded between two calls to [`GpuProfiler::end_frame`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct GpuProfilerFrame {
+    /// Index of the frame, counting up from zero for each call to [`GpuProfiler::end_frame`].
+    pub frame_index: u64,
+    /// Clock the timings were taken with.
+    pub timer: ProfilerTimer,
+    /// Top level scopes, in the order they were opened.
+    pub scopes: Vec<GpuTimerScopeResult>,
+}
+
+/// Misuse of a [`GpuProfiler`] detected by [`GpuProfiler::end_frame`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GpuProfilerError {
+    /// Some scopes opened with [`GpuProfiler::begin_scope`] were never closed.
+    UnclosedScopes(Vec<String>),
+    /// Timestamps were written since the last call to [`GpuProfiler::resolve_queries`].
+    UnresolvedQueries,
+}
+
+impl fmt::Display for GpuProfilerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GpuProfilerError::UnclosedScopes(labels) => {
+                write!(f, "Profiler scopes {:?} are still open at the end of the frame", labels)
+            }
+            GpuProfilerError::UnresolvedQueries => write!(
+                f,
+                "Profiler queries must be resolved with `GpuProfiler::resolve_queries` before ending the frame"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for GpuProfilerError {}
+
+/// A query set along with the buffer its timestamps are resolved into and read back from.
+struct QueryPool {
+    query_set: QuerySet,
+    read_buffer: Buffer,
+    capacity: u32,
+    num_used: u32,
+    num_resolved: u32,
+    mapped: Arc<AtomicBool>,
+}
+
+impl QueryPool {
+    fn new(device: &Device, capacity: u32) -> Self {
+        let size = (capacity * QUERY_SIZE) as u64;
+        QueryPool {
+            query_set: device.create_query_set(&QuerySetDescriptor {
+                label: Some("(wgpu internal) GpuProfiler query set"),
+                ty: QueryType::Timestamp,
+                count: capacity,
+            }),
+            read_buffer: device.create_buffer(&BufferDescriptor {
+                label: Some("(wgpu internal) GpuProfiler read buffer"),
+                size,
+                usage: BufferUsages::COPY_DST | BufferUsages::MAP_READ,
+                mapped_at_creation: false,
+            }),
+            capacity,
+            num_used: 0,
+            num_resolved: 0,
+            mapped: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    fn used_size(&self) -> u64 {
+        (self.num_used * QUERY_SIZE) as u64
+    }
+
+    /// Make the pool available to another frame, cancelling the mapping of the
+    /// read buffer if it is still pending.
+    fn reset(&mut self) {
+        self.read_buffer.unmap();
+        self.num_used = 0;
+        self.num_resolved = 0;
+        self.mapped.store(false, Ordering::Release);
+    }
+}
+
+/// How the start and end of a scope are measured.
+#[derive(Clone, Copy, Debug)]
+enum ScopeTiming {
+    /// A pair of consecutive queries in one of the frame's query pools.
+    Gpu { pool: usize, query: u32 },
+    /// CPU clock readings in milliseconds.
+    Cpu { start: f64, end: f64 },
+    /// The scope was opened on a pass without [`Features::WRITE_TIMESTAMP_INSIDE_PASSES`].
+    ///
+    /// It is dropped from the results and its nested scopes are moved to its parent.
+    Untimed,
+}
+
+struct UnprocessedScope {
+    label: String,
+    timing: ScopeTiming,
+    nested_scopes: Vec<UnprocessedScope>,
+}
+
+#[derive(Default)]
+struct Frame {
+    pools: Vec<QueryPool>,
+    scopes: Vec<UnprocessedScope>,
+}
+
+struct PendingFrame {
+    frame_index: u64,
+    frame: Frame,
+}
+
+impl PendingFrame {
+    fn is_mapped(&self) -> bool {
+        self.frame
+            .pools
+            .iter()
+            .all(|pool| pool.mapped.load(Ordering::Acquire))
+    }
+}
+
+/// Clock used for scopes that can't be timed on the GPU.
+struct CpuClock {
+    #[cfg(not(all(target_arch = "wasm32", not(target_os = "emscripten"))))]
+    epoch: std::time::Instant,
+}
+
+impl CpuClock {
+    fn new() -> Self {
+        CpuClock {
+            #[cfg(not(all(target_arch = "wasm32", not(target_os = "emscripten"))))]
+            epoch: std::time::Instant::now(),
+        }
+    }
+
+    fn now_ms(&self) -> f64 {
+        #[cfg(not(all(target_arch = "wasm32", not(target_os = "emscripten"))))]
+        return self.epoch.elapsed().as_secs_f64() * 1000.0;
+        #[cfg(all(target_arch = "wasm32", not(target_os = "emscripten")))]
+        return js_sys::Date::now();
+    }
+}
+
+/// Records nested timer scopes on command encoders and passes, and reads their timings back.
+///
+/// When [`Features::TIMESTAMP_QUERY`] is enabled on the device, every scope writes
+/// a pair of timestamp queries. Query sets are pooled and reused across frames,
+/// and results are read back through mapped buffers a few frames later, so that
+/// profiling never stalls the GPU. Without the feature, scopes are timed with the
+/// CPU clock while recording instead.
+///
+/// Scopes opened on a [`RenderPass`] or [`ComputePass`] are only timed if
+/// [`Features::WRITE_TIMESTAMP_INSIDE_PASSES`] is enabled as well; otherwise they are
+/// skipped and only the scopes around the pass show up in the results.
+///
+/// Using a profiler generally goes as follows:
+/// 1. Open and close scopes with [`GpuProfiler::begin_scope()`] and [`GpuProfiler::end_scope()`].
+/// 2. Call [`GpuProfiler::resolve_queries()`] on the last encoder of the frame.
+/// 3. Submit all command encoders that were used in steps 1 and 2.
+/// 4. Call [`GpuProfiler::end_frame()`].
+/// 5. Call [`GpuProfiler::process_finished_frame()`] to collect the timings of earlier frames
+///    that are done on the GPU. As with other buffer mappings, the device must be polled
+///    for this to make progress.
+pub struct GpuProfiler {
+    timestamps: bool,
+    timestamps_in_passes: bool,
+    /// Nanoseconds per timestamp tick.
+    timestamp_period: f32,
+    max_pending_frames: usize,
+    next_frame_index: u64,
+    active_frame: Frame,
+    /// Frames that were ended, but whose results haven't been read back yet.
+    pending_frames: VecDeque<PendingFrame>,
+    /// Pools that were read back and can be written to again.
+    free_pools: Vec<QueryPool>,
+    open_scopes: Vec<UnprocessedScope>,
+    cpu_clock: CpuClock,
+}
+
+impl GpuProfiler {
+    /// Create a new profiler for `device`.
+    ///
+    /// `max_pending_frames` is the number of ended frames whose results may be in flight at
+    /// once. When it is exceeded, [`GpuProfiler::end_frame`] drops the oldest pending frame.
+    pub fn new(device: &Device, queue: &Queue, max_pending_frames: usize) -> Self {
+        assert!(
+            max_pending_frames > 0,
+            "max_pending_frames must be at least 1"
+        );
+        let features = device.features();
+        GpuProfiler {
+            timestamps: features.contains(Features::TIMESTAMP_QUERY),
+            timestamps_in_passes: features
+                .contains(Features::TIMESTAMP_QUERY | Features::WRITE_TIMESTAMP_INSIDE_PASSES),
+            timestamp_period: queue.get_timestamp_period(),
+            max_pending_frames,
+            next_frame_index: 0,
+            active_frame: Frame::default(),
+            pending_frames: VecDeque::new(),
+            free_pools: Vec::new(),
+            open_scopes: Vec::new(),
+            cpu_clock: CpuClock::new(),
+        }
+    }
+
+    /// Clock the timings of this profiler are taken with.
+    pub fn timer(&self) -> ProfilerTimer {
+        if self.timestamps {
+            ProfilerTimer::GpuTimestamp
+        } else {
+            ProfilerTimer::Cpu
+        }
+    }
+
+    /// Open a new scope named `label` on `recorder`.
+    ///
+    /// Scopes nest: each must be closed with [`GpuProfiler::end_scope`] on the same
+    /// recorder, and scopes opened in between become its children. The scope is also
+    /// pushed as a debug group, so it shows up in graphics debuggers.
+    pub fn begin_scope<R: ProfilerCommandRecorder>(
+        &mut self,
+        label: &str,
+        recorder: &mut R,
+        device: &Device,
+    ) {
+        let timing = if !self.timestamps {
+            let start = self.cpu_clock.now_ms();
+            ScopeTiming::Cpu { start, end: start }
+        } else if recorder.is_pass() && !self.timestamps_in_passes {
+            ScopeTiming::Untimed
+        } else {
+            let (pool, query) = self.allocate_query_pair(device);
+            recorder.write_timestamp(&self.active_frame.pools[pool].query_set, query);
+            ScopeTiming::Gpu { pool, query }
+        };
+
+        recorder.push_debug_group(label);
+        self.open_scopes.push(UnprocessedScope {
+            label: label.to_string(),
+            timing,
+            nested_scopes: Vec::new(),
+        });
+    }
+
+    /// Close the scope most recently opened with [`GpuProfiler::begin_scope`].
+    ///
+    /// # Panics
+    ///
+    /// - There is no open scope.
+    pub fn end_scope<R: ProfilerCommandRecorder>(&mut self, recorder: &mut R) {
+        let mut scope = self
+            .open_scopes
+            .pop()
+            .expect("GpuProfiler::end_scope called without a matching begin_scope");
+
+        recorder.pop_debug_group();
+        match scope.timing {
+            ScopeTiming::Gpu { pool, query } => {
+                recorder.write_timestamp(&self.active_frame.pools[pool].query_set, query + 1);
+            }
+            ScopeTiming::Cpu { ref mut end, .. } => {
+                *end = self.cpu_clock.now_ms();
+            }
+            ScopeTiming::Untimed => {}
+        }
+
+        let siblings = match self.open_scopes.last_mut() {
+            Some(parent) => &mut parent.nested_scopes,
+            None => &mut self.active_frame.scopes,
+        };
+        match scope.timing {
+            ScopeTiming::Untimed => siblings.append(&mut scope.nested_scopes),
+            _ => siblings.push(scope),
+        }
+    }
+
+    /// Record `f` in a scope named `label`.
+    ///
+    /// Equivalent to calling [`GpuProfiler::begin_scope`] and [`GpuProfiler::end_scope`]
+    /// around `f`.
+    pub fn scope<R: ProfilerCommandRecorder, T>(
+        &mut self,
+        label: &str,
+        recorder: &mut R,
+        device: &Device,
+        f: impl FnOnce(&mut Self, &mut R) -> T,
+    ) -> T {
+        self.begin_scope(label, recorder, device);
+        let result = f(self, recorder);
+        self.end_scope(recorder);
+        result
+    }
+
+    /// Resolve all timestamps written in the current frame so far into buffers that can be read back.
+    ///
+    /// Must be called on an encoder that is submitted after all encoders with scopes of this
+    /// frame, and before [`GpuProfiler::end_frame`]. Calling it more than once per frame is allowed.
+    pub fn resolve_queries(&mut self, encoder: &mut CommandEncoder) {
+        for pool in self.active_frame.pools.iter_mut() {
+            if pool.num_resolved == pool.num_used {
+                continue;
+            }
+            // The destination offset of a resolve must be aligned to
+            // `QUERY_RESOLVE_BUFFER_ALIGNMENT`, so resolve the whole used range again.
+            encoder.resolve_query_set(&pool.query_set, 0..pool.num_used, &pool.read_buffer, 0);
+            pool.num_resolved = pool.num_used;
+        }
+    }
+
+    /// Mark the end of a frame and start reading back its results.
+    ///
+    /// Must be called after the encoders of the frame have been submitted.
+    /// Fails if scopes are still open or timestamps haven't been resolved; in that
+    /// case the frame stays active.
+    pub fn end_frame(&mut self) -> Result<(), GpuProfilerError> {
+        if !self.open_scopes.is_empty() {
+            return Err(GpuProfilerError::UnclosedScopes(
+                self.open_scopes
+                    .iter()
+                    .map(|scope| scope.label.clone())
+                    .collect(),
+            ));
+        }
+        if self
+            .active_frame
+            .pools
+            .iter()
+            .any(|pool| pool.num_resolved != pool.num_used)
+        {
+            return Err(GpuProfilerError::UnresolvedQueries);
+        }
+
+        let frame = std::mem::take(&mut self.active_frame);
+        for pool in frame.pools.iter() {
+            let mapped = Arc::clone(&pool.mapped);
+            pool.read_buffer
+                .slice(..pool.used_size())
+                .map_async(MapMode::Read, move |result| {
+                    if result.is_ok() {
+                        mapped.store(true, Ordering::Release);
+                    }
+                });
+        }
+
+        if self.pending_frames.len() == self.max_pending_frames {
+            let dropped = self.pending_frames.pop_front().unwrap();
+            log::warn!(
+                "GpuProfiler dropped the results of frame {}, the GPU is {} frames behind",
+                dropped.frame_index,
+                self.max_pending_frames,
+            );
+            for mut pool in dropped.frame.pools {
+                pool.reset();
+                self.free_pools.push(pool);
+            }
+        }
+        self.pending_frames.push_back(PendingFrame {
+            frame_index: self.next_frame_index,
+            frame,
+        });
+        self.next_frame_index += 1;
+        Ok(())
+    }
+
+    /// Collect the results of the oldest ended frame, if they are available.
+    ///
+    /// Returns `None` if no frame is pending or the GPU hasn't finished the oldest
+    /// one yet. The device must be polled for pending frames to become available.
+    pub fn process_finished_frame(&mut self) -> Option<GpuProfilerFrame> {
+        if !self.pending_frames.front()?.is_mapped() {
+            return None;
+        }
+        let PendingFrame { frame_index, frame } = self.pending_frames.pop_front().unwrap();
+
+        let timestamps = frame
+            .pools
+            .iter()
+            .map(|pool| {
+                let view = pool
+                    .read_buffer
+                    .slice(..pool.used_size())
+                    .get_mapped_range();
+                view.chunks_exact(QUERY_SIZE as usize)
+                    .map(|bytes| u64::from_le_bytes(bytes.try_into().unwrap()))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let ms_per_tick = self.timestamp_period as f64 / 1_000_000.0;
+        let scopes = frame
+            .scopes
+            .into_iter()
+            .map(|scope| Self::process_scope(scope, &timestamps, ms_per_tick))
+            .collect();
+
+        for mut pool in frame.pools {
+            pool.reset();
+            self.free_pools.push(pool);
+        }
+
+        Some(GpuProfilerFrame {
+            frame_index,
+            timer: self.timer(),
+            scopes,
+        })
+    }
+
+    fn process_scope(
+        scope: UnprocessedScope,
+        timestamps: &[Vec<u64>],
+        ms_per_tick: f64,
+    ) -> GpuTimerScopeResult {
+        let time = match scope.timing {
+            ScopeTiming::Gpu { pool, query } => {
+                let start = timestamps[pool][query as usize];
+                let end = timestamps[pool][query as usize + 1];
+                start as f64 * ms_per_tick..end as f64 * ms_per_tick
+            }
+            ScopeTiming::Cpu { start, end } => start..end,
+            ScopeTiming::Untimed => unreachable!("untimed scopes are never stored"),
+        };
+        GpuTimerScopeResult {
+            label: scope.label,
+            time,
+            nested_scopes: scope
+                .nested_scopes
+                .into_iter()
+                .map(|nested| Self::process_scope(nested, timestamps, ms_per_tick))
+                .collect(),
+        }
+    }
+
+    /// Reserve two consecutive queries in the active frame, returning the pool and first query index.
+    fn allocate_query_pair(&mut self, device: &Device) -> (usize, u32) {
+        let num_pools = self.active_frame.pools.len();
+        if let Some(pool) = self.active_frame.pools.last_mut() {
+            if pool.num_used + 2 <= pool.capacity {
+                pool.num_used += 2;
+                return (num_pools - 1, pool.num_used - 2);
+            }
+        }
+
+        let mut pool = match self.free_pools.pop() {
+            Some(pool) => pool,
+            None => {
+                let capacity = self
+                    .active_frame
+                    .pools
+                    .last()
+                    .map_or(INITIAL_QUERY_POOL_SIZE, |pool| pool.capacity * 2)
+                    .min(QUERY_SET_MAX_QUERIES);
+                QueryPool::new(device, capacity)
+            }
+        };
+        pool.num_used = 2;
+        self.active_frame.pools.push(pool);
+        (self.active_frame.pools.len() - 1, 0)
+    }
+}
+
+impl fmt::Debug for GpuProfiler {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GpuProfiler")
+            .field("timer", &self.timer())
+            .field("timestamps_in_passes", &self.timestamps_in_passes)
+            .field("next_frame_index", &self.next_frame_index)
+            .field("open_scopes", &self.open_scopes.len())
+            .field("pending_frames", &self.pending_frames.len())
+            .field("free_pools", &self.free_pools.len())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Write `frames` in the Chrome trace event format.
+///
+/// The output can be loaded in `chrome://tracing` or [Perfetto](https://ui.perfetto.dev).
+/// Every scope becomes a complete event, tagged with the index of its frame.
+pub fn write_chrome_trace<W: io::Write>(
+    mut writer: W,
+    frames: &[GpuProfilerFrame],
+) -> io::Result<()> {
+    fn write_scope<W: io::Write>(
+        writer: &mut W,
+        scope: &GpuTimerScopeResult,
+        frame_index: u64,
+        first: &mut bool,
+    ) -> io::Result<()> {
+        if !*first {
+            writeln!(writer, ",")?;
+        }
+        *first = false;
+        write!(
+            writer,
+            r#"{{"name":"{}","cat":"gpu","ph":"X","ts":{:.3},"dur":{:.3},"pid":0,"tid":0,"args":{{"frame":{}}}}}"#,
+            escape_json(&scope.label),
+            scope.time.start * 1000.0,
+            scope.duration() * 1000.0,
+            frame_index,
+        )?;
+        for nested in scope.nested_scopes.iter() {
+            write_scope(writer, nested, frame_index, first)?;
+        }
+        Ok(())
+    }
+
+    writeln!(writer, r#"{{"traceEvents":["#)?;
+    let mut first = true;
+    for frame in frames {
+        for scope in frame.scopes.iter() {
+            write_scope(&mut writer, scope, frame.frame_index, &mut first)?;
+        }
+    }
+    writeln!(writer, "\n]}}")
+}
+
+fn escape_json(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,131 @@
+use crate::{
+    BufferAddress, ShaderModule, VertexAttribute, VertexBufferLayout, VertexInput, VertexStepMode,
+    VERTEX_STRIDE_ALIGNMENT,
+};
+
+use super::align_to;
+
+/// How vertex attributes are laid out in vertex buffers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum VertexPacking {
+    /// All attributes are interleaved in a single buffer, in location order.
+    Interleaved,
+    /// Each attribute gets its own buffer ("struct of arrays"), in location order.
+    Separate,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct OwnedVertexBufferLayout {
+    array_stride: BufferAddress,
+    step_mode: VertexStepMode,
+    attributes: Vec<VertexAttribute>,
+}
+
+/// Vertex buffer layouts for a set of vertex inputs.
+///
+/// Offsets are aligned to the size of their format (or 4 bytes for larger
+/// formats) and strides to [`VERTEX_STRIDE_ALIGNMENT`], as required by
+/// [`Device::create_render_pipeline`]. Limits such as
+/// [`Limits::max_vertex_buffers`] aren't checked.
+///
+/// [`Device::create_render_pipeline`]: crate::Device::create_render_pipeline
+/// [`Limits::max_vertex_buffers`]: crate::Limits::max_vertex_buffers
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct VertexBufferLayouts {
+    buffers: Vec<OwnedVertexBufferLayout>,
+}
+
+impl VertexBufferLayouts {
+    /// Lays out `inputs` in vertex buffers stepped with `step_mode`.
+    ///
+    /// The formats of reflected inputs may be replaced with smaller ones of
+    /// the same kind beforehand, e.g. [`VertexFormat::Unorm8x4`] for a
+    /// `vec4<f32>` color.
+    ///
+    /// [`VertexFormat::Unorm8x4`]: crate::VertexFormat::Unorm8x4
+    pub fn new(inputs: &[VertexInput], packing: VertexPacking, step_mode: VertexStepMode) -> Self {
+        let attribute = |input: &VertexInput, offset| VertexAttribute {
+            format: input.format,
+            offset,
+            shader_location: input.shader_location,
+        };
+        let alignment = |input: &VertexInput| input.format.size().min(VERTEX_STRIDE_ALIGNMENT);
+
+        let buffers = match packing {
+            VertexPacking::Interleaved if inputs.is_empty() => Vec::new(),
+            VertexPacking::Interleaved => {
+                let mut offset = 0;
+                let attributes = inputs
+                    .iter()
+                    .map(|input| {
+                        let start = align_to(offset, alignment(input));
+                        offset = start + input.format.size();
+                        attribute(input, start)
+                    })
+                    .collect();
+                vec![OwnedVertexBufferLayout {
+                    array_stride: align_to(offset, VERTEX_STRIDE_ALIGNMENT),
+                    step_mode,
+                    attributes,
+                }]
+            }
+            VertexPacking::Separate => inputs
+                .iter()
+                .map(|input| OwnedVertexBufferLayout {
+                    array_stride: align_to(input.format.size(), VERTEX_STRIDE_ALIGNMENT),
+                    step_mode,
+                    attributes: vec![attribute(input, 0)],
+                })
+                .collect(),
+        };
+        Self { buffers }
+    }
+
+    /// Returns the layouts, to be used in [`VertexState::buffers`].
+    ///
+    /// [`VertexState::buffers`]: crate::VertexState::buffers
+    pub fn layouts(&self) -> Vec<VertexBufferLayout<'_>> {
+        self.buffers
+            .iter()
+            .map(|buffer| VertexBufferLayout {
+                array_stride: buffer.array_stride,
+                step_mode: buffer.step_mode,
+                attributes: &buffer.attributes,
+            })
+            .collect()
+    }
+
+    /// Returns the attributes of the buffer at `index`.
+    pub fn attributes(&self, index: usize) -> &[VertexAttribute] {
+        &self.buffers[index].attributes
+    }
+
+    /// Returns the number of vertex buffers.
+    pub fn len(&self) -> usize {
+        self.buffers.len()
+    }
+
+    /// Returns `true` if there are no vertex buffers.
+    pub fn is_empty(&self) -> bool {
+        self.buffers.is_empty()
+    }
+}
+
+/// Builds vertex buffer layouts matching the inputs of the vertex entry point
+/// `entry_point` of `module`, with the formats returned by
+/// [`ShaderModule::get_vertex_inputs`].
+///
+/// Returns `None` when the inputs can't be reflected, see
+/// [`ShaderModule::get_vertex_inputs`].
+pub fn vertex_buffer_layouts(
+    module: &ShaderModule,
+    entry_point: &str,
+    packing: VertexPacking,
+) -> Option<VertexBufferLayouts> {
+    let inputs = module.get_vertex_inputs(entry_point)?;
+    Some(VertexBufferLayouts::new(
+        &inputs,
+        packing,
+        VertexStepMode::Vertex,
+    ))
+}
@@ -0,0 +1,119 @@
+use crate::{Error, ShaderModule, ShaderModuleDescriptor, ShaderSource};
+
+use std::{
+    borrow::Borrow,
+    fmt, fs,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+struct WatchedShader<M> {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    module: M,
+}
+
+/// Replaces WGSL shader modules when their source file changes.
+///
+/// Modules are replaced with [`ShaderModule::replace`], so pipelines follow
+/// them if [`Device::enable_shader_hot_reload`] was called before creating them.
+/// Files are only checked in debug builds: in release builds [`poll`] does
+/// nothing, and the watcher can be left in place.
+///
+/// `M` is any way of holding a module, such as `ShaderModule`, `&ShaderModule`
+/// or `Arc<ShaderModule>`.
+///
+/// [`Device::enable_shader_hot_reload`]: crate::Device::enable_shader_hot_reload
+/// [`poll`]: Self::poll
+pub struct ShaderWatcher<M = ShaderModule> {
+    shaders: Vec<WatchedShader<M>>,
+}
+
+impl<M: Borrow<ShaderModule>> ShaderWatcher<M> {
+    /// Creates a watcher with no files.
+    pub fn new() -> Self {
+        Self {
+            shaders: Vec::new(),
+        }
+    }
+
+    /// Replaces `module` with the content of the WGSL file at `path` whenever
+    /// it's modified.
+    ///
+    /// The current content is assumed to be the one `module` was created from.
+    pub fn watch(&mut self, path: impl Into<PathBuf>, module: M) {
+        let path = path.into();
+        let modified = modified_time(&path);
+        self.shaders.push(WatchedShader {
+            path,
+            modified,
+            module,
+        });
+    }
+
+    /// Returns the watched modules, with their file.
+    pub fn modules(&self) -> impl Iterator<Item = (&Path, &M)> {
+        self.shaders
+            .iter()
+            .map(|shader| (shader.path.as_path(), &shader.module))
+    }
+
+    /// Replaces the modules whose file changed since the last call, and returns
+    /// the outcome of each replacement.
+    ///
+    /// A file that fails to compile is only tried again once it's modified.
+    /// A file that can't be read is tried again on the next call.
+    pub fn poll(&mut self) -> Vec<(PathBuf, Result<(), Error>)> {
+        let mut reloaded = Vec::new();
+        if !cfg!(debug_assertions) {
+            return reloaded;
+        }
+
+        for shader in self.shaders.iter_mut() {
+            let modified = modified_time(&shader.path);
+            if modified.is_none() || modified == shader.modified {
+                continue;
+            }
+            let source = match fs::read_to_string(&shader.path) {
+                Ok(source) => source,
+                Err(e) => {
+                    log::warn!("Failed to read shader {:?}: {}", shader.path, e);
+                    continue;
+                }
+            };
+            shader.modified = modified;
+
+            let label = shader.path.to_string_lossy();
+            let result = shader.module.borrow().replace(ShaderModuleDescriptor {
+                label: Some(&label),
+                source: ShaderSource::Wgsl(source.into()),
+            });
+            reloaded.push((shader.path.clone(), result));
+        }
+        reloaded
+    }
+}
+
+impl<M: Borrow<ShaderModule>> Default for ShaderWatcher<M> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<M> fmt::Debug for ShaderWatcher<M> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list()
+            .entries(self.shaders.iter().map(|shader| &shader.path))
+            .finish()
+    }
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    match fs::metadata(path).and_then(|metadata| metadata.modified()) {
+        Ok(time) => Some(time),
+        Err(e) => {
+            log::warn!("Failed to check shader {:?}: {}", path, e);
+            None
+        }
+    }
+}
@@ -0,0 +1,108 @@
+//! Tests for the barrier log of devices.
+
+use crate::common::{create_small_buffer, initialize_test, TestParameters, SMALL_BUFFER_SIZE};
+
+#[test]
+fn barrier_log_disabled() {
+    initialize_test(TestParameters::default(), |ctx| {
+        let a = create_small_buffer(
+            &ctx,
+            Some("a"),
+            wgpu::BufferUsages::COPY_SRC | wgpu::BufferUsages::COPY_DST,
+        );
+        let b = create_small_buffer(
+            &ctx,
+            Some("b"),
+            wgpu::BufferUsages::COPY_SRC | wgpu::BufferUsages::COPY_DST,
+        );
+        let mut encoder = ctx
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        encoder.copy_buffer_to_buffer(&a, 0, &b, 0, SMALL_BUFFER_SIZE);
+        ctx.queue.submit([encoder.finish()]);
+
+        assert!(ctx.device.take_barrier_logs().is_empty());
+    })
+}
+
+#[test]
+fn barrier_log_copies() {
+    initialize_test(TestParameters::default(), |ctx| {
+        ctx.device.enable_barrier_log();
+
+        let a = create_small_buffer(
+            &ctx,
+            Some("a"),
+            wgpu::BufferUsages::COPY_SRC | wgpu::BufferUsages::COPY_DST,
+        );
+        let b = create_small_buffer(
+            &ctx,
+            Some("b"),
+            wgpu::BufferUsages::COPY_SRC | wgpu::BufferUsages::COPY_DST,
+        );
+        ctx.queue
+            .write_buffer(&a, 0, &[1; SMALL_BUFFER_SIZE as usize]);
+
+        let mut encoder = ctx
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("copies"),
+            });
+        encoder.copy_buffer_to_buffer(&a, 0, &b, 0, SMALL_BUFFER_SIZE);
+        encoder.copy_buffer_to_buffer(&b, 0, &a, 0, SMALL_BUFFER_SIZE);
+        ctx.queue.submit([encoder.finish()]);
+
+        let logs = ctx.device.take_barrier_logs();
+        assert_eq!(logs.len(), 1);
+        let log = &logs[0];
+        assert_eq!(log.command_buffer, "copies");
+
+        // The submission moves `a` out of the state `write_buffer` left it in,
+        // then the second copy swaps the usages of both buffers.
+        let causes = log
+            .barriers
+            .iter()
+            .map(|record| (record.label.as_str(), record.cause.to_string()))
+            .collect::<Vec<_>>();
+        assert!(
+            causes.contains(&("a", "submission".to_string())),
+            "{:?}",
+            causes
+        );
+        assert!(
+            causes.contains(&("a", "copy_buffer_to_buffer".to_string())),
+            "{:?}",
+            causes
+        );
+        assert!(
+            causes.contains(&("b", "copy_buffer_to_buffer".to_string())),
+            "{:?}",
+            causes
+        );
+        let submission = causes
+            .iter()
+            .position(|(_, cause)| cause == "submission")
+            .unwrap();
+        let copy = causes
+            .iter()
+            .position(|(_, cause)| cause == "copy_buffer_to_buffer")
+            .unwrap();
+        assert!(submission < copy, "{:?}", causes);
+
+        let json = log.to_json();
+        assert!(
+            json.starts_with("{\"command_buffer\":\"copies\",\"barriers\":["),
+            "{}",
+            json
+        );
+        assert!(
+            json.contains(
+                "\"label\":\"b\",\"from\":\"COPY_DST\",\"to\":\"COPY_SRC\",\"cause\":\"copy_buffer_to_buffer\""
+            ),
+            "{}",
+            json
+        );
+
+        assert!(ctx.device.take_barrier_logs().is_empty());
+    })
+}
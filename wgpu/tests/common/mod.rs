@@ -307,3 +307,120 @@ pub fn initialize_test(parameters: TestParameters, test_function: impl FnOnce(Te
         panic!("UNEXPECTED TEST FAILURE DUE TO {}", failure_cause)
     }
 }
+
+/// Maps `buffer` for reading, waits for the mapping, and returns a copy of its contents.
+pub fn read_buffer(ctx: &TestingContext, buffer: &wgpu::Buffer) -> Vec<u8> {
+    let slice = buffer.slice(..);
+    slice.map_async(wgpu::MapMode::Read, |_| ());
+    ctx.device.poll(wgpu::Maintain::Wait);
+    let data = slice.get_mapped_range().to_vec();
+    buffer.unmap();
+    data
+}
+
+/// Size of the buffers made by [`create_small_buffer`].
+pub const SMALL_BUFFER_SIZE: wgpu::BufferAddress = 16;
+
+/// Creates a buffer of [`SMALL_BUFFER_SIZE`] bytes.
+pub fn create_small_buffer(
+    ctx: &TestingContext,
+    label: Option<&str>,
+    usage: wgpu::BufferUsages,
+) -> wgpu::Buffer {
+    ctx.device.create_buffer(&wgpu::BufferDescriptor {
+        label,
+        size: SMALL_BUFFER_SIZE,
+        usage,
+        mapped_at_creation: false,
+    })
+}
+
+/// A 4x4 color attachment to record render passes into.
+pub struct RenderTarget {
+    pub view: wgpu::TextureView,
+}
+
+impl RenderTarget {
+    pub const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8Unorm;
+
+    pub fn new(ctx: &TestingContext) -> Self {
+        let texture = ctx.device.create_texture(&wgpu::TextureDescriptor {
+            label: None,
+            size: wgpu::Extent3d {
+                width: 4,
+                height: 4,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: Self::FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            view_formats: &[],
+        });
+        Self {
+            view: texture.create_view(&wgpu::TextureViewDescriptor::default()),
+        }
+    }
+
+    /// Creates a pipeline drawing to the target with the `vs_main` and `fs_main`
+    /// entry points of `module`.
+    pub fn create_pipeline(
+        &self,
+        ctx: &TestingContext,
+        module: &wgpu::ShaderModule,
+        buffers: &[wgpu::VertexBufferLayout],
+        primitive: wgpu::PrimitiveState,
+    ) -> wgpu::RenderPipeline {
+        ctx.device
+            .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: None,
+                layout: None,
+                vertex: wgpu::VertexState {
+                    module,
+                    entry_point: "vs_main",
+                    constants: None,
+                    buffers,
+                },
+                primitive,
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState::default(),
+                fragment: Some(wgpu::FragmentState {
+                    module,
+                    entry_point: "fs_main",
+                    constants: None,
+                    targets: &[Some(Self::FORMAT.into())],
+                }),
+                multiview: None,
+                cache: None,
+            })
+    }
+
+    /// Records a render pass using `pipeline` into `encoder`, and returns the
+    /// validation error it caused, if any.
+    pub fn render<'a>(
+        &'a self,
+        ctx: &TestingContext,
+        encoder: &'a mut wgpu::CommandEncoder,
+        pipeline: &'a wgpu::RenderPipeline,
+        occlusion_query_set: Option<&'a wgpu::QuerySet>,
+        record: impl FnOnce(&mut wgpu::RenderPass<'a>),
+    ) -> Option<wgpu::Error> {
+        ctx.device.push_error_scope(wgpu::ErrorFilter::Validation);
+        {
+            let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: None,
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &self.view,
+                    resolve_target: None,
+                    ops: wgpu::Operations::default(),
+                })],
+                depth_stencil_attachment: None,
+                occlusion_query_set,
+            });
+            pass.set_pipeline(pipeline);
+            record(&mut pass);
+        }
+        pollster::block_on(ctx.device.pop_error_scope())
+    }
+}
@@ -0,0 +1,213 @@
+//! Tests for `Features::INDEX_RANGE_VALIDATION`.
+
+use wgpu::util::DeviceExt;
+
+use crate::common::{initialize_test, RenderTarget, TestParameters, TestingContext};
+
+const SHADER: &str = "
+@vertex
+fn vs_main(@location(0) position: vec2<f32>) -> @builtin(position) vec4<f32> {
+    return vec4<f32>(position, 0.0, 1.0);
+}
+
+@fragment
+fn fs_main() -> @location(0) vec4<f32> {
+    return vec4<f32>(1.0);
+}
+";
+
+fn parameters() -> TestParameters {
+    TestParameters::default().features(wgpu::Features::INDEX_RANGE_VALIDATION)
+}
+
+struct Target {
+    list: wgpu::RenderPipeline,
+    strip: wgpu::RenderPipeline,
+    vertices: wgpu::Buffer,
+    target: RenderTarget,
+}
+
+impl Target {
+    fn new(ctx: &TestingContext) -> Self {
+        let module = ctx
+            .device
+            .create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: None,
+                source: wgpu::ShaderSource::Wgsl(SHADER.into()),
+            });
+        let target = RenderTarget::new(ctx);
+        let buffers = [wgpu::VertexBufferLayout {
+            array_stride: 8,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &wgpu::vertex_attr_array![0 => Float32x2],
+        }];
+        let list = target.create_pipeline(ctx, &module, &buffers, wgpu::PrimitiveState::default());
+        let strip = target.create_pipeline(
+            ctx,
+            &module,
+            &buffers,
+            wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleStrip,
+                strip_index_format: Some(wgpu::IndexFormat::Uint16),
+                ..Default::default()
+            },
+        );
+        // Three vertices, so indices up to 2 are in range.
+        let vertices = ctx
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: None,
+                contents: bytemuck::cast_slice(&[-1.0f32, -1.0, 1.0, -1.0, 0.0, 1.0]),
+                usage: wgpu::BufferUsages::VERTEX,
+            });
+        Self {
+            list,
+            strip,
+            vertices,
+            target,
+        }
+    }
+
+    /// Records a render pass drawing indices of `indices` with `pipeline`, and
+    /// returns the validation error it caused, if any.
+    fn draw(
+        &self,
+        ctx: &TestingContext,
+        pipeline: &wgpu::RenderPipeline,
+        indices: &wgpu::Buffer,
+        range: std::ops::Range<u32>,
+        base_vertex: i32,
+    ) -> Option<wgpu::Error> {
+        let mut encoder = ctx
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        self.target
+            .render(ctx, &mut encoder, pipeline, None, |pass| {
+                pass.set_vertex_buffer(0, self.vertices.slice(..));
+                pass.set_index_buffer(indices.slice(..), wgpu::IndexFormat::Uint16);
+                pass.draw_indexed(range, base_vertex, 0..1);
+            })
+    }
+}
+
+fn create_index_buffer(ctx: &TestingContext, indices: &[u16]) -> wgpu::Buffer {
+    ctx.device
+        .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: None,
+            contents: bytemuck::cast_slice(indices),
+            usage: wgpu::BufferUsages::INDEX | wgpu::BufferUsages::COPY_DST,
+        })
+}
+
+#[test]
+fn indexed_draw_vertex_range() {
+    initialize_test(parameters(), |ctx| {
+        let target = Target::new(&ctx);
+        let indices = create_index_buffer(&ctx, &[0, 1, 2, 0, 1, 3]);
+
+        assert!(target.draw(&ctx, &target.list, &indices, 0..3, 0).is_none());
+        assert!(target.draw(&ctx, &target.list, &indices, 3..6, 0).is_some());
+        assert!(target.draw(&ctx, &target.list, &indices, 0..3, 1).is_some());
+        assert!(target
+            .draw(&ctx, &target.list, &indices, 0..3, -1)
+            .is_some());
+        assert!(target
+            .draw(&ctx, &target.list, &indices, 1..3, -1)
+            .is_none());
+    })
+}
+
+#[test]
+fn indexed_draw_primitive_restart() {
+    initialize_test(parameters(), |ctx| {
+        let target = Target::new(&ctx);
+        let indices = create_index_buffer(&ctx, &[0, 1, 2, u16::MAX]);
+
+        assert!(target
+            .draw(&ctx, &target.strip, &indices, 0..4, 0)
+            .is_none());
+        assert!(target.draw(&ctx, &target.list, &indices, 0..4, 0).is_some());
+    })
+}
+
+#[test]
+fn indexed_draw_after_write() {
+    initialize_test(parameters(), |ctx| {
+        let target = Target::new(&ctx);
+        let indices = create_index_buffer(&ctx, &[0, 1, 2, 0]);
+
+        assert!(target.draw(&ctx, &target.list, &indices, 0..3, 0).is_none());
+        ctx.queue
+            .write_buffer(&indices, 0, bytemuck::cast_slice(&[0u16, 7]));
+        assert!(target.draw(&ctx, &target.list, &indices, 0..3, 0).is_some());
+        assert!(target.draw(&ctx, &target.list, &indices, 2..3, 0).is_none());
+
+        // Indices copied by the GPU aren't known, so draws fail validation until the
+        // whole buffer is written again.
+        let source = ctx
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: None,
+                contents: bytemuck::cast_slice(&[0u16, 1, 2, 0]),
+                usage: wgpu::BufferUsages::COPY_SRC,
+            });
+        let mut encoder = ctx
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        encoder.copy_buffer_to_buffer(&source, 0, &indices, 0, 8);
+        ctx.queue.submit(Some(encoder.finish()));
+        assert!(target.draw(&ctx, &target.list, &indices, 0..4, 0).is_some());
+        ctx.queue
+            .write_buffer(&indices, 0, bytemuck::cast_slice(&[0u16, 1, 2, 0]));
+        assert!(target.draw(&ctx, &target.list, &indices, 0..4, 0).is_none());
+        assert!(target.draw(&ctx, &target.list, &indices, 0..4, 3).is_some());
+    })
+}
+
+#[test]
+fn indexed_draw_storage_buffer() {
+    initialize_test(parameters(), |ctx| {
+        let target = Target::new(&ctx);
+        // Shaders can write to the buffer, so its indices are never known.
+        let indices = ctx
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: None,
+                contents: bytemuck::cast_slice(&[0u16, 1, 2, 0]),
+                usage: wgpu::BufferUsages::INDEX | wgpu::BufferUsages::STORAGE,
+            });
+        assert!(target.draw(&ctx, &target.list, &indices, 0..3, 0).is_some());
+    })
+}
+
+#[test]
+fn indexed_draw_vertex_range_in_bundle() {
+    fn try_bundle(range: std::ops::Range<u32>, should_panic: bool) {
+        let mut parameters = parameters();
+        if should_panic {
+            parameters = parameters.failure();
+        }
+
+        initialize_test(parameters, |ctx| {
+            let target = Target::new(&ctx);
+            let indices = create_index_buffer(&ctx, &[0, 1, 3, 0]);
+            let mut encoder =
+                ctx.device
+                    .create_render_bundle_encoder(&wgpu::RenderBundleEncoderDescriptor {
+                        label: None,
+                        color_formats: &[Some(RenderTarget::FORMAT)],
+                        depth_stencil: None,
+                        sample_count: 1,
+                        multiview: None,
+                    });
+            encoder.set_pipeline(&target.list);
+            encoder.set_vertex_buffer(0, target.vertices.slice(..));
+            encoder.set_index_buffer(indices.slice(..), wgpu::IndexFormat::Uint16);
+            encoder.draw_indexed(range, 0, 0..1);
+            encoder.finish(&wgpu::RenderBundleDescriptor::default());
+        });
+    }
+
+    try_bundle(0..2, false);
+    try_bundle(0..3, true);
+}
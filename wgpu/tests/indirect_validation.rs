@@ -0,0 +1,392 @@
+//! Tests for `Features::INDIRECT_VALIDATION`.
+
+use std::num::NonZeroU64;
+
+use wgpu::util::DeviceExt;
+
+use crate::common::{initialize_test, read_buffer, TestParameters, TestingContext};
+
+const COMPUTE_SHADER: &str = "
+var<push_constant> amount: u32;
+
+@group(0) @binding(0)
+var<storage, read_write> counter: atomic<u32>;
+
+@compute @workgroup_size(1)
+fn count() {
+    atomicAdd(&counter, amount);
+}
+
+@group(0) @binding(1)
+var<storage, read_write> args: array<u32>;
+
+@compute @workgroup_size(1)
+fn write_args() {
+    args[0] = 3u;
+    args[1] = 1u;
+    args[2] = 1u;
+}
+";
+
+const RENDER_SHADER: &str = "
+@vertex
+fn vs_main(@location(0) position: vec2<f32>) -> @builtin(position) vec4<f32> {
+    return vec4<f32>(position, 0.0, 1.0);
+}
+
+@fragment
+fn fs_main() -> @location(0) vec4<f32> {
+    return vec4<f32>(1.0);
+}
+";
+
+fn parameters() -> TestParameters {
+    TestParameters::default()
+        .features(wgpu::Features::INDIRECT_VALIDATION | wgpu::Features::PUSH_CONSTANT_EMULATION)
+        .downlevel_flags(
+            wgpu::DownlevelFlags::COMPUTE_SHADERS | wgpu::DownlevelFlags::INDIRECT_EXECUTION,
+        )
+        .limits(wgpu::Limits {
+            max_push_constant_size: 4,
+            ..wgpu::Limits::downlevel_defaults()
+        })
+}
+
+struct Counter {
+    pipeline: wgpu::ComputePipeline,
+    write_args_pipeline: wgpu::ComputePipeline,
+    buffer: wgpu::Buffer,
+    readback: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+}
+
+impl Counter {
+    fn new(ctx: &TestingContext) -> Self {
+        let module = ctx
+            .device
+            .create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: None,
+                source: wgpu::ShaderSource::Wgsl(COMPUTE_SHADER.into()),
+            });
+        let bind_group_layout =
+            ctx.device
+                .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                    label: None,
+                    entries: &[wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::COMPUTE,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Storage { read_only: false },
+                            has_dynamic_offset: false,
+                            min_binding_size: NonZeroU64::new(4),
+                        },
+                        count: None,
+                    }],
+                });
+        let pipeline_layout = ctx
+            .device
+            .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: None,
+                bind_group_layouts: &[&bind_group_layout],
+                push_constant_ranges: &[wgpu::PushConstantRange {
+                    stages: wgpu::ShaderStages::COMPUTE,
+                    range: 0..4,
+                }],
+            });
+        let pipeline = ctx
+            .device
+            .create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                label: None,
+                layout: Some(&pipeline_layout),
+                module: &module,
+                entry_point: "count",
+                constants: None,
+                cache: None,
+            });
+        let write_args_pipeline =
+            ctx.device
+                .create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                    label: None,
+                    layout: None,
+                    module: &module,
+                    entry_point: "write_args",
+                    constants: None,
+                    cache: None,
+                });
+        let buffer = ctx
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: None,
+                contents: &[0; 4],
+                usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+            });
+        let readback = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+            label: None,
+            size: 4,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let bind_group = ctx.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: None,
+            layout: &bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: buffer.as_entire_binding(),
+            }],
+        });
+        Self {
+            pipeline,
+            write_args_pipeline,
+            buffer,
+            readback,
+            bind_group,
+        }
+    }
+
+    /// Begins a pass where the counter is bound.
+    fn begin<'a>(&'a self, encoder: &'a mut wgpu::CommandEncoder) -> wgpu::ComputePass<'a> {
+        let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor::default());
+        pass.set_pipeline(&self.pipeline);
+        pass.set_bind_group(0, &self.bind_group, &[]);
+        pass.set_push_constants(0, bytemuck::bytes_of(&1u32));
+        pass
+    }
+
+    /// Submits `encoder` and returns the value of the counter after it.
+    fn read(&self, ctx: &TestingContext, mut encoder: wgpu::CommandEncoder) -> u32 {
+        encoder.copy_buffer_to_buffer(&self.buffer, 0, &self.readback, 0, 4);
+        ctx.queue.submit(Some(encoder.finish()));
+        bytemuck::cast_slice::<_, u32>(&read_buffer(ctx, &self.readback))[0]
+    }
+}
+
+fn create_encoder(ctx: &TestingContext) -> wgpu::CommandEncoder {
+    ctx.device
+        .create_command_encoder(&wgpu::CommandEncoderDescriptor::default())
+}
+
+#[test]
+fn indirect_dispatch_validation() {
+    initialize_test(parameters(), |ctx| {
+        let counter = Counter::new(&ctx);
+        let limit = ctx.device.limits().max_compute_workgroups_per_dimension;
+        let args = ctx
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: None,
+                contents: bytemuck::cast_slice(&[2u32, 1, 1, limit + 1, 1, 1, 1, 1, 1]),
+                usage: wgpu::BufferUsages::INDIRECT,
+            });
+
+        let mut encoder = create_encoder(&ctx);
+        {
+            let mut pass = counter.begin(&mut encoder);
+            pass.dispatch_workgroups_indirect(&args, 0);
+            // Out of range, so it's skipped.
+            pass.dispatch_workgroups_indirect(&args, 12);
+            pass.dispatch_workgroups_indirect(&args, 24);
+        }
+        assert_eq!(counter.read(&ctx, encoder), 3);
+    })
+}
+
+#[test]
+fn indirect_dispatch_written_in_pass() {
+    initialize_test(parameters(), |ctx| {
+        let counter = Counter::new(&ctx);
+        let args = ctx
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: None,
+                contents: bytemuck::cast_slice(&[0u32; 3]),
+                usage: wgpu::BufferUsages::INDIRECT | wgpu::BufferUsages::STORAGE,
+            });
+        let args_bind_group = ctx.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: None,
+            layout: &counter.write_args_pipeline.get_bind_group_layout(0),
+            entries: &[wgpu::BindGroupEntry {
+                binding: 1,
+                resource: args.as_entire_binding(),
+            }],
+        });
+
+        let mut encoder = create_encoder(&ctx);
+        {
+            let mut pass = counter.begin(&mut encoder);
+            pass.set_pipeline(&counter.write_args_pipeline);
+            pass.set_bind_group(0, &args_bind_group, &[]);
+            pass.dispatch_workgroups(1, 1, 1);
+            // The arguments written by the previous dispatch are used, and the
+            // bind groups and push constants are kept after the check.
+            pass.set_pipeline(&counter.pipeline);
+            pass.set_bind_group(0, &counter.bind_group, &[]);
+            pass.set_push_constants(0, bytemuck::bytes_of(&2u32));
+            pass.dispatch_workgroups_indirect(&args, 0);
+            pass.dispatch_workgroups(1, 1, 1);
+        }
+        assert_eq!(counter.read(&ctx, encoder), 8);
+    })
+}
+
+#[test]
+fn indirect_draw_validation() {
+    initialize_test(parameters(), |ctx| {
+        let module = ctx
+            .device
+            .create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: None,
+                source: wgpu::ShaderSource::Wgsl(RENDER_SHADER.into()),
+            });
+        let pipeline = ctx
+            .device
+            .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: None,
+                layout: None,
+                vertex: wgpu::VertexState {
+                    module: &module,
+                    entry_point: "vs_main",
+                    constants: None,
+                    buffers: &[wgpu::VertexBufferLayout {
+                        array_stride: 8,
+                        step_mode: wgpu::VertexStepMode::Vertex,
+                        attributes: &wgpu::vertex_attr_array![0 => Float32x2],
+                    }],
+                },
+                primitive: wgpu::PrimitiveState::default(),
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState::default(),
+                fragment: Some(wgpu::FragmentState {
+                    module: &module,
+                    entry_point: "fs_main",
+                    constants: None,
+                    targets: &[Some(wgpu::TextureFormat::Rgba8Unorm.into())],
+                }),
+                multiview: None,
+                cache: None,
+            });
+        // A triangle covering the whole target.
+        let vertices = ctx
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: None,
+                contents: bytemuck::cast_slice(&[-1.0f32, -1.0, 3.0, -1.0, -1.0, 3.0]),
+                usage: wgpu::BufferUsages::VERTEX,
+            });
+        let indices = ctx
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: None,
+                contents: bytemuck::cast_slice(&[0u32, 1, 2]),
+                usage: wgpu::BufferUsages::INDEX,
+            });
+        let texture = ctx.device.create_texture(&wgpu::TextureDescriptor {
+            label: None,
+            size: wgpu::Extent3d {
+                width: 64,
+                height: 1,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8Unorm,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let readback = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+            label: None,
+            size: 256,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        // Returns whether the draw with the given arguments, recorded in a render
+        // bundle if `in_bundle` is set, covered the target.
+        let draw = |in_bundle: bool, indexed: bool, args: &[u32]| {
+            let args = ctx
+                .device
+                .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: None,
+                    contents: bytemuck::cast_slice(args),
+                    usage: wgpu::BufferUsages::INDIRECT,
+                });
+            let bundle = in_bundle.then(|| {
+                let mut bundle_encoder =
+                    ctx.device
+                        .create_render_bundle_encoder(&wgpu::RenderBundleEncoderDescriptor {
+                            label: None,
+                            color_formats: &[Some(wgpu::TextureFormat::Rgba8Unorm)],
+                            depth_stencil: None,
+                            sample_count: 1,
+                            multiview: None,
+                        });
+                bundle_encoder.set_pipeline(&pipeline);
+                bundle_encoder.set_vertex_buffer(0, vertices.slice(..));
+                if indexed {
+                    bundle_encoder.set_index_buffer(indices.slice(..), wgpu::IndexFormat::Uint32);
+                    bundle_encoder.draw_indexed_indirect(&args, 0);
+                } else {
+                    bundle_encoder.draw_indirect(&args, 0);
+                }
+                bundle_encoder.finish(&wgpu::RenderBundleDescriptor::default())
+            });
+            let mut encoder = create_encoder(&ctx);
+            {
+                let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                    label: None,
+                    color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                        view: &view,
+                        resolve_target: None,
+                        ops: wgpu::Operations::default(),
+                    })],
+                    depth_stencil_attachment: None,
+                    occlusion_query_set: None,
+                });
+                if let Some(ref bundle) = bundle {
+                    pass.execute_bundles(Some(bundle));
+                } else {
+                    pass.set_pipeline(&pipeline);
+                    pass.set_vertex_buffer(0, vertices.slice(..));
+                    if indexed {
+                        pass.set_index_buffer(indices.slice(..), wgpu::IndexFormat::Uint32);
+                        pass.draw_indexed_indirect(&args, 0);
+                    } else {
+                        pass.draw_indirect(&args, 0);
+                    }
+                }
+            }
+            encoder.copy_texture_to_buffer(
+                texture.as_image_copy(),
+                wgpu::ImageCopyBuffer {
+                    buffer: &readback,
+                    layout: wgpu::ImageDataLayout::default(),
+                },
+                wgpu::Extent3d {
+                    width: 64,
+                    height: 1,
+                    depth_or_array_layers: 1,
+                },
+            );
+            ctx.queue.submit(Some(encoder.finish()));
+            read_buffer(&ctx, &readback) == [255; 256]
+        };
+
+        for in_bundle in [false, true] {
+            assert!(draw(in_bundle, false, &[3, 1, 0, 0]));
+            assert!(!draw(in_bundle, false, &[4, 1, 0, 0]));
+            assert!(!draw(in_bundle, false, &[3, 1, 1, 0]));
+            assert!(!draw(in_bundle, false, &[u32::MAX, 1, 2, 0]));
+            assert!(draw(in_bundle, true, &[3, 1, 0, 0, 0]));
+            assert!(!draw(in_bundle, true, &[4, 1, 0, 0, 0]));
+            assert!(!draw(in_bundle, true, &[2, 1, 2, 0, 0]));
+            if !ctx
+                .device
+                .features()
+                .contains(wgpu::Features::INDIRECT_FIRST_INSTANCE)
+            {
+                assert!(!draw(in_bundle, false, &[3, 1, 0, 1]));
+            }
+        }
+    })
+}
@@ -0,0 +1,135 @@
+//! Tests for occlusion queries in render passes.
+
+use crate::common::{initialize_test, RenderTarget, TestParameters, TestingContext};
+
+// Vertices 0..3 cover the whole target, vertices 3..6 are outside of it.
+const SHADER: &str = "
+@vertex
+fn vs_main(@builtin(vertex_index) vertex: u32) -> @builtin(position) vec4<f32> {
+    let uv = vec2<f32>(f32((vertex << 1u) & 2u), f32(vertex & 2u));
+    let offset = f32(vertex / 3u) * 4.0;
+    return vec4<f32>(uv * 2.0 - 1.0 + offset, 0.0, 1.0);
+}
+
+@fragment
+fn fs_main() -> @location(0) vec4<f32> {
+    return vec4<f32>(1.0);
+}
+";
+
+/// Creates a target and a pipeline drawing [`SHADER`] to it.
+fn create_target(ctx: &TestingContext) -> (RenderTarget, wgpu::RenderPipeline) {
+    let module = ctx
+        .device
+        .create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: None,
+            source: wgpu::ShaderSource::Wgsl(SHADER.into()),
+        });
+    let target = RenderTarget::new(ctx);
+    let pipeline = target.create_pipeline(ctx, &module, &[], wgpu::PrimitiveState::default());
+    (target, pipeline)
+}
+
+fn create_query_set(ctx: &TestingContext, count: u32) -> wgpu::QuerySet {
+    ctx.device.create_query_set(&wgpu::QuerySetDescriptor {
+        label: None,
+        ty: wgpu::QueryType::Occlusion,
+        count,
+    })
+}
+
+#[test]
+fn occlusion_query_results() {
+    initialize_test(TestParameters::default(), |ctx| {
+        let (target, pipeline) = create_target(&ctx);
+        let query_set = create_query_set(&ctx, 2);
+        let resolve = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+            label: None,
+            size: 16,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let readback = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+            label: None,
+            size: 16,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let mut encoder = ctx
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        let error = target.render(&ctx, &mut encoder, &pipeline, Some(&query_set), |pass| {
+            pass.begin_occlusion_query(0);
+            pass.draw(0..3, 0..1);
+            pass.end_occlusion_query();
+            pass.begin_occlusion_query(1);
+            pass.draw(3..6, 0..1);
+            pass.end_occlusion_query();
+        });
+        assert!(error.is_none(), "{:?}", error);
+        encoder.resolve_query_set(&query_set, 0..2, &resolve, 0);
+        encoder.copy_buffer_to_buffer(&resolve, 0, &readback, 0, 16);
+        ctx.queue.submit(Some(encoder.finish()));
+
+        let slice = readback.slice(..);
+        slice.map_async(wgpu::MapMode::Read, |_| ());
+        ctx.device.poll(wgpu::Maintain::Wait);
+        let results = bytemuck::cast_slice::<_, u64>(&slice.get_mapped_range()).to_vec();
+        assert_ne!(results[0], 0);
+        assert_eq!(results[1], 0);
+    })
+}
+
+#[test]
+fn occlusion_query_validation() {
+    initialize_test(TestParameters::default(), |ctx| {
+        let (target, pipeline) = create_target(&ctx);
+        let query_set = create_query_set(&ctx, 2);
+        let render = |query_set, record: fn(&mut wgpu::RenderPass)| {
+            let mut encoder = ctx
+                .device
+                .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+            target
+                .render(&ctx, &mut encoder, &pipeline, query_set, record)
+                .is_some()
+        };
+
+        assert!(!render(Some(&query_set), |pass| {
+            pass.begin_occlusion_query(0);
+            pass.end_occlusion_query();
+        }));
+        // No query set in the pass.
+        assert!(render(None, |pass| {
+            pass.begin_occlusion_query(0);
+            pass.end_occlusion_query();
+        }));
+        // Out of bounds.
+        assert!(render(Some(&query_set), |pass| {
+            pass.begin_occlusion_query(2);
+            pass.end_occlusion_query();
+        }));
+        // Nested queries.
+        assert!(render(Some(&query_set), |pass| {
+            pass.begin_occlusion_query(0);
+            pass.begin_occlusion_query(1);
+            pass.end_occlusion_query();
+            pass.end_occlusion_query();
+        }));
+        // Ended without being started.
+        assert!(render(Some(&query_set), |pass| {
+            pass.end_occlusion_query();
+        }));
+        // Still active at the end of the pass.
+        assert!(render(Some(&query_set), |pass| {
+            pass.begin_occlusion_query(0);
+        }));
+        // The same query twice in a pass.
+        assert!(render(Some(&query_set), |pass| {
+            pass.begin_occlusion_query(0);
+            pass.end_occlusion_query();
+            pass.begin_occlusion_query(0);
+            pass.end_occlusion_query();
+        }));
+    })
+}
@@ -0,0 +1,200 @@
+//! Tests for `OwnedRenderPass` and `OwnedComputePass`.
+
+use std::{sync::Arc, thread};
+
+use wgpu::util::DeviceExt;
+
+use crate::common::{initialize_test, read_buffer, TestParameters};
+
+const COMPUTE_SHADER: &str = "
+@group(0) @binding(0)
+var<storage, read_write> output: array<u32>;
+
+@compute @workgroup_size(1)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    output[id.x] = output[id.x] + id.x + 1u;
+}
+";
+
+const RENDER_SHADER: &str = "
+@vertex
+fn vs_main(@builtin(vertex_index) vertex: u32) -> @builtin(position) vec4<f32> {
+    let uv = vec2<f32>(f32((vertex << 1u) & 2u), f32(vertex & 2u));
+    return vec4<f32>(uv * 2.0 - 1.0, 0.0, 1.0);
+}
+
+@fragment
+fn fs_main() -> @location(0) vec4<f32> {
+    return vec4<f32>(0.0, 1.0, 0.0, 1.0);
+}
+";
+
+#[test]
+fn owned_compute_passes_from_threads() {
+    initialize_test(
+        TestParameters::default()
+            .downlevel_flags(wgpu::DownlevelFlags::COMPUTE_SHADERS)
+            .limits(wgpu::Limits::downlevel_defaults()),
+        |ctx| {
+            let module = ctx
+                .device
+                .create_shader_module(wgpu::ShaderModuleDescriptor {
+                    label: None,
+                    source: wgpu::ShaderSource::Wgsl(COMPUTE_SHADER.into()),
+                });
+            let pipeline = Arc::new(ctx.device.create_compute_pipeline(
+                &wgpu::ComputePipelineDescriptor {
+                    label: None,
+                    layout: None,
+                    module: &module,
+                    entry_point: "main",
+                    constants: None,
+                    cache: None,
+                },
+            ));
+            let buffer = ctx
+                .device
+                .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: None,
+                    contents: &[0; 16],
+                    usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+                });
+            let readback = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+                label: None,
+                size: 16,
+                usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            });
+            let bind_group = Arc::new(ctx.device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: None,
+                layout: &pipeline.get_bind_group_layout(0),
+                entries: &[wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: buffer.as_entire_binding(),
+                }],
+            }));
+
+            let mut encoder = ctx
+                .device
+                .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+            let passes = (1..=2)
+                .map(|workgroups| {
+                    let mut pass =
+                        encoder.begin_owned_compute_pass(&wgpu::ComputePassDescriptor::default());
+                    let pipeline = pipeline.clone();
+                    let bind_group = bind_group.clone();
+                    thread::spawn(move || {
+                        pass.set_pipeline(&pipeline);
+                        pass.set_bind_group(0, &bind_group, &[]);
+                        pass.dispatch_workgroups(workgroups, 1, 1);
+                        pass
+                    })
+                })
+                .collect::<Vec<_>>();
+            // The passes keep the resources they use alive.
+            drop((pipeline, bind_group));
+            for pass in passes {
+                encoder.run_compute_pass(pass.join().unwrap());
+            }
+            encoder.copy_buffer_to_buffer(&buffer, 0, &readback, 0, 16);
+            ctx.queue.submit(Some(encoder.finish()));
+
+            let data = read_buffer(&ctx, &readback);
+            assert_eq!(bytemuck::cast_slice::<_, u32>(&data), [2, 2, 0, 0]);
+        },
+    )
+}
+
+#[test]
+fn owned_render_pass() {
+    initialize_test(TestParameters::default(), |ctx| {
+        let module = ctx
+            .device
+            .create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: None,
+                source: wgpu::ShaderSource::Wgsl(RENDER_SHADER.into()),
+            });
+        let pipeline = Arc::new(ctx.device.create_render_pipeline(
+            &wgpu::RenderPipelineDescriptor {
+                label: None,
+                layout: None,
+                vertex: wgpu::VertexState {
+                    module: &module,
+                    entry_point: "vs_main",
+                    constants: None,
+                    buffers: &[],
+                },
+                primitive: wgpu::PrimitiveState::default(),
+                depth_stencil: None,
+                multisample: wgpu::MultisampleState::default(),
+                fragment: Some(wgpu::FragmentState {
+                    module: &module,
+                    entry_point: "fs_main",
+                    constants: None,
+                    targets: &[Some(wgpu::TextureFormat::Rgba8Unorm.into())],
+                }),
+                multiview: None,
+                cache: None,
+            },
+        ));
+        let texture = ctx.device.create_texture(&wgpu::TextureDescriptor {
+            label: None,
+            size: wgpu::Extent3d {
+                width: 64,
+                height: 1,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8Unorm,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+        let readback = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+            label: None,
+            size: 256,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let mut encoder = ctx
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        let mut pass = encoder.begin_owned_render_pass(&wgpu::OwnedRenderPassDescriptor {
+            label: None,
+            color_attachments: &[Some(wgpu::OwnedRenderPassColorAttachment {
+                view: Arc::new(texture.create_view(&wgpu::TextureViewDescriptor::default())),
+                resolve_target: None,
+                ops: wgpu::Operations::default(),
+            })],
+            depth_stencil_attachment: None,
+            occlusion_query_set: None,
+        });
+        let mut pass = thread::spawn(move || {
+            pass.set_pipeline(&pipeline);
+            pass.draw(0..3, 0..1);
+            pass
+        })
+        .join()
+        .unwrap();
+        pass.insert_debug_marker("recorded on another thread");
+        encoder.run_render_pass(pass);
+        encoder.copy_texture_to_buffer(
+            texture.as_image_copy(),
+            wgpu::ImageCopyBuffer {
+                buffer: &readback,
+                layout: wgpu::ImageDataLayout::default(),
+            },
+            wgpu::Extent3d {
+                width: 64,
+                height: 1,
+                depth_or_array_layers: 1,
+            },
+        );
+        ctx.queue.submit(Some(encoder.finish()));
+
+        let data = read_buffer(&ctx, &readback);
+        assert!(data.chunks(4).all(|pixel| pixel == [0, 255, 0, 255]));
+    })
+}
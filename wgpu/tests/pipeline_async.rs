@@ -0,0 +1,191 @@
+//! Tests for `Device::create_render_pipeline_async` and `create_compute_pipeline_async`.
+
+use std::collections::HashMap;
+
+use crate::common::{initialize_test, TestParameters};
+
+const SHADER: &str = "
+@compute @workgroup_size(1)
+fn cs_main() {}
+
+@vertex
+fn vs_main() -> @builtin(position) vec4<f32> {
+    return vec4<f32>(0.0);
+}
+
+@fragment
+fn fs_main() -> @location(0) vec4<f32> {
+    return vec4<f32>(1.0);
+}
+";
+
+fn compute_parameters() -> TestParameters {
+    TestParameters::default()
+        .downlevel_flags(wgpu::DownlevelFlags::COMPUTE_SHADERS)
+        .limits(wgpu::Limits::downlevel_defaults())
+}
+
+#[test]
+fn compute_pipeline_async() {
+    initialize_test(compute_parameters(), |ctx| {
+        let module = ctx
+            .device
+            .create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: None,
+                source: wgpu::ShaderSource::Wgsl(SHADER.into()),
+            });
+        let pipeline = ctx
+            .device
+            .create_compute_pipeline_async(&wgpu::ComputePipelineDescriptor {
+                label: Some("async"),
+                layout: None,
+                module: &module,
+                entry_point: "cs_main",
+                constants: None,
+                cache: None,
+            });
+        let pipeline = pollster::block_on(pipeline).unwrap();
+
+        let mut encoder = ctx
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        {
+            let mut cpass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor::default());
+            cpass.set_pipeline(&pipeline);
+            cpass.dispatch_workgroups(1, 1, 1);
+        }
+        ctx.queue.submit(Some(encoder.finish()));
+        ctx.device.poll(wgpu::Maintain::Wait);
+    })
+}
+
+#[test]
+fn compute_pipeline_async_error() {
+    initialize_test(compute_parameters(), |ctx| {
+        let module = ctx
+            .device
+            .create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: None,
+                source: wgpu::ShaderSource::Wgsl(SHADER.into()),
+            });
+        // Errors are returned by the future, and don't reach the uncaptured error handler.
+        let pipeline = ctx
+            .device
+            .create_compute_pipeline_async(&wgpu::ComputePipelineDescriptor {
+                label: None,
+                layout: None,
+                module: &module,
+                entry_point: "missing",
+                constants: None,
+                cache: None,
+            });
+        match pollster::block_on(pipeline) {
+            Err(wgpu::Error::Validation { .. }) => {}
+            other => panic!("expected a validation error, got {:?}", other.map(|_| ())),
+        }
+    })
+}
+
+#[test]
+fn render_pipelines_async() {
+    initialize_test(TestParameters::default(), |ctx| {
+        let module = ctx
+            .device
+            .create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: None,
+                source: wgpu::ShaderSource::Wgsl(SHADER.into()),
+            });
+        let constants = HashMap::new();
+        let futures: Vec<_> = (0..4)
+            .map(|_| {
+                ctx.device
+                    .create_render_pipeline_async(&wgpu::RenderPipelineDescriptor {
+                        label: None,
+                        layout: None,
+                        vertex: wgpu::VertexState {
+                            module: &module,
+                            entry_point: "vs_main",
+                            constants: Some(&constants),
+                            buffers: &[],
+                        },
+                        primitive: wgpu::PrimitiveState::default(),
+                        depth_stencil: None,
+                        multisample: wgpu::MultisampleState::default(),
+                        fragment: Some(wgpu::FragmentState {
+                            module: &module,
+                            entry_point: "fs_main",
+                            constants: Some(&constants),
+                            targets: &[Some(wgpu::TextureFormat::Rgba8Unorm.into())],
+                        }),
+                        multiview: None,
+                        cache: None,
+                    })
+            })
+            .collect();
+        for future in futures {
+            pollster::block_on(future).unwrap();
+        }
+    })
+}
+
+#[test]
+fn pipeline_async_outlives_sources() {
+    initialize_test(TestParameters::default(), |ctx| {
+        let module = ctx
+            .device
+            .create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: None,
+                source: wgpu::ShaderSource::Wgsl(SHADER.into()),
+            });
+        let layout = ctx
+            .device
+            .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: None,
+                bind_group_layouts: &[],
+                push_constant_ranges: &[],
+            });
+        let cache = unsafe {
+            ctx.device
+                .create_pipeline_cache(&wgpu::PipelineCacheDescriptor {
+                    label: None,
+                    data: None,
+                })
+        };
+        let constants = HashMap::new();
+        let futures: Vec<_> = (0..4)
+            .map(|_| {
+                ctx.device
+                    .create_render_pipeline_async(&wgpu::RenderPipelineDescriptor {
+                        label: None,
+                        layout: Some(&layout),
+                        vertex: wgpu::VertexState {
+                            module: &module,
+                            entry_point: "vs_main",
+                            constants: Some(&constants),
+                            buffers: &[],
+                        },
+                        primitive: wgpu::PrimitiveState::default(),
+                        depth_stencil: None,
+                        multisample: wgpu::MultisampleState::default(),
+                        fragment: Some(wgpu::FragmentState {
+                            module: &module,
+                            entry_point: "fs_main",
+                            constants: Some(&constants),
+                            targets: &[Some(wgpu::TextureFormat::Rgba8Unorm.into())],
+                        }),
+                        multiview: None,
+                        cache: Some(&cache),
+                    })
+            })
+            .collect();
+
+        // The pending pipelines keep the module, the layout and the cache alive.
+        drop(module);
+        drop(layout);
+        drop(cache);
+        for future in futures {
+            pollster::block_on(future).unwrap();
+        }
+        ctx.device.poll(wgpu::Maintain::Wait);
+    })
+}
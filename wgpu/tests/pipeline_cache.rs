@@ -0,0 +1,235 @@
+//! Tests for pipeline caches.
+
+use std::num::NonZeroU64;
+
+use wgpu::util::DeviceExt;
+
+use crate::common::{initialize_test, TestParameters, TestingContext};
+
+/// A shader adding `increment` to 41.
+fn wgsl(increment: u32) -> wgpu::ShaderSource<'static> {
+    wgpu::ShaderSource::Wgsl(
+        format!(
+            "
+struct Params {{
+    value: u32,
+}}
+
+@group(0) @binding(0)
+var<uniform> params: Params;
+@group(0) @binding(1)
+var<storage, read_write> output: array<u32>;
+
+@compute @workgroup_size(1)
+fn main() {{
+    output[0] = params.value + {}u;
+}}
+",
+            increment
+        )
+        .into(),
+    )
+}
+
+/// The GLSL equivalent of [`wgsl`], creating a module from naga IR.
+#[cfg(feature = "glsl")]
+fn glsl(increment: u32) -> wgpu::ShaderSource<'static> {
+    wgpu::ShaderSource::Glsl {
+        shader: format!(
+            "
+#version 450
+layout(local_size_x = 1) in;
+layout(set = 0, binding = 0) uniform Params {{
+    uint value;
+}} params;
+layout(set = 0, binding = 1) buffer Output {{
+    uint data[];
+}} output_;
+
+void main() {{
+    output_.data[0] = params.value + {}u;
+}}
+",
+            increment
+        )
+        .into(),
+        stage: naga::ShaderStage::Compute,
+        defines: Default::default(),
+    }
+}
+
+fn parameters() -> TestParameters {
+    TestParameters::default()
+        .downlevel_flags(wgpu::DownlevelFlags::COMPUTE_SHADERS)
+        .limits(wgpu::Limits::downlevel_defaults())
+}
+
+/// Runs `source` through a pipeline created with `cache`, returning its output.
+fn run(ctx: &TestingContext, cache: &wgpu::PipelineCache, source: wgpu::ShaderSource) -> u32 {
+    let module = ctx
+        .device
+        .create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: None,
+            source,
+        });
+    let pipeline = ctx
+        .device
+        .create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: None,
+            layout: None,
+            module: &module,
+            entry_point: "main",
+            constants: None,
+            cache: Some(cache),
+        });
+
+    let params = ctx
+        .device
+        .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: None,
+            contents: bytemuck::bytes_of(&41u32),
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+    let output = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+        label: None,
+        size: 4,
+        usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+        mapped_at_creation: false,
+    });
+    let readback = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+        label: None,
+        size: 4,
+        usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+    let bind_group = ctx.device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: None,
+        layout: &pipeline.get_bind_group_layout(0),
+        entries: &[
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: params.as_entire_binding(),
+            },
+            wgpu::BindGroupEntry {
+                binding: 1,
+                resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                    buffer: &output,
+                    offset: 0,
+                    size: NonZeroU64::new(4),
+                }),
+            },
+        ],
+    });
+
+    let mut encoder = ctx
+        .device
+        .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+    {
+        let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor::default());
+        pass.set_pipeline(&pipeline);
+        pass.set_bind_group(0, &bind_group, &[]);
+        pass.dispatch_workgroups(1, 1, 1);
+    }
+    encoder.copy_buffer_to_buffer(&output, 0, &readback, 0, 4);
+    ctx.queue.submit(Some(encoder.finish()));
+
+    let slice = readback.slice(..);
+    slice.map_async(wgpu::MapMode::Read, |_| ());
+    ctx.device.poll(wgpu::Maintain::Wait);
+    let value = *bytemuck::from_bytes::<u32>(&slice.get_mapped_range());
+    value
+}
+
+#[test]
+fn pipeline_cache_round_trip() {
+    initialize_test(parameters(), |ctx| {
+        let cache = unsafe {
+            ctx.device
+                .create_pipeline_cache(&wgpu::PipelineCacheDescriptor {
+                    label: Some("first run"),
+                    data: None,
+                })
+        };
+        assert_eq!(run(&ctx, &cache, wgsl(1)), 42);
+        // Hits the translations cached by the first pipeline.
+        assert_eq!(run(&ctx, &cache, wgsl(1)), 42);
+
+        let data = match cache.get_data() {
+            Some(data) => data,
+            // Nothing to cache on this backend.
+            None => return,
+        };
+        let cache = unsafe {
+            ctx.device
+                .create_pipeline_cache(&wgpu::PipelineCacheDescriptor {
+                    label: Some("second run"),
+                    data: Some(&data),
+                })
+        };
+        assert_eq!(run(&ctx, &cache, wgsl(1)), 42);
+        assert_eq!(cache.get_data().map(|data| data.len()), Some(data.len()));
+    })
+}
+
+#[test]
+fn pipeline_cache_invalid_data() {
+    initialize_test(parameters(), |ctx| {
+        let cache = unsafe {
+            ctx.device
+                .create_pipeline_cache(&wgpu::PipelineCacheDescriptor {
+                    label: None,
+                    data: Some(b"not a pipeline cache"),
+                })
+        };
+        assert_eq!(run(&ctx, &cache, wgsl(1)), 42);
+    })
+}
+
+#[test]
+fn pipeline_cache_distinct_modules() {
+    initialize_test(parameters(), |ctx| {
+        let cache = unsafe {
+            ctx.device
+                .create_pipeline_cache(&wgpu::PipelineCacheDescriptor {
+                    label: None,
+                    data: None,
+                })
+        };
+        // Modules only differing in their code don't share translations.
+        assert_eq!(run(&ctx, &cache, wgsl(1)), 42);
+        assert_eq!(run(&ctx, &cache, wgsl(2)), 43);
+        assert_eq!(run(&ctx, &cache, wgsl(1)), 42);
+    })
+}
+
+#[cfg(feature = "glsl")]
+#[test]
+fn pipeline_cache_naga_modules() {
+    initialize_test(parameters(), |ctx| {
+        let cache = unsafe {
+            ctx.device
+                .create_pipeline_cache(&wgpu::PipelineCacheDescriptor {
+                    label: None,
+                    data: None,
+                })
+        };
+        assert_eq!(run(&ctx, &cache, glsl(1)), 42);
+        assert_eq!(run(&ctx, &cache, glsl(2)), 43);
+        assert_eq!(run(&ctx, &cache, glsl(1)), 42);
+
+        let data = match cache.get_data() {
+            Some(data) => data,
+            None => return,
+        };
+        let cache = unsafe {
+            ctx.device
+                .create_pipeline_cache(&wgpu::PipelineCacheDescriptor {
+                    label: None,
+                    data: Some(&data),
+                })
+        };
+        assert_eq!(run(&ctx, &cache, glsl(2)), 43);
+        // Translations of modules without a source aren't serialized.
+        assert_eq!(cache.get_data().map(|data| data.len()), Some(data.len()));
+    })
+}
@@ -0,0 +1,277 @@
+//! Tests for pipeline-overridable constants.
+
+use std::collections::HashMap;
+
+use crate::common::{initialize_test, TestParameters, TestingContext};
+
+const SHADER: &str = "
+@compute @workgroup_size(1)
+fn main() {}
+";
+
+fn create_pipeline(ctx: &TestingContext, constants: Option<&HashMap<String, f64>>) {
+    let module = ctx
+        .device
+        .create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: None,
+            source: wgpu::ShaderSource::Wgsl(SHADER.into()),
+        });
+    ctx.device
+        .create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: None,
+            layout: None,
+            module: &module,
+            entry_point: "main",
+            constants,
+            cache: None,
+        });
+}
+
+#[test]
+fn no_constants() {
+    initialize_test(
+        TestParameters::default()
+            .downlevel_flags(wgpu::DownlevelFlags::COMPUTE_SHADERS)
+            .limits(wgpu::Limits::downlevel_defaults()),
+        |ctx| {
+            create_pipeline(&ctx, None);
+        },
+    )
+}
+
+#[test]
+fn unknown_constant() {
+    initialize_test(
+        TestParameters::default()
+            .downlevel_flags(wgpu::DownlevelFlags::COMPUTE_SHADERS)
+            .limits(wgpu::Limits::downlevel_defaults())
+            .failure(),
+        |ctx| {
+            let constants = HashMap::from([("missing".to_string(), 1.0)]);
+            create_pipeline(&ctx, Some(&constants));
+        },
+    )
+}
+
+/// Assembles a compute shader that stores the spec constant `value`, with id 7 and a
+/// default of 1, into the first word of the storage buffer at binding 0.
+#[cfg(feature = "spirv")]
+fn spec_constant_shader() -> Vec<u32> {
+    fn inst(words: &mut Vec<u32>, opcode: u32, operands: &[u32]) {
+        words.push((operands.len() as u32 + 1) << 16 | opcode);
+        words.extend_from_slice(operands);
+    }
+    fn string(value: &str) -> Vec<u32> {
+        let mut bytes = value.as_bytes().to_vec();
+        bytes.resize(value.len() / 4 * 4 + 4, 0);
+        bytes
+            .chunks_exact(4)
+            .map(|chunk| u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+            .collect()
+    }
+
+    const STORAGE_BUFFER: u32 = 12;
+    let (void, fn_void, uint, value, block, block_ptr, uint_ptr) = (1, 2, 3, 4, 5, 6, 7);
+    let (output, int, int_0, main, label, field) = (8, 9, 10, 11, 12, 13);
+
+    // SPIR-V 1.3, for the `StorageBuffer` storage class.
+    let mut words = vec![0x0723_0203, 0x0001_0300, 0, 14, 0];
+    inst(&mut words, 17, &[1]); // OpCapability Shader
+    inst(&mut words, 14, &[0, 1]); // OpMemoryModel Logical GLSL450
+    inst(
+        &mut words,
+        15,
+        &[[5, main].as_ref(), &string("main")].concat(),
+    ); // OpEntryPoint GLCompute
+    inst(&mut words, 16, &[main, 17, 1, 1, 1]); // OpExecutionMode LocalSize
+    inst(
+        &mut words,
+        5,
+        &[[value].as_ref(), &string("value")].concat(),
+    ); // OpName
+    inst(&mut words, 71, &[value, 1, 7]); // OpDecorate SpecId
+    inst(&mut words, 71, &[block, 2]); // OpDecorate Block
+    inst(&mut words, 72, &[block, 0, 35, 0]); // OpMemberDecorate Offset
+    inst(&mut words, 71, &[output, 34, 0]); // OpDecorate DescriptorSet
+    inst(&mut words, 71, &[output, 33, 0]); // OpDecorate Binding
+    inst(&mut words, 19, &[void]); // OpTypeVoid
+    inst(&mut words, 33, &[fn_void, void]); // OpTypeFunction
+    inst(&mut words, 21, &[uint, 32, 0]); // OpTypeInt
+    inst(&mut words, 50, &[uint, value, 1]); // OpSpecConstant
+    inst(&mut words, 30, &[block, uint]); // OpTypeStruct
+    inst(&mut words, 32, &[block_ptr, STORAGE_BUFFER, block]); // OpTypePointer
+    inst(&mut words, 32, &[uint_ptr, STORAGE_BUFFER, uint]); // OpTypePointer
+    inst(&mut words, 59, &[block_ptr, output, STORAGE_BUFFER]); // OpVariable
+    inst(&mut words, 21, &[int, 32, 1]); // OpTypeInt
+    inst(&mut words, 43, &[int, int_0, 0]); // OpConstant
+    inst(&mut words, 54, &[void, main, 0, fn_void]); // OpFunction
+    inst(&mut words, 248, &[label]); // OpLabel
+    inst(&mut words, 65, &[uint_ptr, field, output, int_0]); // OpAccessChain
+    inst(&mut words, 62, &[field, value]); // OpStore
+    inst(&mut words, 253, &[]); // OpReturn
+    inst(&mut words, 56, &[]); // OpFunctionEnd
+    words
+}
+
+/// Runs the compute shader in `source` with `constants`, and returns the first two
+/// words of the storage buffer at binding 0.
+fn run_shader(
+    ctx: &TestingContext,
+    source: wgpu::ShaderSource,
+    constants: &HashMap<String, f64>,
+) -> [u32; 2] {
+    let module = ctx
+        .device
+        .create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: None,
+            source,
+        });
+    let pipeline = ctx
+        .device
+        .create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: None,
+            layout: None,
+            module: &module,
+            entry_point: "main",
+            constants: Some(constants),
+            cache: None,
+        });
+
+    let storage = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+        label: None,
+        size: 8,
+        usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+        mapped_at_creation: false,
+    });
+    let readback = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+        label: None,
+        size: 8,
+        usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+        mapped_at_creation: false,
+    });
+    let bind_group = ctx.device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: None,
+        layout: &pipeline.get_bind_group_layout(0),
+        entries: &[wgpu::BindGroupEntry {
+            binding: 0,
+            resource: storage.as_entire_binding(),
+        }],
+    });
+
+    let mut encoder = ctx
+        .device
+        .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+    {
+        let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor::default());
+        pass.set_pipeline(&pipeline);
+        pass.set_bind_group(0, &bind_group, &[]);
+        pass.dispatch_workgroups(1, 1, 1);
+    }
+    encoder.copy_buffer_to_buffer(&storage, 0, &readback, 0, 8);
+    ctx.queue.submit([encoder.finish()]);
+
+    let slice = readback.slice(..);
+    slice.map_async(wgpu::MapMode::Read, |_| ());
+    ctx.device.poll(wgpu::Maintain::Wait);
+    let data = slice.get_mapped_range();
+    let word = |i: usize| u32::from_le_bytes([data[i], data[i + 1], data[i + 2], data[i + 3]]);
+    [word(0), word(4)]
+}
+
+#[test]
+#[cfg(feature = "spirv")]
+fn overridden_spec_constant() {
+    initialize_test(
+        TestParameters::default()
+            .downlevel_flags(wgpu::DownlevelFlags::COMPUTE_SHADERS)
+            .limits(wgpu::Limits::downlevel_defaults()),
+        |ctx| {
+            let run = |constants: &HashMap<String, f64>| {
+                let source = wgpu::ShaderSource::SpirV(spec_constant_shader().into());
+                run_shader(&ctx, source, constants)[0]
+            };
+            assert_eq!(run(&HashMap::new()), 1);
+            // Constants can be named by their name, or by their id.
+            let by_name = HashMap::from([("value".to_string(), 42.0)]);
+            assert_eq!(run(&by_name), 42);
+            let by_id = HashMap::from([("7".to_string(), 1234.0)]);
+            assert_eq!(run(&by_id), 1234);
+        },
+    )
+}
+
+/// Stores `value + offset` and the number of invocations of its workgroup.
+const OVERRIDE_SHADER: &str = "
+@id(7) override value: u32 = 1u;
+override offset: u32;
+override block: u32 = 4u;
+
+struct Output {
+    value: u32,
+    count: atomic<u32>,
+}
+
+@group(0) @binding(0)
+var<storage, read_write> output: Output;
+
+@compute @workgroup_size(block)
+fn main(@builtin(local_invocation_index) index: u32) {
+    if (index == 0u) {
+        output.value = value + offset;
+    }
+    atomicAdd(&output.count, 1u);
+}
+";
+
+#[test]
+fn overridden_wgsl_constant() {
+    initialize_test(
+        TestParameters::default()
+            .downlevel_flags(wgpu::DownlevelFlags::COMPUTE_SHADERS)
+            .limits(wgpu::Limits::downlevel_defaults()),
+        |ctx| {
+            let run = |constants: &[(&str, f64)]| {
+                let constants = constants
+                    .iter()
+                    .map(|&(key, value)| (key.to_string(), value))
+                    .collect();
+                let source = wgpu::ShaderSource::Wgsl(OVERRIDE_SHADER.into());
+                run_shader(&ctx, source, &constants)
+            };
+            assert_eq!(run(&[("offset", 0.0)]), [1, 4]);
+            // Constants can be named by their name, or by their id.
+            assert_eq!(run(&[("offset", 1.0), ("value", 42.0)]), [43, 4]);
+            assert_eq!(run(&[("offset", 0.0), ("7", 1234.0)]), [1234, 4]);
+            // The workgroup size follows the constant it's given by.
+            assert_eq!(run(&[("offset", 0.0), ("block", 16.0)]), [1, 16]);
+        },
+    )
+}
+
+#[test]
+fn missing_required_constant() {
+    initialize_test(
+        TestParameters::default()
+            .downlevel_flags(wgpu::DownlevelFlags::COMPUTE_SHADERS)
+            .limits(wgpu::Limits::downlevel_defaults())
+            .failure(),
+        |ctx| {
+            let module = ctx
+                .device
+                .create_shader_module(wgpu::ShaderModuleDescriptor {
+                    label: None,
+                    source: wgpu::ShaderSource::Wgsl(OVERRIDE_SHADER.into()),
+                });
+            // `offset` has no value in the shader.
+            ctx.device
+                .create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                    label: None,
+                    layout: None,
+                    module: &module,
+                    entry_point: "main",
+                    constants: None,
+                    cache: None,
+                });
+        },
+    )
+}
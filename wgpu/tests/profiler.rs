@@ -0,0 +1,115 @@
+use wgpu::util::{write_chrome_trace, GpuProfiler, GpuProfilerError};
+
+use crate::common::{initialize_test, TestParameters};
+
+#[test]
+fn nested_scopes() {
+    initialize_test(TestParameters::default(), |ctx| {
+        let mut profiler = GpuProfiler::new(&ctx.device, &ctx.queue, 2);
+
+        let mut encoder = ctx
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        profiler.begin_scope("outer", &mut encoder, &ctx.device);
+        {
+            let mut cpass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor::default());
+            profiler.scope("pass", &mut cpass, &ctx.device, |_, _| {});
+        }
+        profiler.scope("inner", &mut encoder, &ctx.device, |_, _| {});
+
+        assert_eq!(
+            profiler.end_frame(),
+            Err(GpuProfilerError::UnclosedScopes(vec!["outer".to_string()]))
+        );
+        profiler.end_scope(&mut encoder);
+        profiler.resolve_queries(&mut encoder);
+        ctx.queue.submit(Some(encoder.finish()));
+        profiler.end_frame().unwrap();
+
+        ctx.device.poll(wgpu::Maintain::Wait);
+        let frame = profiler
+            .process_finished_frame()
+            .expect("frame should be finished after waiting on the device");
+        assert_eq!(frame.frame_index, 0);
+        assert_eq!(frame.scopes.len(), 1);
+
+        let outer = &frame.scopes[0];
+        assert_eq!(outer.label, "outer");
+        assert!(outer.duration() >= 0.0);
+        let nested = outer
+            .nested_scopes
+            .iter()
+            .map(|scope| scope.label.as_str())
+            .collect::<Vec<_>>();
+        if ctx
+            .device_features
+            .contains(wgpu::Features::WRITE_TIMESTAMP_INSIDE_PASSES)
+            || !ctx
+                .device_features
+                .contains(wgpu::Features::TIMESTAMP_QUERY)
+        {
+            assert_eq!(nested, ["pass", "inner"]);
+        } else {
+            assert_eq!(nested, ["inner"]);
+        }
+        assert!(profiler.process_finished_frame().is_none());
+
+        let mut trace = Vec::new();
+        write_chrome_trace(&mut trace, &[frame]).unwrap();
+        let trace = String::from_utf8(trace).unwrap();
+        assert!(trace.starts_with(r#"{"traceEvents":["#));
+        assert!(trace.contains(r#""name":"outer""#));
+        assert!(trace.contains(r#""args":{"frame":0}"#));
+    })
+}
+
+#[test]
+fn gpu_timestamps() {
+    initialize_test(
+        TestParameters::default().features(wgpu::Features::TIMESTAMP_QUERY),
+        |ctx| {
+            let mut profiler = GpuProfiler::new(&ctx.device, &ctx.queue, 1);
+
+            // The first frame is dropped when the second one ends, without its
+            // results being read.
+            for _ in 0..2 {
+                let mut encoder = ctx
+                    .device
+                    .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+                profiler.begin_scope("outer", &mut encoder, &ctx.device);
+                profiler.scope("first", &mut encoder, &ctx.device, |_, _| {});
+                profiler.scope("second", &mut encoder, &ctx.device, |_, _| {});
+                profiler.end_scope(&mut encoder);
+                profiler.resolve_queries(&mut encoder);
+                ctx.queue.submit(Some(encoder.finish()));
+                profiler.end_frame().unwrap();
+            }
+
+            ctx.device.poll(wgpu::Maintain::Wait);
+            let frame = profiler
+                .process_finished_frame()
+                .expect("frame should be finished after waiting on the device");
+            assert_eq!(frame.frame_index, 1);
+            assert!(profiler.process_finished_frame().is_none());
+            assert_eq!(frame.scopes.len(), 1);
+
+            let outer = &frame.scopes[0];
+            assert_eq!(outer.label, "outer");
+            let nested = outer
+                .nested_scopes
+                .iter()
+                .map(|scope| scope.label.as_str())
+                .collect::<Vec<_>>();
+            assert_eq!(nested, ["first", "second"]);
+
+            // Timestamps are monotonic, so the nested scopes are ordered and
+            // contained in the outer one.
+            let (first, second) = (&outer.nested_scopes[0], &outer.nested_scopes[1]);
+            assert!(outer.time.start <= first.time.start);
+            assert!(first.time.start <= first.time.end);
+            assert!(first.time.end <= second.time.start);
+            assert!(second.time.start <= second.time.end);
+            assert!(second.time.end <= outer.time.end);
+        },
+    )
+}
@@ -0,0 +1,162 @@
+//! Tests for `Features::PUSH_CONSTANT_EMULATION`.
+
+use std::num::NonZeroU64;
+
+use wgpu::util::DeviceExt;
+
+use crate::common::{initialize_test, TestParameters};
+
+const SHADER: &str = "
+struct PushConstants {
+    index: u32,
+    value: u32,
+}
+var<push_constant> pc: PushConstants;
+
+@group(0) @binding(0)
+var<storage, read_write> output: array<u32>;
+
+@compute @workgroup_size(1)
+fn main() {
+    output[pc.index] = pc.value;
+}
+";
+
+const OUTPUT_LEN: u64 = 4;
+
+#[test]
+fn emulated_push_constants() {
+    initialize_test(
+        TestParameters::default()
+            .features(wgpu::Features::PUSH_CONSTANT_EMULATION)
+            .downlevel_flags(wgpu::DownlevelFlags::COMPUTE_SHADERS)
+            .limits(wgpu::Limits {
+                max_push_constant_size: 8,
+                ..wgpu::Limits::downlevel_defaults()
+            }),
+        |ctx| {
+            let module = ctx
+                .device
+                .create_shader_module(wgpu::ShaderModuleDescriptor {
+                    label: None,
+                    source: wgpu::ShaderSource::Wgsl(SHADER.into()),
+                });
+            let bind_group_layout =
+                ctx.device
+                    .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                        label: None,
+                        entries: &[wgpu::BindGroupLayoutEntry {
+                            binding: 0,
+                            visibility: wgpu::ShaderStages::COMPUTE,
+                            ty: wgpu::BindingType::Buffer {
+                                ty: wgpu::BufferBindingType::Storage { read_only: false },
+                                has_dynamic_offset: false,
+                                min_binding_size: NonZeroU64::new(4),
+                            },
+                            count: None,
+                        }],
+                    });
+            let pipeline_layout =
+                ctx.device
+                    .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                        label: None,
+                        bind_group_layouts: &[&bind_group_layout],
+                        push_constant_ranges: &[wgpu::PushConstantRange {
+                            stages: wgpu::ShaderStages::COMPUTE,
+                            range: 0..8,
+                        }],
+                    });
+            let pipeline = ctx
+                .device
+                .create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                    label: None,
+                    layout: Some(&pipeline_layout),
+                    module: &module,
+                    entry_point: "main",
+                    constants: None,
+                    cache: None,
+                });
+
+            let output = ctx
+                .device
+                .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: None,
+                    contents: &[0xFF; OUTPUT_LEN as usize * 4],
+                    usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+                });
+            let readback = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+                label: None,
+                size: OUTPUT_LEN * 4,
+                usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            });
+            let bind_group = ctx.device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: None,
+                layout: &bind_group_layout,
+                entries: &[wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: output.as_entire_binding(),
+                }],
+            });
+
+            let mut encoder = ctx
+                .device
+                .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+            {
+                let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor::default());
+                pass.set_pipeline(&pipeline);
+                pass.set_bind_group(0, &bind_group, &[]);
+                // Push constants are zeroed when the pipeline is set.
+                pass.dispatch_workgroups(1, 1, 1);
+                pass.set_push_constants(0, bytemuck::cast_slice(&[1u32, 10]));
+                pass.dispatch_workgroups(1, 1, 1);
+                // Setting the same pipeline again keeps the push constants.
+                pass.set_pipeline(&pipeline);
+                pass.set_push_constants(0, bytemuck::cast_slice(&[2u32]));
+                pass.dispatch_workgroups(1, 1, 1);
+            }
+            {
+                let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor::default());
+                pass.set_pipeline(&pipeline);
+                pass.set_bind_group(0, &bind_group, &[]);
+                pass.set_push_constants(0, bytemuck::cast_slice(&[3u32, 30]));
+                pass.dispatch_workgroups(1, 1, 1);
+            }
+            let submit_and_read = |mut encoder: wgpu::CommandEncoder| {
+                encoder.copy_buffer_to_buffer(&output, 0, &readback, 0, OUTPUT_LEN * 4);
+                ctx.queue.submit(Some(encoder.finish()));
+
+                let slice = readback.slice(..);
+                slice.map_async(wgpu::MapMode::Read, |_| ());
+                ctx.device.poll(wgpu::Maintain::Wait);
+                let data: Vec<u32> = bytemuck::cast_slice(&slice.get_mapped_range()).to_vec();
+                readback.unmap();
+                data
+            };
+            assert_eq!(submit_and_read(encoder), [0, 10, 10, 30]);
+
+            // The states of the first pass don't fit in a single block, and the
+            // second pass reuses the block of the previous submission.
+            let mut encoder = ctx
+                .device
+                .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+            {
+                let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor::default());
+                pass.set_pipeline(&pipeline);
+                pass.set_bind_group(0, &bind_group, &[]);
+                for i in 0..300u32 {
+                    pass.set_push_constants(0, bytemuck::cast_slice(&[i % 4, 100 + i]));
+                    pass.dispatch_workgroups(1, 1, 1);
+                }
+            }
+            {
+                let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor::default());
+                pass.set_pipeline(&pipeline);
+                pass.set_bind_group(0, &bind_group, &[]);
+                pass.set_push_constants(0, bytemuck::cast_slice(&[0u32, 7]));
+                pass.dispatch_workgroups(1, 1, 1);
+            }
+            assert_eq!(submit_and_read(encoder), [7, 397, 398, 399]);
+        },
+    )
+}
@@ -0,0 +1,323 @@
+//! Tests for commands recorded in render bundles.
+
+use wgpu::util::DeviceExt;
+
+use crate::common::{initialize_test, read_buffer, TestParameters, TestingContext};
+
+const FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba8Unorm;
+
+const SHADER: &str = r#"
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> @builtin(position) vec4<f32> {
+    let uv = vec2<f32>(f32(index & 1u), f32(index >> 1u));
+    return vec4<f32>(uv * 2.0 - 1.0, 0.0, 1.0);
+}
+
+@fragment
+fn fs_main() -> @location(0) vec4<f32> {
+    return vec4<f32>(1.0);
+}
+"#;
+
+fn create_pipeline(ctx: &TestingContext) -> wgpu::RenderPipeline {
+    let module = ctx
+        .device
+        .create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: None,
+            source: wgpu::ShaderSource::Wgsl(SHADER.into()),
+        });
+    ctx.device
+        .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: None,
+            layout: None,
+            vertex: wgpu::VertexState {
+                module: &module,
+                entry_point: "vs_main",
+                constants: None,
+                buffers: &[],
+            },
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            fragment: Some(wgpu::FragmentState {
+                module: &module,
+                entry_point: "fs_main",
+                constants: None,
+                targets: &[Some(FORMAT.into())],
+            }),
+            multiview: None,
+            cache: None,
+        })
+}
+
+/// Creates an indirect buffer holding `count` non-indexed draws of a single triangle.
+fn create_indirect_buffer(ctx: &TestingContext, count: usize) -> wgpu::Buffer {
+    let args = wgpu::util::DrawIndirect {
+        vertex_count: 3,
+        instance_count: 1,
+        base_vertex: 0,
+        base_instance: 0,
+    };
+    let contents: Vec<u8> = (0..count).flat_map(|_| args.as_bytes().to_vec()).collect();
+    ctx.device
+        .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("indirect"),
+            contents: &contents,
+            usage: wgpu::BufferUsages::INDIRECT,
+        })
+}
+
+fn execute_bundle<'a>(
+    ctx: &'a TestingContext,
+    record: impl FnOnce(&mut wgpu::RenderBundleEncoder<'a>),
+) {
+    execute_bundle_then(ctx, record, |_| ());
+}
+
+/// Like [`execute_bundle`], but calls `after_pass` with the command encoder once
+/// the pass that executes the bundle has ended.
+fn execute_bundle_then<'a>(
+    ctx: &'a TestingContext,
+    record: impl FnOnce(&mut wgpu::RenderBundleEncoder<'a>),
+    after_pass: impl FnOnce(&mut wgpu::CommandEncoder),
+) {
+    let mut bundle_encoder =
+        ctx.device
+            .create_render_bundle_encoder(&wgpu::RenderBundleEncoderDescriptor {
+                label: Some("bundle"),
+                color_formats: &[Some(FORMAT)],
+                depth_stencil: None,
+                sample_count: 1,
+                multiview: None,
+            });
+    record(&mut bundle_encoder);
+    let bundle = bundle_encoder.finish(&wgpu::RenderBundleDescriptor::default());
+
+    let texture = ctx.device.create_texture(&wgpu::TextureDescriptor {
+        label: None,
+        size: wgpu::Extent3d {
+            width: 4,
+            height: 4,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: FORMAT,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+        view_formats: &[],
+    });
+    let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+    let mut encoder = ctx
+        .device
+        .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+    {
+        let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: None,
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: &view,
+                resolve_target: None,
+                ops: wgpu::Operations::default(),
+            })],
+            depth_stencil_attachment: None,
+            occlusion_query_set: None,
+        });
+        rpass.execute_bundles(Some(&bundle));
+    }
+    after_pass(&mut encoder);
+    ctx.queue.submit(Some(encoder.finish()));
+}
+
+#[test]
+fn debug_markers() {
+    initialize_test(TestParameters::default(), |ctx| {
+        execute_bundle(&ctx, |bundle| {
+            bundle.push_debug_group("outer");
+            bundle.insert_debug_marker("marker");
+            bundle.push_debug_group("inner");
+            bundle.pop_debug_group();
+            bundle.pop_debug_group();
+        });
+    })
+}
+
+#[test]
+fn unbalanced_debug_group() {
+    initialize_test(TestParameters::default().failure(), |ctx| {
+        execute_bundle(&ctx, |bundle| {
+            bundle.push_debug_group("never popped");
+        });
+    })
+}
+
+#[test]
+fn pop_without_push() {
+    initialize_test(TestParameters::default().failure(), |ctx| {
+        execute_bundle(&ctx, |bundle| {
+            bundle.pop_debug_group();
+        });
+    })
+}
+
+#[test]
+fn queries() {
+    initialize_test(
+        TestParameters::default().features(
+            wgpu::Features::TIMESTAMP_QUERY
+                | wgpu::Features::WRITE_TIMESTAMP_INSIDE_PASSES
+                | wgpu::Features::PIPELINE_STATISTICS_QUERY,
+        ),
+        |ctx| {
+            let pipeline = create_pipeline(&ctx);
+            let timestamps = ctx.device.create_query_set(&wgpu::QuerySetDescriptor {
+                label: Some("timestamps"),
+                ty: wgpu::QueryType::Timestamp,
+                count: 2,
+            });
+            let statistics = ctx.device.create_query_set(&wgpu::QuerySetDescriptor {
+                label: Some("statistics"),
+                ty: wgpu::QueryType::PipelineStatistics(
+                    wgpu::PipelineStatisticsTypes::VERTEX_SHADER_INVOCATIONS,
+                ),
+                count: 1,
+            });
+            let resolve = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("resolve"),
+                size: 512,
+                usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::COPY_SRC,
+                mapped_at_creation: false,
+            });
+            let readback = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("readback"),
+                size: 512,
+                usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            });
+
+            execute_bundle_then(
+                &ctx,
+                |bundle| {
+                    bundle.set_pipeline(&pipeline);
+                    bundle.write_timestamp(&timestamps, 0);
+                    bundle.begin_pipeline_statistics_query(&statistics, 0);
+                    bundle.draw(0..3, 0..1);
+                    bundle.end_pipeline_statistics_query();
+                    bundle.write_timestamp(&timestamps, 1);
+                },
+                |encoder| {
+                    encoder.resolve_query_set(&timestamps, 0..2, &resolve, 0);
+                    encoder.resolve_query_set(&statistics, 0..1, &resolve, 256);
+                    encoder.copy_buffer_to_buffer(&resolve, 0, &readback, 0, 512);
+                },
+            );
+
+            let data = read_buffer(&ctx, &readback);
+            let read_u64 =
+                |offset: usize| u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap());
+            assert!(read_u64(0) <= read_u64(8));
+            let vertex_invocations = read_u64(256);
+            assert!(vertex_invocations >= 3, "{}", vertex_invocations);
+        },
+    )
+}
+
+#[test]
+fn pipeline_statistics_query_nested() {
+    initialize_test(
+        TestParameters::default()
+            .features(wgpu::Features::PIPELINE_STATISTICS_QUERY)
+            .failure(),
+        |ctx| {
+            let statistics = ctx.device.create_query_set(&wgpu::QuerySetDescriptor {
+                label: Some("statistics"),
+                ty: wgpu::QueryType::PipelineStatistics(
+                    wgpu::PipelineStatisticsTypes::VERTEX_SHADER_INVOCATIONS,
+                ),
+                count: 2,
+            });
+            execute_bundle(&ctx, |bundle| {
+                bundle.begin_pipeline_statistics_query(&statistics, 0);
+                bundle.begin_pipeline_statistics_query(&statistics, 1);
+                bundle.end_pipeline_statistics_query();
+                bundle.end_pipeline_statistics_query();
+            });
+        },
+    )
+}
+
+#[test]
+fn indirect_buffer_overrun() {
+    initialize_test(
+        TestParameters::default()
+            .downlevel_flags(wgpu::DownlevelFlags::INDIRECT_EXECUTION)
+            .failure(),
+        |ctx| {
+            let pipeline = create_pipeline(&ctx);
+            let indirect = create_indirect_buffer(&ctx, 1);
+            execute_bundle(&ctx, |bundle| {
+                bundle.set_pipeline(&pipeline);
+                bundle.draw_indirect(&indirect, 4);
+            });
+        },
+    )
+}
+
+#[test]
+fn multi_draw_indirect() {
+    initialize_test(
+        TestParameters::default()
+            .features(wgpu::Features::MULTI_DRAW_INDIRECT)
+            .downlevel_flags(wgpu::DownlevelFlags::INDIRECT_EXECUTION),
+        |ctx| {
+            let pipeline = create_pipeline(&ctx);
+            let indirect = create_indirect_buffer(&ctx, 2);
+            execute_bundle(&ctx, |bundle| {
+                bundle.set_pipeline(&pipeline);
+                bundle.multi_draw_indirect(&indirect, 0, 2);
+            });
+        },
+    )
+}
+
+#[test]
+fn multi_draw_indirect_overrun() {
+    initialize_test(
+        TestParameters::default()
+            .features(wgpu::Features::MULTI_DRAW_INDIRECT)
+            .downlevel_flags(wgpu::DownlevelFlags::INDIRECT_EXECUTION)
+            .failure(),
+        |ctx| {
+            let pipeline = create_pipeline(&ctx);
+            let indirect = create_indirect_buffer(&ctx, 2);
+            execute_bundle(&ctx, |bundle| {
+                bundle.set_pipeline(&pipeline);
+                bundle.multi_draw_indirect(&indirect, 0, 3);
+            });
+        },
+    )
+}
+
+#[test]
+fn multi_draw_indirect_count() {
+    initialize_test(
+        TestParameters::default()
+            .features(wgpu::Features::MULTI_DRAW_INDIRECT_COUNT)
+            .downlevel_flags(wgpu::DownlevelFlags::INDIRECT_EXECUTION),
+        |ctx| {
+            let pipeline = create_pipeline(&ctx);
+            let indirect = create_indirect_buffer(&ctx, 2);
+            let count = ctx
+                .device
+                .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some("count"),
+                    contents: bytemuck::bytes_of(&1u32),
+                    usage: wgpu::BufferUsages::INDIRECT,
+                });
+            execute_bundle(&ctx, |bundle| {
+                bundle.set_pipeline(&pipeline);
+                bundle.multi_draw_indirect_count(&indirect, 0, &count, 0, 2);
+            });
+        },
+    )
+}
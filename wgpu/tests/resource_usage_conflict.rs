@@ -0,0 +1,82 @@
+//! Tests for the description of resource usage conflicts.
+
+use crate::common::{initialize_test, TestParameters};
+
+#[test]
+fn render_attachment_sampled_in_pass() {
+    initialize_test(TestParameters::default(), |ctx| {
+        let texture = ctx.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("conflicting"),
+            size: wgpu::Extent3d {
+                width: 4,
+                height: 4,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8Unorm,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let bind_group_layout =
+            ctx.device
+                .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                    label: None,
+                    entries: &[wgpu::BindGroupLayoutEntry {
+                        binding: 3,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Texture {
+                            sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                            view_dimension: wgpu::TextureViewDimension::D2,
+                            multisampled: false,
+                        },
+                        count: None,
+                    }],
+                });
+        let bind_group = ctx.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("sampling"),
+            layout: &bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 3,
+                resource: wgpu::BindingResource::TextureView(&view),
+            }],
+        });
+
+        let mut encoder = ctx
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        ctx.device.push_error_scope(wgpu::ErrorFilter::Validation);
+        {
+            let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: None,
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &view,
+                    resolve_target: None,
+                    ops: wgpu::Operations::default(),
+                })],
+                depth_stencil_attachment: None,
+                occlusion_query_set: None,
+            });
+            pass.set_bind_group(1, &bind_group, &[]);
+        }
+        let error = pollster::block_on(ctx.device.pop_error_scope())
+            .expect("sampling a render attachment should be a usage conflict")
+            .to_string();
+
+        assert!(
+            error.contains("by binding 3 of bind group"),
+            "unexpected error: {}",
+            error
+        );
+        assert!(error.contains("at index 1 in command 0"), "{}", error);
+        assert!(error.contains("by color attachment 0"), "{}", error);
+        assert!(error.contains("texture = `conflicting`"), "{}", error);
+        assert!(
+            error.contains("current bind group = `sampling`"),
+            "{}",
+            error
+        );
+    })
+}
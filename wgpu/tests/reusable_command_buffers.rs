@@ -0,0 +1,112 @@
+//! Tests for `Features::REUSABLE_COMMAND_BUFFERS`.
+
+use crate::common::{
+    create_small_buffer, initialize_test, read_buffer, TestParameters, SMALL_BUFFER_SIZE,
+};
+
+#[test]
+fn reusable_command_buffers_require_feature() {
+    initialize_test(TestParameters::default(), |ctx| {
+        let encoder = ctx
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        ctx.device.push_error_scope(wgpu::ErrorFilter::Validation);
+        let _command_buffer = encoder.finish_reusable();
+        assert!(pollster::block_on(ctx.device.pop_error_scope()).is_some());
+    })
+}
+
+// Each submission runs the commands again, on the current contents of the resources.
+#[test]
+fn reusable_command_buffers_resubmit() {
+    initialize_test(
+        TestParameters::default().features(wgpu::Features::REUSABLE_COMMAND_BUFFERS),
+        |ctx| {
+            let src = create_small_buffer(
+                &ctx,
+                None,
+                wgpu::BufferUsages::COPY_SRC | wgpu::BufferUsages::COPY_DST,
+            );
+            let dst = create_small_buffer(
+                &ctx,
+                None,
+                wgpu::BufferUsages::COPY_SRC | wgpu::BufferUsages::COPY_DST,
+            );
+            let readback = create_small_buffer(
+                &ctx,
+                None,
+                wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            );
+
+            let mut encoder = ctx
+                .device
+                .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+            encoder.copy_buffer_to_buffer(&src, 0, &dst, 0, SMALL_BUFFER_SIZE);
+            encoder.copy_buffer_to_buffer(&dst, 0, &readback, 0, SMALL_BUFFER_SIZE);
+            let command_buffer = encoder.finish_reusable();
+
+            for value in 1..4 {
+                ctx.queue
+                    .write_buffer(&src, 0, &[value; SMALL_BUFFER_SIZE as usize]);
+                ctx.queue.submit_reusable([&command_buffer]);
+                assert_eq!(
+                    read_buffer(&ctx, &readback),
+                    [value; SMALL_BUFFER_SIZE as usize]
+                );
+            }
+
+            // The resources stay alive as long as the command buffer.
+            drop(src);
+            drop(dst);
+            ctx.queue
+                .submit_reusable([&command_buffer, &command_buffer]);
+            assert_eq!(
+                read_buffer(&ctx, &readback),
+                [3; SMALL_BUFFER_SIZE as usize]
+            );
+
+            drop(command_buffer);
+            ctx.device.poll(wgpu::Maintain::Wait);
+        },
+    )
+}
+
+#[test]
+fn reusable_command_buffers_mixed_submissions() {
+    initialize_test(
+        TestParameters::default().features(wgpu::Features::REUSABLE_COMMAND_BUFFERS),
+        |ctx| {
+            let buffer = create_small_buffer(
+                &ctx,
+                None,
+                wgpu::BufferUsages::COPY_SRC | wgpu::BufferUsages::COPY_DST,
+            );
+            let readback = create_small_buffer(
+                &ctx,
+                None,
+                wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            );
+
+            let mut encoder = ctx
+                .device
+                .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+            encoder.clear_buffer(&buffer, 0, None);
+            let clear = encoder.finish_reusable();
+
+            for _ in 0..2 {
+                ctx.queue
+                    .write_buffer(&buffer, 0, &[7; SMALL_BUFFER_SIZE as usize]);
+                ctx.queue.submit_reusable([&clear]);
+                let mut encoder = ctx
+                    .device
+                    .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+                encoder.copy_buffer_to_buffer(&buffer, 0, &readback, 0, SMALL_BUFFER_SIZE);
+                ctx.queue.submit([encoder.finish()]);
+                assert_eq!(
+                    read_buffer(&ctx, &readback),
+                    [0; SMALL_BUFFER_SIZE as usize]
+                );
+            }
+        },
+    )
+}
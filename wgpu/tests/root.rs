@@ -1,14 +1,35 @@
 // All files containing tests
 mod common;
 
+mod barrier_log;
 mod buffer_copy;
 mod clear_texture;
 mod device;
 mod example_wgsl;
+mod index_range_validation;
+mod indirect_validation;
 mod instance;
+mod occlusion_query;
+mod owned_pass;
+mod pipeline_async;
+mod pipeline_cache;
+mod pipeline_constants;
 mod poll;
+mod profiler;
+mod push_constant_emulation;
+mod render_bundle;
 mod resource_descriptor_accessor;
+mod resource_usage_conflict;
+mod reusable_command_buffers;
+mod shader_compilation_info;
+mod shader_hot_reload;
 mod shader_primitive_index;
 mod texture_bounds;
+mod texture_container;
+mod texture_view_formats;
+mod uninitialized_resources;
 mod vertex_indices;
+mod vertex_reflection;
+mod wgsl_preprocess;
+mod write_texture_conversion;
 mod zero_init_texture_after_discard;
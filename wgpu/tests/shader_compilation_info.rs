@@ -0,0 +1,65 @@
+//! Tests for `ShaderModule::get_compilation_info`.
+
+use crate::common::{initialize_test, TestParameters, TestingContext};
+
+fn compile(ctx: &TestingContext, source: &str) -> (wgpu::CompilationInfo, Option<wgpu::Error>) {
+    ctx.device.push_error_scope(wgpu::ErrorFilter::Validation);
+    let module = ctx
+        .device
+        .create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: None,
+            source: wgpu::ShaderSource::Wgsl(source.into()),
+        });
+    let error = pollster::block_on(ctx.device.pop_error_scope());
+    (pollster::block_on(module.get_compilation_info()), error)
+}
+
+#[test]
+fn valid_module_has_no_messages() {
+    initialize_test(TestParameters::default(), |ctx| {
+        let (info, error) = compile(
+            &ctx,
+            "@vertex\nfn main() -> @builtin(position) vec4<f32> {\n    return vec4<f32>(0.0);\n}\n",
+        );
+        assert!(error.is_none());
+        assert_eq!(info, wgpu::CompilationInfo::default());
+    })
+}
+
+#[test]
+fn parse_error_location() {
+    initialize_test(TestParameters::default(), |ctx| {
+        let source = "@vertex\nfn main() -> @builtin(position) vec4<f32> {\n    return vec4<f32>(0.0) +;\n}\n";
+        let (info, error) = compile(&ctx, source);
+        assert!(error.is_some());
+        assert!(info.has_errors());
+
+        let message = &info.messages[0];
+        assert_eq!(message.message_type, wgpu::CompilationMessageType::Error);
+        let location = message
+            .location
+            .as_ref()
+            .expect("parse errors have a location");
+        assert_eq!(location.line_number, 3);
+        assert_eq!(
+            &source[location.offset as usize..][..location.length as usize],
+            ";"
+        );
+    })
+}
+
+#[test]
+fn validation_error_location() {
+    initialize_test(TestParameters::default(), |ctx| {
+        let source = "@vertex\nfn main() -> @builtin(position) vec4<f32> {\n    return vec4<f32>(0.0) + 1u;\n}\n";
+        let (info, error) = compile(&ctx, source);
+        assert!(error.is_some());
+        assert!(info.has_errors());
+
+        let location = info.messages[0]
+            .location
+            .as_ref()
+            .expect("validation errors have a location");
+        assert_eq!(location.line_number, 3);
+    })
+}
@@ -0,0 +1,137 @@
+//! Tests for `ShaderModule::replace`.
+
+use wgpu::util::DeviceExt;
+
+use crate::common::{initialize_test, TestParameters, TestingContext};
+
+fn shader(value: u32) -> String {
+    format!(
+        "
+@group(0) @binding(0)
+var<storage, read_write> output: array<u32>;
+
+@compute @workgroup_size(1)
+fn main() {{
+    output[0] = {}u;
+}}
+",
+        value
+    )
+}
+
+fn wgsl(source: &str) -> wgpu::ShaderModuleDescriptor<'_> {
+    wgpu::ShaderModuleDescriptor {
+        label: None,
+        source: wgpu::ShaderSource::Wgsl(source.into()),
+    }
+}
+
+struct Output {
+    buffer: wgpu::Buffer,
+    readback: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+}
+
+impl Output {
+    fn new(ctx: &TestingContext, pipeline: &wgpu::ComputePipeline) -> Self {
+        let buffer = ctx
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: None,
+                contents: &[0; 4],
+                usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+            });
+        let readback = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+            label: None,
+            size: 4,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let bind_group = ctx.device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: None,
+            layout: &pipeline.get_bind_group_layout(0),
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: buffer.as_entire_binding(),
+            }],
+        });
+        Self {
+            buffer,
+            readback,
+            bind_group,
+        }
+    }
+
+    fn encode(
+        &self,
+        ctx: &TestingContext,
+        pipeline: &wgpu::ComputePipeline,
+    ) -> wgpu::CommandBuffer {
+        let mut encoder = ctx
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        {
+            let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor::default());
+            pass.set_pipeline(pipeline);
+            pass.set_bind_group(0, &self.bind_group, &[]);
+            pass.dispatch_workgroups(1, 1, 1);
+        }
+        encoder.copy_buffer_to_buffer(&self.buffer, 0, &self.readback, 0, 4);
+        encoder.finish()
+    }
+
+    fn read(&self, ctx: &TestingContext, command_buffer: wgpu::CommandBuffer) -> u32 {
+        ctx.queue.submit(Some(command_buffer));
+        let slice = self.readback.slice(..);
+        slice.map_async(wgpu::MapMode::Read, |_| ());
+        ctx.device.poll(wgpu::Maintain::Wait);
+        let value = bytemuck::cast_slice::<_, u32>(&slice.get_mapped_range())[0];
+        self.readback.unmap();
+        value
+    }
+
+    fn run(&self, ctx: &TestingContext, pipeline: &wgpu::ComputePipeline) -> u32 {
+        self.read(ctx, self.encode(ctx, pipeline))
+    }
+}
+
+#[test]
+fn replace_shader_module() {
+    initialize_test(
+        TestParameters::default()
+            .downlevel_flags(wgpu::DownlevelFlags::COMPUTE_SHADERS)
+            .limits(wgpu::Limits::downlevel_defaults()),
+        |ctx| {
+            ctx.device.enable_shader_hot_reload();
+            let module = ctx.device.create_shader_module(wgsl(&shader(1)));
+            let pipeline = ctx
+                .device
+                .create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                    label: None,
+                    layout: None,
+                    module: &module,
+                    entry_point: "main",
+                    constants: None,
+                    cache: None,
+                });
+            let output = Output::new(&ctx, &pipeline);
+            assert_eq!(output.run(&ctx, &pipeline), 1);
+
+            // Commands recorded before the replacement keep the old shader.
+            let recorded = output.encode(&ctx, &pipeline);
+            module.replace(wgsl(&shader(2))).unwrap();
+            assert_eq!(output.read(&ctx, recorded), 1);
+            assert_eq!(output.run(&ctx, &pipeline), 2);
+
+            // Failed replacements leave the module and pipeline untouched.
+            assert!(module.replace(wgsl("fn main( {")).is_err());
+            assert_eq!(output.run(&ctx, &pipeline), 2);
+            let missing_entry_point = shader(3).replace("fn main", "fn other");
+            assert!(module.replace(wgsl(&missing_entry_point)).is_err());
+            assert_eq!(output.run(&ctx, &pipeline), 2);
+
+            module.replace(wgsl(&shader(4))).unwrap();
+            assert_eq!(output.run(&ctx, &pipeline), 4);
+        },
+    )
+}
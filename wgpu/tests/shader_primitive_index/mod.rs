@@ -144,6 +144,7 @@ fn pulling_common(
                     }],
                 }],
                 entry_point: "vs_main",
+                constants: None,
                 module: &shader,
             },
             primitive: wgpu::PrimitiveState::default(),
@@ -151,6 +152,7 @@ fn pulling_common(
             multisample: wgpu::MultisampleState::default(),
             fragment: Some(wgpu::FragmentState {
                 entry_point: "fs_main",
+                constants: None,
                 module: &shader,
                 targets: &[Some(wgpu::ColorTargetState {
                     format: wgpu::TextureFormat::Rgba8Unorm,
@@ -159,6 +161,7 @@ fn pulling_common(
                 })],
             }),
             multiview: None,
+            cache: None,
         });
 
     let width = 2;
@@ -176,6 +179,7 @@ fn pulling_common(
         dimension: wgpu::TextureDimension::D2,
         format: wgpu::TextureFormat::Rgba8Unorm,
         usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+        view_formats: &[],
     });
     let color_view = color_texture.create_view(&wgpu::TextureViewDescriptor::default());
 
@@ -194,6 +198,7 @@ fn pulling_common(
         })],
         depth_stencil_attachment: None,
         label: None,
+        occlusion_query_set: None,
     });
 
     rpass.set_pipeline(&pipeline);
@@ -0,0 +1,247 @@
+//! Tests for `wgpu::util::TextureContainer`.
+
+use std::num::NonZeroU32;
+
+use wgpu::util::{DeviceExt, TextureContainer, TextureContainerError};
+
+use crate::common::{initialize_test, TestParameters};
+
+const VK_FORMAT_R8G8B8A8_UNORM: u32 = 37;
+const DXGI_FORMAT_BC1_UNORM: u32 = 71;
+
+/// Builds a KTX2 file. `levels` holds the data of each mip level, with all the layers.
+fn ktx2(vk_format: u32, size: [u32; 3], layers: u32, levels: &[Vec<u8>]) -> Vec<u8> {
+    let mut file = vec![
+        0xAB, 0x4B, 0x54, 0x58, 0x20, 0x32, 0x30, 0xBB, 0x0D, 0x0A, 0x1A, 0x0A,
+    ];
+    let header = [
+        vk_format,
+        1,
+        size[0],
+        size[1],
+        size[2],
+        layers,
+        1,
+        levels.len() as u32,
+        0,
+    ];
+    for value in header.iter().chain(&[0; 4]) {
+        file.extend_from_slice(&value.to_le_bytes());
+    }
+    file.extend_from_slice(&[0; 16]);
+    // The smallest mip level comes first in the file.
+    let mut offset = 80 + 24 * levels.len();
+    let mut offsets = vec![0; levels.len()];
+    for (mip, level) in levels.iter().enumerate().rev() {
+        offsets[mip] = offset;
+        offset += level.len();
+    }
+    for (level, &offset) in levels.iter().zip(offsets.iter()) {
+        for value in [offset as u64, level.len() as u64, level.len() as u64] {
+            file.extend_from_slice(&value.to_le_bytes());
+        }
+    }
+    for level in levels.iter().rev() {
+        file.extend_from_slice(level);
+    }
+    file
+}
+
+/// Builds a DDS file with the DX10 header extension.
+fn dds_dx10(
+    dxgi_format: u32,
+    width: u32,
+    height: u32,
+    mips: u32,
+    cube: bool,
+    data: &[u8],
+) -> Vec<u8> {
+    let mut header = [0u32; 37];
+    header[0] = u32::from_le_bytes(*b"DDS ");
+    header[1] = 124;
+    header[2] = 0x2_1007;
+    header[3] = height;
+    header[4] = width;
+    header[7] = mips;
+    header[19] = 32;
+    header[20] = 0x4;
+    header[21] = u32::from_le_bytes(*b"DX10");
+    header[32] = dxgi_format;
+    header[33] = 3;
+    header[34] = if cube { 0x4 } else { 0 };
+    header[35] = 1;
+    let mut file = header
+        .iter()
+        .flat_map(|value| value.to_le_bytes())
+        .collect::<Vec<_>>();
+    file.extend_from_slice(data);
+    file
+}
+
+/// Fills an image of `len` bytes with an identifier of its layer and mip level.
+fn image(layer: u8, mip: u8, len: usize) -> Vec<u8> {
+    vec![layer * 16 + mip; len]
+}
+
+#[test]
+fn texture_container_ktx2_layout() {
+    // 2 layers of 4x2 texels, with 2 mip levels.
+    let levels = [
+        [image(0, 0, 32), image(1, 0, 32)].concat(),
+        [image(0, 1, 8), image(1, 1, 8)].concat(),
+    ];
+    let container =
+        TextureContainer::parse(&ktx2(VK_FORMAT_R8G8B8A8_UNORM, [4, 2, 0], 2, &levels)).unwrap();
+
+    let desc = container.descriptor(Some("ktx2"), wgpu::TextureUsages::TEXTURE_BINDING);
+    assert_eq!(
+        desc.size,
+        wgpu::Extent3d {
+            width: 4,
+            height: 2,
+            depth_or_array_layers: 2,
+        }
+    );
+    assert_eq!(desc.mip_level_count, 2);
+    assert_eq!(desc.dimension, wgpu::TextureDimension::D2);
+    assert_eq!(desc.format, wgpu::TextureFormat::Rgba8Unorm);
+    assert!(!container.is_cube());
+    assert_eq!(container.check_features(wgpu::Features::empty()), Ok(()));
+
+    // The images are ordered layer by layer.
+    let expected = [
+        image(0, 0, 32),
+        image(0, 1, 8),
+        image(1, 0, 32),
+        image(1, 1, 8),
+    ]
+    .concat();
+    assert_eq!(container.data(), &expected[..]);
+}
+
+#[test]
+fn texture_container_dds_cube() {
+    // BC1 blocks are 4x4 texels in 8 bytes: 8x8 is 4 blocks, 4x4 is 1.
+    let data = (0..6)
+        .flat_map(|face| [image(face, 0, 32), image(face, 1, 8)].concat())
+        .collect::<Vec<_>>();
+    let container =
+        TextureContainer::parse(&dds_dx10(DXGI_FORMAT_BC1_UNORM, 8, 8, 2, true, &data)).unwrap();
+
+    assert_eq!(
+        container.size(),
+        wgpu::Extent3d {
+            width: 8,
+            height: 8,
+            depth_or_array_layers: 6,
+        }
+    );
+    assert_eq!(container.mip_level_count(), 2);
+    assert_eq!(container.format(), wgpu::TextureFormat::Bc1RgbaUnorm);
+    assert!(container.is_cube());
+    assert_eq!(container.data(), &data[..]);
+
+    assert_eq!(
+        container.required_features(),
+        wgpu::Features::TEXTURE_COMPRESSION_BC
+    );
+    assert_eq!(
+        container.check_features(wgpu::Features::empty()),
+        Err(TextureContainerError::MissingFeatures(
+            wgpu::Features::TEXTURE_COMPRESSION_BC
+        ))
+    );
+    assert_eq!(
+        container.check_features(wgpu::Features::TEXTURE_COMPRESSION_BC),
+        Ok(())
+    );
+}
+
+#[test]
+fn texture_container_errors() {
+    assert_eq!(
+        TextureContainer::parse(b"PNG").unwrap_err(),
+        TextureContainerError::UnknownContainer
+    );
+
+    let mut supercompressed = ktx2(VK_FORMAT_R8G8B8A8_UNORM, [1, 1, 0], 0, &[vec![0; 4]]);
+    supercompressed[44] = 2;
+    assert_eq!(
+        TextureContainer::parse(&supercompressed).unwrap_err(),
+        TextureContainerError::Supercompressed(2)
+    );
+
+    // VK_FORMAT_R8G8B8_UNORM has no 3 component equivalent.
+    assert!(matches!(
+        TextureContainer::parse(&ktx2(23, [1, 1, 0], 0, &[vec![0; 3]])),
+        Err(TextureContainerError::UnsupportedFormat(_))
+    ));
+
+    let file = dds_dx10(DXGI_FORMAT_BC1_UNORM, 8, 8, 1, false, &[0; 32]);
+    assert_eq!(
+        TextureContainer::parse(&file[..file.len() - 1]).unwrap_err(),
+        TextureContainerError::Truncated
+    );
+
+    assert_eq!(
+        TextureContainer::parse(&dds_dx10(DXGI_FORMAT_BC1_UNORM, 8, 8, 5, false, &[0; 64]))
+            .unwrap_err(),
+        TextureContainerError::InvalidHeader("too many mip levels")
+    );
+}
+
+#[test]
+fn texture_container_upload() {
+    initialize_test(TestParameters::default(), |ctx| {
+        // Rows of 256 bytes, so that they can be copied to a buffer as they are.
+        let levels = [
+            [image(0, 0, 512), image(1, 0, 512)].concat(),
+            [image(0, 1, 128), image(1, 1, 128)].concat(),
+        ];
+        let container =
+            TextureContainer::parse(&ktx2(VK_FORMAT_R8G8B8A8_UNORM, [64, 2, 0], 2, &levels))
+                .unwrap();
+        let texture = ctx.device.create_texture_with_data(
+            &ctx.queue,
+            &container.descriptor(None, wgpu::TextureUsages::COPY_SRC),
+            container.data(),
+        );
+
+        let buffer = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+            label: None,
+            size: 512,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+        let mut encoder = ctx
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        encoder.copy_texture_to_buffer(
+            wgpu::ImageCopyTexture {
+                texture: &texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d { x: 0, y: 0, z: 1 },
+                aspect: wgpu::TextureAspect::All,
+            },
+            wgpu::ImageCopyBuffer {
+                buffer: &buffer,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: NonZeroU32::new(256),
+                    rows_per_image: None,
+                },
+            },
+            wgpu::Extent3d {
+                width: 64,
+                height: 2,
+                depth_or_array_layers: 1,
+            },
+        );
+        ctx.queue.submit([encoder.finish()]);
+
+        let slice = buffer.slice(..);
+        slice.map_async(wgpu::MapMode::Read, |_| ());
+        ctx.device.poll(wgpu::Maintain::Wait);
+        assert_eq!(&slice.get_mapped_range()[..], &image(1, 0, 512)[..]);
+    })
+}
@@ -0,0 +1,122 @@
+//! Tests for texture view format reinterpretation via `TextureDescriptor::view_formats`.
+
+use std::num::NonZeroU32;
+
+use crate::common::{initialize_test, TestParameters, TestingContext};
+
+const SIZE: wgpu::Extent3d = wgpu::Extent3d {
+    width: 64,
+    height: 1,
+    depth_or_array_layers: 1,
+};
+
+fn create_texture(
+    ctx: &TestingContext,
+    format: wgpu::TextureFormat,
+    view_formats: &[wgpu::TextureFormat],
+) -> wgpu::Texture {
+    ctx.device.create_texture(&wgpu::TextureDescriptor {
+        label: None,
+        size: SIZE,
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+        view_formats,
+    })
+}
+
+#[test]
+fn incompatible_view_format() {
+    initialize_test(TestParameters::default().failure(), |ctx| {
+        create_texture(
+            &ctx,
+            wgpu::TextureFormat::Rgba8Unorm,
+            &[wgpu::TextureFormat::R32Float],
+        );
+    })
+}
+
+#[test]
+fn view_format_not_listed() {
+    initialize_test(TestParameters::default().failure(), |ctx| {
+        let texture = create_texture(&ctx, wgpu::TextureFormat::Rgba8Unorm, &[]);
+        texture.create_view(&wgpu::TextureViewDescriptor {
+            format: Some(wgpu::TextureFormat::Rgba8UnormSrgb),
+            ..Default::default()
+        });
+    })
+}
+
+#[test]
+fn srgb_view_of_linear_texture() {
+    initialize_test(
+        TestParameters::default().downlevel_flags(wgpu::DownlevelFlags::VIEW_FORMATS),
+        |ctx| {
+            let texture = create_texture(
+                &ctx,
+                wgpu::TextureFormat::Rgba8Unorm,
+                &[wgpu::TextureFormat::Rgba8UnormSrgb],
+            );
+            let view = texture.create_view(&wgpu::TextureViewDescriptor {
+                format: Some(wgpu::TextureFormat::Rgba8UnormSrgb),
+                ..Default::default()
+            });
+
+            let bytes_per_row = SIZE.width * 4;
+            let readback = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+                label: None,
+                size: (bytes_per_row * SIZE.height) as u64,
+                usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+                mapped_at_creation: false,
+            });
+
+            let mut encoder = ctx
+                .device
+                .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+            encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: None,
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color {
+                            r: 0.5,
+                            g: 0.5,
+                            b: 0.5,
+                            a: 1.0,
+                        }),
+                        store: true,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                occlusion_query_set: None,
+            });
+            encoder.copy_texture_to_buffer(
+                texture.as_image_copy(),
+                wgpu::ImageCopyBuffer {
+                    buffer: &readback,
+                    layout: wgpu::ImageDataLayout {
+                        offset: 0,
+                        bytes_per_row: NonZeroU32::new(bytes_per_row),
+                        rows_per_image: None,
+                    },
+                },
+                SIZE,
+            );
+            ctx.queue.submit(Some(encoder.finish()));
+
+            let slice = readback.slice(..);
+            slice.map_async(wgpu::MapMode::Read, |_| ());
+            ctx.device.poll(wgpu::Maintain::Wait);
+            let data = slice.get_mapped_range();
+            // Writing linear 0.5 through an srgb view stores the encoded value (~188),
+            // rather than the 128 a linear view would produce.
+            for pixel in data.chunks_exact(4) {
+                assert!((186..=190).contains(&pixel[0]), "{:?}", pixel);
+                assert_eq!(pixel[3], 255);
+            }
+        },
+    )
+}
@@ -0,0 +1,119 @@
+//! Tests for `Features::UNINITIALIZED_RESOURCES`.
+
+use std::num::NonZeroU32;
+
+use crate::common::{initialize_test, TestParameters};
+
+const SIZE: u32 = 64;
+
+fn buffer_descriptor(usage: wgpu::BufferUsages) -> wgpu::BufferDescriptor<'static> {
+    wgpu::BufferDescriptor {
+        label: None,
+        size: (SIZE * SIZE * 4) as u64,
+        usage,
+        mapped_at_creation: false,
+    }
+}
+
+fn texture_descriptor() -> wgpu::TextureDescriptor<'static> {
+    wgpu::TextureDescriptor {
+        label: None,
+        size: wgpu::Extent3d {
+            width: SIZE,
+            height: SIZE,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: wgpu::TextureFormat::Rgba8Unorm,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+            | wgpu::TextureUsages::COPY_DST
+            | wgpu::TextureUsages::COPY_SRC,
+        view_formats: &[],
+    }
+}
+
+#[test]
+fn uninitialized_resources_require_feature() {
+    initialize_test(TestParameters::default(), |ctx| {
+        ctx.device.push_error_scope(wgpu::ErrorFilter::Validation);
+        let _buffer = unsafe {
+            ctx.device
+                .create_buffer_uninitialized(&buffer_descriptor(wgpu::BufferUsages::COPY_DST))
+        };
+        assert!(pollster::block_on(ctx.device.pop_error_scope()).is_some());
+
+        ctx.device.push_error_scope(wgpu::ErrorFilter::Validation);
+        let _texture = unsafe {
+            ctx.device
+                .create_texture_uninitialized(&texture_descriptor())
+        };
+        assert!(pollster::block_on(ctx.device.pop_error_scope()).is_some());
+    })
+}
+
+// Contents written to resources that aren't zero-initialized are kept through render passes and copies.
+#[test]
+fn uninitialized_resources_keep_written_contents() {
+    initialize_test(
+        TestParameters::default().features(wgpu::Features::UNINITIALIZED_RESOURCES),
+        |ctx| {
+            let texture = unsafe {
+                ctx.device
+                    .create_texture_uninitialized(&texture_descriptor())
+            };
+            let buffer = unsafe {
+                ctx.device.create_buffer_uninitialized(&buffer_descriptor(
+                    wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+                ))
+            };
+            let data = (0..SIZE * SIZE * 4).map(|i| i as u8).collect::<Vec<_>>();
+            ctx.queue.write_texture(
+                texture.as_image_copy(),
+                &data,
+                wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: NonZeroU32::new(SIZE * 4),
+                    rows_per_image: None,
+                },
+                texture_descriptor().size,
+            );
+
+            let mut encoder = ctx
+                .device
+                .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+            encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: None,
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &texture.create_view(&wgpu::TextureViewDescriptor::default()),
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Load,
+                        store: true,
+                    },
+                })],
+                depth_stencil_attachment: None,
+                occlusion_query_set: None,
+            });
+            encoder.copy_texture_to_buffer(
+                texture.as_image_copy(),
+                wgpu::ImageCopyBuffer {
+                    buffer: &buffer,
+                    layout: wgpu::ImageDataLayout {
+                        offset: 0,
+                        bytes_per_row: NonZeroU32::new(SIZE * 4),
+                        rows_per_image: None,
+                    },
+                },
+                texture_descriptor().size,
+            );
+            ctx.queue.submit([encoder.finish()]);
+
+            let slice = buffer.slice(..);
+            slice.map_async(wgpu::MapMode::Read, |_| ());
+            ctx.device.poll(wgpu::Maintain::Wait);
+            assert_eq!(&*slice.get_mapped_range(), &data[..]);
+        },
+    )
+}
@@ -63,6 +63,7 @@ fn pulling_common(
             vertex: wgpu::VertexState {
                 buffers: &[],
                 entry_point: "vs_main",
+                constants: None,
                 module: &shader,
             },
             primitive: wgpu::PrimitiveState::default(),
@@ -70,6 +71,7 @@ fn pulling_common(
             multisample: wgpu::MultisampleState::default(),
             fragment: Some(wgpu::FragmentState {
                 entry_point: "fs_main",
+                constants: None,
                 module: &shader,
                 targets: &[Some(wgpu::ColorTargetState {
                     format: wgpu::TextureFormat::Rgba8Unorm,
@@ -78,6 +80,7 @@ fn pulling_common(
                 })],
             }),
             multiview: None,
+            cache: None,
         });
 
     let dummy = ctx
@@ -96,6 +99,7 @@ fn pulling_common(
                 dimension: wgpu::TextureDimension::D2,
                 format: wgpu::TextureFormat::Rgba8Unorm,
                 usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_DST,
+                view_formats: &[],
             },
             &[0, 0, 0, 1],
         )
@@ -113,6 +117,7 @@ fn pulling_common(
         })],
         depth_stencil_attachment: None,
         label: None,
+        occlusion_query_set: None,
     });
 
     rpass.set_pipeline(&pipeline);
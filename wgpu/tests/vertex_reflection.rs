@@ -0,0 +1,134 @@
+//! Tests for `ShaderModule::get_vertex_inputs` and `util::vertex_buffer_layouts`.
+
+use wgpu::util::{vertex_buffer_layouts, VertexBufferLayouts, VertexPacking};
+
+use crate::common::{initialize_test, TestParameters};
+
+const SHADER: &str = "
+struct VertexInput {
+    @location(3) color: vec4<f32>,
+    @location(0) position: vec3<f32>,
+    @builtin(vertex_index) index: u32,
+}
+
+@vertex
+fn vs_main(input: VertexInput, @location(1) id: u32, @location(2) uv: vec2<f32>) -> @builtin(position) vec4<f32> {
+    return vec4<f32>(input.position, f32(id)) * input.color + vec4<f32>(uv, 0.0, 0.0);
+}
+
+@fragment
+fn fs_main() -> @location(0) vec4<f32> {
+    return vec4<f32>(1.0);
+}
+";
+
+fn create_pipeline(
+    ctx: &crate::common::TestingContext,
+    module: &wgpu::ShaderModule,
+    buffers: &[wgpu::VertexBufferLayout],
+) -> Option<wgpu::Error> {
+    ctx.device.push_error_scope(wgpu::ErrorFilter::Validation);
+    let _ = ctx
+        .device
+        .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: None,
+            layout: None,
+            vertex: wgpu::VertexState {
+                module,
+                entry_point: "vs_main",
+                constants: None,
+                buffers,
+            },
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            fragment: Some(wgpu::FragmentState {
+                module,
+                entry_point: "fs_main",
+                constants: None,
+                targets: &[Some(wgpu::TextureFormat::Rgba8Unorm.into())],
+            }),
+            multiview: None,
+            cache: None,
+        });
+    pollster::block_on(ctx.device.pop_error_scope())
+}
+
+#[test]
+fn reflected_vertex_layouts() {
+    initialize_test(TestParameters::default(), |ctx| {
+        let module = ctx
+            .device
+            .create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: None,
+                source: wgpu::ShaderSource::Wgsl(SHADER.into()),
+            });
+
+        let inputs = module.get_vertex_inputs("vs_main").unwrap();
+        assert_eq!(
+            inputs,
+            [
+                (0, wgpu::VertexFormat::Float32x3),
+                (1, wgpu::VertexFormat::Uint32),
+                (2, wgpu::VertexFormat::Float32x2),
+                (3, wgpu::VertexFormat::Float32x4),
+            ]
+            .map(|(shader_location, format)| wgpu::VertexInput {
+                shader_location,
+                format,
+            })
+        );
+        assert_eq!(module.get_vertex_inputs("fs_main"), None);
+        assert_eq!(module.get_vertex_inputs("missing"), None);
+
+        let interleaved =
+            vertex_buffer_layouts(&module, "vs_main", VertexPacking::Interleaved).unwrap();
+        let layouts = interleaved.layouts();
+        assert_eq!(layouts.len(), 1);
+        assert_eq!(layouts[0].array_stride, 40);
+        assert_eq!(
+            layouts[0].attributes,
+            &wgpu::vertex_attr_array![0 => Float32x3, 1 => Uint32, 2 => Float32x2, 3 => Float32x4]
+        );
+        assert_eq!(
+            create_pipeline(&ctx, &module, &layouts).map(|e| e.to_string()),
+            None
+        );
+
+        let separate = vertex_buffer_layouts(&module, "vs_main", VertexPacking::Separate).unwrap();
+        let layouts = separate.layouts();
+        assert_eq!(
+            layouts.iter().map(|l| l.array_stride).collect::<Vec<_>>(),
+            [12, 4, 8, 16]
+        );
+        assert_eq!(
+            create_pipeline(&ctx, &module, &layouts).map(|e| e.to_string()),
+            None
+        );
+
+        // Smaller formats keep offsets aligned.
+        let mut inputs = inputs;
+        inputs[0].format = wgpu::VertexFormat::Float16x4;
+        inputs[2].format = wgpu::VertexFormat::Unorm8x2;
+        inputs[3].format = wgpu::VertexFormat::Unorm8x4;
+        let packed = VertexBufferLayouts::new(
+            &inputs,
+            VertexPacking::Interleaved,
+            wgpu::VertexStepMode::Vertex,
+        );
+        let layouts = packed.layouts();
+        assert_eq!(layouts[0].array_stride, 20);
+        assert_eq!(
+            layouts[0].attributes,
+            &wgpu::vertex_attr_array![0 => Float16x4, 1 => Uint32, 2 => Unorm8x2, 3 => Unorm8x4]
+                .map(|a| wgpu::VertexAttribute {
+                    offset: [0, 8, 12, 16][a.shader_location as usize],
+                    ..a
+                })
+        );
+        assert_eq!(
+            create_pipeline(&ctx, &module, &layouts).map(|e| e.to_string()),
+            None
+        );
+    })
+}
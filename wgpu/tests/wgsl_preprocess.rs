@@ -0,0 +1,146 @@
+//! Tests for `wgpu::util::WgslPreprocessor`.
+
+use std::borrow::Cow;
+
+use wgpu::util::{PreprocessError, PreprocessErrorKind, WgslPreprocessor};
+
+use crate::common::{initialize_test, TestParameters};
+
+const LIB: &str = "\
+fn scale(x: f32) -> f32 {
+#ifdef DOUBLE
+    return x * 2.0;
+#else
+    return x;
+#endif
+}
+";
+
+const BROKEN_LIB: &str = "\
+fn scale(x: f32) -> f32 {
+    return x *;
+}
+";
+
+fn resolver(path: &str) -> Option<Cow<'static, str>> {
+    match path {
+        "lib.wgsl" => Some(Cow::Borrowed(LIB)),
+        "broken.wgsl" => Some(Cow::Borrowed(BROKEN_LIB)),
+        "loop.wgsl" => Some(Cow::Borrowed("#include \"main.wgsl\"\n")),
+        _ => None,
+    }
+}
+
+fn preprocess(
+    preprocessor: &mut WgslPreprocessor,
+    source: &str,
+) -> Result<(String, wgpu::ShaderSourceMap), PreprocessError> {
+    match preprocessor.preprocess("main.wgsl", source)? {
+        wgpu::ShaderSource::WgslPreprocessed { source, source_map } => {
+            Ok((source.into_owned(), source_map))
+        }
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn preprocess_defines_and_includes() {
+    let main = "#include \"lib.wgsl\"\n#include \"lib.wgsl\"\nfn main() {}\n";
+
+    let (source, map) = preprocess(&mut WgslPreprocessor::new(resolver), main).unwrap();
+    assert_eq!(
+        source,
+        "fn scale(x: f32) -> f32 {\n    return x;\n}\nfn main() {}\n"
+    );
+    assert_eq!(map.files, ["main.wgsl", "lib.wgsl"]);
+    let origins = map
+        .lines
+        .iter()
+        .map(|line| (line.file, line.line_number))
+        .collect::<Vec<_>>();
+    assert_eq!(origins, [(1, 1), (1, 5), (1, 7), (0, 3)]);
+
+    let (source, _) =
+        preprocess(&mut WgslPreprocessor::new(resolver).define("DOUBLE"), main).unwrap();
+    assert!(source.contains("return x * 2.0;"));
+
+    let (source, _) = preprocess(
+        &mut WgslPreprocessor::new(resolver),
+        "#define DOUBLE\n#include \"lib.wgsl\"\n",
+    )
+    .unwrap();
+    assert!(source.contains("return x * 2.0;"));
+}
+
+#[test]
+fn preprocess_errors() {
+    let mut preprocessor = WgslPreprocessor::new(resolver);
+    let kind = |preprocessor: &mut WgslPreprocessor, source| {
+        let error = preprocess(preprocessor, source).unwrap_err();
+        (error.line_number, error.kind)
+    };
+
+    assert_eq!(
+        kind(&mut preprocessor, "\n#include \"missing.wgsl\"\n"),
+        (
+            2,
+            PreprocessErrorKind::IncludeNotFound("missing.wgsl".to_string())
+        )
+    );
+    assert_eq!(
+        kind(&mut preprocessor, "#include \"loop.wgsl\"\n"),
+        (
+            1,
+            PreprocessErrorKind::RecursiveInclude("main.wgsl".to_string())
+        )
+    );
+    assert_eq!(
+        kind(&mut preprocessor, "#ifdef A\n"),
+        (1, PreprocessErrorKind::UnterminatedConditional)
+    );
+    assert_eq!(
+        kind(&mut preprocessor, "#endif\n"),
+        (
+            1,
+            PreprocessErrorKind::UnexpectedDirective("#endif".to_string())
+        )
+    );
+    assert_eq!(
+        kind(&mut preprocessor, "#if A\n"),
+        (
+            1,
+            PreprocessErrorKind::InvalidDirective("#if A".to_string())
+        )
+    );
+}
+
+#[test]
+fn preprocess_maps_parse_errors() {
+    initialize_test(TestParameters::default(), |ctx| {
+        let source = WgslPreprocessor::new(resolver)
+            .preprocess("main.wgsl", "#include \"broken.wgsl\"\nfn main() {}\n")
+            .unwrap();
+
+        ctx.device.push_error_scope(wgpu::ErrorFilter::Validation);
+        let module = ctx
+            .device
+            .create_shader_module(wgpu::ShaderModuleDescriptor {
+                label: None,
+                source,
+            });
+        let error = pollster::block_on(ctx.device.pop_error_scope()).expect("invalid shader");
+        assert!(error.to_string().contains("broken.wgsl:2"));
+
+        let info = pollster::block_on(module.get_compilation_info());
+        let location = info.messages[0]
+            .location
+            .as_ref()
+            .expect("parse errors have a location");
+        assert_eq!(location.file.as_deref(), Some("broken.wgsl"));
+        assert_eq!(location.line_number, 2);
+        assert_eq!(
+            &BROKEN_LIB[location.offset as usize..][..location.length as usize],
+            ";"
+        );
+    })
+}
@@ -0,0 +1,241 @@
+//! Tests for `wgpu::util::QueueExt::write_texture_converted`.
+
+use std::num::NonZeroU32;
+
+use wgpu::util::{convert_pixels, PixelLayout, QueueExt, TextureConversionError};
+
+use crate::common::{initialize_test, TestParameters};
+
+fn floats(values: &[f32]) -> Vec<u8> {
+    values
+        .iter()
+        .flat_map(|value| value.to_le_bytes())
+        .collect()
+}
+
+fn u16s(data: &[u8]) -> Vec<u16> {
+    data.chunks_exact(2)
+        .map(|bytes| u16::from_le_bytes([bytes[0], bytes[1]]))
+        .collect()
+}
+
+fn u32s(data: &[u8]) -> Vec<u32> {
+    data.chunks_exact(4)
+        .map(|bytes| u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+        .collect()
+}
+
+#[test]
+fn convert_pixels_unorm() {
+    // Missing alpha is opaque, and sRGB data is kept as it is.
+    assert_eq!(
+        convert_pixels(
+            PixelLayout::Rgb8,
+            &[1, 2, 3, 4, 5, 6],
+            wgpu::TextureFormat::Rgba8UnormSrgb
+        ),
+        Ok(vec![1, 2, 3, 255, 4, 5, 6, 255])
+    );
+    assert_eq!(
+        convert_pixels(
+            PixelLayout::Rgba8,
+            &[1, 2, 3, 4],
+            wgpu::TextureFormat::Bgra8Unorm
+        ),
+        Ok(vec![3, 2, 1, 4])
+    );
+    assert_eq!(
+        convert_pixels(PixelLayout::R8, &[255], wgpu::TextureFormat::Rgba16Unorm),
+        Ok(vec![255, 255, 0, 0, 0, 0, 255, 255])
+    );
+    assert_eq!(
+        convert_pixels(
+            PixelLayout::Rgba32Float,
+            &floats(&[-1.0, 2.0, 0.5, 1.0]),
+            wgpu::TextureFormat::Rgba8Snorm
+        ),
+        Ok(vec![0x81, 127, 64, 127])
+    );
+}
+
+#[test]
+fn convert_pixels_integer() {
+    // Integer formats get the values of the source unchanged.
+    assert_eq!(
+        convert_pixels(PixelLayout::Rg8, &[200, 7], wgpu::TextureFormat::Rgba8Uint),
+        Ok(vec![200, 7, 0, 255])
+    );
+    assert_eq!(
+        convert_pixels(
+            PixelLayout::Rg32Float,
+            &floats(&[3.0, -300.0]),
+            wgpu::TextureFormat::Rg16Sint
+        )
+        .map(|data| u16s(&data)),
+        Ok(vec![3, -300i16 as u16])
+    );
+    assert_eq!(
+        convert_pixels(
+            PixelLayout::R16,
+            &60000u16.to_le_bytes(),
+            wgpu::TextureFormat::R32Uint
+        ),
+        Ok(60000u32.to_le_bytes().to_vec())
+    );
+}
+
+#[test]
+fn convert_pixels_float() {
+    let data = convert_pixels(
+        PixelLayout::Rgba32Float,
+        &floats(&[
+            1.0,
+            -2.0,
+            65504.0,
+            1e6,
+            0.5,
+            2f32.powi(-24),
+            2f32.powi(-15),
+            0.0,
+        ]),
+        wgpu::TextureFormat::Rgba16Float,
+    )
+    .unwrap();
+    assert_eq!(
+        u16s(&data),
+        [0x3C00, 0xC000, 0x7BFF, 0x7C00, 0x3800, 0x0001, 0x0200, 0x0000]
+    );
+
+    let data = convert_pixels(
+        PixelLayout::Rgb32Float,
+        &floats(&[1.0, 0.5, -1.0, 1.0, 0.0, 0.0]),
+        wgpu::TextureFormat::Rg11b10Float,
+    )
+    .unwrap();
+    assert_eq!(u32s(&data), [0x3C0 | 0x380 << 11, 0x3C0]);
+
+    let data = convert_pixels(
+        PixelLayout::Rgba8,
+        &[255, 0, 255, 255],
+        wgpu::TextureFormat::Rgb10a2Unorm,
+    )
+    .unwrap();
+    assert_eq!(u32s(&data), [1023 | 1023 << 20 | 3 << 30]);
+
+    let data = convert_pixels(
+        PixelLayout::R32Float,
+        &floats(&[1.0]),
+        wgpu::TextureFormat::Rgb9e5Ufloat,
+    )
+    .unwrap();
+    assert_eq!(u32s(&data), [256 | 16 << 27]);
+}
+
+#[test]
+fn convert_pixels_errors() {
+    for format in [
+        wgpu::TextureFormat::Bc1RgbaUnorm,
+        wgpu::TextureFormat::Depth32Float,
+    ] {
+        assert_eq!(
+            convert_pixels(PixelLayout::Rgba8, &[0; 4], format),
+            Err(TextureConversionError::UnsupportedFormat(format))
+        );
+    }
+    assert!(matches!(
+        convert_pixels(PixelLayout::Rgb8, &[0; 4], wgpu::TextureFormat::Rgba8Unorm),
+        Err(TextureConversionError::InvalidLayout(_))
+    ));
+}
+
+#[test]
+fn write_texture_converted() {
+    initialize_test(TestParameters::default(), |ctx| {
+        let size = wgpu::Extent3d {
+            width: 3,
+            height: 2,
+            depth_or_array_layers: 1,
+        };
+        let texture = ctx.device.create_texture(&wgpu::TextureDescriptor {
+            label: None,
+            size,
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8Unorm,
+            usage: wgpu::TextureUsages::COPY_DST | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+
+        // RGB pixels after 2 bytes of offset, in rows padded to 12 bytes.
+        let mut data = vec![0xFF; 2 + 12 + 9];
+        for row in 0..2 {
+            for byte in 0..9 {
+                data[2 + row * 12 + byte] = (row * 9 + byte) as u8;
+            }
+        }
+        let data_layout = wgpu::ImageDataLayout {
+            offset: 2,
+            bytes_per_row: NonZeroU32::new(12),
+            rows_per_image: None,
+        };
+
+        assert_eq!(
+            ctx.queue.write_texture_converted(
+                texture.as_image_copy(),
+                wgpu::TextureFormat::Rgba8Unorm,
+                PixelLayout::Rgb8,
+                &data[..data.len() - 1],
+                data_layout,
+                size,
+            ),
+            Err(TextureConversionError::DataTooShort {
+                required: 23,
+                len: 22
+            })
+        );
+        ctx.queue
+            .write_texture_converted(
+                texture.as_image_copy(),
+                wgpu::TextureFormat::Rgba8Unorm,
+                PixelLayout::Rgb8,
+                &data,
+                data_layout,
+                size,
+            )
+            .unwrap();
+
+        let buffer = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+            label: None,
+            size: 256 + 12,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+        let mut encoder = ctx
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        encoder.copy_texture_to_buffer(
+            texture.as_image_copy(),
+            wgpu::ImageCopyBuffer {
+                buffer: &buffer,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: NonZeroU32::new(256),
+                    rows_per_image: None,
+                },
+            },
+            size,
+        );
+        ctx.queue.submit([encoder.finish()]);
+
+        let slice = buffer.slice(..);
+        slice.map_async(wgpu::MapMode::Read, |_| ());
+        ctx.device.poll(wgpu::Maintain::Wait);
+        let mapped = slice.get_mapped_range();
+        assert_eq!(&mapped[..12], &[0, 1, 2, 255, 3, 4, 5, 255, 6, 7, 8, 255]);
+        assert_eq!(
+            &mapped[256..],
+            &[9, 10, 11, 255, 12, 13, 14, 255, 15, 16, 17, 255]
+        );
+    })
+}
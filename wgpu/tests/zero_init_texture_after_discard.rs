@@ -23,6 +23,7 @@ fn discarding_color_target_resets_texture_init_state_check_visible_on_copy_after
                     },
                 })],
                 depth_stencil_attachment: None,
+                occlusion_query_set: None,
             });
             ctx.queue.submit([encoder.finish()]);
         }
@@ -58,6 +59,7 @@ fn discarding_color_target_resets_texture_init_state_check_visible_on_copy_in_sa
                     },
                 })],
                 depth_stencil_attachment: None,
+                occlusion_query_set: None,
             });
             copy_texture_to_buffer(&mut encoder, &texture, &readback_buffer);
             ctx.queue.submit([encoder.finish()]);
@@ -97,6 +99,7 @@ fn discarding_depth_target_resets_texture_init_state_check_visible_on_copy_in_sa
                                 store: false, // discard!
                             }),
                         }),
+                        occlusion_query_set: None,
                     });
                     copy_texture_to_buffer(&mut encoder, &texture, &readback_buffer);
                     ctx.queue.submit([encoder.finish()]);
@@ -133,6 +136,7 @@ fn discarding_either_depth_or_stencil_aspect() {
                         store: true,
                     }),
                 }),
+                occlusion_query_set: None,
             });
             ctx.queue.submit([encoder.finish()]);
         }
@@ -154,6 +158,7 @@ fn discarding_either_depth_or_stencil_aspect() {
                         store: false, // discard!
                     }),
                 }),
+                occlusion_query_set: None,
             });
             ctx.queue.submit([encoder.finish()]);
         }
@@ -202,6 +207,7 @@ fn create_white_texture_and_readback_buffer(
                 | wgpu::TextureUsages::COPY_SRC
                 | wgpu::TextureUsages::RENDER_ATTACHMENT
         },
+        view_formats: &[],
     });
 
     // Clear using a write_texture operation. We could also clear using a render_pass clear.
@@ -231,6 +237,7 @@ fn create_white_texture_and_readback_buffer(
                     store: true,
                 }),
             }),
+            occlusion_query_set: None,
         });
         ctx.queue.submit([encoder.finish()]);
     } else {